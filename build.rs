@@ -0,0 +1,25 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo::rerun-if-changed=proto");
+
+    // protox compiles the schema in Rust, so building does not require a `protoc` install.
+    let file_descriptors = protox::compile(["proto/faucet.proto"], ["proto"])?;
+    tonic_prost_build::configure().compile_fds(file_descriptors)?;
+
+    // Recorded in load test reports, see `src/scenario.rs`.
+    println!("cargo::rerun-if-changed=Cargo.lock");
+    println!(
+        "cargo::rustc-env=MIDEN_CLIENT_VERSION={}",
+        locked_version("miden-client").unwrap_or_else(|| "unknown".into())
+    );
+
+    Ok(())
+}
+
+/// Version of `package` in `Cargo.lock`.
+fn locked_version(package: &str) -> Option<String> {
+    let lock = std::fs::read_to_string("Cargo.lock").ok()?;
+    let mut lines = lock.lines();
+    lines.find(|line| *line == format!("name = \"{package}\""))?;
+    let version = lines.next()?.strip_prefix("version = ")?;
+    Some(version.trim_matches('"').to_string())
+}
@@ -0,0 +1,14 @@
+use std::env;
+
+fn main() {
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").expect("set by cargo");
+    let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml"))
+        .expect("cbindgen.toml is valid");
+
+    cbindgen::generate_with_config(&crate_dir, config)
+        .expect("C header generation failed")
+        .write_to_file(format!("{crate_dir}/include/network_faucet.h"));
+
+    println!("cargo::rerun-if-changed=src");
+    println!("cargo::rerun-if-changed=cbindgen.toml");
+}
@@ -0,0 +1,109 @@
+#ifndef NETWORK_FAUCET_H
+#define NETWORK_FAUCET_H
+
+/* Generated by cbindgen from ffi/src/lib.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Result of every call of the C interface.
+typedef enum FaucetStatus {
+  FAUCET_STATUS_OK = 0,
+  // A pointer was null, a string was not UTF-8 or an account ID could not be parsed.
+  FAUCET_STATUS_INVALID_ARGUMENT = 1,
+  // The configuration file is missing or invalid.
+  FAUCET_STATUS_CONFIG = 2,
+  // The node could not be reached.
+  FAUCET_STATUS_CONNECTION = 3,
+  // The node did not answer in time.
+  FAUCET_STATUS_TIMEOUT = 4,
+  // An account or transaction is unknown to the node.
+  FAUCET_STATUS_NOT_FOUND = 5,
+  // The network discarded the transaction.
+  FAUCET_STATUS_DISCARDED = 6,
+  FAUCET_STATUS_INTERNAL = 7,
+} FaucetStatus;
+
+// Opaque client handle.
+typedef struct FaucetHandle FaucetHandle;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Message of the last failed call on the current thread, or null.
+//
+// The string is owned by the library and valid until the next call on the same thread.
+const char *faucet_last_error(void);
+
+// Releases a string returned by this library. Null is ignored.
+//
+// # Safety
+//
+// `string` must be null or a string returned by this library that was not freed yet.
+void faucet_string_free(char *string);
+
+// Connects the node of the configuration file at `config_path` and stores the handle in `out`.
+//
+// When `config_path` is null, `$FAUCET_CONFIG` or `./faucet.toml` is used.
+//
+// # Safety
+//
+// `config_path` must be null or a NUL-terminated string, `out` a valid pointer.
+FaucetStatus faucet_open(const char *config_path, FaucetHandle **out);
+
+// Releases a handle returned by [`faucet_open`]. Null is ignored.
+//
+// # Safety
+//
+// `handle` must be null or a handle that was not closed yet.
+void faucet_close(FaucetHandle *handle);
+
+// Creates a wallet managed by the client and returns its hex account ID in `account_id`.
+//
+// # Safety
+//
+// `handle` must be a live handle and `account_id` a valid pointer.
+FaucetStatus faucet_create_wallet(FaucetHandle *handle, char **account_id);
+
+// Deploys a network faucet owned by `owner` and returns its account ID and the deployment
+// transaction ID.
+//
+// `script` is the MASM deploy script, or null for the bundled one. With `wait` set, the call
+// returns once the deployment is committed.
+//
+// # Safety
+//
+// `handle` must be a live handle, `owner` a NUL-terminated string, `script` null or a
+// NUL-terminated string and the out-parameters valid pointers.
+FaucetStatus faucet_deploy(FaucetHandle *handle,
+                           const char *owner,
+                           const char *script,
+                           bool wait,
+                           char **faucet_id,
+                           char **transaction_id);
+
+// Mints `amount` tokens of `faucet_id` to `recipient` and returns the mint transaction ID and
+// the ID of the P2ID note the recipient will receive.
+//
+// With `wait` set, the call returns once the mint is committed.
+//
+// # Safety
+//
+// `handle` must be a live handle, `faucet_id` and `recipient` NUL-terminated strings and the
+// out-parameters valid pointers.
+FaucetStatus faucet_mint(FaucetHandle *handle,
+                         const char *faucet_id,
+                         const char *recipient,
+                         uint64_t amount,
+                         bool wait,
+                         char **transaction_id,
+                         char **note_id);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* NETWORK_FAUCET_H */
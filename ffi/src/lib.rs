@@ -0,0 +1,349 @@
+//! C interface to the network faucet library.
+//!
+//! The header is generated into `include/network_faucet.h` on build. A client is opened with
+//! [`faucet_open`] and released with [`faucet_close`]; the handle is not thread-safe and must only
+//! be used from one thread at a time. Every call returns a [`FaucetStatus`]; on failure
+//! [`faucet_last_error`] describes the error. Strings returned through out-parameters are owned by
+//! the caller and must be released with [`faucet_string_free`].
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    future::Future,
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::Path,
+    ptr,
+    rc::Rc,
+};
+
+use miden_client::{
+    account::{AccountId, NetworkId},
+    transaction::TransactionId,
+};
+use network_faucet::{
+    account::parse_account_id,
+    config::Config,
+    deploy::deploy_faucet,
+    mint::mint_p2id,
+    node::{connect, ConfiguredNode, FaucetNode},
+    script::DEPLOY_SCRIPT,
+    wallet::create_wallet,
+    watcher::{wait_for_transaction, BlockWatcher, SharedNode, SYNC_INTERVAL},
+    FaucetError,
+};
+use tokio::{
+    runtime::{Builder, Runtime},
+    sync::Mutex,
+    task::LocalSet,
+};
+
+/// Result of every call of the C interface.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaucetStatus {
+    Ok = 0,
+    /// A pointer was null, a string was not UTF-8 or an account ID could not be parsed.
+    InvalidArgument = 1,
+    /// The configuration file is missing or invalid.
+    Config = 2,
+    /// The node could not be reached.
+    Connection = 3,
+    /// The node did not answer in time.
+    Timeout = 4,
+    /// An account or transaction is unknown to the node.
+    NotFound = 5,
+    /// The network discarded the transaction.
+    Discarded = 6,
+    Internal = 7,
+}
+
+impl From<&FaucetError> for FaucetStatus {
+    fn from(err: &FaucetError) -> Self {
+        match err {
+            FaucetError::InvalidAccountId(..)
+            | FaucetError::AccountNetworkMismatch { .. }
+            | FaucetError::InvalidSerialNumber(..) => Self::InvalidArgument,
+            FaucetError::Config(_) | FaucetError::ConfigParse(_) => Self::Config,
+            FaucetError::ConnectTimeout { .. } | FaucetError::RequestTimeout { .. } => {
+                Self::Timeout
+            }
+            err if err.is_transient() => Self::Connection,
+            FaucetError::AccountNotFound(_) | FaucetError::TransactionNotFound(_) => Self::NotFound,
+            FaucetError::TransactionDiscarded(..) => Self::Discarded,
+            _ => Self::Internal,
+        }
+    }
+}
+
+/// Opaque client handle.
+pub struct FaucetHandle {
+    runtime: Runtime,
+    local: LocalSet,
+    node: SharedNode<ConfiguredNode>,
+    /// Network whose prefix bech32 account IDs must carry, see [`Config::account_network`].
+    account_network: Option<NetworkId>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Message of the last failed call on the current thread, or null.
+///
+/// The string is owned by the library and valid until the next call on the same thread.
+#[no_mangle]
+pub extern "C" fn faucet_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Releases a string returned by this library. Null is ignored.
+///
+/// # Safety
+///
+/// `string` must be null or a string returned by this library that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn faucet_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Connects the node of the configuration file at `config_path` and stores the handle in `out`.
+///
+/// When `config_path` is null, `$FAUCET_CONFIG` or `./faucet.toml` is used.
+///
+/// # Safety
+///
+/// `config_path` must be null or a NUL-terminated string, `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn faucet_open(
+    config_path: *const c_char,
+    out: *mut *mut FaucetHandle,
+) -> FaucetStatus {
+    guard(|| {
+        let out = out_param(out)?;
+        let config = match optional_str(config_path)? {
+            Some(path) => Config::from_file(Path::new(path)),
+            None => Config::load(),
+        }?;
+
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(FaucetError::from)?;
+        let local = LocalSet::new();
+        let node = local.block_on(&runtime, async {
+            let mut node = connect(&config).await?;
+            node.sync_state().await?;
+            Ok::<_, FaucetError>(node)
+        })?;
+
+        let handle = FaucetHandle {
+            runtime,
+            local,
+            node: Rc::new(Mutex::new(node)),
+            account_network: config.account_network(),
+        };
+        *out = Box::into_raw(Box::new(handle));
+        Ok(())
+    })
+}
+
+/// Releases a handle returned by [`faucet_open`]. Null is ignored.
+///
+/// # Safety
+///
+/// `handle` must be null or a handle that was not closed yet.
+#[no_mangle]
+pub unsafe extern "C" fn faucet_close(handle: *mut FaucetHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Creates a wallet managed by the client and returns its hex account ID in `account_id`.
+///
+/// # Safety
+///
+/// `handle` must be a live handle and `account_id` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn faucet_create_wallet(
+    handle: *mut FaucetHandle,
+    account_id: *mut *mut c_char,
+) -> FaucetStatus {
+    guard(|| {
+        let handle = handle_ref(handle)?;
+        let account_id = out_param(account_id)?;
+
+        let account =
+            handle.block_on(async { create_wallet(&mut *handle.node.lock().await).await })?;
+        *account_id = to_c_string(account.id().to_hex());
+        Ok(())
+    })
+}
+
+/// Deploys a network faucet owned by `owner` and returns its account ID and the deployment
+/// transaction ID.
+///
+/// `script` is the MASM deploy script, or null for the bundled one. With `wait` set, the call
+/// returns once the deployment is committed.
+///
+/// # Safety
+///
+/// `handle` must be a live handle, `owner` a NUL-terminated string, `script` null or a
+/// NUL-terminated string and the out-parameters valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn faucet_deploy(
+    handle: *mut FaucetHandle,
+    owner: *const c_char,
+    script: *const c_char,
+    wait: bool,
+    faucet_id: *mut *mut c_char,
+    transaction_id: *mut *mut c_char,
+) -> FaucetStatus {
+    guard(|| {
+        let handle = handle_ref(handle)?;
+        let owner = handle.parse_account_id(required_str(owner)?)?;
+        let script = optional_str(script)?.unwrap_or(DEPLOY_SCRIPT);
+        let (faucet_id, transaction_id) = (out_param(faucet_id)?, out_param(transaction_id)?);
+
+        let deployment = handle.block_on(async {
+            let deployment = deploy_faucet(&mut *handle.node.lock().await, owner, script).await?;
+            if wait {
+                handle.wait(deployment.transaction_id).await?;
+            }
+            Ok(deployment)
+        })?;
+        *faucet_id = to_c_string(deployment.faucet.id().to_hex());
+        *transaction_id = to_c_string(deployment.transaction_id.to_hex());
+        Ok(())
+    })
+}
+
+/// Mints `amount` tokens of `faucet_id` to `recipient` and returns the mint transaction ID and
+/// the ID of the P2ID note the recipient will receive.
+///
+/// With `wait` set, the call returns once the mint is committed.
+///
+/// # Safety
+///
+/// `handle` must be a live handle, `faucet_id` and `recipient` NUL-terminated strings and the
+/// out-parameters valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn faucet_mint(
+    handle: *mut FaucetHandle,
+    faucet_id: *const c_char,
+    recipient: *const c_char,
+    amount: u64,
+    wait: bool,
+    transaction_id: *mut *mut c_char,
+    note_id: *mut *mut c_char,
+) -> FaucetStatus {
+    guard(|| {
+        let handle = handle_ref(handle)?;
+        let faucet_id = handle.parse_account_id(required_str(faucet_id)?)?;
+        let recipient = handle.parse_account_id(required_str(recipient)?)?;
+        let (transaction_id, note_id) = (out_param(transaction_id)?, out_param(note_id)?);
+
+        let mint = handle.block_on(async {
+            let mint =
+                mint_p2id(&mut *handle.node.lock().await, faucet_id, recipient, amount).await?;
+            if wait {
+                handle.wait(mint.transaction_id).await?;
+            }
+            Ok(mint)
+        })?;
+        *transaction_id = to_c_string(mint.transaction_id.to_hex());
+        *note_id = to_c_string(mint.p2id_note.id().to_hex());
+        Ok(())
+    })
+}
+
+impl FaucetHandle {
+    fn parse_account_id(&self, input: &str) -> Result<AccountId, FaucetError> {
+        parse_account_id(input, self.account_network.as_ref())
+    }
+
+    fn block_on<T>(
+        &self,
+        future: impl Future<Output = Result<T, FaucetError>>,
+    ) -> Result<T, FaucetError> {
+        self.local.block_on(&self.runtime, future)
+    }
+
+    async fn wait(&self, transaction_id: TransactionId) -> Result<(), FaucetError> {
+        let watcher = BlockWatcher::spawn(self.node.clone(), SYNC_INTERVAL);
+        wait_for_transaction(&self.node, &watcher, transaction_id).await?;
+        Ok(())
+    }
+}
+
+/// Failure of a call: the status returned to C and the message behind [`faucet_last_error`].
+struct Error(FaucetStatus, String);
+
+impl From<FaucetError> for Error {
+    fn from(err: FaucetError) -> Self {
+        Self(FaucetStatus::from(&err), err.to_string())
+    }
+}
+
+fn invalid_argument(message: &str) -> Error {
+    Error(FaucetStatus::InvalidArgument, message.to_string())
+}
+
+/// Runs `call`, records its error for [`faucet_last_error`] and keeps panics from unwinding into C.
+fn guard(call: impl FnOnce() -> Result<(), Error>) -> FaucetStatus {
+    let result = catch_unwind(AssertUnwindSafe(call)).unwrap_or_else(|_| {
+        Err(Error(
+            FaucetStatus::Internal,
+            "panic in network faucet library".to_string(),
+        ))
+    });
+
+    let (status, message) = match result {
+        Ok(()) => (FaucetStatus::Ok, None),
+        Err(Error(status, message)) => {
+            // Interior NUL bytes cannot be represented in a C string.
+            let message = CString::new(message.replace('\0', " ")).ok();
+            (status, message)
+        }
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    status
+}
+
+unsafe fn handle_ref<'a>(handle: *mut FaucetHandle) -> Result<&'a FaucetHandle, Error> {
+    handle
+        .as_ref()
+        .ok_or_else(|| invalid_argument("handle is null"))
+}
+
+unsafe fn out_param<'a, T>(out: *mut T) -> Result<&'a mut T, Error> {
+    out.as_mut()
+        .ok_or_else(|| invalid_argument("output pointer is null"))
+}
+
+unsafe fn required_str<'a>(string: *const c_char) -> Result<&'a str, Error> {
+    optional_str(string)?.ok_or_else(|| invalid_argument("string argument is null"))
+}
+
+unsafe fn optional_str<'a>(string: *const c_char) -> Result<Option<&'a str>, Error> {
+    if string.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(string)
+        .to_str()
+        .map(Some)
+        .map_err(|_| invalid_argument("string argument is not UTF-8"))
+}
+
+fn to_c_string(string: String) -> *mut c_char {
+    // Hex IDs never contain NUL bytes.
+    CString::new(string)
+        .expect("hex string without NUL bytes")
+        .into_raw()
+}
@@ -0,0 +1,386 @@
+//! Construction of the notes emitted by the network faucet.
+//!
+//! These helpers only depend on `miden-objects` and `miden-lib`, so they build without `std` and
+//! for `wasm32`. Wallets use them to compute the P2ID note a mint will produce before asking the
+//! faucet for it; the faucet uses the same code to build the notes it commits to.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use miden_lib::note::WellKnownNote;
+use miden_objects::{
+    account::AccountId,
+    asset::{Asset, FungibleAsset},
+    block::BlockNumber,
+    note::{
+        Note, NoteAssets, NoteExecutionHint, NoteInputs, NoteMetadata, NoteRecipient, NoteTag,
+        NoteType,
+    },
+    AssetError, Felt, NoteError, Word,
+};
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+/// Aux value of the MINT notes and the P2ID notes emitted by the faucet.
+///
+/// It fills the low byte of every aux value the faucet writes; the bytes above carry an
+/// [`AuxData`], all zero unless the mint names a campaign or a source.
+pub const MINT_NOTE_AUX: u64 = 27;
+
+/// Origin of a mint request, recorded in [`AuxData`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum RequestSource {
+    #[default]
+    Unknown = 0,
+    Cli = 1,
+    Rest = 2,
+    Grpc = 3,
+    Schedule = 4,
+    /// Bonus minted to the referrer of a mint.
+    Referral = 5,
+}
+
+impl RequestSource {
+    /// The source encoded as `code`, or `None` for codes of no known source.
+    pub fn from_code(code: u8) -> Option<Self> {
+        Some(match code {
+            0 => Self::Unknown,
+            1 => Self::Cli,
+            2 => Self::Rest,
+            3 => Self::Grpc,
+            4 => Self::Schedule,
+            5 => Self::Referral,
+            _ => return None,
+        })
+    }
+
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::Cli => "cli",
+            Self::Rest => "rest",
+            Self::Grpc => "grpc",
+            Self::Schedule => "schedule",
+            Self::Referral => "referral",
+        }
+    }
+}
+
+/// Attribution of a mint packed into the aux value of its notes, so notes found on chain can be
+/// traced back to the distribution campaign they were minted for.
+///
+/// The aux value holds [`MINT_NOTE_AUX`] in bits 0-7, the source in bits 8-15 and the campaign ID
+/// in bits 16-47. The default attribution encodes to [`MINT_NOTE_AUX`] itself.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct AuxData {
+    /// Campaign the mint belongs to, 0 for none.
+    pub campaign_id: u32,
+    pub source: RequestSource,
+}
+
+impl AuxData {
+    pub const fn new(campaign_id: u32, source: RequestSource) -> Self {
+        Self {
+            campaign_id,
+            source,
+        }
+    }
+
+    pub fn to_felt(self) -> Felt {
+        Felt::new(
+            MINT_NOTE_AUX
+                | (u64::from(self.source.code()) << 8)
+                | (u64::from(self.campaign_id) << 16),
+        )
+    }
+
+    /// Decodes the aux value of a note, or returns `None` unless the faucet wrote it.
+    pub fn from_felt(aux: Felt) -> Option<Self> {
+        let aux = aux.as_int();
+        if aux & 0xff != MINT_NOTE_AUX || aux >> 48 != 0 {
+            return None;
+        }
+        Some(Self {
+            campaign_id: (aux >> 16) as u32,
+            source: RequestSource::from_code((aux >> 8) as u8)?,
+        })
+    }
+}
+
+/// Recipient of a P2ID note paying `target`.
+///
+/// Its digest is what a MINT note commits to; the faucet builds the P2ID note from it on chain.
+pub fn p2id_recipient(target: AccountId, serial_num: Word) -> Result<NoteRecipient, NoteError> {
+    let note_script = WellKnownNote::P2ID.script();
+    let note_inputs = NoteInputs::new(alloc::vec![target.suffix(), target.prefix().as_felt()])?;
+    Ok(NoteRecipient::new(serial_num, note_script, note_inputs))
+}
+
+/// Reclaim height of the P2IDE notes the sender can never reclaim, the highest block number.
+pub const NEVER_RECLAIMABLE: u32 = u32::MAX;
+
+/// Recipient of a P2IDE note paying `target` that the sender can reclaim from `reclaim_height` on.
+///
+/// Without `unlock_height`, the note carries no timelock and `target` can consume it right away;
+/// otherwise only once the chain reaches `unlock_height`.
+pub fn p2ide_recipient(
+    target: AccountId,
+    serial_num: Word,
+    reclaim_height: BlockNumber,
+    unlock_height: Option<BlockNumber>,
+) -> Result<NoteRecipient, NoteError> {
+    let note_script = WellKnownNote::P2IDE.script();
+    let note_inputs = NoteInputs::new(alloc::vec![
+        target.suffix(),
+        target.prefix().as_felt(),
+        Felt::from(reclaim_height),
+        Felt::from(unlock_height.unwrap_or(BlockNumber::GENESIS)),
+    ])?;
+    Ok(NoteRecipient::new(serial_num, note_script, note_inputs))
+}
+
+/// Reclaim height of a P2IDE note, or `None` for any other note and for P2IDE notes that cannot be
+/// reclaimed.
+pub fn reclaim_height(note: &Note) -> Option<BlockNumber> {
+    p2ide_height_input(note, 2).filter(|height| height.as_u32() != NEVER_RECLAIMABLE)
+}
+
+/// Height from which the recipient can consume a timelocked P2IDE note, or `None` for notes
+/// consumable right away.
+pub fn unlock_height(note: &Note) -> Option<BlockNumber> {
+    p2ide_height_input(note, 3).filter(|height| *height != BlockNumber::GENESIS)
+}
+
+fn p2ide_height_input(note: &Note, index: usize) -> Option<BlockNumber> {
+    let recipient = note.recipient();
+    if recipient.script().root() != WellKnownNote::P2IDE.script_root() {
+        return None;
+    }
+
+    let height = recipient.inputs().values().get(index)?.as_int();
+    u32::try_from(height).ok().map(BlockNumber::from)
+}
+
+/// Builds the P2ID note the faucet emits for `target`.
+///
+/// Unlike `miden_lib::note::create_p2id_note`, the serial number is supplied by the caller so the
+/// recipient digest can be committed to in the MINT note before the P2ID note exists on chain.
+pub fn create_p2id_note_exact(
+    sender: AccountId,
+    target: AccountId,
+    assets: Vec<Asset>,
+    note_type: NoteType,
+    aux: Felt,
+    serial_num: Word,
+) -> Result<Note, NoteError> {
+    let recipient = p2id_recipient(target, serial_num)?;
+
+    let tag = NoteTag::from_account_id(target);
+
+    let metadata = NoteMetadata::new(sender, note_type, tag, NoteExecutionHint::always(), aux)?;
+    let vault = NoteAssets::new(assets)?;
+
+    Ok(Note::new(vault, metadata, recipient))
+}
+
+/// Builds the P2IDE note the faucet emits for `target`, reclaimable by `sender` from
+/// `reclaim_height` on and, with `unlock_height`, consumable by `target` from that height on.
+///
+/// Like [`create_p2id_note_exact`], the serial number is supplied by the caller.
+#[allow(clippy::too_many_arguments)]
+pub fn create_p2ide_note_exact(
+    sender: AccountId,
+    target: AccountId,
+    assets: Vec<Asset>,
+    note_type: NoteType,
+    aux: Felt,
+    serial_num: Word,
+    reclaim_height: BlockNumber,
+    unlock_height: Option<BlockNumber>,
+) -> Result<Note, NoteError> {
+    let recipient = p2ide_recipient(target, serial_num, reclaim_height, unlock_height)?;
+
+    let tag = NoteTag::from_account_id(target);
+
+    let metadata = NoteMetadata::new(sender, note_type, tag, NoteExecutionHint::always(), aux)?;
+    let vault = NoteAssets::new(assets)?;
+
+    Ok(Note::new(vault, metadata, recipient))
+}
+
+/// Kind of note a mint pays the recipient with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MintNoteKind {
+    #[default]
+    P2id,
+    /// Reclaimable by the faucet once the chain reaches `reclaim_height`.
+    P2ide { reclaim_height: BlockNumber },
+    /// A P2IDE note the recipient can only consume once the chain reaches `unlock_height`, and
+    /// the faucet can reclaim from `reclaim_height` on, if set.
+    Timelocked {
+        unlock_height: BlockNumber,
+        reclaim_height: Option<BlockNumber>,
+    },
+}
+
+impl MintNoteKind {
+    /// A P2IDE note when `reclaim_height` is set, a P2ID note otherwise.
+    pub fn from_reclaim_height(reclaim_height: Option<BlockNumber>) -> Self {
+        match reclaim_height {
+            Some(reclaim_height) => Self::P2ide { reclaim_height },
+            None => Self::P2id,
+        }
+    }
+
+    /// The same note, timelocked until `unlock_height` when it is set.
+    pub fn with_unlock_height(self, unlock_height: Option<BlockNumber>) -> Self {
+        let Some(unlock_height) = unlock_height else {
+            return self;
+        };
+        let reclaim_height = match self {
+            Self::P2id => None,
+            Self::P2ide { reclaim_height } => Some(reclaim_height),
+            Self::Timelocked { reclaim_height, .. } => reclaim_height,
+        };
+        Self::Timelocked {
+            unlock_height,
+            reclaim_height,
+        }
+    }
+}
+
+/// Error of [`mint_output_note`].
+#[derive(Debug)]
+pub enum MintNoteError {
+    Asset(AssetError),
+    Note(NoteError),
+}
+
+impl core::fmt::Display for MintNoteError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Asset(err) => write!(f, "invalid mint asset: {err}"),
+            Self::Note(err) => write!(f, "invalid mint note: {err}"),
+        }
+    }
+}
+
+/// The private note produced by a mint of `amount` tokens of `faucet_id` to `recipient`, with the
+/// default [`AuxData`]. Its ID does not depend on the note type or aux value of the actual mint.
+pub fn mint_output_note(
+    faucet_id: AccountId,
+    recipient: AccountId,
+    amount: u64,
+    serial_num: Word,
+    kind: MintNoteKind,
+) -> Result<Note, MintNoteError> {
+    mint_output_note_with(
+        faucet_id,
+        recipient,
+        amount,
+        serial_num,
+        kind,
+        NoteType::Private,
+        AuxData::default(),
+    )
+}
+
+/// Like [`mint_output_note`], for a mint of a note of type `note_type` attributed to `aux`.
+pub fn mint_output_note_with(
+    faucet_id: AccountId,
+    recipient: AccountId,
+    amount: u64,
+    serial_num: Word,
+    kind: MintNoteKind,
+    note_type: NoteType,
+    aux: AuxData,
+) -> Result<Note, MintNoteError> {
+    mint_output_note_tagged(
+        faucet_id,
+        recipient,
+        amount,
+        serial_num,
+        kind,
+        note_type,
+        aux,
+        NoteTag::from_account_id(recipient),
+    )
+}
+
+/// Like [`mint_output_note_with`], for a note tagged `tag` instead of the tag of the recipient,
+/// e.g. a use-case tag network accounts pick the note up by. The tag does not change the note ID.
+#[allow(clippy::too_many_arguments)]
+pub fn mint_output_note_tagged(
+    faucet_id: AccountId,
+    recipient: AccountId,
+    amount: u64,
+    serial_num: Word,
+    kind: MintNoteKind,
+    note_type: NoteType,
+    aux: AuxData,
+    tag: NoteTag,
+) -> Result<Note, MintNoteError> {
+    let asset = FungibleAsset::new(faucet_id, amount).map_err(MintNoteError::Asset)?;
+    let assets = alloc::vec![asset.into()];
+    let aux = aux.to_felt();
+
+    match kind {
+        MintNoteKind::P2id => {
+            create_p2id_note_exact(faucet_id, recipient, assets, note_type, aux, serial_num)
+        }
+        MintNoteKind::P2ide { reclaim_height } => create_p2ide_note_exact(
+            faucet_id,
+            recipient,
+            assets,
+            note_type,
+            aux,
+            serial_num,
+            reclaim_height,
+            None,
+        ),
+        MintNoteKind::Timelocked {
+            unlock_height,
+            reclaim_height,
+        } => create_p2ide_note_exact(
+            faucet_id,
+            recipient,
+            assets,
+            note_type,
+            aux,
+            serial_num,
+            reclaim_height.unwrap_or(BlockNumber::from(NEVER_RECLAIMABLE)),
+            Some(unlock_height),
+        ),
+    }
+    .and_then(|note| retag(note, tag))
+    .map_err(MintNoteError::Note)
+}
+
+/// `note` with its tag replaced by `tag`.
+fn retag(note: Note, tag: NoteTag) -> Result<Note, NoteError> {
+    if note.metadata().tag() == tag {
+        return Ok(note);
+    }
+    let metadata = note.metadata();
+    let metadata = NoteMetadata::new(
+        metadata.sender(),
+        metadata.note_type(),
+        tag,
+        metadata.execution_hint(),
+        metadata.aux(),
+    )?;
+    Ok(Note::new(
+        note.assets().clone(),
+        metadata,
+        note.recipient().clone(),
+    ))
+}
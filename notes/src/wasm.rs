@@ -0,0 +1,147 @@
+//! JavaScript exports of the note helpers.
+//!
+//! Account IDs and words are passed as hex strings, the format the faucet API uses.
+
+use alloc::{format, string::String};
+
+use miden_objects::{
+    account::AccountId,
+    block::BlockNumber,
+    note::{Note, NoteTag, NoteType},
+    Word,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::{mint_output_note_tagged, p2id_recipient, AuxData, MintNoteKind, RequestSource};
+
+/// Commitment of the note a mint will produce.
+///
+/// `serial_num` must be the serial number passed to the faucet's mint request, `reclaim_height`
+/// its reclaim height for reclaimable (P2IDE) mints and `unlock_height` its unlock height for
+/// timelocked mints.
+///
+/// The remaining arguments describe the note as the faucet wrote it and default to a private
+/// note with the tag of `recipient` and no attribution:
+/// - `note_type` is `public` or `private`,
+/// - `tag` is the raw value of the note tag the faucet resolved from the tag strategy of the mint,
+/// - `campaign_id` and `source` are the attribution packed into the aux value, `source` being the
+///   name of the request source, e.g. `rest` for mints through the REST API.
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = mintNoteCommitment)]
+pub fn mint_note_commitment(
+    faucet_id: &str,
+    recipient: &str,
+    amount: u64,
+    serial_num: &str,
+    reclaim_height: Option<u32>,
+    unlock_height: Option<u32>,
+    note_type: Option<String>,
+    tag: Option<u32>,
+    campaign_id: Option<u32>,
+    source: Option<String>,
+) -> Result<String, JsError> {
+    let note = mint_note(
+        faucet_id,
+        recipient,
+        amount,
+        serial_num,
+        reclaim_height,
+        unlock_height,
+        note_type,
+        tag,
+        campaign_id,
+        source,
+    )?;
+    Ok(note.commitment().to_hex())
+}
+
+/// ID of the note a mint will produce, with the arguments of [`mint_note_commitment`].
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = mintNoteId)]
+pub fn mint_note_id(
+    faucet_id: &str,
+    recipient: &str,
+    amount: u64,
+    serial_num: &str,
+    reclaim_height: Option<u32>,
+    unlock_height: Option<u32>,
+    note_type: Option<String>,
+    tag: Option<u32>,
+    campaign_id: Option<u32>,
+    source: Option<String>,
+) -> Result<String, JsError> {
+    let note = mint_note(
+        faucet_id,
+        recipient,
+        amount,
+        serial_num,
+        reclaim_height,
+        unlock_height,
+        note_type,
+        tag,
+        campaign_id,
+        source,
+    )?;
+    Ok(note.id().to_hex())
+}
+
+/// Recipient digest a MINT note paying `recipient` commits to.
+#[wasm_bindgen(js_name = p2idRecipientDigest)]
+pub fn p2id_recipient_digest(recipient: &str, serial_num: &str) -> Result<String, JsError> {
+    let recipient = p2id_recipient(parse_account_id(recipient)?, parse_word(serial_num)?)
+        .map_err(|err| JsError::new(&format!("invalid recipient: {err}")))?;
+    Ok(recipient.digest().to_hex())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn mint_note(
+    faucet_id: &str,
+    recipient: &str,
+    amount: u64,
+    serial_num: &str,
+    reclaim_height: Option<u32>,
+    unlock_height: Option<u32>,
+    note_type: Option<String>,
+    tag: Option<u32>,
+    campaign_id: Option<u32>,
+    source: Option<String>,
+) -> Result<Note, JsError> {
+    let recipient = parse_account_id(recipient)?;
+    let note_type = match note_type.as_deref() {
+        None | Some("private") => NoteType::Private,
+        Some("public") => NoteType::Public,
+        Some(other) => {
+            return Err(JsError::new(&format!(
+                "invalid note type `{other}`, expected `public` or `private`"
+            )))
+        }
+    };
+    let source = match source.as_deref() {
+        None => RequestSource::Unknown,
+        Some(name) => (0..=u8::MAX)
+            .filter_map(RequestSource::from_code)
+            .find(|source| source.as_str() == name)
+            .ok_or_else(|| JsError::new(&format!("unknown request source `{name}`")))?,
+    };
+    mint_output_note_tagged(
+        parse_account_id(faucet_id)?,
+        recipient,
+        amount,
+        parse_word(serial_num)?,
+        MintNoteKind::from_reclaim_height(reclaim_height.map(BlockNumber::from))
+            .with_unlock_height(unlock_height.map(BlockNumber::from)),
+        note_type,
+        AuxData::new(campaign_id.unwrap_or_default(), source),
+        tag.map_or_else(|| NoteTag::from_account_id(recipient), NoteTag::from),
+    )
+    .map_err(|err| JsError::new(&format!("{err}")))
+}
+
+fn parse_account_id(hex: &str) -> Result<AccountId, JsError> {
+    AccountId::from_hex(hex)
+        .map_err(|err| JsError::new(&format!("invalid account ID `{hex}`: {err}")))
+}
+
+fn parse_word(hex: &str) -> Result<Word, JsError> {
+    Word::try_from(hex).map_err(|err| JsError::new(&format!("invalid word `{hex}`: {err}")))
+}
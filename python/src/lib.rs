@@ -0,0 +1,229 @@
+//! Python bindings for the network faucet library.
+//!
+//! Build with `maturin develop -m python/Cargo.toml`. The client is not thread-safe, so a
+//! [`Faucet`] can only be used from the Python thread that created it; every call blocks until the
+//! underlying operation completes.
+//!
+//! ```python
+//! from network_faucet import Faucet
+//!
+//! faucet = Faucet("faucet.toml")
+//! owner = faucet.create_wallet()
+//! faucet_id = faucet.deploy_faucet(owner).faucet_id
+//! alice = faucet.create_wallet()
+//! mint = faucet.mint_to(faucet_id, alice, 50)
+//! faucet.consume(alice, mint.note_id)
+//! assert faucet.get_balance(alice, faucet_id) == 50
+//! ```
+
+use std::{collections::HashMap, future::Future, path::PathBuf, rc::Rc};
+
+use miden_client::{
+    account::{AccountId, NetworkId},
+    note::Note,
+    transaction::TransactionId,
+};
+use network_faucet::{
+    account::parse_account_id,
+    config::Config,
+    deploy::deploy_faucet,
+    mint::{consume_note, get_balance, mint_p2id},
+    node::{connect, ConfiguredNode, FaucetNode},
+    script::DEPLOY_SCRIPT,
+    wallet::create_wallet,
+    watcher::{wait_for_transaction, BlockWatcher, SharedNode, SYNC_INTERVAL},
+    FaucetError,
+};
+use pyo3::{create_exception, exceptions::PyException, prelude::*};
+use tokio::{
+    runtime::{Builder, Runtime},
+    sync::Mutex,
+    task::LocalSet,
+};
+
+create_exception!(network_faucet, FaucetException, PyException);
+
+fn to_py_err(err: FaucetError) -> PyErr {
+    FaucetException::new_err(err.to_string())
+}
+
+/// Result of `Faucet.deploy_faucet`.
+#[pyclass(frozen, get_all)]
+struct Deployment {
+    faucet_id: String,
+    transaction_id: String,
+    /// Block the deployment was committed in, `None` when not waited for.
+    block_num: Option<u32>,
+}
+
+/// Result of `Faucet.mint_to`.
+#[pyclass(frozen, get_all)]
+struct Mint {
+    transaction_id: String,
+    /// ID of the P2ID note the recipient will receive.
+    note_id: String,
+    /// Block the mint was committed in, `None` when not waited for.
+    block_num: Option<u32>,
+}
+
+/// Faucet client connected to the node of a configuration file.
+#[pyclass(unsendable)]
+struct Faucet {
+    runtime: Runtime,
+    local: LocalSet,
+    node: SharedNode<ConfiguredNode>,
+    /// Network whose prefix bech32 account IDs must carry, see [`Config::account_network`].
+    account_network: Option<NetworkId>,
+    /// P2ID notes minted by this client by note ID, kept so their recipients can consume them.
+    notes: HashMap<String, Note>,
+}
+
+#[pymethods]
+impl Faucet {
+    /// Connects the node of the configuration file at `config`, `$FAUCET_CONFIG` or
+    /// `./faucet.toml`.
+    #[new]
+    #[pyo3(signature = (config = None))]
+    fn new(config: Option<PathBuf>) -> PyResult<Self> {
+        let config = match config {
+            Some(path) => Config::from_file(path),
+            None => Config::load(),
+        }
+        .map_err(to_py_err)?;
+
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let local = LocalSet::new();
+        let node = local
+            .block_on(&runtime, async {
+                let mut node = connect(&config).await?;
+                node.sync_state().await?;
+                Ok(node)
+            })
+            .map_err(to_py_err)?;
+
+        Ok(Self {
+            runtime,
+            local,
+            node: Rc::new(Mutex::new(node)),
+            account_network: config.account_network(),
+            notes: HashMap::new(),
+        })
+    }
+
+    /// Creates a wallet managed by this client and returns its account ID.
+    fn create_wallet(&self) -> PyResult<String> {
+        let account = self.block_on(async { create_wallet(&mut *self.node.lock().await).await })?;
+        Ok(account.id().to_hex())
+    }
+
+    /// Deploys a network faucet owned by `owner`.
+    #[pyo3(signature = (owner, script = None, wait = true))]
+    fn deploy_faucet(&self, owner: &str, script: Option<&str>, wait: bool) -> PyResult<Deployment> {
+        let owner = self.parse_account_id(owner)?;
+        let script = script.unwrap_or(DEPLOY_SCRIPT);
+
+        self.block_on(async {
+            let deployment = deploy_faucet(&mut *self.node.lock().await, owner, script).await?;
+            let block_num = self.wait(deployment.transaction_id, wait).await?;
+            Ok(Deployment {
+                faucet_id: deployment.faucet.id().to_hex(),
+                transaction_id: deployment.transaction_id.to_hex(),
+                block_num,
+            })
+        })
+    }
+
+    /// Mints `amount` tokens of `faucet_id` to `recipient`.
+    #[pyo3(signature = (faucet_id, recipient, amount, wait = true))]
+    fn mint_to(
+        &mut self,
+        faucet_id: &str,
+        recipient: &str,
+        amount: u64,
+        wait: bool,
+    ) -> PyResult<Mint> {
+        let faucet_id = self.parse_account_id(faucet_id)?;
+        let recipient = self.parse_account_id(recipient)?;
+
+        let (mint, block_num) = self.block_on(async {
+            let mint =
+                mint_p2id(&mut *self.node.lock().await, faucet_id, recipient, amount).await?;
+            let block_num = self.wait(mint.transaction_id, wait).await?;
+            Ok((mint, block_num))
+        })?;
+
+        let note_id = mint.p2id_note.id().to_hex();
+        self.notes.insert(note_id.clone(), mint.p2id_note);
+        Ok(Mint {
+            transaction_id: mint.transaction_id.to_hex(),
+            note_id,
+            block_num,
+        })
+    }
+
+    /// Consumes a note minted by `mint_to` into `account_id` and returns the transaction ID.
+    #[pyo3(signature = (account_id, note_id, wait = true))]
+    fn consume(&mut self, account_id: &str, note_id: &str, wait: bool) -> PyResult<String> {
+        let account_id = self.parse_account_id(account_id)?;
+        let note = self.notes.remove(note_id).ok_or_else(|| {
+            FaucetException::new_err(format!("note {note_id} was not minted by this client"))
+        })?;
+
+        let transaction_id = self.block_on(async {
+            let transaction_id =
+                consume_note(&mut *self.node.lock().await, account_id, note).await?;
+            self.wait(transaction_id, wait).await?;
+            Ok(transaction_id)
+        })?;
+        Ok(transaction_id.to_hex())
+    }
+
+    /// Balance of `faucet_id` tokens held by `account_id` according to the local store.
+    fn get_balance(&self, account_id: &str, faucet_id: &str) -> PyResult<u64> {
+        let account_id = self.parse_account_id(account_id)?;
+        let faucet_id = self.parse_account_id(faucet_id)?;
+
+        self.block_on(async {
+            let mut node = self.node.lock().await;
+            node.sync_state().await?;
+            get_balance(&mut *node, account_id, faucet_id).await
+        })
+    }
+}
+
+impl Faucet {
+    fn parse_account_id(&self, input: &str) -> PyResult<AccountId> {
+        parse_account_id(input, self.account_network.as_ref()).map_err(to_py_err)
+    }
+
+    fn block_on<T>(&self, future: impl Future<Output = Result<T, FaucetError>>) -> PyResult<T> {
+        self.local
+            .block_on(&self.runtime, future)
+            .map_err(to_py_err)
+    }
+
+    /// Waits for `transaction_id` to be committed when `wait` is set.
+    async fn wait(
+        &self,
+        transaction_id: TransactionId,
+        wait: bool,
+    ) -> Result<Option<u32>, FaucetError> {
+        if !wait {
+            return Ok(None);
+        }
+
+        let watcher = BlockWatcher::spawn(self.node.clone(), SYNC_INTERVAL);
+        let block_num = wait_for_transaction(&self.node, &watcher, transaction_id).await?;
+        Ok(Some(block_num.as_u32()))
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "network_faucet")]
+fn network_faucet_py(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Faucet>()?;
+    module.add_class::<Deployment>()?;
+    module.add_class::<Mint>()?;
+    module.add("FaucetException", module.py().get_type::<FaucetException>())?;
+    Ok(())
+}
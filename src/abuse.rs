@@ -0,0 +1,347 @@
+//! Anti-abuse policies of the mint APIs.
+//!
+//! Every mint requested through the REST or gRPC API is judged by the [`AbusePolicies`] of its
+//! worker before it is submitted. Each [`AbusePolicy`] sees the [`AbuseRequest`]: the identity of
+//! the caller, its IP address, the recipient and the amount, and reads the history of earlier
+//! requests from the [`Ledger`]. The first policy refusing the request fails the mint.
+//!
+//! The built-in policies are configured as `[[abuse_policies]]` entries and combine freely:
+//!
+//! - `velocity`: at most `max_requests` requests per identity, IP address or recipient within
+//!   `window_secs`, failing with [`FaucetError::RateLimited`].
+//! - `new_account`: recipients the faucet never minted to receive at most `max_amount`, and
+//!   recipients with `max_unclaimed` notes they have not claimed receive nothing more.
+//! - `daily_budget`: at most `max_amount` tokens within the last 24 hours, counted by its `basis`:
+//!   the tokens `requested` through the APIs, or the tokens the faucet `distributed`, i.e. its
+//!   submitted and committed mints. The window rolls, and failed or reorged mints do not count
+//!   against a `distributed` budget, their re-mints do. `service.daily_budget` and the
+//!   `daily_budget` of a token faucet add a `distributed` budget to the policies of their faucet.
+//!
+//! Servers embedding the crate add policies of their own with [`AbusePolicies::with_policy`].
+//! Mints of the scheduler and referral bonuses come from the service itself and are not judged,
+//! though they count against a `distributed` budget.
+
+use std::net::IpAddr;
+
+use miden_client::account::AccountId;
+use serde::{Deserialize, Serialize};
+
+use crate::{ledger::Ledger, FaucetError};
+
+/// Window of the `daily_budget` policy.
+pub const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// Mint request judged by the [`AbusePolicies`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbuseRequest {
+    /// Identity of the caller, e.g. `github:octocat`, `None` for anonymous callers.
+    pub identity: Option<String>,
+    /// Address the request came from.
+    pub ip: IpAddr,
+    /// Faucet the request mints from.
+    pub faucet_id: AccountId,
+    pub recipient: AccountId,
+    pub amount: u64,
+    /// Tokens of the requests submitted along with this one and judged before it, e.g. the
+    /// earlier mints of a batch, see [`AbusePolicies::check_all`].
+    pub batched: u64,
+    /// Unix timestamp of the request, in seconds.
+    pub now: u64,
+}
+
+impl AbuseRequest {
+    /// Value of `key` for this request, `None` if the request has none.
+    pub fn subject(&self, key: RequestKey) -> Option<String> {
+        match key {
+            RequestKey::Identity => self.identity.clone(),
+            RequestKey::Ip => Some(self.ip.to_string()),
+            RequestKey::Recipient => Some(self.recipient.to_hex()),
+        }
+    }
+}
+
+/// Field of an [`AbuseRequest`] requests are counted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestKey {
+    Identity,
+    Ip,
+    Recipient,
+}
+
+impl RequestKey {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Identity => "identity",
+            Self::Ip => "ip",
+            Self::Recipient => "recipient",
+        }
+    }
+}
+
+/// Check of mint requests against abuse, see [`crate::abuse`].
+pub trait AbusePolicy {
+    /// Name of the policy in refusals, e.g. `velocity`.
+    fn name(&self) -> &str;
+
+    /// Fails if `request` is to be refused, given the earlier requests recorded in `ledger`.
+    fn check(&self, request: &AbuseRequest, ledger: &Ledger) -> Result<(), FaucetError>;
+}
+
+/// Built-in policy, read from an `[[abuse_policies]]` entry of the configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum AbusePolicyConfig {
+    /// At most `max_requests` requests of the same `key` within `window_secs`.
+    Velocity {
+        key: RequestKey,
+        max_requests: u32,
+        window_secs: u64,
+    },
+    /// Limits of recipients the faucet knows little about.
+    NewAccount {
+        /// Most a recipient without committed mints receives per request.
+        #[serde(default)]
+        max_amount: Option<u64>,
+        /// Refuse recipients with this many committed mints they have not claimed.
+        #[serde(default)]
+        max_unclaimed: Option<u64>,
+    },
+    /// At most `max_amount` tokens within a day, counted by `basis`.
+    DailyBudget {
+        max_amount: u64,
+        #[serde(default)]
+        basis: BudgetBasis,
+    },
+}
+
+/// What counts against a `daily_budget` policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetBasis {
+    /// Tokens requested by all callers of the APIs and admitted by the policies.
+    #[default]
+    Requested,
+    /// Tokens the faucet of the request distributed, whoever asked for them.
+    Distributed,
+}
+
+impl AbusePolicyConfig {
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        let problem = match self {
+            Self::Velocity {
+                max_requests,
+                window_secs,
+                ..
+            } if *max_requests == 0 || *window_secs == 0 => {
+                "needs a positive max_requests and window_secs"
+            }
+            Self::NewAccount {
+                max_amount: None,
+                max_unclaimed: None,
+            } => "needs max_amount or max_unclaimed",
+            Self::DailyBudget { max_amount: 0, .. } => "needs a positive max_amount",
+            _ => return Ok(()),
+        };
+        Err(FaucetError::Config(format!(
+            "abuse policy `{}` {problem}",
+            self.name()
+        )))
+    }
+}
+
+impl AbusePolicy for AbusePolicyConfig {
+    fn name(&self) -> &str {
+        match self {
+            Self::Velocity { .. } => "velocity",
+            Self::NewAccount { .. } => "new_account",
+            Self::DailyBudget { .. } => "daily_budget",
+        }
+    }
+
+    fn check(&self, request: &AbuseRequest, ledger: &Ledger) -> Result<(), FaucetError> {
+        match self {
+            Self::Velocity {
+                key,
+                max_requests,
+                window_secs,
+            } => {
+                let Some(subject) = request.subject(*key) else {
+                    return Ok(());
+                };
+                let since = request.now.saturating_sub(*window_secs);
+                let (requests, oldest) = ledger.requests_since(*key, &subject, since)?;
+                if requests >= *max_requests {
+                    // A request is available again once the oldest one leaves the window.
+                    let retry_after_secs =
+                        (oldest.unwrap_or(request.now) + window_secs).saturating_sub(request.now);
+                    return Err(FaucetError::RateLimited { retry_after_secs });
+                }
+                Ok(())
+            }
+            Self::NewAccount {
+                max_amount,
+                max_unclaimed,
+            } => {
+                let stats = ledger.recipient_stats(request.recipient)?;
+                if let Some(max_amount) = max_amount {
+                    if stats.mints == 0 && request.amount > *max_amount {
+                        return Err(refused(
+                            self,
+                            format!("new recipients receive at most {max_amount} tokens"),
+                        ));
+                    }
+                }
+                let unclaimed = stats.mints.saturating_sub(stats.claimed);
+                if max_unclaimed.is_some_and(|max_unclaimed| unclaimed >= max_unclaimed) {
+                    return Err(refused(
+                        self,
+                        format!(
+                            "{} has {unclaimed} unclaimed notes, claim them first",
+                            request.recipient
+                        ),
+                    ));
+                }
+                Ok(())
+            }
+            Self::DailyBudget {
+                max_amount,
+                basis: BudgetBasis::Requested,
+            } => {
+                let requested =
+                    ledger.requested_amount_since(request.now.saturating_sub(DAY_SECS))?;
+                if requested.saturating_add(request.amount) > *max_amount {
+                    return Err(refused(
+                        self,
+                        format!("the daily budget of {max_amount} tokens is spent"),
+                    ));
+                }
+                Ok(())
+            }
+            Self::DailyBudget {
+                max_amount,
+                basis: BudgetBasis::Distributed,
+            } => check_distributed_budget(request, ledger, *max_amount),
+        }
+    }
+}
+
+/// Fails with [`FaucetError::DailyBudgetExhausted`] if the faucet of `request` would distribute
+/// more than `budget` tokens within a day with it, naming when enough of the budget is free
+/// again.
+///
+/// Fails with [`FaucetError::AmountOutOfRange`] if the request alone exceeds the budget.
+fn check_distributed_budget(
+    request: &AbuseRequest,
+    ledger: &Ledger,
+    budget: u64,
+) -> Result<(), FaucetError> {
+    let amount = request.batched.saturating_add(request.amount);
+    if amount > budget {
+        return Err(FaucetError::AmountOutOfRange {
+            amount,
+            min: 0,
+            max: budget,
+        });
+    }
+    let since = request.now.saturating_sub(DAY_SECS);
+    let mints = ledger.distributed_since(request.faucet_id, since)?;
+    let distributed = mints.iter().map(|(_, amount)| amount).sum::<u64>();
+    if distributed.saturating_add(amount) <= budget {
+        return Ok(());
+    }
+
+    // The request fits once enough of the oldest mints have left the window.
+    let mut freed = 0;
+    let retry_at = mints
+        .iter()
+        .find_map(|&(created_at, minted)| {
+            freed += minted;
+            (distributed - freed + amount <= budget).then_some(created_at + DAY_SECS)
+        })
+        .unwrap_or(request.now + DAY_SECS);
+    Err(FaucetError::DailyBudgetExhausted {
+        budget,
+        remaining: budget.saturating_sub(distributed),
+        retry_at,
+    })
+}
+
+/// Ordered set of policies judging the mint requests of a worker.
+#[derive(Default)]
+pub struct AbusePolicies {
+    policies: Vec<Box<dyn AbusePolicy>>,
+}
+
+impl AbusePolicies {
+    /// The built-in policies of `configs`, in configuration order.
+    pub fn from_config(configs: &[AbusePolicyConfig]) -> Self {
+        let mut policies = Self::default();
+        for config in configs {
+            policies = policies.with_policy(config.clone());
+        }
+        policies
+    }
+
+    /// Adds `policy`, checked after the policies added before.
+    pub fn with_policy(mut self, policy: impl AbusePolicy + 'static) -> Self {
+        self.policies.push(Box::new(policy));
+        self
+    }
+
+    /// Adds a `daily_budget` policy holding the faucet to distribute at most `max_amount` tokens
+    /// within a day, e.g. for `service.daily_budget`, and nothing for `None`.
+    pub fn with_daily_budget(self, max_amount: Option<u64>) -> Self {
+        match max_amount {
+            Some(max_amount) => self.with_policy(AbusePolicyConfig::DailyBudget {
+                max_amount,
+                basis: BudgetBasis::Distributed,
+            }),
+            None => self,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    /// Fails with the refusal of the first policy refusing `request`.
+    pub fn check(&self, request: &AbuseRequest, ledger: &Ledger) -> Result<(), FaucetError> {
+        self.policies
+            .iter()
+            .try_for_each(|policy| policy.check(request, ledger))
+    }
+
+    /// Fails with the first refusal of `requests`, each judged with the requests before it
+    /// counted as admitted, e.g. the mints of one batch. Nothing is recorded.
+    pub fn check_all(&self, requests: &[AbuseRequest], ledger: &Ledger) -> Result<(), FaucetError> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        ledger.dry_run(|ledger| {
+            let mut batched = 0_u64;
+            requests.iter().try_for_each(|request| {
+                let request = AbuseRequest {
+                    batched,
+                    ..request.clone()
+                };
+                self.check(&request, ledger)?;
+                batched = batched.saturating_add(request.amount);
+                ledger.record_request(&request)
+            })
+        })
+    }
+
+    /// Records `request` as admitted, counting it in the history of later requests.
+    pub fn record(&self, request: &AbuseRequest, ledger: &Ledger) -> Result<(), FaucetError> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        ledger.record_request(request)
+    }
+}
+
+/// Refusal of `policy` for `reason`.
+pub fn refused(policy: &dyn AbusePolicy, reason: impl Into<String>) -> FaucetError {
+    FaucetError::AbuseRefused(policy.name().to_string(), reason.into())
+}
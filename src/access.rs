@@ -0,0 +1,535 @@
+//! Access tiers of the mint APIs.
+//!
+//! Each mint request is authenticated by the credential it carries in its `Authorization:
+//! Bearer` header, or gRPC `authorization` metadata, and the resulting [`Tier`] decides the
+//! amounts it may mint and how often. Tiers start from the bounds of the `[mint]` section, which
+//! each `[tiers.<tier>]` section may override, and may limit the requests of each caller, see
+//! [`Access::admit`]:
+//!
+//! - `admin`: API keys of `[[api_keys]]` with the `admin` role, see [`crate::authz`].
+//! - `api_key`: the other API keys, e.g. of CI pipelines pulling larger amounts.
+//! - `github`: GitHub sessions, see [`crate::github`], which also count drips per account.
+//! - `anonymous`: requests without credentials, refused once GitHub sign-in is configured. The
+//!   faucet has no captcha, so anonymous minting is best kept to private deployments.
+//!
+//! The amounts of each tier can be adjusted at runtime through the admin API, see
+//! [`crate::admin`]. With [`Access::with_ledger`], adjusted amounts are kept in the [`Ledger`] and
+//! restored on startup until they are reset. The [`AccessList`]s kept in the [`Ledger`] refuse
+//! mints to or by the accounts and requesters they deny, and once the allow list has entries,
+//! every mint neither its recipient nor its requester is allowed on.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    net::IpAddr,
+    str::FromStr,
+    sync::{Mutex, RwLock},
+};
+
+use miden_client::account::AccountId;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    abuse::DAY_SECS,
+    account::parse_account_id,
+    authz::{find_api_key, Role},
+    github::{GithubAuth, GithubUser},
+    ledger::{unix_now, Ledger},
+    mint::AmountConfig,
+    FaucetError,
+};
+
+/// Level of trust of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tier {
+    Anonymous,
+    Github,
+    ApiKey,
+    Admin,
+}
+
+impl Tier {
+    pub const ALL: [Tier; 4] = [Self::Anonymous, Self::Github, Self::ApiKey, Self::Admin];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Anonymous => "anonymous",
+            Self::Github => "github",
+            Self::ApiKey => "api_key",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+impl FromStr for Tier {
+    type Err = FaucetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|tier| tier.as_str() == s)
+            .ok_or_else(|| FaucetError::InvalidTier(s.to_string()))
+    }
+}
+
+/// Overrides of the `[mint]` amounts for one tier; unset fields keep the `[mint]` value. The
+/// request limits apply to each caller of the tier, and unset ones leave it unlimited.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TierConfig {
+    pub default_amount: Option<u64>,
+    pub min_amount: Option<u64>,
+    pub max_amount: Option<u64>,
+    pub clamp: Option<bool>,
+    /// Most mint requests of a caller within `window_secs`.
+    pub max_requests: Option<u32>,
+    pub window_secs: Option<u64>,
+    /// Most mint requests of a caller within a day.
+    pub daily_requests: Option<u32>,
+}
+
+impl TierConfig {
+    /// Checks the request limits of the tier.
+    pub fn validate_limits(&self) -> Result<(), FaucetError> {
+        let problem = match (self.max_requests, self.window_secs, self.daily_requests) {
+            (Some(0), _, _) | (_, Some(0), _) => "needs a positive max_requests and window_secs",
+            (Some(_), None, _) | (None, Some(_), _) => "sets max_requests without window_secs",
+            (_, _, Some(0)) => "needs a positive daily_requests",
+            _ => return Ok(()),
+        };
+        Err(FaucetError::Config(problem.into()))
+    }
+
+    /// Amounts of the tier on top of `base`.
+    pub fn amounts(&self, base: &AmountConfig) -> AmountConfig {
+        AmountConfig {
+            default_amount: self.default_amount.unwrap_or(base.default_amount),
+            min_amount: self.min_amount.unwrap_or(base.min_amount),
+            max_amount: self.max_amount.unwrap_or(base.max_amount),
+            clamp: self.clamp.unwrap_or(base.clamp),
+        }
+    }
+}
+
+/// Per-tier amounts, read from the `[tiers]` section of the configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TiersConfig {
+    pub anonymous: TierConfig,
+    pub github: TierConfig,
+    pub api_key: TierConfig,
+    pub admin: TierConfig,
+}
+
+impl TiersConfig {
+    pub fn get(&self, tier: Tier) -> &TierConfig {
+        match tier {
+            Tier::Anonymous => &self.anonymous,
+            Tier::Github => &self.github,
+            Tier::ApiKey => &self.api_key,
+            Tier::Admin => &self.admin,
+        }
+    }
+
+    pub fn get_mut(&mut self, tier: Tier) -> &mut TierConfig {
+        match tier {
+            Tier::Anonymous => &mut self.anonymous,
+            Tier::Github => &mut self.github,
+            Tier::ApiKey => &mut self.api_key,
+            Tier::Admin => &mut self.admin,
+        }
+    }
+
+    /// Checks the amounts every tier ends up with on top of `base`.
+    pub fn validate(&self, base: &AmountConfig) -> Result<(), FaucetError> {
+        for tier in Tier::ALL {
+            let config = self.get(tier);
+            config
+                .amounts(base)
+                .validate()
+                .and_then(|()| config.validate_limits())
+                .map_err(|err| match err {
+                    FaucetError::Config(message) => {
+                        FaucetError::Config(format!("tiers.{}: {message}", tier.as_str()))
+                    }
+                    err => err,
+                })?;
+        }
+        Ok(())
+    }
+}
+
+/// API key, read from an `[[api_keys]]` entry of the configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyConfig {
+    /// Recorded as the requester of the key's mints, e.g. `ci`.
+    pub name: String,
+    pub key: String,
+    /// Shorthand for `role = "admin"`.
+    #[serde(default)]
+    pub admin: bool,
+    /// Operations the key may run, see [`crate::authz`]; none but minting when unset. Keys with
+    /// the `admin` role mint in the `admin` tier, others in `api_key`.
+    #[serde(default)]
+    pub role: Option<Role>,
+}
+
+impl ApiKeyConfig {
+    pub fn role(&self) -> Option<Role> {
+        self.role.or(self.admin.then_some(Role::Admin))
+    }
+}
+
+/// Checks that API keys are set and that names and keys are unique.
+pub fn validate_api_keys(api_keys: &[ApiKeyConfig]) -> Result<(), FaucetError> {
+    for (index, api_key) in api_keys.iter().enumerate() {
+        if api_key.name.is_empty() || api_key.key.is_empty() {
+            return Err(FaucetError::Config(
+                "api_keys entries need a name and a key".into(),
+            ));
+        }
+        let others = &api_keys[..index];
+        if others.iter().any(|other| other.name == api_key.name) {
+            return Err(FaucetError::Config(format!(
+                "api key name `{}` is used twice",
+                api_key.name
+            )));
+        }
+        if api_key.admin && api_key.role.is_some_and(|role| role != Role::Admin) {
+            return Err(FaucetError::Config(format!(
+                "api key `{}` sets `admin = true` and another role",
+                api_key.name
+            )));
+        }
+        if others.iter().any(|other| other.key == api_key.key) {
+            return Err(FaucetError::Config(format!(
+                "api keys `{}` and another entry share a key",
+                api_key.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Authenticated requester of a mint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Caller {
+    Anonymous,
+    ApiKey { name: String, role: Option<Role> },
+    Github(GithubUser),
+}
+
+impl Caller {
+    pub fn tier(&self) -> Tier {
+        match self {
+            Self::Anonymous => Tier::Anonymous,
+            Self::ApiKey {
+                role: Some(Role::Admin),
+                ..
+            } => Tier::Admin,
+            Self::ApiKey { .. } => Tier::ApiKey,
+            Self::Github(_) => Tier::Github,
+        }
+    }
+
+    /// Role of the caller, only API keys have one.
+    pub fn role(&self) -> Option<Role> {
+        match self {
+            Self::ApiKey { role, .. } => *role,
+            _ => None,
+        }
+    }
+
+    /// Name the caller's mints are audited and queued under, `None` for anonymous callers.
+    pub fn identity(&self) -> Option<String> {
+        match self {
+            Self::Anonymous => None,
+            Self::ApiKey { name, .. } => Some(format!("api_key:{name}")),
+            Self::Github(user) => Some(format!("github:{}", user.login)),
+        }
+    }
+}
+
+/// Authenticates mint requests and resolves the amounts of their tier.
+pub struct Access {
+    amounts: AmountConfig,
+    /// Overrides of the configuration, see [`Self::reset_tier`].
+    configured: TiersConfig,
+    tiers: RwLock<TiersConfig>,
+    api_keys: Vec<ApiKeyConfig>,
+    github: Option<GithubAuth>,
+    /// Keeps adjusted tiers across restarts, see [`Self::with_ledger`].
+    ledger: Option<Mutex<Ledger>>,
+    /// Times of the requests of the last day admitted per caller, see [`Self::admit`].
+    requests: Mutex<HashMap<String, Vec<u64>>>,
+}
+
+impl Access {
+    pub fn new(
+        amounts: AmountConfig,
+        tiers: TiersConfig,
+        api_keys: Vec<ApiKeyConfig>,
+        github: Option<GithubAuth>,
+    ) -> Self {
+        Self {
+            amounts,
+            configured: tiers.clone(),
+            tiers: RwLock::new(tiers),
+            api_keys,
+            github,
+            ledger: None,
+            requests: Mutex::default(),
+        }
+    }
+
+    /// Keeps the tiers adjusted with [`set_tier`](Self::set_tier) in `ledger` and restores those
+    /// adjusted before a restart.
+    pub fn with_ledger(mut self, ledger: Ledger) -> Result<Self, FaucetError> {
+        for tier in Tier::ALL {
+            let Some(value) = ledger.limit_override(&tier_override(tier))? else {
+                continue;
+            };
+            match serde_json::from_str::<TierConfig>(&value) {
+                Ok(config)
+                    if config.amounts(&self.amounts).validate().is_ok()
+                        && config.validate_limits().is_ok() =>
+                {
+                    *self
+                        .tiers
+                        .get_mut()
+                        .expect("tier lock poisoned")
+                        .get_mut(tier) = config;
+                }
+                _ => eprintln!(
+                    "Ignoring the invalid adjusted amounts `{value}` of tier {} in the ledger",
+                    tier.as_str()
+                ),
+            }
+        }
+        self.ledger = Some(Mutex::new(ledger));
+        Ok(self)
+    }
+
+    pub fn github(&self) -> Option<&GithubAuth> {
+        self.github.as_ref()
+    }
+
+    pub fn api_keys(&self) -> &[ApiKeyConfig] {
+        &self.api_keys
+    }
+
+    /// Caller presenting `authorization`, the value of the `Authorization` header if any.
+    ///
+    /// API keys are checked first, then GitHub sessions if sign-in is configured, in which case
+    /// requests without credentials are refused.
+    pub fn authenticate(&self, authorization: Option<&str>) -> Result<Caller, FaucetError> {
+        let token = authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim);
+        if let Some(api_key) = token.and_then(|token| find_api_key(&self.api_keys, token)) {
+            return Ok(Caller::ApiKey {
+                name: api_key.name.clone(),
+                role: api_key.role(),
+            });
+        }
+        match (&self.github, token) {
+            (Some(github), _) => Ok(Caller::Github(github.authenticate(authorization)?)),
+            (None, Some(_)) => Err(FaucetError::Unauthorized("unknown API key".into())),
+            (None, None) => Ok(Caller::Anonymous),
+        }
+    }
+
+    /// Admits `requests` mint requests of `caller` from `ip` under the request limits of its tier.
+    ///
+    /// Callers are told apart by their identity, anonymous ones by their address. Fails with
+    /// [`FaucetError::RateLimited`] if the requests would exceed a limit, in which case none of
+    /// them count.
+    pub fn admit(
+        &self,
+        caller: &Caller,
+        ip: Option<IpAddr>,
+        requests: usize,
+    ) -> Result<(), FaucetError> {
+        let config = self.tiers().get(caller.tier()).clone();
+        let mut limits = Vec::new();
+        if let (Some(max_requests), Some(window_secs)) = (config.max_requests, config.window_secs) {
+            limits.push((max_requests, window_secs));
+        }
+        if let Some(daily_requests) = config.daily_requests {
+            limits.push((daily_requests, DAY_SECS));
+        }
+        if limits.is_empty() {
+            return Ok(());
+        }
+
+        let key = caller
+            .identity()
+            .or_else(|| ip.map(|ip| format!("ip:{ip}")))
+            .unwrap_or_else(|| "anonymous".into());
+        let now = unix_now();
+        let mut admitted = self.requests.lock().expect("request lock poisoned");
+        let times = admitted.entry(key).or_default();
+        times.retain(|&time| time + DAY_SECS > now);
+        for (max_requests, window_secs) in limits {
+            let recent = times.iter().filter(|&&time| time + window_secs > now);
+            if recent.clone().count() + requests > max_requests as usize {
+                // A request is available again once the oldest one leaves the window.
+                let oldest = recent.min().copied().unwrap_or(now);
+                let retry_after_secs = (oldest + window_secs).saturating_sub(now);
+                return Err(FaucetError::RateLimited { retry_after_secs });
+            }
+        }
+        times.extend(std::iter::repeat_n(now, requests));
+        Ok(())
+    }
+
+    /// Amounts granted to `tier`.
+    pub fn amounts(&self, tier: Tier) -> AmountConfig {
+        self.tiers().get(tier).amounts(&self.amounts)
+    }
+
+    /// Overrides of every tier, including those set with [`set_tier`](Self::set_tier).
+    pub fn tiers(&self) -> TiersConfig {
+        self.tiers.read().expect("tier lock poisoned").clone()
+    }
+
+    /// Replaces the overrides of `tier`, also across restarts with a ledger, and returns the
+    /// amounts the tier ends up with.
+    pub fn set_tier(&self, tier: Tier, config: TierConfig) -> Result<AmountConfig, FaucetError> {
+        let amounts = config.amounts(&self.amounts);
+        amounts
+            .validate()
+            .and_then(|()| config.validate_limits())
+            .map_err(|err| match err {
+                FaucetError::Config(message) => {
+                    FaucetError::Config(format!("tiers.{}: {message}", tier.as_str()))
+                }
+                err => err,
+            })?;
+        if let Some(ledger) = &self.ledger {
+            let value = serde_json::to_string(&config).expect("tier overrides serialize");
+            ledger
+                .lock()
+                .expect("ledger lock poisoned")
+                .set_limit_override(&tier_override(tier), &value)?;
+        }
+        *self
+            .tiers
+            .write()
+            .expect("tier lock poisoned")
+            .get_mut(tier) = config;
+        Ok(amounts)
+    }
+
+    /// Goes back to the overrides of `tier` in the configuration and returns the amounts the tier
+    /// ends up with.
+    pub fn reset_tier(&self, tier: Tier) -> Result<AmountConfig, FaucetError> {
+        if let Some(ledger) = &self.ledger {
+            ledger
+                .lock()
+                .expect("ledger lock poisoned")
+                .remove_limit_override(&tier_override(tier))?;
+        }
+        let config = self.configured.get(tier).clone();
+        let amounts = config.amounts(&self.amounts);
+        *self
+            .tiers
+            .write()
+            .expect("tier lock poisoned")
+            .get_mut(tier) = config;
+        Ok(amounts)
+    }
+}
+
+/// Name of the adjusted amounts of `tier` among the limit overrides of the [`Ledger`].
+fn tier_override(tier: Tier) -> String {
+    format!("tiers.{}", tier.as_str())
+}
+
+/// List of accounts and requesters the mint service refuses or restricts itself to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessList {
+    /// Once not empty, only entries of the list are served.
+    Allow,
+    /// Entries of the list are refused.
+    Deny,
+}
+
+impl AccessList {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+        }
+    }
+}
+
+impl fmt::Display for AccessList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AccessList {
+    type Err = FaucetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(Self::Allow),
+            "deny" => Ok(Self::Deny),
+            other => Err(FaucetError::InvalidAccessList(
+                other.to_string(),
+                "lists are `allow` and `deny`".into(),
+            )),
+        }
+    }
+}
+
+impl FromSql for AccessList {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|err| FromSqlError::Other(Box::new(err)))
+    }
+}
+
+/// Normalizes a subject of an access list: a recipient account ID, or a requester identity as
+/// returned by [`Caller::identity`], e.g. `github:octocat` or `api_key:ci`.
+pub fn parse_list_subject(input: &str) -> Result<String, FaucetError> {
+    let input = input.trim();
+    match input.split_once(':') {
+        Some(("github" | "api_key", name)) if !name.is_empty() => Ok(input.to_string()),
+        Some(_) => Err(FaucetError::InvalidAccessList(
+            input.to_string(),
+            "identities start with `github:` or `api_key:`".into(),
+        )),
+        None => Ok(parse_account_id(input, None)?.to_hex()),
+    }
+}
+
+/// Checks a mint to `recipient` requested by `identity` against the access lists of `ledger`.
+///
+/// Fails with [`FaucetError::AccessDenied`] if either is on the deny list, or if the allow list
+/// has entries and neither is on it.
+pub fn check_access_lists(
+    ledger: &Ledger,
+    identity: &str,
+    recipient: AccountId,
+) -> Result<(), FaucetError> {
+    let recipient_hex = recipient.to_hex();
+    let subjects = [recipient_hex.as_str(), identity];
+    if ledger.is_listed(AccessList::Deny, &subjects)? {
+        return Err(FaucetError::AccessDenied(format!(
+            "{recipient} or {identity} is on the deny list"
+        )));
+    }
+    if ledger.list_len(AccessList::Allow)? > 0 && !ledger.is_listed(AccessList::Allow, &subjects)? {
+        return Err(FaucetError::AccessDenied(format!(
+            "neither {recipient} nor {identity} is on the allow list"
+        )));
+    }
+    Ok(())
+}
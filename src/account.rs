@@ -0,0 +1,84 @@
+//! Account IDs supplied by users.
+//!
+//! IDs are accepted in hex and in the bech32 form wallets display, e.g. `mtst1...`, and handled as
+//! [`AccountId`]s from then on, so the ledger and the responses always show them in hex. A bech32
+//! ID names its network, and is refused on another one, e.g. an `mdev1...` ID on testnet.
+//!
+//! Wherever the CLI expects an account ID it also accepts a label attached with `account label`,
+//! e.g. `alice` or `mde-faucet`. Labels are kept in the [`Ledger`].
+
+use miden_client::account::AccountId;
+use miden_objects::address::NetworkId;
+
+use crate::{ledger::Ledger, FaucetError};
+
+/// Parses an account ID supplied by a user, in hex or bech32.
+///
+/// A bech32 ID must carry the prefix of `network`, the network it is used on, and fails with
+/// [`FaucetError::AccountNetworkMismatch`] otherwise. Hex IDs carry no network and are accepted
+/// on any, as are all IDs for `None`.
+pub fn parse_account_id(
+    input: &str,
+    network: Option<&NetworkId>,
+) -> Result<AccountId, FaucetError> {
+    let trimmed = input.trim();
+    if trimmed.starts_with("0x") {
+        return AccountId::from_hex(trimmed)
+            .map_err(|err| FaucetError::InvalidAccountId(input.to_string(), err.to_string()));
+    }
+    let (found, account_id) = AccountId::from_bech32(trimmed).map_err(|err| {
+        FaucetError::InvalidAccountId(
+            input.to_string(),
+            format!("neither a hex nor a bech32 account ID: {err}"),
+        )
+    })?;
+    match network {
+        Some(expected) if *expected != found => Err(FaucetError::AccountNetworkMismatch {
+            input: input.to_string(),
+            expected: expected.clone(),
+            found,
+        }),
+        _ => Ok(account_id),
+    }
+}
+
+/// Parses an account ID used on `network`, or looks up the account a label was attached to.
+pub fn resolve_account_id(
+    ledger: &Ledger,
+    input: &str,
+    network: Option<&NetworkId>,
+) -> Result<AccountId, FaucetError> {
+    let err = match parse_account_id(input, network) {
+        Ok(id) => return Ok(id),
+        // A well-formed ID of another network is not a label either.
+        Err(err @ FaucetError::AccountNetworkMismatch { .. }) => return Err(err),
+        Err(err) => err,
+    };
+    match ledger.labeled_account(input.trim())? {
+        Some(id) => Ok(id),
+        // Report hex-looking input as a malformed ID rather than an unknown label.
+        None if input.trim().starts_with("0x") => Err(err),
+        None => Err(FaucetError::InvalidAccountId(
+            input.to_string(),
+            "neither an account ID nor a known label".into(),
+        )),
+    }
+}
+
+/// Checks that `label` can name an account: it must not be empty, must not look like a hex ID and
+/// may only contain ASCII letters, digits, `-` and `_`.
+pub fn validate_label(label: &str) -> Result<(), FaucetError> {
+    let reason = if label.is_empty() {
+        "labels cannot be empty"
+    } else if label.starts_with("0x") {
+        "labels cannot start with `0x`"
+    } else if !label
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        "labels may only contain ASCII letters, digits, `-` and `_`"
+    } else {
+        return Ok(());
+    };
+    Err(FaucetError::InvalidLabel(label.to_string(), reason.into()))
+}
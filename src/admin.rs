@@ -0,0 +1,624 @@
+//! Admin REST API.
+//!
+//! Routes under `/admin` let operators run the faucet without shell access to its host: pause and
+//! unpause minting, adjust the amounts of the access tiers and the GitHub drip limit, manage the
+//! allow and deny lists, inspect the mint queue and reclaim expired mints. They require an API key
+//! whose role allows the operation, see [`crate::authz`], and every change is recorded in the
+//! audit log under the key, e.g. `api_key:ops`.
+//!
+//! Adjusted amounts and drip limits are kept in the ledger, so a restart does not undo them, until
+//! they are reset to the configured ones with `DELETE`; the configuration file is left untouched.
+//! Pauses and access lists are kept on chain and in the ledger respectively, so they persist too.
+//!
+//! Pauses, access lists, the queue and reclaims belong to the network named by the `network`
+//! query parameter, the default one when omitted; tiers and drip limits apply to every network.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    routing::{get, post, put},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    access::{parse_list_subject, Access, AccessList, Tier, TierConfig},
+    audit::AuditEntry,
+    authz::{authorize, Action},
+    github::DripLimit,
+    ledger::ListEntry,
+    mint::AmountConfig,
+    network::Networks,
+    rest::{ApiError, ErrorResponse, NetworkQuery},
+    service::FaucetHandle,
+    FaucetError,
+};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PauseResponse {
+    /// Transaction of the owner sending the PAUSE note that sets the pause flag of the faucet.
+    pub transaction_id: String,
+    pub paused: bool,
+}
+
+/// Amounts a tier may mint.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TierAmountsResponse {
+    pub default_amount: u64,
+    pub min_amount: u64,
+    pub max_amount: u64,
+    pub clamp: bool,
+}
+
+/// Overrides of the `[mint]` amounts for one tier, replacing the current ones; unset fields keep
+/// the `[mint]` value.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct TierRequest {
+    pub default_amount: Option<u64>,
+    pub min_amount: Option<u64>,
+    pub max_amount: Option<u64>,
+    pub clamp: Option<bool>,
+}
+
+/// Mints a GitHub account may request within `window_secs`.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DripLimitBody {
+    pub max_drips: u32,
+    pub window_secs: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LimitsResponse {
+    /// Amounts of each tier, by tier name.
+    pub tiers: BTreeMap<String, TierAmountsResponse>,
+    /// Drip limit of GitHub accounts, unset without the `[github]` section.
+    pub drip_limit: Option<DripLimitBody>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ListEntryResponse {
+    /// `allow` or `deny`.
+    pub list: String,
+    /// Recipient account ID, or requester identity such as `github:octocat` or `api_key:ci`.
+    pub subject: String,
+    pub note: Option<String>,
+    pub created_at: u64,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListEntryRequest {
+    /// Why the subject is listed.
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QueuedIdentityResponse {
+    pub identity: String,
+    /// Queued mints of the identity, a batch counting as one.
+    pub mints: usize,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QueueResponse {
+    pub len: usize,
+    pub capacity: usize,
+    /// Identities in the order they are served.
+    pub identities: Vec<QueuedIdentityResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReclaimFailureResponse {
+    pub mint_id: i64,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReclaimResponse {
+    /// IDs of the reclaimed mints.
+    pub reclaimed: Vec<i64>,
+    /// Tokens recovered by the reclaimed mints.
+    pub reclaimed_amount: u64,
+    pub failed: Vec<ReclaimFailureResponse>,
+}
+
+/// Shared state of the admin route handlers.
+#[derive(Clone)]
+pub(crate) struct AdminState {
+    networks: Networks,
+    access: Arc<Access>,
+}
+
+impl AdminState {
+    /// Handle of the faucet of the network and token `query` names.
+    fn handle(&self, query: &NetworkQuery) -> Result<&FaucetHandle, FaucetError> {
+        self.networks
+            .get(query.network.as_deref())?
+            .faucet(query.token.as_deref())
+    }
+}
+
+/// Builds the router of the admin API on `networks`, checking the admin keys of `access`.
+pub fn router(networks: Networks, access: Arc<Access>) -> Router {
+    Router::new()
+        .route("/admin/pause", post(pause))
+        .route("/admin/unpause", post(unpause))
+        .route("/admin/limits", get(limits))
+        .route("/admin/tiers/{tier}", put(set_tier).delete(reset_tier))
+        .route(
+            "/admin/drip-limit",
+            put(set_drip_limit).delete(reset_drip_limit),
+        )
+        .route("/admin/lists", get(list_entries))
+        .route(
+            "/admin/lists/{list}/{subject}",
+            put(add_list_entry).delete(remove_list_entry),
+        )
+        .route("/admin/queue", get(queue))
+        .route("/admin/reclaim", post(reclaim))
+        .with_state(AdminState { networks, access })
+}
+
+/// Pauses minting: sends the faucet a PAUSE note setting its pause flag, like `faucet pause`.
+#[utoipa::path(
+    post,
+    path = "/admin/pause",
+    params(NetworkQuery),
+    responses(
+        (status = 200, body = PauseResponse),
+        (status = 400, description = "Unknown network", body = ErrorResponse),
+        (status = 401, description = "No API key", body = ErrorResponse),
+        (status = 403, description = "The role of the API key does not allow it", body = ErrorResponse),
+        (status = 503, description = "Node unreachable", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn pause(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Json<PauseResponse>, ApiError> {
+    toggle_pause(&state, &headers, &query, true).await
+}
+
+/// Resumes minting, like `faucet unpause`.
+#[utoipa::path(
+    post,
+    path = "/admin/unpause",
+    params(NetworkQuery),
+    responses(
+        (status = 200, body = PauseResponse),
+        (status = 400, description = "Unknown network", body = ErrorResponse),
+        (status = 401, description = "No API key", body = ErrorResponse),
+        (status = 403, description = "The role of the API key does not allow it", body = ErrorResponse),
+        (status = 503, description = "Node unreachable", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn unpause(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Json<PauseResponse>, ApiError> {
+    toggle_pause(&state, &headers, &query, false).await
+}
+
+async fn toggle_pause(
+    state: &AdminState,
+    headers: &HeaderMap,
+    query: &NetworkQuery,
+    paused: bool,
+) -> Result<Json<PauseResponse>, ApiError> {
+    let actor = authorize_admin(state, headers, Action::Pause)?;
+    let handle = state.handle(query)?.with_actor(actor);
+    let transaction_id = handle.set_paused(paused).await?;
+    Ok(Json(PauseResponse {
+        transaction_id: transaction_id.to_hex(),
+        paused,
+    }))
+}
+
+/// Returns the amounts of every tier and the GitHub drip limit in effect.
+#[utoipa::path(
+    get,
+    path = "/admin/limits",
+    responses(
+        (status = 200, body = LimitsResponse),
+        (status = 401, description = "No API key", body = ErrorResponse),
+        (status = 403, description = "The role of the API key does not allow it", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn limits(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+) -> Result<Json<LimitsResponse>, ApiError> {
+    authorize_admin(&state, &headers, Action::View)?;
+    let tiers = Tier::ALL
+        .into_iter()
+        .map(|tier| {
+            (
+                tier.as_str().to_string(),
+                TierAmountsResponse::from(state.access.amounts(tier)),
+            )
+        })
+        .collect();
+    let drip_limit = state
+        .access
+        .github()
+        .map(|github| DripLimitBody::from(github.drip_limit()));
+    Ok(Json(LimitsResponse { tiers, drip_limit }))
+}
+
+/// Replaces the amount overrides of a tier, also across restarts.
+#[utoipa::path(
+    put,
+    path = "/admin/tiers/{tier}",
+    params(("tier" = String, Path, description = "`anonymous`, `github`, `api_key` or `admin`")),
+    request_body = TierRequest,
+    responses(
+        (status = 200, description = "Amounts the tier ends up with", body = TierAmountsResponse),
+        (status = 400, description = "Unknown tier or inconsistent amounts", body = ErrorResponse),
+        (status = 401, description = "No API key", body = ErrorResponse),
+        (status = 403, description = "The role of the API key does not allow it", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn set_tier(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    Path(tier): Path<String>,
+    Json(request): Json<TierRequest>,
+) -> Result<Json<TierAmountsResponse>, ApiError> {
+    let actor = authorize_admin(&state, &headers, Action::SetLimits)?;
+    let tier: Tier = tier.parse()?;
+    // The request limits of the tier stay as they are.
+    let config = TierConfig {
+        default_amount: request.default_amount,
+        min_amount: request.min_amount,
+        max_amount: request.max_amount,
+        clamp: request.clamp,
+        ..state.access.tiers().get(tier).clone()
+    };
+    let amounts = state
+        .access
+        .set_tier(tier, config)
+        .map_err(|err| ApiError(StatusCode::BAD_REQUEST, err.to_string()))?;
+    state
+        .networks
+        .default_handle()
+        .audit(
+            AuditEntry::new(actor, "admin.tier")
+                .param("tier", tier.as_str())
+                .param("default_amount", amounts.default_amount)
+                .param("min_amount", amounts.min_amount)
+                .param("max_amount", amounts.max_amount)
+                .param("clamp", amounts.clamp),
+        )
+        .await?;
+    Ok(Json(amounts.into()))
+}
+
+/// Goes back to the amount overrides of a tier in the configuration.
+#[utoipa::path(
+    delete,
+    path = "/admin/tiers/{tier}",
+    params(("tier" = String, Path, description = "`anonymous`, `github`, `api_key` or `admin`")),
+    responses(
+        (status = 200, description = "Amounts the tier ends up with", body = TierAmountsResponse),
+        (status = 400, description = "Unknown tier", body = ErrorResponse),
+        (status = 401, description = "No API key", body = ErrorResponse),
+        (status = 403, description = "The role of the API key does not allow it", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn reset_tier(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    Path(tier): Path<String>,
+) -> Result<Json<TierAmountsResponse>, ApiError> {
+    let actor = authorize_admin(&state, &headers, Action::SetLimits)?;
+    let tier: Tier = tier.parse()?;
+    let amounts = state.access.reset_tier(tier)?;
+    state
+        .networks
+        .default_handle()
+        .audit(AuditEntry::new(actor, "admin.tier_reset").param("tier", tier.as_str()))
+        .await?;
+    Ok(Json(amounts.into()))
+}
+
+/// Replaces the drip limit of GitHub accounts, also across restarts.
+#[utoipa::path(
+    put,
+    path = "/admin/drip-limit",
+    request_body = DripLimitBody,
+    responses(
+        (status = 200, body = DripLimitBody),
+        (status = 400, description = "Zero limit or GitHub sign-in not configured", body = ErrorResponse),
+        (status = 401, description = "No API key", body = ErrorResponse),
+        (status = 403, description = "The role of the API key does not allow it", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn set_drip_limit(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    Json(request): Json<DripLimitBody>,
+) -> Result<Json<DripLimitBody>, ApiError> {
+    let actor = authorize_admin(&state, &headers, Action::SetLimits)?;
+    let Some(github) = state.access.github() else {
+        return Err(ApiError(
+            StatusCode::BAD_REQUEST,
+            "GitHub sign-in is not configured, see the [github] section".into(),
+        ));
+    };
+    let limit = DripLimit {
+        max_drips: request.max_drips,
+        window_secs: request.window_secs,
+    };
+    github
+        .set_drip_limit(limit)
+        .map_err(|err| ApiError(StatusCode::BAD_REQUEST, err.to_string()))?;
+    state
+        .networks
+        .default_handle()
+        .audit(
+            AuditEntry::new(actor, "admin.drip_limit")
+                .param("max_drips", limit.max_drips)
+                .param("window_secs", limit.window_secs),
+        )
+        .await?;
+    Ok(Json(limit.into()))
+}
+
+/// Goes back to the drip limit of GitHub accounts in the configuration.
+#[utoipa::path(
+    delete,
+    path = "/admin/drip-limit",
+    responses(
+        (status = 200, body = DripLimitBody),
+        (status = 400, description = "GitHub sign-in not configured", body = ErrorResponse),
+        (status = 401, description = "No API key", body = ErrorResponse),
+        (status = 403, description = "The role of the API key does not allow it", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn reset_drip_limit(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+) -> Result<Json<DripLimitBody>, ApiError> {
+    let actor = authorize_admin(&state, &headers, Action::SetLimits)?;
+    let Some(github) = state.access.github() else {
+        return Err(ApiError(
+            StatusCode::BAD_REQUEST,
+            "GitHub sign-in is not configured, see the [github] section".into(),
+        ));
+    };
+    let limit = github.reset_drip_limit()?;
+    state
+        .networks
+        .default_handle()
+        .audit(
+            AuditEntry::new(actor, "admin.drip_limit_reset")
+                .param("max_drips", limit.max_drips)
+                .param("window_secs", limit.window_secs),
+        )
+        .await?;
+    Ok(Json(limit.into()))
+}
+
+/// Returns the entries of the allow and deny lists.
+#[utoipa::path(
+    get,
+    path = "/admin/lists",
+    params(NetworkQuery),
+    responses(
+        (status = 200, body = [ListEntryResponse]),
+        (status = 400, description = "Unknown network", body = ErrorResponse),
+        (status = 401, description = "No API key", body = ErrorResponse),
+        (status = 403, description = "The role of the API key does not allow it", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn list_entries(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Json<Vec<ListEntryResponse>>, ApiError> {
+    let actor = authorize_admin(&state, &headers, Action::View)?;
+    let handle = state.handle(&query)?.with_actor(actor);
+    let entries = handle.list_entries().await?;
+    Ok(Json(
+        entries.into_iter().map(ListEntryResponse::from).collect(),
+    ))
+}
+
+/// Puts a recipient account ID or requester identity on the allow or deny list.
+///
+/// Once the allow list has entries, only mints to or by its entries are served.
+#[utoipa::path(
+    put,
+    path = "/admin/lists/{list}/{subject}",
+    params(
+        ("list" = String, Path, description = "`allow` or `deny`"),
+        ("subject" = String, Path, description = "Account ID, or identity such as `github:octocat`"),
+        NetworkQuery,
+    ),
+    request_body = ListEntryRequest,
+    responses(
+        (status = 200, body = ListEntryResponse),
+        (status = 400, description = "Unknown list or network, or invalid subject", body = ErrorResponse),
+        (status = 401, description = "No API key", body = ErrorResponse),
+        (status = 403, description = "The role of the API key does not allow it", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn add_list_entry(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    Query(query): Query<NetworkQuery>,
+    Path((list, subject)): Path<(String, String)>,
+    Json(request): Json<ListEntryRequest>,
+) -> Result<Json<ListEntryResponse>, ApiError> {
+    let actor = authorize_admin(&state, &headers, Action::EditAccessLists)?;
+    let handle = state.handle(&query)?.with_actor(actor);
+    let list: AccessList = list.parse()?;
+    let entry = handle
+        .add_list_entry(list, parse_list_subject(&subject)?, request.note)
+        .await?;
+    Ok(Json(entry.into()))
+}
+
+/// Takes a subject off the allow or deny list.
+#[utoipa::path(
+    delete,
+    path = "/admin/lists/{list}/{subject}",
+    params(
+        ("list" = String, Path, description = "`allow` or `deny`"),
+        ("subject" = String, Path, description = "Account ID, or identity such as `github:octocat`"),
+        NetworkQuery,
+    ),
+    responses(
+        (status = 204, description = "Removed"),
+        (status = 400, description = "Unknown list or network, or invalid subject", body = ErrorResponse),
+        (status = 401, description = "No API key", body = ErrorResponse),
+        (status = 403, description = "The role of the API key does not allow it", body = ErrorResponse),
+        (status = 404, description = "Not on the list", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn remove_list_entry(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    Query(query): Query<NetworkQuery>,
+    Path((list, subject)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    let actor = authorize_admin(&state, &headers, Action::EditAccessLists)?;
+    let handle = state.handle(&query)?.with_actor(actor);
+    let list: AccessList = list.parse()?;
+    let subject = parse_list_subject(&subject)?;
+    if !handle.remove_list_entry(list, subject.clone()).await? {
+        return Err(ApiError(
+            StatusCode::NOT_FOUND,
+            format!("{subject} is not on the {list} list"),
+        ));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Returns the mints waiting in the queue of the service, by requester identity.
+#[utoipa::path(
+    get,
+    path = "/admin/queue",
+    params(NetworkQuery),
+    responses(
+        (status = 200, body = QueueResponse),
+        (status = 400, description = "Unknown network", body = ErrorResponse),
+        (status = 401, description = "No API key", body = ErrorResponse),
+        (status = 403, description = "The role of the API key does not allow it", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn queue(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Json<QueueResponse>, ApiError> {
+    let actor = authorize_admin(&state, &headers, Action::View)?;
+    let handle = state.handle(&query)?.with_actor(actor);
+    let queue = handle.queue().await?;
+    Ok(Json(QueueResponse {
+        len: queue.len,
+        capacity: queue.capacity,
+        identities: queue
+            .identities
+            .into_iter()
+            .map(|(identity, mints)| QueuedIdentityResponse { identity, mints })
+            .collect(),
+    }))
+}
+
+/// Reclaims every expired unclaimed mint, like `faucet reclaim`, and returns once the reclaims
+/// are committed.
+#[utoipa::path(
+    post,
+    path = "/admin/reclaim",
+    params(NetworkQuery),
+    responses(
+        (status = 200, body = ReclaimResponse),
+        (status = 400, description = "Unknown network", body = ErrorResponse),
+        (status = 401, description = "No API key", body = ErrorResponse),
+        (status = 403, description = "The role of the API key does not allow it", body = ErrorResponse),
+        (status = 503, description = "Node unreachable", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn reclaim(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Json<ReclaimResponse>, ApiError> {
+    let actor = authorize_admin(&state, &headers, Action::Reclaim)?;
+    let handle = state.handle(&query)?.with_actor(actor);
+    let report = handle.reclaim().await?;
+    Ok(Json(ReclaimResponse {
+        reclaimed: report.reclaimed.iter().map(|mint| mint.id).collect(),
+        reclaimed_amount: report.reclaimed_amount,
+        failed: report
+            .failed
+            .into_iter()
+            .map(|(mint, err)| ReclaimFailureResponse {
+                mint_id: mint.id,
+                error: err.to_string(),
+            })
+            .collect(),
+    }))
+}
+
+/// Authenticates an admin request for `action` and returns the identity of its API key, e.g.
+/// `api_key:ops`.
+///
+/// Fails with [`FaucetError::Unauthorized`] without credentials and [`FaucetError::AccessDenied`]
+/// for callers whose role does not allow `action`, see [`authorize`].
+fn authorize_admin(
+    state: &AdminState,
+    headers: &HeaderMap,
+    action: Action,
+) -> Result<String, FaucetError> {
+    let authorization = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    let caller = state.access.authenticate(authorization)?;
+    let Some(identity) = caller.identity() else {
+        return Err(FaucetError::Unauthorized(
+            "the admin API requires an API key with a role".into(),
+        ));
+    };
+    authorize(&identity, caller.role(), action)?;
+    Ok(identity)
+}
+
+impl From<AmountConfig> for TierAmountsResponse {
+    fn from(amounts: AmountConfig) -> Self {
+        Self {
+            default_amount: amounts.default_amount,
+            min_amount: amounts.min_amount,
+            max_amount: amounts.max_amount,
+            clamp: amounts.clamp,
+        }
+    }
+}
+
+impl From<DripLimit> for DripLimitBody {
+    fn from(limit: DripLimit) -> Self {
+        Self {
+            max_drips: limit.max_drips,
+            window_secs: limit.window_secs,
+        }
+    }
+}
+
+impl From<ListEntry> for ListEntryResponse {
+    fn from(entry: ListEntry) -> Self {
+        Self {
+            list: entry.list.to_string(),
+            subject: entry.subject,
+            note: entry.note,
+            created_at: entry.created_at,
+        }
+    }
+}
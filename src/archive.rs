@@ -0,0 +1,394 @@
+//! Snapshots of the files of a faucet host, to move or rebuild it.
+//!
+//! [`create_snapshot`] bundles what a host cannot get back from the chain into one versioned
+//! archive: a consistent copy of the client store and of the [`Ledger`], which holds the registry
+//! of deployed faucets, the audit log and the mints still waiting for their transaction. The keys
+//! of the keystore are only referenced by file name and digest, so archives can be kept where
+//! keys must not be, e.g. when the keys are managed outside the host; `--include-keys` embeds the
+//! key files for hosts whose keystore is their only copy.
+//!
+//! [`restore_snapshot`] checks every digest before writing anything, restores the files for the
+//! configured network and lists the referenced keys the keystore lacks, to be restored with
+//! `recover` or from wherever the keys are managed.
+//!
+//! An archive is [`SNAPSHOT_MAGIC`], the length of the JSON [`SnapshotManifest`] as a
+//! little-endian `u64`, the manifest and the contents of its entries in manifest order.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use miden_crypto::hash::rpo::Rpo256;
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::Config,
+    ledger::{unix_now, Ledger},
+    network::claim_for_network,
+    FaucetError,
+};
+
+/// Version of the archive format written by this build.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// First bytes of every archive.
+pub const SNAPSHOT_MAGIC: &[u8; 8] = b"NFSNAP\r\n";
+
+/// Entry holding the client store.
+pub const STORE_ENTRY: &str = "store.sqlite3";
+
+/// Entry holding the ledger.
+pub const LEDGER_ENTRY: &str = "ledger.sqlite3";
+
+/// Prefix of the entries holding key files, followed by the name of the file.
+pub const KEY_ENTRY_PREFIX: &str = "keystore/";
+
+/// Description of an archive, stored in front of its entries.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub version: u32,
+    pub created_at: u64,
+    /// Network the store and keystore belong to.
+    pub network: String,
+    /// Schema version of the archived ledger.
+    pub ledger_schema: u32,
+    /// Ledger IDs of the mints submitted but not committed or failed yet.
+    pub pending_mints: Vec<i64>,
+    pub entries: Vec<SnapshotEntry>,
+    /// Key files of the keystore, also archived as entries if the snapshot includes keys.
+    pub keys: Vec<SnapshotEntry>,
+}
+
+impl SnapshotManifest {
+    /// Whether the key files are archived, not only referenced.
+    pub fn includes_keys(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.name.starts_with(KEY_ENTRY_PREFIX))
+    }
+}
+
+/// File of a [`SnapshotManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    pub name: String,
+    pub size: u64,
+    /// RPO digest of the contents, as hex.
+    pub digest: String,
+}
+
+impl SnapshotEntry {
+    fn new(name: impl Into<String>, contents: &[u8]) -> Self {
+        Self {
+            name: name.into(),
+            size: contents.len() as u64,
+            digest: digest(contents),
+        }
+    }
+}
+
+/// Archive read and verified by [`Snapshot::read`].
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub manifest: SnapshotManifest,
+    contents: BTreeMap<String, Vec<u8>>,
+}
+
+impl Snapshot {
+    /// Reads the archive at `path` and checks its version and the digest of every entry.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, FaucetError> {
+        let bytes = fs::read(path)?;
+        let rest = bytes
+            .strip_prefix(SNAPSHOT_MAGIC.as_slice())
+            .ok_or_else(|| FaucetError::Snapshot("not a faucet snapshot".into()))?;
+        let (len, rest) = split(rest, 8)?;
+        let len = u64::from_le_bytes(len.try_into().expect("8 bytes"));
+        let (manifest, mut rest) = split(rest, len)?;
+        let manifest: SnapshotManifest = serde_json::from_slice(manifest)
+            .map_err(|err| FaucetError::Snapshot(format!("invalid manifest: {err}")))?;
+        if manifest.version > SNAPSHOT_VERSION {
+            return Err(FaucetError::Snapshot(format!(
+                "snapshot version {} is newer than version {SNAPSHOT_VERSION} of this build",
+                manifest.version
+            )));
+        }
+
+        let mut contents = BTreeMap::new();
+        for entry in &manifest.entries {
+            let (data, tail) = split(rest, entry.size)?;
+            if digest(data) != entry.digest {
+                return Err(FaucetError::Snapshot(format!(
+                    "entry `{}` does not match its digest",
+                    entry.name
+                )));
+            }
+            contents.insert(entry.name.clone(), data.to_vec());
+            rest = tail;
+        }
+        if !rest.is_empty() {
+            return Err(FaucetError::Snapshot(format!(
+                "{} bytes after the last entry",
+                rest.len()
+            )));
+        }
+        for name in [STORE_ENTRY, LEDGER_ENTRY] {
+            if !contents.contains_key(name) {
+                return Err(FaucetError::Snapshot(format!("entry `{name}` is missing")));
+            }
+        }
+        for key in &manifest.keys {
+            check_key_name(&key.name)?;
+        }
+
+        Ok(Self { manifest, contents })
+    }
+
+    /// Contents of entry `name`.
+    pub fn entry(&self, name: &str) -> Option<&[u8]> {
+        self.contents.get(name).map(Vec::as_slice)
+    }
+}
+
+/// Outcome of [`restore_snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoreReport {
+    pub manifest: SnapshotManifest,
+    /// Key files written from the archive.
+    pub restored_keys: Vec<String>,
+    /// Referenced key files the keystore lacks or holds with other contents.
+    pub missing_keys: Vec<String>,
+}
+
+/// Writes a snapshot of the store, ledger and keystore of `config` to a new file at `path`,
+/// readable by its owner only, embedding the key files if `include_keys` is set.
+///
+/// The store and ledger are copied with `VACUUM INTO`, so the snapshot is consistent even while
+/// `serve` is running.
+pub fn create_snapshot(
+    config: &Config,
+    path: impl AsRef<Path>,
+    include_keys: bool,
+) -> Result<SnapshotManifest, FaucetError> {
+    let path = path.as_ref();
+    if !config.store_path.exists() {
+        return Err(FaucetError::Snapshot(format!(
+            "no client store at `{}`",
+            config.store_path.display()
+        )));
+    }
+    let network = config.rpc.endpoint.network_name();
+    claim_for_network(&config.store_path, &network)?;
+
+    let store = with_staging(path, STORE_ENTRY, |staging| {
+        let conn =
+            Connection::open_with_flags(&config.store_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        conn.execute("VACUUM INTO ?1", [staging.to_string_lossy().as_ref()])?;
+        Ok(())
+    })?;
+    let ledger = Ledger::open(&config.ledger_path)?;
+    let ledger_schema = ledger.schema_version()?;
+    let pending_mints = ledger
+        .submitted_mints()?
+        .into_iter()
+        .map(|mint| mint.id)
+        .collect();
+    let ledger = with_staging(path, LEDGER_ENTRY, |staging| ledger.backup(staging))?;
+
+    let mut entries = vec![
+        (SnapshotEntry::new(STORE_ENTRY, &store), store),
+        (SnapshotEntry::new(LEDGER_ENTRY, &ledger), ledger),
+    ];
+    let mut keys = Vec::new();
+    for (name, contents) in key_files(&config.keystore_path)? {
+        keys.push(SnapshotEntry::new(&name, &contents));
+        if include_keys {
+            let entry = format!("{KEY_ENTRY_PREFIX}{name}");
+            entries.push((SnapshotEntry::new(entry, &contents), contents));
+        }
+    }
+
+    let manifest = SnapshotManifest {
+        version: SNAPSHOT_VERSION,
+        created_at: unix_now(),
+        network,
+        ledger_schema,
+        pending_mints,
+        entries: entries.iter().map(|(entry, _)| entry.clone()).collect(),
+        keys,
+    };
+    let header = serde_json::to_vec(&manifest)
+        .map_err(|err| FaucetError::Snapshot(format!("invalid manifest: {err}")))?;
+
+    let mut file = private_file().open(path)?;
+    file.write_all(SNAPSHOT_MAGIC)?;
+    file.write_all(&(header.len() as u64).to_le_bytes())?;
+    file.write_all(&header)?;
+    for (_, contents) in &entries {
+        file.write_all(contents)?;
+    }
+    file.sync_all()?;
+    Ok(manifest)
+}
+
+/// Restores the store, ledger and archived keys of the snapshot at `path` to the files of
+/// `config`.
+///
+/// Fails without writing anything if the snapshot is of another network, or if the store or
+/// ledger exists and `force` is not set. Key files already in the keystore are kept.
+pub fn restore_snapshot(
+    config: &Config,
+    path: impl AsRef<Path>,
+    force: bool,
+) -> Result<RestoreReport, FaucetError> {
+    let path = path.as_ref();
+    let snapshot = Snapshot::read(path)?;
+    let manifest = snapshot.manifest.clone();
+    let network = config.rpc.endpoint.network_name();
+    if manifest.network != network {
+        return Err(FaucetError::NetworkMismatch {
+            path: path.display().to_string(),
+            recorded: manifest.network,
+            configured: network,
+        });
+    }
+    let targets = [
+        (STORE_ENTRY, &config.store_path),
+        (LEDGER_ENTRY, &config.ledger_path),
+    ];
+    for (_, target) in targets {
+        if target.exists() && !force {
+            return Err(FaucetError::Snapshot(format!(
+                "`{}` exists, pass --force to replace it",
+                target.display()
+            )));
+        }
+    }
+
+    for (name, target) in targets {
+        let contents = snapshot.entry(name).expect("checked by Snapshot::read");
+        if let Some(parent) = target
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let staging = staging_path(target, "restore");
+        fs::write(&staging, contents)?;
+        // The journal of a replaced database would be replayed onto the restored one.
+        for suffix in ["-wal", "-shm", "-journal"] {
+            let mut journal = target.as_os_str().to_owned();
+            journal.push(suffix);
+            let _ = fs::remove_file(journal);
+        }
+        fs::rename(&staging, target)?;
+    }
+    claim_for_network(&config.store_path, &network)?;
+    Ledger::open(&config.ledger_path)?;
+
+    fs::create_dir_all(&config.keystore_path)?;
+    claim_for_network(&config.keystore_path, &network)?;
+    let mut restored_keys = Vec::new();
+    let mut missing_keys = Vec::new();
+    for key in &manifest.keys {
+        let target = config.keystore_path.join(&key.name);
+        let archived = snapshot.entry(&format!("{KEY_ENTRY_PREFIX}{}", key.name));
+        match (fs::read(&target), archived) {
+            (Ok(existing), _) if digest(&existing) == key.digest => {}
+            (Err(err), Some(contents)) if err.kind() == std::io::ErrorKind::NotFound => {
+                private_file().open(&target)?.write_all(contents)?;
+                restored_keys.push(key.name.clone());
+            }
+            (Err(err), _) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+            _ => missing_keys.push(key.name.clone()),
+        }
+    }
+
+    Ok(RestoreReport {
+        manifest,
+        restored_keys,
+        missing_keys,
+    })
+}
+
+/// RPO digest of `contents`, as hex.
+fn digest(contents: &[u8]) -> String {
+    Rpo256::hash(contents).to_hex()
+}
+
+/// Splits `len` bytes off the front of `bytes`.
+fn split(bytes: &[u8], len: u64) -> Result<(&[u8], &[u8]), FaucetError> {
+    usize::try_from(len)
+        .ok()
+        .filter(|&len| len <= bytes.len())
+        .map(|len| bytes.split_at(len))
+        .ok_or_else(|| FaucetError::Snapshot("archive is truncated".into()))
+}
+
+/// Key files are restored into the keystore directory, so their names must not leave it.
+fn check_key_name(name: &str) -> Result<(), FaucetError> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\'])
+        && Path::new(name).file_name().is_some();
+    if !valid {
+        return Err(FaucetError::Snapshot(format!(
+            "invalid key file name `{name}`"
+        )));
+    }
+    Ok(())
+}
+
+/// Key files of the keystore at `path` by name, without the network file.
+fn key_files(path: &Path) -> Result<Vec<(String, Vec<u8>)>, FaucetError> {
+    let dir = match fs::read_dir(path) {
+        Ok(dir) => dir,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    let mut files = Vec::new();
+    for entry in dir {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if entry.file_type()?.is_file() && check_key_name(&name).is_ok() {
+            files.push((name, fs::read(entry.path())?));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Runs `copy` with a path next to `archive` that does not exist yet, returns the contents `copy`
+/// wrote there and removes the file.
+fn with_staging(
+    archive: &Path,
+    name: &str,
+    copy: impl FnOnce(&Path) -> Result<(), FaucetError>,
+) -> Result<Vec<u8>, FaucetError> {
+    let staging = staging_path(archive, name);
+    let _ = fs::remove_file(&staging);
+    let contents = copy(&staging).and_then(|()| Ok(fs::read(&staging)?));
+    let _ = fs::remove_file(&staging);
+    contents
+}
+
+/// Temporary file next to `path`, e.g. `faucet.snapshot.store.sqlite3.tmp`.
+fn staging_path(path: &Path, name: &str) -> PathBuf {
+    let mut file = path.file_name().unwrap_or_default().to_os_string();
+    file.push(format!(".{name}.tmp"));
+    path.with_file_name(file)
+}
+
+/// Options creating a new file readable by its owner only.
+fn private_file() -> fs::OpenOptions {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+}
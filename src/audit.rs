@@ -0,0 +1,145 @@
+//! Append-only audit log.
+//!
+//! Administrative and minting actions, whether run from the CLI, the binaries or the mint service,
+//! are appended to the audit log of the [`Ledger`] with who asked for them, the account whose key
+//! signed the resulting transaction, their parameters and the transaction ID.
+//!
+//! Entries form a hash chain: each one stores the hash of its predecessor and its own hash over
+//! both, so an entry modified, removed or inserted after the fact breaks the chain from that point
+//! on. The ledger refuses to update or delete entries, and [`verify_audit_log`] checks the chain,
+//! see `audit verify`. Whoever can write the database file can still rebuild a consistent chain,
+//! so the head hash reported by the verification should be kept somewhere else as well.
+
+use miden_client::{account::AccountId, transaction::TransactionId, Word};
+use miden_crypto::hash::rpo::Rpo256;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{ledger::Ledger, FaucetError};
+
+/// An action to append to the audit log with [`Ledger::append_audit`].
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    /// Who asked for the action, e.g. `cli:alice` or `rest`.
+    pub actor: String,
+    /// What was done, e.g. `mint` or `faucet.pause`.
+    pub action: String,
+    /// Account whose key signed the resulting transaction, or the account acted upon.
+    pub account: Option<AccountId>,
+    pub params: Map<String, Value>,
+    pub transaction_id: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(actor: impl Into<String>, action: impl Into<String>) -> Self {
+        Self {
+            actor: actor.into(),
+            action: action.into(),
+            account: None,
+            params: Map::new(),
+            transaction_id: None,
+        }
+    }
+
+    pub fn account(mut self, account_id: AccountId) -> Self {
+        self.account = Some(account_id);
+        self
+    }
+
+    /// Adds a parameter of the action.
+    pub fn param(mut self, name: &str, value: impl Serialize) -> Self {
+        let value = serde_json::to_value(value).unwrap_or(Value::Null);
+        self.params.insert(name.to_string(), value);
+        self
+    }
+
+    pub fn transaction(mut self, transaction_id: TransactionId) -> Self {
+        self.transaction_id = Some(transaction_id.to_hex());
+        self
+    }
+}
+
+/// An entry of the audit log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// Position in the chain, starting at 1.
+    pub id: i64,
+    pub created_at: u64,
+    pub actor: String,
+    pub action: String,
+    pub account: Option<String>,
+    /// Parameters as a JSON object.
+    pub params: String,
+    pub transaction_id: Option<String>,
+    /// Hash of the previous entry, [`genesis_hash`] for the first one.
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditRecord {
+    /// Hash of the entry: the RPO hash of its fields, including the hash of the previous entry.
+    pub fn compute_hash(&self) -> String {
+        let fields = serde_json::json!([
+            self.prev_hash,
+            self.id,
+            self.created_at,
+            self.actor,
+            self.action,
+            self.account,
+            self.params,
+            self.transaction_id,
+        ]);
+        Rpo256::hash(fields.to_string().as_bytes()).to_hex()
+    }
+}
+
+/// Previous hash of the first entry of the log.
+pub fn genesis_hash() -> String {
+    Word::default().to_hex()
+}
+
+/// Actor recorded for actions run from the command line: `cli:` followed by the local user.
+pub fn cli_actor() -> String {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".into());
+    format!("cli:{user}")
+}
+
+/// Result of [`verify_audit_log`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditHead {
+    pub entries: usize,
+    /// Hash of the last entry, [`genesis_hash`] for an empty log.
+    pub hash: String,
+}
+
+/// Checks that the entries of the audit log form an unbroken hash chain.
+///
+/// Fails with [`FaucetError::AuditChainBroken`] at the first entry that does not follow from its
+/// predecessor.
+pub fn verify_audit_log(ledger: &Ledger) -> Result<AuditHead, FaucetError> {
+    let mut head = AuditHead {
+        entries: 0,
+        hash: genesis_hash(),
+    };
+    for record in ledger.audit_log()? {
+        let broken = |reason: &str| FaucetError::AuditChainBroken(record.id, reason.into());
+        if record.id != head.entries as i64 + 1 {
+            return Err(broken(&format!(
+                "expected entry {} after entry {}",
+                head.entries + 1,
+                head.entries
+            )));
+        }
+        if record.prev_hash != head.hash {
+            return Err(broken("previous hash does not match the previous entry"));
+        }
+        if record.compute_hash() != record.hash {
+            return Err(broken("hash does not match the entry"));
+        }
+        head.entries += 1;
+        head.hash = record.hash;
+    }
+    Ok(head)
+}
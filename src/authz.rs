@@ -0,0 +1,153 @@
+//! Roles of API keys and the operations they allow.
+//!
+//! Each `[[api_keys]]` entry may carry a [`Role`]; `admin = true` is shorthand for
+//! `role = "admin"`. Roles are ordered, each allowing the operations of the ones below it:
+//!
+//! - `viewer`: inspect the limits, access lists and queue of the admin API.
+//! - `operator`: pause and unpause minting, reclaim expired mints, edit the access lists, burn
+//!   tokens, remove wallets and prune the store.
+//! - `admin`: change the amounts and drip limits, and restore keys into the keystore.
+//!
+//! The admin API checks the role of the key of every request. The CLI checks the role of the key
+//! given with `--api-key` or `$FAUCET_API_KEY` for the [`Action`]s it runs, once the configuration
+//! gives any API key a role; deployments without roles keep the CLI unrestricted.
+//!
+//! Every check goes through [`authorize`], so the table of [`Action::required_role`] is the only
+//! place deciding who may do what.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{access::ApiKeyConfig, config::Config, FaucetError};
+
+/// Name of the environment variable holding the API key presented by the CLI.
+pub const API_KEY_ENV: &str = "FAUCET_API_KEY";
+
+/// Role of an API key, from least to most privileged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Viewer,
+    Operator,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Operator => "operator",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Operation guarded by a role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Read the limits, access lists or queue through the admin API.
+    View,
+    Pause,
+    Reclaim,
+    EditAccessLists,
+    Burn,
+    RemoveWallet,
+    PruneStore,
+    /// Change the amounts of a tier or the GitHub drip limit.
+    SetLimits,
+    /// Restore a key into the keystore, see `recover`.
+    RestoreKey,
+    /// Pause a faucet for good and retire it, see `faucet decommission`.
+    Decommission,
+    /// Create or restore snapshots of the host files, see `snapshot`.
+    ManageSnapshots,
+}
+
+impl Action {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::View => "view",
+            Self::Pause => "pause",
+            Self::Reclaim => "reclaim",
+            Self::EditAccessLists => "edit access lists",
+            Self::Burn => "burn",
+            Self::RemoveWallet => "remove wallets",
+            Self::PruneStore => "prune the store",
+            Self::SetLimits => "set limits",
+            Self::RestoreKey => "restore keys",
+            Self::Decommission => "decommission faucets",
+            Self::ManageSnapshots => "manage snapshots",
+        }
+    }
+
+    /// Least privileged role allowed to run the action.
+    pub fn required_role(&self) -> Role {
+        match self {
+            Self::View => Role::Viewer,
+            Self::Pause
+            | Self::Reclaim
+            | Self::EditAccessLists
+            | Self::Burn
+            | Self::RemoveWallet
+            | Self::PruneStore => Role::Operator,
+            Self::SetLimits | Self::RestoreKey | Self::Decommission | Self::ManageSnapshots => {
+                Role::Admin
+            }
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Checks that `role`, the role of the caller if it has one, allows `action`.
+///
+/// Fails with [`FaucetError::AccessDenied`] naming `caller` otherwise.
+pub fn authorize(caller: &str, role: Option<Role>, action: Action) -> Result<(), FaucetError> {
+    let required = action.required_role();
+    if role.is_some_and(|role| role >= required) {
+        return Ok(());
+    }
+    Err(FaucetError::AccessDenied(format!(
+        "{caller} may not {action}, which requires the {required} role"
+    )))
+}
+
+/// Checks that the API key presented by the CLI, `config.api_key`, allows `action`.
+///
+/// Passes without a key as long as no API key of the configuration has a role. Fails with
+/// [`FaucetError::Unauthorized`] for missing or unknown keys.
+pub fn authorize_cli(config: &Config, action: Action) -> Result<(), FaucetError> {
+    if !config
+        .api_keys
+        .iter()
+        .any(|api_key| api_key.role().is_some())
+    {
+        return Ok(());
+    }
+    let key = config.api_key.as_deref().ok_or_else(|| {
+        FaucetError::Unauthorized(format!(
+            "`{action}` requires an API key with a role, pass --api-key or set ${API_KEY_ENV}"
+        ))
+    })?;
+    let api_key = find_api_key(&config.api_keys, key)
+        .ok_or_else(|| FaucetError::Unauthorized("unknown API key".into()))?;
+    authorize(&format!("api_key:{}", api_key.name), api_key.role(), action)
+}
+
+/// The entry of `api_keys` with key `key`.
+pub fn find_api_key<'a>(api_keys: &'a [ApiKeyConfig], key: &str) -> Option<&'a ApiKeyConfig> {
+    api_keys
+        .iter()
+        .find(|api_key| api_key.key.as_bytes() == key.trim().as_bytes())
+}
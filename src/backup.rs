@@ -0,0 +1,78 @@
+//! Recovery bundles for wallet keys.
+//!
+//! Wallets created with [`create_wallet_from_seed`](crate::wallet::create_wallet_from_seed) are
+//! derived from a 32-byte seed, so the seed alone restores both their account ID and their key.
+//! A [`RecoveryBundle`] holds that seed as a 24-word BIP-39 phrase together with the account ID it
+//! derives, and [`RecoveryBundle::recover`] rebuilds the key and checks it against that ID.
+//!
+//! The bundle is not encrypted: anyone holding the phrase controls the wallet, so it belongs
+//! offline. Network faucets are authenticated by their owner rather than by a key of their own,
+//! so backing up the owner is enough to operate a faucet again.
+
+use std::{fs, io::Write, path::Path};
+
+use bip39::Mnemonic;
+use miden_client::{account::AccountId, crypto::rpo_falcon512::SecretKey};
+use serde::{Deserialize, Serialize};
+
+use crate::{account::parse_account_id, wallet::wallet_from_seed, FaucetError};
+
+/// Seed of a wallet and the account it derives, as written by `deploy --backup`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryBundle {
+    pub account_id: String,
+    /// BIP-39 phrase encoding the seed given to [`wallet_from_seed`].
+    pub phrase: String,
+}
+
+impl RecoveryBundle {
+    pub fn new(account_id: AccountId, seed: [u8; 32]) -> Result<Self, FaucetError> {
+        let mnemonic = Mnemonic::from_entropy(&seed)
+            .map_err(|err| FaucetError::Backup(format!("cannot encode seed: {err}")))?;
+        Ok(Self {
+            account_id: account_id.to_hex(),
+            phrase: mnemonic.to_string(),
+        })
+    }
+
+    /// Seed encoded by the phrase.
+    pub fn seed(&self) -> Result<[u8; 32], FaucetError> {
+        let mnemonic = Mnemonic::parse(&self.phrase)
+            .map_err(|err| FaucetError::Backup(format!("bad recovery phrase: {err}")))?;
+        mnemonic
+            .to_entropy()
+            .try_into()
+            .map_err(|_| FaucetError::Backup("recovery phrase must have 24 words".into()))
+    }
+
+    /// Rebuilds the key of the wallet, failing unless the seed derives the recorded account.
+    pub fn recover(&self) -> Result<(AccountId, SecretKey), FaucetError> {
+        let account_id = parse_account_id(&self.account_id, None)?;
+        let (account, key) = wallet_from_seed(self.seed()?)?;
+        if account.id() != account_id {
+            return Err(FaucetError::Backup(format!(
+                "phrase derives account {}, not {account_id}",
+                account.id()
+            )));
+        }
+        Ok((account_id, key))
+    }
+
+    /// Writes the bundle to a new file at `path`, readable by its owner only.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), FaucetError> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|err| FaucetError::Backup(err.to_string()))?;
+        options.open(path)?.write_all(contents.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, FaucetError> {
+        let contents = fs::read_to_string(path)?;
+        serde_json::from_str(&contents).map_err(|err| FaucetError::Backup(err.to_string()))
+    }
+}
@@ -0,0 +1,301 @@
+use std::{cell::RefCell, collections::VecDeque, rc::Rc, time::Duration};
+
+use clap::Parser;
+use miden_client::account::AccountId;
+use network_faucet::{
+    account::resolve_account_id,
+    config::Config,
+    ledger::{Ledger, MintRecord, MintStats, MintStatus},
+    node::connect,
+    watcher::{BlockWatcher, ChainTip},
+    FaucetError,
+};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Block, List, Paragraph, Row, Table},
+    DefaultTerminal, Frame,
+};
+use tokio::{sync::Mutex, task::LocalSet};
+
+/// Mints listed in the recent mints table.
+const RECENT_MINTS: usize = 20;
+/// Errors kept for the errors panel.
+const RECENT_ERRORS: usize = 10;
+
+type ErrorLog = Rc<RefCell<VecDeque<String>>>;
+
+/// Live view of a faucet: chain tip, supply, mints in flight and recent errors.
+///
+/// Reads the mint ledger shared with `serve` and follows the chain through its own block watcher.
+/// Press `q` or `Esc` to quit.
+#[derive(Debug, Parser)]
+struct Args {
+    /// Faucet to show, defaults to `service.faucet_id`; every faucet of the ledger when neither
+    /// is set.
+    #[arg(long)]
+    faucet: Option<String>,
+    /// Milliseconds between two reads of the ledger.
+    #[arg(long, default_value_t = 500)]
+    refresh_ms: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), FaucetError> {
+    let args = Args::parse();
+    LocalSet::new().run_until(run(args)).await
+}
+
+async fn run(args: Args) -> Result<(), FaucetError> {
+    let config = Config::load()?;
+    let ledger = Ledger::open(&config.ledger_path)?;
+    let faucet_id = args
+        .faucet
+        .or_else(|| config.service.faucet_id.clone())
+        .map(|faucet| resolve_account_id(&ledger, &faucet, config.account_network().as_ref()))
+        .transpose()?;
+
+    // Sync errors are collected for the errors panel; printing them would corrupt the screen.
+    let errors = ErrorLog::default();
+    let node = Rc::new(Mutex::new(connect(&config).await?));
+    let watcher = {
+        let errors = errors.clone();
+        BlockWatcher::spawn_with_reporter(node, config.poll.sync_interval(), move |err| {
+            log_error(&errors, format!("sync failed: {err}"))
+        })
+    };
+
+    let mut terminal = ratatui::init();
+    let result = dashboard(
+        &mut terminal,
+        &ledger,
+        &watcher,
+        faucet_id,
+        &errors,
+        Duration::from_millis(args.refresh_ms),
+    )
+    .await;
+    ratatui::restore();
+    result
+}
+
+async fn dashboard(
+    terminal: &mut DefaultTerminal,
+    ledger: &Ledger,
+    watcher: &BlockWatcher,
+    faucet_id: Option<AccountId>,
+    errors: &ErrorLog,
+    refresh: Duration,
+) -> Result<(), FaucetError> {
+    let mut tip = watcher.subscribe();
+    let mut refresh = tokio::time::interval(refresh);
+
+    loop {
+        let view = View::read(ledger, faucet_id, watcher.tip(), errors);
+        terminal.draw(|frame| view.render(frame))?;
+
+        // Input is polled between redraws; a blocking read would stall the watcher.
+        while event::poll(Duration::ZERO)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press
+                    && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+                {
+                    return Ok(());
+                }
+            }
+        }
+
+        tokio::select! {
+            _ = refresh.tick() => {}
+            changed = tip.changed() => changed.map_err(|_| FaucetError::WatcherStopped)?,
+        }
+    }
+}
+
+fn log_error(errors: &ErrorLog, message: String) {
+    let mut errors = errors.borrow_mut();
+    if errors.len() == RECENT_ERRORS {
+        errors.pop_front();
+    }
+    errors.push_back(message);
+}
+
+/// Everything shown on one frame.
+struct View {
+    faucet_id: Option<AccountId>,
+    tip: Option<ChainTip>,
+    stats: MintStats,
+    in_flight: Vec<MintRecord>,
+    recent: Vec<MintRecord>,
+    errors: Vec<String>,
+}
+
+impl View {
+    fn read(
+        ledger: &Ledger,
+        faucet_id: Option<AccountId>,
+        tip: Option<ChainTip>,
+        errors: &ErrorLog,
+    ) -> Self {
+        let read = || -> Result<_, FaucetError> {
+            Ok((
+                ledger.stats(faucet_id)?,
+                ledger.recent_mints(faucet_id, Some(MintStatus::Submitted), RECENT_MINTS)?,
+                ledger.recent_mints(faucet_id, None, RECENT_MINTS)?,
+                ledger.recent_mints(faucet_id, Some(MintStatus::Failed), RECENT_ERRORS)?,
+            ))
+        };
+        let (stats, in_flight, recent, failed) = read().unwrap_or_else(|err| {
+            log_error(errors, format!("ledger read failed: {err}"));
+            Default::default()
+        });
+
+        let mut messages: Vec<String> = errors.borrow().iter().rev().cloned().collect();
+        messages.extend(failed.iter().map(|mint| {
+            format!(
+                "mint {} failed: {}",
+                mint.id,
+                mint.error.as_deref().unwrap_or("unknown error")
+            )
+        }));
+        messages.truncate(RECENT_ERRORS);
+
+        Self {
+            faucet_id,
+            tip,
+            stats,
+            in_flight,
+            recent,
+            errors: messages,
+        }
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let [header, summary, in_flight, recent, errors] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(9),
+            Constraint::Min(5),
+            Constraint::Min(8),
+            Constraint::Length(7),
+        ])
+        .areas(frame.area());
+        let [supply, mints] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(summary);
+
+        let faucet = self
+            .faucet_id
+            .map_or_else(|| "all faucets".to_string(), |id| id.to_string());
+        let tip = match self.tip {
+            Some(tip) => format!(
+                "block {} (synced {}s ago)",
+                tip.block_num,
+                tip.synced_at.elapsed().as_secs()
+            ),
+            None => "syncing...".to_string(),
+        };
+        frame.render_widget(
+            Line::from(format!(" {faucet} | chain tip: {tip} | q to quit")).bold(),
+            header,
+        );
+
+        let stats = &self.stats;
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(format!("Minted:     {}", stats.minted_amount)),
+                Line::from(format!("Delivered:  {}", stats.delivered_amount)),
+                Line::from(format!("Reclaimed:  {}", stats.reclaimed_amount)),
+                Line::from(format!("Burned:     {}", stats.burned_amount)),
+                Line::from(format!("Returned:   {}", stats.returned_amount)),
+                Line::from(format!(
+                    "Net issued: {}",
+                    stats.minted_amount.saturating_sub(stats.burned_amount)
+                )),
+            ])
+            .block(Block::bordered().title("Supply (ledger)")),
+            supply,
+        );
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(format!("Submitted:  {}", stats.submitted)),
+                Line::from(format!(
+                    "In flight:  {}",
+                    stats.submitted - stats.committed - stats.failed
+                )),
+                Line::from(format!("Committed:  {}", stats.committed)),
+                Line::from(format!("Failed:     {}", stats.failed)),
+                Line::from(format!(
+                    "Claimed:    {} of {}",
+                    stats.delivered,
+                    stats.delivered + stats.unclaimed
+                )),
+            ])
+            .block(Block::bordered().title("Mints")),
+            mints,
+        );
+
+        frame.render_widget(
+            mint_table(&self.in_flight).block(Block::bordered().title("Awaiting commitment")),
+            in_flight,
+        );
+        frame.render_widget(
+            mint_table(&self.recent).block(Block::bordered().title("Recent mints")),
+            recent,
+        );
+        frame.render_widget(
+            List::new(self.errors.iter().map(String::as_str))
+                .style(Style::new().fg(Color::Red))
+                .block(Block::bordered().title("Recent errors")),
+            errors,
+        );
+    }
+}
+
+fn mint_table(mints: &[MintRecord]) -> Table<'_> {
+    let rows = mints.iter().map(|mint| {
+        let color = match mint.status {
+            MintStatus::Submitted => Color::Yellow,
+            MintStatus::Committed => Color::Green,
+            MintStatus::Failed => Color::Red,
+            MintStatus::Invalidated => Color::Magenta,
+        };
+        Row::new(vec![
+            mint.id.to_string(),
+            mint.recipient.clone(),
+            mint.amount.to_string(),
+            mint.status.to_string(),
+            mint.commit_block
+                .map(|block| block.to_string())
+                .unwrap_or_default(),
+            mint.claim_block
+                .map(|block| block.to_string())
+                .unwrap_or_default(),
+        ])
+        .style(Style::new().fg(color))
+    });
+
+    Table::new(
+        rows,
+        [
+            Constraint::Length(6),
+            Constraint::Length(34),
+            Constraint::Length(12),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(10),
+        ],
+    )
+    .header(
+        Row::new([
+            "ID",
+            "Recipient",
+            "Amount",
+            "Status",
+            "Committed",
+            "Claimed",
+        ])
+        .bold(),
+    )
+}
@@ -1,142 +1,231 @@
-use rand::RngCore;
-use std::{fs, path::Path, sync::Arc};
-
-use miden_client::{
-    account::{
-        component::{BasicWallet, NetworkFungibleFaucet},
-        AccountBuilder, AccountStorageMode, AccountType,
-    },
-    asset::TokenSymbol,
-    auth::{AuthRpoFalcon512, AuthSecretKey},
-    builder::ClientBuilder,
-    crypto::rpo_falcon512::SecretKey,
-    keystore::FilesystemKeyStore,
-    rpc::{Endpoint, GrpcClient},
-    testing::Auth,
-    transaction::TransactionRequestBuilder,
-    ClientError, Felt,
+use std::{path::PathBuf, rc::Rc};
+
+use clap::Parser;
+use miden_client::{account::AccountStorageMode, Word};
+use network_faucet::{
+    account::resolve_account_id,
+    audit::cli_actor,
+    client::{parse_seed, seeded_rng},
+    config::Config,
+    deploy::{estimate_deployment, parse_storage_mode, plan_deployment, FaucetAccount, FaucetAuth},
+    flow::{start_flow, DeployFlow, Flow, FlowRunner},
+    ledger::Ledger,
+    mint::parse_transaction_id,
+    node::{connect, FaucetNode},
+    script::{load_script, ScriptParams, DEPLOY_SCRIPT},
+    wallet::parse_key_id,
+    watcher::BlockWatcher,
+    FaucetError,
 };
-use miden_client_sqlite_store::ClientBuilderSqliteExt;
+use tokio::{sync::Mutex, task::LocalSet};
+
+/// Deploys a network faucet owned by an existing wallet, or by a new wallet for Alice.
+///
+/// The deployment runs as a flow, see `network-faucet flows`: if it stops half-way, `flows resume`
+/// continues it instead of deploying another faucet.
+#[derive(Debug, Parser)]
+struct Args {
+    /// Wallet ID or label of the faucet owner. Defaults to the default account; a new wallet is
+    /// created when neither is set.
+    #[arg(long)]
+    owner: Option<String>,
+    /// Deployment script replacing the one embedded in the binary.
+    #[arg(long)]
+    script_path: Option<PathBuf>,
+    /// Value of a `{{name}}` placeholder of the deployment script, as `name=value`. Values are
+    /// decimal numbers, account IDs or hex words. May be repeated.
+    #[arg(long = "param", value_name = "NAME=VALUE")]
+    params: Vec<String>,
+    /// Ticker of the token, up to six uppercase letters. Defaults to `token.symbol`.
+    #[arg(long)]
+    symbol: Option<String>,
+    /// Decimals of the token. Defaults to `token.decimals`.
+    #[arg(long)]
+    decimals: Option<u8>,
+    /// Tokens the faucet can ever issue, in base units. Defaults to `token.max_supply`.
+    #[arg(long)]
+    max_supply: Option<u64>,
+    /// Authentication of the faucet, `incr-nonce` or `rpo-falcon512`. Network faucets need
+    /// `incr-nonce`, as the network executes their transactions without a key; only notes of the
+    /// owner then mint and pause, so mainnet refuses owners without a key.
+    #[arg(long, value_name = "AUTH", default_value = "incr-nonce")]
+    auth: FaucetAuth,
+    /// Storage mode of the faucet, `network`, `public` or `private`. Only `network` faucets mint
+    /// through MINT notes; mainnet refuses nonce-only faucets outside of it and private faucets.
+    #[arg(long, value_name = "MODE", default_value = "network", value_parser = parse_storage_mode)]
+    storage_mode: AccountStorageMode,
+    /// Bind an `rpo-falcon512` faucet to this key of the keystore, by the hex commitment of its
+    /// public key, instead of generating a new one. Lets several hosts deploy with one key.
+    #[arg(long, value_name = "COMMITMENT", value_parser = parse_key_id)]
+    key_id: Option<Word>,
+    /// Hex word placed on the stack when the deployment script starts.
+    #[arg(long)]
+    script_arg: Option<String>,
+    /// Write the recovery phrase of the new owner wallet to this file, see `recover`. Only
+    /// available when the owner is created by this deployment.
+    #[arg(long, value_name = "PATH")]
+    backup: Option<PathBuf>,
+    /// Hex seed of the client RNG, to reproduce account IDs and note commitments exactly.
+    #[arg(long, value_name = "HEX")]
+    seed: Option<String>,
+    /// Execute the deployment and print its cost without submitting it. Needs an owner, the new
+    /// faucet is only added to the local store.
+    #[arg(long, conflicts_with = "backup")]
+    dry_run: bool,
+    /// Print the accounts and script the deployment would use as JSON, without connecting to the
+    /// node. Needs a seed, so the deployment with the same seed creates the planned accounts.
+    #[arg(long, conflicts_with_all = ["backup", "dry_run"])]
+    plan: bool,
+}
 
 #[tokio::main]
-async fn main() -> Result<(), ClientError> {
-    // Initialize client & keystore
-    // let endpoint = Endpoint::new("http".into(), "localhost".into(), Some(57291));
-    let endpoint = Endpoint::testnet();
-    let timeout_ms = 10_000;
-    let rpc_client = Arc::new(GrpcClient::new(&endpoint, timeout_ms));
-    let keystore: FilesystemKeyStore<rand::prelude::StdRng> =
-        FilesystemKeyStore::new("./keystore".into()).unwrap().into();
+async fn main() -> Result<(), FaucetError> {
+    let args = Args::parse();
+    LocalSet::new().run_until(run(args)).await
+}
 
-    let mut client = ClientBuilder::new()
-        .rpc(rpc_client)
-        .sqlite_store("./store.sqlite3".into())
-        // .store(StoreBuilder::Factory(Box::new(SqliteStoreFactory::new(
-        //     "./store.sqlite3",
-        // ))))
-        .authenticator(keystore.clone().into())
-        .in_debug_mode(true.into())
-        .build()
-        .await?;
+async fn run(args: Args) -> Result<(), FaucetError> {
+    let mut params = ScriptParams::new();
+    for assignment in &args.params {
+        params = params.with_assignment(assignment)?;
+    }
+    if let Some(arg) = &args.script_arg {
+        let arg = Word::try_from(arg.as_str()).map_err(|err| {
+            FaucetError::ScriptTemplate(format!("invalid script argument: {err}"))
+        })?;
+        params = params.with_script_arg(arg);
+    }
 
-    let sync_summary = client.sync_state().await.unwrap();
-    println!("Latest block: {}", sync_summary.block_num);
+    // Initialize client & keystore
+    let mut config = Config::load()?;
+    config.seed = args.seed.as_deref().map(parse_seed).transpose()?;
+    if let Some(symbol) = &args.symbol {
+        config.token.symbol = symbol.clone();
+    }
+    if let Some(decimals) = args.decimals {
+        config.token.decimals = decimals;
+    }
+    if let Some(max_supply) = args.max_supply {
+        config.token.max_supply = max_supply;
+    }
+    // Checked before anything touches the node, so a bad token fails fast.
+    let token = config.token.clone();
+    token.validate()?;
+    let account = FaucetAccount {
+        auth: args.auth,
+        storage_mode: args.storage_mode,
+        key_id: args.key_id,
+    };
+    account.check(config.is_mainnet())?;
+
+    let ledger = Ledger::open(&config.ledger_path)?;
+    let owner = match &args.owner {
+        Some(owner) => Some(resolve_account_id(
+            &ledger,
+            owner,
+            config.account_network().as_ref(),
+        )?),
+        None => ledger.default_account()?,
+    };
+    // Load the MASM script referencing the increment procedure
+    let script_code = load_script(args.script_path.as_deref(), DEPLOY_SCRIPT)?;
+
+    if let Some(backup) = &args.backup {
+        if let Some(owner_id) = owner {
+            return Err(FaucetError::Backup(format!(
+                "the key of existing owner {owner_id} was not derived from a recovery phrase"
+            )));
+        }
+        if backup.exists() {
+            return Err(FaucetError::Backup(format!(
+                "`{}` already exists",
+                backup.display()
+            )));
+        }
+    }
+
+    if args.plan {
+        let seed = config.seed.ok_or_else(|| {
+            FaucetError::Config("--plan needs --seed to derive the deployed accounts".into())
+        })?;
+        let plan = plan_deployment(
+            &mut seeded_rng(seed),
+            owner,
+            &script_code,
+            &params,
+            &token,
+            account,
+        )?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&plan).expect("plans serialize to JSON")
+        );
+        return Ok(());
+    }
+    let mut node = connect(&config).await?;
+
+    let latest_block = node.sync_state().await?;
+    println!("Latest block: {latest_block}");
+    // Owners created by the flow are keyed wallets; an existing one must be checked.
+    if let Some(owner_id) = owner {
+        let owner_account = node
+            .get_account(owner_id)
+            .await?
+            .ok_or(FaucetError::AccountNotFound(owner_id))?;
+        account.check_owner(&owner_account, config.is_mainnet())?;
+    }
+
+    if args.dry_run {
+        let owner_id = owner.ok_or_else(|| {
+            FaucetError::Config("--dry-run needs --owner or a default account".into())
+        })?;
+        let (faucet, cost) =
+            estimate_deployment(&mut node, owner_id, &script_code, &params, &token, account)
+                .await?;
+        println!(
+            "Estimated deployment cost of faucet {}: fee {}, {} cycles",
+            faucet.id(),
+            cost.fee,
+            cost.cycles
+        );
+        return Ok(());
+    }
 
     //------------------------------------------------------------
-    // STEP 1: Create a basic wallet for Alice
+    // Create the owner unless given, then create and deploy the network faucet
     //------------------------------------------------------------
-    println!("\n[STEP 1] Creating a new account for Alice");
-
-    // Account seed
-    let mut init_seed = [0_u8; 32];
-    client.rng().fill_bytes(&mut init_seed);
-    let alice_key_pair = SecretKey::with_rng(client.rng());
-
-    // Build the account
-    let builder = AccountBuilder::new(init_seed)
-        .account_type(AccountType::RegularAccountUpdatableCode)
-        .storage_mode(AccountStorageMode::Public)
-        .with_auth_component(AuthRpoFalcon512::new(
-            alice_key_pair.public_key().to_commitment().into(),
-        ))
-        .with_component(BasicWallet);
-
-    let alice_account = builder.build().unwrap();
-
-    // Add the account to the client
-    client.add_account(&alice_account, false).await?;
-
-    // Add the key pair to the keystore
-    keystore
-        .add_key(&AuthSecretKey::RpoFalcon512(alice_key_pair))
-        .unwrap();
+    let flow = DeployFlow::new(owner, token, account, script_code)
+        .with_params(args.params, args.script_arg)
+        .with_backup(args.backup)
+        .with_script_path(args.script_path);
+    let id = start_flow(&ledger, &Flow::Deploy(flow))?;
+    println!("\nStarted deploy flow {id}");
+
+    let node = Rc::new(Mutex::new(node));
+    let watcher = BlockWatcher::spawn(node.clone(), config.poll.sync_interval());
+    let runner = FlowRunner::new(node, &watcher, &ledger, cli_actor());
+    let flow = runner.resume(id).await.inspect_err(|_| {
+        eprintln!("Deploy flow {id} stopped, continue it with `network-faucet flows resume {id}`")
+    })?;
+    let Flow::Deploy(deployed) = flow else {
+        unreachable!("flow {id} was started as a deploy flow");
+    };
 
     println!(
-        "Alice account created and added to client, ID: {:?}",
-        alice_account.id()
+        "Faucet account created and deployed, ID: {}",
+        deployed.faucet_id.as_deref().unwrap_or_default()
     );
-
-    //------------------------------------------------------------
-    // STEP 3: Create the network faucet account
-    //------------------------------------------------------------
-
-    let mut faucet_init_seed = [0_u8; 32];
-    client.rng().fill_bytes(&mut faucet_init_seed);
-
-    let network_faucet_component = NetworkFungibleFaucet::new(
-        TokenSymbol::new("MDE").unwrap(),
-        8,
-        Felt::new(1_000_000),
-        alice_account.id(),
-    )
-    .unwrap();
-
-    // Build the account
-    let builder = AccountBuilder::new(faucet_init_seed)
-        .account_type(AccountType::FungibleFaucet)
-        .storage_mode(AccountStorageMode::Network)
-        .with_auth_component(Auth::IncrNonce)
-        .with_component(network_faucet_component);
-
-    let faucet_account = builder.build().unwrap();
-
-    // Add the faucet to the client
-    client.add_account(&faucet_account, false).await?;
-
     println!(
-        "Faucet account created and added to client, ID: {:?}",
-        faucet_account.id()
+        "Token {}: {} decimals, max supply {}",
+        deployed.token.symbol, deployed.token.decimals, deployed.token.max_supply
     );
 
-    //------------------------------------------------------------
-    // STEP 4: Deploy the network faucet contract using the increment nonce script
-    //------------------------------------------------------------
-
-    // Load the MASM script referencing the increment procedure
-    let script_path = Path::new("./masm/deploy.masm");
-    let script_code = fs::read_to_string(script_path).unwrap();
-
-    let tx_script = client
-        .script_builder()
-        .compile_tx_script(&script_code)
-        .unwrap();
-
-    // Build a transaction request with the custom script
-    let tx_deployment_request = TransactionRequestBuilder::new()
-        .custom_script(tx_script)
-        .build()
-        .unwrap();
-
-    // Execute and submit the transaction
-    let tx_id = client
-        .submit_new_transaction(faucet_account.id(), tx_deployment_request)
-        .await
-        .unwrap();
-
-    println!(
-        "View transaction on MidenScan: https://testnet.midenscan.com/tx/{:?}",
-        tx_id
-    );
+    if let (Some(explorer), Some(transaction_id)) = (config.explorer(), &deployed.transaction_id) {
+        println!(
+            "View transaction: {}",
+            explorer.transaction(parse_transaction_id(transaction_id)?)
+        );
+    }
 
     Ok(())
 }
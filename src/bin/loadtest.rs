@@ -0,0 +1,473 @@
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use miden_client::{account::AccountId, transaction::TransactionId};
+use miden_objects::block::BlockNumber;
+use network_faucet::{
+    account::parse_account_id,
+    authz::API_KEY_ENV,
+    config::Config,
+    ledger::{unix_now, Ledger},
+    mint::{mint_with_options, MintNoteKind, MintOptions},
+    node::{connect, FaucetNode, TxState},
+    rest::{MintRequest, MintResponse, MintState, MintStatusResponse, StatusResponse},
+    scenario::{
+        AmountDistribution, LatencySummary, NoteMix, PlannedMint, RecipientPool, Scenario,
+        ScenarioReport, MIDEN_CLIENT_VERSION,
+    },
+    wallet::{build_wallet, wallet_from_seed},
+    watcher::BlockWatcher,
+    FaucetError,
+};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use reqwest::{header::AUTHORIZATION, StatusCode};
+use tokio::{sync::Mutex, task::LocalSet};
+
+/// Time a running service gets to answer each request of a `--target` run.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Interval at which a `--target` run polls the status of its submitted mints.
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Fires mint requests at a deployed network faucet and reports latency and failure statistics.
+///
+/// Recipients are throwaway wallets that are never registered with the store. By default requests
+/// go through the library API on a single client, so submissions are serialized; the rate caps how
+/// fast they are issued. With `--target`, they are sent to the REST API of a running service
+/// instead, which queues and serves them like those of real users. The workload is read from a
+/// scenario file, see `network_faucet::scenario`, or given by the flags below.
+#[derive(Debug, Parser)]
+struct Args {
+    /// Network faucet to mint from.
+    #[arg(long, required_unless_present = "target", conflicts_with = "target")]
+    faucet: Option<String>,
+    /// Base URL of the REST API of a running service to send the mints to, e.g.
+    /// `https://faucet.example.com`, instead of minting through the library.
+    #[arg(long, value_name = "URL")]
+    target: Option<String>,
+    /// API key the mints of a `--target` run are sent with, selecting its tier. Defaults to
+    /// `$FAUCET_API_KEY`.
+    #[arg(long, requires = "target", value_name = "KEY")]
+    api_key: Option<String>,
+    /// Scenario file describing the workload, replacing the workload flags.
+    #[arg(long, conflicts_with_all = ["recipients", "rate", "amount", "commit_timeout_secs"])]
+    scenario: Option<PathBuf>,
+    /// Write the report of the run to this file as JSON.
+    #[arg(long)]
+    report: Option<PathBuf>,
+    /// Number of recipient wallets, one mint each.
+    #[arg(long, default_value_t = 10)]
+    recipients: usize,
+    /// Maximum mint submissions per second.
+    #[arg(long, default_value_t = 1.0)]
+    rate: f64,
+    #[arg(long, default_value_t = 50)]
+    amount: u64,
+    /// Give up waiting for commitments after this many seconds.
+    #[arg(long, default_value_t = 300)]
+    commit_timeout_secs: u64,
+}
+
+impl Args {
+    /// Scenario of the scenario file, or else of the workload flags.
+    fn scenario(&self) -> Result<Scenario, FaucetError> {
+        if let Some(path) = &self.scenario {
+            return Scenario::from_file(path);
+        }
+        let scenario = Scenario {
+            name: "flags".into(),
+            rate: self.rate,
+            duration_secs: self.recipients as f64 / self.rate,
+            seed: 0,
+            commit_timeout_secs: self.commit_timeout_secs,
+            amount: AmountDistribution::Fixed {
+                amount: self.amount,
+            },
+            recipients: RecipientPool {
+                size: self.recipients,
+                random: false,
+            },
+            notes: NoteMix::default(),
+        };
+        scenario.validate()?;
+        Ok(scenario)
+    }
+}
+
+struct PendingMint {
+    transaction_id: TransactionId,
+    submitted_at: Instant,
+}
+
+/// Mint of a `--target` run waiting for its commitment.
+struct PendingRequest {
+    mint_id: i64,
+    submitted_at: Instant,
+}
+
+/// Measurements of a run, whichever way its mints were sent.
+#[derive(Default)]
+struct Outcome {
+    submitted: usize,
+    submit_phase: Duration,
+    submit_latencies: Vec<Duration>,
+    commit_times: Vec<Duration>,
+    failures: BTreeMap<String, usize>,
+    notes: BTreeMap<String, usize>,
+    minted_amount: u64,
+}
+
+impl Outcome {
+    fn fail(&mut self, kind: impl Into<String>) {
+        *self.failures.entry(kind.into()).or_default() += 1;
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), FaucetError> {
+    LocalSet::new().run_until(run()).await
+}
+
+async fn run() -> Result<(), FaucetError> {
+    let args = Args::parse();
+    let scenario = args.scenario()?;
+    let plan = scenario.plan();
+
+    let started_at = unix_now();
+    let mut outcome = match (&args.target, &args.faucet) {
+        (Some(target), _) => {
+            let api_key = args
+                .api_key
+                .clone()
+                .or_else(|| std::env::var(API_KEY_ENV).ok());
+            run_against(target, api_key.as_deref(), &scenario, &plan).await?
+        }
+        (None, Some(faucet)) => {
+            run_in_process(parse_account_id(faucet, None)?, &scenario, &plan).await?
+        }
+        (None, None) => unreachable!("clap requires --faucet without --target"),
+    };
+
+    let report = ScenarioReport {
+        scenario: scenario.name.clone(),
+        seed: scenario.seed,
+        faucet_version: env!("CARGO_PKG_VERSION").into(),
+        miden_client_version: MIDEN_CLIENT_VERSION.into(),
+        started_at,
+        planned: plan.len(),
+        submitted: outcome.submitted,
+        committed: outcome.commit_times.len(),
+        minted_amount: outcome.minted_amount,
+        achieved_rate: outcome.submitted as f64
+            / outcome.submit_phase.as_secs_f64().max(f64::EPSILON),
+        notes: outcome.notes,
+        submit_latency: LatencySummary::new(&mut outcome.submit_latencies),
+        commit_time: LatencySummary::new(&mut outcome.commit_times),
+        failures: outcome.failures,
+    };
+    print_report(&report);
+    if let Some(path) = &args.report {
+        report.write(path)?;
+        println!("\nWrote the report to {}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Mints `plan` through the library API, on a client of the local configuration.
+async fn run_in_process(
+    faucet_id: AccountId,
+    scenario: &Scenario,
+    plan: &[PlannedMint],
+) -> Result<Outcome, FaucetError> {
+    let config = Config::load()?;
+    let mut node = connect(&config).await?;
+    let ledger = Ledger::open(&config.ledger_path)?;
+    node.sync_state().await?;
+
+    let recipients = (0..scenario.recipients.size)
+        .map(|_| build_wallet(&mut node).map(|(account, _)| account.id()))
+        .collect::<Result<Vec<_>, _>>()?;
+    println!(
+        "Running scenario `{}`: {} mints to {} recipient wallets",
+        scenario.name,
+        plan.len(),
+        recipients.len()
+    );
+
+    let node = Rc::new(Mutex::new(node));
+    let watcher = BlockWatcher::spawn(node.clone(), config.poll.sync_interval());
+
+    let start = Instant::now();
+    let mut outcome = Outcome::default();
+    let mut pending = Vec::new();
+
+    for mint in plan {
+        tokio::time::sleep_until((start + mint.offset).into()).await;
+        let recipient = recipients[mint.recipient];
+        let started = Instant::now();
+        let tip = match watcher.tip() {
+            Some(tip) => tip.block_num,
+            None => node.lock().await.sync_state().await?,
+        };
+        let options = MintOptions {
+            note_kind: mint.note.note_kind(&scenario.notes, tip),
+            ..MintOptions::default()
+        };
+        let result = mint_with_options(
+            &mut *node.lock().await,
+            faucet_id,
+            recipient,
+            mint.amount,
+            options,
+        )
+        .await;
+        match result {
+            Ok(submitted) => {
+                outcome.submit_latencies.push(started.elapsed());
+                ledger.record_mint(
+                    faucet_id,
+                    recipient,
+                    mint.amount,
+                    submitted.transaction_id,
+                    &submitted.p2id_note,
+                )?;
+                *outcome.notes.entry(mint.note.as_str().into()).or_default() += 1;
+                outcome.minted_amount += mint.amount;
+                pending.push(PendingMint {
+                    transaction_id: submitted.transaction_id,
+                    submitted_at: started,
+                });
+            }
+            Err(err) => outcome.fail(failure_kind(&err)),
+        }
+    }
+    outcome.submit_phase = start.elapsed();
+    outcome.submitted = pending.len();
+
+    println!(
+        "Submitted {} mints, waiting for commitments...",
+        outcome.submitted
+    );
+
+    let deadline = Instant::now() + Duration::from_secs(scenario.commit_timeout_secs);
+    let mut tip = watcher.subscribe();
+    while !pending.is_empty() {
+        let transaction_ids = pending
+            .iter()
+            .map(|mint| mint.transaction_id)
+            .collect::<Vec<_>>();
+        let states = node.lock().await.transaction_states(&transaction_ids).await;
+        match states {
+            Ok(mut states) => {
+                let mut still_pending = Vec::new();
+                for mint in pending {
+                    match states.remove(&mint.transaction_id) {
+                        Some(TxState::Committed(block_num)) => {
+                            outcome.commit_times.push(mint.submitted_at.elapsed());
+                            ledger.mark_committed(mint.transaction_id, block_num)?;
+                        }
+                        Some(TxState::Discarded(cause)) => {
+                            outcome.fail("discarded");
+                            ledger.mark_failed(mint.transaction_id, &cause)?;
+                        }
+                        Some(TxState::Pending) | None => still_pending.push(mint),
+                    }
+                }
+                pending = still_pending;
+            }
+            Err(err) => outcome.fail(failure_kind(&err)),
+        }
+
+        match tokio::time::timeout_at(deadline.into(), tip.changed()).await {
+            Ok(Ok(())) => {}
+            Ok(Err(_)) => return Err(FaucetError::WatcherStopped),
+            Err(_) => break,
+        }
+    }
+    if !pending.is_empty() {
+        *outcome.failures.entry("commit timeout".into()).or_default() += pending.len();
+    }
+    Ok(outcome)
+}
+
+/// Sends `plan` to the REST API at `target`, as a client of the running service would.
+///
+/// Reclaim and unlock heights are counted from the chain tip the service reports at the start of
+/// the run. Recipients are derived from the seed of the scenario, so no local store is needed.
+async fn run_against(
+    target: &str,
+    api_key: Option<&str>,
+    scenario: &Scenario,
+    plan: &[PlannedMint],
+) -> Result<Outcome, FaucetError> {
+    let base = target.trim_end_matches('/');
+    let http = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|err| FaucetError::Server(err.to_string()))?;
+    let status: StatusResponse = http
+        .get(format!("{base}/api/status"))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| FaucetError::Server(format!("status of {base}: {err}")))?
+        .json()
+        .await
+        .map_err(|err| FaucetError::Server(format!("status of {base}: {err}")))?;
+    let tip = BlockNumber::from(status.chain_tip);
+
+    let mut rng = ChaCha20Rng::seed_from_u64(scenario.seed);
+    let recipients = (0..scenario.recipients.size)
+        .map(|_| {
+            let mut seed = [0_u8; 32];
+            rng.fill_bytes(&mut seed);
+            wallet_from_seed(seed).map(|(account, _)| account.id())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    println!(
+        "Running scenario `{}` against {base}: {} mints to {} recipient wallets",
+        scenario.name,
+        plan.len(),
+        recipients.len()
+    );
+
+    let start = Instant::now();
+    let mut outcome = Outcome::default();
+    let mut pending = Vec::new();
+
+    for mint in plan {
+        tokio::time::sleep_until((start + mint.offset).into()).await;
+        let (reclaim_height, unlock_height) = match mint.note.note_kind(&scenario.notes, tip) {
+            MintNoteKind::P2id => (None, None),
+            MintNoteKind::P2ide { reclaim_height } => (Some(reclaim_height), None),
+            MintNoteKind::Timelocked {
+                unlock_height,
+                reclaim_height,
+            } => (reclaim_height, Some(unlock_height)),
+        };
+        let request = MintRequest {
+            recipient: recipients[mint.recipient].to_hex(),
+            amount: Some(mint.amount),
+            reclaim_height: reclaim_height.map(|height| height.as_u32()),
+            unlock_height: unlock_height.map(|height| height.as_u32()),
+            ..MintRequest::default()
+        };
+        let started = Instant::now();
+        let mut builder = http.post(format!("{base}/api/mint")).json(&request);
+        if let Some(api_key) = api_key {
+            builder = builder.header(AUTHORIZATION, format!("Bearer {api_key}"));
+        }
+        let response = match builder.send().await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                outcome.fail(status_kind(response.status()));
+                continue;
+            }
+            Err(_) => {
+                outcome.fail("unreachable");
+                continue;
+            }
+        };
+        match response.json::<MintResponse>().await {
+            Ok(minted) => {
+                outcome.submit_latencies.push(started.elapsed());
+                *outcome.notes.entry(mint.note.as_str().into()).or_default() += 1;
+                outcome.minted_amount += minted.amount;
+                pending.push(PendingRequest {
+                    mint_id: minted.mint_id,
+                    submitted_at: started,
+                });
+            }
+            Err(_) => outcome.fail("invalid response"),
+        }
+    }
+    outcome.submit_phase = start.elapsed();
+    outcome.submitted = pending.len();
+
+    println!(
+        "Submitted {} mints, waiting for commitments...",
+        outcome.submitted
+    );
+
+    let deadline = Instant::now() + Duration::from_secs(scenario.commit_timeout_secs);
+    while !pending.is_empty() && Instant::now() < deadline {
+        let mut still_pending = Vec::new();
+        for mint in pending {
+            let status = http
+                .get(format!("{base}/api/mints/{}", mint.mint_id))
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            let status = match status {
+                Ok(response) => response.json::<MintStatusResponse>().await,
+                Err(err) => Err(err),
+            };
+            match status.map(|status| status.status) {
+                Ok(MintState::Committed) => {
+                    outcome.commit_times.push(mint.submitted_at.elapsed());
+                }
+                Ok(MintState::Failed | MintState::Invalidated) => outcome.fail("discarded"),
+                Ok(MintState::Submitted) => still_pending.push(mint),
+                Err(err) => {
+                    outcome.fail(err.status().map_or("unreachable".into(), status_kind));
+                    still_pending.push(mint);
+                }
+            }
+        }
+        pending = still_pending;
+        tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+    }
+    if !pending.is_empty() {
+        *outcome.failures.entry("commit timeout".into()).or_default() += pending.len();
+    }
+    Ok(outcome)
+}
+
+/// Groups refused requests of a `--target` run by status, e.g. `HTTP 429`.
+fn status_kind(status: StatusCode) -> String {
+    format!("HTTP {}", status.as_u16())
+}
+
+/// Groups errors by variant so the report does not list every distinct message.
+fn failure_kind(err: &FaucetError) -> String {
+    let debug = format!("{err:?}");
+    debug
+        .split(['(', ' ', '{'])
+        .next()
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+fn print_report(report: &ScenarioReport) {
+    println!(
+        "\nSubmitted {} of {} planned mints at {:.2}/s, {} committed",
+        report.submitted, report.planned, report.achieved_rate, report.committed
+    );
+    print_latency("Submission latency", &report.submit_latency);
+    print_latency("Time to commit", &report.commit_time);
+    println!("\nFailures:");
+    if report.failures.is_empty() {
+        println!("  none");
+    }
+    for (kind, count) in &report.failures {
+        println!("  {kind}: {count}");
+    }
+}
+
+fn print_latency(title: &str, summary: &LatencySummary) {
+    println!("\n{title} ({} samples):", summary.samples);
+    if summary.samples == 0 {
+        return;
+    }
+    println!("  p50: {:.1}ms", summary.p50_ms);
+    println!("  p90: {:.1}ms", summary.p90_ms);
+    println!("  p99: {:.1}ms", summary.p99_ms);
+    println!("  max: {:.1}ms", summary.max_ms);
+}
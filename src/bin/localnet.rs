@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use network_faucet::{
+    localnet::{LocalNode, LocalnetConfig, NodeLauncher},
+    FaucetError,
+};
+
+/// Starts a local node and keeps it running until Ctrl-C.
+///
+/// By default the node runs in docker (`MIDEN_NODE_IMAGE`, `miden-node:latest` if unset). Set
+/// `MIDEN_NODE_BIN` to run a `miden-node` binary from the host instead.
+#[tokio::main]
+async fn main() -> Result<(), FaucetError> {
+    let node = LocalNode::start(LocalnetConfig {
+        launcher: NodeLauncher::from_env(),
+        startup_timeout: Duration::from_secs(120),
+        ..LocalnetConfig::default()
+    })
+    .await?;
+
+    let config = node.client_config();
+    println!("\nPoint the faucet binaries at the node with this faucet.toml:\n");
+    println!("store_path = {:?}", config.store_path);
+    println!("keystore_path = {:?}", config.keystore_path);
+    println!("ledger_path = {:?}", config.ledger_path);
+    println!("\n[rpc]\nendpoint = \"{}\"", node.endpoint());
+
+    tokio::signal::ctrl_c().await?;
+    println!("Stopping local node");
+
+    Ok(())
+}
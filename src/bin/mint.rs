@@ -1,266 +1,175 @@
-use std::{sync::Arc, time::Duration};
+use std::{rc::Rc, time::Duration};
 
+use clap::Parser;
 use miden_client::{
-    account::{component::BasicWallet, AccountBuilder, AccountId, AccountStorageMode, AccountType},
-    asset::{Asset, FungibleAsset},
-    auth::{AuthRpoFalcon512, AuthSecretKey, TransactionAuthenticator},
-    builder::ClientBuilder,
-    crypto::{rpo_falcon512::SecretKey, FeltRng},
-    keystore::FilesystemKeyStore,
-    note::{
-        Note, NoteAssets, NoteError, NoteExecutionHint, NoteInputs, NoteMetadata, NoteRecipient,
-        NoteTag, NoteType, WellKnownNote,
+    account::{AccountId, AccountStorageMode},
+    note::NoteType,
+};
+use network_faucet::{
+    account::{parse_account_id, resolve_account_id},
+    audit::cli_actor,
+    campaign::check_campaign_mints,
+    client::parse_seed,
+    config::Config,
+    flow::{start_flow, Flow, FlowRunner, MintFlow},
+    ledger::{unix_now, Ledger},
+    mint::{
+        check_recipient, estimate_mint_batch, get_balance, parse_note_type, BatchMint,
+        NoteTagStrategy,
     },
-    rpc::{Endpoint, GrpcClient},
-    store::TransactionFilter,
-    transaction::{OutputNote, TransactionId, TransactionRequestBuilder, TransactionStatus},
-    Client, ClientError, Felt, Word,
+    node::{connect, FaucetNode, TransactionCost},
+    tx::{parse_consume_mode, ConsumeMode},
+    watcher::BlockWatcher,
+    FaucetError,
 };
-use miden_client_sqlite_store::ClientBuilderSqliteExt;
-use miden_lib::note::create_mint_note;
-use rand::RngCore;
-
-fn create_p2id_note_exact(
-    sender: AccountId,
-    target: AccountId,
-    assets: Vec<Asset>,
-    note_type: NoteType,
-    aux: Felt,
-    serial_num: Word,
-) -> Result<Note, NoteError> {
-    let note_script = WellKnownNote::P2ID.script();
-    let note_inputs = NoteInputs::new(vec![target.suffix(), target.prefix().as_felt()])?;
-    let recipient = NoteRecipient::new(serial_num, note_script, note_inputs);
-
-    let tag = NoteTag::from_account_id(target);
-
-    let metadata = NoteMetadata::new(sender, note_type, tag, NoteExecutionHint::always(), aux)?;
-    let vault = NoteAssets::new(assets)?;
-
-    Ok(Note::new(vault, metadata, recipient))
+use tokio::{sync::Mutex, task::LocalSet};
+
+/// Mints tokens to an existing wallet, or to a new wallet for Alice, and consumes them.
+///
+/// The mint runs as a flow, see `network-faucet flows`: if it stops half-way, `flows resume`
+/// continues it instead of minting again.
+#[derive(Debug, Parser)]
+struct Args {
+    /// Wallet ID or label of the recipient, managed by this client. Defaults to the default
+    /// account; a new wallet is created when neither is set.
+    #[arg(long)]
+    recipient: Option<String>,
+    /// Tokens to mint, within the bounds of the `[mint]` section. Defaults to
+    /// `mint.default_amount`.
+    #[arg(long)]
+    amount: Option<u64>,
+    /// Timelock the minted note, so it can only be consumed from block N on. The consume step
+    /// waits for that block.
+    #[arg(long, value_name = "N")]
+    unlock_after_block: Option<u32>,
+    /// Type of the minted note, `public` or `private`. Private notes are also exported to
+    /// `mint-<id>.mno` once committed, for wallets that cannot find them through sync.
+    #[arg(long, value_name = "TYPE", value_parser = parse_note_type)]
+    note_type: Option<NoteType>,
+    /// Campaign the mint is attributed to and checked against, see `campaign create`.
+    #[arg(long, value_name = "ID")]
+    campaign: Option<u32>,
+    /// Tag of the minted note: `account_id`, `network` for the network to consume it into a
+    /// network account recipient, or `use_case:<id>[:<payload>]`. Notes tagged for network
+    /// execution are not consumed by this client; the mint waits for the network instead.
+    #[arg(long, value_name = "TAG")]
+    note_tag: Option<NoteTagStrategy>,
+    /// Time allowed for the consumed note to show up on chain once the consume transaction is
+    /// committed.
+    #[arg(long, default_value_t = 60)]
+    claim_timeout_secs: u64,
+    /// How the minted note is consumed: `auto` with its inclusion proof once it is committed and
+    /// without while racing the chain, `authenticated` only once committed, or `unauthenticated`.
+    #[arg(long, value_name = "MODE", default_value = "auto", value_parser = parse_consume_mode)]
+    consume: ConsumeMode,
+    /// Hex seed of the client RNG, to reproduce account IDs and note commitments exactly.
+    #[arg(long, value_name = "HEX")]
+    seed: Option<String>,
+    /// Execute the mint transaction and print its cost without submitting it. Needs a recipient
+    /// or a default account, no wallet is created for Alice.
+    #[arg(long)]
+    dry_run: bool,
 }
 
-/// Waits for a transaction to be committed by the network.
-async fn wait_for_transaction<AUTH: TransactionAuthenticator + Sync + 'static>(
-    client: &mut Client<AUTH>,
-    transaction_id: TransactionId,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    loop {
-        client.sync_state().await?;
-
-        let tracked_transaction = client
-            .get_transactions(TransactionFilter::Ids(vec![transaction_id]))
-            .await
-            .map_err(|err| {
-                format!(
-                    "Failed to fetch transaction status while waiting for commitment: {}",
-                    err
-                )
-            })?
-            .pop()
-            .ok_or_else(|| {
-                format!(
-                    "Transaction with ID {} not found while waiting for commitment",
-                    transaction_id
-                )
-            })?;
-
-        match tracked_transaction.status {
-            TransactionStatus::Committed { block_number, .. } => {
-                println!("Transaction committed at block {block_number}.");
-                return Ok(());
-            }
-            TransactionStatus::Pending => {
-                tokio::time::sleep(Duration::from_secs(1)).await;
-            }
-            TransactionStatus::Discarded(cause) => {
-                return Err(format!(
-                    "Transaction was discarded while waiting for commitment. Cause: {cause:?}"
-                )
-                .into());
-            }
-        }
-    }
+#[tokio::main]
+async fn main() -> Result<(), FaucetError> {
+    let args = Args::parse();
+    LocalSet::new().run_until(run(args)).await
 }
 
-#[tokio::main]
-async fn main() -> Result<(), ClientError> {
+async fn run(args: Args) -> Result<(), FaucetError> {
     // Initialize client & keystore
-    // let endpoint = Endpoint::new("http".into(), "localhost".into(), Some(57291));
-    let endpoint = Endpoint::testnet();
-    let timeout_ms = 10_000;
-    let rpc_client = Arc::new(GrpcClient::new(&endpoint, timeout_ms));
-    let keystore: FilesystemKeyStore<rand::prelude::StdRng> =
-        FilesystemKeyStore::new("./keystore".into()).unwrap().into();
-
-    let mut client = ClientBuilder::new()
-        .rpc(rpc_client)
-        .sqlite_store("./store.sqlite3".into())
-        .authenticator(keystore.clone().into())
-        .in_debug_mode(true.into())
-        .build()
-        .await?;
-
-    let sync_summary = client.sync_state().await.unwrap();
-    println!("Latest block: {}", sync_summary.block_num);
-
-    //------------------------------------------------------------
-    // STEP 1: Create a basic wallet for Alice
-    //------------------------------------------------------------
-    println!("\n[STEP 1] Creating a new account for Alice");
-
-    // Account seed
-    let mut init_seed = [0_u8; 32];
-    client.rng().fill_bytes(&mut init_seed);
-    let alice_key_pair = SecretKey::with_rng(client.rng());
-
-    // Build the account
-    let builder = AccountBuilder::new(init_seed)
-        .account_type(AccountType::RegularAccountUpdatableCode)
-        .storage_mode(AccountStorageMode::Public)
-        .with_auth_component(AuthRpoFalcon512::new(
-            alice_key_pair.public_key().to_commitment().into(),
-        ))
-        .with_component(BasicWallet);
-
-    let mut alice_account = builder.build().unwrap();
-
-    // Add the account to the client
-    client.add_account(&alice_account, false).await?;
-
-    // Add the key pair to the keystore
-    keystore
-        .add_key(&AuthSecretKey::RpoFalcon512(alice_key_pair))
-        .unwrap();
+    let mut config = Config::load()?;
+    config.seed = args.seed.as_deref().map(parse_seed).transpose()?;
+    // Checked before anything touches the node, so a bad amount fails fast.
+    let amount = config.mint.resolve(args.amount)?;
+    let mut node = connect(&config).await?;
+    let ledger = Ledger::open(&config.ledger_path)?;
+
+    let latest_block = node.sync_state().await?;
+    println!("Latest block: {latest_block}");
+
+    let recipient = match &args.recipient {
+        Some(recipient) => Some(resolve_account_id(
+            &ledger,
+            recipient,
+            config.account_network().as_ref(),
+        )?),
+        None => ledger.default_account()?,
+    };
 
-    println!(
-        "Alice account created and added to client, ID: {:?}",
-        alice_account.id()
-    );
+    let node = Rc::new(Mutex::new(node));
+    let watcher = BlockWatcher::spawn(node.clone(), config.poll.sync_interval());
 
-    //------------------------------------------------------------
-    // STEP 2: Define the network faucet account ID
-    //------------------------------------------------------------
+    // The network faucet minting the tokens
     let faucet_account_id = AccountId::from_hex("0xd8e3fa793ea82360734ec91a98e798").unwrap();
-    let faucet_details = client.get_account(faucet_account_id.into()).await.unwrap();
-
-    let faucet = if let Some(account_record) = faucet_details {
-        // Clone the account to get an owned instance
-        let account = account_record.account().clone();
-        println!(
-            "Account details: {:?}",
-            account.storage().slots().first().unwrap()
-        );
-        account
-    } else {
-        panic!("Faucet not found!");
-    };
 
     //------------------------------------------------------------
-    // STEP 4: Issue MINT note from network faucet to alice
+    // Create a wallet for Alice unless a recipient is given, then issue a MINT note from the
+    // network faucet and consume its output note
     //------------------------------------------------------------
+    let flow = MintFlow::new(faucet_account_id, recipient, amount)
+        .with_unlock_block(args.unlock_after_block)
+        .with_note_type(args.note_type)
+        .with_campaign(args.campaign)
+        .with_note_tag(args.note_tag)
+        .with_consume(args.consume, Duration::from_secs(args.claim_timeout_secs));
+    if args.dry_run {
+        let recipient = recipient.ok_or_else(|| {
+            FaucetError::Config("--dry-run needs --recipient or a default account".into())
+        })?;
+        check_recipient(faucet_account_id, recipient)?;
+        let mint = BatchMint {
+            recipient,
+            amount,
+            options: flow.options()?,
+        };
+        check_campaign_mints(
+            &ledger,
+            faucet_account_id,
+            std::slice::from_ref(&mint),
+            unix_now(),
+        )?;
+        let cost =
+            estimate_mint_batch(&mut *node.lock().await, faucet_account_id, vec![mint]).await?;
+        print_cost("Estimated MINT TX cost", cost);
+        return Ok(());
+    }
+    let id = start_flow(&ledger, &Flow::Mint(flow))?;
+    println!("\nStarted mint flow {id}");
+
+    let runner = FlowRunner::new(node.clone(), &watcher, &ledger, cli_actor())
+        .with_lock_dir(config.lock_dir());
+    let flow = runner.resume(id).await.inspect_err(|_| {
+        eprintln!("Mint flow {id} stopped, continue it with `network-faucet flows resume {id}`")
+    })?;
+    let Flow::Mint(minted) = flow else {
+        unreachable!("flow {id} was started as a mint flow");
+    };
 
-    let stored_owner_word = faucet.storage().get_item(2).unwrap();
-    let stored_owner_id = AccountId::new_unchecked([stored_owner_word[3], stored_owner_word[2]]);
-
-    // Compute the output P2ID note
-    let amount = 50;
-    let mint_asset = FungibleAsset::new(faucet.id(), amount).unwrap().into();
-    let aux = Felt::new(27);
-    let serial_num = client.rng().draw_word();
-
-    let output_note_tag = NoteTag::from_account_id(alice_account.id());
-    let p2id_mint_output_note = create_p2id_note_exact(
-        faucet_account_id,
-        alice_account.id(),
-        vec![mint_asset],
-        NoteType::Private,
-        aux,
-        serial_num,
-    )?;
-
-    println!(
-        "P2ID OUTPUT NOTE COMMITMENT: {:?}",
-        p2id_mint_output_note.commitment().to_hex()
-    );
-
-    let recipient = p2id_mint_output_note.recipient().digest();
-
-    let mint_note = create_mint_note(
-        faucet.id(),
-        stored_owner_id.into(),
-        recipient,
-        output_note_tag.into(),
-        Felt::new(amount),
-        aux,
-        aux,
-        client.rng(),
-    )?;
-
-    println!(
-        "MINT NOTE COMMITMENT: {:?}",
-        mint_note.commitment().to_hex()
-    );
-
-    let mint_transaction_request = TransactionRequestBuilder::new()
-        .own_output_notes(vec![OutputNote::Full(mint_note)])
-        .build()
-        .unwrap();
-
-    let mint_transaction_id = client
-        .submit_new_transaction(stored_owner_id, mint_transaction_request)
-        .await
-        .unwrap();
-
-    println!(
-        "MINT TX successfully submitted: {:?}",
-        mint_transaction_id.to_hex()
-    );
-
-    println!("Waiting for MINT transaction to be committed...");
-
-    // tokio::time::sleep(std::time::Duration::from_secs(15)).await;
-    wait_for_transaction(&mut client, mint_transaction_id)
-        .await
-        .unwrap();
-
-    // Craft transaction to consume the newly created P2ID note
-    let consume_p2id_note_transaction_request = TransactionRequestBuilder::new()
-        .unauthenticated_input_notes(vec![(p2id_mint_output_note, None)])
-        .build()
-        .unwrap();
-
-    let consume_transaction_id = client
-        .submit_new_transaction(alice_account.id(), consume_p2id_note_transaction_request)
-        .await
-        .unwrap();
-
-    println!(
-        "CONSUME TX successfully submitted: {:?}",
-        consume_transaction_id.to_hex()
-    );
-
-    println!("Waiting for CONSUME transaction to be committed...");
-
-    wait_for_transaction(&mut client, consume_transaction_id)
-        .await
-        .unwrap();
-
-    client.sync_state().await.unwrap();
-
-    alice_account = client
-        .get_account(alice_account.id())
-        .await
-        .unwrap()
-        .unwrap()
-        .into();
+    let alice_id = parse_account_id(minted.recipient.as_deref().unwrap_or_default(), None)?;
+    println!("Minted {amount} tokens to {alice_id}");
+    if let Some(path) = &minted.note_path {
+        println!("Exported the private note to {}", path.display());
+    }
+    if alice_id.storage_mode() == AccountStorageMode::Network {
+        if let Some(executed_at) = minted.claimed_at {
+            println!("Note executed by the network at block {executed_at}");
+        }
+        // The client does not track network accounts.
+        return Ok(());
+    }
+    if let Some(claimed_at) = minted.claimed_at {
+        println!("Note claimed at block {claimed_at}");
+    }
 
     // print vault assets
-    let asset_balance = alice_account
-        .vault()
-        .get_balance(faucet_account_id)
-        .unwrap();
+    let asset_balance = get_balance(&mut *node.lock().await, alice_id, faucet_account_id).await?;
     println!("Vault assets: {:?}", asset_balance);
 
     Ok(())
 }
+
+fn print_cost(title: &str, cost: TransactionCost) {
+    println!("{title}: fee {}, {} cycles", cost.fee, cost.cycles);
+}
@@ -0,0 +1,85 @@
+//! Cached faucet state.
+//!
+//! Every mint needs the owner of the faucet and its pause flag, both read from the faucet account
+//! in the store. The store only changes them when it syncs, so [`FaucetCache`] keeps a
+//! [`FaucetSnapshot`] of the account until the chain tip moves instead of reading the account for
+//! every mint.
+
+use std::cell::Cell;
+
+use miden_client::account::AccountId;
+use miden_objects::block::BlockNumber;
+
+use crate::{mint::owner_of, node::FaucetNode, pause::paused_flag, FaucetError};
+
+/// The state of a network faucet a mint needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaucetSnapshot {
+    pub faucet_id: AccountId,
+    /// Owner the MINT notes are sent from.
+    pub owner_id: AccountId,
+    pub paused: bool,
+}
+
+impl FaucetSnapshot {
+    /// Reads the faucet `faucet_id` from the store.
+    ///
+    /// The faucet must be tracked by `node`.
+    pub async fn read<N: FaucetNode>(
+        node: &mut N,
+        faucet_id: AccountId,
+    ) -> Result<Self, FaucetError> {
+        let faucet = node
+            .get_account(faucet_id)
+            .await?
+            .ok_or(FaucetError::AccountNotFound(faucet_id))?;
+        Ok(Self {
+            faucet_id,
+            owner_id: owner_of(&faucet)?,
+            paused: paused_flag(&faucet),
+        })
+    }
+}
+
+/// [`FaucetSnapshot`] of one faucet, read again once the chain tip moves.
+pub struct FaucetCache {
+    faucet_id: AccountId,
+    /// The snapshot and the chain tip it was read at.
+    cached: Cell<Option<(FaucetSnapshot, BlockNumber)>>,
+}
+
+impl FaucetCache {
+    pub fn new(faucet_id: AccountId) -> Self {
+        Self {
+            faucet_id,
+            cached: Cell::new(None),
+        }
+    }
+
+    pub fn faucet_id(&self) -> AccountId {
+        self.faucet_id
+    }
+
+    /// The snapshot of the faucet at the chain tip `tip`, e.g. the one of a
+    /// [`BlockWatcher`](crate::watcher::BlockWatcher), read from `node` unless cached at the same
+    /// tip. Nothing is cached while the tip is unknown.
+    pub async fn get<N: FaucetNode>(
+        &self,
+        node: &mut N,
+        tip: Option<BlockNumber>,
+    ) -> Result<FaucetSnapshot, FaucetError> {
+        if let (Some((snapshot, read_at)), Some(tip)) = (self.cached.get(), tip) {
+            if read_at == tip {
+                return Ok(snapshot);
+            }
+        }
+        let snapshot = FaucetSnapshot::read(node, self.faucet_id).await?;
+        self.cached.set(tip.map(|tip| (snapshot, tip)));
+        Ok(snapshot)
+    }
+
+    /// Drops the snapshot, e.g. after a transaction against the faucet was applied to the store.
+    pub fn invalidate(&self) {
+        self.cached.set(None);
+    }
+}
@@ -0,0 +1,165 @@
+//! Distribution campaigns.
+//!
+//! A campaign groups mints of one faucet under a name, e.g. a hackathon or a partner onboarding,
+//! with a budget of tokens, an optional limit per recipient and an optional time window.
+//! Campaigns are persisted in the [`Ledger`] and managed with `campaign create/list/close`.
+//!
+//! A mint names its campaign in the aux value of its notes, see [`AuxData`](crate::mint::AuxData),
+//! so the ledger attributes it without a column of its own. Before mints are submitted,
+//! [`check_campaign_mints`] checks them against the window, budget and per-user limit of their
+//! campaigns.
+
+use std::{collections::BTreeMap, fmt};
+
+use miden_client::account::AccountId;
+
+use crate::{
+    ledger::{CampaignRecord, Ledger},
+    mint::BatchMint,
+    FaucetError,
+};
+
+/// Whether a campaign mints at a given time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CampaignStatus {
+    /// The window of the campaign has not started yet.
+    Scheduled,
+    Active,
+    /// The window of the campaign is over.
+    Ended,
+    /// Closed by the operator.
+    Closed,
+}
+
+impl CampaignStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Scheduled => "scheduled",
+            Self::Active => "active",
+            Self::Ended => "ended",
+            Self::Closed => "closed",
+        }
+    }
+}
+
+impl fmt::Display for CampaignStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl CampaignRecord {
+    /// Status of the campaign at `now`.
+    pub fn status(&self, now: u64) -> CampaignStatus {
+        if self.closed_at.is_some() {
+            CampaignStatus::Closed
+        } else if self.starts_at.is_some_and(|starts_at| now < starts_at) {
+            CampaignStatus::Scheduled
+        } else if self.ends_at.is_some_and(|ends_at| now >= ends_at) {
+            CampaignStatus::Ended
+        } else {
+            CampaignStatus::Active
+        }
+    }
+}
+
+/// Checks the name of a new campaign: ASCII letters, digits, `-` and `_`, not only digits so it
+/// cannot be mistaken for a campaign ID.
+pub fn validate_campaign_name(name: &str) -> Result<(), FaucetError> {
+    let reason = if name.is_empty() {
+        "names cannot be empty"
+    } else if name.chars().all(|c| c.is_ascii_digit()) {
+        "names cannot be only digits"
+    } else if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        "names may only contain ASCII letters, digits, `-` and `_`"
+    } else {
+        return Ok(());
+    };
+    Err(FaucetError::InvalidCampaign(
+        name.to_string(),
+        reason.into(),
+    ))
+}
+
+/// The campaign named `input`, or with ID `input`.
+pub fn resolve_campaign(ledger: &Ledger, input: &str) -> Result<CampaignRecord, FaucetError> {
+    let campaign = match input.parse::<u32>() {
+        Ok(id) => ledger.campaign(id)?,
+        Err(_) => ledger.campaign_by_name(input)?,
+    };
+    campaign
+        .ok_or_else(|| FaucetError::InvalidCampaign(input.to_string(), "no such campaign".into()))
+}
+
+/// Checks `mints` of `faucet_id` against their campaigns at `now`, as if all of them were
+/// submitted together. Mints without campaign are not checked.
+///
+/// Fails with [`FaucetError::InvalidCampaign`] for unknown campaigns and campaigns of other
+/// faucets, [`FaucetError::CampaignInactive`] outside the window of the campaign or once it is
+/// closed, and [`FaucetError::CampaignBudgetExhausted`] or [`FaucetError::CampaignUserLimit`] when
+/// the mints would exceed its budget or the limit of a recipient.
+pub fn check_campaign_mints(
+    ledger: &Ledger,
+    faucet_id: AccountId,
+    mints: &[BatchMint],
+    now: u64,
+) -> Result<(), FaucetError> {
+    let mut requested: BTreeMap<u32, BTreeMap<AccountId, u64>> = BTreeMap::new();
+    for mint in mints {
+        let campaign_id = mint.options.aux.campaign_id;
+        if campaign_id != 0 {
+            *requested
+                .entry(campaign_id)
+                .or_default()
+                .entry(mint.recipient)
+                .or_default() += mint.amount;
+        }
+    }
+
+    for (campaign_id, recipients) in requested {
+        let campaign = ledger.campaign(campaign_id)?.ok_or_else(|| {
+            FaucetError::InvalidCampaign(campaign_id.to_string(), "no such campaign".into())
+        })?;
+        if campaign.faucet_id != faucet_id {
+            return Err(FaucetError::InvalidCampaign(
+                campaign.name,
+                format!("the campaign mints from faucet {}", campaign.faucet_id),
+            ));
+        }
+        let status = campaign.status(now);
+        if status != CampaignStatus::Active {
+            return Err(FaucetError::CampaignInactive(
+                campaign.name,
+                status.to_string(),
+            ));
+        }
+
+        let remaining = campaign
+            .budget
+            .saturating_sub(ledger.campaign_spent(campaign.id, None)?);
+        if recipients.values().sum::<u64>() > remaining {
+            return Err(FaucetError::CampaignBudgetExhausted {
+                campaign: campaign.name,
+                remaining,
+            });
+        }
+        let Some(limit) = campaign.per_user_limit else {
+            continue;
+        };
+        for (recipient, amount) in recipients {
+            let remaining =
+                limit.saturating_sub(ledger.campaign_spent(campaign.id, Some(recipient))?);
+            if amount > remaining {
+                return Err(FaucetError::CampaignUserLimit {
+                    campaign: campaign.name,
+                    recipient,
+                    remaining,
+                });
+            }
+        }
+    }
+    Ok(())
+}
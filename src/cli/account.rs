@@ -0,0 +1,192 @@
+use clap::Subcommand;
+use miden_objects::block::BlockNumber;
+use network_faucet::{
+    account::validate_label,
+    config::Config,
+    history::{account_diff, snapshot_account, Change},
+    ledger::Ledger,
+    node::{connect, FaucetNode},
+    watch::{unwatch_account, watch_account, watch_reports},
+    FaucetError,
+};
+
+use super::resolve_account;
+
+#[derive(Debug, Subcommand)]
+pub enum AccountCommand {
+    /// Attach a label to an account, so it can be given in place of its ID.
+    Label {
+        /// Account ID or existing label.
+        account: String,
+        /// New label, e.g. `alice` or `mde-faucet`.
+        label: String,
+    },
+    /// Remove a label.
+    Unlabel { label: String },
+    /// List the labels and their accounts.
+    Labels,
+    /// Use an account when a command that needs a wallet is given none.
+    SetDefault {
+        /// Account ID or label.
+        account: String,
+    },
+    /// Stop falling back to a default account.
+    ClearDefault,
+    /// Print the default account.
+    Default,
+    /// Show the nonce, vault and storage changes of an account between two blocks.
+    ///
+    /// States are recorded by `serve` on every block and by this command; each block is compared
+    /// with the latest state recorded at or before it.
+    Diff {
+        /// Account ID or label, tracked by this client.
+        account: String,
+        #[arg(long)]
+        from_block: u32,
+        /// Defaults to the current state.
+        #[arg(long)]
+        to_block: Option<u32>,
+    },
+    /// Track a public account without its keys, to report its balances and claims.
+    ///
+    /// The client never holds a key of a watched account, so nothing can be spent from it.
+    Watch {
+        /// Account ID or label.
+        account: String,
+    },
+    /// Stop reporting on a watched account.
+    Unwatch {
+        /// Account ID or label.
+        account: String,
+    },
+    /// Print the balances of the watched accounts and the mints they received and claimed.
+    Watched,
+}
+
+impl AccountCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        match self {
+            Self::Label { account, label } => {
+                validate_label(&label)?;
+                let account_id = resolve_account(config, &account)?;
+                Ledger::open(&config.ledger_path)?.set_label(&label, account_id)?;
+                println!("{label} -> {account_id}");
+                Ok(())
+            }
+            Self::Unlabel { label } => {
+                if !Ledger::open(&config.ledger_path)?.remove_label(&label)? {
+                    return Err(FaucetError::InvalidLabel(label, "no such label".into()));
+                }
+                println!("Removed label {label}");
+                Ok(())
+            }
+            Self::SetDefault { account } => {
+                let account_id = resolve_account(config, &account)?;
+                Ledger::open(&config.ledger_path)?.set_default_account(Some(account_id))?;
+                println!("Default account: {account_id}");
+                Ok(())
+            }
+            Self::ClearDefault => {
+                Ledger::open(&config.ledger_path)?.set_default_account(None)?;
+                println!("Default account cleared");
+                Ok(())
+            }
+            Self::Default => {
+                match Ledger::open(&config.ledger_path)?.default_account()? {
+                    Some(account_id) => println!("{account_id}"),
+                    None => println!("No default account set"),
+                }
+                Ok(())
+            }
+            Self::Diff {
+                account,
+                from_block,
+                to_block,
+            } => {
+                let account_id = resolve_account(config, &account)?;
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let mut node = connect(config).await?;
+                let tip = node.sync_state().await?;
+                snapshot_account(&mut node, &ledger, account_id, tip).await?;
+
+                let to_block = to_block.map_or(tip, BlockNumber::from);
+                let diff =
+                    account_diff(&ledger, account_id, BlockNumber::from(from_block), to_block)?;
+                println!(
+                    "{account_id}: state of block {} -> state of block {}",
+                    diff.from_block, diff.to_block
+                );
+                if diff.is_empty() {
+                    println!("No changes");
+                    return Ok(());
+                }
+                println!("Nonce: {} -> {}", diff.nonce.0, diff.nonce.1);
+                print_changes("Vault", &diff.vault);
+                print_changes("Storage", &diff.storage);
+                Ok(())
+            }
+            Self::Watch { account } => {
+                let account_id = resolve_account(config, &account)?;
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let mut node = connect(config).await?;
+                node.sync_state().await?;
+                if watch_account(&mut node, &ledger, account_id).await? {
+                    println!("Watching {account_id}");
+                } else {
+                    println!("{account_id} is already watched");
+                }
+                Ok(())
+            }
+            Self::Unwatch { account } => {
+                let account_id = resolve_account(config, &account)?;
+                if !unwatch_account(&Ledger::open(&config.ledger_path)?, account_id)? {
+                    return Err(FaucetError::AccountNotFound(account_id));
+                }
+                println!("Stopped watching {account_id}");
+                Ok(())
+            }
+            Self::Watched => {
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let mut node = connect(config).await?;
+                node.sync_state().await?;
+                for report in watch_reports(&mut node, &ledger).await? {
+                    println!("{} (nonce {})", report.account_id, report.nonce);
+                    for (faucet_id, amount) in &report.balances {
+                        println!("  {amount} of faucet {faucet_id}");
+                    }
+                    let mints = &report.mints;
+                    print!(
+                        "  {} mints of {} tokens, {} claimed ({} tokens)",
+                        mints.mints, mints.minted_amount, mints.claimed, mints.claimed_amount
+                    );
+                    match mints.last_claim_block {
+                        Some(block_num) => println!(", last in block {block_num}"),
+                        None => println!(),
+                    }
+                }
+                Ok(())
+            }
+            Self::Labels => {
+                for (label, account_id) in Ledger::open(&config.ledger_path)?.labels()? {
+                    println!("{label:<20} {account_id}");
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn print_changes(title: &str, changes: &[Change]) {
+    if changes.is_empty() {
+        return;
+    }
+    println!("{title}:");
+    for change in changes {
+        println!(
+            "  {}: {} -> {}",
+            change.key,
+            change.before.as_deref().unwrap_or("-"),
+            change.after.as_deref().unwrap_or("-")
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use clap::Subcommand;
+use network_faucet::{
+    audit::{cli_actor, AuditEntry},
+    campaign::{resolve_campaign, validate_campaign_name},
+    config::Config,
+    ledger::{unix_now, Ledger},
+    schedule::{format_interval, parse_interval},
+    FaucetError,
+};
+
+use super::resolve_account;
+
+#[derive(Debug, Subcommand)]
+pub enum CampaignCommand {
+    /// Create a campaign that mints named by it are checked against, e.g. a hackathon.
+    Create {
+        /// Name of the campaign, e.g. `hackathon-2025`.
+        name: String,
+        /// Faucet the campaign mints from, defaults to `service.faucet_id`.
+        #[arg(long)]
+        faucet: Option<String>,
+        /// Tokens the campaign may mint in total.
+        #[arg(long)]
+        budget: u64,
+        /// Tokens a single recipient may receive from the campaign, unlimited when omitted.
+        #[arg(long)]
+        per_user: Option<u64>,
+        /// Delay before the campaign starts minting, e.g. `1d`; right away when omitted.
+        #[arg(long, value_parser = parse_interval)]
+        start_in: Option<Duration>,
+        /// How long the campaign mints once started, e.g. `2w`; until closed when omitted.
+        #[arg(long, value_parser = parse_interval)]
+        duration: Option<Duration>,
+    },
+    /// List the campaigns with their status and spent budget.
+    List {
+        /// Only list the campaigns of this faucet.
+        #[arg(long)]
+        faucet: Option<String>,
+    },
+    /// Stop a campaign from minting.
+    Close {
+        /// Name or ID of the campaign.
+        campaign: String,
+    },
+}
+
+impl CampaignCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        match self {
+            Self::Create {
+                name,
+                faucet,
+                budget,
+                per_user,
+                start_in,
+                duration,
+            } => {
+                validate_campaign_name(&name)?;
+                if per_user.is_some_and(|per_user| per_user > budget) {
+                    return Err(FaucetError::InvalidCampaign(
+                        name,
+                        "the per-user limit exceeds the budget".into(),
+                    ));
+                }
+                let faucet = faucet
+                    .or_else(|| config.service.faucet_id.clone())
+                    .ok_or_else(|| {
+                        FaucetError::Config("no faucet given and service.faucet_id unset".into())
+                    })?;
+                let faucet_id = resolve_account(config, &faucet)?;
+                let starts_at = unix_now() + start_in.unwrap_or_default().as_secs();
+                let ends_at = duration.map(|duration| starts_at + duration.as_secs());
+
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let id = ledger.add_campaign(
+                    &name,
+                    faucet_id,
+                    budget,
+                    per_user,
+                    start_in.map(|_| starts_at),
+                    ends_at,
+                )?;
+                ledger.append_audit(
+                    &AuditEntry::new(cli_actor(), "campaign.create")
+                        .account(faucet_id)
+                        .param("campaign_id", id)
+                        .param("name", &name)
+                        .param("budget", budget)
+                        .param("per_user_limit", per_user)
+                        .param("starts_at", starts_at)
+                        .param("ends_at", ends_at),
+                )?;
+                println!("Campaign {id}: `{name}` mints up to {budget} from {faucet_id}");
+                if let Some(duration) = duration {
+                    println!("  for {}", format_interval(duration));
+                }
+                println!("Mint for it with `--campaign {id}` or `\"campaign_id\": {id}`");
+                Ok(())
+            }
+            Self::List { faucet } => {
+                let faucet = faucet
+                    .as_deref()
+                    .map(|faucet| resolve_account(config, faucet))
+                    .transpose()?;
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let now = unix_now();
+                let stats = ledger.campaign_stats(faucet)?;
+                for (campaign, stats) in ledger.campaigns(faucet)?.into_iter().zip(stats) {
+                    let per_user = campaign
+                        .per_user_limit
+                        .map_or("-".to_string(), |limit| limit.to_string());
+                    println!(
+                        "{:<5} {:<24} {:<9} {:>12} / {:<12} per user {:<8} {} recipient(s)",
+                        campaign.id,
+                        campaign.name,
+                        campaign.status(now).as_str(),
+                        stats.spent_amount,
+                        campaign.budget,
+                        per_user,
+                        stats.unique_recipients,
+                    );
+                }
+                Ok(())
+            }
+            Self::Close { campaign } => {
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let campaign = resolve_campaign(&ledger, &campaign)?;
+                if !ledger.close_campaign(campaign.id, unix_now())? {
+                    return Err(FaucetError::InvalidCampaign(
+                        campaign.name,
+                        "the campaign is closed already".into(),
+                    ));
+                }
+                ledger.append_audit(
+                    &AuditEntry::new(cli_actor(), "campaign.close")
+                        .account(campaign.faucet_id)
+                        .param("campaign_id", campaign.id),
+                )?;
+                println!("Closed campaign {} `{}`", campaign.id, campaign.name);
+                Ok(())
+            }
+        }
+    }
+}
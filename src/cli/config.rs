@@ -0,0 +1,47 @@
+use std::path::Path;
+
+use clap::Subcommand;
+use network_faucet::{config::Config, FaucetError};
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Check the configuration without contacting the network and print its effective values.
+    Validate,
+}
+
+impl ConfigCommand {
+    pub async fn execute(self, config: &Config, path: Option<&Path>) -> Result<(), FaucetError> {
+        match self {
+            Self::Validate => {
+                match path.map(Path::to_path_buf).or_else(Config::location) {
+                    Some(path) => println!("# Read from {}", path.display()),
+                    None => println!("# No configuration file, using the defaults"),
+                }
+                config.validate()?;
+
+                let mut effective = config.clone();
+                effective.resolve_paths()?;
+                for api_key in &mut effective.api_keys {
+                    api_key.key = "<redacted>".into();
+                }
+                if let Some(github) = &mut effective.github {
+                    github.client_secret = "<redacted>".into();
+                }
+                if let Some(smtp) = &mut effective.smtp {
+                    if smtp.password.is_some() {
+                        smtp.password = Some("<redacted>".into());
+                    }
+                }
+                if let Some(webhook) = &mut effective.webhook {
+                    if webhook.token.is_some() {
+                        webhook.token = Some("<redacted>".into());
+                    }
+                }
+                let document = toml::to_string_pretty(&effective)
+                    .map_err(|err| FaucetError::Config(err.to_string()))?;
+                print!("{document}");
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use clap::Args;
+use network_faucet::{
+    config::Config,
+    doctor::{run_checks, CheckStatus},
+    FaucetError,
+};
+
+/// Checks the environment of the faucet and prints how to fix the problems found.
+#[derive(Debug, Args)]
+pub struct DoctorCommand {}
+
+impl DoctorCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        let checks = run_checks(config).await;
+        for check in &checks {
+            println!(
+                "[{:>4}] {:<8} {}",
+                check.status.as_str(),
+                check.name,
+                check.detail
+            );
+            if let Some(fix) = &check.fix {
+                println!("{:16}{fix}", "");
+            }
+        }
+
+        let failed = checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+            .count();
+        if failed > 0 {
+            return Err(FaucetError::ChecksFailed(failed));
+        }
+        Ok(())
+    }
+}
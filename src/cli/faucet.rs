@@ -0,0 +1,418 @@
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use clap::Subcommand;
+use miden_objects::block::BlockNumber;
+use network_faucet::{
+    audit::{cli_actor, AuditEntry},
+    authz::{authorize_cli, Action},
+    config::Config,
+    decommission::decommission,
+    ledger::{Ledger, StatsGranularity},
+    mint::{burn, rebuild_mint_note},
+    node::{connect, FaucetNode},
+    pause::set_paused,
+    reclaim::reclaim_expired,
+    watcher::{wait_for_transaction, BlockWatcher},
+    FaucetError,
+};
+use tokio::sync::Mutex;
+
+use super::{resolve_account, resolve_account_or_default};
+
+#[derive(Debug, Subcommand)]
+pub enum FaucetCommand {
+    /// Print mint, claim and burn statistics from the ledger.
+    Stats {
+        /// Only count mints of this faucet.
+        #[arg(long)]
+        faucet: Option<String>,
+        /// Report the mints, unique recipients, tokens and failure rate per `hour` or `day` (UTC)
+        /// instead of the totals.
+        #[arg(long)]
+        granularity: Option<StatsGranularity>,
+        /// Report the mints, recipients and tokens of each campaign instead of the totals.
+        #[arg(long, conflicts_with = "granularity")]
+        campaigns: bool,
+        /// Print the report as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Recover the tokens of reclaimable (P2IDE) mints that expired unclaimed.
+    Reclaim {
+        /// Faucet whose expired mints are reclaimed.
+        #[arg(long)]
+        faucet: String,
+        /// Only list the expired mints.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Stop minting from a faucet, by the mint service and through MINT notes.
+    Pause {
+        /// Faucet to pause, tracked by this client.
+        #[arg(long)]
+        faucet: String,
+    },
+    /// Let the mint service mint from a paused faucet again.
+    Unpause {
+        /// Faucet to unpause, tracked by this client.
+        #[arg(long)]
+        faucet: String,
+    },
+    /// Send tokens from a wallet back to the faucet to reduce the issued supply.
+    Burn {
+        /// Faucet that issued the tokens.
+        #[arg(long)]
+        faucet: String,
+        /// Wallet managed by this client holding the tokens, defaults to the default account.
+        #[arg(long)]
+        account: Option<String>,
+        #[arg(long)]
+        amount: u64,
+    },
+    /// Take a faucet out of service: pause it, reclaim its expired mints, write a final report
+    /// and retire its deployment.
+    Decommission {
+        /// Faucet to decommission, tracked by this client.
+        #[arg(long)]
+        faucet: String,
+        /// File the final report is written to as JSON.
+        #[arg(long)]
+        report: PathBuf,
+    },
+    /// List the faucets deployed with `deploy` and the tokens they issue.
+    Deployments {
+        /// Print the deployments as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+impl FaucetCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        match self {
+            Self::Stats {
+                faucet,
+                granularity,
+                campaigns,
+                json,
+            } => {
+                let faucet = faucet
+                    .as_deref()
+                    .map(|faucet| resolve_account(config, faucet))
+                    .transpose()?;
+                let ledger = Ledger::open(&config.ledger_path)?;
+                if let Some(granularity) = granularity {
+                    let buckets = ledger.bucketed_stats(faucet, granularity)?;
+                    if json {
+                        println!("{}", to_json(&buckets));
+                        return Ok(());
+                    }
+                    println!(
+                        "{:<16} {:>8} {:>10} {:>14} {:>8}",
+                        granularity, "mints", "recipients", "tokens", "failed"
+                    );
+                    for bucket in buckets {
+                        println!(
+                            "{:<16} {:>8} {:>10} {:>14} {:>7.1}%",
+                            bucket.label,
+                            bucket.mints,
+                            bucket.unique_recipients,
+                            bucket.amount,
+                            bucket.failure_rate * 100.0
+                        );
+                    }
+                    return Ok(());
+                }
+
+                if campaigns {
+                    let campaigns = ledger.campaign_stats(faucet)?;
+                    if json {
+                        println!("{}", to_json(&campaigns));
+                        return Ok(());
+                    }
+                    println!(
+                        "{:<24} {:>8} {:>10} {:>14} {:>14} {:>14} {:>14}",
+                        "campaign", "mints", "recipients", "budget", "spent", "minted", "delivered"
+                    );
+                    for campaign in campaigns {
+                        println!(
+                            "{:<24} {:>8} {:>10} {:>14} {:>14} {:>14} {:>14}",
+                            campaign.name,
+                            campaign.mints,
+                            campaign.unique_recipients,
+                            campaign.budget,
+                            campaign.spent_amount,
+                            campaign.minted_amount,
+                            campaign.delivered_amount
+                        );
+                    }
+                    return Ok(());
+                }
+
+                let stats = ledger.stats(faucet)?;
+                if json {
+                    println!("{}", to_json(&stats));
+                    return Ok(());
+                }
+
+                println!("Mints submitted:   {}", stats.submitted);
+                println!("  committed:       {}", stats.committed);
+                println!("  failed:          {}", stats.failed);
+                println!(
+                    "  pending:         {}",
+                    stats.submitted - stats.committed - stats.failed
+                );
+                println!("Committed mints:");
+                println!("  delivered:       {}", stats.delivered);
+                println!("  unclaimed:       {}", stats.unclaimed);
+                println!("  reclaimed:       {}", stats.reclaimed);
+                println!("Tokens minted:     {}", stats.minted_amount);
+                println!("Tokens delivered:  {}", stats.delivered_amount);
+                println!("Tokens reclaimed:  {}", stats.reclaimed_amount);
+                println!("Burns committed:   {}", stats.burned);
+                println!("Tokens burned:     {}", stats.burned_amount);
+                println!("Deposits returned: {}", stats.returned);
+                println!("Tokens returned:   {}", stats.returned_amount);
+                println!("Fees paid:         {}", stats.fees_paid);
+                println!("Cycles spent:      {}", stats.cycles_spent);
+                Ok(())
+            }
+            Self::Reclaim { faucet, dry_run } => {
+                if !dry_run {
+                    authorize_cli(config, Action::Reclaim)?;
+                }
+                reclaim(config, &faucet, dry_run).await
+            }
+            Self::Pause { faucet } => {
+                authorize_cli(config, Action::Pause)?;
+                toggle_pause(config, &faucet, true).await
+            }
+            Self::Unpause { faucet } => {
+                authorize_cli(config, Action::Pause)?;
+                toggle_pause(config, &faucet, false).await
+            }
+            Self::Burn {
+                faucet,
+                account,
+                amount,
+            } => {
+                authorize_cli(config, Action::Burn)?;
+                burn_tokens(config, &faucet, account.as_deref(), amount).await
+            }
+            Self::Decommission { faucet, report } => {
+                authorize_cli(config, Action::Decommission)?;
+                decommission_faucet(config, &faucet, &report).await
+            }
+            Self::Deployments { json } => {
+                let deployments = Ledger::open(&config.ledger_path)?.deployments()?;
+                if json {
+                    println!("{}", to_json(&deployments));
+                    return Ok(());
+                }
+                for deployment in deployments {
+                    let retired = if deployment.retired_at.is_some() {
+                        ", retired"
+                    } else {
+                        ""
+                    };
+                    println!(
+                        "{} {:<6} {:>2} decimals, max supply {}, owner {}{retired}",
+                        deployment.faucet_id,
+                        deployment.symbol,
+                        deployment.decimals,
+                        deployment.max_supply,
+                        deployment.owner_id
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+async fn reclaim(config: &Config, faucet: &str, dry_run: bool) -> Result<(), FaucetError> {
+    let faucet_id = resolve_account(config, faucet)?;
+    let ledger = Ledger::open(&config.ledger_path)?;
+    let node = Rc::new(Mutex::new(connect(config).await?));
+    let watcher = BlockWatcher::spawn(node.clone(), config.poll.sync_interval());
+
+    if dry_run {
+        let tip = watcher.wait_for_block(BlockNumber::GENESIS).await?;
+        let expired = ledger.expired_mints(faucet_id, tip.block_num)?;
+        for mint in &expired {
+            let status = match rebuild_mint_note(mint) {
+                Ok(_) => "reclaimable".to_string(),
+                Err(err) => err.to_string(),
+            };
+            println!(
+                "Mint {}: {} tokens to {}, expired at block {}: {status}",
+                mint.id,
+                mint.amount,
+                mint.recipient,
+                mint.reclaim_block.unwrap_or_default()
+            );
+        }
+        let total: u64 = expired.iter().map(|mint| mint.amount).sum();
+        println!("{} expired mints, {total} tokens", expired.len());
+        return Ok(());
+    }
+
+    let report = reclaim_expired(&node, &watcher, &ledger, faucet_id).await?;
+    for mint in &report.reclaimed {
+        let mut entry = AuditEntry::new(cli_actor(), "faucet.reclaim")
+            .account(faucet_id)
+            .param("mint_id", mint.id)
+            .param("amount", mint.amount);
+        entry.transaction_id = mint.reclaim_transaction_id.clone();
+        ledger.append_audit(&entry)?;
+        println!("Reclaimed mint {}: {} tokens", mint.id, mint.amount);
+    }
+    for (mint, err) in &report.failed {
+        eprintln!("Failed to reclaim mint {}: {err}", mint.id);
+    }
+    println!(
+        "Reclaimed {} mints, {} tokens recovered, {} failed",
+        report.reclaimed.len(),
+        report.reclaimed_amount,
+        report.failed.len()
+    );
+    Ok(())
+}
+
+async fn toggle_pause(config: &Config, faucet: &str, paused: bool) -> Result<(), FaucetError> {
+    let faucet_id = resolve_account(config, faucet)?;
+    let ledger = Ledger::open(&config.ledger_path)?;
+    let mut node = connect(config).await?;
+    node.sync_state().await?;
+    let transaction_id = set_paused(&mut node, faucet_id, paused).await?;
+    let action = if paused {
+        "faucet.pause"
+    } else {
+        "faucet.unpause"
+    };
+    ledger.append_audit(
+        &AuditEntry::new(cli_actor(), action)
+            .account(faucet_id)
+            .transaction(transaction_id),
+    )?;
+    println!("Transaction submitted: {}", transaction_id.to_hex());
+
+    let node = Rc::new(Mutex::new(node));
+    let watcher = BlockWatcher::spawn(node.clone(), config.poll.sync_interval());
+    let block_num = wait_for_transaction(&node, &watcher, transaction_id).await?;
+    let state = if paused { "paused" } else { "unpaused" };
+    println!("Faucet {faucet_id} {state} at block {block_num}");
+    Ok(())
+}
+
+async fn decommission_faucet(
+    config: &Config,
+    faucet: &str,
+    report_path: &Path,
+) -> Result<(), FaucetError> {
+    let faucet_id = resolve_account(config, faucet)?;
+    let ledger = Ledger::open(&config.ledger_path)?;
+    let mut node = connect(config).await?;
+    node.sync_state().await?;
+    let node = Rc::new(Mutex::new(node));
+    let watcher = BlockWatcher::spawn(node.clone(), config.poll.sync_interval());
+
+    let report = decommission(&node, &watcher, &ledger, faucet_id).await?;
+    std::fs::write(report_path, to_json(&report))?;
+    let mut entry = AuditEntry::new(cli_actor(), "faucet.decommission")
+        .account(faucet_id)
+        .param("reclaimed", report.reclaimed)
+        .param("reclaimed_amount", report.reclaimed_amount)
+        .param("outstanding", report.outstanding.len())
+        .param("retired", report.retired_at.is_some());
+    entry.transaction_id = report.pause_transaction_id.clone();
+    ledger.append_audit(&entry)?;
+
+    match report.paused_block {
+        Some(block_num) => println!("Faucet {faucet_id} paused at block {block_num}"),
+        None => println!("Faucet {faucet_id} was already paused"),
+    }
+    println!(
+        "Reclaimed {} mints, {} tokens recovered",
+        report.reclaimed, report.reclaimed_amount
+    );
+    for failed in &report.failed_reclaims {
+        eprintln!(
+            "Failed to reclaim mint {}: {}",
+            failed.mint_id, failed.error
+        );
+    }
+    if !report.outstanding.is_empty() {
+        let amount: u64 = report.outstanding.iter().map(|mint| mint.amount).sum();
+        println!(
+            "{} unclaimed mints hold {amount} tokens, reclaim them with `faucet reclaim` once \
+             they expire",
+            report.outstanding.len()
+        );
+    }
+    println!("Final report written to {}", report_path.display());
+    if report.retired_at.is_none() {
+        return Err(FaucetError::DecommissionIncomplete {
+            faucet_id,
+            failed: report.failed_reclaims.len(),
+        });
+    }
+    println!("Faucet {faucet_id} retired");
+    Ok(())
+}
+
+async fn burn_tokens(
+    config: &Config,
+    faucet: &str,
+    account: Option<&str>,
+    amount: u64,
+) -> Result<(), FaucetError> {
+    let faucet_id = resolve_account(config, faucet)?;
+    let account_id = resolve_account_or_default(config, account)?;
+    let ledger = Ledger::open(&config.ledger_path)?;
+
+    let mut node = connect(config).await?;
+    node.sync_state().await?;
+    let outcome = burn(&mut node, account_id, faucet_id, amount).await?;
+    let burn_id = ledger.record_burn(
+        faucet_id,
+        account_id,
+        amount,
+        outcome.transaction_id,
+        &outcome.burn_note,
+    )?;
+    ledger.append_audit(
+        &AuditEntry::new(cli_actor(), "faucet.burn")
+            .account(account_id)
+            .param("faucet_id", faucet_id.to_hex())
+            .param("amount", amount)
+            .param("burn_id", burn_id)
+            .transaction(outcome.transaction_id),
+    )?;
+    println!(
+        "Burn {burn_id} submitted: transaction {}, note {}",
+        outcome.transaction_id.to_hex(),
+        outcome.burn_note.id().to_hex()
+    );
+
+    let node = Rc::new(Mutex::new(node));
+    let watcher = BlockWatcher::spawn(node.clone(), config.poll.sync_interval());
+    match wait_for_transaction(&node, &watcher, outcome.transaction_id).await {
+        Ok(block_num) => {
+            ledger.mark_burn_committed(outcome.transaction_id, block_num)?;
+            println!("Burn {burn_id} committed at block {block_num}");
+            Ok(())
+        }
+        Err(err) => {
+            ledger.mark_burn_failed(outcome.transaction_id, &err.to_string())?;
+            Err(err)
+        }
+    }
+}
+
+fn to_json(value: &impl serde::Serialize) -> String {
+    serde_json::to_string_pretty(value).expect("stats serialize to JSON")
+}
@@ -0,0 +1,174 @@
+use std::{path::PathBuf, rc::Rc};
+
+use clap::Subcommand;
+use network_faucet::{
+    account::validate_label,
+    audit::{cli_actor, AuditEntry},
+    config::Config,
+    fixtures::{create_fixture_wallets, mint_starting_balances, FixtureManifest, FixtureWallet},
+    ledger::{unix_now, Ledger},
+    mint::consume_mint_note,
+    node::{connect, FaucetNode},
+    tx::ConsumeMode,
+    watcher::{wait_for_transaction, BlockWatcher},
+    FaucetError,
+};
+use tokio::sync::Mutex;
+
+use super::resolve_account;
+
+#[derive(Debug, Subcommand)]
+pub enum FixturesCommand {
+    /// Create funded wallets and write a manifest test suites can load.
+    ///
+    /// Each wallet is minted a starting balance and consumes it, like Alice in the `mint` flow.
+    Generate {
+        /// Wallets to create.
+        #[arg(long)]
+        count: usize,
+        /// Faucet of the starting balances, defaults to `service.faucet_id`.
+        #[arg(long)]
+        faucet: Option<String>,
+        /// Starting balance of each wallet, `mint.default_amount` when omitted.
+        #[arg(long)]
+        amount: Option<u64>,
+        /// Label the wallets `<prefix>-1` to `<prefix>-<count>`.
+        #[arg(long)]
+        label_prefix: Option<String>,
+        /// Manifest to write.
+        #[arg(long, default_value = "fixtures.json")]
+        out: PathBuf,
+    },
+}
+
+impl FixturesCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        match self {
+            Self::Generate {
+                count,
+                faucet,
+                amount,
+                label_prefix,
+                out,
+            } => {
+                if count == 0 {
+                    return Err(FaucetError::Config("--count must be positive".into()));
+                }
+                let faucet = faucet
+                    .or_else(|| config.service.faucet_id.clone())
+                    .ok_or_else(|| {
+                        FaucetError::Config("no faucet given and service.faucet_id unset".into())
+                    })?;
+                let faucet_id = resolve_account(config, &faucet)?;
+                let amount = config.mint.resolve(amount)?;
+                let labels: Vec<_> = (1..=count)
+                    .map(|index| {
+                        label_prefix
+                            .as_ref()
+                            .map(|prefix| format!("{prefix}-{index}"))
+                    })
+                    .collect();
+                for label in labels.iter().flatten() {
+                    validate_label(label)?;
+                }
+
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let mut node = connect(config).await?;
+                node.sync_state().await?;
+                let wallets = create_fixture_wallets(&mut node, count).await?;
+                for (wallet, label) in wallets.iter().zip(&labels) {
+                    if let Some(label) = label {
+                        ledger.set_label(label, *wallet)?;
+                    }
+                }
+                println!("Created {count} wallets");
+
+                let batches =
+                    mint_starting_balances(&mut node, faucet_id, &wallets, amount).await?;
+                let node = Rc::new(Mutex::new(node));
+                let watcher = BlockWatcher::spawn(node.clone(), config.poll.sync_interval());
+                let mut notes = Vec::with_capacity(count);
+                for batch in batches {
+                    println!(
+                        "Minting {} starting balances in transaction {}",
+                        batch.p2id_notes.len(),
+                        batch.transaction_id.to_hex()
+                    );
+                    if let Some(cost) = batch.cost {
+                        ledger.record_cost(faucet_id, "mint", batch.transaction_id, cost)?;
+                    }
+                    for (offset, note) in batch.p2id_notes.iter().enumerate() {
+                        let recipient = wallets[notes.len() + offset];
+                        let mint_id = ledger.record_mint(
+                            faucet_id,
+                            recipient,
+                            amount,
+                            batch.transaction_id,
+                            note,
+                        )?;
+                        ledger.append_audit(
+                            &AuditEntry::new(cli_actor(), "mint")
+                                .account(batch.owner_id)
+                                .param("faucet_id", faucet_id.to_hex())
+                                .param("recipient", recipient.to_hex())
+                                .param("amount", amount)
+                                .param("mint_id", mint_id)
+                                .param("note_id", note.id().to_hex())
+                                .transaction(batch.transaction_id),
+                        )?;
+                    }
+                    match wait_for_transaction(&node, &watcher, batch.transaction_id).await {
+                        Ok(block_num) => ledger.mark_committed(batch.transaction_id, block_num)?,
+                        Err(err) => {
+                            ledger.mark_failed(batch.transaction_id, &err.to_string())?;
+                            return Err(err);
+                        }
+                    }
+                    notes.extend(
+                        batch
+                            .p2id_notes
+                            .into_iter()
+                            .map(|note| (batch.transaction_id, note)),
+                    );
+                }
+
+                let mut consumes = Vec::with_capacity(count);
+                for (&wallet, (mint_transaction_id, note)) in wallets.iter().zip(&notes) {
+                    let transaction_id = consume_mint_note(
+                        &mut *node.lock().await,
+                        wallet,
+                        *mint_transaction_id,
+                        note.clone(),
+                        ConsumeMode::Auto,
+                    )
+                    .await?;
+                    consumes.push(transaction_id);
+                }
+                println!("Consuming the starting balances in {count} transactions");
+                for transaction_id in consumes {
+                    wait_for_transaction(&node, &watcher, transaction_id).await?;
+                }
+
+                let manifest = FixtureManifest {
+                    faucet_id: faucet_id.to_hex(),
+                    keystore_path: config.keystore_path.display().to_string(),
+                    created_at: unix_now(),
+                    wallets: wallets
+                        .iter()
+                        .zip(labels)
+                        .zip(&notes)
+                        .map(|((wallet, label), (_, note))| FixtureWallet {
+                            account_id: wallet.to_hex(),
+                            label,
+                            balance: amount,
+                            note_id: note.id().to_hex(),
+                        })
+                        .collect(),
+                };
+                manifest.write(&out)?;
+                println!("Wrote {count} funded wallets to {}", out.display());
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::rc::Rc;
+
+use clap::Subcommand;
+use network_faucet::{
+    audit::cli_actor,
+    config::Config,
+    flow::{Flow, FlowRunner},
+    ledger::Ledger,
+    node::connect,
+    watcher::BlockWatcher,
+    FaucetError,
+};
+use tokio::sync::Mutex;
+
+#[derive(Debug, Subcommand)]
+pub enum FlowsCommand {
+    /// List the deploy and mint flows that have not finished, with the step they stopped at.
+    List {
+        /// List finished flows as well.
+        #[arg(long)]
+        all: bool,
+    },
+    /// Continue a flow from the step it stopped at.
+    Resume { id: i64 },
+}
+
+impl FlowsCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        let ledger = Ledger::open(&config.ledger_path)?;
+        match self {
+            Self::List { all } => {
+                for flow in ledger.flows(all)? {
+                    println!(
+                        "{:<5} {:<6} {:<16} attempts {:<3} updated {}",
+                        flow.id, flow.kind, flow.step, flow.attempts, flow.updated_at,
+                    );
+                    if let Some(error) = flow.last_error {
+                        println!("      failed: {error}");
+                    }
+                }
+                Ok(())
+            }
+            Self::Resume { id } => {
+                let node = Rc::new(Mutex::new(connect(config).await?));
+                let watcher = BlockWatcher::spawn(node.clone(), config.poll.sync_interval());
+                let runner = FlowRunner::new(node, &watcher, &ledger, cli_actor())
+                    .with_lock_dir(config.lock_dir());
+                let flow = runner.resume(id).await?;
+                println!("Flow {id} ({}) is done", flow.kind());
+                match &flow {
+                    Flow::Deploy(deployed) => {
+                        if let Some(faucet_id) = &deployed.faucet_id {
+                            println!("  faucet:      {faucet_id}");
+                        }
+                        if let Some(transaction_id) = &deployed.transaction_id {
+                            println!("  transaction: {transaction_id}");
+                        }
+                    }
+                    Flow::Mint(minted) => {
+                        if let Some(mint_id) = minted.mint_id {
+                            println!("  mint:        {mint_id}");
+                        }
+                        if let Some(claimed_at) = minted.claimed_at {
+                            println!("  claimed at:  block {claimed_at}");
+                        }
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
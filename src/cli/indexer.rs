@@ -0,0 +1,25 @@
+use std::rc::Rc;
+
+use clap::Args;
+use network_faucet::{
+    config::Config, indexer::run_indexer, ledger::Ledger, node::connect, watcher::BlockWatcher,
+    webhook::ClaimWebhook, FaucetError,
+};
+use tokio::sync::Mutex;
+
+#[derive(Debug, Args)]
+pub struct IndexerCommand {}
+
+impl IndexerCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        let ledger = Ledger::open(&config.ledger_path)?;
+        let webhook = config.webhook.clone().map(ClaimWebhook::new).transpose()?;
+        let node = Rc::new(Mutex::new(connect(config).await?));
+        let watcher = BlockWatcher::spawn(node.clone(), config.poll.sync_interval());
+
+        tokio::select! {
+            result = run_indexer(&node, &watcher, &ledger, webhook.as_ref()) => result,
+            result = tokio::signal::ctrl_c() => Ok(result?),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use network_faucet::{
+    audit::{cli_actor, AuditEntry},
+    config::Config,
+    ledger::{Ledger, SCHEMA_VERSION},
+    FaucetError,
+};
+
+/// Upgrades the schema of the ledger in place, after backing it up next to it.
+///
+/// Commands opening the ledger upgrade it too; running `migrate` first keeps the backup and shows
+/// what changes. The client store is upgraded by the Miden client itself.
+#[derive(Debug, Args)]
+pub struct MigrateCommand {
+    /// Only list the pending migrations.
+    #[arg(long)]
+    dry_run: bool,
+    /// Upgrade without writing a backup first.
+    #[arg(long)]
+    no_backup: bool,
+}
+
+impl MigrateCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        let path = &config.ledger_path;
+        if !path.exists() {
+            println!("No ledger at {}, nothing to migrate", path.display());
+            return Ok(());
+        }
+        let ledger = Ledger::open_unmigrated(path)?;
+        let version = ledger.schema_version()?;
+        let pending = ledger.pending_migrations()?;
+        if pending.is_empty() {
+            println!(
+                "Ledger {} is at schema version {version}, nothing to migrate",
+                path.display()
+            );
+            return Ok(());
+        }
+
+        println!(
+            "Ledger {} is at schema version {version}, this build writes version {SCHEMA_VERSION}:",
+            path.display()
+        );
+        for migration in &pending {
+            println!("  {}: {}", migration.version, migration.description);
+        }
+        if self.dry_run {
+            return Ok(());
+        }
+
+        if !self.no_backup {
+            let backup = backup_path(path, version);
+            ledger.backup(&backup)?;
+            println!("Backed up the ledger to {}", backup.display());
+        }
+        ledger.migrate()?;
+        ledger.append_audit(
+            &AuditEntry::new(cli_actor(), "ledger.migrate")
+                .param("from", version)
+                .param("to", SCHEMA_VERSION),
+        )?;
+        println!("Migrated the ledger to schema version {SCHEMA_VERSION}");
+        Ok(())
+    }
+}
+
+/// Backup of the ledger at `path` before upgrading it from `version`, e.g.
+/// `ledger.sqlite3.v0.bak`.
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{version}.bak"));
+    path.with_file_name(name)
+}
@@ -0,0 +1,200 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use miden_client::account::AccountId;
+use network_faucet::{
+    account::{parse_account_id, resolve_account_id},
+    authz::API_KEY_ENV,
+    client::parse_seed,
+    config::Config,
+    ledger::Ledger,
+    FaucetError,
+};
+
+mod account;
+mod audit;
+mod campaign;
+mod config;
+mod doctor;
+mod faucet;
+mod fixtures;
+mod flows;
+mod indexer;
+mod migrate;
+mod note;
+mod openapi;
+mod receipt;
+mod recover;
+mod referral;
+mod returns;
+mod schedule;
+mod script;
+mod serve;
+mod snapshot;
+mod status;
+mod store;
+mod tx;
+mod wallet;
+
+/// Operator CLI for the network faucet.
+#[derive(Debug, Parser)]
+#[command(name = "network-faucet", version)]
+pub struct Cli {
+    /// Configuration file, defaults to `$FAUCET_CONFIG` or `./faucet.toml`.
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Hex seed of the client RNG, to reproduce account IDs and note commitments exactly, e.g.
+    /// in tests and bug reports. Never use it for real funds.
+    #[arg(long, global = true, value_name = "HEX")]
+    seed: Option<String>,
+
+    /// API key whose role allows the guarded commands, e.g. `faucet pause`, once API keys have
+    /// roles. Defaults to `$FAUCET_API_KEY`.
+    #[arg(long, global = true, value_name = "KEY")]
+    api_key: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Label accounts and choose the default account.
+    #[command(subcommand)]
+    Account(account::AccountCommand),
+    /// Inspect and verify the audit log of administrative and minting actions.
+    #[command(subcommand)]
+    Audit(audit::AuditCommand),
+    /// Group mints under campaigns with their own budget, per-user limit and time window.
+    #[command(subcommand)]
+    Campaign(campaign::CampaignCommand),
+    /// Validate the configuration and print its effective values.
+    #[command(subcommand)]
+    Config(config::ConfigCommand),
+    /// Diagnose the node connection, store, keystore, scripts, disk space and clock.
+    Doctor(doctor::DoctorCommand),
+    /// Inspect and operate deployed faucets.
+    #[command(subcommand)]
+    Faucet(faucet::FaucetCommand),
+    /// Create funded test wallets for external test suites.
+    #[command(subcommand)]
+    Fixtures(fixtures::FixturesCommand),
+    /// List the deploy and mint flows and resume those that stopped half-way.
+    #[command(subcommand)]
+    Flows(flows::FlowsCommand),
+    /// Track claims of minted notes until interrupted.
+    Indexer(indexer::IndexerCommand),
+    /// Upgrade the schema of the ledger in place after a crate upgrade.
+    Migrate(migrate::MigrateCommand),
+    /// Export, import and consume notes.
+    #[command(subcommand)]
+    Note(note::NoteCommand),
+    /// Print the OpenAPI document of the REST API.
+    #[command(name = "openapi")]
+    OpenApi(openapi::OpenApiCommand),
+    /// Export and verify signed mint receipts.
+    #[command(subcommand)]
+    Receipt(receipt::ReceiptCommand),
+    /// Restore a wallet key into the keystore from a recovery bundle.
+    Recover(recover::RecoverCommand),
+    /// Hand out referral codes and report their conversions.
+    #[command(subcommand)]
+    Referral(referral::ReferralCommand),
+    /// Collect tokens holders return to the faucet owner.
+    #[command(subcommand)]
+    Returns(returns::ReturnsCommand),
+    /// Manage recurring mints run by `serve`.
+    #[command(subcommand)]
+    Schedule(schedule::ScheduleCommand),
+    /// Validate transaction scripts before using them.
+    #[command(subcommand)]
+    Script(script::ScriptCommand),
+    /// Serve mint requests over the network APIs until interrupted.
+    Serve(serve::ServeCommand),
+    /// Archive the store, ledger and keystore of the host, and restore them.
+    #[command(subcommand)]
+    Snapshot(snapshot::SnapshotCommand),
+    /// Report the sync progress, queue and prover backlog of the running service.
+    Status(status::StatusCommand),
+    /// Maintain the client store.
+    #[command(subcommand)]
+    Store(store::StoreCommand),
+    /// Look up transactions on the explorer of the network.
+    #[command(subcommand)]
+    Tx(tx::TxCommand),
+    /// Operate wallets managed by this client.
+    #[command(subcommand)]
+    Wallet(wallet::WalletCommand),
+}
+
+impl Cli {
+    pub async fn execute(self) -> Result<(), FaucetError> {
+        let mut config = match &self.config {
+            Some(path) => Config::from_file(path)?,
+            None => Config::load()?,
+        };
+        config.seed = self.seed.as_deref().map(parse_seed).transpose()?;
+        config.api_key = self
+            .api_key
+            .or_else(|| std::env::var(API_KEY_ENV).ok())
+            .filter(|key| !key.is_empty());
+
+        match self.command {
+            Command::Account(command) => command.execute(&config).await,
+            Command::Audit(command) => command.execute(&config).await,
+            Command::Campaign(command) => command.execute(&config).await,
+            Command::Config(command) => command.execute(&config, self.config.as_deref()).await,
+            Command::Doctor(command) => command.execute(&config).await,
+            Command::Faucet(command) => command.execute(&config).await,
+            Command::Fixtures(command) => command.execute(&config).await,
+            Command::Flows(command) => command.execute(&config).await,
+            Command::Indexer(command) => command.execute(&config).await,
+            Command::Migrate(command) => command.execute(&config).await,
+            Command::Note(command) => command.execute(&config).await,
+            Command::OpenApi(command) => command.execute(&config).await,
+            Command::Receipt(command) => command.execute(&config).await,
+            Command::Recover(command) => command.execute(&config).await,
+            Command::Referral(command) => command.execute(&config).await,
+            Command::Returns(command) => command.execute(&config).await,
+            Command::Schedule(command) => command.execute(&config).await,
+            Command::Script(command) => command.execute(&config).await,
+            Command::Serve(command) => command.execute(&config).await,
+            Command::Snapshot(command) => command.execute(&config).await,
+            Command::Status(command) => command.execute(&config).await,
+            Command::Store(command) => command.execute(&config).await,
+            Command::Tx(command) => command.execute(&config).await,
+            Command::Wallet(command) => command.execute(&config).await,
+        }
+    }
+}
+
+/// Resolves an account ID or label given on the command line.
+///
+/// The ledger is only opened to look up labels.
+fn resolve_account(config: &Config, input: &str) -> Result<AccountId, FaucetError> {
+    let network = config.account_network();
+    match parse_account_id(input, network.as_ref()) {
+        Err(FaucetError::InvalidAccountId(..)) => {
+            resolve_account_id(&Ledger::open(&config.ledger_path)?, input, network.as_ref())
+        }
+        parsed => parsed,
+    }
+}
+
+/// Resolves an optional account argument, falling back to the default account.
+fn resolve_account_or_default(
+    config: &Config,
+    input: Option<&str>,
+) -> Result<AccountId, FaucetError> {
+    match input {
+        Some(input) => resolve_account(config, input),
+        None => Ledger::open(&config.ledger_path)?
+            .default_account()?
+            .ok_or_else(|| {
+                FaucetError::Config(
+                    "no account given and no default account set, see `account set-default`".into(),
+                )
+            }),
+    }
+}
@@ -0,0 +1,145 @@
+use std::{path::PathBuf, rc::Rc};
+
+use clap::Subcommand;
+use miden_client::note::NoteFile;
+use network_faucet::{
+    config::Config,
+    ledger::Ledger,
+    mint::{consume_stored_notes, parse_note_id, rebuild_mint_note},
+    node::{connect, FaucetNode},
+    note_file::{mint_note_file, read_note_file, write_note_file},
+    proof::prove_note,
+    watcher::{wait_for_transaction, BlockWatcher},
+    FaucetError,
+};
+use tokio::sync::Mutex;
+
+use super::resolve_account_or_default;
+
+#[derive(Debug, Subcommand)]
+pub enum NoteCommand {
+    /// Write the private note of a mint to a Miden note file for the recipient's wallet.
+    Export {
+        /// Ledger ID of the mint.
+        #[arg(long)]
+        mint: i64,
+        /// Output file, defaults to `mint-<id>.mno`.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Fetch the inclusion proof of a committed note and verify it against its block header.
+    Prove {
+        /// ID of the note, minted by the faucet or held by the store.
+        note: String,
+        /// Also write a note file carrying the verified proof.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Register the note of a Miden note file with the store.
+    Import {
+        /// Note file, as written by `note export`.
+        file: PathBuf,
+    },
+    /// Consume imported notes into an account managed by this client.
+    Consume {
+        /// Account receiving the notes, defaults to the default account.
+        #[arg(long)]
+        account: Option<String>,
+        /// IDs of the notes to consume.
+        #[arg(required = true)]
+        notes: Vec<String>,
+    },
+}
+
+impl NoteCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        match self {
+            Self::Export { mint, output } => {
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let record = ledger
+                    .get_mint(mint)?
+                    .ok_or_else(|| FaucetError::Ledger(format!("mint {mint} not found")))?;
+
+                let mut node = connect(config).await?;
+                let note_file = mint_note_file(&mut node, &record).await?;
+                let output = output.unwrap_or_else(|| PathBuf::from(format!("mint-{mint}.mno")));
+                write_note_file(&output, &note_file)?;
+
+                let contents = match note_file {
+                    NoteFile::NoteWithProof(..) => "note with inclusion proof",
+                    _ => "note details, the note is not committed yet",
+                };
+                println!(
+                    "Exported note {} to {} ({contents})",
+                    record.note_id,
+                    output.display()
+                );
+                Ok(())
+            }
+            Self::Prove { note, output } => {
+                let note_id = parse_note_id(&note)?;
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let mut node = connect(config).await?;
+                let note = match ledger.mint_of_note(&note_id.to_hex())? {
+                    Some(record) => rebuild_mint_note(&record)?,
+                    None => node
+                        .stored_note(note_id)
+                        .await?
+                        .map(|stored| stored.note)
+                        .ok_or_else(|| {
+                            FaucetError::InvalidNoteId(
+                                note,
+                                "not minted by this faucet nor held by the store".into(),
+                            )
+                        })?,
+                };
+
+                let proof = prove_note(&mut node, &note).await?.ok_or_else(|| {
+                    FaucetError::InvalidInclusionProof(
+                        note_id.to_hex(),
+                        "the node has no proof, the note is not committed yet".into(),
+                    )
+                })?;
+                println!(
+                    "Note {} is included in block {} at index {}; proof verified against the \
+                     block header",
+                    note_id.to_hex(),
+                    proof.location().block_num(),
+                    proof.location().node_index_in_block()
+                );
+
+                if let Some(output) = output {
+                    write_note_file(&output, &NoteFile::NoteWithProof(note, proof))?;
+                    println!("Wrote note file {}", output.display());
+                }
+                Ok(())
+            }
+            Self::Import { file } => {
+                let note_file = read_note_file(&file)?;
+                let mut node = connect(config).await?;
+                let note_id = node.import_note(note_file).await?;
+                println!("Imported note {}", note_id.to_hex());
+                Ok(())
+            }
+            Self::Consume { account, notes } => {
+                let account_id = resolve_account_or_default(config, account.as_deref())?;
+                let note_ids = notes
+                    .iter()
+                    .map(|note| parse_note_id(note))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let mut node = connect(config).await?;
+                // Notes imported from their details get their metadata from the sync.
+                node.sync_state().await?;
+                let transaction_id = consume_stored_notes(&mut node, account_id, &note_ids).await?;
+                println!("Consume transaction submitted: {}", transaction_id.to_hex());
+
+                let node = Rc::new(Mutex::new(node));
+                let watcher = BlockWatcher::spawn(node.clone(), config.poll.sync_interval());
+                let block_num = wait_for_transaction(&node, &watcher, transaction_id).await?;
+                println!("Consumed {} notes at block {block_num}", note_ids.len());
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use network_faucet::{config::Config, rest::openapi, FaucetError};
+
+/// Writes the REST API's OpenAPI document, for generating clients without a running server.
+#[derive(Debug, Args)]
+pub struct OpenApiCommand {
+    /// Output file, defaults to stdout.
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+impl OpenApiCommand {
+    pub async fn execute(self, _config: &Config) -> Result<(), FaucetError> {
+        let document = openapi()
+            .to_pretty_json()
+            .map_err(|err| FaucetError::Server(err.to_string()))?;
+        match self.output {
+            Some(path) => tokio::fs::write(path, document).await?,
+            None => println!("{document}"),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use network_faucet::{
+    account::parse_account_id,
+    audit::cli_actor,
+    config::Config,
+    finality::FinalityChecker,
+    ledger::Ledger,
+    node::{connect, FaucetNode},
+    receipt::{mint_receipt, MintReceipt, AUTH_KEY_SLOT},
+    FaucetError,
+};
+
+#[derive(Debug, Subcommand)]
+pub enum ReceiptCommand {
+    /// Write a receipt of a committed mint, signed with the key of the faucet owner.
+    Export {
+        /// Ledger ID of the mint.
+        #[arg(long)]
+        mint: i64,
+        /// Output file, defaults to stdout.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Check the signature of a receipt and that it was signed by the owner's key.
+    Verify {
+        /// Receipt file, as written by `receipt export` or the receipt endpoint of the APIs.
+        file: PathBuf,
+    },
+}
+
+impl ReceiptCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        match self {
+            Self::Export { mint, output } => {
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let record = ledger
+                    .get_mint(mint)?
+                    .ok_or_else(|| FaucetError::Ledger(format!("mint {mint} not found")))?;
+                if let Some(finality) = &config.finality {
+                    FinalityChecker::new(finality.clone())?
+                        .verify_mint(&ledger, &cli_actor(), &record)
+                        .await?;
+                }
+                let receipt = mint_receipt(&mut connect(config).await?, &record).await?;
+
+                let json =
+                    serde_json::to_string_pretty(&receipt).expect("receipts serialize to JSON");
+                match output {
+                    Some(path) => {
+                        std::fs::write(&path, json)?;
+                        println!("Wrote receipt of mint {mint} to {}", path.display());
+                    }
+                    None => println!("{json}"),
+                }
+                Ok(())
+            }
+            Self::Verify { file } => {
+                let receipt: MintReceipt = serde_json::from_slice(&std::fs::read(&file)?)
+                    .map_err(|err| FaucetError::InvalidReceipt(err.to_string()))?;
+                let key_commitment = receipt.verify()?;
+                println!(
+                    "Signature valid: {} tokens of {} to {} in block {}",
+                    receipt.amount, receipt.faucet_id, receipt.recipient, receipt.block_num
+                );
+
+                // The signer is only trusted if the key is the auth key of its account.
+                let signer = parse_account_id(&receipt.signer, None)?;
+                let mut node = connect(config).await?;
+                match node.get_account(signer).await? {
+                    Some(account)
+                        if account.storage().get_item(AUTH_KEY_SLOT)? == key_commitment =>
+                    {
+                        println!("Signed by the auth key of {signer}");
+                        Ok(())
+                    }
+                    Some(_) => Err(FaucetError::InvalidReceipt(format!(
+                        "the receipt was not signed by the auth key of {signer}"
+                    ))),
+                    None => {
+                        println!(
+                            "{signer} is not tracked by this client; compare its auth key with \
+                             the key commitment {}",
+                            key_commitment.to_hex()
+                        );
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+}
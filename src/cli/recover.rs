@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use miden_client::auth::AuthSecretKey;
+use network_faucet::{
+    audit::{cli_actor, AuditEntry},
+    authz::{authorize_cli, Action},
+    backup::RecoveryBundle,
+    client::open_keystore,
+    config::Config,
+    ledger::Ledger,
+    FaucetError,
+};
+
+/// Restores the key of a wallet into the keystore from its recovery bundle, without contacting
+/// the node.
+#[derive(Debug, Args)]
+pub struct RecoverCommand {
+    /// Bundle written by `deploy --backup`.
+    bundle: PathBuf,
+}
+
+impl RecoverCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        authorize_cli(config, Action::RestoreKey)?;
+        let (account_id, key) = RecoveryBundle::read(&self.bundle)?.recover()?;
+        open_keystore(config)?.add_key(&AuthSecretKey::RpoFalcon512(key))?;
+
+        Ledger::open(&config.ledger_path)?
+            .append_audit(&AuditEntry::new(cli_actor(), "keystore.recover").account(account_id))?;
+        println!(
+            "Restored the key of {account_id} to {}",
+            config.keystore_path.display()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use clap::Subcommand;
+use network_faucet::{
+    audit::{cli_actor, AuditEntry},
+    config::Config,
+    ledger::Ledger,
+    referral::{generate_referral_code, parse_referral_code},
+    FaucetError,
+};
+
+use super::resolve_account;
+
+#[derive(Debug, Subcommand)]
+pub enum ReferralCommand {
+    /// Create a referral code crediting an account with bonus mints.
+    Create {
+        /// Account receiving the bonuses, as hex ID or label.
+        #[arg(long)]
+        referrer: String,
+    },
+    /// List the referral codes with their conversions and bonuses.
+    List {
+        /// Print the list as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Stop a referral code from being converted.
+    Disable { code: String },
+}
+
+impl ReferralCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        match self {
+            Self::Create { referrer } => {
+                let referrer = resolve_account(config, &referrer)?;
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let code = generate_referral_code();
+                ledger.add_referral_code(&code, referrer)?;
+                ledger.append_audit(
+                    &AuditEntry::new(cli_actor(), "referral.create")
+                        .account(referrer)
+                        .param("code", &code),
+                )?;
+                println!("{code}");
+                if config.referral.is_none() {
+                    eprintln!("Note: mints only accept referral codes with a `[referral]` section");
+                }
+                Ok(())
+            }
+            Self::List { json } => {
+                let stats = Ledger::open(&config.ledger_path)?.referral_stats()?;
+                if json {
+                    let json =
+                        serde_json::to_string_pretty(&stats).expect("stats serialize to JSON");
+                    println!("{json}");
+                    return Ok(());
+                }
+                for stats in stats {
+                    println!(
+                        "{:<10} {} {:>6} conversion(s) {:>6} bonus(es) {:>12} minted{}",
+                        stats.code,
+                        stats.referrer,
+                        stats.conversions,
+                        stats.bonuses,
+                        stats.bonus_amount,
+                        if stats.disabled { " (disabled)" } else { "" },
+                    );
+                }
+                Ok(())
+            }
+            Self::Disable { code } => {
+                let code = parse_referral_code(&code)?;
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let Some(record) = ledger.referral_code(&code)? else {
+                    return Err(FaucetError::InvalidReferral(code, "no such code".into()));
+                };
+                if !ledger.disable_referral_code(&code)? {
+                    return Err(FaucetError::InvalidReferral(
+                        code,
+                        "the code is disabled already".into(),
+                    ));
+                }
+                ledger.append_audit(
+                    &AuditEntry::new(cli_actor(), "referral.disable")
+                        .account(record.referrer)
+                        .param("code", &code),
+                )?;
+                println!("Disabled referral code {code}");
+                Ok(())
+            }
+        }
+    }
+}
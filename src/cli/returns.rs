@@ -0,0 +1,65 @@
+use std::rc::Rc;
+
+use clap::Subcommand;
+use network_faucet::{
+    config::Config, ledger::Ledger, node::connect, returns::run_returns_watcher,
+    watcher::BlockWatcher, FaucetError,
+};
+use tokio::sync::Mutex;
+
+use super::resolve_account;
+
+#[derive(Debug, Subcommand)]
+pub enum ReturnsCommand {
+    /// Collect tokens paid back to the faucet owner on every block until interrupted.
+    Watch {
+        /// Faucet whose tokens are collected; its owner must be managed by this client.
+        #[arg(long)]
+        faucet: String,
+        /// Burn the collected tokens, reducing the issued supply.
+        #[arg(long)]
+        burn: bool,
+    },
+    /// List the most recent deposits collected from holders.
+    List {
+        /// Only list the deposits of this faucet.
+        #[arg(long)]
+        faucet: Option<String>,
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+impl ReturnsCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        match self {
+            Self::Watch { faucet, burn } => {
+                let faucet_id = resolve_account(config, &faucet)?;
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let node = Rc::new(Mutex::new(connect(config).await?));
+                let watcher = BlockWatcher::spawn(node.clone(), config.poll.sync_interval());
+
+                tokio::select! {
+                    result = run_returns_watcher(&node, &watcher, &ledger, faucet_id, burn) => result,
+                    result = tokio::signal::ctrl_c() => Ok(result?),
+                }
+            }
+            Self::List { faucet, limit } => {
+                let faucet = faucet
+                    .as_deref()
+                    .map(|faucet| resolve_account(config, faucet))
+                    .transpose()?;
+                for record in Ledger::open(&config.ledger_path)?.recent_returns(faucet, limit)? {
+                    let block = record
+                        .commit_block
+                        .map_or_else(String::new, |block| format!(" at block {block}"));
+                    println!(
+                        "{:<5} {} tokens from {} ({}{block})",
+                        record.id, record.amount, record.sender, record.status
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+}
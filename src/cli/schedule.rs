@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use clap::Subcommand;
+use network_faucet::{
+    audit::{cli_actor, AuditEntry},
+    config::Config,
+    ledger::{unix_now, Ledger},
+    schedule::{format_interval, parse_interval, CatchUp},
+    FaucetError,
+};
+
+use super::resolve_account;
+
+#[derive(Debug, Subcommand)]
+pub enum ScheduleCommand {
+    /// Mint to a recipient at a fixed interval while `serve` runs.
+    Add {
+        /// Faucet to mint from, defaults to `service.faucet_id`.
+        #[arg(long)]
+        faucet: Option<String>,
+        /// Account ID or label of the recipient.
+        #[arg(long)]
+        to: String,
+        /// Tokens per mint, `mint.default_amount` when omitted.
+        #[arg(long)]
+        amount: Option<u64>,
+        /// Interval between two mints, e.g. `6h`, `30m` or `1d`.
+        #[arg(long, value_parser = parse_interval)]
+        every: Duration,
+        /// Delay before the first mint, which is otherwise minted right away.
+        #[arg(long, value_parser = parse_interval)]
+        start_in: Option<Duration>,
+        /// Mint every run missed while `serve` was down (`all`) or a single one (`once`).
+        #[arg(long, default_value_t = CatchUp::All)]
+        catch_up: CatchUp,
+    },
+    /// List the schedules and their next runs.
+    List {
+        /// Only list the schedules of this faucet.
+        #[arg(long)]
+        faucet: Option<String>,
+    },
+    /// Stop and delete a schedule.
+    Remove { id: i64 },
+}
+
+impl ScheduleCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        match self {
+            Self::Add {
+                faucet,
+                to,
+                amount,
+                every,
+                start_in,
+                catch_up,
+            } => {
+                let faucet = faucet
+                    .or_else(|| config.service.faucet_id.clone())
+                    .ok_or_else(|| {
+                        FaucetError::Config("no faucet given and service.faucet_id unset".into())
+                    })?;
+                let faucet_id = resolve_account(config, &faucet)?;
+                let recipient = resolve_account(config, &to)?;
+                let amount = config.mint.resolve(amount)?;
+                let first_run = unix_now() + start_in.unwrap_or_default().as_secs();
+
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let id = ledger
+                    .add_schedule(faucet_id, recipient, amount, every, catch_up, first_run)?;
+                ledger.append_audit(
+                    &AuditEntry::new(cli_actor(), "schedule.add")
+                        .account(faucet_id)
+                        .param("schedule_id", id)
+                        .param("recipient", recipient.to_hex())
+                        .param("amount", amount)
+                        .param("interval_secs", every.as_secs())
+                        .param("catch_up", catch_up.as_str())
+                        .param("first_run", first_run),
+                )?;
+                println!(
+                    "Schedule {id}: mint {amount} from {faucet_id} to {recipient} every {}",
+                    format_interval(every)
+                );
+                Ok(())
+            }
+            Self::List { faucet } => {
+                let faucet = faucet
+                    .as_deref()
+                    .map(|faucet| resolve_account(config, faucet))
+                    .transpose()?;
+                let now = unix_now();
+                for schedule in Ledger::open(&config.ledger_path)?.schedules(faucet)? {
+                    let next = match schedule.next_run.checked_sub(now) {
+                        Some(0) | None => "due".to_string(),
+                        Some(secs) => format!("in {}", format_remaining(secs)),
+                    };
+                    println!(
+                        "{:<5} {} -> {} {:>12} every {:<5} catch-up {:<4} next run {next}",
+                        schedule.id,
+                        schedule.faucet_id,
+                        schedule.recipient,
+                        schedule.amount,
+                        format_interval(schedule.interval),
+                        schedule.catch_up,
+                    );
+                    if let Some(error) = schedule.last_error {
+                        println!("      last run failed: {error}");
+                    }
+                }
+                Ok(())
+            }
+            Self::Remove { id } => {
+                let ledger = Ledger::open(&config.ledger_path)?;
+                if !ledger.remove_schedule(id)? {
+                    return Err(FaucetError::InvalidSchedule(
+                        id.to_string(),
+                        "no such schedule".into(),
+                    ));
+                }
+                ledger.append_audit(
+                    &AuditEntry::new(cli_actor(), "schedule.remove").param("schedule_id", id),
+                )?;
+                println!("Removed schedule {id}");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Formats a delay in seconds as e.g. `5h 12m`.
+fn format_remaining(secs: u64) -> String {
+    match (secs / 3600, secs % 3600 / 60) {
+        (0, 0) => format!("{secs}s"),
+        (0, minutes) => format!("{minutes}m"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
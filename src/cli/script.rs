@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use network_faucet::{
+    config::Config,
+    node::{connect, FaucetNode},
+    script::{check_script, load_script, ScriptCheck, ScriptParams, ScriptTemplate},
+    FaucetError,
+};
+
+use super::resolve_account;
+
+#[derive(Debug, Subcommand)]
+pub enum ScriptCommand {
+    /// Compile a transaction script and report its errors without submitting anything.
+    Check {
+        /// Script to check, looked up like `--script-path` of the deploy binary.
+        path: PathBuf,
+        /// Value of a `{{name}}` placeholder, as `name=value`. May be repeated.
+        #[arg(long = "param", value_name = "NAME=VALUE")]
+        params: Vec<String>,
+        /// Also execute the script against the faucet, without proving or submitting it.
+        #[arg(long, requires = "faucet")]
+        dry_run: bool,
+        /// Faucet the script runs against, tracked by this client.
+        #[arg(long)]
+        faucet: Option<String>,
+    },
+}
+
+impl ScriptCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        match self {
+            Self::Check {
+                path,
+                params,
+                dry_run,
+                faucet,
+            } => {
+                let template = ScriptTemplate::new(load_script(Some(&path), "")?);
+                let mut script_params = ScriptParams::new();
+                for assignment in &params {
+                    script_params = script_params.with_assignment(assignment)?;
+                }
+                let dry_run = match (dry_run, faucet) {
+                    (true, Some(faucet)) => Some(resolve_account(config, &faucet)?),
+                    _ => None,
+                };
+
+                let mut node = connect(config).await?;
+                if dry_run.is_some() {
+                    node.sync_state().await?;
+                }
+                match check_script(&mut node, &template, &script_params, dry_run).await {
+                    Ok(ScriptCheck::Compiled) => println!("{}: compiles", path.display()),
+                    Ok(ScriptCheck::Executed(account_id, cost)) => println!(
+                        "{}: compiles and executes against {account_id}, fee {}, {} cycles",
+                        path.display(),
+                        cost.fee,
+                        cost.cycles
+                    ),
+                    Err(err) => {
+                        eprintln!("{}: {err}", path.display());
+                        // Assembler diagnostics refer to the rendered script, list it for reference.
+                        if let (FaucetError::Script(_), Ok(code)) =
+                            (&err, template.render(&script_params, &[]))
+                        {
+                            for (number, line) in code.lines().enumerate() {
+                                eprintln!("{:>4} | {line}", number + 1);
+                            }
+                        }
+                        return Err(err);
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
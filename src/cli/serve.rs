@@ -0,0 +1,237 @@
+use std::{rc::Rc, sync::Arc, time::Instant};
+
+use clap::Args;
+use futures::future::{self, FutureExt, LocalBoxFuture};
+use network_faucet::{
+    abuse::AbusePolicies,
+    access::Access,
+    config::Config,
+    email::NoteMailer,
+    executor::Executors,
+    finality::FinalityChecker,
+    github::GithubAuth,
+    grpc,
+    history::run_account_history,
+    ledger::Ledger,
+    network::Networks,
+    node::{connect, FaucetNode},
+    rest,
+    schedule::run_scheduler,
+    service::{faucet_service, FaucetHandle},
+    signing::check_signing,
+    supply::run_supply_reconciler,
+    watcher::BlockWatcher,
+    FaucetError,
+};
+use tokio::{sync::Mutex, task::JoinSet};
+
+use super::resolve_account;
+
+#[derive(Debug, Args)]
+pub struct ServeCommand {
+    /// Network faucet to mint from, overrides `service.faucet_id`.
+    #[arg(long)]
+    faucet: Option<String>,
+}
+
+impl ServeCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        if config.service.grpc_addr.is_none() && config.service.rest_addr.is_none() {
+            return Err(FaucetError::Config(
+                "no API enabled, set service.grpc_addr or service.rest_addr".into(),
+            ));
+        }
+
+        let mut networks: Option<Networks> = None;
+        let mut tasks = Vec::new();
+        for name in config.network_names() {
+            let network = config.network(&name)?;
+            // `--faucet` only overrides the faucet of the default network.
+            let faucet = match &self.faucet {
+                Some(faucet) if networks.is_none() => Some(faucet.clone()),
+                _ => network.service.faucet_id.clone(),
+            };
+            let faucet = faucet.ok_or_else(|| {
+                FaucetError::Config("no faucet to serve, set service.faucet_id".into())
+            })?;
+            let (handle, tokens) = start_network(&network, &faucet, &mut tasks).await?;
+            let explorer = network.explorer();
+            let mut served = match networks {
+                None => Networks::new(name.clone(), handle, explorer),
+                Some(networks) => networks.with_network(name.clone(), handle, explorer),
+            };
+            for (token, handle) in tokens {
+                served = served.with_token(&name, token, handle);
+            }
+            if let Some(account_network) = network.account_network() {
+                served = served.with_account_network(&name, account_network);
+            }
+            networks = Some(served);
+        }
+        let networks = networks.expect("the default network is always served");
+
+        let github = match &config.github {
+            Some(github) => Some(GithubAuth::new(
+                github.clone(),
+                Ledger::open(&config.ledger_path)?,
+            )?),
+            None => None,
+        };
+        let access = Arc::new(
+            Access::new(
+                config.mint.clone(),
+                config.tiers.clone(),
+                config.api_keys.clone(),
+                github,
+            )
+            .with_ledger(Ledger::open(&config.ledger_path)?)?,
+        );
+
+        let mut servers = JoinSet::new();
+        if let Some(addr) = config.service.grpc_addr {
+            servers.spawn(grpc::serve(
+                addr,
+                networks.with_actor("grpc"),
+                access.clone(),
+            ));
+        }
+        if let Some(addr) = config.service.rest_addr {
+            servers.spawn(rest::serve(
+                addr,
+                networks.with_actor("rest"),
+                access,
+                config.tls.clone(),
+                config.http.clone(),
+            ));
+        }
+
+        tokio::select! {
+            _ = future::select_all(tasks) => Ok(()),
+            Some(result) = servers.join_next() => {
+                result.map_err(|err| FaucetError::Server(err.to_string()))?
+            }
+            result = tokio::signal::ctrl_c() => Ok(result?),
+        }
+    }
+}
+
+/// Connects to the node of `config` and starts the workers minting from `faucet` and from the
+/// token faucets of `service.tokens`, pushing them and the tasks of the network onto `tasks`.
+/// Returns the handle of `faucet` and those of the token faucets by symbol.
+async fn start_network(
+    config: &Config,
+    faucet: &str,
+    tasks: &mut Vec<LocalBoxFuture<'static, ()>>,
+) -> Result<(FaucetHandle, Vec<(String, FaucetHandle)>), FaucetError> {
+    let faucet_id = resolve_account(config, faucet)?;
+    let ledger = Rc::new(Ledger::open(&config.ledger_path)?);
+    let mut node = connect(config).await?;
+    // Cold starts replay every block since the last run, so report when the store caught up.
+    println!("Syncing the client store of {}...", config.service.network);
+    let started = Instant::now();
+    let block_num = node.sync_state().await?;
+    println!(
+        "Store synced to block {block_num} in {:.1}s",
+        started.elapsed().as_secs_f64()
+    );
+    // Fail now rather than with the first mint if the owner key is missing or broken.
+    let signer = check_signing(&mut node, faucet_id).await?;
+    println!("Signing self-test passed for owner {}", signer.account_id);
+    let node = Rc::new(Mutex::new(node));
+    let watcher = Rc::new(
+        BlockWatcher::spawn(node.clone(), config.poll.sync_interval())
+            .with_confirmations(config.service.confirmations),
+    );
+    let (handle, mut worker) = faucet_service(
+        node.clone(),
+        watcher.clone(),
+        ledger.clone(),
+        faucet_id,
+        &config.service,
+    );
+    // Mints wait for the other processes submitting from the faucet owner, e.g. `mint`.
+    let executors = Rc::new(Executors::new(node.clone()).with_lock_dir(config.lock_dir()));
+    worker = worker
+        .with_executors(executors.clone())
+        .with_amounts(config.mint.clone());
+    if let Some(smtp) = &config.smtp {
+        worker = worker.with_mailer(NoteMailer::new(smtp.clone())?);
+    }
+    if let Some(finality) = &config.finality {
+        worker = worker.with_finality(FinalityChecker::new(finality.clone())?);
+    }
+    if let Some(referral) = &config.referral {
+        worker = worker.with_referrals(referral.clone());
+    }
+    worker = worker.with_abuse_policies(
+        AbusePolicies::from_config(&config.abuse_policies)
+            .with_daily_budget(config.service.daily_budget),
+    );
+
+    let scheduler = run_scheduler(
+        handle.with_actor("scheduler").with_identity("scheduler"),
+        ledger.clone(),
+        faucet_id,
+        config.poll.schedule_interval(),
+    );
+    tasks.push(worker.run().boxed_local());
+    tasks.push(scheduler.boxed_local());
+
+    // Token faucets share the node, and the executors so faucets with one owner never overlap.
+    let mut tokens = Vec::new();
+    for (symbol, token) in &config.service.tokens {
+        let token_faucet = token.resolve_faucet_id(symbol, &ledger)?;
+        check_signing(&mut *node.lock().await, token_faucet).await?;
+        let token_ledger = match &token.ledger_path {
+            Some(path) => Rc::new(Ledger::open(path)?),
+            None => ledger.clone(),
+        };
+        let (token_handle, mut token_worker) = faucet_service(
+            node.clone(),
+            watcher.clone(),
+            token_ledger.clone(),
+            token_faucet,
+            &config.service,
+        );
+        token_worker = token_worker
+            .with_executors(executors.clone())
+            .with_amounts(token.mint.clone())
+            .with_abuse_policies(
+                AbusePolicies::from_config(&config.abuse_policies)
+                    .with_daily_budget(token.daily_budget),
+            );
+        if let Some(smtp) = &config.smtp {
+            token_worker = token_worker.with_mailer(NoteMailer::new(smtp.clone())?);
+        }
+        if let Some(finality) = &config.finality {
+            token_worker = token_worker.with_finality(FinalityChecker::new(finality.clone())?);
+        }
+        println!("Serving token {symbol} from faucet {token_faucet}");
+        tasks.push(token_worker.run().boxed_local());
+        let reconciler = {
+            let (node, watcher) = (node.clone(), watcher.clone());
+            let interval = config.poll.supply_interval();
+            async move {
+                run_supply_reconciler(&node, &watcher, &token_ledger, token_faucet, interval).await
+            }
+        };
+        tasks.push(reconciler.boxed_local());
+        tokens.push((symbol.clone(), token_handle));
+    }
+
+    let (reconciled, supply_interval) = (node.clone(), config.poll.supply_interval());
+    let (supply_watcher, supply_ledger) = (watcher.clone(), ledger.clone());
+    let reconciler = async move {
+        run_supply_reconciler(
+            &reconciled,
+            &supply_watcher,
+            &supply_ledger,
+            faucet_id,
+            supply_interval,
+        )
+        .await
+    };
+    tasks.push(reconciler.boxed_local());
+    tasks.push(async move { run_account_history(&node, &watcher, &ledger).await }.boxed_local());
+    Ok((handle, tokens))
+}
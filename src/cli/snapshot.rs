@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use network_faucet::{
+    archive::{create_snapshot, restore_snapshot},
+    audit::{cli_actor, AuditEntry},
+    authz::{authorize_cli, Action},
+    config::Config,
+    ledger::Ledger,
+    FaucetError,
+};
+
+#[derive(Debug, Subcommand)]
+pub enum SnapshotCommand {
+    /// Write the client store, the ledger and references to the keys of the keystore to a new
+    /// archive.
+    Create {
+        /// Archive to write, must not exist yet.
+        path: PathBuf,
+        /// Embed the key files instead of only referencing them. Keep such archives as safe as
+        /// the keystore.
+        #[arg(long)]
+        include_keys: bool,
+    },
+    /// Restore the client store, the ledger and the archived keys from an archive. Stop `serve`
+    /// and other commands using the files first.
+    Restore {
+        path: PathBuf,
+        /// Replace an existing store and ledger.
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+impl SnapshotCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        authorize_cli(config, Action::ManageSnapshots)?;
+        match self {
+            Self::Create { path, include_keys } => {
+                let manifest = create_snapshot(config, &path, include_keys)?;
+                let keys = if manifest.includes_keys() {
+                    "included"
+                } else {
+                    "referenced"
+                };
+                println!(
+                    "Wrote snapshot version {} of network `{}` to {}",
+                    manifest.version,
+                    manifest.network,
+                    path.display()
+                );
+                println!("  ledger schema: {}", manifest.ledger_schema);
+                println!("  pending mints: {}", manifest.pending_mints.len());
+                println!("  keys:          {} ({keys})", manifest.keys.len());
+
+                Ledger::open(&config.ledger_path)?.append_audit(
+                    &AuditEntry::new(cli_actor(), "snapshot.create")
+                        .param("path", path.display().to_string())
+                        .param("include_keys", include_keys),
+                )?;
+                Ok(())
+            }
+            Self::Restore { path, force } => {
+                let report = restore_snapshot(config, &path, force)?;
+                println!(
+                    "Restored snapshot of network `{}` taken at {}",
+                    report.manifest.network, report.manifest.created_at
+                );
+                println!("  pending mints: {}", report.manifest.pending_mints.len());
+                println!("  keys restored: {}", report.restored_keys.len());
+                if !report.missing_keys.is_empty() {
+                    println!(
+                        "The keystore at {} lacks {} referenced keys, restore them with \
+                         `recover`:",
+                        config.keystore_path.display(),
+                        report.missing_keys.len()
+                    );
+                    for key in &report.missing_keys {
+                        println!("  {key}");
+                    }
+                }
+
+                Ledger::open(&config.ledger_path)?.append_audit(
+                    &AuditEntry::new(cli_actor(), "snapshot.restore")
+                        .param("path", path.display().to_string())
+                        .param("created_at", report.manifest.created_at)
+                        .param("missing_keys", report.missing_keys.len()),
+                )?;
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use clap::Args;
+use network_faucet::{config::Config, rest::StatusResponse, FaucetError};
+
+/// Time the running service gets to answer before the faucet counts as unreachable.
+const STATUS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reports the sync progress and backlogs of the running service from its `GET /api/status`.
+#[derive(Debug, Args)]
+pub struct StatusCommand {
+    /// Base URL of the REST API, defaults to `service.rest_addr` of the configuration.
+    #[arg(long)]
+    url: Option<String>,
+    /// Network to report on, defaults to the default network of the service.
+    #[arg(long)]
+    network: Option<String>,
+    /// Token faucet to report on, defaults to the main faucet of the network.
+    #[arg(long)]
+    token: Option<String>,
+    /// Print the status as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+impl StatusCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        let base = match self.url {
+            Some(url) => url,
+            None => {
+                let addr = config.service.rest_addr.ok_or_else(|| {
+                    FaucetError::Config("pass --url or set service.rest_addr".into())
+                })?;
+                let scheme = if config.tls.is_some() {
+                    "https"
+                } else {
+                    "http"
+                };
+                format!("{scheme}://{addr}")
+            }
+        };
+        let mut query = Vec::new();
+        query.extend(self.network.map(|network| ("network", network)));
+        query.extend(self.token.map(|token| ("token", token)));
+
+        let http = reqwest::Client::builder()
+            .timeout(STATUS_TIMEOUT)
+            .build()
+            .map_err(|err| FaucetError::Server(err.to_string()))?;
+        let response = http
+            .get(format!("{}/api/status", base.trim_end_matches('/')))
+            .query(&query)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| FaucetError::Server(format!("status of {base}: {err}")))?;
+        let status: StatusResponse = response
+            .json()
+            .await
+            .map_err(|err| FaucetError::Server(format!("status of {base}: {err}")))?;
+
+        if self.json {
+            let json = serde_json::to_string_pretty(&status).expect("status serializes to JSON");
+            println!("{json}");
+            return Ok(());
+        }
+        match status.store_block {
+            Some(block) => println!(
+                "Store block:     {block} (chain tip {}, {} behind)",
+                status.chain_tip, status.lag_blocks
+            ),
+            None => println!(
+                "Store block:     not synced (chain tip {})",
+                status.chain_tip
+            ),
+        }
+        match status.seconds_since_sync {
+            Some(age) => println!("Last sync:       {age}s ago"),
+            None => println!("Last sync:       never"),
+        }
+        println!(
+            "Queue:           {}/{} mints",
+            status.queue_len, status.queue_capacity
+        );
+        println!("Prover backlog:  {} transactions", status.prover_backlog);
+        Ok(())
+    }
+}
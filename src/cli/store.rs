@@ -0,0 +1,94 @@
+use clap::Subcommand;
+use network_faucet::{
+    audit::{cli_actor, AuditEntry},
+    authz::{authorize_cli, Action},
+    config::Config,
+    ledger::Ledger,
+    node::{FaucetNode, NodeClient},
+    store::prune_store,
+    FaucetError,
+};
+
+#[derive(Debug, Subcommand)]
+pub enum StoreCommand {
+    /// Delete consumed notes, old transactions and old block headers from the client store and
+    /// compact it. Stop `serve` and other commands using the store first.
+    Prune {
+        /// Keep the transactions and block headers of this many most recent blocks.
+        #[arg(long, default_value_t = 10_000)]
+        keep_blocks: u32,
+        /// Only count the records that would be deleted.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Sync with the node and report how long each sync takes, e.g. to compare scoped syncs.
+    Sync {
+        #[arg(long, default_value_t = 3)]
+        rounds: u32,
+    },
+}
+
+impl StoreCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        match self {
+            Self::Prune {
+                keep_blocks,
+                dry_run,
+            } => {
+                if !dry_run {
+                    authorize_cli(config, Action::PruneStore)?;
+                }
+                let report = prune_store(&config.store_path, keep_blocks, dry_run)?;
+                let verb = if dry_run { "Would delete" } else { "Deleted" };
+                println!("{verb}:");
+                println!("  consumed input notes:  {}", report.input_notes);
+                println!("  consumed output notes: {}", report.output_notes);
+                println!("  transactions:          {}", report.transactions);
+                println!("  block headers:         {}", report.block_headers);
+                if dry_run {
+                    return Ok(());
+                }
+                println!(
+                    "Store compacted from {} to {} bytes",
+                    report.size_before, report.size_after
+                );
+
+                Ledger::open(&config.ledger_path)?.append_audit(
+                    &AuditEntry::new(cli_actor(), "store.prune")
+                        .param("keep_blocks", keep_blocks)
+                        .param("input_notes", report.input_notes)
+                        .param("output_notes", report.output_notes)
+                        .param("transactions", report.transactions)
+                        .param("block_headers", report.block_headers),
+                )?;
+                Ok(())
+            }
+            Self::Sync { rounds } => {
+                let mut node = NodeClient::new(config).await?;
+                let tags = node.client_mut().get_note_tags().await?.len();
+                let scope = if config.sync.scoped {
+                    "scoped"
+                } else {
+                    "unscoped"
+                };
+                println!("Tracking {tags} note tags ({scope})");
+
+                for round in 1..=rounds {
+                    let block_num = node.sync_state().await?;
+                    println!(
+                        "Sync {round}: block {block_num} in {}ms",
+                        node.sync_stats().last.as_millis()
+                    );
+                }
+                let stats = node.sync_stats();
+                println!(
+                    "{} syncs, mean {}ms, max {}ms",
+                    stats.syncs,
+                    stats.mean().as_millis(),
+                    stats.max.as_millis()
+                );
+                Ok(())
+            }
+        }
+    }
+}
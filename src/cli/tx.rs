@@ -0,0 +1,38 @@
+use clap::Subcommand;
+use network_faucet::{config::Config, mint::parse_transaction_id, FaucetError};
+
+#[derive(Debug, Subcommand)]
+pub enum TxCommand {
+    /// Open the explorer page of a transaction in the browser.
+    Open {
+        /// Hex-encoded transaction ID.
+        transaction_id: String,
+        /// Only print the link.
+        #[arg(long)]
+        print: bool,
+    },
+}
+
+impl TxCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        match self {
+            Self::Open {
+                transaction_id,
+                print,
+            } => {
+                let transaction_id = parse_transaction_id(&transaction_id)?;
+                let explorer = config.explorer().ok_or_else(|| {
+                    FaucetError::Config(
+                        "the network has no known explorer, set explorer.url".into(),
+                    )
+                })?;
+                let url = explorer.transaction(transaction_id);
+                println!("{url}");
+                if !print {
+                    open::that_detached(&url)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use std::{path::PathBuf, rc::Rc};
+
+use clap::Subcommand;
+use miden_client::{asset::Asset, note::NoteType, Word};
+use network_faucet::{
+    account::validate_label,
+    authz::{authorize_cli, Action},
+    config::Config,
+    ledger::Ledger,
+    mint::get_balance,
+    node::{connect, FaucetNode},
+    note_file::{note_file, write_note_file},
+    tx::TxPolicy,
+    wallet::{create_wallet, create_wallet_with_key, list_wallets, parse_key_id, pay, sweep_notes},
+    watch::ensure_spendable,
+    watcher::{wait_for_transaction, BlockWatcher},
+    FaucetError,
+};
+use tokio::sync::Mutex;
+
+use super::{resolve_account, resolve_account_or_default};
+
+#[derive(Debug, Subcommand)]
+pub enum WalletCommand {
+    /// Create a wallet managed by this client.
+    Create {
+        /// Label to attach to the new wallet.
+        #[arg(long)]
+        label: Option<String>,
+        /// Bind the wallet to this key of the keystore, by the hex commitment of its public key,
+        /// instead of generating a new one.
+        #[arg(long, value_name = "COMMITMENT", value_parser = parse_key_id)]
+        key_id: Option<Word>,
+    },
+    /// List the wallets managed by this client.
+    List,
+    /// Print the state of a wallet as known to the store.
+    Show {
+        /// Wallet ID or label.
+        account: String,
+    },
+    /// Remove a wallet from the wallet list and drop its labels.
+    ///
+    /// The store keeps tracking the wallet and the keystore keeps its key.
+    Remove {
+        /// Wallet ID or label.
+        account: String,
+    },
+    /// Consume every note the store holds for a wallet.
+    Sweep {
+        /// Wallet managed by this client, defaults to the default account.
+        account_id: Option<String>,
+    },
+    /// Print the balance of a wallet in the tokens of a faucet, as known to the store.
+    Balance {
+        /// Wallet ID or label, defaults to the default account.
+        account: Option<String>,
+        /// Faucet of the tokens.
+        #[arg(long)]
+        faucet: String,
+    },
+    /// Send tokens from a wallet managed by this client to any account in a P2ID note.
+    Pay {
+        /// Paying wallet, managed by this client; defaults to the default account.
+        #[arg(long)]
+        from: Option<String>,
+        /// Receiving account.
+        #[arg(long)]
+        to: String,
+        /// Faucet of the tokens to send.
+        #[arg(long)]
+        faucet: String,
+        #[arg(long)]
+        amount: u64,
+        /// Create a public note the receiver discovers through sync instead of a private one.
+        #[arg(long)]
+        public: bool,
+        /// Write the note to this Miden note file for delivery to the receiver.
+        #[arg(long)]
+        export: Option<PathBuf>,
+    },
+}
+
+impl WalletCommand {
+    pub async fn execute(self, config: &Config) -> Result<(), FaucetError> {
+        match self {
+            Self::Create { label, key_id } => {
+                if let Some(label) = &label {
+                    validate_label(label)?;
+                }
+                let mut node = connect(config).await?;
+                node.sync_state().await?;
+                let wallet = match key_id {
+                    Some(key_id) => create_wallet_with_key(&mut node, key_id).await?,
+                    None => create_wallet(&mut node).await?,
+                };
+                if let Some(label) = &label {
+                    Ledger::open(&config.ledger_path)?.set_label(label, wallet.id())?;
+                }
+                println!("Created wallet {}", wallet.id());
+                Ok(())
+            }
+            Self::List => {
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let mut node = connect(config).await?;
+                for account_id in list_wallets(&mut node, &ledger).await? {
+                    println!("{account_id} {}", ledger.labels_of(account_id)?.join(", "));
+                }
+                Ok(())
+            }
+            Self::Show { account } => {
+                let account_id = resolve_account(config, &account)?;
+                let ledger = Ledger::open(&config.ledger_path)?;
+                let mut node = connect(config).await?;
+                let wallet = node
+                    .get_account(account_id)
+                    .await?
+                    .ok_or(FaucetError::AccountNotFound(account_id))?;
+
+                println!("Wallet:     {account_id}");
+                println!("Labels:     {}", ledger.labels_of(account_id)?.join(", "));
+                println!("Storage:    {}", account_id.storage_mode());
+                println!("Nonce:      {}", wallet.nonce());
+                println!("Code:       {}", wallet.code().commitment().to_hex());
+                println!("Assets:");
+                for asset in wallet.vault().assets() {
+                    match asset {
+                        Asset::Fungible(asset) => {
+                            println!("  {} of faucet {}", asset.amount(), asset.faucet_id())
+                        }
+                        Asset::NonFungible(asset) => {
+                            println!(
+                                "  non-fungible asset of faucet {}",
+                                asset.faucet_id_prefix()
+                            )
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Self::Remove { account } => {
+                authorize_cli(config, Action::RemoveWallet)?;
+                let account_id = resolve_account(config, &account)?;
+                Ledger::open(&config.ledger_path)?.remove_wallet(account_id)?;
+                println!("Removed wallet {account_id} from the wallet list");
+                Ok(())
+            }
+            Self::Balance { account, faucet } => {
+                let account_id = resolve_account_or_default(config, account.as_deref())?;
+                let faucet_id = resolve_account(config, &faucet)?;
+                let mut node = connect(config).await?;
+                node.sync_state().await?;
+                let balance = get_balance(&mut node, account_id, faucet_id).await?;
+                println!("{balance}");
+                Ok(())
+            }
+            Self::Sweep { account_id } => {
+                let account_id = resolve_account_or_default(config, account_id.as_deref())?;
+                ensure_spendable(&Ledger::open(&config.ledger_path)?, account_id)?;
+                let mut node = connect(config).await?;
+                node.sync_state().await?;
+
+                let batches = sweep_notes(&mut node, account_id).await?;
+                if batches.is_empty() {
+                    println!("No consumable notes for {}", account_id.to_hex());
+                    return Ok(());
+                }
+
+                let node = Rc::new(Mutex::new(node));
+                let watcher = BlockWatcher::spawn(node.clone(), config.poll.sync_interval());
+                let mut consumed = 0;
+                for batch in &batches {
+                    println!(
+                        "Consuming {} notes in transaction {}",
+                        batch.notes,
+                        batch.transaction_id.to_hex()
+                    );
+                    wait_for_transaction(&node, &watcher, batch.transaction_id).await?;
+                    consumed += batch.notes;
+                }
+                println!(
+                    "Consumed {consumed} notes in {} transactions",
+                    batches.len()
+                );
+                Ok(())
+            }
+            Self::Pay {
+                from,
+                to,
+                faucet,
+                amount,
+                public,
+                export,
+            } => {
+                let sender = resolve_account_or_default(config, from.as_deref())?;
+                ensure_spendable(&Ledger::open(&config.ledger_path)?, sender)?;
+                let target = resolve_account(config, &to)?;
+                let faucet_id = resolve_account(config, &faucet)?;
+                let note_type = if public {
+                    NoteType::Public
+                } else {
+                    TxPolicy::default().note_type
+                };
+
+                let mut node = connect(config).await?;
+                node.sync_state().await?;
+                let payment = pay(&mut node, sender, target, faucet_id, amount, note_type).await?;
+                println!(
+                    "Payment submitted: transaction {}, note {}",
+                    payment.transaction_id.to_hex(),
+                    payment.note.id().to_hex()
+                );
+
+                let node = Rc::new(Mutex::new(node));
+                let watcher = BlockWatcher::spawn(node.clone(), config.poll.sync_interval());
+                let block_num =
+                    wait_for_transaction(&node, &watcher, payment.transaction_id).await?;
+                println!("Payment committed at block {block_num}");
+
+                if let Some(path) = export {
+                    let file =
+                        note_file(&mut *node.lock().await, payment.note, Some(block_num)).await?;
+                    write_note_file(&path, &file)?;
+                    println!("Exported note to {}", path.display());
+                }
+                Ok(())
+            }
+        }
+    }
+}
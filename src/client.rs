@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use miden_client::{
+    builder::ClientBuilder, crypto::RpoRandomCoin, keystore::FilesystemKeyStore,
+    rpc::NodeRpcClient, Client, ClientRng, Word,
+};
+use miden_client_sqlite_store::ClientBuilderSqliteExt;
+use miden_crypto::hash::rpo::Rpo256;
+use rand::prelude::StdRng;
+
+use crate::{config::Config, network::claim_for_network, rpc::build_rpc_client, FaucetError};
+
+/// Keystore used by all faucet binaries.
+pub type FaucetKeyStore = FilesystemKeyStore<StdRng>;
+
+/// Client type used by all faucet binaries.
+pub type FaucetClient = Client<FaucetKeyStore>;
+
+/// Builds a client and its keystore from `config`.
+pub async fn build_client(config: &Config) -> Result<(FaucetClient, FaucetKeyStore), FaucetError> {
+    let rpc_client = build_rpc_client(&config.rpc).await?;
+    build_client_with_rpc(config, rpc_client).await
+}
+
+/// Builds a client and its keystore from `config` around an existing RPC client.
+///
+/// Fails if the store or keystore belongs to another network than the configured node, see
+/// [`claim_for_network`].
+pub async fn build_client_with_rpc(
+    config: &Config,
+    rpc_client: Arc<dyn NodeRpcClient>,
+) -> Result<(FaucetClient, FaucetKeyStore), FaucetError> {
+    let keystore = open_keystore(config)?;
+    claim_for_network(&config.store_path, &config.rpc.endpoint.network_name())?;
+
+    let mut builder = ClientBuilder::new()
+        .rpc(rpc_client)
+        .sqlite_store(config.store_path.clone())
+        .authenticator(keystore.clone().into())
+        .in_debug_mode(true.into());
+    if let Some(seed) = config.seed {
+        builder = builder.rng(Box::new(seeded_rng(seed)));
+    }
+    let client = builder.build().await?;
+
+    Ok((client, keystore))
+}
+
+/// Opens the keystore of `config`, creating its directory if needed.
+///
+/// Fails if the keystore belongs to another network than the configured node.
+pub fn open_keystore(config: &Config) -> Result<FaucetKeyStore, FaucetError> {
+    claim_for_network(&config.keystore_path, &config.rpc.endpoint.network_name())?;
+    FilesystemKeyStore::new(config.keystore_path.clone()).map_err(|err| {
+        FaucetError::Config(format!(
+            "failed to open keystore at `{}`: {err}",
+            config.keystore_path.display()
+        ))
+    })
+}
+
+/// Parses the hex seed of a reproducible run, see [`Config::seed`]. Seeds of any length are
+/// accepted and hashed into the word seeding the RNG.
+pub fn parse_seed(input: &str) -> Result<Word, FaucetError> {
+    let digits = input.trim().trim_start_matches("0x");
+    let bytes = hex::decode(digits)
+        .map_err(|err| FaucetError::Config(format!("invalid seed `{input}`: {err}")))?;
+    if bytes.is_empty() {
+        return Err(FaucetError::Config("the seed must not be empty".into()));
+    }
+    Ok(Rpo256::hash(&bytes))
+}
+
+/// RNG drawing the same account seeds, keys and note serial numbers for the same `seed`.
+pub fn seeded_rng(seed: Word) -> ClientRng {
+    ClientRng::new(Box::new(RpoRandomCoin::new(seed)))
+}
@@ -0,0 +1,428 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use miden_client::Word;
+use miden_objects::address::NetworkId;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "fault-injection")]
+use crate::fault::FaultConfig;
+use crate::{
+    abuse::AbusePolicyConfig,
+    access::{validate_api_keys, ApiKeyConfig, TiersConfig},
+    deploy::TokenConfig,
+    email::SmtpConfig,
+    explorer::{Explorer, ExplorerConfig},
+    finality::FinalityConfig,
+    github::GithubConfig,
+    http::HttpConfig,
+    mint::AmountConfig,
+    network::{validate_network_name, NetworkConfig, MAINNET},
+    referral::ReferralConfig,
+    rpc::RpcConfig,
+    service::ServiceConfig,
+    sync::SyncConfig,
+    tls::TlsConfig,
+    watcher::PollConfig,
+    webhook::WebhookConfig,
+    FaucetError,
+};
+
+/// Name of the environment variable that overrides the configuration file location.
+pub const CONFIG_PATH_ENV: &str = "FAUCET_CONFIG";
+
+/// Configuration file looked up in the working directory when no override is given.
+pub const DEFAULT_CONFIG_PATH: &str = "./faucet.toml";
+
+/// Directory holding a subdirectory of files per network, e.g. `./data/testnet/store.sqlite3`,
+/// for configurations that leave their paths unset.
+pub const DATA_DIR: &str = "./data";
+
+/// Top-level configuration shared by all faucet binaries.
+///
+/// Every field has a default, so an absent or empty `faucet.toml` mints on testnet. Loaded
+/// configurations keep the store, keystore and ledger in the data directory of their network
+/// unless they set the paths, see [`Config::use_network_dir`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub store_path: PathBuf,
+    pub keystore_path: PathBuf,
+    /// SQLite database holding the faucet's own records (mint ledger, claim status).
+    pub ledger_path: PathBuf,
+    pub rpc: RpcConfig,
+    pub service: ServiceConfig,
+    pub sync: SyncConfig,
+    pub poll: PollConfig,
+    pub mint: AmountConfig,
+    /// Token of the faucets `deploy` creates.
+    pub token: TokenConfig,
+    /// Overrides of the `[mint]` amounts per access tier of the APIs.
+    pub tiers: TiersConfig,
+    /// Keys granting the `api_key` or `admin` tier, and the roles of [`crate::authz`].
+    pub api_keys: Vec<ApiKeyConfig>,
+    /// Checks of the mints requested through the APIs, see [`crate::abuse`].
+    pub abuse_policies: Vec<AbusePolicyConfig>,
+    /// GitHub sign-in required to mint through the REST API.
+    pub github: Option<GithubConfig>,
+    /// Server sending the notes of mints requested with an email address.
+    pub smtp: Option<SmtpConfig>,
+    /// Endpoint notified when a minted note is claimed.
+    pub webhook: Option<WebhookConfig>,
+    pub explorer: ExplorerConfig,
+    /// Independent source confirming commitments before receipts are issued.
+    pub finality: Option<FinalityConfig>,
+    /// Bonus mints crediting the referrers of mints requested with a referral code.
+    pub referral: Option<ReferralConfig>,
+    /// HTTPS for the REST API, served in plain HTTP when unset.
+    pub tls: Option<TlsConfig>,
+    /// CORS and security headers of the REST API.
+    pub http: HttpConfig,
+    /// Networks `serve` mints on next to the one of the top-level settings, see
+    /// [`crate::network`].
+    pub networks: BTreeMap<String, NetworkConfig>,
+    #[cfg(feature = "fault-injection")]
+    pub fault_injection: FaultConfig,
+    /// Seeds the client RNG, so account IDs and note commitments repeat from run to run. Only
+    /// set from the command line with `--seed`: fixed serial numbers make notes predictable.
+    #[serde(skip)]
+    pub seed: Option<Word>,
+    /// API key the CLI presents for commands guarded by a role, see [`crate::authz`]. Only set
+    /// from the command line with `--api-key` or from `$FAUCET_API_KEY`.
+    #[serde(skip)]
+    pub api_key: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            store_path: PathBuf::from("./store.sqlite3"),
+            keystore_path: PathBuf::from("./keystore"),
+            ledger_path: PathBuf::from("./ledger.sqlite3"),
+            rpc: RpcConfig::default(),
+            service: ServiceConfig::default(),
+            sync: SyncConfig::default(),
+            poll: PollConfig::default(),
+            mint: AmountConfig::default(),
+            token: TokenConfig::default(),
+            tiers: TiersConfig::default(),
+            api_keys: Vec::new(),
+            abuse_policies: Vec::new(),
+            github: None,
+            smtp: None,
+            webhook: None,
+            explorer: ExplorerConfig::default(),
+            finality: None,
+            referral: None,
+            tls: None,
+            http: HttpConfig::default(),
+            networks: BTreeMap::new(),
+            #[cfg(feature = "fault-injection")]
+            fault_injection: FaultConfig::default(),
+            seed: None,
+            api_key: None,
+        }
+    }
+}
+
+impl Config {
+    /// Default configuration with every file kept inside `data_dir`.
+    pub fn in_data_dir(data_dir: &Path) -> Self {
+        Self {
+            store_path: data_dir.join("store.sqlite3"),
+            keystore_path: data_dir.join("keystore"),
+            ledger_path: data_dir.join("ledger.sqlite3"),
+            ..Self::default()
+        }
+    }
+
+    /// Parses the configuration file at `path`, keeping the files whose paths it leaves unset in
+    /// the data directory of its network, see [`Config::use_network_dir`].
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, FaucetError> {
+        let contents = fs::read_to_string(path.as_ref())?;
+        let table: toml::Table = toml::from_str(&contents)?;
+        let mut config: Self = toml::from_str(&contents)?;
+        config.use_network_dir(|name| table.contains_key(name))?;
+        Ok(config)
+    }
+
+    /// Loads the configuration from `$FAUCET_CONFIG` or `./faucet.toml`.
+    ///
+    /// An explicitly configured path must exist; the default path is optional and falls back to
+    /// [`Config::default`], with the files in the data directory of testnet, when missing.
+    pub fn load() -> Result<Self, FaucetError> {
+        match Self::location() {
+            Some(path) => Self::from_file(path),
+            None => {
+                let mut config = Self::default();
+                config.use_network_dir(|_| false)?;
+                Ok(config)
+            }
+        }
+    }
+
+    /// Data directory of the network of the configured node, e.g. `./data/testnet`.
+    pub fn network_dir(&self) -> PathBuf {
+        let name: String = self
+            .rpc
+            .endpoint
+            .network_name()
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | '0'..='9' | '.' | '-' | '_' => c,
+                _ => '_',
+            })
+            .collect();
+        Path::new(DATA_DIR).join(name)
+    }
+
+    /// Moves the store, keystore and ledger into [`Config::network_dir`], creating it, unless
+    /// `configured` tells their path was set explicitly.
+    ///
+    /// Files left at the paths used before data directories existed, e.g. `./store.sqlite3`, are
+    /// kept as long as the data directory has none, so upgrades do not start over with an empty
+    /// store. The network recorded next to them still keeps other networks from using them, see
+    /// [`crate::network::claim_for_network`].
+    pub fn use_network_dir(
+        &mut self,
+        configured: impl Fn(&str) -> bool,
+    ) -> Result<(), FaucetError> {
+        let dir = self.network_dir();
+        let mut used = false;
+        for (name, path, file) in [
+            ("store_path", &mut self.store_path, "store.sqlite3"),
+            ("keystore_path", &mut self.keystore_path, "keystore"),
+            ("ledger_path", &mut self.ledger_path, "ledger.sqlite3"),
+        ] {
+            if configured(name) {
+                continue;
+            }
+            let derived = dir.join(file);
+            if path.exists() && !derived.exists() {
+                eprintln!(
+                    "Using `{}` from before per-network data directories, move it to `{}`",
+                    path.display(),
+                    derived.display()
+                );
+                continue;
+            }
+            *path = derived;
+            used = true;
+        }
+        if used {
+            fs::create_dir_all(&dir)?;
+        }
+        Ok(())
+    }
+
+    /// File read by [`Config::load`], `None` when it falls back to the defaults.
+    pub fn location() -> Option<PathBuf> {
+        match std::env::var_os(CONFIG_PATH_ENV) {
+            Some(path) => Some(PathBuf::from(path)),
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => Some(DEFAULT_CONFIG_PATH.into()),
+            None => None,
+        }
+    }
+
+    /// Checks the value ranges of every section without contacting the network.
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        for (name, path) in [
+            ("store_path", &self.store_path),
+            ("keystore_path", &self.keystore_path),
+            ("ledger_path", &self.ledger_path),
+        ] {
+            let dir = path
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            if !dir.is_dir() {
+                return Err(FaucetError::Config(format!(
+                    "the directory of {name} `{}` does not exist",
+                    path.display()
+                )));
+            }
+        }
+        self.token.validate()?;
+        self.rpc.validate()?;
+        self.service.validate()?;
+        self.sync.validate()?;
+        self.poll.validate()?;
+        self.mint.validate()?;
+        self.tiers.validate(&self.mint)?;
+        validate_api_keys(&self.api_keys)?;
+        for policy in &self.abuse_policies {
+            policy.validate()?;
+        }
+        if let Some(github) = &self.github {
+            github.validate()?;
+        }
+        if let Some(smtp) = &self.smtp {
+            smtp.validate()?;
+        }
+        if let Some(webhook) = &self.webhook {
+            webhook.validate()?;
+        }
+        self.explorer.validate()?;
+        if let Some(finality) = &self.finality {
+            finality.validate()?;
+        }
+        if let Some(referral) = &self.referral {
+            referral.validate()?;
+        }
+        if let Some(tls) = &self.tls {
+            tls.validate()?;
+        }
+        self.http.validate()?;
+        #[cfg(feature = "fault-injection")]
+        self.fault_injection.validate()?;
+        self.validate_networks()
+    }
+
+    /// Checks the `[networks]` profiles, which must not share a client store or ledger with each
+    /// other or the default network.
+    fn validate_networks(&self) -> Result<(), FaucetError> {
+        if self.networks.contains_key(&self.service.network) {
+            return Err(FaucetError::Config(format!(
+                "networks.{0} clashes with the default network `{0}` of service.network",
+                self.service.network
+            )));
+        }
+        let mut stores = HashMap::new();
+        let mut ledgers = HashMap::new();
+        for name in self.network_names() {
+            validate_network_name(&name)?;
+            let network = self.network(&name)?;
+            if name != self.service.network {
+                network.validate().map_err(|err| match err {
+                    FaucetError::Config(message) => {
+                        FaucetError::Config(format!("networks.{name}: {message}"))
+                    }
+                    err => err,
+                })?;
+            }
+            for (kind, paths, path) in [
+                ("store_path", &mut stores, network.store_path),
+                ("ledger_path", &mut ledgers, network.ledger_path),
+            ] {
+                let path = std::path::absolute(&path).unwrap_or(path);
+                if let Some(other) = paths.insert(path, name.clone()) {
+                    return Err(FaucetError::Config(format!(
+                        "networks `{other}` and `{name}` share their {kind}"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the top-level settings target mainnet: the default network or the host of the node
+    /// is named [`MAINNET`].
+    pub fn is_mainnet(&self) -> bool {
+        self.service.network == MAINNET || self.rpc.endpoint.network_name() == MAINNET
+    }
+
+    /// Network whose prefix bech32 account IDs must carry on the top-level settings: mainnet, or
+    /// testnet and devnet for their well-known nodes. `None` for local and other nodes, on which
+    /// IDs of any network are accepted.
+    pub fn account_network(&self) -> Option<NetworkId> {
+        if self.is_mainnet() {
+            return Some(NetworkId::Mainnet);
+        }
+        match self.rpc.endpoint.network_name().as_str() {
+            "testnet" => Some(NetworkId::Testnet),
+            "devnet" => Some(NetworkId::Devnet),
+            _ => None,
+        }
+    }
+
+    /// Names of the served networks, the default network of the top-level settings first.
+    pub fn network_names(&self) -> Vec<String> {
+        let mut names = vec![self.service.network.clone()];
+        names.extend(self.networks.keys().cloned());
+        names
+    }
+
+    /// Settings of network `name`: the top-level settings with the node, files, faucet and
+    /// explorer of its `[networks.<name>]` section, or unchanged for the default network.
+    pub fn network(&self, name: &str) -> Result<Config, FaucetError> {
+        let mut config = Config {
+            networks: BTreeMap::new(),
+            ..self.clone()
+        };
+        if name == self.service.network {
+            return Ok(config);
+        }
+        let Some(network) = self.networks.get(name) else {
+            return Err(FaucetError::UnknownNetwork(
+                name.to_string(),
+                self.network_names().join(", "),
+            ));
+        };
+        config.rpc = network.rpc.clone();
+        config.store_path = network.store_path.clone();
+        config.keystore_path = network.keystore_path.clone();
+        config.ledger_path = network.ledger_path.clone();
+        config.service.network = name.to_string();
+        config.service.faucet_id = Some(network.faucet_id.clone());
+        config.service.tokens = network.tokens.clone();
+        if let Some(explorer) = &network.explorer {
+            config.explorer = explorer.clone();
+        }
+        Ok(config)
+    }
+
+    /// Directory of the account locks taken by the processes sharing the store, see
+    /// [`AccountLock`](crate::executor::AccountLock).
+    pub fn lock_dir(&self) -> PathBuf {
+        let dir = self
+            .store_path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        dir.join("locks")
+    }
+
+    /// Explorer of the configured network, `None` if it has none.
+    pub fn explorer(&self) -> Option<Explorer> {
+        Explorer::for_network(&self.explorer, &self.rpc.endpoint)
+    }
+
+    /// Makes every path absolute, resolving relative paths against the working directory the way
+    /// the commands do.
+    pub fn resolve_paths(&mut self) -> Result<(), FaucetError> {
+        for path in [
+            &mut self.store_path,
+            &mut self.keystore_path,
+            &mut self.ledger_path,
+        ] {
+            *path = std::path::absolute(&*path)?;
+        }
+        if let Some(ca_cert) = &mut self.rpc.ca_cert {
+            *ca_cert = std::path::absolute(&*ca_cert)?;
+        }
+        for network in self.networks.values_mut() {
+            for path in [
+                &mut network.store_path,
+                &mut network.keystore_path,
+                &mut network.ledger_path,
+            ] {
+                *path = std::path::absolute(&*path)?;
+            }
+            if let Some(ca_cert) = &mut network.rpc.ca_cert {
+                *ca_cert = std::path::absolute(&*ca_cert)?;
+            }
+        }
+        if let Some(tls) = &mut self.tls {
+            for path in [&mut tls.cert_path, &mut tls.key_path]
+                .into_iter()
+                .flatten()
+            {
+                *path = std::path::absolute(&*path)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+//! Decommissioning faucets.
+//!
+//! [`decommission`] takes a faucet out of service in the order a controlled teardown needs: it
+//! pauses the faucet so no new mint can be executed, reclaims the expired notes still holding its
+//! tokens, gathers the final figures of the [`Ledger`] into a [`DecommissionReport`] and marks
+//! the deployment retired in the registry, so token faucets no longer resolve to it.
+//!
+//! Mints whose note has not expired yet cannot be reclaimed; they are listed in the report as
+//! outstanding and can be reclaimed later with `faucet reclaim`, which does not depend on the
+//! faucet being in service.
+
+use miden_client::account::AccountId;
+use serde::Serialize;
+
+use crate::{
+    ledger::{CampaignStats, DeploymentRecord, Ledger, MintStats},
+    node::FaucetNode,
+    pause::{is_paused, set_paused},
+    reclaim::reclaim_expired,
+    watcher::{wait_for_transaction, BlockWatcher, SharedNode},
+    FaucetError,
+};
+
+/// Final report of a decommissioned faucet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecommissionReport {
+    pub faucet_id: String,
+    /// Registry entry of the faucet, `None` for faucets not deployed with `deploy`.
+    pub deployment: Option<DeploymentRecord>,
+    /// Transaction pausing the faucet, `None` if it was paused already.
+    pub pause_transaction_id: Option<String>,
+    pub paused_block: Option<u32>,
+    /// Expired mints reclaimed by the decommission.
+    pub reclaimed: u64,
+    pub reclaimed_amount: u64,
+    /// Expired mints whose reclaim failed.
+    pub failed_reclaims: Vec<FailedReclaim>,
+    /// Unclaimed mints left after the reclaims.
+    pub outstanding: Vec<OutstandingMint>,
+    pub stats: MintStats,
+    pub campaigns: Vec<CampaignStats>,
+    /// When the deployment was retired, `None` if a reclaim failed.
+    pub retired_at: Option<u64>,
+}
+
+/// Expired mint of a [`DecommissionReport`] that could not be reclaimed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FailedReclaim {
+    pub mint_id: i64,
+    pub amount: u64,
+    pub error: String,
+}
+
+/// Unclaimed mint of a [`DecommissionReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutstandingMint {
+    pub mint_id: i64,
+    pub recipient: String,
+    pub amount: u64,
+    /// Block from which the note can be reclaimed, `None` for notes that cannot be.
+    pub reclaim_block: Option<u32>,
+}
+
+/// Pauses `faucet_id`, reclaims its expired mints and retires its deployment.
+///
+/// The faucet is only retired once every expired mint was reclaimed; otherwise the report lists
+/// the failed reclaims and the decommission can be run again, which skips the pause.
+pub async fn decommission<N: FaucetNode>(
+    node: &SharedNode<N>,
+    watcher: &BlockWatcher,
+    ledger: &Ledger,
+    faucet_id: AccountId,
+) -> Result<DecommissionReport, FaucetError> {
+    let (pause_transaction_id, paused_block) =
+        if is_paused(&mut *node.lock().await, faucet_id).await? {
+            (None, None)
+        } else {
+            let transaction_id = set_paused(&mut *node.lock().await, faucet_id, true).await?;
+            let block_num = wait_for_transaction(node, watcher, transaction_id).await?;
+            (Some(transaction_id.to_hex()), Some(block_num.as_u32()))
+        };
+
+    let reclaims = reclaim_expired(node, watcher, ledger, faucet_id).await?;
+    let failed_reclaims = reclaims
+        .failed
+        .iter()
+        .map(|(mint, err)| FailedReclaim {
+            mint_id: mint.id,
+            amount: mint.amount,
+            error: err.to_string(),
+        })
+        .collect::<Vec<_>>();
+
+    let faucet = faucet_id.to_hex();
+    let outstanding = ledger
+        .unclaimed_mints()?
+        .into_iter()
+        .filter(|mint| mint.faucet_id == faucet)
+        .map(|mint| OutstandingMint {
+            mint_id: mint.id,
+            recipient: mint.recipient,
+            amount: mint.amount,
+            reclaim_block: mint.reclaim_block,
+        })
+        .collect();
+    let retired_at = failed_reclaims
+        .is_empty()
+        .then(|| ledger.retire_deployment(faucet_id))
+        .transpose()?;
+    let deployment = ledger
+        .deployments()?
+        .into_iter()
+        .find(|deployment| deployment.faucet_id == faucet);
+
+    Ok(DecommissionReport {
+        faucet_id: faucet,
+        deployment,
+        pause_transaction_id,
+        paused_block,
+        reclaimed: reclaims.reclaimed.len() as u64,
+        reclaimed_amount: reclaims.reclaimed_amount,
+        failed_reclaims,
+        outstanding,
+        stats: ledger.stats(Some(faucet_id))?,
+        campaigns: ledger.campaign_stats(Some(faucet_id))?,
+        retired_at,
+    })
+}
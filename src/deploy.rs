@@ -0,0 +1,520 @@
+use std::{fmt, str::FromStr};
+
+use miden_client::{
+    account::{
+        component::{BasicFungibleFaucet, NetworkFungibleFaucet},
+        Account, AccountBuilder, AccountComponent, AccountId, AccountStorageMode, AccountType,
+    },
+    asset::{FungibleAsset, TokenSymbol},
+    auth::{AuthRpoFalcon512, AuthSecretKey},
+    crypto::rpo_falcon512::SecretKey,
+    testing::Auth,
+    transaction::{TransactionId, TransactionRequest},
+    Felt, Word,
+};
+use miden_lib::{account::interface::AccountInterface, utils::ScriptBuilder};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    node::{FaucetNode, TransactionCost},
+    pause::pausable_component,
+    script::{script_request, ScriptParams, ScriptTemplate, ScriptValue},
+    tx::tx_scripts,
+    wallet::{keystore_key, wallet_from_seed},
+    FaucetError,
+};
+
+/// Token of the faucets deployed without a `[token]` section.
+pub const TOKEN_SYMBOL: &str = "MDE";
+pub const TOKEN_DECIMALS: u8 = 8;
+pub const MAX_SUPPLY: u64 = 1_000_000;
+
+/// Parameters every deployment script can use, filled in by [`deploy_faucet_with_params`].
+pub const DEPLOY_SCRIPT_BUILTINS: &[&str] = &["faucet", "owner", "max_supply", "decimals"];
+
+/// Components of every deployed faucet besides its authentication, in account order.
+pub const FAUCET_COMPONENTS: &[&str] = &["network-fungible-faucet", "pausable"];
+
+/// Checks token parameters against the limits of fungible faucets and assets.
+pub fn check_token_parameters(decimals: u8, max_supply: u64) -> Result<(), FaucetError> {
+    if decimals > BasicFungibleFaucet::MAX_DECIMALS {
+        return Err(FaucetError::Config(format!(
+            "token decimals {decimals} exceed the maximum of {}",
+            BasicFungibleFaucet::MAX_DECIMALS
+        )));
+    }
+    if max_supply == 0 || max_supply > FungibleAsset::MAX_AMOUNT {
+        return Err(FaucetError::Config(format!(
+            "max supply {max_supply} must be between 1 and {}",
+            FungibleAsset::MAX_AMOUNT
+        )));
+    }
+    Ok(())
+}
+
+/// Token of the faucets `deploy` creates, read from the `[token]` section of the configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TokenConfig {
+    /// Ticker of the token, up to six uppercase letters.
+    pub symbol: String,
+    pub decimals: u8,
+    /// Tokens the faucet can ever issue, in base units.
+    pub max_supply: u64,
+}
+
+impl Default for TokenConfig {
+    fn default() -> Self {
+        Self {
+            symbol: TOKEN_SYMBOL.into(),
+            decimals: TOKEN_DECIMALS,
+            max_supply: MAX_SUPPLY,
+        }
+    }
+}
+
+impl TokenConfig {
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        self.token_symbol()?;
+        check_token_parameters(self.decimals, self.max_supply)
+    }
+
+    /// The symbol as stored by the faucet, failing for symbols the protocol cannot encode.
+    pub fn token_symbol(&self) -> Result<TokenSymbol, FaucetError> {
+        TokenSymbol::new(&self.symbol).map_err(|err| {
+            FaucetError::Config(format!("invalid token symbol `{}`: {err}", self.symbol))
+        })
+    }
+}
+
+/// Authentication of a deployed faucet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FaucetAuth {
+    /// Any transaction bumping the nonce is accepted, by anyone; the faucet's own procedures check
+    /// that the notes they serve were sent by the owner. Network faucets need it, as the network
+    /// executes their transactions without a key.
+    #[default]
+    IncrNonce,
+    /// Transactions are signed with a Falcon key created with the faucet and kept in the keystore.
+    RpoFalcon512,
+}
+
+impl FaucetAuth {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::IncrNonce => "incr-nonce",
+            Self::RpoFalcon512 => "rpo-falcon512",
+        }
+    }
+}
+
+impl fmt::Display for FaucetAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FaucetAuth {
+    type Err = FaucetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "incr-nonce" => Ok(Self::IncrNonce),
+            "rpo-falcon512" => Ok(Self::RpoFalcon512),
+            other => Err(FaucetError::Config(format!(
+                "invalid faucet auth `{other}`, expected `incr-nonce` or `rpo-falcon512`"
+            ))),
+        }
+    }
+}
+
+/// Parses the storage mode of a faucet supplied by a user, `network`, `public` or `private`.
+pub fn parse_storage_mode(input: &str) -> Result<AccountStorageMode, FaucetError> {
+    match input.trim().to_ascii_lowercase().as_str() {
+        "network" => Ok(AccountStorageMode::Network),
+        "public" => Ok(AccountStorageMode::Public),
+        "private" => Ok(AccountStorageMode::Private),
+        other => Err(FaucetError::Config(format!(
+            "invalid storage mode `{other}`, expected `network`, `public` or `private`"
+        ))),
+    }
+}
+
+/// Name of `storage_mode` as accepted by [`parse_storage_mode`].
+pub fn storage_mode_name(storage_mode: AccountStorageMode) -> &'static str {
+    match storage_mode {
+        AccountStorageMode::Network => "network",
+        AccountStorageMode::Public => "public",
+        AccountStorageMode::Private => "private",
+    }
+}
+
+/// Account of a deployed faucet: a network faucet with nonce-only authentication by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaucetAccount {
+    pub auth: FaucetAuth,
+    pub storage_mode: AccountStorageMode,
+    /// Keystore key a [`FaucetAuth::RpoFalcon512`] faucet is bound to, see
+    /// [`keystore_key`]; a fresh key is created when unset.
+    pub key_id: Option<Word>,
+}
+
+impl Default for FaucetAccount {
+    fn default() -> Self {
+        Self {
+            auth: FaucetAuth::IncrNonce,
+            storage_mode: AccountStorageMode::Network,
+            key_id: None,
+        }
+    }
+}
+
+impl FaucetAccount {
+    /// Refuses faucets that cannot work, and on `mainnet` those that are unsafe there, see
+    /// [`Config::is_mainnet`](crate::config::Config::is_mainnet).
+    ///
+    /// A keyed network faucet is refused everywhere, since the network cannot sign its
+    /// transactions. On mainnet, private faucets are refused, since nobody could check their
+    /// supply, and nonce-only faucets must be network accounts. Anyone can execute transactions
+    /// against a nonce-only faucet, so only its procedures guard it: minting and pausing serve
+    /// notes sent by the owner alone, which leaves the owner's key, see
+    /// [`check_owner`](Self::check_owner), as the key of the faucet. Faucets outside the network
+    /// have a key of their own to sign with, and must use it.
+    pub fn check(&self, mainnet: bool) -> Result<(), FaucetError> {
+        if self.key_id.is_some() && self.auth != FaucetAuth::RpoFalcon512 {
+            return Err(FaucetError::Config(format!(
+                "a {} faucet has no key, a key ID needs {}",
+                self.auth,
+                FaucetAuth::RpoFalcon512
+            )));
+        }
+        let unsafe_account = |reason: &str| {
+            FaucetError::UnsafeFaucetAccount(
+                self.auth.to_string(),
+                storage_mode_name(self.storage_mode).to_string(),
+                reason.to_string(),
+            )
+        };
+        if self.auth == FaucetAuth::RpoFalcon512 && self.storage_mode == AccountStorageMode::Network
+        {
+            return Err(unsafe_account(
+                "the network cannot sign the transactions of a network faucet",
+            ));
+        }
+        if !mainnet {
+            return Ok(());
+        }
+        if self.auth == FaucetAuth::IncrNonce && self.storage_mode != AccountStorageMode::Network {
+            return Err(unsafe_account(
+                "a faucet outside the network must sign its transactions on mainnet",
+            ));
+        }
+        if self.storage_mode == AccountStorageMode::Private {
+            return Err(unsafe_account("nobody could check its supply on mainnet"));
+        }
+        Ok(())
+    }
+
+    /// Refuses, on `mainnet`, a nonce-only faucet of an `owner` without a key.
+    ///
+    /// The procedures of a nonce-only faucet only trust the sender of the notes they serve, see
+    /// [`check`](Self::check), so an owner that signs nothing, e.g. a nonce-only account itself,
+    /// would let anyone mint and pause in its name.
+    pub fn check_owner(&self, owner: &Account, mainnet: bool) -> Result<(), FaucetError> {
+        if !mainnet || self.auth != FaucetAuth::IncrNonce {
+            return Ok(());
+        }
+        let keyed = AccountInterface::from(owner)
+            .auth()
+            .iter()
+            .any(|scheme| !scheme.get_public_key_commitments().is_empty());
+        if keyed {
+            return Ok(());
+        }
+        Err(FaucetError::UnsafeFaucetAccount(
+            self.auth.to_string(),
+            storage_mode_name(self.storage_mode).to_string(),
+            format!(
+                "owner {} has no key to guard its minting and pausing on mainnet",
+                owner.id()
+            ),
+        ))
+    }
+}
+
+/// Result of [`deploy_faucet`].
+#[derive(Debug, Clone)]
+pub struct Deployment {
+    pub faucet: Account,
+    pub transaction_id: TransactionId,
+    /// Cost of the deployment transaction, if the node measured it.
+    pub cost: Option<TransactionCost>,
+}
+
+/// Creates a pausable network fungible faucet owned by `owner` and deploys it by running
+/// `script_code` against it.
+pub async fn deploy_faucet<N: FaucetNode>(
+    node: &mut N,
+    owner: AccountId,
+    script_code: &str,
+) -> Result<Deployment, FaucetError> {
+    deploy_faucet_with_params(
+        node,
+        owner,
+        script_code,
+        &ScriptParams::default(),
+        &TokenConfig::default(),
+        FaucetAccount::default(),
+    )
+    .await
+}
+
+/// Like [`deploy_faucet`], issuing `token` from an `account` faucet with the deployment script
+/// rendered as a [`ScriptTemplate`].
+///
+/// Besides `params`, the script can use the [`DEPLOY_SCRIPT_BUILTINS`]: the new faucet, its owner,
+/// its maximum supply and its decimals. The token and the script are validated, and the script
+/// compiled, before the faucet is added to the store. The new key of a
+/// [`FaucetAuth::RpoFalcon512`] faucet is added to the keystore with it.
+pub async fn deploy_faucet_with_params<N: FaucetNode>(
+    node: &mut N,
+    owner: AccountId,
+    script_code: &str,
+    params: &ScriptParams,
+    token: &TokenConfig,
+    account: FaucetAccount,
+) -> Result<Deployment, FaucetError> {
+    let (faucet, request) =
+        deployment_request(node, owner, script_code, params, token, account).await?;
+    let transaction_id = node.submit_transaction(faucet.id(), request).await?;
+
+    Ok(Deployment {
+        faucet,
+        transaction_id,
+        cost: node.take_transaction_cost(transaction_id),
+    })
+}
+
+/// Executes the deployment [`deploy_faucet_with_params`] would submit, without submitting it, and
+/// returns its cost.
+///
+/// Executing needs the new faucet in the store, so it is added like in a deployment; the faucet
+/// never reaches the network.
+pub async fn estimate_deployment<N: FaucetNode>(
+    node: &mut N,
+    owner: AccountId,
+    script_code: &str,
+    params: &ScriptParams,
+    token: &TokenConfig,
+    account: FaucetAccount,
+) -> Result<(Account, TransactionCost), FaucetError> {
+    let (faucet, request) =
+        deployment_request(node, owner, script_code, params, token, account).await?;
+    let cost = node.execute_transaction(faucet.id(), request).await?;
+    Ok((faucet, cost))
+}
+
+/// New faucet of a deployment, added to the store with its key, and the transaction deploying it.
+async fn deployment_request<N: FaucetNode>(
+    node: &mut N,
+    owner: AccountId,
+    script_code: &str,
+    params: &ScriptParams,
+    token: &TokenConfig,
+    account: FaucetAccount,
+) -> Result<(Account, TransactionRequest), FaucetError> {
+    let faucet = create_faucet(node, owner, script_code, params, token, account).await?;
+    let request = deploy_script_request(node, faucet.id(), owner, script_code, params, token)?;
+    Ok((faucet, request))
+}
+
+/// First half of [`deploy_faucet_with_params`]: creates the faucet and adds it to the store with
+/// its key, without deploying it.
+///
+/// The token is validated, and the deployment script compiled against the new faucet, before
+/// anything is stored.
+pub async fn create_faucet<N: FaucetNode>(
+    node: &mut N,
+    owner: AccountId,
+    script_code: &str,
+    params: &ScriptParams,
+    token: &TokenConfig,
+    account: FaucetAccount,
+) -> Result<Account, FaucetError> {
+    token.validate()?;
+    let mut faucet_init_seed = [0_u8; 32];
+    node.rng().fill_bytes(&mut faucet_init_seed);
+
+    let key = match account.auth {
+        FaucetAuth::IncrNonce => None,
+        FaucetAuth::RpoFalcon512 => Some(match account.key_id {
+            Some(key_id) => (keystore_key(node, key_id).await?, false),
+            None => (new_faucet_key(node.rng()), true),
+        }),
+    };
+    let key_commitment = key
+        .as_ref()
+        .map(|(key, _)| key.public_key().to_commitment());
+    let faucet = faucet_account(faucet_init_seed, owner, token, account, key_commitment)?;
+    deploy_script_request(node, faucet.id(), owner, script_code, params, token)?;
+
+    node.add_account(&faucet).await?;
+    if let Some((key, true)) = key {
+        node.add_key(AuthSecretKey::RpoFalcon512(key)).await?;
+    }
+    Ok(faucet)
+}
+
+/// Second half of [`deploy_faucet_with_params`]: the transaction running the deployment script
+/// against `faucet_id`, with the [`DEPLOY_SCRIPT_BUILTINS`] filled in.
+pub fn deploy_script_request<N: FaucetNode>(
+    node: &N,
+    faucet_id: AccountId,
+    owner: AccountId,
+    script_code: &str,
+    params: &ScriptParams,
+    token: &TokenConfig,
+) -> Result<TransactionRequest, FaucetError> {
+    script_request(
+        node,
+        &ScriptTemplate::new(script_code),
+        &deployment_params(params, faucet_id, owner, token),
+        DEPLOY_SCRIPT_BUILTINS,
+    )
+}
+
+/// New Falcon key of a [`FaucetAuth::RpoFalcon512`] faucet, drawn from `rng`.
+fn new_faucet_key(rng: &mut impl RngCore) -> SecretKey {
+    let mut key_seed = [0_u8; 32];
+    rng.fill_bytes(&mut key_seed);
+    SecretKey::with_rng(&mut ChaCha20Rng::from_seed(key_seed))
+}
+
+/// Pausable fungible faucet of `owner` issuing `token`, authenticated by the key committed to by
+/// `key_commitment` if any.
+fn faucet_account(
+    init_seed: [u8; 32],
+    owner: AccountId,
+    token: &TokenConfig,
+    account: FaucetAccount,
+    key_commitment: Option<Word>,
+) -> Result<Account, FaucetError> {
+    let auth: AccountComponent = match key_commitment {
+        None => Auth::IncrNonce.into(),
+        Some(commitment) => AuthRpoFalcon512::new(commitment.into()).into(),
+    };
+    let network_faucet_component = NetworkFungibleFaucet::new(
+        token.token_symbol()?,
+        token.decimals,
+        Felt::new(token.max_supply),
+        owner,
+    )
+    .map_err(|err| FaucetError::Config(format!("invalid faucet parameters: {err}")))?;
+
+    // Build the account
+    Ok(AccountBuilder::new(init_seed)
+        .account_type(AccountType::FungibleFaucet)
+        .storage_mode(account.storage_mode)
+        .with_auth_component(auth)
+        .with_component(network_faucet_component)
+        .with_component(pausable_component(owner)?)
+        .build()?)
+}
+
+/// `params` with the [`DEPLOY_SCRIPT_BUILTINS`] of a deployment filled in.
+fn deployment_params(
+    params: &ScriptParams,
+    faucet: AccountId,
+    owner: AccountId,
+    token: &TokenConfig,
+) -> ScriptParams {
+    params
+        .clone()
+        .with("faucet", ScriptValue::Account(faucet))
+        .with("owner", ScriptValue::Account(owner))
+        .with("max_supply", ScriptValue::Felt(token.max_supply))
+        .with("decimals", ScriptValue::Felt(token.decimals.into()))
+}
+
+/// Accounts and script of a deployment, as planned by [`plan_deployment`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeploymentPlan {
+    pub faucet_id: String,
+    pub owner_id: String,
+    /// Whether the owner wallet is created by the deployment.
+    pub new_owner: bool,
+    /// Components of the faucet account, its authentication first.
+    pub components: Vec<String>,
+    pub auth: String,
+    /// Commitment of the public key of a `rpo-falcon512` faucet.
+    pub key_commitment: Option<String>,
+    pub storage_mode: String,
+    pub token: TokenConfig,
+    /// MAST root of the compiled deployment script.
+    pub script_root: String,
+}
+
+/// Plans the deployment [`deploy_faucet_with_params`] would make, without touching the node or
+/// the store.
+///
+/// The accounts are derived from `rng` in the order of a deployment, so a client RNG seeded with
+/// the same seed, see [`crate::client::seeded_rng`], deploys exactly the planned accounts. With
+/// `owner` unset, the owner wallet a deployment would create first is planned as well. A
+/// `key_id` is taken as the commitment it is, without checking that the keystore holds it.
+pub fn plan_deployment(
+    rng: &mut impl RngCore,
+    owner: Option<AccountId>,
+    script_code: &str,
+    params: &ScriptParams,
+    token: &TokenConfig,
+    account: FaucetAccount,
+) -> Result<DeploymentPlan, FaucetError> {
+    token.validate()?;
+    let new_owner = owner.is_none();
+    let owner = match owner {
+        Some(owner) => owner,
+        None => {
+            let mut seed = [0_u8; 32];
+            rng.fill_bytes(&mut seed);
+            wallet_from_seed(seed)?.0.id()
+        }
+    };
+    let mut faucet_init_seed = [0_u8; 32];
+    rng.fill_bytes(&mut faucet_init_seed);
+    let key_commitment = match account.auth {
+        FaucetAuth::IncrNonce => None,
+        FaucetAuth::RpoFalcon512 => Some(
+            account
+                .key_id
+                .unwrap_or_else(|| new_faucet_key(rng).public_key().to_commitment()),
+        ),
+    };
+    let faucet = faucet_account(faucet_init_seed, owner, token, account, key_commitment)?;
+
+    let code = ScriptTemplate::new(script_code).render(
+        &deployment_params(params, faucet.id(), owner, token),
+        DEPLOY_SCRIPT_BUILTINS,
+    )?;
+    let script = tx_scripts().get_or_compile(&code, |code| {
+        ScriptBuilder::default()
+            .compile_tx_script(code)
+            .map_err(|err| FaucetError::Script(err.to_string()))
+    })?;
+
+    Ok(DeploymentPlan {
+        faucet_id: faucet.id().to_hex(),
+        owner_id: owner.to_hex(),
+        new_owner,
+        components: std::iter::once(account.auth.as_str())
+            .chain(FAUCET_COMPONENTS.iter().copied())
+            .map(String::from)
+            .collect(),
+        auth: account.auth.to_string(),
+        key_commitment: key_commitment.map(|commitment| commitment.to_hex()),
+        storage_mode: storage_mode_name(account.storage_mode).to_string(),
+        token: token.clone(),
+        script_root: script.root().to_hex(),
+    })
+}
@@ -0,0 +1,309 @@
+//! Environment diagnostics.
+//!
+//! Most failures reported by operators come from the environment rather than from the faucet: an
+//! unreachable or incompatible node, a store written by another client version, an unreadable
+//! keystore, a full disk or a skewed clock. [`run_checks`] looks at each of them in turn, without
+//! modifying anything, and [`Check::fix`] tells the operator what to do about the problems found.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use miden_client::{block::BlockHeader, rpc::NodeRpcClient};
+
+use crate::{
+    config::Config,
+    ledger::unix_now,
+    pause::pausable_library,
+    rpc::build_rpc_client,
+    script::{ScriptTemplate, DEPLOY_SCRIPT, SCRIPT_DIR},
+    store::check_store_schema,
+    FaucetError,
+};
+
+/// Free space below which the disk check warns.
+pub const LOW_DISK_SPACE: u64 = 1 << 30;
+
+/// Free space below which the disk check fails.
+pub const CRITICAL_DISK_SPACE: u64 = 100 << 20;
+
+/// Difference between the local clock and the latest block timestamp above which the clock check
+/// warns. Blocks are produced every few seconds, so a healthy node stays well within it.
+pub const MAX_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+/// Outcome of a single [`Check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    /// Works for now, but likely to cause trouble.
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Warn => "warn",
+            Self::Fail => "FAIL",
+        }
+    }
+}
+
+/// Result of one diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// What to do about a warning or failure.
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Runs every diagnostic against the environment described by `config`.
+pub async fn run_checks(config: &Config) -> Vec<Check> {
+    let (rpc, header) = check_rpc(config).await;
+    let clock = match header {
+        Some(header) => check_clock(u64::from(header.timestamp()), unix_now()),
+        None => Check::warn(
+            "clock",
+            "skipped, no block header from the node",
+            "fix the rpc check first",
+        ),
+    };
+    vec![
+        rpc,
+        clock,
+        check_store(&config.store_path),
+        check_keystore(&config.keystore_path),
+        check_scripts(),
+        check_disk_space(&config.store_path),
+    ]
+}
+
+/// Connects to the node and fetches its latest block header, also returned for [`check_clock`].
+pub async fn check_rpc(config: &Config) -> (Check, Option<BlockHeader>) {
+    let rpc = match build_rpc_client(&config.rpc).await {
+        Ok(rpc) => rpc,
+        Err(err @ (FaucetError::Connect { .. } | FaucetError::ConnectTimeout { .. })) => {
+            let fix = "check `rpc.endpoint`, that the node is up and that no firewall blocks the \
+                       port; behind a proxy, set `rpc.proxy`";
+            return (Check::fail("rpc", err.to_string(), fix), None);
+        }
+        Err(err) => {
+            let fix = "fix the `[rpc]` section of the configuration";
+            return (Check::fail("rpc", err.to_string(), fix), None);
+        }
+    };
+
+    let timeout = Duration::from_millis(config.rpc.request_timeout_ms);
+    match tokio::time::timeout(timeout, rpc.get_block_header_by_number(None, false)).await {
+        Ok(Ok((header, _))) => {
+            let detail = format!(
+                "node at block {}, block version {}",
+                header.block_num(),
+                header.version()
+            );
+            (Check::ok("rpc", detail), Some(header))
+        }
+        Ok(Err(err)) => {
+            let fix = "the node refused the request; make sure it runs a release compatible with \
+                       miden-client 0.12, and that `rpc.ca_cert` holds its certificate chain";
+            (Check::fail("rpc", err.to_string(), fix), None)
+        }
+        Err(_) => {
+            let detail = format!("no block header after {}ms", config.rpc.request_timeout_ms);
+            let fix = "the node is reachable but slow; raise `rpc.request_timeout_ms` or try again";
+            (Check::fail("rpc", detail, fix), None)
+        }
+    }
+}
+
+/// Compares the local clock, `now`, with the timestamp of the latest block, both in seconds.
+pub fn check_clock(block_timestamp: u64, now: u64) -> Check {
+    let max_skew = MAX_CLOCK_SKEW.as_secs();
+    if block_timestamp > now + max_skew {
+        return Check::warn(
+            "clock",
+            format!(
+                "local clock is {}s behind the latest block",
+                block_timestamp - now
+            ),
+            "synchronise the clock with NTP, e.g. `timedatectl set-ntp true`",
+        );
+    }
+    if now > block_timestamp + max_skew {
+        return Check::warn(
+            "clock",
+            format!(
+                "latest block is {}s older than the local clock",
+                now - block_timestamp
+            ),
+            "either the local clock is ahead, then synchronise it with NTP, or the node stopped \
+             producing blocks, then check its status",
+        );
+    }
+    Check::ok(
+        "clock",
+        format!(
+            "within {}s of the latest block",
+            now.abs_diff(block_timestamp)
+        ),
+    )
+}
+
+/// Checks that the client store, if it exists yet, has the schema the faucet expects.
+pub fn check_store(path: &Path) -> Check {
+    if !path.exists() {
+        return Check::warn(
+            "store",
+            format!("no store at `{}`", path.display()),
+            "it is created by the first command that connects; if one should exist, check \
+             `store_path`",
+        );
+    }
+    match check_store_schema(path) {
+        Ok(()) => Check::ok("store", format!("`{}` schema supported", path.display())),
+        Err(err) => Check::fail(
+            "store",
+            err.to_string(),
+            "the store was written by an incompatible client version; move it away and let the \
+             faucet rebuild it from the node, keeping the keystore",
+        ),
+    }
+}
+
+/// Checks that the keystore directory exists and every key file in it can be read.
+pub fn check_keystore(path: &Path) -> Check {
+    if !path.exists() {
+        return Check::warn(
+            "keystore",
+            format!("no keystore at `{}`", path.display()),
+            "it is created on first use; if keys were deployed already, point `keystore_path` at \
+             them",
+        );
+    }
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(err) => {
+            return Check::fail(
+                "keystore",
+                format!("cannot list `{}`: {err}", path.display()),
+                "make the directory readable by the user running the faucet",
+            )
+        }
+    };
+
+    let mut keys = 0;
+    for entry in entries {
+        let file = match entry {
+            Ok(entry) => entry.path(),
+            Err(err) => {
+                return Check::fail("keystore", err.to_string(), "check the keystore directory")
+            }
+        };
+        if let Err(err) = fs::File::open(&file) {
+            return Check::fail(
+                "keystore",
+                format!("cannot read `{}`: {err}", file.display()),
+                "make the key files readable by the user running the faucet, e.g. `chmod 600` \
+                 as that user",
+            );
+        }
+        keys += 1;
+    }
+    if keys == 0 {
+        return Check::warn(
+            "keystore",
+            format!("`{}` holds no keys", path.display()),
+            "deploy a faucet or import its keys, otherwise nothing can be signed",
+        );
+    }
+    Check::ok("keystore", format!("{keys} keys readable"))
+}
+
+/// Checks that the embedded MASM sources assemble, and reports the override directory in use.
+pub fn check_scripts() -> Check {
+    if let Err(err) = pausable_library() {
+        return Check::fail(
+            "masm",
+            format!("pausable.masm: {err}"),
+            "the binary was built from broken sources; rebuild it from a release",
+        );
+    }
+    if let Err(err) = ScriptTemplate::new(DEPLOY_SCRIPT).placeholders() {
+        return Check::fail(
+            "masm",
+            format!("deploy.masm: {err}"),
+            "the binary was built from broken sources; rebuild it from a release",
+        );
+    }
+    let overrides = PathBuf::from(SCRIPT_DIR);
+    let detail = if overrides.is_dir() {
+        format!("embedded scripts assemble, relative overrides also read from `{SCRIPT_DIR}`")
+    } else {
+        "embedded scripts assemble".to_string()
+    };
+    Check::ok("masm", detail)
+}
+
+/// Checks the free space of the file system holding `path`, or its closest existing ancestor.
+pub fn check_disk_space(path: &Path) -> Check {
+    let dir = path
+        .ancestors()
+        .find(|ancestor| !ancestor.as_os_str().is_empty() && ancestor.exists())
+        .unwrap_or(Path::new("."));
+    match fs2::available_space(dir) {
+        Ok(free) => disk_space_check(dir, free),
+        Err(err) => Check::warn(
+            "disk",
+            format!("cannot read the free space of `{}`: {err}", dir.display()),
+            "check the free space manually, e.g. with `df -h`",
+        ),
+    }
+}
+
+/// Rates `free` bytes available on the file system holding `dir`.
+pub fn disk_space_check(dir: &Path, free: u64) -> Check {
+    let detail = format!("{} MiB free at `{}`", free >> 20, dir.display());
+    let fix = "free up space or move `store_path` and `ledger_path` to a larger volume, `store \
+               prune` also shrinks the store";
+    if free < CRITICAL_DISK_SPACE {
+        Check::fail("disk", detail, fix)
+    } else if free < LOW_DISK_SPACE {
+        Check::warn("disk", detail, fix)
+    } else {
+        Check::ok("disk", detail)
+    }
+}
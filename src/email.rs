@@ -0,0 +1,178 @@
+//! Email delivery of minted notes.
+//!
+//! Mints pay recipients with private notes, which a user without a synced wallet cannot discover.
+//! When the `[smtp]` section is configured, mint requests may name an email address: once the mint
+//! is committed, [`NoteMailer`] sends the note file, which any wallet can import, and a claim link
+//! if [`SmtpConfig::claim_url`] is set.
+//!
+//! Addresses are only kept in memory until the email is sent, never in the ledger, so emails of
+//! mints still pending when the service stops are not sent.
+
+use std::time::Duration;
+
+use lettre::{
+    message::{header::ContentType, Attachment, Mailbox, MultiPart, SinglePart},
+    transport::smtp::authentication::Credentials,
+    Address, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use miden_client::{note::NoteFile, utils::Serializable};
+use serde::{Deserialize, Serialize};
+
+use crate::{ledger::MintRecord, FaucetError};
+
+/// SMTP settings, read from the `[smtp]` section of the configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SmtpConfig {
+    pub host: String,
+    /// Defaults to 587 with STARTTLS and 25 without.
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Sender, e.g. `Faucet <faucet@example.com>`.
+    pub from: String,
+    /// Upgrade the connection with STARTTLS; only disable it for a local relay.
+    #[serde(default = "default_starttls")]
+    pub starttls: bool,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Link included in the email, with `{mint_id}` and `{note_id}` replaced, e.g. a wallet page
+    /// importing the note.
+    #[serde(default)]
+    pub claim_url: Option<String>,
+}
+
+fn default_starttls() -> bool {
+    true
+}
+
+fn default_timeout_ms() -> u64 {
+    10_000
+}
+
+impl SmtpConfig {
+    /// Checks the settings without connecting to the server.
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        parse_mailbox(&self.from)?;
+        if self.timeout_ms == 0 {
+            return Err(FaucetError::Config(
+                "smtp.timeout_ms must be positive".into(),
+            ));
+        }
+        if self.username.is_some() != self.password.is_some() {
+            return Err(FaucetError::Config(
+                "smtp.username and smtp.password must be set together".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Parses an email address supplied with a mint request.
+pub fn parse_email(input: &str) -> Result<Address, FaucetError> {
+    input
+        .trim()
+        .parse()
+        .map_err(|err| FaucetError::InvalidEmail(input.to_string(), format!("{err}")))
+}
+
+fn parse_mailbox(input: &str) -> Result<Mailbox, FaucetError> {
+    input
+        .parse()
+        .map_err(|err| FaucetError::InvalidEmail(input.to_string(), format!("{err}")))
+}
+
+/// The claim link of `record`: `template` with `{mint_id}` and `{note_id}` replaced.
+pub fn claim_link(template: &str, record: &MintRecord) -> String {
+    template
+        .replace("{mint_id}", &record.id.to_string())
+        .replace("{note_id}", &record.note_id)
+}
+
+/// Sends the notes of committed mints by email.
+pub struct NoteMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    claim_url: Option<String>,
+}
+
+impl NoteMailer {
+    /// Sets up the SMTP transport; the server is only contacted when sending.
+    pub fn new(config: SmtpConfig) -> Result<Self, FaucetError> {
+        config.validate()?;
+        let mut builder = if config.starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+                .map_err(|err| FaucetError::Email(err.to_string()))?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+        };
+        builder = builder
+            .port(
+                config
+                    .port
+                    .unwrap_or(if config.starttls { 587 } else { 25 }),
+            )
+            .timeout(Some(Duration::from_millis(config.timeout_ms)));
+        if let (Some(username), Some(password)) = (config.username, config.password) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: parse_mailbox(&config.from)?,
+            claim_url: config.claim_url,
+        })
+    }
+
+    /// Email to `to` carrying the note file of `record` as attachment.
+    pub fn note_email(
+        &self,
+        record: &MintRecord,
+        note_file: &NoteFile,
+        to: &Address,
+    ) -> Result<Message, FaucetError> {
+        let mut body = format!(
+            "You received {} tokens of faucet {}.\n\n\
+             The attached note file holds note {}; import it into your wallet to claim them.\n",
+            record.amount, record.faucet_id, record.note_id
+        );
+        if let Some(template) = &self.claim_url {
+            body.push_str(&format!(
+                "\nOr claim them at {}\n",
+                claim_link(template, record)
+            ));
+        }
+
+        let attachment = Attachment::new(format!("mint-{}.mno", record.id)).body(
+            note_file.to_bytes(),
+            ContentType::parse("application/octet-stream").expect("valid content type"),
+        );
+        Message::builder()
+            .from(self.from.clone())
+            .to(Mailbox::new(None, to.clone()))
+            .subject(format!("Your {} tokens from the faucet", record.amount))
+            .multipart(
+                MultiPart::mixed()
+                    .singlepart(SinglePart::plain(body))
+                    .singlepart(attachment),
+            )
+            .map_err(|err| FaucetError::Email(err.to_string()))
+    }
+
+    pub async fn send(
+        &self,
+        record: &MintRecord,
+        note_file: &NoteFile,
+        to: &Address,
+    ) -> Result<(), FaucetError> {
+        let email = self.note_email(record, note_file, to)?;
+        self.transport
+            .send(email)
+            .await
+            .map_err(|err| FaucetError::Email(err.to_string()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,271 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use faucet_notes::MintNoteError;
+use miden_client::{
+    account::AccountId,
+    keystore::KeyStoreError,
+    note::NoteError,
+    transaction::{TransactionId, TransactionRequestError},
+    ClientError,
+};
+use miden_objects::{address::NetworkId, AccountError, AssetError, AssetVaultError};
+use thiserror::Error;
+
+use crate::rpc::RpcCall;
+
+/// Errors produced by the faucet library.
+#[derive(Debug, Error)]
+pub enum FaucetError {
+    #[error("access denied: {0}")]
+    AccessDenied(String),
+    #[error("account error: {0}")]
+    Account(#[from] AccountError),
+    #[error("account {0} is not tracked by the client")]
+    AccountNotFound(AccountId),
+    #[error("account {0} is already managed by this client")]
+    AlreadyTracked(AccountId),
+    #[error("account {0} is private; only public accounts can be watched")]
+    WatchPrivate(AccountId),
+    #[error("account {0} is watch-only, the faucet holds no key to spend from it")]
+    WatchOnly(AccountId),
+    #[error("GitHub account {login} is {age_days} days old, the faucet requires {min_days} days")]
+    AccountTooNew {
+        login: String,
+        age_days: u64,
+        min_days: u32,
+    },
+    #[error("amount {amount} is outside the allowed range {min}..={max}")]
+    AmountOutOfRange { amount: u64, min: u64, max: u64 },
+    #[error("asset error: {0}")]
+    Asset(#[from] AssetError),
+    #[error("asset vault error: {0}")]
+    AssetVault(#[from] AssetVaultError),
+    #[error("audit log entry {0} breaks the hash chain: {1}")]
+    AuditChainBroken(i64, String),
+    #[error("invalid recovery bundle: {0}")]
+    Backup(String),
+    #[error("snapshot error: {0}")]
+    Snapshot(String),
+    #[error("flow error: {0}")]
+    Flow(String),
+    #[error("refused by abuse policy `{0}`: {1}")]
+    AbuseRefused(String, String),
+    #[error("batch of {size} mints, expected 1 to {max}")]
+    BatchSize { size: usize, max: usize },
+    #[error("campaign `{campaign}` has {remaining} tokens of its budget left")]
+    CampaignBudgetExhausted { campaign: String, remaining: u64 },
+    #[error(
+        "daily budget of {budget} tokens exhausted, {remaining} left; come back at {}",
+        rfc3339(.retry_at)
+    )]
+    DailyBudgetExhausted {
+        budget: u64,
+        remaining: u64,
+        /// Unix timestamp, in seconds, from which the mint fits in the budget again.
+        retry_at: u64,
+    },
+    #[error("campaign `{0}` is not minting: {1}")]
+    CampaignInactive(String, String),
+    #[error("{recipient} can receive {remaining} more tokens from campaign `{campaign}`")]
+    CampaignUserLimit {
+        campaign: String,
+        recipient: AccountId,
+        remaining: u64,
+    },
+    #[error("{0} environment checks failed")]
+    ChecksFailed(usize),
+    #[error("client error: {0}")]
+    Client(Box<ClientError>),
+    #[error("failed to connect to {addr}: {source}")]
+    Connect {
+        addr: String,
+        source: std::io::Error,
+    },
+    #[error("note with nullifier {nullifier} was not consumed within {timeout_ms}ms")]
+    ConsumeTimeout { nullifier: String, timeout_ms: u64 },
+    #[error("timed out after {timeout_ms}ms connecting to {addr}")]
+    ConnectTimeout { addr: String, timeout_ms: u64 },
+    #[error("{call} timed out after {timeout_ms}ms")]
+    RequestTimeout { call: RpcCall, timeout_ms: u64 },
+    #[error("invalid configuration: {0}")]
+    Config(String),
+    #[error("failed to parse configuration file: {0}")]
+    ConfigParse(#[from] toml::de::Error),
+    #[error("faucet {faucet_id} was not retired: {failed} expired mints could not be reclaimed")]
+    DecommissionIncomplete { faucet_id: AccountId, failed: usize },
+    #[error("input note error: {0}")]
+    InputNote(String),
+    #[error("invalid access list entry `{0}`: {1}")]
+    InvalidAccessList(String, String),
+    #[error("invalid account ID `{0}`: {1}")]
+    InvalidAccountId(String, String),
+    #[error("account ID `{input}` is for network `{found}`, not `{expected}`")]
+    AccountNetworkMismatch {
+        input: String,
+        expected: NetworkId,
+        found: NetworkId,
+    },
+    #[error("invalid account label `{0}`: {1}")]
+    InvalidLabel(String, String),
+    #[error("invalid note ID `{0}`: {1}")]
+    InvalidNoteId(String, String),
+    #[error("invalid note type `{0}`, expected `public` or `private`")]
+    InvalidNoteType(String),
+    #[error("invalid note tag `{0}`: {1}")]
+    InvalidNoteTag(String, String),
+    #[error("refusing a {0} faucet with {1} storage: {2}")]
+    UnsafeFaucetAccount(String, String, String),
+    #[error("invalid consume mode `{0}`, expected `auto`, `authenticated` or `unauthenticated`")]
+    InvalidConsumeMode(String),
+    #[error("invalid campaign `{0}`: {1}")]
+    InvalidCampaign(String, String),
+    #[error("invalid referral code `{0}`: {1}")]
+    InvalidReferral(String, String),
+    #[error("invalid email address `{0}`: {1}")]
+    InvalidEmail(String, String),
+    #[error("unknown network `{0}`, this faucet serves {1}")]
+    UnknownNetwork(String, String),
+    #[error("unknown token `{0}`, this network serves {1}")]
+    UnknownToken(String, String),
+    #[error(
+        "`{path}` belongs to network `{recorded}`, not to `{configured}` of the configuration; \
+         point the paths at the files of `{configured}`"
+    )]
+    NetworkMismatch {
+        path: String,
+        recorded: String,
+        configured: String,
+    },
+    #[error("invalid inclusion proof for note {0}: {1}")]
+    InvalidInclusionProof(String, String),
+    #[error("note {0} is not the note its mint emits: {1}")]
+    NoteLinkage(String, String),
+    #[error("invalid mint receipt: {0}")]
+    InvalidReceipt(String),
+    #[error("account {0} cannot receive mints: {1}")]
+    InvalidRecipient(AccountId, String),
+    #[error("recipient {0} has no state on chain")]
+    RecipientNotFound(AccountId),
+    #[error("invalid schedule `{0}`: {1}")]
+    InvalidSchedule(String, String),
+    #[error("invalid tier `{0}`, expected `anonymous`, `github`, `api_key` or `admin`")]
+    InvalidTier(String),
+    #[error("invalid serial number `{0}`: {1}")]
+    InvalidSerialNumber(String, String),
+    #[error("invalid transaction ID `{0}`: {1}")]
+    InvalidTransactionId(String, String),
+    #[error("keystore error: {0}")]
+    KeyStore(#[from] KeyStoreError),
+    #[error("the keystore holds no key of account {0}")]
+    KeyNotFound(AccountId),
+    #[error("signing self-test of account {0} failed: {1}")]
+    SigningSelfTest(AccountId, String),
+    #[error("the keystore holds no Falcon key with commitment {0}")]
+    KeyIdNotFound(String),
+    #[error("invalid key ID `{0}`: {1}")]
+    InvalidKeyId(String, String),
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("ledger error: {0}")]
+    Ledger(String),
+    #[error("ledger database error: {0}")]
+    LedgerDb(#[from] rusqlite::Error),
+    #[error("local node error: {0}")]
+    Localnet(String),
+    #[error("mint {0} is not committed")]
+    MintNotCommitted(i64),
+    #[error("note error: {0}")]
+    Note(#[from] NoteError),
+    #[error("invalid note file {0}")]
+    NoteFile(String),
+    #[error("email delivery failed: {0}")]
+    Email(String),
+    #[error("email delivery is not configured, see the [smtp] section")]
+    EmailDisabled,
+    #[error("invalid fixture manifest: {0}")]
+    Fixtures(String),
+    #[error(
+        "transaction {transaction_id} is committed in block {local_block} locally, but the \
+         finality source reports {}",
+        remote_block.map_or("no block".to_string(), |block_num| format!("block {block_num}"))
+    )]
+    FinalityDisputed {
+        transaction_id: TransactionId,
+        local_block: u32,
+        remote_block: Option<u32>,
+    },
+    #[error("finality source unavailable: {0}")]
+    FinalityUnavailable(String),
+    #[error("faucet {0} is paused")]
+    FaucetPaused(AccountId),
+    #[error("GitHub request failed: {0}")]
+    Github(String),
+    #[error("drip limit reached, try again in {retry_after_secs}s")]
+    RateLimited { retry_after_secs: u64 },
+    #[error("failed to compile transaction script: {0}")]
+    Script(String),
+    #[error("script `{path}` not found, searched: {searched}")]
+    ScriptNotFound { path: String, searched: String },
+    #[error("invalid script template: {0}")]
+    ScriptTemplate(String),
+    #[error("client store error: {0}")]
+    Store(String),
+    #[error("transaction {0} was discarded: {1}")]
+    TransactionDiscarded(TransactionId, String),
+    #[error("transaction {0} is not tracked by the store")]
+    TransactionNotFound(TransactionId),
+    #[error("server error: {0}")]
+    Server(String),
+    #[error("TLS error: {0}")]
+    Tls(String),
+    #[error(
+        "ledger schema version {found} is newer than version {supported} of this build, upgrade \
+         the faucet or restore a backup"
+    )]
+    LedgerVersion { found: u32, supported: u32 },
+    #[error("faucet service stopped")]
+    ServiceStopped,
+    #[error("transaction executor of account {0} stopped")]
+    ExecutorStopped(AccountId),
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("webhook delivery failed: {0}")]
+    Webhook(String),
+    #[error("block watcher stopped")]
+    WatcherStopped,
+    #[error("invalid transaction request: {0}")]
+    TransactionRequest(#[from] TransactionRequestError),
+}
+
+impl FaucetError {
+    /// Whether the operation may succeed if simply tried again later.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Client(err) => matches!(**err, ClientError::RpcError(_)),
+            Self::Connect { .. } | Self::ConnectTimeout { .. } | Self::RequestTimeout { .. } => {
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<ClientError> for FaucetError {
+    fn from(err: ClientError) -> Self {
+        Self::Client(Box::new(err))
+    }
+}
+
+/// Unix timestamp `secs` in RFC 3339, e.g. `2025-06-01T10:15:00Z`.
+fn rfc3339(secs: &u64) -> String {
+    humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(*secs)).to_string()
+}
+
+impl From<MintNoteError> for FaucetError {
+    fn from(err: MintNoteError) -> Self {
+        match err {
+            MintNoteError::Asset(err) => Self::Asset(err),
+            MintNoteError::Note(err) => Self::Note(err),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Confirming the network execution of minted notes.
+//!
+//! Mints to network accounts emit public notes with network-execution tags, see
+//! [`NoteTagStrategy::Network`](crate::mint::NoteTagStrategy::Network). The network consumes
+//! such notes into the recipient without a transaction of the recipient, so the faucet confirms
+//! the execution by waiting for the nullifier of the note and records the consuming block as the
+//! claim of the mint.
+
+use std::time::Duration;
+
+use miden_objects::block::BlockNumber;
+
+use crate::{
+    audit::AuditEntry,
+    ledger::{Ledger, MintRecord},
+    mint::rebuild_mint_note,
+    node::FaucetNode,
+    watcher::{wait_for_note_consumption, BlockWatcher, SharedNode},
+    FaucetError,
+};
+
+/// Waits up to `timeout` for the network to consume the note of the committed mint `record`,
+/// then marks the mint claimed and audits the execution as `actor`. Returns the block that
+/// consumed the note.
+///
+/// Fails for mints whose note carries no network-execution tag or that are not committed yet,
+/// and with [`FaucetError::ConsumeTimeout`] if the network has not consumed the note in time.
+pub async fn track_network_execution<N: FaucetNode>(
+    node: &SharedNode<N>,
+    watcher: &BlockWatcher,
+    ledger: &Ledger,
+    actor: &str,
+    record: &MintRecord,
+    timeout: Duration,
+) -> Result<BlockNumber, FaucetError> {
+    if !record.network_executed() {
+        return Err(FaucetError::Ledger(format!(
+            "the note of mint {} is not executed by the network",
+            record.id
+        )));
+    }
+    let commit_block = record
+        .commit_block
+        .ok_or_else(|| FaucetError::Ledger(format!("mint {} is not committed yet", record.id)))?;
+
+    let nullifier = rebuild_mint_note(record)?.nullifier();
+    let executed_at =
+        wait_for_note_consumption(node, watcher, nullifier, commit_block.into(), timeout).await?;
+    ledger.mark_claimed(&nullifier.to_hex(), executed_at)?;
+    ledger.append_audit(
+        &AuditEntry::new(actor, "mint.network_executed")
+            .param("faucet_id", &record.faucet_id)
+            .param("recipient", &record.recipient)
+            .param("mint_id", record.id)
+            .param("block", executed_at.as_u32()),
+    )?;
+    Ok(executed_at)
+}
@@ -0,0 +1,226 @@
+//! Single-writer transaction execution.
+//!
+//! A transaction is built on the state the local store holds for its account, so two
+//! transactions built from the same state, e.g. two mints from the same faucet owner, conflict and
+//! the node rejects the later one. A [`TxExecutor`] owns the transactions of one account: jobs
+//! sent to it are built and submitted one after the other, each seeing the state the previous one
+//! left behind. [`Executors`] hands out the executor of each account.
+//!
+//! With a lock directory, see [`Executors::with_lock_dir`], every job also holds the
+//! [`AccountLock`] of its account, so processes sharing the store, like `serve` and the mint
+//! binary, take turns as well.
+//!
+//! Client futures are not `Send`, so executors run through [`tokio::task::spawn_local`] and must
+//! be used inside a [`tokio::task::LocalSet`].
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
+
+use fs2::FileExt;
+use futures::future::LocalBoxFuture;
+use miden_client::account::AccountId;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{node::FaucetNode, watcher::SharedNode, FaucetError};
+
+/// Interval between two attempts to take an account lock held by another process.
+pub const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Advisory lock of an account shared by the processes using the same store, released on drop.
+pub struct AccountLock {
+    file: File,
+}
+
+impl AccountLock {
+    /// Lock file of `account_id` in `dir`.
+    pub fn path(dir: &Path, account_id: AccountId) -> PathBuf {
+        dir.join(format!("{}.lock", account_id.to_hex()))
+    }
+
+    /// Takes the lock of `account_id` in `dir`, or returns `None` if another holder has it.
+    pub fn try_acquire(dir: &Path, account_id: AccountId) -> Result<Option<Self>, FaucetError> {
+        fs::create_dir_all(dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(Self::path(dir, account_id))?;
+        match file.try_lock_exclusive() {
+            Ok(()) => Ok(Some(Self { file })),
+            Err(err) if err.kind() == fs2::lock_contended_error().kind() => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Takes the lock of `account_id` in `dir`, waiting for the current holder to release it.
+    pub async fn acquire(dir: &Path, account_id: AccountId) -> Result<Self, FaucetError> {
+        let mut reported = false;
+        loop {
+            if let Some(lock) = Self::try_acquire(dir, account_id)? {
+                return Ok(lock);
+            }
+            if !reported {
+                println!("Waiting for another process submitting from account {account_id}...");
+                reported = true;
+            }
+            tokio::time::sleep(LOCK_POLL_INTERVAL).await;
+        }
+    }
+}
+
+impl Drop for AccountLock {
+    fn drop(&mut self) {
+        // Closing the file releases the lock as well.
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+type Job<N> = Box<dyn for<'a> FnOnce(Result<&'a mut N, FaucetError>) -> LocalBoxFuture<'a, ()>>;
+
+/// Pins the signature of `job` down for the compiler, which does not infer it from [`Job`].
+fn boxed_job<N>(
+    job: impl for<'a> FnOnce(Result<&'a mut N, FaucetError>) -> LocalBoxFuture<'a, ()> + 'static,
+) -> Job<N> {
+    Box::new(job)
+}
+
+/// Cloneable handle to the actor running the transactions of one account in order.
+///
+/// The actor stops once every handle has been dropped.
+pub struct TxExecutor<N> {
+    account_id: AccountId,
+    sender: mpsc::UnboundedSender<Job<N>>,
+    /// Jobs sent and not done yet, shared by the handles.
+    backlog: Rc<Cell<usize>>,
+}
+
+impl<N> Clone for TxExecutor<N> {
+    fn clone(&self) -> Self {
+        Self {
+            account_id: self.account_id,
+            sender: self.sender.clone(),
+            backlog: self.backlog.clone(),
+        }
+    }
+}
+
+impl<N: FaucetNode + 'static> TxExecutor<N> {
+    /// Starts the actor of `account_id`, holding its lock in `lock_dir` during every job if set.
+    pub fn spawn(node: SharedNode<N>, account_id: AccountId, lock_dir: Option<PathBuf>) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Job<N>>();
+        tokio::task::spawn_local(async move {
+            while let Some(job) = receiver.recv().await {
+                let lock = match &lock_dir {
+                    Some(dir) => match AccountLock::acquire(dir, account_id).await {
+                        Ok(lock) => Some(lock),
+                        Err(err) => {
+                            job(Err(err)).await;
+                            continue;
+                        }
+                    },
+                    None => None,
+                };
+                let mut node = node.lock().await;
+                job(Ok(&mut *node)).await;
+                drop(node);
+                drop(lock);
+            }
+        });
+        Self {
+            account_id,
+            sender,
+            backlog: Rc::default(),
+        }
+    }
+
+    pub fn account_id(&self) -> AccountId {
+        self.account_id
+    }
+
+    /// Jobs sent to the executor that are not done yet, the running one included: transactions
+    /// waiting to be built, proven and submitted.
+    pub fn backlog(&self) -> usize {
+        self.backlog.get()
+    }
+
+    /// Runs `job` once the jobs sent before it are done, with exclusive use of the node.
+    ///
+    /// The job builds and submits the transactions of the account, e.g. with
+    /// [`mint_batch`](crate::mint::mint_batch):
+    /// `executor.run(move |node| Box::pin(mint_batch(node, faucet_id, mints)))`.
+    pub async fn run<T: 'static>(
+        &self,
+        job: impl for<'a> FnOnce(&'a mut N) -> LocalBoxFuture<'a, Result<T, FaucetError>> + 'static,
+    ) -> Result<T, FaucetError> {
+        let (reply, response) = oneshot::channel();
+        let backlog = self.backlog.clone();
+        let job = boxed_job(move |node| {
+            Box::pin(async move {
+                let result = match node {
+                    Ok(node) => job(node).await,
+                    Err(err) => Err(err),
+                };
+                backlog.set(backlog.get() - 1);
+                let _ = reply.send(result);
+            })
+        });
+        self.backlog.set(self.backlog.get() + 1);
+        if self.sender.send(job).is_err() {
+            self.backlog.set(self.backlog.get() - 1);
+            return Err(FaucetError::ExecutorStopped(self.account_id));
+        }
+        response
+            .await
+            .map_err(|_| FaucetError::ExecutorStopped(self.account_id))?
+    }
+}
+
+/// The [`TxExecutor`] of every account submitting through one node, started on first use.
+pub struct Executors<N> {
+    node: SharedNode<N>,
+    lock_dir: Option<PathBuf>,
+    executors: RefCell<BTreeMap<AccountId, TxExecutor<N>>>,
+}
+
+impl<N: FaucetNode + 'static> Executors<N> {
+    pub fn new(node: SharedNode<N>) -> Self {
+        Self {
+            node,
+            lock_dir: None,
+            executors: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// Holds the [`AccountLock`] of the account in `dir` during every job, see
+    /// [`Config::lock_dir`](crate::config::Config::lock_dir).
+    pub fn with_lock_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.lock_dir = Some(dir.into());
+        self
+    }
+
+    /// Jobs not done yet across the executors of every account, see [`TxExecutor::backlog`].
+    pub fn backlog(&self) -> usize {
+        self.executors
+            .borrow()
+            .values()
+            .map(TxExecutor::backlog)
+            .sum()
+    }
+
+    /// The executor of `account_id`.
+    pub fn for_account(&self, account_id: AccountId) -> TxExecutor<N> {
+        self.executors
+            .borrow_mut()
+            .entry(account_id)
+            .or_insert_with(|| {
+                TxExecutor::spawn(self.node.clone(), account_id, self.lock_dir.clone())
+            })
+            .clone()
+    }
+}
@@ -0,0 +1,77 @@
+//! Links to block explorer pages.
+//!
+//! The explorer follows the network of the node: `testnet` and `devnet` endpoints link to their
+//! MidenScan instance, other networks only get links when `[explorer] url` names one.
+
+use miden_client::{account::AccountId, note::NoteId, transaction::TransactionId};
+use serde::{Deserialize, Serialize};
+
+use crate::{rpc::EndpointConfig, FaucetError};
+
+const TESTNET_URL: &str = "https://testnet.midenscan.com";
+const DEVNET_URL: &str = "https://devnet.midenscan.com";
+
+/// Explorer settings, read from the `[explorer]` section of the configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExplorerConfig {
+    /// Base URL of the explorer, serving `/tx/<id>`, `/account/<id>` and `/note/<id>` pages.
+    /// Defaults to MidenScan on testnet and devnet.
+    pub url: Option<String>,
+    /// Leave links out of the output altogether.
+    pub disabled: bool,
+}
+
+impl ExplorerConfig {
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        if let Some(url) = &self.url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(FaucetError::Config(format!(
+                    "explorer.url `{url}` must be an http:// or https:// URL"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builds explorer links of transactions, accounts and notes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explorer {
+    base_url: String,
+}
+
+impl Explorer {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Explorer of the network `endpoint` connects to, `None` if disabled or unknown.
+    pub fn for_network(config: &ExplorerConfig, endpoint: &EndpointConfig) -> Option<Self> {
+        if config.disabled {
+            return None;
+        }
+        if let Some(url) = &config.url {
+            return Some(Self::new(url.as_str()));
+        }
+        match endpoint.network_name().as_str() {
+            "testnet" => Some(Self::new(TESTNET_URL)),
+            "devnet" => Some(Self::new(DEVNET_URL)),
+            _ => None,
+        }
+    }
+
+    pub fn transaction(&self, transaction_id: TransactionId) -> String {
+        format!("{}/tx/{}", self.base_url, transaction_id.to_hex())
+    }
+
+    pub fn account(&self, account_id: AccountId) -> String {
+        format!("{}/account/{}", self.base_url, account_id.to_hex())
+    }
+
+    pub fn note(&self, note_id: NoteId) -> String {
+        format!("{}/note/{}", self.base_url, note_id.to_hex())
+    }
+}
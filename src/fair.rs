@@ -0,0 +1,87 @@
+//! Fair scheduling of the mint queue.
+//!
+//! Proving limits the worker to a few mints at a time, so the queue backs up under load, and
+//! serving it first come, first served lets a single bulk requester starve everyone else.
+//! [`FairQueue`] keeps one queue per requester identity instead and takes turns between them:
+//! each identity with queued mints is served `weight` mints per round, 1 unless configured
+//! otherwise, in the order the identities arrived.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Weighted round-robin queue of items grouped by identity.
+#[derive(Debug)]
+pub struct FairQueue<T> {
+    queues: HashMap<String, VecDeque<T>>,
+    /// Identities with queued items, the one being served first.
+    turns: VecDeque<String>,
+    weights: BTreeMap<String, u32>,
+    /// Items served in the current turn.
+    served: u32,
+    len: usize,
+}
+
+impl<T> FairQueue<T> {
+    /// Queue serving each identity of `weights` that many items per round, and others one.
+    pub fn new(weights: BTreeMap<String, u32>) -> Self {
+        Self {
+            queues: HashMap::new(),
+            turns: VecDeque::new(),
+            weights,
+            served: 0,
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, identity: &str, item: T) {
+        let queue = self.queues.entry(identity.to_string()).or_default();
+        if queue.is_empty() {
+            self.turns.push_back(identity.to_string());
+        }
+        queue.push_back(item);
+        self.len += 1;
+    }
+
+    /// Takes the next item and the identity it was queued under.
+    pub fn pop(&mut self) -> Option<(String, T)> {
+        let identity = self.turns.front()?.clone();
+        let queue = self.queues.get_mut(&identity)?;
+        let item = queue.pop_front()?;
+        self.len -= 1;
+        self.served += 1;
+
+        if queue.is_empty() {
+            self.queues.remove(&identity);
+            self.turns.pop_front();
+            self.served = 0;
+        } else if self.served >= self.weight(&identity) {
+            self.turns.rotate_left(1);
+            self.served = 0;
+        }
+        Some((identity, item))
+    }
+
+    /// Identities with queued items and how many, the one being served first.
+    pub fn identities(&self) -> Vec<(&str, usize)> {
+        self.turns
+            .iter()
+            .map(|identity| {
+                (
+                    identity.as_str(),
+                    self.queues.get(identity).map_or(0, VecDeque::len),
+                )
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn weight(&self, identity: &str) -> u32 {
+        self.weights.get(identity).copied().unwrap_or(1).max(1)
+    }
+}
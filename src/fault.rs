@@ -0,0 +1,295 @@
+//! Fault injection for resilience testing.
+//!
+//! [`FaultyNode`] wraps another [`FaucetNode`] and randomly fails calls according to a
+//! [`FaultConfig`], so the retry and wait logic can be exercised against a healthy node.
+
+use std::collections::BTreeMap;
+
+use miden_client::{
+    account::{Account, AccountId},
+    auth::AuthSecretKey,
+    note::{Note, NoteFile, NoteId, NoteInclusionProof, Nullifier},
+    transaction::{TransactionId, TransactionRequest, TransactionScript},
+    ClientRng, Word,
+};
+use miden_objects::block::BlockNumber;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    node::{FaucetNode, StoredNote, TransactionCost, TxState},
+    rpc::RpcCall,
+    FaucetError,
+};
+
+/// Probabilities (between `0.0` and `1.0`) of each injected fault.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FaultConfig {
+    /// The call fails with a timeout before reaching the node.
+    pub timeout_probability: f64,
+    /// The call reaches the node but its response is lost, so the caller sees a timeout.
+    pub drop_probability: f64,
+    /// A committed transaction keeps being reported as pending for `commit_delay_syncs` more
+    /// syncs.
+    pub commit_delay_probability: f64,
+    pub commit_delay_syncs: u32,
+}
+
+impl FaultConfig {
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        for (name, value) in [
+            ("timeout_probability", self.timeout_probability),
+            ("drop_probability", self.drop_probability),
+            ("commit_delay_probability", self.commit_delay_probability),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(FaucetError::Config(format!(
+                    "fault_injection.{name} must be between 0 and 1, got {value}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// [`FaucetNode`] that injects faults into the calls forwarded to `inner`.
+pub struct FaultyNode<N> {
+    inner: N,
+    config: FaultConfig,
+    syncs: u64,
+    /// Sync count from which a delayed transaction may be reported as committed.
+    delayed_commits: BTreeMap<TransactionId, u64>,
+}
+
+impl<N: FaucetNode> FaultyNode<N> {
+    pub fn new(inner: N, config: FaultConfig) -> Self {
+        Self {
+            inner,
+            config,
+            syncs: 0,
+            delayed_commits: BTreeMap::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> N {
+        self.inner
+    }
+
+    fn roll(probability: f64) -> bool {
+        probability > 0.0 && rand::rng().random_bool(probability.min(1.0))
+    }
+
+    fn timeout(call: RpcCall) -> FaucetError {
+        FaucetError::RequestTimeout {
+            call,
+            timeout_ms: 0,
+        }
+    }
+
+    /// Fails before the call is forwarded.
+    fn before(&self, call: RpcCall) -> Result<(), FaucetError> {
+        if Self::roll(self.config.timeout_probability) {
+            eprintln!("[fault] injecting timeout into {call}");
+            return Err(Self::timeout(call));
+        }
+        Ok(())
+    }
+
+    /// Drops the response of a call that was forwarded.
+    fn after<T>(&self, call: RpcCall, result: Result<T, FaucetError>) -> Result<T, FaucetError> {
+        if result.is_ok() && Self::roll(self.config.drop_probability) {
+            eprintln!("[fault] dropping response of {call}");
+            return Err(Self::timeout(call));
+        }
+        result
+    }
+
+    /// Reports a committed transaction as pending until its commitment delay, rolled the first
+    /// time it is seen committed, has passed.
+    fn delay_commit(&mut self, transaction_id: TransactionId, state: TxState) -> TxState {
+        if !matches!(state, TxState::Committed(_)) {
+            return state;
+        }
+        let syncs = self.syncs;
+        let config = &self.config;
+        let release_at = *self
+            .delayed_commits
+            .entry(transaction_id)
+            .or_insert_with(|| {
+                if Self::roll(config.commit_delay_probability) {
+                    eprintln!("[fault] delaying commitment of {transaction_id}");
+                    syncs + u64::from(config.commit_delay_syncs)
+                } else {
+                    syncs
+                }
+            });
+        if self.syncs < release_at {
+            TxState::Pending
+        } else {
+            state
+        }
+    }
+}
+
+impl<N: FaucetNode> FaucetNode for FaultyNode<N> {
+    fn rng(&mut self) -> &mut ClientRng {
+        self.inner.rng()
+    }
+
+    async fn sync_state(&mut self) -> Result<BlockNumber, FaucetError> {
+        self.before(RpcCall::SyncState)?;
+        let result = self.inner.sync_state().await;
+        if result.is_ok() {
+            self.syncs += 1;
+        }
+        self.after(RpcCall::SyncState, result)
+    }
+
+    async fn chain_tip(&mut self) -> Result<BlockNumber, FaucetError> {
+        self.before(RpcCall::GetBlockHeader)?;
+        let result = self.inner.chain_tip().await;
+        self.after(RpcCall::GetBlockHeader, result)
+    }
+
+    async fn add_account(&mut self, account: &Account) -> Result<(), FaucetError> {
+        self.inner.add_account(account).await
+    }
+
+    async fn add_key(&mut self, key: AuthSecretKey) -> Result<(), FaucetError> {
+        self.inner.add_key(key).await
+    }
+
+    async fn secret_key(&mut self, pub_key: Word) -> Result<Option<AuthSecretKey>, FaucetError> {
+        self.inner.secret_key(pub_key).await
+    }
+
+    async fn get_account(&mut self, account_id: AccountId) -> Result<Option<Account>, FaucetError> {
+        self.before(RpcCall::GetAccount)?;
+        let result = self.inner.get_account(account_id).await;
+        self.after(RpcCall::GetAccount, result)
+    }
+
+    async fn import_account(&mut self, account_id: AccountId) -> Result<(), FaucetError> {
+        self.before(RpcCall::GetAccount)?;
+        let result = self.inner.import_account(account_id).await;
+        self.after(RpcCall::GetAccount, result)
+    }
+
+    async fn fetch_account(
+        &mut self,
+        account_id: AccountId,
+    ) -> Result<Option<Account>, FaucetError> {
+        self.before(RpcCall::GetAccount)?;
+        let result = self.inner.fetch_account(account_id).await;
+        self.after(RpcCall::GetAccount, result)
+    }
+
+    // Store-only operations are passed through without faults.
+    async fn tracked_accounts(&mut self) -> Result<Vec<AccountId>, FaucetError> {
+        self.inner.tracked_accounts().await
+    }
+
+    async fn import_note(&mut self, note_file: NoteFile) -> Result<NoteId, FaucetError> {
+        self.inner.import_note(note_file).await
+    }
+
+    async fn stored_note(&mut self, note_id: NoteId) -> Result<Option<StoredNote>, FaucetError> {
+        self.inner.stored_note(note_id).await
+    }
+
+    async fn consumable_notes(
+        &mut self,
+        account_id: AccountId,
+    ) -> Result<Vec<StoredNote>, FaucetError> {
+        self.inner.consumable_notes(account_id).await
+    }
+
+    fn compile_tx_script(&self, code: &str) -> Result<TransactionScript, FaucetError> {
+        self.inner.compile_tx_script(code)
+    }
+
+    async fn execute_transaction(
+        &mut self,
+        account_id: AccountId,
+        request: TransactionRequest,
+    ) -> Result<TransactionCost, FaucetError> {
+        self.inner.execute_transaction(account_id, request).await
+    }
+
+    async fn submit_transaction(
+        &mut self,
+        account_id: AccountId,
+        request: TransactionRequest,
+    ) -> Result<TransactionId, FaucetError> {
+        self.before(RpcCall::SubmitTransaction)?;
+        let result = self.inner.submit_transaction(account_id, request).await;
+        self.after(RpcCall::SubmitTransaction, result)
+    }
+
+    fn take_transaction_cost(&mut self, transaction_id: TransactionId) -> Option<TransactionCost> {
+        self.inner.take_transaction_cost(transaction_id)
+    }
+
+    async fn transaction_state(
+        &mut self,
+        transaction_id: TransactionId,
+    ) -> Result<Option<TxState>, FaucetError> {
+        self.before(RpcCall::GetTransactions)?;
+        let state = self.inner.transaction_state(transaction_id).await;
+        let state = self.after(RpcCall::GetTransactions, state)?;
+        Ok(state.map(|state| self.delay_commit(transaction_id, state)))
+    }
+
+    async fn transaction_states(
+        &mut self,
+        transaction_ids: &[TransactionId],
+    ) -> Result<BTreeMap<TransactionId, TxState>, FaucetError> {
+        self.before(RpcCall::GetTransactions)?;
+        let states = self.inner.transaction_states(transaction_ids).await;
+        let states = self.after(RpcCall::GetTransactions, states)?;
+        Ok(states
+            .into_iter()
+            .map(|(transaction_id, state)| {
+                (transaction_id, self.delay_commit(transaction_id, state))
+            })
+            .collect())
+    }
+
+    async fn transaction_output_notes(
+        &mut self,
+        transaction_id: TransactionId,
+    ) -> Result<Option<Vec<Note>>, FaucetError> {
+        self.before(RpcCall::GetTransactions)?;
+        let result = self.inner.transaction_output_notes(transaction_id).await;
+        self.after(RpcCall::GetTransactions, result)
+    }
+
+    async fn consumed_nullifiers(
+        &mut self,
+        prefixes: &[u16],
+        from_block: BlockNumber,
+    ) -> Result<Vec<(Nullifier, BlockNumber)>, FaucetError> {
+        self.before(RpcCall::SyncNullifiers)?;
+        let result = self.inner.consumed_nullifiers(prefixes, from_block).await;
+        self.after(RpcCall::SyncNullifiers, result)
+    }
+
+    async fn note_inclusion_proof(
+        &mut self,
+        note_id: NoteId,
+    ) -> Result<Option<NoteInclusionProof>, FaucetError> {
+        self.before(RpcCall::GetNotes)?;
+        let result = self.inner.note_inclusion_proof(note_id).await;
+        self.after(RpcCall::GetNotes, result)
+    }
+
+    async fn block_note_root(
+        &mut self,
+        block_num: BlockNumber,
+    ) -> Result<Option<Word>, FaucetError> {
+        self.before(RpcCall::GetBlockHeader)?;
+        let result = self.inner.block_note_root(block_num).await;
+        self.after(RpcCall::GetBlockHeader, result)
+    }
+}
@@ -0,0 +1,205 @@
+//! Independent finality checks.
+//!
+//! The ledger marks a mint committed when the local store syncs its transaction, so a stale or
+//! forked store could have the faucet sign receipts for transactions the network never included.
+//! With the `[finality]` section set, [`FinalityChecker`] asks an independent source, such as the
+//! MidenScan API, for the block of the transaction before a receipt is issued, and disagreements
+//! are refused and recorded in the audit log.
+//!
+//! The source is queried with `GET` on [`FinalityConfig::transaction_url`] and must answer with a
+//! JSON document holding the block number at [`FinalityConfig::block_pointer`], null or missing
+//! while the transaction is pending; `404 Not Found` means it does not know the transaction.
+
+use std::time::Duration;
+
+use miden_client::transaction::TransactionId;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit::AuditEntry,
+    ledger::{Ledger, MintRecord, MintStatus},
+    mint::parse_transaction_id,
+    FaucetError,
+};
+
+/// Finality source settings, read from the `[finality]` section of the configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FinalityConfig {
+    /// URL of a transaction, with `{transaction_id}` replaced by its hex ID.
+    pub transaction_url: String,
+    /// JSON pointer to the block number in the response, e.g. `/data/block_num`.
+    #[serde(default = "default_block_pointer")]
+    pub block_pointer: String,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Refuse receipts while the source cannot be reached, instead of trusting the local store.
+    #[serde(default)]
+    pub required: bool,
+}
+
+fn default_block_pointer() -> String {
+    "/block_num".into()
+}
+
+fn default_timeout_ms() -> u64 {
+    5_000
+}
+
+impl FinalityConfig {
+    /// Checks the settings without contacting the source.
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        if !self.transaction_url.starts_with("http://")
+            && !self.transaction_url.starts_with("https://")
+        {
+            return Err(FaucetError::Config(format!(
+                "finality.transaction_url `{}` must be an http:// or https:// URL",
+                self.transaction_url
+            )));
+        }
+        if !self.transaction_url.contains("{transaction_id}") {
+            return Err(FaucetError::Config(
+                "finality.transaction_url must contain `{transaction_id}`".into(),
+            ));
+        }
+        if !self.block_pointer.is_empty() && !self.block_pointer.starts_with('/') {
+            return Err(FaucetError::Config(format!(
+                "finality.block_pointer `{}` must be a JSON pointer starting with `/`",
+                self.block_pointer
+            )));
+        }
+        if self.timeout_ms == 0 {
+            return Err(FaucetError::Config(
+                "finality.timeout_ms must be positive".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Verdict of [`FinalityChecker::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Finality {
+    /// The source reports the transaction in the same block as the local store.
+    Confirmed,
+    /// The source reports another block, or none, for the transaction.
+    Disputed { remote_block: Option<u32> },
+    /// The source could not be asked.
+    Unavailable(String),
+}
+
+/// Asks the finality source about committed transactions.
+pub struct FinalityChecker {
+    config: FinalityConfig,
+    http: reqwest::Client,
+}
+
+impl FinalityChecker {
+    pub fn new(config: FinalityConfig) -> Result<Self, FaucetError> {
+        config.validate()?;
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .map_err(|err| FaucetError::Config(format!("cannot build finality client: {err}")))?;
+        Ok(Self { config, http })
+    }
+
+    /// Compares the block the local store committed `transaction_id` in with the source's.
+    pub async fn check(&self, transaction_id: TransactionId, local_block: u32) -> Finality {
+        match self.remote_block(transaction_id).await {
+            Ok(Some(block_num)) if block_num == local_block => Finality::Confirmed,
+            Ok(remote_block) => Finality::Disputed { remote_block },
+            Err(reason) => Finality::Unavailable(reason),
+        }
+    }
+
+    /// Fails unless the source confirms that `transaction_id` was committed in `local_block`, or
+    /// cannot be reached and is not [`required`](FinalityConfig::required).
+    pub async fn verify(
+        &self,
+        transaction_id: TransactionId,
+        local_block: u32,
+    ) -> Result<(), FaucetError> {
+        match self.check(transaction_id, local_block).await {
+            Finality::Confirmed => Ok(()),
+            Finality::Disputed { remote_block } => Err(FaucetError::FinalityDisputed {
+                transaction_id,
+                local_block,
+                remote_block,
+            }),
+            Finality::Unavailable(reason) if self.config.required => {
+                Err(FaucetError::FinalityUnavailable(reason))
+            }
+            Finality::Unavailable(reason) => {
+                eprintln!("Could not verify the finality of {transaction_id}: {reason}");
+                Ok(())
+            }
+        }
+    }
+
+    /// [`verify`](Self::verify) for the transaction of a committed mint, recording a dispute in
+    /// the audit log of `ledger` as `actor`. Mints not committed locally are left to the caller.
+    pub async fn verify_mint(
+        &self,
+        ledger: &Ledger,
+        actor: &str,
+        record: &MintRecord,
+    ) -> Result<(), FaucetError> {
+        let (MintStatus::Committed, Some(local_block)) = (record.status, record.commit_block)
+        else {
+            return Ok(());
+        };
+        let transaction_id = parse_transaction_id(&record.transaction_id)?;
+        match self.verify(transaction_id, local_block).await {
+            Err(err @ FaucetError::FinalityDisputed { remote_block, .. }) => {
+                eprintln!("Finality of mint {} disputed: {err}", record.id);
+                ledger.append_audit(
+                    &AuditEntry::new(actor, "finality.disputed")
+                        .param("mint_id", record.id)
+                        .param("local_block", local_block)
+                        .param("remote_block", remote_block)
+                        .transaction(transaction_id),
+                )?;
+                Err(err)
+            }
+            result => result,
+        }
+    }
+
+    async fn remote_block(&self, transaction_id: TransactionId) -> Result<Option<u32>, String> {
+        let url = self
+            .config
+            .transaction_url
+            .replace("{transaction_id}", &transaction_id.to_hex());
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|err| err.to_string())?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let document: serde_json::Value = response
+            .error_for_status()
+            .map_err(|err| err.to_string())?
+            .json()
+            .await
+            .map_err(|err| err.to_string())?;
+        remote_block(&document, &self.config.block_pointer)
+    }
+}
+
+/// Block number at `pointer` of a response of the finality source, `None` while pending.
+pub fn remote_block(document: &serde_json::Value, pointer: &str) -> Result<Option<u32>, String> {
+    match document.pointer(pointer) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .or_else(|| value.as_str().and_then(|text| text.parse().ok()))
+            .and_then(|block_num| u32::try_from(block_num).ok())
+            .map(Some)
+            .ok_or_else(|| format!("`{value}` at {pointer} is not a block number")),
+    }
+}
@@ -0,0 +1,90 @@
+//! Funded wallets for external test suites.
+//!
+//! `fixtures generate` creates basic wallets the way the `mint` flow creates Alice's, with their
+//! keys in the keystore, mints each a starting balance and consumes it. The [`FixtureManifest`]
+//! it writes lists the wallets, so test suites sharing the store and keystore can load accounts
+//! ready to transact instead of setting them up on every run.
+
+use std::{fs, path::Path};
+
+use miden_client::account::AccountId;
+use miden_objects::MAX_OUTPUT_NOTES_PER_TX;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    mint::{mint_batch, BatchMint, BatchMintOutcome, MintOptions},
+    node::FaucetNode,
+    wallet::create_wallet,
+    FaucetError,
+};
+
+/// Wallets written by `fixtures generate`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureManifest {
+    /// Faucet of the starting balances.
+    pub faucet_id: String,
+    /// Keystore holding the keys of the wallets.
+    pub keystore_path: String,
+    /// Unix timestamp, in seconds, of the generation.
+    pub created_at: u64,
+    pub wallets: Vec<FixtureWallet>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureWallet {
+    pub account_id: String,
+    pub label: Option<String>,
+    /// Tokens of the faucet minted to the wallet.
+    pub balance: u64,
+    /// The minted note, consumed by the wallet.
+    pub note_id: String,
+}
+
+impl FixtureManifest {
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), FaucetError> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|err| FaucetError::Fixtures(err.to_string()))?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, FaucetError> {
+        let contents = fs::read_to_string(path)?;
+        serde_json::from_str(&contents).map_err(|err| FaucetError::Fixtures(err.to_string()))
+    }
+}
+
+/// Creates `count` basic wallets, registered with the client and their keys stored.
+pub async fn create_fixture_wallets<N: FaucetNode>(
+    node: &mut N,
+    count: usize,
+) -> Result<Vec<AccountId>, FaucetError> {
+    let mut wallets = Vec::with_capacity(count);
+    for _ in 0..count {
+        wallets.push(create_wallet(node).await?.id());
+    }
+    Ok(wallets)
+}
+
+/// Mints `amount` tokens of `faucet_id` to each of `wallets`, as many per transaction as the
+/// protocol's output note limit allows.
+pub async fn mint_starting_balances<N: FaucetNode>(
+    node: &mut N,
+    faucet_id: AccountId,
+    wallets: &[AccountId],
+    amount: u64,
+) -> Result<Vec<BatchMintOutcome>, FaucetError> {
+    let mut batches = Vec::new();
+    for chunk in wallets.chunks(MAX_OUTPUT_NOTES_PER_TX) {
+        let mints = chunk
+            .iter()
+            .map(|&recipient| BatchMint {
+                recipient,
+                amount,
+                options: MintOptions::default(),
+            })
+            .collect();
+        batches.push(mint_batch(node, faucet_id, mints).await?);
+    }
+    Ok(batches)
+}
@@ -0,0 +1,774 @@
+//! Resumable multi-step flows.
+//!
+//! `deploy` and `mint` run as flows: state machines whose state is persisted in the [`Ledger`]
+//! after every step, so a flow cut short by a crash, a lost connection or a failing step is
+//! continued where it stopped with `flows resume` instead of being started over.
+//!
+//! - deploy: create owner → create faucet → run deploy script → verify
+//! - mint: create recipient → build note → submit → wait → export → consume → claim
+//!
+//! A step failing with a transient error, see [`FaucetError::is_transient`], is retried as set by
+//! [`StepRetry`]. Steps submitting a transaction are never retried on their own, since the node
+//! may have received the failed attempt. A flow stopped by a failing step keeps the step and its
+//! error, and can be resumed at any time.
+
+use std::{path::PathBuf, time::Duration};
+
+use miden_client::{account::AccountId, crypto::FeltRng, note::NoteType, Word};
+use miden_objects::block::BlockNumber;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    account::parse_account_id,
+    audit::AuditEntry,
+    backup::RecoveryBundle,
+    campaign::check_campaign_mints,
+    deploy::{
+        create_faucet, deploy_script_request, parse_storage_mode, storage_mode_name, FaucetAccount,
+        TokenConfig,
+    },
+    execution::track_network_execution,
+    executor::TxExecutor,
+    ledger::{unix_now, FlowRecord, Ledger, MintRecord},
+    mint::{
+        check_recipient, consume_mint_note, faucet_owner, mint_with_options, parse_transaction_id,
+        rebuild_mint_note, AuxData, BatchMint, MintNoteKind, MintOptions, NoteTagStrategy,
+        RequestSource,
+    },
+    node::FaucetNode,
+    note_file::{note_file, write_note_file},
+    script::ScriptParams,
+    tx::ConsumeMode,
+    wallet::{create_wallet, create_wallet_from_seed, parse_key_id},
+    watcher::{wait_for_note_consumption, wait_for_transaction, BlockWatcher, SharedNode},
+    FaucetError,
+};
+
+/// Step of a [`DeployFlow`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployStep {
+    /// Create a new wallet owning the faucet.
+    CreateOwner,
+    /// Create the faucet and add it to the store.
+    CreateFaucet,
+    /// Submit the deployment script against the faucet.
+    RunScript,
+    /// Wait for the deployment and check the owner of the deployed faucet.
+    Verify,
+    Done,
+}
+
+impl DeployStep {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CreateOwner => "create_owner",
+            Self::CreateFaucet => "create_faucet",
+            Self::RunScript => "run_script",
+            Self::Verify => "verify",
+            Self::Done => "done",
+        }
+    }
+}
+
+/// Step of a [`MintFlow`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MintStep {
+    /// Create a new wallet receiving the tokens.
+    CreateRecipient,
+    /// Check the mint and draw the serial number of its note.
+    BuildNote,
+    /// Submit the MINT transaction and record the mint.
+    Submit,
+    /// Wait for the MINT transaction.
+    Wait,
+    /// Export a private note to a note file.
+    Export,
+    /// Submit the transaction consuming the note, unless the network executes it.
+    Consume,
+    /// Wait for the note to be consumed on chain, by the recipient or the network.
+    Claim,
+    Done,
+}
+
+impl MintStep {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CreateRecipient => "create_recipient",
+            Self::BuildNote => "build_note",
+            Self::Submit => "submit",
+            Self::Wait => "wait",
+            Self::Export => "export",
+            Self::Consume => "consume",
+            Self::Claim => "claim",
+            Self::Done => "done",
+        }
+    }
+}
+
+/// Deployment of a faucet, see [`crate::deploy::deploy_faucet_with_params`].
+///
+/// Accounts and transactions are kept as hex, like in the rest of the ledger.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeployFlow {
+    pub step: DeployStep,
+    /// Owner of the faucet, set by [`DeployStep::CreateOwner`] unless given.
+    pub owner_id: Option<String>,
+    /// File the recovery phrase of a new owner is written to, see [`RecoveryBundle`].
+    pub backup: Option<PathBuf>,
+    pub token: TokenConfig,
+    pub auth: String,
+    pub storage_mode: String,
+    pub key_id: Option<String>,
+    /// Deployment script, kept so a resumed flow runs the script it started with.
+    pub script: String,
+    /// File the script was read from, for the audit log.
+    pub script_path: Option<PathBuf>,
+    /// `name=value` values of the script placeholders.
+    pub params: Vec<String>,
+    pub script_arg: Option<String>,
+    pub faucet_id: Option<String>,
+    pub transaction_id: Option<String>,
+}
+
+impl DeployFlow {
+    /// Deployment of an `account` faucet issuing `token` by running `script`, owned by `owner`
+    /// or by a new wallet.
+    pub fn new(
+        owner: Option<AccountId>,
+        token: TokenConfig,
+        account: FaucetAccount,
+        script: impl Into<String>,
+    ) -> Self {
+        Self {
+            step: match owner {
+                Some(_) => DeployStep::CreateFaucet,
+                None => DeployStep::CreateOwner,
+            },
+            owner_id: owner.map(|owner| owner.to_hex()),
+            backup: None,
+            token,
+            auth: account.auth.to_string(),
+            storage_mode: storage_mode_name(account.storage_mode).to_string(),
+            key_id: account.key_id.map(|key_id| key_id.to_hex()),
+            script: script.into(),
+            script_path: None,
+            params: Vec::new(),
+            script_arg: None,
+            faucet_id: None,
+            transaction_id: None,
+        }
+    }
+
+    /// Sets the `name=value` placeholder values and the hex script argument, checked by
+    /// [`DeployFlow::script_params`].
+    pub fn with_params(mut self, params: Vec<String>, script_arg: Option<String>) -> Self {
+        self.params = params;
+        self.script_arg = script_arg;
+        self
+    }
+
+    pub fn with_backup(mut self, backup: Option<PathBuf>) -> Self {
+        self.backup = backup;
+        self
+    }
+
+    pub fn with_script_path(mut self, script_path: Option<PathBuf>) -> Self {
+        self.script_path = script_path;
+        self
+    }
+
+    pub fn account(&self) -> Result<FaucetAccount, FaucetError> {
+        Ok(FaucetAccount {
+            auth: self.auth.parse()?,
+            storage_mode: parse_storage_mode(&self.storage_mode)?,
+            key_id: self.key_id.as_deref().map(parse_key_id).transpose()?,
+        })
+    }
+
+    pub fn script_params(&self) -> Result<ScriptParams, FaucetError> {
+        let mut params = ScriptParams::new();
+        for assignment in &self.params {
+            params = params.with_assignment(assignment)?;
+        }
+        if let Some(arg) = &self.script_arg {
+            let arg = Word::try_from(arg.as_str()).map_err(|err| {
+                FaucetError::ScriptTemplate(format!("invalid script argument: {err}"))
+            })?;
+            params = params.with_script_arg(arg);
+        }
+        Ok(params)
+    }
+}
+
+/// Mint to a wallet of this client, consuming the minted note, as run by `mint`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MintFlow {
+    pub step: MintStep,
+    pub faucet_id: String,
+    /// Recipient of the tokens, set by [`MintStep::CreateRecipient`] unless given.
+    pub recipient: Option<String>,
+    pub amount: u64,
+    /// Block from which the note can be consumed, if it is timelocked.
+    pub unlock_block: Option<u32>,
+    /// Whether the note is public, the [`crate::tx::TxPolicy`] note type if unset.
+    pub public_note: Option<bool>,
+    pub campaign: Option<u32>,
+    /// How the note is tagged, the tag of the recipient if unset. Notes tagged for network
+    /// execution skip [`MintStep::Consume`].
+    pub note_tag: Option<NoteTagStrategy>,
+    pub consume: ConsumeMode,
+    /// Time allowed for the consumed note to show up on chain.
+    pub claim_timeout_secs: u64,
+    /// Serial number of the note, drawn by [`MintStep::BuildNote`].
+    pub serial_num: Option<String>,
+    /// Ledger ID of the mint, set by [`MintStep::Submit`].
+    pub mint_id: Option<i64>,
+    /// Block the MINT transaction was committed in.
+    pub minted_at: Option<u32>,
+    /// Note file a private note was exported to.
+    pub note_path: Option<PathBuf>,
+    pub consume_transaction_id: Option<String>,
+    /// Block the note was consumed in.
+    pub claimed_at: Option<u32>,
+}
+
+impl MintFlow {
+    /// Mint of `amount` tokens of `faucet_id` to `recipient`, or to a new wallet.
+    pub fn new(faucet_id: AccountId, recipient: Option<AccountId>, amount: u64) -> Self {
+        Self {
+            step: match recipient {
+                Some(_) => MintStep::BuildNote,
+                None => MintStep::CreateRecipient,
+            },
+            faucet_id: faucet_id.to_hex(),
+            recipient: recipient.map(|recipient| recipient.to_hex()),
+            amount,
+            unlock_block: None,
+            public_note: None,
+            campaign: None,
+            note_tag: None,
+            consume: ConsumeMode::default(),
+            claim_timeout_secs: 60,
+            serial_num: None,
+            mint_id: None,
+            minted_at: None,
+            note_path: None,
+            consume_transaction_id: None,
+            claimed_at: None,
+        }
+    }
+
+    pub fn with_unlock_block(mut self, unlock_block: Option<u32>) -> Self {
+        self.unlock_block = unlock_block;
+        self
+    }
+
+    pub fn with_note_type(mut self, note_type: Option<NoteType>) -> Self {
+        self.public_note = note_type.map(|note_type| note_type == NoteType::Public);
+        self
+    }
+
+    pub fn with_campaign(mut self, campaign: Option<u32>) -> Self {
+        self.campaign = campaign;
+        self
+    }
+
+    pub fn with_note_tag(mut self, note_tag: Option<NoteTagStrategy>) -> Self {
+        self.note_tag = note_tag;
+        self
+    }
+
+    pub fn with_consume(mut self, consume: ConsumeMode, claim_timeout: Duration) -> Self {
+        self.consume = consume;
+        self.claim_timeout_secs = claim_timeout.as_secs();
+        self
+    }
+
+    /// Options of the mint, with the serial number once drawn.
+    pub fn options(&self) -> Result<MintOptions, FaucetError> {
+        let serial_num = self
+            .serial_num
+            .as_deref()
+            .map(|serial_num| {
+                Word::try_from(serial_num)
+                    .map_err(|_| FaucetError::Flow(format!("bad serial number {serial_num}")))
+            })
+            .transpose()?;
+        Ok(MintOptions {
+            serial_num,
+            note_kind: MintNoteKind::P2id.with_unlock_height(self.unlock_block.map(Into::into)),
+            note_type: self.public_note.map(|public| {
+                if public {
+                    NoteType::Public
+                } else {
+                    NoteType::Private
+                }
+            }),
+            aux: AuxData::new(self.campaign.unwrap_or_default(), RequestSource::Cli),
+            note_tag: self.note_tag,
+        })
+    }
+}
+
+/// A flow with its state, as persisted in the ledger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Flow {
+    Deploy(DeployFlow),
+    Mint(MintFlow),
+}
+
+impl Flow {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Deploy(_) => "deploy",
+            Self::Mint(_) => "mint",
+        }
+    }
+
+    /// Next step of the flow.
+    pub fn step(&self) -> &'static str {
+        match self {
+            Self::Deploy(flow) => flow.step.as_str(),
+            Self::Mint(flow) => flow.step.as_str(),
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        matches!(
+            self,
+            Self::Deploy(DeployFlow {
+                step: DeployStep::Done,
+                ..
+            }) | Self::Mint(MintFlow {
+                step: MintStep::Done,
+                ..
+            })
+        )
+    }
+
+    /// Whether the next step may run again after failing: whether it submits no transaction.
+    pub fn is_repeatable(&self) -> bool {
+        !matches!(
+            self,
+            Self::Deploy(DeployFlow {
+                step: DeployStep::RunScript,
+                ..
+            }) | Self::Mint(MintFlow {
+                step: MintStep::Submit | MintStep::Consume,
+                ..
+            })
+        )
+    }
+
+    pub fn from_record(record: &FlowRecord) -> Result<Self, FaucetError> {
+        let invalid =
+            |err: serde_json::Error| FaucetError::Flow(format!("flow {}: {err}", record.id));
+        match record.kind.as_str() {
+            "deploy" => Ok(Self::Deploy(
+                serde_json::from_str(&record.state).map_err(invalid)?,
+            )),
+            "mint" => Ok(Self::Mint(
+                serde_json::from_str(&record.state).map_err(invalid)?,
+            )),
+            other => Err(FaucetError::Flow(format!(
+                "flow {} is of unknown kind `{other}`",
+                record.id
+            ))),
+        }
+    }
+
+    fn state(&self) -> String {
+        match self {
+            Self::Deploy(flow) => serde_json::to_string(flow),
+            Self::Mint(flow) => serde_json::to_string(flow),
+        }
+        .expect("flow states serialize to JSON")
+    }
+}
+
+/// Records `flow` in `ledger` and returns its ID, to be run by [`FlowRunner::resume`].
+pub fn start_flow(ledger: &Ledger, flow: &Flow) -> Result<i64, FaucetError> {
+    ledger.create_flow(flow.kind(), flow.step(), &flow.state())
+}
+
+/// How often a step failing with a transient error is run, counting the first attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepRetry {
+    pub attempts: u32,
+    pub backoff: Duration,
+}
+
+impl Default for StepRetry {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Runs the steps of persisted flows.
+pub struct FlowRunner<'a, N> {
+    node: SharedNode<N>,
+    watcher: &'a BlockWatcher,
+    ledger: &'a Ledger,
+    /// Actor of the audit entries of the flows.
+    actor: String,
+    lock_dir: Option<PathBuf>,
+    retry: StepRetry,
+}
+
+impl<'a, N: FaucetNode + 'static> FlowRunner<'a, N> {
+    pub fn new(
+        node: SharedNode<N>,
+        watcher: &'a BlockWatcher,
+        ledger: &'a Ledger,
+        actor: impl Into<String>,
+    ) -> Self {
+        Self {
+            node,
+            watcher,
+            ledger,
+            actor: actor.into(),
+            lock_dir: None,
+            retry: StepRetry::default(),
+        }
+    }
+
+    /// Holds the lock of the faucet owner in `lock_dir` while submitting mints, see
+    /// [`TxExecutor::spawn`].
+    pub fn with_lock_dir(mut self, lock_dir: PathBuf) -> Self {
+        self.lock_dir = Some(lock_dir);
+        self
+    }
+
+    pub fn with_retry(mut self, retry: StepRetry) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Runs flow `id` from its next step until it is done, persisting it after every step, and
+    /// returns it.
+    ///
+    /// Fails with the error of the step that failed for good; the flow stays at that step.
+    pub async fn resume(&self, id: i64) -> Result<Flow, FaucetError> {
+        let record = self
+            .ledger
+            .flow(id)?
+            .ok_or_else(|| FaucetError::Flow(format!("no flow {id}")))?;
+        let mut flow = Flow::from_record(&record)?;
+        // Failed attempts of the step, across runs, and in this run.
+        let mut attempts = record.attempts;
+        let mut tries = 0;
+        while !flow.is_done() {
+            match self.advance(&flow).await {
+                Ok(next) => {
+                    flow = next;
+                    attempts = 0;
+                    tries = 0;
+                    self.ledger
+                        .update_flow(id, flow.step(), &flow.state(), 0, None)?;
+                }
+                Err(err) => {
+                    attempts += 1;
+                    tries += 1;
+                    self.ledger.update_flow(
+                        id,
+                        flow.step(),
+                        &flow.state(),
+                        attempts,
+                        Some(&err.to_string()),
+                    )?;
+                    if !(err.is_transient() && flow.is_repeatable()) || tries >= self.retry.attempts
+                    {
+                        return Err(err);
+                    }
+                    eprintln!(
+                        "Step {} of flow {id} failed (attempt {tries}/{}), retrying: {err}",
+                        flow.step(),
+                        self.retry.attempts
+                    );
+                    tokio::time::sleep(self.retry.backoff).await;
+                }
+            }
+        }
+        Ok(flow)
+    }
+
+    /// Runs the next step of `flow` and returns the flow after it.
+    async fn advance(&self, flow: &Flow) -> Result<Flow, FaucetError> {
+        match flow {
+            Flow::Deploy(flow) => self.advance_deploy(flow.clone()).await.map(Flow::Deploy),
+            Flow::Mint(flow) => self.advance_mint(flow.clone()).await.map(Flow::Mint),
+        }
+    }
+
+    async fn advance_deploy(&self, mut flow: DeployFlow) -> Result<DeployFlow, FaucetError> {
+        match flow.step {
+            DeployStep::CreateOwner => {
+                let mut node = self.node.lock().await;
+                let mut seed = [0_u8; 32];
+                node.rng().fill_bytes(&mut seed);
+                let owner = create_wallet_from_seed(&mut *node, seed).await?;
+                if let Some(backup) = &flow.backup {
+                    RecoveryBundle::new(owner.id(), seed)?.write(backup)?;
+                }
+                flow.owner_id = Some(owner.id().to_hex());
+                flow.step = DeployStep::CreateFaucet;
+            }
+            DeployStep::CreateFaucet => {
+                let faucet = create_faucet(
+                    &mut *self.node.lock().await,
+                    recorded_account(&flow.owner_id, "owner")?,
+                    &flow.script,
+                    &flow.script_params()?,
+                    &flow.token,
+                    flow.account()?,
+                )
+                .await?;
+                flow.faucet_id = Some(faucet.id().to_hex());
+                flow.step = DeployStep::RunScript;
+            }
+            DeployStep::RunScript => {
+                let owner_id = recorded_account(&flow.owner_id, "owner")?;
+                let faucet_id = recorded_account(&flow.faucet_id, "faucet")?;
+                let (transaction_id, cost) = {
+                    let mut node = self.node.lock().await;
+                    let request = deploy_script_request(
+                        &*node,
+                        faucet_id,
+                        owner_id,
+                        &flow.script,
+                        &flow.script_params()?,
+                        &flow.token,
+                    )?;
+                    let transaction_id = node.submit_transaction(faucet_id, request).await?;
+                    (transaction_id, node.take_transaction_cost(transaction_id))
+                };
+                if let Some(cost) = cost {
+                    self.ledger
+                        .record_cost(faucet_id, "deploy", transaction_id, cost)?;
+                }
+                self.ledger
+                    .record_deployment(faucet_id, owner_id, &flow.token, transaction_id)?;
+                self.ledger.append_audit(
+                    &AuditEntry::new(&self.actor, "faucet.deploy")
+                        .account(faucet_id)
+                        .param("owner", owner_id.to_hex())
+                        .param("symbol", &flow.token.symbol)
+                        .param("decimals", flow.token.decimals)
+                        .param("max_supply", flow.token.max_supply)
+                        .param("auth", &flow.auth)
+                        .param("storage_mode", &flow.storage_mode)
+                        .param("key_id", &flow.key_id)
+                        .param("script_path", &flow.script_path)
+                        .param("params", &flow.params)
+                        .param("script_arg", &flow.script_arg)
+                        .transaction(transaction_id),
+                )?;
+                flow.transaction_id = Some(transaction_id.to_hex());
+                flow.step = DeployStep::Verify;
+            }
+            DeployStep::Verify => {
+                let owner_id = recorded_account(&flow.owner_id, "owner")?;
+                let faucet_id = recorded_account(&flow.faucet_id, "faucet")?;
+                let transaction_id =
+                    parse_transaction_id(&recorded(&flow.transaction_id, "transaction")?)?;
+                wait_for_transaction(&self.node, self.watcher, transaction_id).await?;
+                let deployed_owner = faucet_owner(&mut *self.node.lock().await, faucet_id).await?;
+                if deployed_owner != owner_id {
+                    return Err(FaucetError::Flow(format!(
+                        "faucet {faucet_id} is owned by {deployed_owner}, not {owner_id}"
+                    )));
+                }
+                flow.step = DeployStep::Done;
+            }
+            DeployStep::Done => {}
+        }
+        Ok(flow)
+    }
+
+    async fn advance_mint(&self, mut flow: MintFlow) -> Result<MintFlow, FaucetError> {
+        let faucet_id = parse_account_id(&flow.faucet_id, None)?;
+        match flow.step {
+            MintStep::CreateRecipient => {
+                let recipient = create_wallet(&mut *self.node.lock().await).await?;
+                flow.recipient = Some(recipient.id().to_hex());
+                flow.step = MintStep::BuildNote;
+            }
+            MintStep::BuildNote => {
+                let recipient = recorded_account(&flow.recipient, "recipient")?;
+                check_recipient(faucet_id, recipient)?;
+                if let Some(note_tag) = flow.note_tag {
+                    note_tag.tag(recipient)?;
+                }
+                let mint = BatchMint {
+                    recipient,
+                    amount: flow.amount,
+                    options: flow.options()?,
+                };
+                check_campaign_mints(self.ledger, faucet_id, &[mint], unix_now())?;
+                let serial_num = self.node.lock().await.rng().draw_word();
+                flow.serial_num = Some(serial_num.to_hex());
+                flow.step = MintStep::Submit;
+            }
+            MintStep::Submit => {
+                let recipient = recorded_account(&flow.recipient, "recipient")?;
+                let amount = flow.amount;
+                let options = flow.options()?;
+                // Other processes minting from the same owner, like `serve`, wait until this one
+                // is out.
+                let owner_id = faucet_owner(&mut *self.node.lock().await, faucet_id).await?;
+                let executor =
+                    TxExecutor::spawn(self.node.clone(), owner_id, self.lock_dir.clone());
+                let mint = executor
+                    .run(move |node| {
+                        Box::pin(mint_with_options(
+                            node, faucet_id, recipient, amount, options,
+                        ))
+                    })
+                    .await?;
+                if let Some(cost) = mint.cost {
+                    self.ledger
+                        .record_cost(faucet_id, "mint", mint.transaction_id, cost)?;
+                }
+                let mint_id = self.ledger.record_mint(
+                    faucet_id,
+                    recipient,
+                    amount,
+                    mint.transaction_id,
+                    &mint.p2id_note,
+                )?;
+                self.ledger.append_audit(
+                    &AuditEntry::new(&self.actor, "mint")
+                        .account(mint.owner_id)
+                        .param("faucet_id", faucet_id.to_hex())
+                        .param("recipient", recipient.to_hex())
+                        .param("amount", amount)
+                        .param("mint_id", mint_id)
+                        .param("note_id", mint.p2id_note.id().to_hex())
+                        .transaction(mint.transaction_id),
+                )?;
+                flow.mint_id = Some(mint_id);
+                flow.step = MintStep::Wait;
+            }
+            MintStep::Wait => {
+                let record = self.mint_record(&flow)?;
+                let transaction_id = parse_transaction_id(&record.transaction_id)?;
+                match wait_for_transaction(&self.node, self.watcher, transaction_id).await {
+                    Ok(block_num) => {
+                        self.ledger.mark_committed(transaction_id, block_num)?;
+                        flow.minted_at = Some(block_num.as_u32());
+                    }
+                    Err(err) => {
+                        if !err.is_transient() {
+                            self.ledger.mark_failed(transaction_id, &err.to_string())?;
+                        }
+                        return Err(err);
+                    }
+                }
+                flow.step = MintStep::Export;
+            }
+            MintStep::Export => {
+                let record = self.mint_record(&flow)?;
+                if record.note_type() != NoteType::Public {
+                    let minted_at = recorded(&flow.minted_at, "mint block")?;
+                    let path = PathBuf::from(format!("mint-{}.mno", record.id));
+                    let file = note_file(
+                        &mut *self.node.lock().await,
+                        rebuild_mint_note(&record)?,
+                        Some(minted_at.into()),
+                    )
+                    .await?;
+                    write_note_file(&path, &file)?;
+                    flow.note_path = Some(path);
+                }
+                flow.step = MintStep::Consume;
+            }
+            MintStep::Consume => {
+                let record = self.mint_record(&flow)?;
+                // The network consumes notes with network-execution tags itself.
+                if record.network_executed() {
+                    flow.step = MintStep::Claim;
+                    return Ok(flow);
+                }
+                if let Some(unlock_block) = flow.unlock_block {
+                    self.watcher
+                        .wait_for_block(BlockNumber::from(unlock_block))
+                        .await?;
+                }
+                let transaction_id = consume_mint_note(
+                    &mut *self.node.lock().await,
+                    recorded_account(&flow.recipient, "recipient")?,
+                    parse_transaction_id(&record.transaction_id)?,
+                    rebuild_mint_note(&record)?,
+                    flow.consume,
+                )
+                .await?;
+                flow.consume_transaction_id = Some(transaction_id.to_hex());
+                flow.step = MintStep::Claim;
+            }
+            MintStep::Claim => {
+                let record = self.mint_record(&flow)?;
+                let claim_timeout = Duration::from_secs(flow.claim_timeout_secs);
+                if record.network_executed() {
+                    let executed_at = track_network_execution(
+                        &self.node,
+                        self.watcher,
+                        self.ledger,
+                        &self.actor,
+                        &record,
+                        claim_timeout,
+                    )
+                    .await?;
+                    flow.claimed_at = Some(executed_at.as_u32());
+                    flow.step = MintStep::Done;
+                    return Ok(flow);
+                }
+                let transaction_id = parse_transaction_id(&recorded(
+                    &flow.consume_transaction_id,
+                    "consume transaction",
+                )?)?;
+                let committed_at =
+                    wait_for_transaction(&self.node, self.watcher, transaction_id).await?;
+                self.watcher.wait_for_block(committed_at).await?;
+
+                let nullifier = rebuild_mint_note(&record)?.nullifier();
+                let claimed_at = wait_for_note_consumption(
+                    &self.node,
+                    self.watcher,
+                    nullifier,
+                    recorded(&flow.minted_at, "mint block")?.into(),
+                    claim_timeout,
+                )
+                .await?;
+                self.ledger.mark_claimed(&nullifier.to_hex(), claimed_at)?;
+                flow.claimed_at = Some(claimed_at.as_u32());
+                flow.step = MintStep::Done;
+            }
+            MintStep::Done => {}
+        }
+        Ok(flow)
+    }
+
+    fn mint_record(&self, flow: &MintFlow) -> Result<MintRecord, FaucetError> {
+        let mint_id = recorded(&flow.mint_id, "mint")?;
+        self.ledger
+            .get_mint(mint_id)?
+            .ok_or_else(|| FaucetError::Flow(format!("mint {mint_id} is not in the ledger")))
+    }
+}
+
+/// Value an earlier step recorded in the state of a flow.
+fn recorded<T: Clone>(value: &Option<T>, what: &str) -> Result<T, FaucetError> {
+    value
+        .clone()
+        .ok_or_else(|| FaucetError::Flow(format!("the flow has no {what} yet")))
+}
+
+fn recorded_account(value: &Option<String>, what: &str) -> Result<AccountId, FaucetError> {
+    parse_account_id(&recorded(value, what)?, None)
+}
@@ -0,0 +1,398 @@
+//! GitHub sign-in for public faucets.
+//!
+//! With the `[github]` section set, the mint APIs only mint for API keys and users signed in
+//! with a GitHub account at least [`GithubConfig::min_account_age_days`] old, at most
+//! [`GithubConfig::max_drips`] times per [`GithubConfig::window_secs`] and account. Fresh
+//! accounts are cheap to make in bulk, old ones are not, which is what deters sybils.
+//!
+//! Sign-in follows the OAuth web flow: `GET /api/auth/github` redirects to GitHub, which sends
+//! the user back to `GET /api/auth/github/callback`. The callback checks the account and returns
+//! a session token, sent with mints to either API as `Authorization: Bearer <token>`, see
+//! [`crate::access`]. Sessions and drips are
+//! recorded in the [`Ledger`] under the numeric GitHub user ID, which unlike the login cannot be
+//! changed; only a hash of each token is stored.
+//!
+//! The drip limit can be adjusted at runtime through the admin API, see [`crate::admin`]. The
+//! adjusted limit is kept in the ledger and restored on startup, so a restart does not undo a limit
+//! tightened against a drain, until it is reset to the configured one.
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use miden_crypto::hash::rpo::Rpo256;
+use reqwest::{
+    header::{ACCEPT, USER_AGENT},
+    Url,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ledger::{unix_now, Ledger},
+    FaucetError,
+};
+
+const AUTHORIZE_URL: &str = "https://github.com/login/oauth/authorize";
+const TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+const USER_URL: &str = "https://api.github.com/user";
+
+/// Time a user has to complete the sign-in on GitHub.
+const STATE_TTL_SECS: u64 = 600;
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// GitHub sign-in settings, read from the `[github]` section of the configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct GithubConfig {
+    /// Client ID and secret of the GitHub OAuth app.
+    pub client_id: String,
+    pub client_secret: String,
+    /// Callback URL registered with the OAuth app: `/api/auth/github/callback` of the REST API,
+    /// as reachable from browsers.
+    pub redirect_url: String,
+    #[serde(default = "default_min_account_age_days")]
+    pub min_account_age_days: u32,
+    /// Mints per GitHub account within `window_secs`.
+    #[serde(default = "default_max_drips")]
+    pub max_drips: u32,
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+    #[serde(default = "default_session_ttl_secs")]
+    pub session_ttl_secs: u64,
+}
+
+fn default_min_account_age_days() -> u32 {
+    30
+}
+
+fn default_max_drips() -> u32 {
+    1
+}
+
+fn default_window_secs() -> u64 {
+    SECS_PER_DAY
+}
+
+fn default_session_ttl_secs() -> u64 {
+    SECS_PER_DAY
+}
+
+impl GithubConfig {
+    /// Checks the settings without contacting GitHub.
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        if self.client_id.is_empty() || self.client_secret.is_empty() {
+            return Err(FaucetError::Config(
+                "github.client_id and github.client_secret must be set".into(),
+            ));
+        }
+        if !self.redirect_url.starts_with("http://") && !self.redirect_url.starts_with("https://") {
+            return Err(FaucetError::Config(format!(
+                "github.redirect_url `{}` must be an http:// or https:// URL",
+                self.redirect_url
+            )));
+        }
+        if self.max_drips == 0 {
+            return Err(FaucetError::Config(
+                "github.max_drips must be positive".into(),
+            ));
+        }
+        if self.window_secs == 0 || self.session_ttl_secs == 0 {
+            return Err(FaucetError::Config(
+                "github.window_secs and github.session_ttl_secs must be positive".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs)
+    }
+
+    pub fn drip_limit(&self) -> DripLimit {
+        DripLimit {
+            max_drips: self.max_drips,
+            window_secs: self.window_secs,
+        }
+    }
+}
+
+/// Mints a GitHub account may request within a window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DripLimit {
+    pub max_drips: u32,
+    pub window_secs: u64,
+}
+
+impl DripLimit {
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        if self.max_drips == 0 || self.window_secs == 0 {
+            return Err(FaucetError::Config(
+                "max_drips and window_secs must be positive".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// GitHub account a session belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GithubUser {
+    pub id: u64,
+    pub login: String,
+}
+
+/// Session opened by a successful sign-in.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub token: String,
+    pub login: String,
+    /// Unix timestamp, in seconds, after which the token is rejected.
+    pub expires_at: u64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Account {
+    id: u64,
+    login: String,
+    created_at: String,
+}
+
+/// Name of the adjusted drip limit among the limit overrides of the [`Ledger`].
+const DRIP_LIMIT_OVERRIDE: &str = "github.drip_limit";
+
+/// Signs users in with GitHub and enforces the per-account drip limit.
+pub struct GithubAuth {
+    config: GithubConfig,
+    http: reqwest::Client,
+    ledger: Mutex<Ledger>,
+    /// Drip limit of the configuration, unless adjusted since, also before a restart.
+    limit: Mutex<DripLimit>,
+    /// OAuth states handed out by [`Self::authorize_url`], with their expiry.
+    states: Mutex<HashMap<String, u64>>,
+}
+
+impl GithubAuth {
+    /// Records sessions and drips in `ledger`, a connection of its own since the REST API runs
+    /// outside the service worker, and restores the drip limit adjusted before a restart.
+    pub fn new(config: GithubConfig, ledger: Ledger) -> Result<Self, FaucetError> {
+        config.validate()?;
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|err| FaucetError::Github(err.to_string()))?;
+        let limit = match ledger.limit_override(DRIP_LIMIT_OVERRIDE)? {
+            Some(value) => match serde_json::from_str::<DripLimit>(&value) {
+                Ok(limit) if limit.validate().is_ok() => limit,
+                _ => {
+                    eprintln!("Ignoring the invalid adjusted drip limit `{value}` of the ledger");
+                    config.drip_limit()
+                }
+            },
+            None => config.drip_limit(),
+        };
+        Ok(Self {
+            limit: Mutex::new(limit),
+            config,
+            http,
+            ledger: Mutex::new(ledger),
+            states: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// GitHub page asking the user to authorize the OAuth app, valid for ten minutes.
+    pub fn authorize_url(&self) -> String {
+        let state = hex::encode(rand::random::<[u8; 16]>());
+        let now = unix_now();
+        let mut states = self.states.lock().expect("state lock poisoned");
+        states.retain(|_, expires_at| *expires_at > now);
+        states.insert(state.clone(), now + STATE_TTL_SECS);
+
+        Url::parse_with_params(
+            AUTHORIZE_URL,
+            [
+                ("client_id", self.config.client_id.as_str()),
+                ("redirect_uri", self.config.redirect_url.as_str()),
+                ("state", state.as_str()),
+                ("allow_signup", "false"),
+            ],
+        )
+        .expect("authorize URL is valid")
+        .to_string()
+    }
+
+    /// Completes the sign-in GitHub redirected back with, and opens a session if the account is
+    /// old enough.
+    pub async fn sign_in(&self, code: &str, state: &str) -> Result<Session, FaucetError> {
+        let expires_at = self
+            .states
+            .lock()
+            .expect("state lock poisoned")
+            .remove(state);
+        if expires_at.is_none_or(|expires_at| expires_at <= unix_now()) {
+            return Err(FaucetError::Unauthorized(
+                "unknown or expired sign-in, start again".into(),
+            ));
+        }
+
+        let token: TokenResponse = self
+            .http
+            .post(TOKEN_URL)
+            .header(ACCEPT, "application/json")
+            .form(&[
+                ("client_id", self.config.client_id.as_str()),
+                ("client_secret", self.config.client_secret.as_str()),
+                ("code", code),
+                ("redirect_uri", self.config.redirect_url.as_str()),
+            ])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| FaucetError::Github(err.to_string()))?
+            .json()
+            .await
+            .map_err(|err| FaucetError::Github(err.to_string()))?;
+        let Some(access_token) = token.access_token else {
+            let reason = token
+                .error_description
+                .or(token.error)
+                .unwrap_or_else(|| "no access token".into());
+            return Err(FaucetError::Unauthorized(reason));
+        };
+
+        let account: Account = self
+            .http
+            .get(USER_URL)
+            .bearer_auth(access_token)
+            .header(ACCEPT, "application/vnd.github+json")
+            .header(USER_AGENT, "network-faucet")
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| FaucetError::Github(err.to_string()))?
+            .json()
+            .await
+            .map_err(|err| FaucetError::Github(err.to_string()))?;
+        check_account_age(
+            &account.login,
+            &account.created_at,
+            unix_now(),
+            self.config.min_account_age_days,
+        )?;
+
+        self.start_session(&GithubUser {
+            id: account.id,
+            login: account.login,
+        })
+    }
+
+    /// Opens a session of `user` without going through GitHub, e.g. for an account checked
+    /// already.
+    pub fn start_session(&self, user: &GithubUser) -> Result<Session, FaucetError> {
+        let token = hex::encode(rand::random::<[u8; 32]>());
+        let expires_at = unix_now() + self.config.session_ttl_secs;
+        self.ledger()
+            .create_github_session(&token_hash(&token), user, expires_at)?;
+        Ok(Session {
+            token,
+            login: user.login.clone(),
+            expires_at,
+        })
+    }
+
+    /// User of the session in the `Authorization` header of a request.
+    pub fn authenticate(&self, authorization: Option<&str>) -> Result<GithubUser, FaucetError> {
+        let token = authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| {
+                FaucetError::Unauthorized("sign in with GitHub at /api/auth/github first".into())
+            })?;
+        self.ledger()
+            .github_session(&token_hash(token.trim()), unix_now())?
+            .ok_or_else(|| FaucetError::Unauthorized("session expired, sign in again".into()))
+    }
+
+    /// Reserves one of the drips `user` has left in the current window. Fails with
+    /// [`FaucetError::RateLimited`] when none are left.
+    pub fn reserve_drip(&self, user: &GithubUser) -> Result<i64, FaucetError> {
+        let limit = self.drip_limit();
+        self.ledger().reserve_drip(
+            user.id,
+            unix_now(),
+            Duration::from_secs(limit.window_secs),
+            limit.max_drips,
+        )
+    }
+
+    pub fn drip_limit(&self) -> DripLimit {
+        *self.limit.lock().expect("limit lock poisoned")
+    }
+
+    /// Replaces the drip limit, also across restarts. Drips already counted stay counted.
+    pub fn set_drip_limit(&self, limit: DripLimit) -> Result<(), FaucetError> {
+        limit.validate()?;
+        let value = serde_json::to_string(&limit).expect("drip limits serialize");
+        self.ledger()
+            .set_limit_override(DRIP_LIMIT_OVERRIDE, &value)?;
+        *self.limit.lock().expect("limit lock poisoned") = limit;
+        Ok(())
+    }
+
+    /// Goes back to the drip limit of the configuration and returns it.
+    pub fn reset_drip_limit(&self) -> Result<DripLimit, FaucetError> {
+        self.ledger().remove_limit_override(DRIP_LIMIT_OVERRIDE)?;
+        let limit = self.config.drip_limit();
+        *self.limit.lock().expect("limit lock poisoned") = limit;
+        Ok(limit)
+    }
+
+    /// Attaches the mint of drip `drip_id`, or gives the drip back if the mint failed.
+    pub fn finish_drip(&self, drip_id: i64, mint_id: Option<i64>) -> Result<(), FaucetError> {
+        let ledger = self.ledger();
+        match mint_id {
+            Some(mint_id) => ledger.set_drip_mint(drip_id, mint_id),
+            None => ledger.release_drip(drip_id),
+        }
+    }
+
+    fn ledger(&self) -> std::sync::MutexGuard<'_, Ledger> {
+        self.ledger.lock().expect("ledger lock poisoned")
+    }
+}
+
+/// Fails unless the account created at `created_at`, an RFC 3339 timestamp as returned by GitHub,
+/// is at least `min_days` old at `now`.
+pub fn check_account_age(
+    login: &str,
+    created_at: &str,
+    now: u64,
+    min_days: u32,
+) -> Result<(), FaucetError> {
+    let created = humantime::parse_rfc3339(created_at)
+        .map_err(|err| FaucetError::Github(format!("bad creation time `{created_at}`: {err}")))?
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let age_days = now.saturating_sub(created) / SECS_PER_DAY;
+    if age_days < u64::from(min_days) {
+        return Err(FaucetError::AccountTooNew {
+            login: login.to_string(),
+            age_days,
+            min_days,
+        });
+    }
+    Ok(())
+}
+
+fn token_hash(token: &str) -> String {
+    Rpo256::hash(token.as_bytes()).to_hex()
+}
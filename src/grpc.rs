@@ -0,0 +1,310 @@
+//! gRPC API of the faucet service, defined in `proto/faucet.proto`.
+
+use std::{net::SocketAddr, sync::Arc};
+
+use miden_client::{note::NoteType, utils::Serializable};
+use miden_objects::block::BlockNumber;
+use tonic::{transport::Server, Request, Response, Status};
+
+use crate::{
+    access::{Access, Caller},
+    email::parse_email,
+    ledger::{MintRecord, MintStatus},
+    mint::{
+        parse_note_type, parse_serial_num, AuxData, MintNoteKind, MintOptions, NoteTagStrategy,
+        RequestSource,
+    },
+    network::Networks,
+    note_file::pending_note_file,
+    receipt::MintReceipt,
+    referral::parse_referral_code,
+    FaucetError,
+};
+
+pub mod proto {
+    tonic::include_proto!("faucet.v1");
+}
+
+use proto::faucet_server::{Faucet, FaucetServer};
+
+/// Serves the gRPC API on `addr` until the server fails, minting on `networks` and
+/// authenticating mints with `access`.
+pub async fn serve(
+    addr: SocketAddr,
+    networks: Networks,
+    access: Arc<Access>,
+) -> Result<(), FaucetError> {
+    println!("gRPC API listening on {addr}");
+    Server::builder()
+        .add_service(FaucetServer::new(FaucetGrpc { networks, access }))
+        .serve(addr)
+        .await
+        .map_err(|err| FaucetError::Server(err.to_string()))
+}
+
+struct FaucetGrpc {
+    networks: Networks,
+    access: Arc<Access>,
+}
+
+#[tonic::async_trait]
+impl Faucet for FaucetGrpc {
+    async fn mint(
+        &self,
+        request: Request<proto::MintRequest>,
+    ) -> Result<Response<proto::MintResponse>, Status> {
+        let authorization = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok());
+        let caller = self.access.authenticate(authorization).map_err(to_status)?;
+        let peer = request.remote_addr();
+        let request = request.into_inner();
+        let serial_num = request
+            .serial_num
+            .as_deref()
+            .map(parse_serial_num)
+            .transpose()
+            .map_err(to_status)?;
+        let note_type = request
+            .note_type
+            .as_deref()
+            .map(parse_note_type)
+            .transpose()
+            .map_err(to_status)?;
+        let note_tag = request
+            .note_tag
+            .as_deref()
+            .map(str::parse::<NoteTagStrategy>)
+            .transpose()
+            .map_err(to_status)?;
+        let email = request
+            .email
+            .as_deref()
+            .map(parse_email)
+            .transpose()
+            .map_err(to_status)?;
+
+        let referral_code = request
+            .referral_code
+            .as_deref()
+            .map(parse_referral_code)
+            .transpose()
+            .map_err(to_status)?;
+
+        let network = self
+            .networks
+            .get(request.network.as_deref())
+            .map_err(to_status)?;
+        let recipient = network
+            .parse_account_id(&request.recipient)
+            .map_err(to_status)?;
+        let mut handle = network
+            .mint_handle(request.token.as_deref(), self.access.amounts(caller.tier()))
+            .map_err(to_status)?;
+        if let Some(identity) = caller.identity() {
+            handle = handle.with_actor(identity.clone()).with_identity(identity);
+        }
+        if let Some(code) = referral_code {
+            handle = handle.with_referral(code);
+        }
+        self.access
+            .admit(&caller, peer.map(|peer| peer.ip()), 1)
+            .map_err(to_status)?;
+        if let Some(peer) = peer {
+            handle = handle.with_client_ip(peer.ip());
+        }
+        let drip = match (&caller, self.access.github()) {
+            (Caller::Github(user), Some(github)) => {
+                Some((github, github.reserve_drip(user).map_err(to_status)?))
+            }
+            _ => None,
+        };
+        let result = handle
+            .mint_with_email(
+                recipient,
+                request.amount,
+                MintOptions {
+                    serial_num,
+                    note_kind: MintNoteKind::from_reclaim_height(
+                        request.reclaim_height.map(Into::into),
+                    )
+                    .with_unlock_height(request.unlock_height.map(Into::into)),
+                    note_type,
+                    aux: AuxData::new(request.campaign_id.unwrap_or_default(), RequestSource::Grpc),
+                    note_tag,
+                },
+                email,
+            )
+            .await;
+        if let Some((github, drip_id)) = drip {
+            github
+                .finish_drip(drip_id, result.as_ref().ok().map(|ticket| ticket.mint_id))
+                .map_err(to_status)?;
+        }
+
+        let ticket = result.map_err(to_status)?;
+        Ok(Response::new(proto::MintResponse {
+            mint_id: ticket.mint_id,
+            transaction_id: ticket.transaction_id.to_hex(),
+            note_id: ticket.note_id.to_hex(),
+            amount: ticket.amount,
+            note_file: (ticket.note.metadata().note_type() != NoteType::Public)
+                .then(|| pending_note_file(ticket.note.clone(), BlockNumber::GENESIS).to_bytes()),
+        }))
+    }
+
+    async fn get_mint_status(
+        &self,
+        request: Request<proto::MintStatusRequest>,
+    ) -> Result<Response<proto::MintStatusResponse>, Status> {
+        let request = request.into_inner();
+        let mint_id = request.mint_id;
+        let record = self
+            .networks
+            .get(request.network.as_deref())
+            .and_then(|network| network.faucet(request.token.as_deref()))
+            .map_err(to_status)?
+            .status(mint_id)
+            .await
+            .map_err(to_status)?
+            .ok_or_else(|| Status::not_found(format!("mint {mint_id} not found")))?;
+
+        Ok(Response::new(record.into()))
+    }
+
+    async fn get_mint_receipt(
+        &self,
+        request: Request<proto::MintReceiptRequest>,
+    ) -> Result<Response<proto::MintReceipt>, Status> {
+        let request = request.into_inner();
+        let mint_id = request.mint_id;
+        let receipt = self
+            .networks
+            .get(request.network.as_deref())
+            .and_then(|network| network.faucet(request.token.as_deref()))
+            .map_err(to_status)?
+            .receipt(mint_id)
+            .await
+            .map_err(to_status)?
+            .ok_or_else(|| Status::not_found(format!("mint {mint_id} not found")))?;
+
+        Ok(Response::new(receipt.into()))
+    }
+
+    async fn get_stats(
+        &self,
+        request: Request<proto::StatsRequest>,
+    ) -> Result<Response<proto::StatsResponse>, Status> {
+        let request = request.into_inner();
+        let stats = self
+            .networks
+            .get(request.network.as_deref())
+            .and_then(|network| network.faucet(request.token.as_deref()))
+            .map_err(to_status)?
+            .stats()
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(proto::StatsResponse {
+            submitted: stats.submitted,
+            committed: stats.committed,
+            failed: stats.failed,
+            delivered: stats.delivered,
+            unclaimed: stats.unclaimed,
+            minted_amount: stats.minted_amount,
+            delivered_amount: stats.delivered_amount,
+            reclaimed: stats.reclaimed,
+            reclaimed_amount: stats.reclaimed_amount,
+            burned: stats.burned,
+            burned_amount: stats.burned_amount,
+            returned: stats.returned,
+            returned_amount: stats.returned_amount,
+            fees_paid: stats.fees_paid,
+            cycles_spent: stats.cycles_spent,
+        }))
+    }
+}
+
+impl From<MintRecord> for proto::MintStatusResponse {
+    fn from(record: MintRecord) -> Self {
+        let status = match record.status {
+            MintStatus::Submitted => proto::MintStatus::Submitted,
+            MintStatus::Committed => proto::MintStatus::Committed,
+            MintStatus::Failed => proto::MintStatus::Failed,
+            MintStatus::Invalidated => proto::MintStatus::Invalidated,
+        };
+
+        Self {
+            mint_id: record.id,
+            faucet_id: record.faucet_id,
+            recipient: record.recipient,
+            amount: record.amount,
+            transaction_id: record.transaction_id,
+            note_id: record.note_id,
+            status: status.into(),
+            commit_block: record.commit_block,
+            claim_block: record.claim_block,
+            reclaim_block: record.reclaim_block,
+            unlock_block: record.unlock_block,
+            replaced_by: record.replaced_by,
+            error: record.error,
+        }
+    }
+}
+
+impl From<MintReceipt> for proto::MintReceipt {
+    fn from(receipt: MintReceipt) -> Self {
+        Self {
+            mint_id: receipt.mint_id,
+            faucet_id: receipt.faucet_id,
+            recipient: receipt.recipient,
+            amount: receipt.amount,
+            transaction_id: receipt.transaction_id,
+            note_id: receipt.note_id,
+            note_commitment: receipt.note_commitment,
+            block_num: receipt.block_num,
+            signer: receipt.signer,
+            public_key: receipt.public_key,
+            signature: receipt.signature,
+        }
+    }
+}
+
+fn to_status(err: FaucetError) -> Status {
+    match err {
+        FaucetError::AmountOutOfRange { .. }
+        | FaucetError::InvalidAccountId(..)
+        | FaucetError::AccountNetworkMismatch { .. }
+        | FaucetError::InvalidCampaign(..)
+        | FaucetError::InvalidEmail(..)
+        | FaucetError::InvalidNoteType(_)
+        | FaucetError::InvalidNoteTag(..)
+        | FaucetError::InvalidRecipient(..)
+        | FaucetError::InvalidReferral(..)
+        | FaucetError::InvalidSerialNumber(..)
+        | FaucetError::UnknownNetwork(..)
+        | FaucetError::UnknownToken(..) => Status::invalid_argument(err.to_string()),
+        FaucetError::EmailDisabled | FaucetError::RecipientNotFound(_) => {
+            Status::failed_precondition(err.to_string())
+        }
+        FaucetError::Unauthorized(_) => Status::unauthenticated(err.to_string()),
+        FaucetError::AccessDenied(_)
+        | FaucetError::AbuseRefused(..)
+        | FaucetError::AccountTooNew { .. } => Status::permission_denied(err.to_string()),
+        FaucetError::RateLimited { .. }
+        | FaucetError::DailyBudgetExhausted { .. }
+        | FaucetError::CampaignBudgetExhausted { .. }
+        | FaucetError::CampaignUserLimit { .. } => Status::resource_exhausted(err.to_string()),
+        FaucetError::CampaignInactive(..) => Status::failed_precondition(err.to_string()),
+        FaucetError::ServiceStopped
+        | FaucetError::ExecutorStopped(_)
+        | FaucetError::FaucetPaused(_)
+        | FaucetError::FinalityUnavailable(_) => Status::unavailable(err.to_string()),
+        FaucetError::MintNotCommitted(_) | FaucetError::FinalityDisputed { .. } => {
+            Status::failed_precondition(err.to_string())
+        }
+        FaucetError::Github(_) => Status::unavailable(err.to_string()),
+        err if err.is_transient() => Status::unavailable(err.to_string()),
+        err => Status::internal(err.to_string()),
+    }
+}
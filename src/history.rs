@@ -0,0 +1,208 @@
+//! Account state history.
+//!
+//! The client store only keeps the latest state of each account, so the states needed to explain
+//! how an account changed over time are snapshotted into the [`Ledger`]: `serve` records the
+//! tracked accounts whose commitment changed on every block, see [`run_account_history`], and
+//! `account diff` records the current state before diffing. [`account_diff`] compares the latest
+//! states known at two blocks: nonce, vault assets and storage slots, including map entries.
+
+use std::collections::BTreeMap;
+
+use miden_client::{
+    account::{Account, AccountId, StorageSlot},
+    asset::Asset,
+    utils::{Deserializable, Serializable},
+    Word,
+};
+use miden_objects::block::BlockNumber;
+
+use crate::{
+    ledger::{AccountStateRecord, Ledger},
+    node::FaucetNode,
+    watcher::{BlockWatcher, SharedNode},
+    FaucetError,
+};
+
+/// A value that differs between the two states of an [`AccountDiff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// Faucet ID or asset word for vault changes, `slot N` or `slot N[key]` for storage changes.
+    pub key: String,
+    /// Value in the older state, `None` if absent.
+    pub before: Option<String>,
+    /// Value in the newer state, `None` if removed.
+    pub after: Option<String>,
+}
+
+/// Differences between two recorded states of an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountDiff {
+    /// Blocks at which the compared states were recorded.
+    pub from_block: u32,
+    pub to_block: u32,
+    pub from_commitment: String,
+    pub to_commitment: String,
+    pub nonce: (u64, u64),
+    pub vault: Vec<Change>,
+    pub storage: Vec<Change>,
+}
+
+impl AccountDiff {
+    pub fn is_empty(&self) -> bool {
+        self.from_commitment == self.to_commitment
+    }
+}
+
+/// Records the state of every account tracked by `node` whose commitment changed since its last
+/// snapshot, as of `block_num`. Returns the number of states recorded.
+pub async fn snapshot_accounts<N: FaucetNode>(
+    node: &mut N,
+    ledger: &Ledger,
+    block_num: BlockNumber,
+) -> Result<usize, FaucetError> {
+    let mut recorded = 0;
+    for account_id in node.tracked_accounts().await? {
+        if snapshot_account(node, ledger, account_id, block_num).await? {
+            recorded += 1;
+        }
+    }
+    Ok(recorded)
+}
+
+/// Records the state of `account_id` as of `block_num` if it changed since its last snapshot, and
+/// returns whether it did.
+pub async fn snapshot_account<N: FaucetNode>(
+    node: &mut N,
+    ledger: &Ledger,
+    account_id: AccountId,
+    block_num: BlockNumber,
+) -> Result<bool, FaucetError> {
+    let account = node
+        .get_account(account_id)
+        .await?
+        .ok_or(FaucetError::AccountNotFound(account_id))?;
+    ledger.record_account_state(
+        account_id,
+        block_num,
+        account.commitment(),
+        account.nonce().as_int(),
+        &account.to_bytes(),
+    )
+}
+
+/// Snapshots the tracked accounts every time the watcher reports a new block, until the watcher
+/// stops.
+///
+/// Failed snapshots are reported on stderr and retried on the next block.
+pub async fn run_account_history<N: FaucetNode>(
+    node: &SharedNode<N>,
+    watcher: &BlockWatcher,
+    ledger: &Ledger,
+) {
+    let mut tip = watcher.subscribe();
+    while tip.changed().await.is_ok() {
+        let Some(block_num) = watcher.tip().map(|tip| tip.block_num) else {
+            continue;
+        };
+        let result = snapshot_accounts(&mut *node.lock().await, ledger, block_num).await;
+        if let Err(err) = result {
+            eprintln!("Failed to record account states at block {block_num}: {err}");
+        }
+    }
+}
+
+/// Compares the latest recorded states of `account_id` at `from_block` and at `to_block`.
+pub fn account_diff(
+    ledger: &Ledger,
+    account_id: AccountId,
+    from_block: BlockNumber,
+    to_block: BlockNumber,
+) -> Result<AccountDiff, FaucetError> {
+    let state_at = |block_num: BlockNumber| {
+        ledger
+            .account_state_at(account_id, block_num)?
+            .ok_or_else(|| {
+                FaucetError::Ledger(format!(
+                    "no state of {account_id} recorded at or before block {block_num}"
+                ))
+            })
+    };
+    let (from, to) = (state_at(from_block)?, state_at(to_block)?);
+    Ok(diff_accounts(
+        &decode_state(&from)?,
+        from.block_num,
+        &decode_state(&to)?,
+        to.block_num,
+    ))
+}
+
+/// Compares two states of an account, `from` recorded at `from_block` and `to` at `to_block`.
+pub fn diff_accounts(from: &Account, from_block: u32, to: &Account, to_block: u32) -> AccountDiff {
+    AccountDiff {
+        from_block,
+        to_block,
+        from_commitment: from.commitment().to_hex(),
+        to_commitment: to.commitment().to_hex(),
+        nonce: (from.nonce().as_int(), to.nonce().as_int()),
+        vault: changes(vault_entries(from), vault_entries(to)),
+        storage: changes(storage_entries(from), storage_entries(to)),
+    }
+}
+
+fn decode_state(record: &AccountStateRecord) -> Result<Account, FaucetError> {
+    Account::read_from_bytes(&record.state).map_err(|err| {
+        FaucetError::Ledger(format!(
+            "bad state of {} at block {}: {err}",
+            record.account_id, record.block_num
+        ))
+    })
+}
+
+fn vault_entries(account: &Account) -> BTreeMap<String, String> {
+    account
+        .vault()
+        .assets()
+        .map(|asset| match asset {
+            Asset::Fungible(asset) => (asset.faucet_id().to_hex(), asset.amount().to_string()),
+            Asset::NonFungible(_) => (Word::from(asset).to_hex(), "present".into()),
+        })
+        .collect()
+}
+
+fn storage_entries(account: &Account) -> BTreeMap<String, String> {
+    let mut entries = BTreeMap::new();
+    for (index, slot) in account.storage().slots().iter().enumerate() {
+        match slot {
+            StorageSlot::Value(value) => {
+                entries.insert(format!("slot {index}"), value.to_hex());
+            }
+            StorageSlot::Map(map) => {
+                for (key, value) in map.entries() {
+                    entries.insert(format!("slot {index}[{}]", key.to_hex()), value.to_hex());
+                }
+            }
+        }
+    }
+    entries
+}
+
+fn changes(before: BTreeMap<String, String>, mut after: BTreeMap<String, String>) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for (key, old) in before {
+        match after.remove(&key) {
+            Some(new) if new == old => {}
+            new => changes.push(Change {
+                key,
+                before: Some(old),
+                after: new,
+            }),
+        }
+    }
+    changes.extend(after.into_iter().map(|(key, new)| Change {
+        key,
+        before: None,
+        after: Some(new),
+    }));
+    changes.sort_by(|a, b| a.key.cmp(&b.key));
+    changes
+}
@@ -0,0 +1,178 @@
+//! CORS and security headers of the REST API.
+//!
+//! Browser wallets served from other origins can only call the mint API if it answers their
+//! preflight requests, so [`CorsConfig`] lists the origins and methods allowed. Without a
+//! `[http.cors]` section browsers keep blocking cross-origin calls, as before.
+//!
+//! Every response also carries the usual security headers unless `security_headers` is disabled,
+//! e.g. because a proxy in front of the faucet sets its own.
+
+use std::time::Duration;
+
+use axum::{
+    http::{
+        header::{
+            ACCEPT, AUTHORIZATION, CONTENT_SECURITY_POLICY, CONTENT_TYPE, REFERRER_POLICY,
+            STRICT_TRANSPORT_SECURITY, X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+        },
+        HeaderName, HeaderValue, Method,
+    },
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use tower_http::{
+    cors::{AllowOrigin, CorsLayer},
+    set_header::SetResponseHeaderLayer,
+};
+
+use crate::FaucetError;
+
+/// HTTP settings of the REST API, read from the `[http]` section of the configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    /// Add `X-Content-Type-Options`, `X-Frame-Options`, `Referrer-Policy` and
+    /// `Content-Security-Policy` to every response, and `Strict-Transport-Security` when served
+    /// over HTTPS.
+    pub security_headers: bool,
+    /// `max-age` of `Strict-Transport-Security`.
+    pub hsts_max_age_secs: u64,
+    /// Log every request, see [`crate::request_log`].
+    pub access_log: bool,
+    /// Cross-origin requests allowed from browsers; all are refused when unset.
+    pub cors: Option<CorsConfig>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            security_headers: true,
+            hsts_max_age_secs: 31_536_000,
+            access_log: true,
+            cors: None,
+        }
+    }
+}
+
+impl HttpConfig {
+    /// Checks the settings without building the layers.
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        if let Some(cors) = &self.cors {
+            let _ = cors.layer()?;
+        }
+        Ok(())
+    }
+
+    /// Wraps `router` in the configured CORS and security header layers, `https` telling whether
+    /// it is served over TLS.
+    pub fn apply(&self, mut router: Router, https: bool) -> Result<Router, FaucetError> {
+        if self.security_headers {
+            let mut headers = vec![
+                (X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff")),
+                (X_FRAME_OPTIONS, HeaderValue::from_static("DENY")),
+                (REFERRER_POLICY, HeaderValue::from_static("no-referrer")),
+                (
+                    CONTENT_SECURITY_POLICY,
+                    HeaderValue::from_static("default-src 'none'; frame-ancestors 'none'"),
+                ),
+            ];
+            if https {
+                headers.push((
+                    STRICT_TRANSPORT_SECURITY,
+                    HeaderValue::try_from(format!("max-age={}", self.hsts_max_age_secs))
+                        .expect("numbers are valid header values"),
+                ));
+            }
+            for (name, value) in headers {
+                router = router.layer(SetResponseHeaderLayer::if_not_present(name, value));
+            }
+        }
+        if let Some(cors) = &self.cors {
+            router = router.layer(cors.layer()?);
+        }
+        Ok(router)
+    }
+}
+
+/// Cross-origin settings, read from the `[http.cors]` section of the configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CorsConfig {
+    /// Origins allowed to call the API, e.g. `https://wallet.example.com`, or `["*"]` for any.
+    pub allowed_origins: Vec<String>,
+    #[serde(default = "default_allowed_methods")]
+    pub allowed_methods: Vec<String>,
+    /// Request headers allowed besides `Accept`, `Authorization` and `Content-Type`.
+    #[serde(default)]
+    pub allowed_headers: Vec<String>,
+    /// How long browsers may cache the answer to a preflight request.
+    #[serde(default = "default_max_age_secs")]
+    pub max_age_secs: u64,
+}
+
+fn default_allowed_methods() -> Vec<String> {
+    vec!["GET".into(), "POST".into()]
+}
+
+fn default_max_age_secs() -> u64 {
+    3600
+}
+
+impl CorsConfig {
+    /// Layer answering preflight requests and adding the CORS headers to responses.
+    pub fn layer(&self) -> Result<CorsLayer, FaucetError> {
+        let allow_origin = match self.allowed_origins.as_slice() {
+            [] => {
+                return Err(FaucetError::Config(
+                    "http.cors.allowed_origins must not be empty".into(),
+                ))
+            }
+            [any] if any == "*" => AllowOrigin::any(),
+            origins => AllowOrigin::list(
+                origins
+                    .iter()
+                    .map(|origin| parse_origin(origin.trim()))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+        };
+        let methods = self
+            .allowed_methods
+            .iter()
+            .map(|method| {
+                Method::from_bytes(method.trim().to_ascii_uppercase().as_bytes()).map_err(|_| {
+                    FaucetError::Config(format!(
+                        "http.cors.allowed_methods entry `{method}` is not an HTTP method"
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut headers = vec![ACCEPT, AUTHORIZATION, CONTENT_TYPE];
+        for header in &self.allowed_headers {
+            headers.push(HeaderName::try_from(header.trim()).map_err(|_| {
+                FaucetError::Config(format!(
+                    "http.cors.allowed_headers entry `{header}` is not a header name"
+                ))
+            })?);
+        }
+        Ok(CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .max_age(Duration::from_secs(self.max_age_secs)))
+    }
+}
+
+/// Parses an origin as browsers send it: scheme, host and optional port, without a path.
+fn parse_origin(origin: &str) -> Result<HeaderValue, FaucetError> {
+    let invalid = || {
+        FaucetError::Config(format!(
+            "http.cors.allowed_origins entry `{origin}` is not an origin like \
+             `https://wallet.example.com`"
+        ))
+    };
+    let (scheme, host) = origin.split_once("://").ok_or_else(invalid)?;
+    if !matches!(scheme, "http" | "https") || host.is_empty() || host.contains('/') {
+        return Err(invalid());
+    }
+    HeaderValue::try_from(origin).map_err(|_| invalid())
+}
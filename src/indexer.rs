@@ -0,0 +1,98 @@
+//! Claim indexer.
+//!
+//! Watches the nullifiers of the P2ID notes recorded in the [`Ledger`] and marks a mint as
+//! claimed once its note is consumed on chain. Claims are attributed to the campaign and source
+//! decoded from the aux value of the note, see [`AuxData`](crate::mint::AuxData).
+
+use miden_objects::block::BlockNumber;
+
+use crate::{
+    ledger::{Ledger, MintRecord},
+    node::FaucetNode,
+    watcher::{BlockWatcher, SharedNode},
+    webhook::ClaimWebhook,
+    FaucetError,
+};
+
+/// Checks the unclaimed mints of the ledger against the chain once and returns the mints that
+/// were newly marked as claimed.
+///
+/// Blocks before `scanned_to` are assumed to have been checked by an earlier call.
+pub async fn index_claims<N: FaucetNode>(
+    node: &mut N,
+    ledger: &Ledger,
+    scanned_to: Option<BlockNumber>,
+) -> Result<Vec<MintRecord>, FaucetError> {
+    let unclaimed = ledger.unclaimed_mints()?;
+    let Some(oldest_commit) = unclaimed.iter().filter_map(|mint| mint.commit_block).min() else {
+        return Ok(Vec::new());
+    };
+    let from_block = scanned_to.map_or(oldest_commit, |block| block.as_u32().max(oldest_commit));
+
+    let mut prefixes: Vec<u16> = unclaimed.iter().map(|mint| mint.nullifier_prefix).collect();
+    prefixes.sort_unstable();
+    prefixes.dedup();
+
+    let mut claimed = Vec::new();
+    for (nullifier, block_num) in node
+        .consumed_nullifiers(&prefixes, BlockNumber::from(from_block))
+        .await?
+    {
+        if let Some(mint) = ledger.mark_claimed(&nullifier.to_hex(), block_num)? {
+            match mint.aux_data() {
+                Some(aux) if aux.campaign_id != 0 => println!(
+                    "Mint {} of campaign {} claimed by {} at block {block_num}",
+                    mint.id, aux.campaign_id, mint.recipient
+                ),
+                _ => println!(
+                    "Mint {} claimed by {} at block {block_num}",
+                    mint.id, mint.recipient
+                ),
+            }
+            claimed.push(mint);
+        }
+    }
+
+    Ok(claimed)
+}
+
+/// Runs [`index_claims`] every time the watcher reports a new block, notifying `webhook` of each
+/// claim.
+///
+/// Transient failures are logged and retried on the next block; other errors stop the indexer.
+/// Webhook delivery failures are logged and never stop indexing.
+pub async fn run_indexer<N: FaucetNode>(
+    node: &SharedNode<N>,
+    watcher: &BlockWatcher,
+    ledger: &Ledger,
+    webhook: Option<&ClaimWebhook>,
+) -> Result<(), FaucetError> {
+    let mut tip = watcher.subscribe();
+    let mut scanned_to = None;
+
+    loop {
+        let current_tip = watcher.tip().map(|tip| tip.block_num);
+        let result = index_claims(&mut *node.lock().await, ledger, scanned_to).await;
+        match result {
+            Ok(claimed) => {
+                scanned_to = current_tip.or(scanned_to);
+                if let Some(webhook) = webhook {
+                    for mint in &claimed {
+                        if let Err(err) = webhook.notify(mint).await {
+                            eprintln!(
+                                "Failed to deliver claim webhook for mint {}: {err}",
+                                mint.id
+                            );
+                        }
+                    }
+                }
+            }
+            Err(err) if err.is_transient() => eprintln!("Claim indexing failed, retrying: {err}"),
+            Err(err) => return Err(err),
+        }
+
+        tip.changed()
+            .await
+            .map_err(|_| FaucetError::WatcherStopped)?;
+    }
+}
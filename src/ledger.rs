@@ -0,0 +1,2535 @@
+//! Mint ledger.
+//!
+//! Every mint submitted by the faucet is recorded together with the P2ID note it produces, so its
+//! lifecycle (submitted, committed, claimed by the recipient) can be tracked and reported. Burns
+//! sending tokens back to a faucet and deposits returned to its owner, see [`crate::returns`], are
+//! recorded alongside, so the stats cover the net supply. The
+//! ledger also keeps the labels users attach to accounts, see [`crate::account`], the default
+//! account, the wallets removed from the wallet list, the recurring mints of
+//! [`crate::schedule`], the campaigns of [`crate::campaign`], the referral codes and conversions
+//! of [`crate::referral`], the access lists of [`crate::access`], the audit log of [`crate::audit`],
+//! the account state snapshots of [`crate::history`], the GitHub sessions and drips of
+//! [`crate::github`], the tokens of the faucets deployed by `deploy` and the progress of the
+//! resumable flows of [`crate::flow`].
+//!
+//! The version of the schema is kept in `PRAGMA user_version`. [`Ledger::open`] upgrades older
+//! ledgers in place by running the pending [`Migration`]s, and refuses ledgers written by newer
+//! versions of the crate; the `migrate` command runs the upgrade ahead of time, after a backup.
+
+use std::{
+    fmt,
+    path::Path,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use miden_client::{
+    account::AccountId,
+    note::{Note, NoteTag, NoteType},
+    transaction::TransactionId,
+    Felt, Word,
+};
+use miden_objects::{block::BlockNumber, note::NoteExecutionMode};
+use rusqlite::{
+    params,
+    types::{FromSql, FromSqlError, FromSqlResult, ValueRef},
+    Connection, OptionalExtension, Transaction, TransactionBehavior,
+};
+use serde::Serialize;
+
+use crate::{
+    abuse::{AbuseRequest, RequestKey},
+    access::AccessList,
+    audit::{genesis_hash, AuditEntry, AuditRecord},
+    deploy::TokenConfig,
+    github::GithubUser,
+    mint::{reclaim_height, unlock_height, AuxData, MINT_NOTE_AUX},
+    node::TransactionCost,
+    schedule::CatchUp,
+    FaucetError,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS mints (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    faucet_id TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    transaction_id TEXT NOT NULL,
+    note_id TEXT NOT NULL,
+    nullifier TEXT NOT NULL,
+    nullifier_prefix INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    error TEXT,
+    created_at INTEGER NOT NULL,
+    commit_block INTEGER,
+    claim_block INTEGER,
+    serial_num TEXT,
+    reclaim_block INTEGER,
+    reclaim_transaction_id TEXT,
+    reclaimed_block INTEGER,
+    unlock_block INTEGER,
+    replaced_by INTEGER,
+    public_note INTEGER,
+    aux INTEGER,
+    note_tag INTEGER,
+    queued_at INTEGER
+);
+CREATE INDEX IF NOT EXISTS mints_unclaimed ON mints (status, claim_block);
+CREATE INDEX IF NOT EXISTS mints_by_transaction ON mints (transaction_id);
+CREATE TABLE IF NOT EXISTS burns (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    faucet_id TEXT NOT NULL,
+    account_id TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    transaction_id TEXT NOT NULL UNIQUE,
+    note_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    error TEXT,
+    created_at INTEGER NOT NULL,
+    commit_block INTEGER
+);
+CREATE TABLE IF NOT EXISTS returns (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    faucet_id TEXT NOT NULL,
+    owner_id TEXT NOT NULL,
+    sender TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    note_id TEXT NOT NULL UNIQUE,
+    transaction_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    error TEXT,
+    created_at INTEGER NOT NULL,
+    commit_block INTEGER
+);
+CREATE TABLE IF NOT EXISTS transaction_costs (
+    transaction_id TEXT PRIMARY KEY,
+    faucet_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    fee INTEGER NOT NULL,
+    cycles INTEGER NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS account_labels (
+    label TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS schedules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    faucet_id TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    interval_secs INTEGER NOT NULL,
+    catch_up TEXT NOT NULL,
+    next_run INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    last_mint_id INTEGER,
+    last_error TEXT
+);
+CREATE TABLE IF NOT EXISTS campaigns (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    faucet_id TEXT NOT NULL,
+    budget INTEGER NOT NULL,
+    per_user_limit INTEGER,
+    starts_at INTEGER,
+    ends_at INTEGER,
+    created_at INTEGER NOT NULL,
+    closed_at INTEGER
+);
+CREATE TABLE IF NOT EXISTS referral_codes (
+    code TEXT PRIMARY KEY,
+    referrer TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    disabled_at INTEGER
+);
+CREATE TABLE IF NOT EXISTS referrals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    code TEXT NOT NULL,
+    referee TEXT NOT NULL UNIQUE,
+    mint_id INTEGER NOT NULL,
+    bonus_mint_id INTEGER,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS access_lists (
+    list TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    note TEXT,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (list, subject)
+);
+CREATE TABLE IF NOT EXISTS limit_overrides (
+    name TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS removed_wallets (
+    account_id TEXT PRIMARY KEY,
+    removed_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS deployments (
+    faucet_id TEXT PRIMARY KEY,
+    owner_id TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    decimals INTEGER NOT NULL,
+    max_supply INTEGER NOT NULL,
+    transaction_id TEXT NOT NULL,
+    deployed_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS retired_deployments (
+    faucet_id TEXT PRIMARY KEY,
+    retired_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS watched_accounts (
+    account_id TEXT PRIMARY KEY,
+    watched_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY,
+    created_at INTEGER NOT NULL,
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,
+    account TEXT,
+    params TEXT NOT NULL,
+    transaction_id TEXT,
+    prev_hash TEXT NOT NULL UNIQUE,
+    hash TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS account_states (
+    account_id TEXT NOT NULL,
+    block_num INTEGER NOT NULL,
+    commitment TEXT NOT NULL,
+    nonce INTEGER NOT NULL,
+    state BLOB NOT NULL,
+    PRIMARY KEY (account_id, block_num)
+);
+CREATE TABLE IF NOT EXISTS github_sessions (
+    token_hash TEXT PRIMARY KEY,
+    github_id INTEGER NOT NULL,
+    login TEXT NOT NULL,
+    expires_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS github_drips (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    github_id INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    mint_id INTEGER
+);
+CREATE INDEX IF NOT EXISTS github_drips_by_user ON github_drips (github_id, created_at);
+CREATE TABLE IF NOT EXISTS abuse_requests (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    identity TEXT,
+    ip TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS abuse_requests_by_time ON abuse_requests (created_at);
+CREATE TABLE IF NOT EXISTS flows (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    step TEXT NOT NULL,
+    state TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE TRIGGER IF NOT EXISTS audit_log_no_update BEFORE UPDATE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'the audit log is append-only');
+END;
+CREATE TRIGGER IF NOT EXISTS audit_log_no_delete BEFORE DELETE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'the audit log is append-only');
+END;
+";
+
+/// Settings key of the account used when a command is given none.
+const DEFAULT_ACCOUNT_KEY: &str = "default_account";
+
+/// Schema version of the ledgers written by this build, see [`MIGRATIONS`].
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// Upgrades of the schema, oldest first. Each brings a ledger of the previous version to
+/// `version`; ledgers from before versions were tracked are at version 0.
+///
+/// Tables and indexes added since are created by `SCHEMA` itself, so migrations only have to
+/// change existing tables. They must be idempotent: ledgers created at version 0 may already
+/// have been upgraded by the code preceding the migrations.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "add the mint columns of later releases and allow batch mints sharing a \
+                      transaction",
+        apply: migrate_mints,
+    },
+    Migration {
+        version: 2,
+        description: "record the tag of minted notes",
+        apply: migrate_note_tags,
+    },
+    Migration {
+        version: 3,
+        description: "record when mints entered the mint queue",
+        apply: migrate_queue_times,
+    },
+];
+
+/// Upgrade of the ledger schema, see [`Ledger::pending_migrations`].
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// Schema version the migration upgrades to.
+    pub version: u32,
+    pub description: &'static str,
+    apply: fn(&Connection) -> Result<(), FaucetError>,
+}
+
+/// Columns added after the first release of the schema, created on open when missing.
+const ADDED_COLUMNS: &[(&str, &str)] = &[
+    ("serial_num", "TEXT"),
+    ("reclaim_block", "INTEGER"),
+    ("reclaim_transaction_id", "TEXT"),
+    ("reclaimed_block", "INTEGER"),
+    ("unlock_block", "INTEGER"),
+    ("replaced_by", "INTEGER"),
+    ("public_note", "INTEGER"),
+    ("aux", "INTEGER"),
+];
+
+/// Lifecycle state of a recorded mint or burn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MintStatus {
+    Submitted,
+    Committed,
+    Failed,
+    /// The mint was committed, but its transaction was dropped from the chain by a reorg.
+    Invalidated,
+}
+
+impl MintStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Submitted => "submitted",
+            Self::Committed => "committed",
+            Self::Failed => "failed",
+            Self::Invalidated => "invalidated",
+        }
+    }
+}
+
+impl fmt::Display for MintStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MintStatus {
+    type Err = FaucetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "submitted" => Ok(Self::Submitted),
+            "committed" => Ok(Self::Committed),
+            "failed" => Ok(Self::Failed),
+            "invalidated" => Ok(Self::Invalidated),
+            other => Err(FaucetError::Ledger(format!(
+                "unknown mint status `{other}`"
+            ))),
+        }
+    }
+}
+
+impl FromSql for MintStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|err| FromSqlError::Other(Box::new(err)))
+    }
+}
+
+/// A row of the mint ledger.
+#[derive(Debug, Clone)]
+pub struct MintRecord {
+    pub id: i64,
+    pub faucet_id: String,
+    pub recipient: String,
+    pub amount: u64,
+    pub transaction_id: String,
+    pub note_id: String,
+    pub nullifier: String,
+    pub nullifier_prefix: u16,
+    pub status: MintStatus,
+    pub error: Option<String>,
+    pub created_at: u64,
+    pub commit_block: Option<u32>,
+    pub claim_block: Option<u32>,
+    /// Serial number of the note, unknown for mints recorded before it was stored.
+    pub serial_num: Option<String>,
+    /// Block from which the faucet can reclaim the note if it is a P2IDE note.
+    pub reclaim_block: Option<u32>,
+    pub reclaim_transaction_id: Option<String>,
+    /// Block in which the faucet reclaimed the unclaimed note.
+    pub reclaimed_block: Option<u32>,
+    /// Block from which the recipient can consume the note if it is timelocked.
+    pub unlock_block: Option<u32>,
+    /// Mint re-submitting the note of this one after it was invalidated.
+    pub replaced_by: Option<i64>,
+    /// Whether the note is public, so the recipient finds it through sync; private notes are
+    /// delivered as note files.
+    pub public_note: bool,
+    /// Aux value of the note, see [`AuxData`].
+    pub aux: u64,
+    /// Tag of the note, `None` for notes tagged for their recipient before tags were stored.
+    pub note_tag: Option<u32>,
+    /// Unix timestamp at which the request entered the mint queue of the service, `None` for
+    /// mints that did not go through it or were recorded before queue times were stored.
+    pub queued_at: Option<u64>,
+}
+
+impl MintRecord {
+    pub fn note_type(&self) -> NoteType {
+        if self.public_note {
+            NoteType::Public
+        } else {
+            NoteType::Private
+        }
+    }
+
+    /// Campaign and source of the mint, or `None` if the aux value was not written by the faucet.
+    pub fn aux_data(&self) -> Option<AuxData> {
+        AuxData::from_felt(Felt::new(self.aux))
+    }
+
+    /// Whether the note carries a network-execution tag, so the network rather than the
+    /// recipient consumes it, see [`crate::execution`].
+    pub fn network_executed(&self) -> bool {
+        self.note_tag
+            .is_some_and(|tag| NoteTag::from(tag).execution_mode() == NoteExecutionMode::Network)
+    }
+}
+
+/// A faucet deployed by `deploy`, with the token it was created with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeploymentRecord {
+    pub faucet_id: String,
+    pub owner_id: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub max_supply: u64,
+    pub transaction_id: String,
+    pub deployed_at: u64,
+    /// When `faucet decommission` retired the faucet, `None` while it is in service.
+    pub retired_at: Option<u64>,
+}
+
+/// A row of the burn ledger.
+#[derive(Debug, Clone)]
+pub struct BurnRecord {
+    pub id: i64,
+    pub faucet_id: String,
+    /// Wallet the burned tokens were sent from.
+    pub account_id: String,
+    pub amount: u64,
+    pub transaction_id: String,
+    /// ID of the burn note the faucet consumes.
+    pub note_id: String,
+    pub status: MintStatus,
+    pub error: Option<String>,
+    pub created_at: u64,
+    pub commit_block: Option<u32>,
+}
+
+/// A deposit of faucet tokens collected by the faucet owner, see [`crate::returns`].
+#[derive(Debug, Clone)]
+pub struct ReturnRecord {
+    pub id: i64,
+    pub faucet_id: String,
+    /// Owner wallet that consumed the deposit.
+    pub owner_id: String,
+    /// Account that sent the deposit.
+    pub sender: String,
+    pub amount: u64,
+    pub note_id: String,
+    /// Transaction consuming the deposit into the owner wallet.
+    pub transaction_id: String,
+    pub status: MintStatus,
+    pub error: Option<String>,
+    pub created_at: u64,
+    pub commit_block: Option<u32>,
+}
+
+/// A recurring mint, see [`crate::schedule`].
+#[derive(Debug, Clone)]
+pub struct ScheduleRecord {
+    pub id: i64,
+    pub faucet_id: AccountId,
+    pub recipient: AccountId,
+    pub amount: u64,
+    pub interval: Duration,
+    pub catch_up: CatchUp,
+    /// Unix time of the next run.
+    pub next_run: u64,
+    pub created_at: u64,
+    /// Ledger ID of the latest mint of the schedule.
+    pub last_mint_id: Option<i64>,
+    /// Error of the latest run, cleared by the next successful one.
+    pub last_error: Option<String>,
+}
+
+/// A resumable flow, see [`crate::flow`].
+#[derive(Debug, Clone)]
+pub struct FlowRecord {
+    pub id: i64,
+    /// `deploy` or `mint`.
+    pub kind: String,
+    /// Next step of the flow, `done` once it finished.
+    pub step: String,
+    /// JSON state of the flow, read by [`crate::flow::Flow::from_record`].
+    pub state: String,
+    /// Failed attempts of the current step.
+    pub attempts: u32,
+    /// Error of the latest failed attempt, cleared once the step succeeds.
+    pub last_error: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// A distribution campaign, see [`crate::campaign`].
+#[derive(Debug, Clone)]
+pub struct CampaignRecord {
+    /// ID written to the aux value of the notes minted for the campaign.
+    pub id: u32,
+    pub name: String,
+    pub faucet_id: AccountId,
+    /// Tokens the campaign may mint in total.
+    pub budget: u64,
+    /// Tokens a single recipient may receive from the campaign.
+    pub per_user_limit: Option<u64>,
+    /// Unix time from which the campaign mints, right away when unset.
+    pub starts_at: Option<u64>,
+    /// Unix time from which the campaign no longer mints, never when unset.
+    pub ends_at: Option<u64>,
+    pub created_at: u64,
+    /// Unix time the campaign was closed at.
+    pub closed_at: Option<u64>,
+}
+
+/// Mints of one campaign, see [`Ledger::campaign_stats`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CampaignStats {
+    pub id: u32,
+    pub name: String,
+    pub budget: u64,
+    /// Mints submitted for the campaign, whatever their outcome.
+    pub mints: u64,
+    pub unique_recipients: u64,
+    /// Tokens of the submitted and committed mints, which count against the budget.
+    pub spent_amount: u64,
+    pub minted_amount: u64,
+    pub delivered_amount: u64,
+}
+
+/// Entry of an [`AccessList`]: a recipient account ID or a requester identity.
+#[derive(Debug, Clone)]
+pub struct ListEntry {
+    pub list: AccessList,
+    pub subject: String,
+    /// Why the subject was listed, as given by the operator.
+    pub note: Option<String>,
+    pub created_at: u64,
+}
+
+/// A referral code, see [`crate::referral`].
+#[derive(Debug, Clone)]
+pub struct ReferralCodeRecord {
+    pub code: String,
+    /// Account credited with the bonuses of the code.
+    pub referrer: AccountId,
+    pub created_at: u64,
+    pub disabled_at: Option<u64>,
+}
+
+/// Conversions of one referral code, see [`Ledger::referral_stats`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReferralStats {
+    pub code: String,
+    pub referrer: String,
+    pub disabled: bool,
+    /// Mints requested with the code.
+    pub conversions: u64,
+    /// Bonus mints the referrer received for them.
+    pub bonuses: u64,
+    pub bonus_amount: u64,
+}
+
+/// Snapshot of an account state, see [`crate::history`].
+#[derive(Debug, Clone)]
+pub struct AccountStateRecord {
+    pub account_id: String,
+    /// Block at which the state was recorded.
+    pub block_num: u32,
+    pub commitment: String,
+    pub nonce: u64,
+    /// The serialized account.
+    pub state: Vec<u8>,
+}
+
+/// Aggregated ledger figures reported by `faucet stats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MintStats {
+    pub submitted: u64,
+    pub committed: u64,
+    pub failed: u64,
+    /// Committed mints whose P2ID note was consumed by the recipient.
+    pub delivered: u64,
+    /// Committed mints whose P2ID note has not been consumed yet.
+    pub unclaimed: u64,
+    pub minted_amount: u64,
+    pub delivered_amount: u64,
+    /// Committed mints whose note was reclaimed by the faucet after expiring unclaimed.
+    pub reclaimed: u64,
+    pub reclaimed_amount: u64,
+    /// Committed burns sending tokens back to the faucet.
+    pub burned: u64,
+    pub burned_amount: u64,
+    /// Deposits collected by the faucet owner.
+    pub returned: u64,
+    pub returned_amount: u64,
+    /// Fees of the recorded mint and deploy transactions, see [`Ledger::record_cost`].
+    pub fees_paid: u64,
+    /// VM cycles of the recorded mint and deploy transactions.
+    pub cycles_spent: u64,
+}
+
+/// Length of the time buckets of [`Ledger::bucketed_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsGranularity {
+    Hour,
+    Day,
+}
+
+impl StatsGranularity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hour => "hour",
+            Self::Day => "day",
+        }
+    }
+
+    /// Length of a bucket in seconds.
+    pub fn secs(&self) -> u64 {
+        match self {
+            Self::Hour => 3600,
+            Self::Day => 86_400,
+        }
+    }
+
+    /// SQLite `strftime` format of the bucket labels.
+    fn label_format(&self) -> &'static str {
+        match self {
+            Self::Hour => "%Y-%m-%d %H:00",
+            Self::Day => "%Y-%m-%d",
+        }
+    }
+}
+
+impl fmt::Display for StatsGranularity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for StatsGranularity {
+    type Err = FaucetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hour" => Ok(Self::Hour),
+            "day" => Ok(Self::Day),
+            other => Err(FaucetError::Config(format!(
+                "invalid granularity `{other}`, expected `hour` or `day`"
+            ))),
+        }
+    }
+}
+
+/// Mints submitted within one time bucket, see [`Ledger::bucketed_stats`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MintBucket {
+    /// Start of the bucket in seconds since the Unix epoch.
+    pub start: u64,
+    /// Start of the bucket in UTC, e.g. `2025-06-01` or `2025-06-01 14:00`.
+    pub label: String,
+    pub mints: u64,
+    pub unique_recipients: u64,
+    /// Tokens requested by the mints of the bucket, whatever their outcome.
+    pub amount: u64,
+    pub committed: u64,
+    pub failed: u64,
+    /// Share of the mints of the bucket that failed, from 0 to 1.
+    pub failure_rate: f64,
+}
+
+/// Committed mints of one recipient, see [`Ledger::recipient_stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecipientStats {
+    pub mints: u64,
+    pub minted_amount: u64,
+    /// Mints whose note the recipient consumed.
+    pub claimed: u64,
+    pub claimed_amount: u64,
+    pub last_claim_block: Option<u32>,
+}
+
+/// SQLite-backed mint ledger.
+pub struct Ledger {
+    conn: Connection,
+}
+
+impl Ledger {
+    /// Opens (and if needed creates) the ledger database at `path`, upgrading its schema to
+    /// [`SCHEMA_VERSION`].
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, FaucetError> {
+        let ledger = Self::open_unmigrated(path)?;
+        ledger.migrate()?;
+        Ok(ledger)
+    }
+
+    /// Opens the ledger database at `path` without upgrading its schema, e.g. to back it up
+    /// first. Only [`schema_version`](Self::schema_version), [`backup`](Self::backup) and
+    /// [`migrate`](Self::migrate) are safe to call on an older ledger.
+    ///
+    /// Fails with [`FaucetError::LedgerVersion`] for ledgers of a newer schema version.
+    pub fn open_unmigrated(path: impl AsRef<Path>) -> Result<Self, FaucetError> {
+        let ledger = Self {
+            conn: Connection::open(path)?,
+        };
+        let version = ledger.schema_version()?;
+        if version > SCHEMA_VERSION {
+            return Err(FaucetError::LedgerVersion {
+                found: version,
+                supported: SCHEMA_VERSION,
+            });
+        }
+        Ok(ledger)
+    }
+
+    /// Schema version of the ledger, 0 for ledgers from before versions were tracked.
+    pub fn schema_version(&self) -> Result<u32, FaucetError> {
+        Ok(self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?)
+    }
+
+    /// Migrations [`migrate`](Self::migrate) would run, oldest first.
+    pub fn pending_migrations(&self) -> Result<Vec<Migration>, FaucetError> {
+        let version = self.schema_version()?;
+        Ok(MIGRATIONS
+            .iter()
+            .filter(|migration| migration.version > version)
+            .copied()
+            .collect())
+    }
+
+    /// Upgrades the schema to [`SCHEMA_VERSION`] and returns the migrations it ran.
+    ///
+    /// The version is stored after every migration, so an interrupted upgrade resumes with the
+    /// migration that failed.
+    pub fn migrate(&self) -> Result<Vec<Migration>, FaucetError> {
+        let pending = self.pending_migrations()?;
+        self.conn.execute_batch(SCHEMA)?;
+        for migration in &pending {
+            (migration.apply)(&self.conn)?;
+            self.conn
+                .pragma_update(None, "user_version", migration.version)?;
+        }
+        Ok(pending)
+    }
+
+    /// Writes a consistent copy of the ledger to `path`, which must not exist yet.
+    pub fn backup(&self, path: impl AsRef<Path>) -> Result<(), FaucetError> {
+        let path = path.as_ref().to_string_lossy();
+        self.conn.execute("VACUUM INTO ?1", [path.as_ref()])?;
+        Ok(())
+    }
+
+    /// Records a freshly submitted mint and returns its ledger ID.
+    pub fn record_mint(
+        &self,
+        faucet_id: AccountId,
+        recipient: AccountId,
+        amount: u64,
+        transaction_id: TransactionId,
+        p2id_note: &Note,
+    ) -> Result<i64, FaucetError> {
+        let nullifier = p2id_note.nullifier();
+        let reclaim_block = reclaim_height(p2id_note).map(|height| height.as_u32());
+        let unlock_block = unlock_height(p2id_note).map(|height| height.as_u32());
+        self.conn.execute(
+            "INSERT INTO mints (faucet_id, recipient, amount, transaction_id, note_id, nullifier,
+                nullifier_prefix, status, created_at, serial_num, reclaim_block, unlock_block,
+                public_note, aux, note_tag)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            params![
+                faucet_id.to_hex(),
+                recipient.to_hex(),
+                amount,
+                transaction_id.to_hex(),
+                p2id_note.id().to_hex(),
+                nullifier.to_hex(),
+                nullifier.prefix(),
+                MintStatus::Submitted.as_str(),
+                unix_now(),
+                p2id_note.recipient().serial_num().to_hex(),
+                reclaim_block,
+                unlock_block,
+                p2id_note.metadata().note_type() == NoteType::Public,
+                p2id_note.metadata().aux().as_int(),
+                p2id_note.metadata().tag().as_u32(),
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Records that mint `mint_id` entered the mint queue at Unix timestamp `queued_at`.
+    pub fn set_queued_at(&self, mint_id: i64, queued_at: u64) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE mints SET queued_at = ?1 WHERE id = ?2",
+            params![queued_at, mint_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn mark_committed(
+        &self,
+        transaction_id: TransactionId,
+        block_num: BlockNumber,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE mints SET status = ?1, commit_block = ?2 WHERE transaction_id = ?3",
+            params![
+                MintStatus::Committed.as_str(),
+                block_num.as_u32(),
+                transaction_id.to_hex()
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn mark_failed(
+        &self,
+        transaction_id: TransactionId,
+        error: &str,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE mints SET status = ?1, error = ?2 WHERE transaction_id = ?3",
+            params![MintStatus::Failed.as_str(), error, transaction_id.to_hex()],
+        )?;
+        Ok(())
+    }
+
+    /// Flags a committed mint whose transaction was dropped from the chain as invalidated.
+    pub fn mark_invalidated(
+        &self,
+        transaction_id: TransactionId,
+        reason: &str,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE mints SET status = ?1, error = ?2 WHERE transaction_id = ?3",
+            params![
+                MintStatus::Invalidated.as_str(),
+                reason,
+                transaction_id.to_hex()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Moves a committed mint whose transaction went back to pending back to submitted.
+    pub fn mark_reverted(
+        &self,
+        transaction_id: TransactionId,
+        reason: &str,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE mints SET status = ?1, error = ?2, commit_block = NULL
+             WHERE transaction_id = ?3",
+            params![
+                MintStatus::Submitted.as_str(),
+                reason,
+                transaction_id.to_hex()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Records that mint `replaced_by` re-submits the note of the invalidated mint `id`.
+    pub fn set_replacement(&self, id: i64, replaced_by: i64) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE mints SET replaced_by = ?1 WHERE id = ?2",
+            params![replaced_by, id],
+        )?;
+        Ok(())
+    }
+
+    /// Mints of `faucet_id` committed at or after `block_num`.
+    pub fn committed_mints_since(
+        &self,
+        faucet_id: AccountId,
+        block_num: BlockNumber,
+    ) -> Result<Vec<MintRecord>, FaucetError> {
+        self.query_mints(
+            "WHERE faucet_id = ?1 AND status = 'committed' AND commit_block >= ?2
+             ORDER BY commit_block",
+            params![faucet_id.to_hex(), block_num.as_u32()],
+        )
+    }
+
+    /// Invalidated mints of `faucet_id` that were not re-submitted yet.
+    pub fn unreplaced_mints(&self, faucet_id: AccountId) -> Result<Vec<MintRecord>, FaucetError> {
+        self.query_mints(
+            "WHERE faucet_id = ?1 AND status = 'invalidated' AND replaced_by IS NULL ORDER BY id",
+            [faucet_id.to_hex()],
+        )
+    }
+
+    /// Mints submitted to the node whose transaction has not been committed or failed yet.
+    pub fn submitted_mints(&self) -> Result<Vec<MintRecord>, FaucetError> {
+        self.query_mints("WHERE status = 'submitted' ORDER BY id", [])
+    }
+
+    /// Creation time and amount of the mints of `faucet_id` created after `since` that were not
+    /// failed or invalidated, oldest first.
+    pub fn distributed_since(
+        &self,
+        faucet_id: AccountId,
+        since: u64,
+    ) -> Result<Vec<(u64, u64)>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT created_at, amount FROM mints
+             WHERE faucet_id = ?1 AND status IN ('submitted', 'committed') AND created_at > ?2
+             ORDER BY created_at, id",
+        )?;
+        let rows = stmt.query_map(params![faucet_id.to_hex(), since], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Records that the P2ID note with `nullifier` was consumed at `block_num`.
+    ///
+    /// Returns the updated mint, or `None` if no unclaimed mint has this nullifier.
+    pub fn mark_claimed(
+        &self,
+        nullifier: &str,
+        block_num: BlockNumber,
+    ) -> Result<Option<MintRecord>, FaucetError> {
+        let updated = self.conn.execute(
+            "UPDATE mints SET claim_block = ?1
+             WHERE nullifier = ?2 AND claim_block IS NULL AND reclaimed_block IS NULL",
+            params![block_num.as_u32(), nullifier],
+        )?;
+        if updated == 0 {
+            return Ok(None);
+        }
+        Ok(self.query_mints("WHERE nullifier = ?1", [nullifier])?.pop())
+    }
+
+    /// Committed mints whose P2ID note has not been seen consumed.
+    pub fn unclaimed_mints(&self) -> Result<Vec<MintRecord>, FaucetError> {
+        self.query_mints(
+            "WHERE status = 'committed' AND claim_block IS NULL AND reclaimed_block IS NULL
+             ORDER BY commit_block",
+            [],
+        )
+    }
+
+    /// Unclaimed mints of `faucet_id` whose P2IDE note is reclaimable at `block_num`.
+    pub fn expired_mints(
+        &self,
+        faucet_id: AccountId,
+        block_num: BlockNumber,
+    ) -> Result<Vec<MintRecord>, FaucetError> {
+        self.query_mints(
+            "WHERE faucet_id = ?1 AND status = 'committed' AND claim_block IS NULL
+                AND reclaimed_block IS NULL AND serial_num IS NOT NULL AND reclaim_block <= ?2
+             ORDER BY reclaim_block",
+            params![faucet_id.to_hex(), block_num.as_u32()],
+        )
+    }
+
+    /// Records that the faucet reclaimed the note of mint `id` in `block_num`.
+    ///
+    /// Reclaiming consumes the note, so a claim the indexer recorded from that consumption is
+    /// cleared.
+    pub fn mark_reclaimed(
+        &self,
+        id: i64,
+        transaction_id: TransactionId,
+        block_num: BlockNumber,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE mints SET reclaim_transaction_id = ?1, reclaimed_block = ?2, claim_block = NULL
+             WHERE id = ?3",
+            params![transaction_id.to_hex(), block_num.as_u32(), id],
+        )?;
+        Ok(())
+    }
+
+    /// Records the measured cost of a `kind` transaction of `faucet_id`, e.g. a `mint` or `deploy`.
+    pub fn record_cost(
+        &self,
+        faucet_id: AccountId,
+        kind: &str,
+        transaction_id: TransactionId,
+        cost: TransactionCost,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO transaction_costs (transaction_id, faucet_id, kind, fee, cycles,
+                created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                transaction_id.to_hex(),
+                faucet_id.to_hex(),
+                kind,
+                cost.fee,
+                cost.cycles,
+                unix_now(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Records a freshly submitted burn and returns its ledger ID.
+    pub fn record_burn(
+        &self,
+        faucet_id: AccountId,
+        account_id: AccountId,
+        amount: u64,
+        transaction_id: TransactionId,
+        burn_note: &Note,
+    ) -> Result<i64, FaucetError> {
+        self.conn.execute(
+            "INSERT INTO burns (faucet_id, account_id, amount, transaction_id, note_id, status,
+                created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                faucet_id.to_hex(),
+                account_id.to_hex(),
+                amount,
+                transaction_id.to_hex(),
+                burn_note.id().to_hex(),
+                MintStatus::Submitted.as_str(),
+                unix_now(),
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn mark_burn_committed(
+        &self,
+        transaction_id: TransactionId,
+        block_num: BlockNumber,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE burns SET status = ?1, commit_block = ?2 WHERE transaction_id = ?3",
+            params![
+                MintStatus::Committed.as_str(),
+                block_num.as_u32(),
+                transaction_id.to_hex()
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn mark_burn_failed(
+        &self,
+        transaction_id: TransactionId,
+        error: &str,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE burns SET status = ?1, error = ?2 WHERE transaction_id = ?3",
+            params![MintStatus::Failed.as_str(), error, transaction_id.to_hex()],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_burn(&self, id: i64) -> Result<Option<BurnRecord>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, faucet_id, account_id, amount, transaction_id, note_id, status, error,
+                created_at, commit_block
+             FROM burns WHERE id = ?1",
+        )?;
+        let mut rows = stmt.query_map([id], |row| {
+            Ok(BurnRecord {
+                id: row.get(0)?,
+                faucet_id: row.get(1)?,
+                account_id: row.get(2)?,
+                amount: row.get(3)?,
+                transaction_id: row.get(4)?,
+                note_id: row.get(5)?,
+                status: row.get(6)?,
+                error: row.get(7)?,
+                created_at: row.get(8)?,
+                commit_block: row.get(9)?,
+            })
+        })?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Records that `owner_id` is collecting the deposit `note` of `amount` tokens in
+    /// `transaction_id` and returns its ledger ID.
+    ///
+    /// A deposit whose earlier collection failed is recorded again under its existing ID.
+    pub fn record_return(
+        &self,
+        faucet_id: AccountId,
+        owner_id: AccountId,
+        note: &Note,
+        amount: u64,
+        transaction_id: TransactionId,
+    ) -> Result<i64, FaucetError> {
+        let id = self.conn.query_row(
+            "INSERT INTO returns (faucet_id, owner_id, sender, amount, note_id, transaction_id,
+                status, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT (note_id) DO UPDATE SET transaction_id = excluded.transaction_id,
+                status = excluded.status, error = NULL
+             RETURNING id",
+            params![
+                faucet_id.to_hex(),
+                owner_id.to_hex(),
+                note.metadata().sender().to_hex(),
+                amount,
+                note.id().to_hex(),
+                transaction_id.to_hex(),
+                MintStatus::Submitted.as_str(),
+                unix_now(),
+            ],
+            |row| row.get(0),
+        )?;
+        Ok(id)
+    }
+
+    pub fn mark_returns_committed(
+        &self,
+        transaction_id: TransactionId,
+        block_num: BlockNumber,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE returns SET status = ?1, commit_block = ?2 WHERE transaction_id = ?3",
+            params![
+                MintStatus::Committed.as_str(),
+                block_num.as_u32(),
+                transaction_id.to_hex()
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn mark_returns_failed(
+        &self,
+        transaction_id: TransactionId,
+        error: &str,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE returns SET status = ?1, error = ?2 WHERE transaction_id = ?3",
+            params![MintStatus::Failed.as_str(), error, transaction_id.to_hex()],
+        )?;
+        Ok(())
+    }
+
+    /// Whether the deposit note `note_id` is collected or being collected.
+    pub fn is_collected_return(&self, note_id: &str) -> Result<bool, FaucetError> {
+        let collected = self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM returns WHERE note_id = ?1 AND status != 'failed')",
+            [note_id],
+            |row| row.get(0),
+        )?;
+        Ok(collected)
+    }
+
+    /// Most recent deposits first, optionally restricted to a faucet.
+    pub fn recent_returns(
+        &self,
+        faucet_id: Option<AccountId>,
+        limit: usize,
+    ) -> Result<Vec<ReturnRecord>, FaucetError> {
+        self.query_returns(
+            "WHERE ?1 IS NULL OR faucet_id = ?1 ORDER BY id DESC LIMIT ?2",
+            params![faucet_id.map(|id| id.to_hex()), limit as i64],
+        )
+    }
+
+    /// Deposits collected by `transaction_id`.
+    pub fn returns_of(
+        &self,
+        transaction_id: TransactionId,
+    ) -> Result<Vec<ReturnRecord>, FaucetError> {
+        self.query_returns(
+            "WHERE transaction_id = ?1 ORDER BY id",
+            [transaction_id.to_hex()],
+        )
+    }
+
+    fn query_returns(
+        &self,
+        filter: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<ReturnRecord>, FaucetError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, faucet_id, owner_id, sender, amount, note_id, transaction_id, status,
+                error, created_at, commit_block
+             FROM returns {filter}"
+        ))?;
+        let rows = stmt.query_map(params, |row| {
+            Ok(ReturnRecord {
+                id: row.get(0)?,
+                faucet_id: row.get(1)?,
+                owner_id: row.get(2)?,
+                sender: row.get(3)?,
+                amount: row.get(4)?,
+                note_id: row.get(5)?,
+                transaction_id: row.get(6)?,
+                status: row.get(7)?,
+                error: row.get(8)?,
+                created_at: row.get(9)?,
+                commit_block: row.get(10)?,
+            })
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Attaches `label` to `account_id`, moving it if it named another account.
+    pub fn set_label(&self, label: &str, account_id: AccountId) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "INSERT INTO account_labels (label, account_id) VALUES (?1, ?2)
+             ON CONFLICT (label) DO UPDATE SET account_id = excluded.account_id",
+            params![label, account_id.to_hex()],
+        )?;
+        Ok(())
+    }
+
+    /// Removes `label` and returns whether it existed.
+    pub fn remove_label(&self, label: &str) -> Result<bool, FaucetError> {
+        let removed = self
+            .conn
+            .execute("DELETE FROM account_labels WHERE label = ?1", [label])?;
+        Ok(removed > 0)
+    }
+
+    /// Returns the account named by `label`.
+    pub fn labeled_account(&self, label: &str) -> Result<Option<AccountId>, FaucetError> {
+        Ok(self
+            .query_labels("WHERE label = ?1", [label])?
+            .pop()
+            .map(|(_, id)| id))
+    }
+
+    /// All labels with their accounts, ordered by label.
+    pub fn labels(&self) -> Result<Vec<(String, AccountId)>, FaucetError> {
+        self.query_labels("ORDER BY label", [])
+    }
+
+    fn query_labels(
+        &self,
+        filter: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<(String, AccountId)>, FaucetError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT label, account_id FROM account_labels {filter}"
+        ))?;
+        let rows = stmt
+            .query_map(params, |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(|(label, id)| {
+                let id = AccountId::from_hex(&id).map_err(|err| {
+                    FaucetError::Ledger(format!("invalid account ID for label `{label}`: {err}"))
+                })?;
+                Ok((label, id))
+            })
+            .collect()
+    }
+
+    /// Makes `account_id` the account commands fall back to, or clears it with `None`.
+    pub fn set_default_account(&self, account_id: Option<AccountId>) -> Result<(), FaucetError> {
+        match account_id {
+            Some(id) => self.conn.execute(
+                "INSERT INTO settings (key, value) VALUES (?1, ?2)
+                 ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+                params![DEFAULT_ACCOUNT_KEY, id.to_hex()],
+            )?,
+            None => self
+                .conn
+                .execute("DELETE FROM settings WHERE key = ?1", [DEFAULT_ACCOUNT_KEY])?,
+        };
+        Ok(())
+    }
+
+    pub fn default_account(&self) -> Result<Option<AccountId>, FaucetError> {
+        let value: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = ?1",
+                [DEFAULT_ACCOUNT_KEY],
+                |row| row.get(0),
+            )
+            .optional()?;
+        value
+            .map(|id| {
+                AccountId::from_hex(&id).map_err(|err| {
+                    FaucetError::Ledger(format!("invalid default account ID: {err}"))
+                })
+            })
+            .transpose()
+    }
+
+    /// Hides `account_id` from the wallet list, drops its labels and unsets it as the default
+    /// account.
+    ///
+    /// The client store has no way to stop tracking an account, so the wallet and its key stay in
+    /// the store and keystore.
+    pub fn remove_wallet(&self, account_id: AccountId) -> Result<(), FaucetError> {
+        let id = account_id.to_hex();
+        self.conn.execute(
+            "INSERT OR IGNORE INTO removed_wallets (account_id, removed_at) VALUES (?1, ?2)",
+            params![id, unix_now()],
+        )?;
+        self.conn
+            .execute("DELETE FROM account_labels WHERE account_id = ?1", [&id])?;
+        self.conn.execute(
+            "DELETE FROM settings WHERE key = ?1 AND value = ?2",
+            params![DEFAULT_ACCOUNT_KEY, id],
+        )?;
+        Ok(())
+    }
+
+    /// Whether `account_id` was removed with [`Ledger::remove_wallet`].
+    pub fn is_removed_wallet(&self, account_id: AccountId) -> Result<bool, FaucetError> {
+        let removed = self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM removed_wallets WHERE account_id = ?1)",
+            [account_id.to_hex()],
+            |row| row.get(0),
+        )?;
+        Ok(removed)
+    }
+
+    /// Records `account_id` as tracked without its keys, see [`crate::watch`]. Returns whether it
+    /// was not watched yet.
+    pub fn add_watched_account(&self, account_id: AccountId) -> Result<bool, FaucetError> {
+        let added = self.conn.execute(
+            "INSERT OR IGNORE INTO watched_accounts (account_id, watched_at) VALUES (?1, ?2)",
+            params![account_id.to_hex(), unix_now()],
+        )?;
+        Ok(added > 0)
+    }
+
+    /// Stops reporting on a watched account. Returns whether it was watched.
+    pub fn remove_watched_account(&self, account_id: AccountId) -> Result<bool, FaucetError> {
+        let removed = self.conn.execute(
+            "DELETE FROM watched_accounts WHERE account_id = ?1",
+            [account_id.to_hex()],
+        )?;
+        Ok(removed > 0)
+    }
+
+    pub fn is_watched_account(&self, account_id: AccountId) -> Result<bool, FaucetError> {
+        let watched = self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM watched_accounts WHERE account_id = ?1)",
+            [account_id.to_hex()],
+            |row| row.get(0),
+        )?;
+        Ok(watched)
+    }
+
+    /// Watched accounts with the time they were added, oldest first.
+    pub fn watched_accounts(&self) -> Result<Vec<(AccountId, u64)>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT account_id, watched_at FROM watched_accounts ORDER BY watched_at, account_id",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.into_iter()
+            .map(|(id, watched_at)| {
+                let id = AccountId::from_hex(&id).map_err(|err| {
+                    FaucetError::Ledger(format!("invalid watched account ID `{id}`: {err}"))
+                })?;
+                Ok((id, watched_at))
+            })
+            .collect()
+    }
+
+    /// Committed mints to `recipient` and how many of them it claimed, see [`RecipientStats`].
+    pub fn recipient_stats(&self, recipient: AccountId) -> Result<RecipientStats, FaucetError> {
+        let stats = self.conn.query_row(
+            "SELECT
+                    COUNT(*),
+                    COALESCE(SUM(amount), 0),
+                    COUNT(*) FILTER (WHERE claim_block IS NOT NULL),
+                    COALESCE(SUM(amount) FILTER (WHERE claim_block IS NOT NULL), 0),
+                    MAX(claim_block)
+                 FROM mints WHERE recipient = ?1 AND status = 'committed'",
+            [recipient.to_hex()],
+            |row| {
+                Ok(RecipientStats {
+                    mints: row.get(0)?,
+                    minted_amount: row.get(1)?,
+                    claimed: row.get(2)?,
+                    claimed_amount: row.get(3)?,
+                    last_claim_block: row.get(4)?,
+                })
+            },
+        )?;
+        Ok(stats)
+    }
+
+    /// Labels attached to `account_id`.
+    pub fn labels_of(&self, account_id: AccountId) -> Result<Vec<String>, FaucetError> {
+        Ok(self
+            .query_labels(
+                "WHERE account_id = ?1 ORDER BY label",
+                [account_id.to_hex()],
+            )?
+            .into_iter()
+            .map(|(label, _)| label)
+            .collect())
+    }
+
+    /// Records a schedule minting `amount` to `recipient` every `interval` from `first_run` and
+    /// returns its ID.
+    pub fn add_schedule(
+        &self,
+        faucet_id: AccountId,
+        recipient: AccountId,
+        amount: u64,
+        interval: Duration,
+        catch_up: CatchUp,
+        first_run: u64,
+    ) -> Result<i64, FaucetError> {
+        self.conn.execute(
+            "INSERT INTO schedules (faucet_id, recipient, amount, interval_secs, catch_up, next_run,
+                created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                faucet_id.to_hex(),
+                recipient.to_hex(),
+                amount,
+                interval.as_secs(),
+                catch_up.as_str(),
+                first_run,
+                unix_now(),
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Removes schedule `id` and returns whether it existed.
+    pub fn remove_schedule(&self, id: i64) -> Result<bool, FaucetError> {
+        let removed = self
+            .conn
+            .execute("DELETE FROM schedules WHERE id = ?1", [id])?;
+        Ok(removed > 0)
+    }
+
+    /// Schedules ordered by ID, optionally restricted to a faucet.
+    pub fn schedules(
+        &self,
+        faucet_id: Option<AccountId>,
+    ) -> Result<Vec<ScheduleRecord>, FaucetError> {
+        self.query_schedules(
+            "WHERE ?1 IS NULL OR faucet_id = ?1 ORDER BY id",
+            [faucet_id.map(|id| id.to_hex())],
+        )
+    }
+
+    /// Schedules of `faucet_id` with a run due at `now`, most overdue first.
+    pub fn due_schedules(
+        &self,
+        faucet_id: AccountId,
+        now: u64,
+    ) -> Result<Vec<ScheduleRecord>, FaucetError> {
+        self.query_schedules(
+            "WHERE faucet_id = ?1 AND next_run <= ?2 ORDER BY next_run",
+            params![faucet_id.to_hex(), now],
+        )
+    }
+
+    /// Moves schedule `id` to `next_run` and records the outcome of its latest run.
+    pub fn record_schedule_run(
+        &self,
+        id: i64,
+        next_run: u64,
+        mint_id: Option<i64>,
+        error: Option<&str>,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE schedules
+             SET next_run = ?1, last_mint_id = COALESCE(?2, last_mint_id), last_error = ?3
+             WHERE id = ?4",
+            params![next_run, mint_id, error, id],
+        )?;
+        Ok(())
+    }
+
+    fn query_schedules(
+        &self,
+        filter: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<ScheduleRecord>, FaucetError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, faucet_id, recipient, amount, interval_secs, catch_up, next_run, created_at,
+                last_mint_id, last_error
+             FROM schedules {filter}"
+        ))?;
+
+        let rows = stmt.query_map(params, |row| {
+            Ok(ScheduleRecord {
+                id: row.get(0)?,
+                faucet_id: account_column(row, 1)?,
+                recipient: account_column(row, 2)?,
+                amount: row.get(3)?,
+                interval: Duration::from_secs(row.get(4)?),
+                catch_up: row.get(5)?,
+                next_run: row.get(6)?,
+                created_at: row.get(7)?,
+                last_mint_id: row.get(8)?,
+                last_error: row.get(9)?,
+            })
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Records a campaign of `faucet_id` and returns its ID.
+    ///
+    /// Fails if a campaign named `name` exists already.
+    pub fn add_campaign(
+        &self,
+        name: &str,
+        faucet_id: AccountId,
+        budget: u64,
+        per_user_limit: Option<u64>,
+        starts_at: Option<u64>,
+        ends_at: Option<u64>,
+    ) -> Result<u32, FaucetError> {
+        if self.campaign_by_name(name)?.is_some() {
+            return Err(FaucetError::InvalidCampaign(
+                name.to_string(),
+                "a campaign of that name exists already".into(),
+            ));
+        }
+        self.conn.execute(
+            "INSERT INTO campaigns (name, faucet_id, budget, per_user_limit, starts_at, ends_at,
+                created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                name,
+                faucet_id.to_hex(),
+                budget,
+                per_user_limit,
+                starts_at,
+                ends_at,
+                unix_now(),
+            ],
+        )?;
+        u32::try_from(self.conn.last_insert_rowid())
+            .map_err(|_| FaucetError::Ledger("campaign IDs exhausted".into()))
+    }
+
+    /// Closes campaign `id` at `now` and returns whether it was open.
+    pub fn close_campaign(&self, id: u32, now: u64) -> Result<bool, FaucetError> {
+        let closed = self.conn.execute(
+            "UPDATE campaigns SET closed_at = ?1 WHERE id = ?2 AND closed_at IS NULL",
+            params![now, id],
+        )?;
+        Ok(closed > 0)
+    }
+
+    pub fn campaign(&self, id: u32) -> Result<Option<CampaignRecord>, FaucetError> {
+        Ok(self.query_campaigns("WHERE id = ?1", [id])?.pop())
+    }
+
+    pub fn campaign_by_name(&self, name: &str) -> Result<Option<CampaignRecord>, FaucetError> {
+        Ok(self.query_campaigns("WHERE name = ?1", [name])?.pop())
+    }
+
+    /// Campaigns ordered by ID, optionally restricted to a faucet.
+    pub fn campaigns(
+        &self,
+        faucet_id: Option<AccountId>,
+    ) -> Result<Vec<CampaignRecord>, FaucetError> {
+        self.query_campaigns(
+            "WHERE ?1 IS NULL OR faucet_id = ?1 ORDER BY id",
+            [faucet_id.map(|id| id.to_hex())],
+        )
+    }
+
+    /// Tokens of the submitted and committed mints of campaign `id`, optionally only those to
+    /// `recipient`.
+    ///
+    /// Failed mints do not count, nor do invalidated ones, whose notes are minted again under the
+    /// same campaign.
+    pub fn campaign_spent(
+        &self,
+        id: u32,
+        recipient: Option<AccountId>,
+    ) -> Result<u64, FaucetError> {
+        Ok(self.conn.query_row(
+            "SELECT COALESCE(SUM(amount), 0) FROM mints
+             WHERE (aux >> 16) & 4294967295 = ?1 AND status IN ('submitted', 'committed')
+                AND (?2 IS NULL OR recipient = ?2)",
+            params![id, recipient.map(|id| id.to_hex())],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Mints of every campaign, optionally restricted to the campaigns of a faucet.
+    pub fn campaign_stats(
+        &self,
+        faucet_id: Option<AccountId>,
+    ) -> Result<Vec<CampaignStats>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.id, c.name, c.budget,
+                    COUNT(m.id),
+                    COUNT(DISTINCT m.recipient),
+                    COALESCE(SUM(m.amount) FILTER (WHERE m.status IN ('submitted', 'committed')), 0),
+                    COALESCE(SUM(m.amount) FILTER (WHERE m.status = 'committed'), 0),
+                    COALESCE(SUM(m.amount) FILTER (WHERE m.claim_block IS NOT NULL), 0)
+             FROM campaigns c LEFT JOIN mints m ON (m.aux >> 16) & 4294967295 = c.id
+             WHERE ?1 IS NULL OR c.faucet_id = ?1
+             GROUP BY c.id ORDER BY c.id",
+        )?;
+
+        let rows = stmt.query_map([faucet_id.map(|id| id.to_hex())], |row| {
+            Ok(CampaignStats {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                budget: row.get(2)?,
+                mints: row.get(3)?,
+                unique_recipients: row.get(4)?,
+                spent_amount: row.get(5)?,
+                minted_amount: row.get(6)?,
+                delivered_amount: row.get(7)?,
+            })
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn query_campaigns(
+        &self,
+        filter: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<CampaignRecord>, FaucetError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, name, faucet_id, budget, per_user_limit, starts_at, ends_at, created_at,
+                closed_at
+             FROM campaigns {filter}"
+        ))?;
+
+        let rows = stmt.query_map(params, |row| {
+            Ok(CampaignRecord {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                faucet_id: account_column(row, 2)?,
+                budget: row.get(3)?,
+                per_user_limit: row.get(4)?,
+                starts_at: row.get(5)?,
+                ends_at: row.get(6)?,
+                created_at: row.get(7)?,
+                closed_at: row.get(8)?,
+            })
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Puts `subject` on `list`, replacing its note if listed already.
+    pub fn add_list_entry(
+        &self,
+        list: AccessList,
+        subject: &str,
+        note: Option<&str>,
+    ) -> Result<ListEntry, FaucetError> {
+        let created_at = unix_now();
+        self.conn.execute(
+            "INSERT INTO access_lists (list, subject, note, created_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (list, subject) DO UPDATE SET note = excluded.note",
+            params![list.as_str(), subject, note, created_at],
+        )?;
+        Ok(ListEntry {
+            list,
+            subject: subject.to_string(),
+            note: note.map(str::to_string),
+            created_at,
+        })
+    }
+
+    /// Takes `subject` off `list` and returns whether it was on it.
+    pub fn remove_list_entry(&self, list: AccessList, subject: &str) -> Result<bool, FaucetError> {
+        let removed = self.conn.execute(
+            "DELETE FROM access_lists WHERE list = ?1 AND subject = ?2",
+            params![list.as_str(), subject],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Records the deployment of `faucet_id` by `owner_id`, issuing `token`.
+    pub fn record_deployment(
+        &self,
+        faucet_id: AccountId,
+        owner_id: AccountId,
+        token: &TokenConfig,
+        transaction_id: TransactionId,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO deployments (faucet_id, owner_id, symbol, decimals, max_supply,
+                transaction_id, deployed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                faucet_id.to_hex(),
+                owner_id.to_hex(),
+                token.symbol,
+                token.decimals,
+                token.max_supply,
+                transaction_id.to_hex(),
+                unix_now(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Marks `faucet_id` as retired and returns when it was retired, keeping the time of an
+    /// earlier retirement.
+    pub fn retire_deployment(&self, faucet_id: AccountId) -> Result<u64, FaucetError> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO retired_deployments (faucet_id, retired_at) VALUES (?1, ?2)",
+            params![faucet_id.to_hex(), unix_now()],
+        )?;
+        Ok(self.conn.query_row(
+            "SELECT retired_at FROM retired_deployments WHERE faucet_id = ?1",
+            [faucet_id.to_hex()],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Deployed faucets, oldest first.
+    pub fn deployments(&self) -> Result<Vec<DeploymentRecord>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT d.faucet_id, owner_id, symbol, decimals, max_supply, transaction_id,
+                deployed_at, retired_at
+             FROM deployments d LEFT JOIN retired_deployments r ON r.faucet_id = d.faucet_id
+             ORDER BY deployed_at, d.faucet_id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(DeploymentRecord {
+                faucet_id: row.get(0)?,
+                owner_id: row.get(1)?,
+                symbol: row.get(2)?,
+                decimals: row.get(3)?,
+                max_supply: row.get(4)?,
+                transaction_id: row.get(5)?,
+                deployed_at: row.get(6)?,
+                retired_at: row.get(7)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Entries of both access lists, oldest first.
+    pub fn list_entries(&self) -> Result<Vec<ListEntry>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT list, subject, note, created_at FROM access_lists ORDER BY created_at, list",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ListEntry {
+                list: row.get(0)?,
+                subject: row.get(1)?,
+                note: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Whether any of `subjects` is on `list`.
+    pub fn is_listed(&self, list: AccessList, subjects: &[&str]) -> Result<bool, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT EXISTS (SELECT 1 FROM access_lists WHERE list = ?1 AND subject = ?2)",
+        )?;
+        for subject in subjects {
+            if stmt.query_row(params![list.as_str(), subject], |row| row.get(0))? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    pub fn list_len(&self, list: AccessList) -> Result<u64, FaucetError> {
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*) FROM access_lists WHERE list = ?1",
+            [list.as_str()],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Keeps limit `name`, adjusted at runtime, e.g. `github.drip_limit`, as JSON `value` so it
+    /// survives restarts.
+    pub fn set_limit_override(&self, name: &str, value: &str) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "INSERT INTO limit_overrides (name, value, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT (name)
+             DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            params![name, value, unix_now()],
+        )?;
+        Ok(())
+    }
+
+    /// JSON value of limit override `name`, if set.
+    pub fn limit_override(&self, name: &str) -> Result<Option<String>, FaucetError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT value FROM limit_overrides WHERE name = ?1",
+                [name],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Drops limit override `name` and returns whether it was set.
+    pub fn remove_limit_override(&self, name: &str) -> Result<bool, FaucetError> {
+        let removed = self
+            .conn
+            .execute("DELETE FROM limit_overrides WHERE name = ?1", [name])?;
+        Ok(removed > 0)
+    }
+
+    /// Records referral code `code` crediting `referrer`.
+    pub fn add_referral_code(&self, code: &str, referrer: AccountId) -> Result<(), FaucetError> {
+        if self.referral_code(code)?.is_some() {
+            return Err(FaucetError::InvalidReferral(
+                code.to_string(),
+                "the code exists already".into(),
+            ));
+        }
+        self.conn.execute(
+            "INSERT INTO referral_codes (code, referrer, created_at) VALUES (?1, ?2, ?3)",
+            params![code, referrer.to_hex(), unix_now()],
+        )?;
+        Ok(())
+    }
+
+    pub fn referral_code(&self, code: &str) -> Result<Option<ReferralCodeRecord>, FaucetError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT code, referrer, created_at, disabled_at FROM referral_codes
+                 WHERE code = ?1",
+                [code],
+                |row| {
+                    Ok(ReferralCodeRecord {
+                        code: row.get(0)?,
+                        referrer: account_column(row, 1)?,
+                        created_at: row.get(2)?,
+                        disabled_at: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Stops code `code` from being converted and returns whether it was enabled.
+    pub fn disable_referral_code(&self, code: &str) -> Result<bool, FaucetError> {
+        let disabled = self.conn.execute(
+            "UPDATE referral_codes SET disabled_at = ?1 WHERE code = ?2 AND disabled_at IS NULL",
+            params![unix_now(), code],
+        )?;
+        Ok(disabled > 0)
+    }
+
+    /// Whether `referee` converted a referral code before.
+    pub fn was_referred(&self, referee: AccountId) -> Result<bool, FaucetError> {
+        Ok(self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM referrals WHERE referee = ?1)",
+            [referee.to_hex()],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Records the conversion of `code` by mint `mint_id` to `referee` and returns its ID.
+    pub fn record_referral(
+        &self,
+        code: &str,
+        referee: AccountId,
+        mint_id: i64,
+    ) -> Result<i64, FaucetError> {
+        self.conn.execute(
+            "INSERT INTO referrals (code, referee, mint_id, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![code, referee.to_hex(), mint_id, unix_now()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Records the bonus mint `bonus_mint_id` of conversion `id`.
+    pub fn set_referral_bonus(&self, id: i64, bonus_mint_id: i64) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE referrals SET bonus_mint_id = ?1 WHERE id = ?2",
+            params![bonus_mint_id, id],
+        )?;
+        Ok(())
+    }
+
+    /// Bonus mints `referrer` received for the conversions of its codes.
+    pub fn referral_bonuses(&self, referrer: AccountId) -> Result<u32, FaucetError> {
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*) FROM referrals r JOIN referral_codes c ON c.code = r.code
+             WHERE c.referrer = ?1 AND r.bonus_mint_id IS NOT NULL",
+            [referrer.to_hex()],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Conversions of every referral code, most converted first.
+    pub fn referral_stats(&self) -> Result<Vec<ReferralStats>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.code, c.referrer, c.disabled_at IS NOT NULL,
+                    COUNT(r.id),
+                    COUNT(r.bonus_mint_id),
+                    COALESCE(SUM(m.amount), 0)
+             FROM referral_codes c
+                LEFT JOIN referrals r ON r.code = c.code
+                LEFT JOIN mints m ON m.id = r.bonus_mint_id
+             GROUP BY c.code ORDER BY COUNT(r.id) DESC, c.code",
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(ReferralStats {
+                code: row.get(0)?,
+                referrer: row.get(1)?,
+                disabled: row.get(2)?,
+                conversions: row.get(3)?,
+                bonuses: row.get(4)?,
+                bonus_amount: row.get(5)?,
+            })
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Appends `entry` to the audit log, chained to the last entry, and returns it.
+    pub fn append_audit(&self, entry: &AuditEntry) -> Result<AuditRecord, FaucetError> {
+        // Taking the write lock up front keeps concurrent writers from chaining to the same entry.
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        let (last_id, prev_hash) = tx
+            .query_row(
+                "SELECT id, hash FROM audit_log ORDER BY id DESC LIMIT 1",
+                [],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()?
+            .unwrap_or_else(|| (0, genesis_hash()));
+
+        let mut record = AuditRecord {
+            id: last_id + 1,
+            created_at: unix_now(),
+            actor: entry.actor.clone(),
+            action: entry.action.clone(),
+            account: entry.account.map(|id| id.to_hex()),
+            params: serde_json::Value::Object(entry.params.clone()).to_string(),
+            transaction_id: entry.transaction_id.clone(),
+            prev_hash,
+            hash: String::new(),
+        };
+        record.hash = record.compute_hash();
+        tx.execute(
+            "INSERT INTO audit_log (id, created_at, actor, action, account, params, transaction_id,
+                prev_hash, hash)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                record.id,
+                record.created_at,
+                record.actor,
+                record.action,
+                record.account,
+                record.params,
+                record.transaction_id,
+                record.prev_hash,
+                record.hash,
+            ],
+        )?;
+        tx.commit()?;
+        Ok(record)
+    }
+
+    /// Every entry of the audit log, oldest first.
+    pub fn audit_log(&self) -> Result<Vec<AuditRecord>, FaucetError> {
+        self.query_audit("ORDER BY id", [])
+    }
+
+    /// Most recent audit log entries first.
+    pub fn recent_audit(&self, limit: usize) -> Result<Vec<AuditRecord>, FaucetError> {
+        self.query_audit("ORDER BY id DESC LIMIT ?1", [limit as i64])
+    }
+
+    fn query_audit(
+        &self,
+        filter: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<AuditRecord>, FaucetError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, created_at, actor, action, account, params, transaction_id, prev_hash, hash
+             FROM audit_log {filter}"
+        ))?;
+
+        let rows = stmt.query_map(params, |row| {
+            Ok(AuditRecord {
+                id: row.get(0)?,
+                created_at: row.get(1)?,
+                actor: row.get(2)?,
+                action: row.get(3)?,
+                account: row.get(4)?,
+                params: row.get(5)?,
+                transaction_id: row.get(6)?,
+                prev_hash: row.get(7)?,
+                hash: row.get(8)?,
+            })
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Records the state of `account_id` at `block_num` unless it is the state last recorded.
+    ///
+    /// Returns whether the state was recorded.
+    pub fn record_account_state(
+        &self,
+        account_id: AccountId,
+        block_num: BlockNumber,
+        commitment: Word,
+        nonce: u64,
+        state: &[u8],
+    ) -> Result<bool, FaucetError> {
+        let latest = self.account_state_at(account_id, BlockNumber::from(u32::MAX))?;
+        if latest.is_some_and(|latest| latest.commitment == commitment.to_hex()) {
+            return Ok(false);
+        }
+        self.conn.execute(
+            "INSERT OR REPLACE INTO account_states (account_id, block_num, commitment, nonce, state)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                account_id.to_hex(),
+                block_num.as_u32(),
+                commitment.to_hex(),
+                nonce,
+                state
+            ],
+        )?;
+        Ok(true)
+    }
+
+    /// Latest state of `account_id` recorded at or before `block_num`.
+    pub fn account_state_at(
+        &self,
+        account_id: AccountId,
+        block_num: BlockNumber,
+    ) -> Result<Option<AccountStateRecord>, FaucetError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT account_id, block_num, commitment, nonce, state FROM account_states
+                 WHERE account_id = ?1 AND block_num <= ?2
+                 ORDER BY block_num DESC LIMIT 1",
+                params![account_id.to_hex(), block_num.as_u32()],
+                |row| {
+                    Ok(AccountStateRecord {
+                        account_id: row.get(0)?,
+                        block_num: row.get(1)?,
+                        commitment: row.get(2)?,
+                        nonce: row.get(3)?,
+                        state: row.get(4)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Records a GitHub session, identified by the hash of its token, valid until `expires_at`.
+    pub fn create_github_session(
+        &self,
+        token_hash: &str,
+        user: &GithubUser,
+        expires_at: u64,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "DELETE FROM github_sessions WHERE expires_at <= ?1",
+            [unix_now()],
+        )?;
+        self.conn.execute(
+            "INSERT INTO github_sessions (token_hash, github_id, login, expires_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![token_hash, user.id, user.login, expires_at],
+        )?;
+        Ok(())
+    }
+
+    /// User of the session with token hash `token_hash`, unless it expired by `now`.
+    pub fn github_session(
+        &self,
+        token_hash: &str,
+        now: u64,
+    ) -> Result<Option<GithubUser>, FaucetError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT github_id, login FROM github_sessions
+                 WHERE token_hash = ?1 AND expires_at > ?2",
+                params![token_hash, now],
+                |row| {
+                    Ok(GithubUser {
+                        id: row.get(0)?,
+                        login: row.get(1)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Reserves a drip of GitHub user `github_id` at `now` and returns its ID, failing with
+    /// [`FaucetError::RateLimited`] if the user had `max_drips` drips within `window` already.
+    pub fn reserve_drip(
+        &self,
+        github_id: u64,
+        now: u64,
+        window: Duration,
+        max_drips: u32,
+    ) -> Result<i64, FaucetError> {
+        // Taking the write lock up front keeps concurrent requests of a user from both passing.
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        let (drips, oldest): (u32, Option<u64>) = tx.query_row(
+            "SELECT COUNT(*), MIN(created_at) FROM github_drips
+             WHERE github_id = ?1 AND created_at > ?2",
+            params![github_id, now.saturating_sub(window.as_secs())],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        if drips >= max_drips {
+            // A drip is available again once the oldest one leaves the window.
+            let retry_after_secs = (oldest.unwrap_or(now) + window.as_secs()).saturating_sub(now);
+            return Err(FaucetError::RateLimited { retry_after_secs });
+        }
+        tx.execute(
+            "INSERT INTO github_drips (github_id, created_at) VALUES (?1, ?2)",
+            params![github_id, now],
+        )?;
+        let id = tx.last_insert_rowid();
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Runs `f` against the ledger and rolls back whatever it wrote, e.g. to judge a request as if
+    /// others were recorded before it.
+    pub fn dry_run<T>(
+        &self,
+        f: impl FnOnce(&Self) -> Result<T, FaucetError>,
+    ) -> Result<T, FaucetError> {
+        self.conn.execute_batch("SAVEPOINT dry_run")?;
+        let result = f(self);
+        self.conn
+            .execute_batch("ROLLBACK TO dry_run; RELEASE dry_run")?;
+        result
+    }
+
+    /// Records a mint request admitted by the abuse policies, see [`crate::abuse`].
+    pub fn record_request(&self, request: &AbuseRequest) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "INSERT INTO abuse_requests (identity, ip, recipient, amount, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                request.identity,
+                request.ip.to_string(),
+                request.recipient.to_hex(),
+                request.amount,
+                request.now,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Number of recorded requests whose `key` is `subject` after `since`, and when the oldest
+    /// of them was made.
+    pub fn requests_since(
+        &self,
+        key: RequestKey,
+        subject: &str,
+        since: u64,
+    ) -> Result<(u32, Option<u64>), FaucetError> {
+        Ok(self.conn.query_row(
+            &format!(
+                "SELECT COUNT(*), MIN(created_at) FROM abuse_requests
+                 WHERE {} = ?1 AND created_at > ?2",
+                key.as_str()
+            ),
+            params![subject, since],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?)
+    }
+
+    /// Amount of the recorded requests after `since`.
+    pub fn requested_amount_since(&self, since: u64) -> Result<u64, FaucetError> {
+        Ok(self.conn.query_row(
+            "SELECT COALESCE(SUM(amount), 0) FROM abuse_requests WHERE created_at > ?1",
+            [since],
+            |row| row.get(0),
+        )?)
+    }
+
+    pub fn set_drip_mint(&self, drip_id: i64, mint_id: i64) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE github_drips SET mint_id = ?1 WHERE id = ?2",
+            [mint_id, drip_id],
+        )?;
+        Ok(())
+    }
+
+    /// Gives back a drip whose mint failed.
+    pub fn release_drip(&self, drip_id: i64) -> Result<(), FaucetError> {
+        self.conn
+            .execute("DELETE FROM github_drips WHERE id = ?1", [drip_id])?;
+        Ok(())
+    }
+
+    /// Records a new flow of `kind` at `step` and returns its ID.
+    pub fn create_flow(&self, kind: &str, step: &str, state: &str) -> Result<i64, FaucetError> {
+        let now = unix_now();
+        self.conn.execute(
+            "INSERT INTO flows (kind, step, state, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?4)",
+            params![kind, step, state, now],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Moves flow `id` to `step` with `state`, recording the failed `attempts` of the step and
+    /// the latest error.
+    pub fn update_flow(
+        &self,
+        id: i64,
+        step: &str,
+        state: &str,
+        attempts: u32,
+        error: Option<&str>,
+    ) -> Result<(), FaucetError> {
+        self.conn.execute(
+            "UPDATE flows
+             SET step = ?1, state = ?2, attempts = ?3, last_error = ?4, updated_at = ?5
+             WHERE id = ?6",
+            params![step, state, attempts, error, unix_now(), id],
+        )?;
+        Ok(())
+    }
+
+    pub fn flow(&self, id: i64) -> Result<Option<FlowRecord>, FaucetError> {
+        Ok(self.query_flows("WHERE id = ?1", [id])?.pop())
+    }
+
+    /// Flows ordered by ID, only those not done yet unless `all`.
+    pub fn flows(&self, all: bool) -> Result<Vec<FlowRecord>, FaucetError> {
+        self.query_flows("WHERE ?1 OR step != 'done' ORDER BY id", [all])
+    }
+
+    fn query_flows(
+        &self,
+        filter: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<FlowRecord>, FaucetError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, kind, step, state, attempts, last_error, created_at, updated_at
+             FROM flows {filter}"
+        ))?;
+
+        let rows = stmt.query_map(params, |row| {
+            Ok(FlowRecord {
+                id: row.get(0)?,
+                kind: row.get(1)?,
+                step: row.get(2)?,
+                state: row.get(3)?,
+                attempts: row.get(4)?,
+                last_error: row.get(5)?,
+                created_at: row.get(6)?,
+                updated_at: row.get(7)?,
+            })
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Most recent mints first, optionally restricted to a faucet and a status.
+    pub fn recent_mints(
+        &self,
+        faucet_id: Option<AccountId>,
+        status: Option<MintStatus>,
+        limit: usize,
+    ) -> Result<Vec<MintRecord>, FaucetError> {
+        self.query_mints(
+            "WHERE (?1 IS NULL OR faucet_id = ?1) AND (?2 IS NULL OR status = ?2)
+             ORDER BY id DESC LIMIT ?3",
+            params![
+                faucet_id.map(|id| id.to_hex()),
+                status.map(|status| status.as_str()),
+                limit as i64
+            ],
+        )
+    }
+
+    pub fn get_mint(&self, id: i64) -> Result<Option<MintRecord>, FaucetError> {
+        Ok(self.query_mints("WHERE id = ?1", [id])?.pop())
+    }
+
+    /// Latest mint of the note `note_id`.
+    pub fn mint_of_note(&self, note_id: &str) -> Result<Option<MintRecord>, FaucetError> {
+        Ok(self
+            .query_mints("WHERE note_id = ?1 ORDER BY id DESC LIMIT 1", [note_id])?
+            .pop())
+    }
+
+    /// Aggregates the ledger, optionally restricted to a single faucet.
+    pub fn stats(&self, faucet_id: Option<AccountId>) -> Result<MintStats, FaucetError> {
+        let faucet = faucet_id.map(|id| id.to_hex());
+        let stats = self.conn.query_row(
+            "SELECT
+                    COUNT(*),
+                    COUNT(*) FILTER (WHERE status = 'committed'),
+                    COUNT(*) FILTER (WHERE status = 'failed'),
+                    COUNT(*) FILTER (WHERE status = 'committed' AND claim_block IS NOT NULL),
+                    COUNT(*) FILTER (WHERE status = 'committed' AND claim_block IS NULL
+                        AND reclaimed_block IS NULL),
+                    COALESCE(SUM(amount) FILTER (WHERE status = 'committed'), 0),
+                    COALESCE(SUM(amount) FILTER (WHERE claim_block IS NOT NULL), 0),
+                    COUNT(*) FILTER (WHERE reclaimed_block IS NOT NULL),
+                    COALESCE(SUM(amount) FILTER (WHERE reclaimed_block IS NOT NULL), 0),
+                    (SELECT COUNT(*) FROM burns
+                        WHERE status = 'committed' AND (?1 IS NULL OR faucet_id = ?1)),
+                    (SELECT COALESCE(SUM(amount), 0) FROM burns
+                        WHERE status = 'committed' AND (?1 IS NULL OR faucet_id = ?1)),
+                    (SELECT COUNT(*) FROM returns
+                        WHERE status = 'committed' AND (?1 IS NULL OR faucet_id = ?1)),
+                    (SELECT COALESCE(SUM(amount), 0) FROM returns
+                        WHERE status = 'committed' AND (?1 IS NULL OR faucet_id = ?1)),
+                    (SELECT COALESCE(SUM(fee), 0) FROM transaction_costs
+                        WHERE ?1 IS NULL OR faucet_id = ?1),
+                    (SELECT COALESCE(SUM(cycles), 0) FROM transaction_costs
+                        WHERE ?1 IS NULL OR faucet_id = ?1)
+                 FROM mints WHERE ?1 IS NULL OR faucet_id = ?1",
+            [faucet],
+            |row| {
+                Ok(MintStats {
+                    submitted: row.get(0)?,
+                    committed: row.get(1)?,
+                    failed: row.get(2)?,
+                    delivered: row.get(3)?,
+                    unclaimed: row.get(4)?,
+                    minted_amount: row.get(5)?,
+                    delivered_amount: row.get(6)?,
+                    reclaimed: row.get(7)?,
+                    reclaimed_amount: row.get(8)?,
+                    burned: row.get(9)?,
+                    burned_amount: row.get(10)?,
+                    returned: row.get(11)?,
+                    returned_amount: row.get(12)?,
+                    fees_paid: row.get(13)?,
+                    cycles_spent: row.get(14)?,
+                })
+            },
+        )?;
+        Ok(stats)
+    }
+
+    /// Mints per time bucket of `granularity` in UTC, oldest first, optionally restricted to a
+    /// faucet. Buckets without mints are left out.
+    pub fn bucketed_stats(
+        &self,
+        faucet_id: Option<AccountId>,
+        granularity: StatsGranularity,
+    ) -> Result<Vec<MintBucket>, FaucetError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT created_at / ?2 * ?2 AS bucket,
+                    strftime(?3, created_at / ?2 * ?2, 'unixepoch'),
+                    COUNT(*),
+                    COUNT(DISTINCT recipient),
+                    COALESCE(SUM(amount), 0),
+                    COUNT(*) FILTER (WHERE status = 'committed'),
+                    COUNT(*) FILTER (WHERE status = 'failed')
+             FROM mints WHERE ?1 IS NULL OR faucet_id = ?1
+             GROUP BY bucket ORDER BY bucket",
+        )?;
+
+        let rows = stmt.query_map(
+            params![
+                faucet_id.map(|id| id.to_hex()),
+                granularity.secs(),
+                granularity.label_format()
+            ],
+            |row| {
+                let mints: u64 = row.get(2)?;
+                let failed: u64 = row.get(6)?;
+                Ok(MintBucket {
+                    start: row.get(0)?,
+                    label: row.get(1)?,
+                    mints,
+                    unique_recipients: row.get(3)?,
+                    amount: row.get(4)?,
+                    committed: row.get(5)?,
+                    failed,
+                    failure_rate: failed as f64 / mints as f64,
+                })
+            },
+        )?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn query_mints(
+        &self,
+        filter: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<MintRecord>, FaucetError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, faucet_id, recipient, amount, transaction_id, note_id, nullifier,
+                nullifier_prefix, status, error, created_at, commit_block, claim_block, serial_num,
+                reclaim_block, reclaim_transaction_id, reclaimed_block, unlock_block, replaced_by,
+                public_note, aux, note_tag, queued_at
+             FROM mints {filter}"
+        ))?;
+
+        let rows = stmt.query_map(params, |row| {
+            Ok(MintRecord {
+                id: row.get(0)?,
+                faucet_id: row.get(1)?,
+                recipient: row.get(2)?,
+                amount: row.get(3)?,
+                transaction_id: row.get(4)?,
+                note_id: row.get(5)?,
+                nullifier: row.get(6)?,
+                nullifier_prefix: row.get(7)?,
+                status: row.get(8)?,
+                error: row.get(9)?,
+                created_at: row.get(10)?,
+                commit_block: row.get(11)?,
+                claim_block: row.get(12)?,
+                serial_num: row.get(13)?,
+                reclaim_block: row.get(14)?,
+                reclaim_transaction_id: row.get(15)?,
+                reclaimed_block: row.get(16)?,
+                unlock_block: row.get(17)?,
+                replaced_by: row.get(18)?,
+                // Mints recorded before note types were stored are private.
+                public_note: row.get::<_, Option<bool>>(19)?.unwrap_or_default(),
+                // Mints recorded before aux values were stored carry the default one.
+                aux: row.get::<_, Option<u64>>(20)?.unwrap_or(MINT_NOTE_AUX),
+                note_tag: row.get(21)?,
+                queued_at: row.get(22)?,
+            })
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+}
+
+/// Migration to version 1: adds the columns of [`ADDED_COLUMNS`] to `mints` and drops the
+/// uniqueness of transaction IDs.
+fn migrate_mints(conn: &Connection) -> Result<(), FaucetError> {
+    let existing = mint_columns(conn)?;
+
+    for (column, ty) in ADDED_COLUMNS {
+        if !existing.iter().any(|name| name == column) {
+            conn.execute_batch(&format!("ALTER TABLE mints ADD COLUMN {column} {ty}"))?;
+        }
+    }
+
+    // Ledgers created before batch mints hold transaction IDs unique, which SQLite can only drop
+    // by rebuilding the table.
+    let unique_transactions: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_index_list('mints') WHERE origin = 'u'",
+        [],
+        |row| row.get(0),
+    )?;
+    if unique_transactions {
+        let columns = existing
+            .iter()
+            .map(String::as_str)
+            .chain(
+                ADDED_COLUMNS
+                    .iter()
+                    .map(|(column, _)| *column)
+                    .filter(|column| !existing.iter().any(|name| name == column)),
+            )
+            .collect::<Vec<_>>()
+            .join(", ");
+        conn.execute_batch(&format!(
+            "BEGIN;
+             DROP INDEX IF EXISTS mints_unclaimed;
+             DROP INDEX IF EXISTS mints_by_transaction;
+             ALTER TABLE mints RENAME TO mints_unique_transactions;
+             {SCHEMA}
+             INSERT INTO mints ({columns}) SELECT {columns} FROM mints_unique_transactions;
+             DROP TABLE mints_unique_transactions;
+             COMMIT;"
+        ))?;
+    }
+    Ok(())
+}
+
+/// Migration to version 2: adds the `note_tag` column to `mints`. Mints recorded before carry
+/// the tag of their recipient.
+fn migrate_note_tags(conn: &Connection) -> Result<(), FaucetError> {
+    if !mint_columns(conn)?.iter().any(|name| name == "note_tag") {
+        conn.execute_batch("ALTER TABLE mints ADD COLUMN note_tag INTEGER")?;
+    }
+    Ok(())
+}
+
+/// Migration to version 3: adds the `queued_at` column to `mints`.
+fn migrate_queue_times(conn: &Connection) -> Result<(), FaucetError> {
+    if !mint_columns(conn)?.iter().any(|name| name == "queued_at") {
+        conn.execute_batch("ALTER TABLE mints ADD COLUMN queued_at INTEGER")?;
+    }
+    Ok(())
+}
+
+fn mint_columns(conn: &Connection) -> Result<Vec<String>, FaucetError> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info('mints')")?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(columns)
+}
+
+/// Reads an account ID stored as hex.
+fn account_column(row: &rusqlite::Row<'_>, idx: usize) -> rusqlite::Result<AccountId> {
+    let hex: String = row.get(idx)?;
+    AccountId::from_hex(&hex).map_err(|err| {
+        rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(err))
+    })
+}
+
+/// Seconds since the Unix epoch.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,67 @@
+//! Shared building blocks for the network faucet binaries.
+//!
+//! The binaries in `src/bin` used to carry their own copies of the client setup code. Everything
+//! that is not specific to a single flow lives here instead.
+
+pub mod abuse;
+pub mod access;
+pub mod account;
+pub mod admin;
+pub mod archive;
+pub mod audit;
+pub mod authz;
+pub mod backup;
+pub mod cache;
+pub mod campaign;
+pub mod client;
+pub mod config;
+pub mod decommission;
+pub mod deploy;
+pub mod doctor;
+pub mod email;
+pub mod errors;
+pub mod execution;
+pub mod executor;
+pub mod explorer;
+pub mod fair;
+#[cfg(feature = "fault-injection")]
+pub mod fault;
+pub mod finality;
+pub mod fixtures;
+pub mod flow;
+pub mod github;
+pub mod grpc;
+pub mod history;
+pub mod http;
+pub mod indexer;
+pub mod ledger;
+pub mod localnet;
+pub mod mint;
+pub mod network;
+pub mod node;
+pub mod note_file;
+pub mod pause;
+pub mod proof;
+pub mod receipt;
+pub mod reclaim;
+pub mod referral;
+pub mod request_log;
+pub mod rest;
+pub mod returns;
+pub mod rpc;
+pub mod scenario;
+pub mod schedule;
+pub mod script;
+pub mod service;
+pub mod signing;
+pub mod store;
+pub mod supply;
+pub mod sync;
+pub mod tls;
+pub mod tx;
+pub mod wallet;
+pub mod watch;
+pub mod watcher;
+pub mod webhook;
+
+pub use errors::FaucetError;
@@ -0,0 +1,186 @@
+//! Local miden-node bootstrap for end-to-end runs.
+//!
+//! [`LocalNode::start`] launches a node either from a docker image or from a `miden-node` binary
+//! on the host, waits until its RPC answers and hands back a [`Config`] pointing at it. The node
+//! is stopped when the [`LocalNode`] is dropped.
+
+use std::{
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use miden_client::rpc::{Endpoint, GrpcClient, NodeRpcClient};
+
+use crate::{config::Config, rpc::EndpointConfig, FaucetError};
+
+/// RPC port the node listens on inside the container and on the host.
+pub const DEFAULT_RPC_PORT: u16 = 57291;
+
+/// How the node process is provided.
+#[derive(Debug, Clone)]
+pub enum NodeLauncher {
+    /// Runs the given image with `docker run`.
+    Docker { image: String },
+    /// Runs `miden-node bundled` from the given binary.
+    Binary { path: PathBuf },
+}
+
+impl NodeLauncher {
+    /// Runs `MIDEN_NODE_BIN` from the host if set, otherwise docker with `MIDEN_NODE_IMAGE`
+    /// (`miden-node:latest` if unset).
+    pub fn from_env() -> Self {
+        match std::env::var_os("MIDEN_NODE_BIN") {
+            Some(path) => Self::Binary {
+                path: PathBuf::from(path),
+            },
+            None => Self::Docker {
+                image: std::env::var("MIDEN_NODE_IMAGE")
+                    .unwrap_or_else(|_| "miden-node:latest".into()),
+            },
+        }
+    }
+}
+
+/// Settings for [`LocalNode::start`].
+#[derive(Debug, Clone)]
+pub struct LocalnetConfig {
+    pub launcher: NodeLauncher,
+    pub rpc_port: u16,
+    /// Directory holding the node data and the client store/keystore of the run.
+    pub data_dir: PathBuf,
+    pub startup_timeout: Duration,
+}
+
+impl Default for LocalnetConfig {
+    fn default() -> Self {
+        Self {
+            launcher: NodeLauncher::Docker {
+                image: "miden-node:latest".into(),
+            },
+            rpc_port: DEFAULT_RPC_PORT,
+            data_dir: PathBuf::from("./localnet"),
+            startup_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+enum NodeHandle {
+    Container(String),
+    Process(Child),
+}
+
+/// A running local node.
+pub struct LocalNode {
+    handle: NodeHandle,
+    config: LocalnetConfig,
+}
+
+impl LocalNode {
+    /// Starts the node and waits until it serves block headers.
+    pub async fn start(config: LocalnetConfig) -> Result<Self, FaucetError> {
+        std::fs::create_dir_all(&config.data_dir)?;
+
+        let handle = match &config.launcher {
+            NodeLauncher::Docker { image } => {
+                let name = format!("network-faucet-localnet-{}", config.rpc_port);
+                let output = Command::new("docker")
+                    .args(["run", "--rm", "--detach", "--name", &name, "--publish"])
+                    .arg(format!("{0}:{0}", config.rpc_port))
+                    .arg(image)
+                    .output()?;
+                if !output.status.success() {
+                    return Err(FaucetError::Localnet(format!(
+                        "docker run failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )));
+                }
+                NodeHandle::Container(name)
+            }
+            NodeLauncher::Binary { path } => {
+                let node_dir = config.data_dir.join("node");
+                if !node_dir.exists() {
+                    let status = Command::new(path)
+                        .args(["bundled", "bootstrap", "--data-directory"])
+                        .arg(&node_dir)
+                        .arg("--accounts-directory")
+                        .arg(&config.data_dir)
+                        .status()?;
+                    if !status.success() {
+                        return Err(FaucetError::Localnet(format!(
+                            "`{} bundled bootstrap` exited with {status}",
+                            path.display()
+                        )));
+                    }
+                }
+
+                let child = Command::new(path)
+                    .args(["bundled", "start", "--data-directory"])
+                    .arg(&node_dir)
+                    .arg("--rpc.url")
+                    .arg(format!("http://0.0.0.0:{}", config.rpc_port))
+                    .stdout(Stdio::null())
+                    .spawn()?;
+                NodeHandle::Process(child)
+            }
+        };
+
+        let node = Self { handle, config };
+        node.wait_until_healthy().await?;
+        Ok(node)
+    }
+
+    pub fn endpoint(&self) -> Endpoint {
+        Endpoint::new(
+            "http".into(),
+            "localhost".into(),
+            Some(self.config.rpc_port),
+        )
+    }
+
+    /// Client configuration pointing at this node, with the store, keystore and ledger kept
+    /// inside the localnet data directory.
+    pub fn client_config(&self) -> Config {
+        let mut config = Config::in_data_dir(&self.config.data_dir);
+        config.rpc.endpoint = EndpointConfig::Url(self.endpoint().to_string());
+        config
+    }
+
+    async fn wait_until_healthy(&self) -> Result<(), FaucetError> {
+        let rpc = GrpcClient::new(&self.endpoint(), 1_000);
+        let started = Instant::now();
+
+        loop {
+            match rpc.get_block_header_by_number(None, false).await {
+                Ok((header, _)) => {
+                    println!("Local node is up at block {}", header.block_num());
+                    return Ok(());
+                }
+                Err(err) if started.elapsed() >= self.config.startup_timeout => {
+                    return Err(FaucetError::Localnet(format!(
+                        "node did not become healthy within {:?}: {err}",
+                        self.config.startup_timeout
+                    )));
+                }
+                Err(_) => tokio::time::sleep(Duration::from_millis(500)).await,
+            }
+        }
+    }
+}
+
+impl Drop for LocalNode {
+    fn drop(&mut self) {
+        match &mut self.handle {
+            NodeHandle::Container(name) => {
+                let _ = Command::new("docker")
+                    .args(["stop", name])
+                    .stdout(Stdio::null())
+                    .status();
+            }
+            NodeHandle::Process(child) => {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        }
+    }
+}
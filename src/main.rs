@@ -0,0 +1,21 @@
+use clap::Parser;
+use network_faucet::FaucetError;
+use tokio::task::LocalSet;
+use tracing_subscriber::EnvFilter;
+
+mod cli;
+
+#[tokio::main]
+async fn main() -> Result<(), FaucetError> {
+    let cli = cli::Cli::parse();
+    // Logs go to stderr, keeping stdout to the output of the commands. `RUST_LOG` overrides the
+    // default of warnings plus the faucet's own info events, e.g. the REST access log.
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| EnvFilter::new("warn,network_faucet=info")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+    LocalSet::new().run_until(cli.execute()).await
+}
@@ -0,0 +1,847 @@
+use std::{fmt, str::FromStr, sync::LazyLock};
+
+use faucet_notes::mint_output_note_tagged;
+pub use faucet_notes::{
+    create_p2id_note_exact, create_p2ide_note_exact, reclaim_height, unlock_height, AuxData,
+    MintNoteKind, RequestSource, MINT_NOTE_AUX,
+};
+use miden_client::{
+    account::{Account, AccountId, AccountStorageMode},
+    asset::FungibleAsset,
+    crypto::FeltRng,
+    note::{
+        Note, NoteAssets, NoteExecutionHint, NoteFile, NoteId, NoteInputs, NoteMetadata,
+        NoteRecipient, NoteScript, NoteTag, NoteType,
+    },
+    transaction::{TransactionId, TransactionRequest},
+    Felt, Word,
+};
+use miden_lib::note::{create_burn_note, WellKnownNote};
+use miden_objects::{note::NoteExecutionMode, NoteError, MAX_OUTPUT_NOTES_PER_TX};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cache::FaucetSnapshot,
+    ledger::MintRecord,
+    node::{FaucetNode, StoredNote, TransactionCost},
+    pause::compile_note_script,
+    proof::prove_note,
+    tx::{ConsumeMode, TxBuilder, TxPolicy},
+    FaucetError,
+};
+
+/// Storage slot of the network faucet holding the owner account ID.
+pub const OWNER_SLOT: u8 = 2;
+
+const MINT_MASM: &str = include_str!("../masm/mint.masm");
+
+const PUBLIC_MINT_MASM: &str = include_str!("../masm/public_mint.masm");
+
+/// Script of MINT notes for private output notes, compiled on first use. The source is embedded,
+/// so a failure is permanent and kept as the assembler's message.
+static MINT_SCRIPT: LazyLock<Result<NoteScript, String>> =
+    LazyLock::new(|| compile_note_script(MINT_MASM));
+
+/// Script of MINT notes for public output notes, compiled like [`MINT_SCRIPT`].
+static PUBLIC_MINT_SCRIPT: LazyLock<Result<NoteScript, String>> =
+    LazyLock::new(|| compile_note_script(PUBLIC_MINT_MASM));
+
+fn mint_script() -> Result<NoteScript, FaucetError> {
+    MINT_SCRIPT.clone().map_err(FaucetError::Script)
+}
+
+fn public_mint_script() -> Result<NoteScript, FaucetError> {
+    PUBLIC_MINT_SCRIPT.clone().map_err(FaucetError::Script)
+}
+
+/// Parses the hex-encoded serial number of a P2ID note supplied by a user.
+pub fn parse_serial_num(input: &str) -> Result<Word, FaucetError> {
+    Word::try_from(input.trim())
+        .map_err(|err| FaucetError::InvalidSerialNumber(input.to_string(), err.to_string()))
+}
+
+/// Parses the type of a minted note supplied by a user, `public` or `private`.
+pub fn parse_note_type(input: &str) -> Result<NoteType, FaucetError> {
+    match input.trim().to_ascii_lowercase().as_str() {
+        "public" => Ok(NoteType::Public),
+        "private" => Ok(NoteType::Private),
+        _ => Err(FaucetError::InvalidNoteType(input.to_string())),
+    }
+}
+
+/// How the output notes of mints are tagged, which decides who picks them up.
+///
+/// Written as `account_id`, `network` or `use_case:<id>[:<payload>]` in the configuration and the
+/// APIs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum NoteTagStrategy {
+    /// The tag of the recipient, so its wallet finds the note through sync.
+    #[default]
+    AccountId,
+    /// The network-execution tag of a network account recipient, so the network consumes the
+    /// note into it without a transaction of the recipient. Only public notes carry such tags.
+    Network,
+    /// A use-case tag for network execution, for the network accounts watching the tags of
+    /// `use_case_id`. Only public notes carry such tags.
+    UseCase { use_case_id: u16, payload: u16 },
+}
+
+impl NoteTagStrategy {
+    /// Tag of the output note of a mint to `recipient`.
+    pub fn tag(self, recipient: AccountId) -> Result<NoteTag, FaucetError> {
+        match self {
+            Self::AccountId => Ok(NoteTag::from_account_id(recipient)),
+            // Tags of network accounts are network-execution tags.
+            Self::Network if recipient.storage_mode() == AccountStorageMode::Network => {
+                Ok(NoteTag::from_account_id(recipient))
+            }
+            Self::Network => Err(FaucetError::InvalidNoteTag(
+                self.to_string(),
+                format!("{recipient} is not a network account"),
+            )),
+            Self::UseCase {
+                use_case_id,
+                payload,
+            } => network_use_case_tag(use_case_id, payload)
+                .map_err(|err| FaucetError::InvalidNoteTag(self.to_string(), err.to_string())),
+        }
+    }
+
+    /// Strategy that tagged `note`, minted to `recipient`, or `None` for tags the faucet does not
+    /// write.
+    pub fn of_note(note: &Note, recipient: AccountId) -> Option<Self> {
+        let tag = note.metadata().tag();
+        let bits = tag.as_u32();
+        let use_case = Self::UseCase {
+            use_case_id: ((bits >> 16) & 0x3fff) as u16,
+            payload: bits as u16,
+        };
+        [Self::AccountId, use_case]
+            .into_iter()
+            .find(|strategy| strategy.tag(recipient).ok() == Some(tag))
+    }
+
+    /// Whether the tagged notes must be public, the type of the mints that name no type.
+    pub fn requires_public_notes(self) -> bool {
+        matches!(self, Self::Network | Self::UseCase { .. })
+    }
+}
+
+fn network_use_case_tag(use_case_id: u16, payload: u16) -> Result<NoteTag, NoteError> {
+    NoteTag::for_public_use_case(use_case_id, payload, NoteExecutionMode::Network)
+}
+
+impl fmt::Display for NoteTagStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AccountId => f.write_str("account_id"),
+            Self::Network => f.write_str("network"),
+            Self::UseCase {
+                use_case_id,
+                payload,
+            } => write!(f, "use_case:{use_case_id}:{payload}"),
+        }
+    }
+}
+
+impl FromStr for NoteTagStrategy {
+    type Err = FaucetError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| FaucetError::InvalidNoteTag(input.to_string(), reason.into());
+        let trimmed = input.trim().to_ascii_lowercase();
+        match trimmed.as_str() {
+            "account_id" => return Ok(Self::AccountId),
+            "network" => return Ok(Self::Network),
+            _ => {}
+        }
+        let Some(use_case) = trimmed.strip_prefix("use_case:") else {
+            return Err(invalid(
+                "expected `account_id`, `network` or `use_case:<id>[:<payload>]`",
+            ));
+        };
+        let (use_case_id, payload) = use_case.split_once(':').unwrap_or((use_case, "0"));
+        let use_case_id = use_case_id
+            .parse()
+            .map_err(|_| invalid("the use case ID is not a number"))?;
+        let payload = payload
+            .parse()
+            .map_err(|_| invalid("the payload is not a 16-bit number"))?;
+        network_use_case_tag(use_case_id, payload).map_err(|err| invalid(&err.to_string()))?;
+        Ok(Self::UseCase {
+            use_case_id,
+            payload,
+        })
+    }
+}
+
+impl TryFrom<String> for NoteTagStrategy {
+    type Error = FaucetError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<NoteTagStrategy> for String {
+    fn from(strategy: NoteTagStrategy) -> Self {
+        strategy.to_string()
+    }
+}
+
+/// Checks that `recipient` can consume the P2ID note of a mint of `faucet_id`: faucets cannot
+/// receive fungible assets, so notes minted to them are never claimed.
+///
+/// Malformed IDs are already refused when parsed, see
+/// [`parse_account_id`](crate::account::parse_account_id).
+pub fn check_recipient(faucet_id: AccountId, recipient: AccountId) -> Result<(), FaucetError> {
+    let reason = if recipient == faucet_id {
+        "the faucet cannot mint to itself"
+    } else if !recipient.is_regular_account() {
+        "faucets cannot consume minted notes"
+    } else {
+        return Ok(());
+    };
+    Err(FaucetError::InvalidRecipient(recipient, reason.into()))
+}
+
+/// Checks that a public or network `recipient` has a state on chain, failing with
+/// [`FaucetError::RecipientNotFound`] otherwise. Private accounts keep their state off chain and
+/// pass unchecked.
+///
+/// New public accounts only reach the chain with their first transaction, so this refuses fresh
+/// wallets; `serve` only checks with `service.require_onchain_recipients`.
+pub async fn check_recipient_on_chain<N: FaucetNode>(
+    node: &mut N,
+    recipient: AccountId,
+) -> Result<(), FaucetError> {
+    if recipient.storage_mode() == AccountStorageMode::Private {
+        return Ok(());
+    }
+    match node.fetch_account(recipient).await? {
+        Some(_) => Ok(()),
+        None => Err(FaucetError::RecipientNotFound(recipient)),
+    }
+}
+
+/// Amounts a mint may request, read from the `[mint]` section of the configuration.
+///
+/// Enforced by `serve` and the `mint` binary; burns and re-submissions of existing mints are not
+/// bounded.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AmountConfig {
+    /// Amount minted when the request names none.
+    pub default_amount: u64,
+    pub min_amount: u64,
+    pub max_amount: u64,
+    /// Mint the nearest bound instead of rejecting out-of-range amounts.
+    pub clamp: bool,
+}
+
+impl Default for AmountConfig {
+    fn default() -> Self {
+        Self {
+            default_amount: 50,
+            min_amount: 1,
+            max_amount: 1_000,
+            clamp: false,
+        }
+    }
+}
+
+impl AmountConfig {
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        if self.min_amount == 0 {
+            return Err(FaucetError::Config(
+                "mint.min_amount must be positive".into(),
+            ));
+        }
+        if self.min_amount > self.max_amount {
+            return Err(FaucetError::Config(format!(
+                "mint.min_amount {} exceeds mint.max_amount {}",
+                self.min_amount, self.max_amount
+            )));
+        }
+        if !(self.min_amount..=self.max_amount).contains(&self.default_amount) {
+            return Err(FaucetError::Config(format!(
+                "mint.default_amount {} is outside {}..={}",
+                self.default_amount, self.min_amount, self.max_amount
+            )));
+        }
+        if self.max_amount > FungibleAsset::MAX_AMOUNT {
+            return Err(FaucetError::Config(format!(
+                "mint.max_amount exceeds the largest fungible amount, {}",
+                FungibleAsset::MAX_AMOUNT
+            )));
+        }
+        Ok(())
+    }
+
+    /// Amount to mint for a request of `requested`, the default amount if `None`.
+    ///
+    /// Fails with [`FaucetError::AmountOutOfRange`] for amounts outside the bounds, unless
+    /// [`clamp`](Self::clamp) is set.
+    pub fn resolve(&self, requested: Option<u64>) -> Result<u64, FaucetError> {
+        let Some(amount) = requested else {
+            return Ok(self.default_amount);
+        };
+        if (self.min_amount..=self.max_amount).contains(&amount) {
+            return Ok(amount);
+        }
+        if self.clamp {
+            return Ok(amount.clamp(self.min_amount, self.max_amount));
+        }
+        Err(FaucetError::AmountOutOfRange {
+            amount,
+            min: self.min_amount,
+            max: self.max_amount,
+        })
+    }
+}
+
+/// Optional parameters of [`mint_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct MintOptions {
+    /// Serial number of the output note, drawn from the client RNG when unset.
+    pub serial_num: Option<Word>,
+    /// Kind of output note, a plain P2ID note by default.
+    pub note_kind: MintNoteKind,
+    /// Type of the output note, the [`TxPolicy`] note type by default. Recipients only discover
+    /// public notes through sync; private ones are delivered as note files.
+    pub note_type: Option<NoteType>,
+    /// Campaign and source written to the aux value of the MINT note and its output note.
+    pub aux: AuxData,
+    /// How the output note is tagged, by default `service.note_tag` in the service and the tag
+    /// of the recipient elsewhere.
+    pub note_tag: Option<NoteTagStrategy>,
+}
+
+/// Rebuilds the output note of a mint from its ledger record.
+///
+/// Fails for mints recorded before the ledger stored serial numbers.
+pub fn rebuild_mint_note(record: &MintRecord) -> Result<Note, FaucetError> {
+    let invalid = |what: &str| FaucetError::Ledger(format!("mint {} has {what}", record.id));
+
+    let faucet_id =
+        AccountId::from_hex(&record.faucet_id).map_err(|_| invalid("a bad faucet ID"))?;
+    let recipient =
+        AccountId::from_hex(&record.recipient).map_err(|_| invalid("a bad recipient"))?;
+    let serial_num = record
+        .serial_num
+        .as_deref()
+        .ok_or_else(|| invalid("no serial number"))
+        .and_then(|hex| Word::try_from(hex).map_err(|_| invalid("a bad serial number")))?;
+    let note_kind = MintNoteKind::from_reclaim_height(record.reclaim_block.map(Into::into))
+        .with_unlock_height(record.unlock_block.map(Into::into));
+
+    let aux = record
+        .aux_data()
+        .ok_or_else(|| invalid("an aux value the faucet does not write"))?;
+    let note = mint_output_note_tagged(
+        faucet_id,
+        recipient,
+        record.amount,
+        serial_num,
+        note_kind,
+        record.note_type(),
+        aux,
+        record
+            .note_tag
+            .map_or_else(|| NoteTag::from_account_id(recipient), NoteTag::from),
+    )?;
+    if note.id().to_hex() != record.note_id {
+        return Err(invalid("a note that does not match its recorded ID"));
+    }
+    Ok(note)
+}
+
+/// Options minting the note of a recorded mint again, e.g. after a reorg dropped its transaction.
+///
+/// The note keeps its serial number and therefore its nullifier, so the recipient can consume at
+/// most one of the two notes.
+pub fn remint_options(record: &MintRecord) -> Result<MintOptions, FaucetError> {
+    let note = rebuild_mint_note(record)?;
+    Ok(MintOptions {
+        serial_num: Some(note.recipient().serial_num()),
+        note_kind: MintNoteKind::from_reclaim_height(record.reclaim_block.map(Into::into))
+            .with_unlock_height(record.unlock_block.map(Into::into)),
+        note_type: Some(record.note_type()),
+        // Checked by `rebuild_mint_note`.
+        aux: record.aux_data().unwrap_or_default(),
+        note_tag: Some(
+            AccountId::from_hex(&record.recipient)
+                .ok()
+                .and_then(|recipient| NoteTagStrategy::of_note(&note, recipient))
+                .ok_or_else(|| {
+                    FaucetError::Ledger(format!(
+                        "mint {} has a tag the faucet does not write",
+                        record.id
+                    ))
+                })?,
+        ),
+    })
+}
+
+/// Reads the owner of the network faucet `faucet_id` from its storage.
+///
+/// The faucet must be tracked by `node`.
+pub async fn faucet_owner<N: FaucetNode>(
+    node: &mut N,
+    faucet_id: AccountId,
+) -> Result<AccountId, FaucetError> {
+    Ok(FaucetSnapshot::read(node, faucet_id).await?.owner_id)
+}
+
+/// Owner stored by the network faucet `faucet`.
+pub(crate) fn owner_of(faucet: &Account) -> Result<AccountId, FaucetError> {
+    let owner_word = faucet.storage().get_item(OWNER_SLOT)?;
+    Ok(AccountId::new_unchecked([owner_word[3], owner_word[2]]))
+}
+
+/// Result of [`mint_p2id`].
+#[derive(Debug, Clone)]
+pub struct MintOutcome {
+    pub transaction_id: TransactionId,
+    /// Faucet owner the MINT note was sent from.
+    pub owner_id: AccountId,
+    /// The note the faucet will emit once the MINT note is executed, a P2IDE note for
+    /// reclaimable mints.
+    pub p2id_note: Note,
+    /// Cost of the transaction, if the node measured it.
+    pub cost: Option<TransactionCost>,
+}
+
+/// Submits a MINT note to the network faucet `faucet_id` that pays `amount` to `recipient`.
+///
+/// The transaction is sent from the faucet owner read from the faucet storage, so the owner
+/// account must be managed by `node`.
+pub async fn mint_p2id<N: FaucetNode>(
+    node: &mut N,
+    faucet_id: AccountId,
+    recipient: AccountId,
+    amount: u64,
+) -> Result<MintOutcome, FaucetError> {
+    mint_with_options(node, faucet_id, recipient, amount, MintOptions::default()).await
+}
+
+/// Like [`mint_p2id`], with the output note configured by `options`.
+///
+/// Callers that choose the serial number can compute the resulting note with
+/// [`faucet_notes::mint_output_note`] before the mint is submitted.
+pub async fn mint_with_options<N: FaucetNode>(
+    node: &mut N,
+    faucet_id: AccountId,
+    recipient: AccountId,
+    amount: u64,
+    options: MintOptions,
+) -> Result<MintOutcome, FaucetError> {
+    let mut batch = mint_batch(
+        node,
+        faucet_id,
+        vec![BatchMint {
+            recipient,
+            amount,
+            options,
+        }],
+    )
+    .await?;
+
+    Ok(MintOutcome {
+        transaction_id: batch.transaction_id,
+        owner_id: batch.owner_id,
+        p2id_note: batch.p2id_notes.remove(0),
+        cost: batch.cost,
+    })
+}
+
+/// One payment of [`mint_batch`].
+#[derive(Debug, Clone)]
+pub struct BatchMint {
+    pub recipient: AccountId,
+    pub amount: u64,
+    pub options: MintOptions,
+}
+
+/// Result of [`mint_batch`].
+#[derive(Debug, Clone)]
+pub struct BatchMintOutcome {
+    pub transaction_id: TransactionId,
+    /// Faucet owner the MINT notes were sent from.
+    pub owner_id: AccountId,
+    /// The notes the faucet will emit, in the order of the payments.
+    pub p2id_notes: Vec<Note>,
+    /// Cost of the transaction, if the node measured it.
+    pub cost: Option<TransactionCost>,
+}
+
+/// Submits one MINT note per payment of `mints` to the network faucet `faucet_id`, all in a single
+/// transaction of the faucet owner.
+///
+/// Fails with [`FaucetError::BatchSize`] unless there are between 1 and
+/// [`MAX_OUTPUT_NOTES_PER_TX`] payments.
+pub async fn mint_batch<N: FaucetNode>(
+    node: &mut N,
+    faucet_id: AccountId,
+    mints: Vec<BatchMint>,
+) -> Result<BatchMintOutcome, FaucetError> {
+    let faucet = FaucetSnapshot::read(node, faucet_id).await?;
+    mint_batch_from(node, &faucet, mints).await
+}
+
+/// [`mint_batch`] from a faucet whose state was read already, e.g. by a
+/// [`FaucetCache`](crate::cache::FaucetCache).
+pub async fn mint_batch_from<N: FaucetNode>(
+    node: &mut N,
+    faucet: &FaucetSnapshot,
+    mints: Vec<BatchMint>,
+) -> Result<BatchMintOutcome, FaucetError> {
+    let owner_id = faucet.owner_id;
+    let (request, p2id_notes) = batch_request(node, faucet, mints)?;
+    let transaction_id = node.submit_transaction(owner_id, request).await?;
+
+    Ok(BatchMintOutcome {
+        transaction_id,
+        owner_id,
+        p2id_notes,
+        cost: node.take_transaction_cost(transaction_id),
+    })
+}
+
+/// Executes the transaction [`mint_batch`] would submit for `mints`, without submitting it, and
+/// returns its cost.
+pub async fn estimate_mint_batch<N: FaucetNode>(
+    node: &mut N,
+    faucet_id: AccountId,
+    mints: Vec<BatchMint>,
+) -> Result<TransactionCost, FaucetError> {
+    let faucet = FaucetSnapshot::read(node, faucet_id).await?;
+    let (request, _) = batch_request(node, &faucet, mints)?;
+    node.execute_transaction(faucet.owner_id, request).await
+}
+
+/// Owner transaction of [`mint_batch`] and the notes it makes the faucet emit.
+fn batch_request<N: FaucetNode>(
+    node: &mut N,
+    faucet: &FaucetSnapshot,
+    mints: Vec<BatchMint>,
+) -> Result<(TransactionRequest, Vec<Note>), FaucetError> {
+    if mints.is_empty() || mints.len() > MAX_OUTPUT_NOTES_PER_TX {
+        return Err(FaucetError::BatchSize {
+            size: mints.len(),
+            max: MAX_OUTPUT_NOTES_PER_TX,
+        });
+    }
+
+    let policy = TxPolicy::default();
+    let mut mint_notes = Vec::with_capacity(mints.len());
+    let mut p2id_notes = Vec::with_capacity(mints.len());
+    for mint in mints {
+        // Compute the output P2ID note
+        let serial_num = match mint.options.serial_num {
+            Some(serial_num) => serial_num,
+            None => node.rng().draw_word(),
+        };
+        let tag_strategy = mint.options.note_tag.unwrap_or_default();
+        let output_note_tag = tag_strategy.tag(mint.recipient)?;
+        let note_type = match mint.options.note_type {
+            Some(note_type) => note_type,
+            None if tag_strategy.requires_public_notes() => NoteType::Public,
+            None => policy.note_type,
+        };
+        if tag_strategy.requires_public_notes() && note_type != NoteType::Public {
+            return Err(FaucetError::InvalidNoteTag(
+                tag_strategy.to_string(),
+                "tags for network execution need public notes".into(),
+            ));
+        }
+        let p2id_note = mint_output_note_tagged(
+            faucet.faucet_id,
+            mint.recipient,
+            mint.amount,
+            serial_num,
+            mint.options.note_kind,
+            note_type,
+            mint.options.aux,
+            output_note_tag,
+        )?;
+
+        let mint_note = mint_note(
+            faucet.faucet_id,
+            faucet.owner_id,
+            &p2id_note,
+            mint.amount,
+            mint.options.aux,
+            node.rng(),
+        )?;
+        mint_notes.push(mint_note);
+        p2id_notes.push(p2id_note);
+    }
+
+    let mint_transaction_request = TxBuilder::with_policy(policy)
+        .output_notes(mint_notes)
+        .build()?;
+
+    Ok((mint_transaction_request, p2id_notes))
+}
+
+/// Result of [`burn`].
+#[derive(Debug, Clone)]
+pub struct BurnOutcome {
+    pub transaction_id: TransactionId,
+    /// The BURN note the network faucet consumes to reduce its issued supply.
+    pub burn_note: Note,
+}
+
+/// MINT note sent by the faucet owner `sender` that makes the network faucet `faucet_id` emit
+/// `output_note`, which must hold `amount` tokens of the faucet.
+///
+/// The MINT note carries `aux` as well, so the attribution is visible on chain even when the
+/// output note is private. Its script works like the MINT script of miden-lib, but fails while the
+/// faucet is paused, see [`crate::pause`].
+///
+/// The MINT script of miden-lib only passes the recipient digest on, and the faucet cannot emit a
+/// public note without the script, inputs and serial number behind it. For public output notes,
+/// the script of `masm/public_mint.masm` takes them as inputs of the MINT note as well and
+/// rebuilds the recipient, which puts them into the advice map of the faucet transaction. The
+/// network looks up the script of the output note by its root, so it must be a script the network
+/// knows, like P2ID.
+pub fn mint_note(
+    faucet_id: AccountId,
+    sender: AccountId,
+    output_note: &Note,
+    amount: u64,
+    aux: AuxData,
+    rng: &mut impl FeltRng,
+) -> Result<Note, FaucetError> {
+    let output_metadata = output_note.metadata();
+    let output_recipient = output_note.recipient();
+    let execution_hint = NoteExecutionHint::always();
+    let mut inputs = output_recipient.digest().as_elements().to_vec();
+    inputs.extend([
+        Felt::new(u64::from(execution_hint)),
+        Felt::from(output_metadata.note_type()),
+        output_metadata.aux(),
+        output_metadata.tag().into(),
+        Felt::new(amount),
+    ]);
+    let script = match output_metadata.note_type() {
+        NoteType::Public => {
+            inputs.extend([Felt::new(0); 3]);
+            inputs.extend(output_recipient.script().root().as_elements());
+            inputs.extend(output_recipient.serial_num().as_elements());
+            inputs.extend(output_recipient.inputs().values());
+            public_mint_script()?
+        }
+        _ => mint_script()?,
+    };
+    let recipient = NoteRecipient::new(rng.draw_word(), script, NoteInputs::new(inputs)?);
+    // MINT notes are public, so the network faucet can execute them.
+    let metadata = NoteMetadata::new(
+        sender,
+        NoteType::Public,
+        NoteTag::from_account_id(faucet_id),
+        execution_hint,
+        aux.to_felt(),
+    )?;
+    Ok(Note::new(NoteAssets::default(), metadata, recipient))
+}
+
+/// Sends `amount` tokens of `faucet_id` from `account_id` back to the faucet in a BURN note.
+///
+/// The account must be a wallet managed by `node` holding the tokens. The supply shrinks once the
+/// network consumes the note against the faucet.
+pub async fn burn<N: FaucetNode>(
+    node: &mut N,
+    account_id: AccountId,
+    faucet_id: AccountId,
+    amount: u64,
+) -> Result<BurnOutcome, FaucetError> {
+    let policy = TxPolicy::default();
+    let asset = FungibleAsset::new(faucet_id, amount)?;
+    let burn_note = create_burn_note(account_id, faucet_id, asset.into(), policy.aux, node.rng())?;
+
+    let request = TxBuilder::with_policy(policy)
+        .output_notes([burn_note.clone()])
+        .build()?;
+    let transaction_id = node.submit_transaction(account_id, request).await?;
+
+    Ok(BurnOutcome {
+        transaction_id,
+        burn_note,
+    })
+}
+
+/// Checks that `note`, built locally, is the output note of the mint transaction
+/// `mint_transaction_id` before it is consumed.
+///
+/// The transaction record of the store must hold a MINT note targeting the recipient digest of
+/// `note`, and, once the node knows the note, its inclusion proof must lead from the commitment of
+/// `note` to its block. A note rebuilt with a drifted serial number, script or inputs fails here
+/// with [`FaucetError::NoteLinkage`] or [`FaucetError::InvalidInclusionProof`] rather than as an
+/// opaque rejection of the consume transaction. Mints whose transaction the store no longer knows
+/// are only checked against the inclusion proof.
+pub async fn verify_mint_note<N: FaucetNode>(
+    node: &mut N,
+    mint_transaction_id: TransactionId,
+    note: &Note,
+) -> Result<(), FaucetError> {
+    if let Some(output_notes) = node.transaction_output_notes(mint_transaction_id).await? {
+        let digest = note.recipient().digest();
+        let mint_roots = [
+            mint_script()?.root(),
+            public_mint_script()?.root(),
+            // The MINT script of miden-lib made the MINT notes of mints recorded before the
+            // faucet could be paused.
+            WellKnownNote::MINT.script_root(),
+        ];
+        let linked = output_notes.iter().any(|output| {
+            mint_roots.contains(&output.recipient().script().root())
+                && output.recipient().inputs().values().get(..4) == Some(digest.as_elements())
+        });
+        if !linked {
+            return Err(FaucetError::NoteLinkage(
+                note.id().to_hex(),
+                format!(
+                    "no MINT note of transaction {} targets its recipient digest {}",
+                    mint_transaction_id.to_hex(),
+                    digest.to_hex()
+                ),
+            ));
+        }
+    }
+    prove_note(node, note).await?;
+    Ok(())
+}
+
+/// Consumes the output note of the mint transaction `mint_transaction_id` into `account_id` once
+/// [`verify_mint_note`] accepts it, as `mode` asks.
+pub async fn consume_mint_note<N: FaucetNode>(
+    node: &mut N,
+    account_id: AccountId,
+    mint_transaction_id: TransactionId,
+    note: Note,
+    mode: ConsumeMode,
+) -> Result<TransactionId, FaucetError> {
+    verify_mint_note(node, mint_transaction_id, &note).await?;
+    let policy = TxPolicy {
+        consume: mode,
+        ..TxPolicy::default()
+    };
+    consume_note_with_policy(node, account_id, note, policy).await
+}
+
+/// Consumes `note` into `account_id` as the default [`TxPolicy`] asks: with its inclusion proof
+/// once it is committed, without while racing the chain.
+pub async fn consume_note<N: FaucetNode>(
+    node: &mut N,
+    account_id: AccountId,
+    note: Note,
+) -> Result<TransactionId, FaucetError> {
+    consume_note_with_policy(node, account_id, note, TxPolicy::default()).await
+}
+
+/// Like [`consume_note`], consuming `note` as `policy.consume` asks.
+pub async fn consume_note_with_policy<N: FaucetNode>(
+    node: &mut N,
+    account_id: AccountId,
+    note: Note,
+    policy: TxPolicy,
+) -> Result<TransactionId, FaucetError> {
+    let stored = consumable_note(node, note, policy.consume).await?;
+    let consume_request = TxBuilder::with_policy(policy)
+        .consume(vec![stored])
+        .build()?;
+
+    node.submit_transaction(account_id, consume_request).await
+}
+
+/// `note` as consumed under `mode`.
+///
+/// Unless `mode` is [`ConsumeMode::Unauthenticated`], the note is authenticated with the
+/// inclusion proof of the store, or else with a proof fetched from the node and imported into the
+/// store. Notes the node does not know yet are consumed unauthenticated by
+/// [`ConsumeMode::Auto`] and refused by [`ConsumeMode::Authenticated`].
+async fn consumable_note<N: FaucetNode>(
+    node: &mut N,
+    note: Note,
+    mode: ConsumeMode,
+) -> Result<StoredNote, FaucetError> {
+    let unauthenticated = |note| StoredNote {
+        note,
+        authenticated: false,
+    };
+    if mode == ConsumeMode::Unauthenticated {
+        return Ok(unauthenticated(note));
+    }
+    if let Some(stored) = node.stored_note(note.id()).await? {
+        if stored.authenticated {
+            return Ok(stored);
+        }
+    }
+    match prove_note(node, &note).await? {
+        Some(proof) => {
+            node.import_note(NoteFile::NoteWithProof(note.clone(), proof))
+                .await?;
+            Ok(StoredNote {
+                note,
+                authenticated: true,
+            })
+        }
+        None if mode == ConsumeMode::Auto => Ok(unauthenticated(note)),
+        None => Err(FaucetError::InputNote(format!(
+            "note {} is not committed yet, it cannot be consumed authenticated",
+            note.id()
+        ))),
+    }
+}
+
+/// Consumes notes of the store into `account_id`.
+///
+/// Notes with an inclusion proof in the store are consumed as authenticated notes, the others
+/// without waiting for their proof.
+pub async fn consume_stored_notes<N: FaucetNode>(
+    node: &mut N,
+    account_id: AccountId,
+    note_ids: &[NoteId],
+) -> Result<TransactionId, FaucetError> {
+    let mut notes = Vec::with_capacity(note_ids.len());
+    for &note_id in note_ids {
+        let stored = node
+            .stored_note(note_id)
+            .await?
+            .ok_or_else(|| FaucetError::InputNote(format!("note {note_id} is not in the store")))?;
+        notes.push(stored);
+    }
+
+    node.submit_transaction(account_id, TxBuilder::new().consume(notes).build()?)
+        .await
+}
+
+/// Parses a hex-encoded note ID supplied by a user.
+pub fn parse_note_id(input: &str) -> Result<NoteId, FaucetError> {
+    NoteId::try_from_hex(input.trim())
+        .map_err(|err| FaucetError::InvalidNoteId(input.to_string(), err.to_string()))
+}
+
+/// Parses a hex-encoded transaction ID supplied by a user.
+pub fn parse_transaction_id(input: &str) -> Result<TransactionId, FaucetError> {
+    Word::try_from(input.trim())
+        .map(TransactionId::from)
+        .map_err(|err| FaucetError::InvalidTransactionId(input.to_string(), err.to_string()))
+}
+
+/// Returns the balance of `faucet_id` tokens held by `account_id` according to the local store.
+pub async fn get_balance<N: FaucetNode>(
+    node: &mut N,
+    account_id: AccountId,
+    faucet_id: AccountId,
+) -> Result<u64, FaucetError> {
+    let account = node
+        .get_account(account_id)
+        .await?
+        .ok_or(FaucetError::AccountNotFound(account_id))?;
+
+    Ok(account.vault().get_balance(faucet_id)?)
+}
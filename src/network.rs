@@ -0,0 +1,277 @@
+//! Several networks served from one process.
+//!
+//! The top-level settings of the configuration describe the default network, named by
+//! `service.network`. Each `[networks.<name>]` section adds another one with its own node, client
+//! store, keystore, ledger and faucet, e.g. devnet next to testnet, while the APIs, access tiers,
+//! API keys and the other secrets stay shared. Requests pick their network with a `network`
+//! field, or the `network` query parameter of the routes without a body, and go to the default
+//! network without one.
+//!
+//! Access tiers, drip limits and GitHub sessions are kept in the ledger of the default network.
+//!
+//! Within a network, requests mint from the faucet of `service.faucet_id` unless their `token`
+//! names one of the token faucets of `[service.tokens]`, see
+//! [`TokenFaucetConfig`](crate::service::TokenFaucetConfig).
+//!
+//! A client store or keystore only ever serves one network: [`claim_for_network`] records the
+//! network of each next to it on first use and refuses to open it for another one afterwards.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use miden_client::account::AccountId;
+use miden_objects::address::NetworkId;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    account::parse_account_id,
+    explorer::{Explorer, ExplorerConfig},
+    mint::AmountConfig,
+    rpc::RpcConfig,
+    service::{FaucetHandle, TokenFaucetConfig},
+    FaucetError,
+};
+
+/// Name of the Miden mainnet, see [`crate::config::Config::is_mainnet`].
+pub const MAINNET: &str = "mainnet";
+
+/// Network served next to the default one, read from a `[networks.<name>]` section of the
+/// configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Node of the network; `[networks.<name>.rpc]` takes the same settings as `[rpc]`.
+    pub rpc: RpcConfig,
+    pub store_path: PathBuf,
+    pub keystore_path: PathBuf,
+    pub ledger_path: PathBuf,
+    /// Network faucet minting on this network.
+    pub faucet_id: String,
+    /// Explorer of this network, following its node like `[explorer]` when unset.
+    #[serde(default)]
+    pub explorer: Option<ExplorerConfig>,
+    /// Token faucets of this network, like `[service.tokens]` for the default network.
+    #[serde(default)]
+    pub tokens: BTreeMap<String, TokenFaucetConfig>,
+}
+
+/// Checks that `name` can be sent as the `network` of a request.
+pub fn validate_network_name(name: &str) -> Result<(), FaucetError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid {
+        return Err(FaucetError::Config(format!(
+            "network name `{name}` must be lowercase letters, digits, `-` and `_`"
+        )));
+    }
+    Ok(())
+}
+
+/// Extension of the file recording the network of a client store or keystore, e.g.
+/// `store.sqlite3.network` next to `store.sqlite3`.
+pub const NETWORK_FILE_EXTENSION: &str = "network";
+
+/// File recording the network of the client store or keystore at `path`.
+pub fn network_file(path: &Path) -> PathBuf {
+    let mut file = path.as_os_str().to_owned();
+    file.push(".");
+    file.push(NETWORK_FILE_EXTENSION);
+    PathBuf::from(file)
+}
+
+/// Fails with [`FaucetError::NetworkMismatch`] if the client store or keystore at `path` was used
+/// with another network than `network`, and records `network` for it if it was not recorded yet.
+///
+/// Files from before networks were recorded are claimed by the first network opening them.
+pub fn claim_for_network(path: &Path, network: &str) -> Result<(), FaucetError> {
+    let file = network_file(path);
+    match fs::read_to_string(&file) {
+        Ok(recorded) if recorded.trim() == network => Ok(()),
+        Ok(recorded) => Err(FaucetError::NetworkMismatch {
+            path: path.display().to_string(),
+            recorded: recorded.trim().to_string(),
+            configured: network.to_string(),
+        }),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            Ok(fs::write(&file, format!("{network}\n"))?)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Faucets and explorer of one served network.
+#[derive(Clone)]
+pub struct Network {
+    /// Main faucet, minting for requests that do not name a token.
+    pub handle: FaucetHandle,
+    pub explorer: Option<Explorer>,
+    /// Token faucets by token symbol.
+    pub tokens: BTreeMap<String, FaucetHandle>,
+    /// Network whose prefix bech32 account IDs of requests must carry, see
+    /// [`Config::account_network`](crate::config::Config::account_network).
+    pub account_network: Option<NetworkId>,
+}
+
+impl Network {
+    fn new(handle: FaucetHandle, explorer: Option<Explorer>) -> Self {
+        Self {
+            handle,
+            explorer,
+            tokens: BTreeMap::new(),
+            account_network: None,
+        }
+    }
+
+    /// Parses an account ID of a request to this network, see [`parse_account_id`].
+    pub fn parse_account_id(&self, input: &str) -> Result<AccountId, FaucetError> {
+        parse_account_id(input, self.account_network.as_ref())
+    }
+
+    /// Faucet minting `token`, or the main faucet for `None`.
+    ///
+    /// Fails with [`FaucetError::UnknownToken`] for tokens not served.
+    pub fn faucet(&self, token: Option<&str>) -> Result<&FaucetHandle, FaucetError> {
+        let Some(token) = token else {
+            return Ok(&self.handle);
+        };
+        self.tokens.get(token).ok_or_else(|| {
+            let served = self.tokens.keys().map(String::as_str);
+            FaucetError::UnknownToken(token.to_string(), served.collect::<Vec<_>>().join(", "))
+        })
+    }
+
+    /// Handle minting `token` for a caller granted `amounts`. Token faucets keep their own
+    /// amounts.
+    pub fn mint_handle(
+        &self,
+        token: Option<&str>,
+        amounts: AmountConfig,
+    ) -> Result<FaucetHandle, FaucetError> {
+        match token {
+            None => Ok(self.handle.with_amounts(amounts)),
+            Some(_) => self.faucet(token).cloned(),
+        }
+    }
+}
+
+/// Networks of the APIs, selected by the `network` of a request.
+#[derive(Clone)]
+pub struct Networks {
+    default: String,
+    networks: Arc<BTreeMap<String, Network>>,
+}
+
+impl Networks {
+    /// Serves `handle` as the default network `name`.
+    pub fn new(name: impl Into<String>, handle: FaucetHandle, explorer: Option<Explorer>) -> Self {
+        let default = name.into();
+        let networks = BTreeMap::from([(default.clone(), Network::new(handle, explorer))]);
+        Self {
+            default,
+            networks: Arc::new(networks),
+        }
+    }
+
+    /// Also serves `handle` as network `name`.
+    pub fn with_network(
+        mut self,
+        name: impl Into<String>,
+        handle: FaucetHandle,
+        explorer: Option<Explorer>,
+    ) -> Self {
+        Arc::make_mut(&mut self.networks).insert(name.into(), Network::new(handle, explorer));
+        self
+    }
+
+    /// Also serves `handle` as the faucet of `token` on network `name`, which must be served.
+    pub fn with_token(
+        mut self,
+        name: &str,
+        token: impl Into<String>,
+        handle: FaucetHandle,
+    ) -> Self {
+        let network = Arc::make_mut(&mut self.networks)
+            .get_mut(name)
+            .expect("tokens are added to served networks");
+        network.tokens.insert(token.into(), handle);
+        self
+    }
+
+    /// Requires the bech32 account IDs of requests to network `name`, which must be served, to
+    /// carry the prefix of `account_network`.
+    pub fn with_account_network(mut self, name: &str, account_network: NetworkId) -> Self {
+        let network = Arc::make_mut(&mut self.networks)
+            .get_mut(name)
+            .expect("account networks are set on served networks");
+        network.account_network = Some(account_network);
+        self
+    }
+
+    /// Name of the network serving requests that do not pick one.
+    pub fn default_name(&self) -> &str {
+        &self.default
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.networks.keys().map(String::as_str)
+    }
+
+    /// Every network served with its name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Network)> {
+        self.networks
+            .iter()
+            .map(|(name, network)| (name.as_str(), network))
+    }
+
+    /// Network `name`, or the default network for `None`.
+    ///
+    /// Fails with [`FaucetError::UnknownNetwork`] for networks not served.
+    pub fn get(&self, name: Option<&str>) -> Result<&Network, FaucetError> {
+        let name = name.unwrap_or(&self.default);
+        self.networks.get(name).ok_or_else(|| {
+            FaucetError::UnknownNetwork(
+                name.to_string(),
+                self.names().collect::<Vec<_>>().join(", "),
+            )
+        })
+    }
+
+    /// Handle of the default network.
+    pub fn default_handle(&self) -> &FaucetHandle {
+        &self.networks[&self.default].handle
+    }
+
+    /// Networks whose handles record their requests under `actor`, see
+    /// [`FaucetHandle::with_actor`].
+    pub fn with_actor(&self, actor: impl Into<String>) -> Self {
+        let actor = actor.into();
+        let networks = self
+            .networks
+            .iter()
+            .map(|(name, network)| {
+                let tokens = network
+                    .tokens
+                    .iter()
+                    .map(|(token, handle)| (token.clone(), handle.with_actor(actor.clone())))
+                    .collect();
+                let network = Network {
+                    handle: network.handle.with_actor(actor.clone()),
+                    explorer: network.explorer.clone(),
+                    tokens,
+                    account_network: network.account_network.clone(),
+                };
+                (name.clone(), network)
+            })
+            .collect();
+        Self {
+            default: self.default.clone(),
+            networks: Arc::new(networks),
+        }
+    }
+}
@@ -0,0 +1,637 @@
+//! Client abstraction used by the faucet flows.
+//!
+//! The flows in [`crate::deploy`] and [`crate::mint`] only talk to the node and the local store
+//! through [`FaucetNode`]. [`NodeClient`] implements it for a real client; tests provide an
+//! in-memory implementation instead.
+
+use std::{collections::BTreeMap, sync::Arc, time::Instant};
+
+use futures::{stream, StreamExt, TryStreamExt};
+
+use miden_client::{
+    account::{Account, AccountId},
+    auth::AuthSecretKey,
+    note::{Note, NoteFile, NoteId, NoteInclusionProof, NoteRelevance, Nullifier},
+    rpc::{domain::note::FetchedNote, GrpcError, NodeRpcClient, RpcError},
+    store::TransactionFilter,
+    transaction::{
+        ExecutedTransaction, OutputNote, TransactionId, TransactionRequest, TransactionScript,
+        TransactionStatus,
+    },
+    ClientError, ClientRng, Word,
+};
+use miden_objects::block::BlockNumber;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::{build_client_with_rpc, FaucetClient, FaucetKeyStore},
+    config::Config,
+    rpc::{build_rpc_client, with_retries, RpcCall, RpcConfig},
+    sync::{block_chunks, scope_note_tags, SyncConfig, SyncStats},
+    tx::tx_scripts,
+    FaucetError,
+};
+
+/// Commitment state of a submitted transaction as seen by the local store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxState {
+    Pending,
+    Committed(BlockNumber),
+    Discarded(String),
+}
+
+/// Resources a transaction consumes, as measured by executing it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionCost {
+    /// Fee paid by the account, in base units of the native asset.
+    pub fee: u64,
+    /// VM cycles of the execution, which drive the proving time.
+    pub cycles: usize,
+}
+
+impl TransactionCost {
+    pub fn of(transaction: &ExecutedTransaction) -> Self {
+        Self {
+            fee: transaction.fee().amount(),
+            cycles: transaction.measurements().total_cycles(),
+        }
+    }
+}
+
+/// A note held by the local store, ready to be consumed.
+#[derive(Debug, Clone)]
+pub struct StoredNote {
+    pub note: Note,
+    /// Whether the store holds the note's inclusion proof, so it can be consumed as an
+    /// authenticated note.
+    pub authenticated: bool,
+}
+
+/// Node and store operations needed by the faucet flows.
+#[allow(async_fn_in_trait)]
+pub trait FaucetNode {
+    fn rng(&mut self) -> &mut ClientRng;
+
+    /// Syncs with the node and returns the latest block number.
+    async fn sync_state(&mut self) -> Result<BlockNumber, FaucetError>;
+
+    /// Returns the latest block of the chain according to the node, without syncing the store.
+    async fn chain_tip(&mut self) -> Result<BlockNumber, FaucetError>;
+
+    async fn add_account(&mut self, account: &Account) -> Result<(), FaucetError>;
+
+    async fn add_key(&mut self, key: AuthSecretKey) -> Result<(), FaucetError>;
+
+    /// Returns the key of the keystore whose public key has commitment `pub_key`.
+    async fn secret_key(&mut self, pub_key: Word) -> Result<Option<AuthSecretKey>, FaucetError>;
+
+    async fn get_account(&mut self, account_id: AccountId) -> Result<Option<Account>, FaucetError>;
+
+    /// IDs of the accounts tracked by the store.
+    async fn tracked_accounts(&mut self) -> Result<Vec<AccountId>, FaucetError>;
+
+    /// Starts tracking the public account `account_id` with its state on chain, without a key to
+    /// sign for it.
+    async fn import_account(&mut self, account_id: AccountId) -> Result<(), FaucetError>;
+
+    /// Fetches the state of the public account `account_id` from the node without tracking it,
+    /// or `None` if the chain has no state for it.
+    async fn fetch_account(
+        &mut self,
+        account_id: AccountId,
+    ) -> Result<Option<Account>, FaucetError>;
+
+    /// Registers the note of `note_file` with the store and returns its ID.
+    async fn import_note(&mut self, note_file: NoteFile) -> Result<NoteId, FaucetError>;
+
+    /// Returns an input note of the store, or `None` if the store does not know it.
+    async fn stored_note(&mut self, note_id: NoteId) -> Result<Option<StoredNote>, FaucetError>;
+
+    /// Returns the input notes of the store that `account_id` can consume now.
+    async fn consumable_notes(
+        &mut self,
+        account_id: AccountId,
+    ) -> Result<Vec<StoredNote>, FaucetError>;
+
+    /// Compiles a transaction script, reporting the assembler diagnostics on failure.
+    fn compile_tx_script(&self, code: &str) -> Result<TransactionScript, FaucetError>;
+
+    /// Executes `request` against `account_id` without proving or submitting it, returning what
+    /// submitting it would cost.
+    async fn execute_transaction(
+        &mut self,
+        account_id: AccountId,
+        request: TransactionRequest,
+    ) -> Result<TransactionCost, FaucetError>;
+
+    async fn submit_transaction(
+        &mut self,
+        account_id: AccountId,
+        request: TransactionRequest,
+    ) -> Result<TransactionId, FaucetError>;
+
+    /// Cost of the transaction last submitted through this node, `None` once taken or if
+    /// `transaction_id` is another one.
+    fn take_transaction_cost(&mut self, transaction_id: TransactionId) -> Option<TransactionCost>;
+
+    /// Returns the state of a transaction previously submitted through this node, or `None` if
+    /// the store does not know it.
+    async fn transaction_state(
+        &mut self,
+        transaction_id: TransactionId,
+    ) -> Result<Option<TxState>, FaucetError>;
+
+    /// Returns the states of the transactions among `transaction_ids` the store knows, read in a
+    /// single query.
+    async fn transaction_states(
+        &mut self,
+        transaction_ids: &[TransactionId],
+    ) -> Result<BTreeMap<TransactionId, TxState>, FaucetError>;
+
+    /// Returns the full output notes of a transaction previously submitted through this node, or
+    /// `None` if the store does not know it.
+    async fn transaction_output_notes(
+        &mut self,
+        transaction_id: TransactionId,
+    ) -> Result<Option<Vec<Note>>, FaucetError>;
+
+    /// Returns the nullifiers starting with one of `prefixes` that were consumed on chain from
+    /// `from_block` onwards, together with the block that consumed them.
+    async fn consumed_nullifiers(
+        &mut self,
+        prefixes: &[u16],
+        from_block: BlockNumber,
+    ) -> Result<Vec<(Nullifier, BlockNumber)>, FaucetError>;
+
+    /// Returns the inclusion proof of a note committed on chain, or `None` if the node does not
+    /// know the note.
+    async fn note_inclusion_proof(
+        &mut self,
+        note_id: NoteId,
+    ) -> Result<Option<NoteInclusionProof>, FaucetError>;
+
+    /// Returns the root of the note tree of block `block_num`, or `None` if the node does not
+    /// know the block.
+    async fn block_note_root(
+        &mut self,
+        block_num: BlockNumber,
+    ) -> Result<Option<Word>, FaucetError>;
+}
+
+/// Node used by the binaries: a [`NodeClient`], wrapped in a [`crate::fault::FaultyNode`] when the
+/// `fault-injection` feature is enabled.
+#[cfg(not(feature = "fault-injection"))]
+pub type ConfiguredNode = NodeClient;
+#[cfg(feature = "fault-injection")]
+pub type ConfiguredNode = crate::fault::FaultyNode<NodeClient>;
+
+/// Connects the node described by `config`.
+pub async fn connect(config: &Config) -> Result<ConfiguredNode, FaucetError> {
+    let node = NodeClient::new(config).await?;
+
+    #[cfg(feature = "fault-injection")]
+    let node = {
+        config.fault_injection.validate()?;
+        crate::fault::FaultyNode::new(node, config.fault_injection.clone())
+    };
+
+    Ok(node)
+}
+
+/// [`FaucetNode`] backed by a real client, retrying RPC calls according to the configuration.
+pub struct NodeClient {
+    client: FaucetClient,
+    keystore: FaucetKeyStore,
+    rpc_api: Arc<dyn NodeRpcClient>,
+    rpc: RpcConfig,
+    sync: SyncConfig,
+    sync_stats: SyncStats,
+    /// Cost of the last submitted transaction, until taken.
+    last_cost: Option<(TransactionId, TransactionCost)>,
+}
+
+impl NodeClient {
+    /// Connects the client and, with `sync.scoped` set, restricts the note tags it tracks.
+    pub async fn new(config: &Config) -> Result<Self, FaucetError> {
+        let rpc_api: Arc<dyn NodeRpcClient> = build_rpc_client(&config.rpc).await?;
+        let (mut client, keystore) = build_client_with_rpc(config, rpc_api.clone()).await?;
+        if config.sync.scoped {
+            scope_note_tags(&mut client, &config.sync).await?;
+        }
+        Ok(Self {
+            client,
+            keystore,
+            rpc_api,
+            rpc: config.rpc.clone(),
+            sync: config.sync.clone(),
+            sync_stats: SyncStats::default(),
+            last_cost: None,
+        })
+    }
+
+    /// Durations of the syncs run so far.
+    pub fn sync_stats(&self) -> &SyncStats {
+        &self.sync_stats
+    }
+
+    /// Gives access to the underlying client for operations not covered by [`FaucetNode`].
+    pub fn client_mut(&mut self) -> &mut FaucetClient {
+        &mut self.client
+    }
+}
+
+impl FaucetNode for NodeClient {
+    fn rng(&mut self) -> &mut ClientRng {
+        self.client.rng()
+    }
+
+    async fn sync_state(&mut self) -> Result<BlockNumber, FaucetError> {
+        let started = Instant::now();
+        let summary = with_retries(&mut self.client, &self.rpc, RpcCall::SyncState, |client| {
+            Box::pin(client.sync_state())
+        })
+        .await?;
+
+        let elapsed = started.elapsed();
+        self.sync_stats.record(elapsed);
+        if let Some(slow_ms) = self.sync.slow_sync_ms {
+            if elapsed.as_millis() > u128::from(slow_ms) {
+                eprintln!(
+                    "Slow sync to block {}: {}ms",
+                    summary.block_num,
+                    elapsed.as_millis()
+                );
+            }
+        }
+        Ok(summary.block_num)
+    }
+
+    async fn chain_tip(&mut self) -> Result<BlockNumber, FaucetError> {
+        let (header, _) = with_retries(
+            &mut self.rpc_api,
+            &self.rpc,
+            RpcCall::GetBlockHeader,
+            |rpc_api| {
+                Box::pin(async move {
+                    rpc_api
+                        .get_block_header_by_number(None, false)
+                        .await
+                        .map_err(ClientError::from)
+                })
+            },
+        )
+        .await?;
+        Ok(header.block_num())
+    }
+
+    async fn add_account(&mut self, account: &Account) -> Result<(), FaucetError> {
+        Ok(self.client.add_account(account, false).await?)
+    }
+
+    async fn add_key(&mut self, key: AuthSecretKey) -> Result<(), FaucetError> {
+        Ok(self.keystore.add_key(&key)?)
+    }
+
+    async fn secret_key(&mut self, pub_key: Word) -> Result<Option<AuthSecretKey>, FaucetError> {
+        Ok(self.keystore.get_key(pub_key)?)
+    }
+
+    async fn get_account(&mut self, account_id: AccountId) -> Result<Option<Account>, FaucetError> {
+        let record = with_retries(&mut self.client, &self.rpc, RpcCall::GetAccount, |client| {
+            Box::pin(client.get_account(account_id))
+        })
+        .await?;
+        Ok(record.map(|record| record.account().clone()))
+    }
+
+    async fn tracked_accounts(&mut self) -> Result<Vec<AccountId>, FaucetError> {
+        let headers = self.client.get_account_headers().await?;
+        Ok(headers.into_iter().map(|(header, _)| header.id()).collect())
+    }
+
+    async fn import_account(&mut self, account_id: AccountId) -> Result<(), FaucetError> {
+        Ok(self.client.import_account_by_id(account_id).await?)
+    }
+
+    async fn fetch_account(
+        &mut self,
+        account_id: AccountId,
+    ) -> Result<Option<Account>, FaucetError> {
+        with_retries(
+            &mut self.rpc_api,
+            &self.rpc,
+            RpcCall::GetAccount,
+            |rpc_api| {
+                Box::pin(async move {
+                    match rpc_api.get_account_details(account_id).await {
+                        Ok(fetched) => Ok(fetched.account().cloned()),
+                        Err(RpcError::GrpcError {
+                            error_kind: GrpcError::NotFound,
+                            ..
+                        }) => Ok(None),
+                        Err(err) => Err(ClientError::from(err)),
+                    }
+                })
+            },
+        )
+        .await
+    }
+
+    async fn import_note(&mut self, note_file: NoteFile) -> Result<NoteId, FaucetError> {
+        Ok(self.client.import_note(note_file).await?)
+    }
+
+    async fn stored_note(&mut self, note_id: NoteId) -> Result<Option<StoredNote>, FaucetError> {
+        let Some(record) = self.client.get_input_note(note_id).await? else {
+            return Ok(None);
+        };
+
+        let authenticated = record.inclusion_proof().is_some();
+        // Notes imported from their details only get metadata once the sync finds them on chain.
+        let note: Note = record.try_into().map_err(|err| {
+            FaucetError::InputNote(format!("note {note_id} cannot be consumed yet: {err}"))
+        })?;
+        Ok(Some(StoredNote {
+            note,
+            authenticated,
+        }))
+    }
+
+    async fn consumable_notes(
+        &mut self,
+        account_id: AccountId,
+    ) -> Result<Vec<StoredNote>, FaucetError> {
+        let candidates = self.client.get_consumable_notes(Some(account_id)).await?;
+
+        Ok(candidates
+            .into_iter()
+            .filter(|(_, consumability)| {
+                consumability.iter().any(|(id, relevance)| {
+                    *id == account_id && matches!(relevance, NoteRelevance::Now)
+                })
+            })
+            // Records without metadata are not consumable and never reported here.
+            .filter_map(|(record, _)| {
+                let authenticated = record.inclusion_proof().is_some();
+                TryInto::<Note>::try_into(record)
+                    .ok()
+                    .map(|note| StoredNote {
+                        note,
+                        authenticated,
+                    })
+            })
+            .collect())
+    }
+
+    fn compile_tx_script(&self, code: &str) -> Result<TransactionScript, FaucetError> {
+        tx_scripts().get_or_compile(code, |code| {
+            self.client
+                .script_builder()
+                .compile_tx_script(code)
+                .map_err(|err| script_error(&err))
+        })
+    }
+
+    async fn execute_transaction(
+        &mut self,
+        account_id: AccountId,
+        request: TransactionRequest,
+    ) -> Result<TransactionCost, FaucetError> {
+        let result = self.client.execute_transaction(account_id, request).await?;
+        Ok(TransactionCost::of(result.executed_transaction()))
+    }
+
+    async fn submit_transaction(
+        &mut self,
+        account_id: AccountId,
+        request: TransactionRequest,
+    ) -> Result<TransactionId, FaucetError> {
+        let (transaction_id, cost) = with_retries(
+            &mut self.client,
+            &self.rpc,
+            RpcCall::SubmitTransaction,
+            |client| Box::pin(submit_new_transaction(client, account_id, request.clone())),
+        )
+        .await?;
+        self.last_cost = Some((transaction_id, cost));
+        Ok(transaction_id)
+    }
+
+    fn take_transaction_cost(&mut self, transaction_id: TransactionId) -> Option<TransactionCost> {
+        match self.last_cost.take() {
+            Some((id, cost)) if id == transaction_id => Some(cost),
+            last_cost => {
+                self.last_cost = last_cost;
+                None
+            }
+        }
+    }
+
+    async fn transaction_state(
+        &mut self,
+        transaction_id: TransactionId,
+    ) -> Result<Option<TxState>, FaucetError> {
+        let mut records = with_retries(
+            &mut self.client,
+            &self.rpc,
+            RpcCall::GetTransactions,
+            |client| {
+                Box::pin(client.get_transactions(TransactionFilter::Ids(vec![transaction_id])))
+            },
+        )
+        .await?;
+
+        Ok(records.pop().map(|record| tx_state(record.status)))
+    }
+
+    async fn transaction_states(
+        &mut self,
+        transaction_ids: &[TransactionId],
+    ) -> Result<BTreeMap<TransactionId, TxState>, FaucetError> {
+        let records = with_retries(
+            &mut self.client,
+            &self.rpc,
+            RpcCall::GetTransactions,
+            |client| {
+                Box::pin(client.get_transactions(TransactionFilter::Ids(transaction_ids.to_vec())))
+            },
+        )
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| (record.id, tx_state(record.status)))
+            .collect())
+    }
+
+    async fn transaction_output_notes(
+        &mut self,
+        transaction_id: TransactionId,
+    ) -> Result<Option<Vec<Note>>, FaucetError> {
+        let mut records = with_retries(
+            &mut self.client,
+            &self.rpc,
+            RpcCall::GetTransactions,
+            |client| {
+                Box::pin(client.get_transactions(TransactionFilter::Ids(vec![transaction_id])))
+            },
+        )
+        .await?;
+
+        Ok(records.pop().map(|record| {
+            record
+                .details
+                .output_notes
+                .iter()
+                .filter_map(|note| match note {
+                    OutputNote::Full(note) => Some(note.clone()),
+                    _ => None,
+                })
+                .collect()
+        }))
+    }
+
+    async fn consumed_nullifiers(
+        &mut self,
+        prefixes: &[u16],
+        from_block: BlockNumber,
+    ) -> Result<Vec<(Nullifier, BlockNumber)>, FaucetError> {
+        // The store is synced by the block watcher, so its height is close to the chain tip.
+        let chain_tip = self.client.get_sync_height().await?;
+        let mut chunks = block_chunks(from_block, chain_tip, self.sync.chunk_blocks);
+        if chunks.len() <= 1 {
+            return sync_nullifiers(self.rpc_api.clone(), &self.rpc, prefixes, from_block, None)
+                .await;
+        }
+
+        // The last range runs open-ended so blocks produced during the catch-up are included.
+        let total = chunks.len();
+        let last = chunks.pop().map(|(from, _)| from).unwrap_or(from_block);
+        let ranges = chunks
+            .into_iter()
+            .map(|(from, to)| (from, Some(to)))
+            .chain([(last, None)]);
+        eprintln!(
+            "Catching up on nullifiers from block {from_block} to {chain_tip} in {total} ranges"
+        );
+
+        let rpc = &self.rpc;
+        let rpc_api = &self.rpc_api;
+        let mut done = 0;
+        let mut consumed: Vec<(Nullifier, BlockNumber)> = stream::iter(ranges)
+            .map(|(from, to)| sync_nullifiers(rpc_api.clone(), rpc, prefixes, from, to))
+            .buffer_unordered(self.sync.parallelism.max(1))
+            .inspect_ok(|_| {
+                done += 1;
+                eprintln!("Nullifier catch-up: {done}/{total} ranges fetched");
+            })
+            .try_concat()
+            .await?;
+
+        consumed.sort_by_key(|(nullifier, block_num)| (*block_num, nullifier.to_hex()));
+        consumed.dedup();
+        Ok(consumed)
+    }
+
+    async fn note_inclusion_proof(
+        &mut self,
+        note_id: NoteId,
+    ) -> Result<Option<NoteInclusionProof>, FaucetError> {
+        let notes = with_retries(&mut self.rpc_api, &self.rpc, RpcCall::GetNotes, |rpc_api| {
+            Box::pin(async move {
+                rpc_api
+                    .get_notes_by_id(&[note_id])
+                    .await
+                    .map_err(ClientError::from)
+            })
+        })
+        .await?;
+
+        Ok(notes.into_iter().next().map(|note| match note {
+            FetchedNote::Private(_, _, proof) => proof,
+            FetchedNote::Public(_, proof) => proof,
+        }))
+    }
+
+    async fn block_note_root(
+        &mut self,
+        block_num: BlockNumber,
+    ) -> Result<Option<Word>, FaucetError> {
+        let (header, _) = with_retries(
+            &mut self.rpc_api,
+            &self.rpc,
+            RpcCall::GetBlockHeader,
+            |rpc_api| {
+                Box::pin(async move {
+                    rpc_api
+                        .get_block_header_by_number(Some(block_num), false)
+                        .await
+                        .map_err(ClientError::from)
+                })
+            },
+        )
+        .await?;
+        Ok(Some(header.note_root()))
+    }
+}
+
+/// `Client::submit_new_transaction`, keeping the cost of the executed transaction.
+async fn submit_new_transaction(
+    client: &mut FaucetClient,
+    account_id: AccountId,
+    request: TransactionRequest,
+) -> Result<(TransactionId, TransactionCost), ClientError> {
+    let result = client.execute_transaction(account_id, request).await?;
+    let executed = result.executed_transaction();
+    let cost = TransactionCost::of(executed);
+    let proven = client.prove_transaction(&result).await?;
+    let submission_height = client.submit_proven_transaction(proven, &result).await?;
+    client.apply_transaction(&result, submission_height).await?;
+    Ok((executed.id(), cost))
+}
+
+/// Fetches the nullifiers starting with one of `prefixes` consumed from `from_block`, up to
+/// `to_block` or the chain tip.
+async fn sync_nullifiers(
+    mut rpc_api: Arc<dyn NodeRpcClient>,
+    rpc: &RpcConfig,
+    prefixes: &[u16],
+    from_block: BlockNumber,
+    to_block: Option<BlockNumber>,
+) -> Result<Vec<(Nullifier, BlockNumber)>, FaucetError> {
+    let updates = with_retries(&mut rpc_api, rpc, RpcCall::SyncNullifiers, |rpc_api| {
+        let prefixes = prefixes.to_vec();
+        Box::pin(async move {
+            rpc_api
+                .sync_nullifiers(&prefixes, from_block, to_block)
+                .await
+                .map_err(ClientError::from)
+        })
+    })
+    .await?;
+
+    Ok(updates
+        .into_iter()
+        .map(|update| (update.nullifier, update.block_num))
+        .collect())
+}
+
+fn tx_state(status: TransactionStatus) -> TxState {
+    match status {
+        TransactionStatus::Pending => TxState::Pending,
+        TransactionStatus::Committed { block_number, .. } => TxState::Committed(block_number),
+        TransactionStatus::Discarded(cause) => TxState::Discarded(format!("{cause:?}")),
+    }
+}
+
+/// Turns a script compilation error into [`FaucetError::Script`], keeping the whole chain of
+/// causes: the assembler diagnostics carrying the source locations sit below the builder error.
+fn script_error(err: &dyn std::error::Error) -> FaucetError {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        message.push_str(&format!("\n  caused by: {cause}"));
+        source = cause.source();
+    }
+    FaucetError::Script(message)
+}
@@ -0,0 +1,74 @@
+//! Note files for out-of-band delivery.
+//!
+//! Mints pay recipients with private notes, which the recipient cannot discover through sync. The
+//! faucet exports them in the standard Miden note file format instead, which wallets can import.
+
+use std::path::Path;
+
+use miden_client::{
+    note::{Note, NoteFile},
+    utils::{Deserializable, Serializable},
+};
+use miden_objects::block::BlockNumber;
+
+use crate::{
+    ledger::{MintRecord, MintStatus},
+    mint::rebuild_mint_note,
+    node::FaucetNode,
+    proof::prove_note,
+    FaucetError,
+};
+
+/// Note file of the output note of a mint.
+///
+/// The file carries the inclusion proof once the note is committed, verified against the header of
+/// its block; before that it only holds the note details, to be looked up from the mint's commit
+/// block on.
+pub async fn mint_note_file<N: FaucetNode>(
+    node: &mut N,
+    record: &MintRecord,
+) -> Result<NoteFile, FaucetError> {
+    let note = rebuild_mint_note(record)?;
+    let committed_at = match record.status {
+        MintStatus::Committed => record.commit_block.map(BlockNumber::from),
+        _ => None,
+    };
+    note_file(node, note, committed_at).await
+}
+
+/// Note file of an output note created by a transaction committed at `committed_at`, if known.
+pub async fn note_file<N: FaucetNode>(
+    node: &mut N,
+    note: Note,
+    committed_at: Option<BlockNumber>,
+) -> Result<NoteFile, FaucetError> {
+    if committed_at.is_some() {
+        if let Some(proof) = prove_note(node, &note).await? {
+            return Ok(NoteFile::NoteWithProof(note, proof));
+        }
+    }
+
+    Ok(pending_note_file(note, committed_at.unwrap_or_default()))
+}
+
+/// Note file holding the details of `note` without proof, for wallets to look the note up from
+/// `after_block` on, e.g. the file of a private mint handed out on submission.
+pub fn pending_note_file(note: Note, after_block: BlockNumber) -> NoteFile {
+    NoteFile::NoteDetails {
+        after_block_num: after_block,
+        tag: Some(note.metadata().tag()),
+        details: note.into(),
+    }
+}
+
+pub fn write_note_file(path: impl AsRef<Path>, note_file: &NoteFile) -> Result<(), FaucetError> {
+    std::fs::write(path, note_file.to_bytes())?;
+    Ok(())
+}
+
+pub fn read_note_file(path: impl AsRef<Path>) -> Result<NoteFile, FaucetError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)?;
+    NoteFile::read_from_bytes(&bytes)
+        .map_err(|err| FaucetError::NoteFile(format!("{}: {err}", path.display())))
+}
@@ -0,0 +1,155 @@
+//! Pausable faucets.
+//!
+//! Faucets deployed by this crate carry a small component storing a pause flag in
+//! [`PAUSED_SLOT`] and the owner of the faucet in [`PAUSE_OWNER_SLOT`]. `faucet pause` and
+//! `faucet unpause` send a PAUSE note from the owner to the faucet, and only notes of the owner
+//! can set the flag, like only the owner can mint. The flag changes once the network consumes
+//! the note.
+//!
+//! The MINT notes of this crate fail against a paused faucet, and the mint service refuses to mint
+//! from it in the first place. Faucets deployed before the component existed cannot be paused.
+
+use std::sync::{Arc, LazyLock};
+
+use miden_client::{
+    account::{Account, AccountComponent, AccountId, StorageSlot},
+    assembly::{DefaultSourceManager, Library, LibraryPath, Module, ModuleKind},
+    crypto::FeltRng,
+    note::{
+        Note, NoteAssets, NoteExecutionHint, NoteInputs, NoteMetadata, NoteRecipient, NoteScript,
+        NoteTag, NoteType,
+    },
+    transaction::TransactionId,
+    Felt, Word,
+};
+use miden_lib::{transaction::TransactionKernel, utils::ScriptBuilder};
+
+use crate::{mint::faucet_owner, node::FaucetNode, tx::TxBuilder, FaucetError};
+
+/// Storage slot of the faucet holding the owner allowed to set the pause flag, laid out like the
+/// owner slot of the network fungible faucet, see [`crate::mint::OWNER_SLOT`].
+///
+/// Follows the reserved faucet slot and the two slots of the network fungible faucet component.
+pub const PAUSE_OWNER_SLOT: u8 = 3;
+
+/// Storage slot of the faucet holding the pause flag.
+pub const PAUSED_SLOT: u8 = 4;
+
+/// Library path of the pause component, used by scripts calling its procedures.
+pub const PAUSABLE_LIBRARY_PATH: &str = "network_faucet::pausable";
+
+const PAUSABLE_MASM: &str = include_str!("../masm/pausable.masm");
+
+const PAUSE_MASM: &str = include_str!("../masm/pause.masm");
+
+/// The pause library, assembled on first use. The sources are embedded, so a failure is permanent
+/// and kept as the assembler's message.
+static PAUSABLE_LIBRARY: LazyLock<Result<Library, String>> =
+    LazyLock::new(assemble_pausable_library);
+
+/// Script of PAUSE notes, compiled on first use like [`PAUSABLE_LIBRARY`].
+static PAUSE_SCRIPT: LazyLock<Result<NoteScript, String>> =
+    LazyLock::new(|| compile_note_script(PAUSE_MASM));
+
+/// Library of the pause component, assembled once per process.
+pub fn pausable_library() -> Result<Library, FaucetError> {
+    PAUSABLE_LIBRARY.clone().map_err(FaucetError::Script)
+}
+
+/// Compiles the note script `code`, which may call the procedures of the pause component.
+pub(crate) fn compile_note_script(code: &str) -> Result<NoteScript, String> {
+    let library = PAUSABLE_LIBRARY.clone()?;
+    ScriptBuilder::default()
+        .with_dynamically_linked_library(&library)
+        .and_then(|builder| builder.compile_note_script(code))
+        .map_err(|err| err.to_string())
+}
+
+fn assemble_pausable_library() -> Result<Library, String> {
+    let source_manager = Arc::new(DefaultSourceManager::default());
+    let path = LibraryPath::new(PAUSABLE_LIBRARY_PATH).map_err(|err| err.to_string())?;
+    let module = Module::parser(ModuleKind::Library)
+        .parse_str(path, PAUSABLE_MASM, &source_manager)
+        .map_err(|err| err.to_string())?;
+    TransactionKernel::assembler()
+        .assemble_library([module])
+        .map_err(|err| err.to_string())
+}
+
+/// Account component of a faucet owned by `owner` holding the pause flag, initially unpaused.
+pub fn pausable_component(owner: AccountId) -> Result<AccountComponent, FaucetError> {
+    let owner_word = Word::from([
+        Felt::new(0),
+        Felt::new(0),
+        owner.suffix(),
+        owner.prefix().as_felt(),
+    ]);
+    let component = AccountComponent::new(
+        pausable_library()?,
+        vec![
+            StorageSlot::Value(owner_word),
+            StorageSlot::Value(Word::default()),
+        ],
+    )?;
+    Ok(component.with_supports_all_types())
+}
+
+/// PAUSE note sent by the faucet owner `sender` that sets the pause flag of `faucet_id` to
+/// `paused` once the faucet consumes it.
+pub fn pause_note(
+    faucet_id: AccountId,
+    sender: AccountId,
+    paused: bool,
+    rng: &mut impl FeltRng,
+) -> Result<Note, FaucetError> {
+    let script = PAUSE_SCRIPT.clone().map_err(FaucetError::Script)?;
+    let inputs = NoteInputs::new(vec![Felt::from(paused)])?;
+    let recipient = NoteRecipient::new(rng.draw_word(), script, inputs);
+    // Like MINT notes, PAUSE notes are public, so the network faucet can execute them.
+    let metadata = NoteMetadata::new(
+        sender,
+        NoteType::Public,
+        NoteTag::from_account_id(faucet_id),
+        NoteExecutionHint::always(),
+        Felt::new(0),
+    )?;
+    Ok(Note::new(NoteAssets::default(), metadata, recipient))
+}
+
+/// Submits a transaction of the owner of `faucet_id` sending it a [`pause_note`] that sets its
+/// pause flag to `paused`.
+///
+/// The faucet and its owner must be tracked by `node`. The flag changes once the network
+/// consumes the note against the faucet.
+pub async fn set_paused<N: FaucetNode>(
+    node: &mut N,
+    faucet_id: AccountId,
+    paused: bool,
+) -> Result<TransactionId, FaucetError> {
+    let owner = faucet_owner(node, faucet_id).await?;
+    let note = pause_note(faucet_id, owner, paused, node.rng())?;
+    let request = TxBuilder::new().output_notes([note]).build()?;
+    node.submit_transaction(owner, request).await
+}
+
+/// Reads the pause flag of `faucet_id` from the store.
+///
+/// Faucets without the pause component are reported as not paused.
+pub async fn is_paused<N: FaucetNode>(
+    node: &mut N,
+    faucet_id: AccountId,
+) -> Result<bool, FaucetError> {
+    let faucet = node
+        .get_account(faucet_id)
+        .await?
+        .ok_or(FaucetError::AccountNotFound(faucet_id))?;
+    Ok(paused_flag(&faucet))
+}
+
+/// Pause flag of `faucet`, false without the pause component.
+pub(crate) fn paused_flag(faucet: &Account) -> bool {
+    faucet
+        .storage()
+        .get_item(PAUSED_SLOT)
+        .is_ok_and(|word| word != Word::default())
+}
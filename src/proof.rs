@@ -0,0 +1,58 @@
+//! Note inclusion proofs.
+//!
+//! The node proves that a note was created in a block with a Merkle path from the note to the
+//! note tree root in the header of that block. [`prove_note`] fetches the proof and checks the
+//! path locally, so note files carrying the proof, see [`crate::note_file`], are only written for
+//! notes actually on chain.
+
+use miden_client::{
+    note::{Note, NoteInclusionProof},
+    Word,
+};
+
+use crate::{node::FaucetNode, FaucetError};
+
+/// Fetches the inclusion proof of `note` and verifies it against the header of its block.
+///
+/// Returns `None` while the node does not know the note, e.g. before it is committed.
+pub async fn prove_note<N: FaucetNode>(
+    node: &mut N,
+    note: &Note,
+) -> Result<Option<NoteInclusionProof>, FaucetError> {
+    let Some(proof) = node.note_inclusion_proof(note.id()).await? else {
+        return Ok(None);
+    };
+
+    let block_num = proof.location().block_num();
+    let note_root = node.block_note_root(block_num).await?.ok_or_else(|| {
+        FaucetError::InvalidInclusionProof(
+            note.id().to_hex(),
+            format!("block {block_num} is unknown to the node"),
+        )
+    })?;
+    verify_inclusion_proof(note, &proof, note_root)?;
+    Ok(Some(proof))
+}
+
+/// Checks that `proof` leads from `note` to `note_root`, the note tree root of the block the proof
+/// places the note in.
+pub fn verify_inclusion_proof(
+    note: &Note,
+    proof: &NoteInclusionProof,
+    note_root: Word,
+) -> Result<(), FaucetError> {
+    let invalid = |reason: String| FaucetError::InvalidInclusionProof(note.id().to_hex(), reason);
+
+    let index = proof.location().node_index_in_block();
+    let root = proof
+        .note_path()
+        .compute_root(index.into(), note.commitment())
+        .map_err(|err| invalid(err.to_string()))?;
+    if root != note_root {
+        return Err(invalid(format!(
+            "path does not lead to the note root of block {}",
+            proof.location().block_num()
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//! Signed mint receipts.
+//!
+//! A [`MintReceipt`] states that a faucet minted an amount of tokens to a recipient in a committed
+//! transaction, signed with the RPO Falcon 512 key of the faucet owner. Anyone can check a receipt
+//! with [`MintReceipt::verify`] and compare its public key commitment with the auth key of the
+//! owner account on chain, without trusting the faucet operator.
+//!
+//! The signed message is the RPO hash of the receipt fields, see [`MintReceipt::message`], so it
+//! can also be checked by a Miden program.
+
+use miden_client::{
+    account::AccountId,
+    auth::AuthSecretKey,
+    crypto::rpo_falcon512::{PublicKey, Signature},
+    utils::{Deserializable, Serializable},
+    Felt, Word,
+};
+use miden_crypto::hash::rpo::Rpo256;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    ledger::{MintRecord, MintStatus},
+    mint::{faucet_owner, rebuild_mint_note},
+    node::FaucetNode,
+    FaucetError,
+};
+
+/// Storage slot of a basic wallet holding the commitment to its auth public key.
+pub const AUTH_KEY_SLOT: u8 = 0;
+
+/// Signed evidence of a committed mint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct MintReceipt {
+    pub mint_id: i64,
+    pub faucet_id: String,
+    pub recipient: String,
+    pub amount: u64,
+    pub transaction_id: String,
+    pub note_id: String,
+    /// Commitment to the note ID and metadata, the leaf of the note in the note tree of its block.
+    pub note_commitment: String,
+    /// Block the mint transaction was committed in.
+    pub block_num: u32,
+    /// Faucet owner whose key signed the receipt.
+    pub signer: String,
+    /// Hex-encoded public key of the owner; its commitment is the owner's auth key on chain.
+    pub public_key: String,
+    /// Hex-encoded signature over [`MintReceipt::message`].
+    pub signature: String,
+}
+
+impl MintReceipt {
+    /// Message signed by the owner: the RPO hash of the faucet ID, recipient, amount, transaction
+    /// ID, note commitment and block number.
+    pub fn message(&self) -> Result<Word, FaucetError> {
+        let faucet_id = parse_account(&self.faucet_id)?;
+        let recipient = parse_account(&self.recipient)?;
+        let mut elements = vec![
+            faucet_id.prefix().as_felt(),
+            faucet_id.suffix(),
+            recipient.prefix().as_felt(),
+            recipient.suffix(),
+            Felt::new(self.amount),
+        ];
+        elements
+            .extend_from_slice(parse_word("transaction ID", &self.transaction_id)?.as_elements());
+        elements
+            .extend_from_slice(parse_word("note commitment", &self.note_commitment)?.as_elements());
+        elements.push(Felt::from(self.block_num));
+        Ok(Rpo256::hash_elements(&elements))
+    }
+
+    /// Checks the signature against the public key of the receipt and returns the commitment to
+    /// that key, to be compared with the auth key of the signer account.
+    pub fn verify(&self) -> Result<Word, FaucetError> {
+        let public_key: PublicKey = decode(&self.public_key, "public key")?;
+        let signature: Signature = decode(&self.signature, "signature")?;
+        if !public_key.verify(self.message()?, &signature) {
+            return Err(FaucetError::InvalidReceipt(
+                "signature does not match the receipt".into(),
+            ));
+        }
+        Ok(public_key.to_commitment())
+    }
+}
+
+/// Issues a receipt for a committed mint, signed with the key of the faucet owner.
+///
+/// The owner must be tracked by `node` and its key held by the keystore.
+pub async fn mint_receipt<N: FaucetNode>(
+    node: &mut N,
+    record: &MintRecord,
+) -> Result<MintReceipt, FaucetError> {
+    let block_num = match (record.status, record.commit_block) {
+        (MintStatus::Committed, Some(block_num)) => block_num,
+        _ => return Err(FaucetError::MintNotCommitted(record.id)),
+    };
+    let note = rebuild_mint_note(record)?;
+    let faucet_id = parse_account(&record.faucet_id)?;
+
+    let owner_id = faucet_owner(node, faucet_id).await?;
+    let owner = node
+        .get_account(owner_id)
+        .await?
+        .ok_or(FaucetError::AccountNotFound(owner_id))?;
+    let key_commitment = owner.storage().get_item(AUTH_KEY_SLOT)?;
+    let Some(AuthSecretKey::RpoFalcon512(key)) = node.secret_key(key_commitment).await? else {
+        return Err(FaucetError::KeyNotFound(owner_id));
+    };
+
+    let mut receipt = MintReceipt {
+        mint_id: record.id,
+        faucet_id: record.faucet_id.clone(),
+        recipient: record.recipient.clone(),
+        amount: record.amount,
+        transaction_id: record.transaction_id.clone(),
+        note_id: record.note_id.clone(),
+        note_commitment: note.commitment().to_hex(),
+        block_num,
+        signer: owner_id.to_hex(),
+        public_key: hex::encode((&key.public_key()).to_bytes()),
+        signature: String::new(),
+    };
+    receipt.signature = hex::encode(key.sign(receipt.message()?).to_bytes());
+    Ok(receipt)
+}
+
+fn parse_account(hex: &str) -> Result<AccountId, FaucetError> {
+    AccountId::from_hex(hex)
+        .map_err(|err| FaucetError::InvalidReceipt(format!("bad account ID `{hex}`: {err}")))
+}
+
+fn parse_word(what: &str, hex: &str) -> Result<Word, FaucetError> {
+    Word::try_from(hex).map_err(|err| FaucetError::InvalidReceipt(format!("bad {what}: {err}")))
+}
+
+fn decode<T: Deserializable>(hex: &str, what: &str) -> Result<T, FaucetError> {
+    let bytes = hex::decode(hex)
+        .map_err(|err| FaucetError::InvalidReceipt(format!("bad {what}: {err}")))?;
+    T::read_from_bytes(&bytes)
+        .map_err(|err| FaucetError::InvalidReceipt(format!("bad {what}: {err}")))
+}
@@ -0,0 +1,79 @@
+//! Reclaiming expired mint notes.
+//!
+//! Mints paid with P2IDE notes record a reclaim height in the [`Ledger`]. Once the chain reaches it
+//! and the recipient still has not consumed the note, the note can be consumed back by its sender
+//! to recover the tokens. The sender of a mint note is the network faucet, so the reclaim
+//! transactions are submitted from the faucet account.
+
+use miden_client::{account::AccountId, transaction::TransactionId};
+use miden_objects::block::BlockNumber;
+
+use crate::{
+    ledger::{Ledger, MintRecord},
+    mint::{consume_mint_note, parse_transaction_id, rebuild_mint_note},
+    node::FaucetNode,
+    tx::ConsumeMode,
+    watcher::{wait_for_transaction, BlockWatcher, SharedNode},
+    FaucetError,
+};
+
+/// Result of [`reclaim_expired`].
+#[derive(Debug, Default)]
+pub struct ReclaimReport {
+    /// Reclaimed mints, with their reclaim transaction and block.
+    pub reclaimed: Vec<MintRecord>,
+    /// Tokens recovered by the reclaimed mints.
+    pub reclaimed_amount: u64,
+    pub failed: Vec<(MintRecord, FaucetError)>,
+}
+
+/// Reclaims every expired unclaimed mint of `faucet_id` and waits for the reclaims to commit.
+///
+/// Each mint is handled on its own: a failure is reported in the returned [`ReclaimReport`] and
+/// does not stop the others.
+pub async fn reclaim_expired<N: FaucetNode>(
+    node: &SharedNode<N>,
+    watcher: &BlockWatcher,
+    ledger: &Ledger,
+    faucet_id: AccountId,
+) -> Result<ReclaimReport, FaucetError> {
+    let tip = watcher.wait_for_block(BlockNumber::GENESIS).await?;
+    let expired = ledger.expired_mints(faucet_id, tip.block_num)?;
+    let mut report = ReclaimReport::default();
+
+    let mut submitted: Vec<(MintRecord, TransactionId)> = Vec::new();
+    for record in expired {
+        let result = async {
+            let note = rebuild_mint_note(&record)?;
+            let mint_transaction_id = parse_transaction_id(&record.transaction_id)?;
+            consume_mint_note(
+                &mut *node.lock().await,
+                faucet_id,
+                mint_transaction_id,
+                note,
+                ConsumeMode::Auto,
+            )
+            .await
+        }
+        .await;
+        match result {
+            Ok(transaction_id) => submitted.push((record, transaction_id)),
+            Err(err) => report.failed.push((record, err)),
+        }
+    }
+
+    for (mut record, transaction_id) in submitted {
+        match wait_for_transaction(node, watcher, transaction_id).await {
+            Ok(block_num) => {
+                ledger.mark_reclaimed(record.id, transaction_id, block_num)?;
+                record.reclaim_transaction_id = Some(transaction_id.to_hex());
+                record.reclaimed_block = Some(block_num.as_u32());
+                report.reclaimed_amount += record.amount;
+                report.reclaimed.push(record);
+            }
+            Err(err) => report.failed.push((record, err)),
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,99 @@
+//! Referral codes.
+//!
+//! An operator hands a referrer a code with `referral create`. A mint requested with the code,
+//! see [`FaucetHandle::with_referral`](crate::service::FaucetHandle::with_referral), is recorded
+//! in the [`Ledger`] as a conversion of the code, and the referrer receives a bonus mint of
+//! [`ReferralConfig::bonus_amount`], up to [`ReferralConfig::max_bonuses`] bonuses per referrer.
+//!
+//! Each recipient converts at most once, and cannot refer themselves. Conversions are recorded
+//! whether a bonus was minted or not, so `referral list` reports the growth each code brought.
+
+use miden_client::account::AccountId;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ledger::{Ledger, ReferralCodeRecord},
+    FaucetError,
+};
+
+/// Characters of generated codes, without look-alikes such as `0` and `O`.
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// Length of generated codes.
+pub const CODE_LENGTH: usize = 8;
+
+/// Referral bonus settings, read from the `[referral]` section of the configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReferralConfig {
+    /// Tokens minted to the referrer for each conversion of their codes.
+    pub bonus_amount: u64,
+    /// Bonuses a referrer receives at most; further conversions are recorded without bonus.
+    pub max_bonuses: u32,
+}
+
+impl Default for ReferralConfig {
+    fn default() -> Self {
+        Self {
+            bonus_amount: 10,
+            max_bonuses: 10,
+        }
+    }
+}
+
+impl ReferralConfig {
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        if self.bonus_amount == 0 {
+            return Err(FaucetError::Config(
+                "referral.bonus_amount must be positive".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Draws a new random referral code, e.g. `K7QH2MZP`.
+pub fn generate_referral_code() -> String {
+    rand::random::<[u8; CODE_LENGTH]>()
+        .into_iter()
+        .map(|byte| char::from(CODE_ALPHABET[usize::from(byte) % CODE_ALPHABET.len()]))
+        .collect()
+}
+
+/// Normalizes a referral code supplied by a user, ignoring case and surrounding whitespace.
+pub fn parse_referral_code(input: &str) -> Result<String, FaucetError> {
+    let code = input.trim().to_ascii_uppercase();
+    if code.is_empty() || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(FaucetError::InvalidReferral(
+            input.to_string(),
+            "codes may only contain ASCII letters and digits".into(),
+        ));
+    }
+    Ok(code)
+}
+
+/// The code `code` if a mint to `referee` may convert it.
+///
+/// Fails with [`FaucetError::InvalidReferral`] for unknown and disabled codes, codes of the
+/// referee itself and referees who converted a code before.
+pub fn check_referral(
+    ledger: &Ledger,
+    code: &str,
+    referee: AccountId,
+) -> Result<ReferralCodeRecord, FaucetError> {
+    let invalid = |reason: &str| FaucetError::InvalidReferral(code.to_string(), reason.into());
+
+    let record = ledger
+        .referral_code(code)?
+        .ok_or_else(|| invalid("no such code"))?;
+    if record.disabled_at.is_some() {
+        return Err(invalid("the code is disabled"));
+    }
+    if record.referrer == referee {
+        return Err(invalid("referrers cannot use their own code"));
+    }
+    if ledger.was_referred(referee)? {
+        return Err(invalid("the recipient was referred before"));
+    }
+    Ok(record)
+}
@@ -0,0 +1,108 @@
+//! Access log of the REST API.
+//!
+//! [`log_requests`] emits one `tracing` event per request with the method, path, status, latency,
+//! caller and whether a rate limit refused the request. Events are `info`, or `warn` for server
+//! errors, under the target of this module; `RUST_LOG` selects them, see `main`.
+//!
+//! Nothing secret reaches the log: API keys and GitHub session tokens are never logged, API key
+//! callers are named by their configured name, query values that may carry credentials (e.g. the
+//! OAuth `code` of the GitHub callback) are replaced by [`REDACTED`], and neither request nor
+//! response bodies, which hold serial numbers and exported note files, are logged.
+
+use std::{sync::Arc, time::Instant};
+
+use axum::{
+    extract::{Request, State},
+    http::{header::AUTHORIZATION, StatusCode, Uri},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{access::Access, authz::find_api_key};
+
+/// Placeholder of redacted values.
+pub const REDACTED: &str = "[redacted]";
+
+/// Query parameters whose values are never logged.
+const SENSITIVE_PARAMS: &[&str] = &[
+    "access_token",
+    "api_key",
+    "code",
+    "key",
+    "note",
+    "note_file",
+    "password",
+    "secret",
+    "serial_num",
+    "state",
+    "token",
+];
+
+/// Middleware logging every request once its response is ready.
+pub async fn log_requests(
+    State(access): State<Arc<Access>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let started = Instant::now();
+    let method = request.method().clone();
+    let path = redact_uri(request.uri());
+    let caller = redact_caller(
+        &access,
+        request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok()),
+    );
+
+    let response = next.run(request).await;
+
+    let status = response.status();
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let rate_limited = status == StatusCode::TOO_MANY_REQUESTS;
+    if status.is_server_error() {
+        tracing::warn!(
+            %method, %path, status = status.as_u16(), latency_ms, %caller, rate_limited,
+            "request failed"
+        );
+    } else {
+        tracing::info!(
+            %method, %path, status = status.as_u16(), latency_ms, %caller, rate_limited,
+            "request"
+        );
+    }
+    response
+}
+
+/// Path and query of `uri`, with the values of credential-like query parameters redacted.
+pub fn redact_uri(uri: &Uri) -> String {
+    let Some(query) = uri.query() else {
+        return uri.path().to_string();
+    };
+    let query = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) if SENSITIVE_PARAMS.contains(&name.to_ascii_lowercase().as_str()) => {
+                format!("{name}={REDACTED}")
+            }
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{}?{query}", uri.path())
+}
+
+/// Caller named by the `Authorization` header, without revealing the credential.
+///
+/// API keys are named `api_key:<name>`; other bearer tokens, i.e. GitHub sessions and unknown
+/// keys, are only reported as present, since looking sessions up would cost a ledger query.
+pub fn redact_caller(access: &Access, authorization: Option<&str>) -> String {
+    let Some(authorization) = authorization else {
+        return "anonymous".into();
+    };
+    let token = authorization.strip_prefix("Bearer ").map(str::trim);
+    match token.and_then(|token| find_api_key(access.api_keys(), token)) {
+        Some(api_key) => format!("api_key:{}", api_key.name),
+        None => format!("bearer:{REDACTED}"),
+    }
+}
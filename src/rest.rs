@@ -0,0 +1,1132 @@
+//! REST API of the faucet service.
+//!
+//! The OpenAPI document is generated from the route handlers below and served at
+//! [`OPENAPI_PATH`], so clients can be generated from it instead of hand-written.
+//!
+//! Mint requests are authenticated by [`Access`], which decides the amounts they may mint and how
+//! often; see [`crate::access`]. Requests go to the network named by their `network` field or
+//! query parameter, and to the token faucet named by their `token`, see [`crate::network`].
+//! Responses naming a transaction link it on the explorer of the network, if it has one. The admin
+//! routes of [`crate::admin`] are served alongside. With a `[tls]` section the API is served over HTTPS, see
+//! [`crate::tls`]; [`crate::http`] adds the CORS and security headers, and [`crate::request_log`]
+//! logs the requests.
+
+use std::{collections::BTreeMap, convert::Infallible, net::SocketAddr, sync::Arc};
+
+use axum::{
+    extract::{ConnectInfo, FromRef, Path, Query, State},
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    middleware,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Redirect, Response,
+    },
+    routing::{get, post},
+    serve::ListenerExt,
+    Extension, Json, Router,
+};
+use futures::{stream, Stream};
+use miden_client::{note::NoteType, utils::Serializable};
+use miden_objects::block::BlockNumber;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+use crate::{
+    access::{Access, Caller},
+    admin,
+    email::parse_email,
+    github::Session,
+    http::HttpConfig,
+    ledger::{unix_now, MintRecord, MintStats, MintStatus},
+    mint::{
+        parse_note_type, parse_serial_num, parse_transaction_id, AuxData, MintNoteKind,
+        MintOptions, RequestSource,
+    },
+    network::{Network, Networks},
+    note_file::pending_note_file,
+    receipt::MintReceipt,
+    referral::parse_referral_code,
+    request_log::log_requests,
+    service::{BatchEntry, FaucetHandle, MintTicket, MintUpdate, SyncStatus},
+    tls::{TlsConfig, TlsListener},
+    FaucetError,
+};
+
+/// Route serving the OpenAPI document of this API.
+pub const OPENAPI_PATH: &str = "/api/openapi.json";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Network faucet API",
+        description = "Mint operations of a network faucet deployment."
+    ),
+    paths(
+        mint,
+        mint_batch,
+        mint_preview,
+        mint_status,
+        mint_events,
+        mint_receipt,
+        stats,
+        status,
+        list_networks,
+        github_sign_in,
+        github_callback,
+        admin::pause,
+        admin::unpause,
+        admin::limits,
+        admin::set_tier,
+        admin::reset_tier,
+        admin::set_drip_limit,
+        admin::reset_drip_limit,
+        admin::list_entries,
+        admin::add_list_entry,
+        admin::remove_list_entry,
+        admin::queue,
+        admin::reclaim
+    )
+)]
+struct ApiDoc;
+
+/// Returns the OpenAPI document describing the REST API.
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
+
+/// Shared state of the route handlers.
+#[derive(Clone)]
+struct ApiState {
+    networks: Networks,
+    access: Arc<Access>,
+}
+
+impl FromRef<ApiState> for Networks {
+    fn from_ref(state: &ApiState) -> Self {
+        state.networks.clone()
+    }
+}
+
+impl FromRef<ApiState> for Arc<Access> {
+    fn from_ref(state: &ApiState) -> Self {
+        state.access.clone()
+    }
+}
+
+/// Builds the REST API router, minting on `networks` and authenticating mints with `access`.
+pub fn router(networks: Networks, access: Arc<Access>) -> Router {
+    let admin = admin::router(networks.clone(), access.clone());
+    Router::new()
+        .route("/api/mint", post(mint))
+        .route("/api/mint/batch", post(mint_batch))
+        .route("/api/mint/preview", post(mint_preview))
+        .route("/api/mints/{mint_id}", get(mint_status))
+        .route("/api/mint/{mint_id}/events", get(mint_events))
+        .route("/api/mints/{mint_id}/receipt", get(mint_receipt))
+        .route("/api/stats", get(stats))
+        .route("/api/status", get(status))
+        .route("/api/networks", get(list_networks))
+        .route("/api/auth/github", get(github_sign_in))
+        .route("/api/auth/github/callback", get(github_callback))
+        .route(OPENAPI_PATH, get(|| async { Json(openapi()) }))
+        .with_state(ApiState { networks, access })
+        .merge(admin)
+}
+
+/// Serves the REST API on `addr` until the server fails.
+pub async fn serve(
+    addr: SocketAddr,
+    networks: Networks,
+    access: Arc<Access>,
+    tls: Option<TlsConfig>,
+    http: HttpConfig,
+) -> Result<(), FaucetError> {
+    let mut router = http.apply(router(networks, access.clone()), tls.is_some())?;
+    if http.access_log {
+        router = router.layer(middleware::from_fn_with_state(access, log_requests));
+    }
+    let served = match tls {
+        Some(tls) => {
+            // `axum` only provides the peer address of plain TCP and tapped listeners.
+            let listener = TlsListener::bind(addr, &tls).await?.tap_io(|_| {});
+            println!("REST API listening on https://{addr}");
+            axum::serve(
+                listener,
+                router.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+        }
+        None => {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            println!("REST API listening on {addr}");
+            axum::serve(
+                listener,
+                router.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+        }
+    };
+    served.map_err(|err| FaucetError::Server(err.to_string()))
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct MintRequest {
+    /// Recipient account ID, in hex or bech32.
+    pub recipient: String,
+    /// Within the bounds of the `[mint]` section; its default amount when omitted.
+    #[serde(default)]
+    pub amount: Option<u64>,
+    /// Hex-encoded serial number of the P2ID note, drawn by the faucet when omitted.
+    ///
+    /// Supplying it lets the caller compute the note ahead of the mint.
+    #[serde(default)]
+    pub serial_num: Option<String>,
+    /// Mint a P2IDE note the faucet can reclaim from this block height on.
+    ///
+    /// When omitted, the service's default reclaim period applies, if any.
+    #[serde(default)]
+    pub reclaim_height: Option<u32>,
+    /// Timelock the note, so the recipient can only consume it from this block height on.
+    #[serde(default)]
+    pub unlock_height: Option<u32>,
+    /// `public` or `private`, the faucet's default note type when omitted.
+    ///
+    /// Recipients only discover public notes through sync; private notes are handed out as note
+    /// files in the response.
+    #[serde(default)]
+    pub note_type: Option<String>,
+    /// `account_id` to tag the note for the recipient, `network` for the network to consume it
+    /// into a network account recipient, or `use_case:<id>[:<payload>]` for a use-case tag
+    /// network accounts pick the note up by; `service.note_tag` when omitted.
+    ///
+    /// Tags for network execution need public notes, the default note type of mints tagged that
+    /// way.
+    #[serde(default)]
+    pub note_tag: Option<String>,
+    /// ID of the campaign the mint is attributed to and checked against, see `campaign create`.
+    #[serde(default)]
+    pub campaign_id: Option<u32>,
+    /// Referral code crediting its referrer with a bonus mint, if the `[referral]` section is set.
+    /// Not accepted in batches.
+    #[serde(default)]
+    pub referral_code: Option<String>,
+    /// Email the note file to this address once the mint is committed, for recipients without a
+    /// synced wallet. Requires the `[smtp]` section of the configuration.
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Network to mint on, one of `GET /api/networks`; the default network when omitted. Set on
+    /// the batch rather than its mints in batches.
+    #[serde(default)]
+    pub network: Option<String>,
+    /// Symbol of the token to mint, one of the `tokens` of `GET /api/networks`; the main faucet
+    /// of the network when omitted. Set on the batch rather than its mints in batches.
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct MintResponse {
+    pub mint_id: i64,
+    pub transaction_id: String,
+    /// ID of the P2ID note the recipient will receive.
+    pub note_id: String,
+    /// Amount minted, which differs from the requested one if the service clamped it.
+    pub amount: u64,
+    /// Page of the transaction on the explorer of the network, if it has one.
+    pub explorer_url: Option<String>,
+    /// Hex-encoded Miden note file of a private note, for the recipient to import into their
+    /// wallet. Unset for public notes.
+    pub note_file: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MintPreviewResponse {
+    pub recipient: String,
+    /// Amount that would be minted, after the service's defaults and bounds.
+    pub amount: u64,
+    /// Fee the faucet owner would pay, in base units of the native asset.
+    pub fee: u64,
+    /// VM cycles of the mint transaction.
+    pub cycles: usize,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchMintRequest {
+    /// Mints submitted together in one transaction, at most `max_batch_size` of the `[service]`
+    /// section.
+    pub mints: Vec<MintRequest>,
+    /// Network to mint on, the default network when omitted.
+    #[serde(default)]
+    pub network: Option<String>,
+    /// Token to mint, the main faucet of the network when omitted.
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchMintResponse {
+    /// The transaction carrying every mint of the batch.
+    pub transaction_id: String,
+    /// Page of the transaction on the explorer of the network, if it has one.
+    pub explorer_url: Option<String>,
+    /// In the order of the request.
+    pub mints: Vec<BatchMintResult>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchMintResult {
+    pub mint_id: i64,
+    pub recipient: String,
+    pub note_id: String,
+    /// Commitment to the note and its metadata, as found in the note tree of the block.
+    pub note_commitment: String,
+    pub amount: u64,
+    /// Hex-encoded Miden note file of a private note, unset for public notes.
+    pub note_file: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MintState {
+    Submitted,
+    Committed,
+    Failed,
+    Invalidated,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct MintStatusResponse {
+    pub mint_id: i64,
+    pub faucet_id: String,
+    pub recipient: String,
+    pub amount: u64,
+    pub transaction_id: String,
+    pub note_id: String,
+    pub status: MintState,
+    pub commit_block: Option<u32>,
+    /// Block in which the recipient consumed the note, once claimed.
+    pub claim_block: Option<u32>,
+    /// Block from which the faucet can reclaim an unclaimed P2IDE note.
+    pub reclaim_block: Option<u32>,
+    /// Block from which the recipient can consume a timelocked note.
+    pub unlock_block: Option<u32>,
+    /// Mint re-submitting the note after a reorg invalidated this one.
+    pub replaced_by: Option<i64>,
+    pub error: Option<String>,
+    /// Stages the mint went through, oldest first, for support to answer where the tokens of a
+    /// request are.
+    pub stages: Vec<MintStage>,
+    /// What the mint waits for next, unset once its tokens were claimed or the mint ended.
+    pub waiting_for: Option<String>,
+    /// Page of the transaction on the explorer of the network, if it has one.
+    pub explorer_url: Option<String>,
+}
+
+/// A stage of the lifecycle of a mint in [`MintStatusResponse`], named by its `stage` field.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum MintStage {
+    /// The request entered the mint queue of the service, at a Unix timestamp in seconds. Mints
+    /// get their ID once submitted, so a request still waiting is only reported by
+    /// `/admin/queue`.
+    Queued {
+        at: u64,
+    },
+    /// The mint transaction was submitted, at a Unix timestamp in seconds.
+    Submitted {
+        at: u64,
+    },
+    Committed {
+        block_num: u32,
+    },
+    Failed {
+        error: Option<String>,
+    },
+    /// A reorg dropped the transaction; the note is re-submitted by `replaced_by`, if set.
+    Invalidated {
+        replaced_by: Option<i64>,
+    },
+    /// The recipient consumed the note, as found by the claim indexer.
+    Claimed {
+        block_num: u32,
+    },
+    /// The faucet consumed the note back after it expired unclaimed.
+    Reclaimed {
+        block_num: u32,
+        transaction_id: Option<String>,
+    },
+}
+
+/// Payload of the server-sent events of a mint; the event name is the `status` field.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum MintEventResponse {
+    Submitted,
+    Pending,
+    Committed {
+        block_num: u32,
+    },
+    Discarded {
+        reason: String,
+    },
+    Invalidated {
+        reason: String,
+        replaced_by: Option<i64>,
+    },
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StatsResponse {
+    pub submitted: u64,
+    pub committed: u64,
+    pub failed: u64,
+    pub delivered: u64,
+    pub unclaimed: u64,
+    pub minted_amount: u64,
+    pub delivered_amount: u64,
+    pub reclaimed: u64,
+    pub reclaimed_amount: u64,
+    pub burned: u64,
+    pub burned_amount: u64,
+    pub returned: u64,
+    pub returned_amount: u64,
+    /// Fees paid for the faucet's mint and deploy transactions.
+    pub fees_paid: u64,
+    pub cycles_spent: u64,
+}
+
+/// Sync progress and backlogs of a faucet, for monitoring.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct StatusResponse {
+    /// Block the local store is synced to, absent before the first sync.
+    pub store_block: Option<u32>,
+    /// Latest block of the chain according to the node.
+    pub chain_tip: u32,
+    /// Blocks the local store is behind the chain tip.
+    pub lag_blocks: u32,
+    /// Unix timestamp of the last successful sync, absent before the first one.
+    pub last_sync_at: Option<u64>,
+    /// Seconds since the last successful sync, growing while syncs fail.
+    pub seconds_since_sync: Option<u64>,
+    /// Queued mints, a batch counting as one.
+    pub queue_len: usize,
+    pub queue_capacity: usize,
+    /// Transactions waiting to be built, proven and submitted.
+    pub prover_backlog: usize,
+}
+
+/// Network queried by the routes without a request body.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NetworkQuery {
+    /// One of `GET /api/networks`, the default network when omitted.
+    pub network: Option<String>,
+    /// Token faucet of the network, its main faucet when omitted.
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NetworksResponse {
+    /// Network of requests that do not name one.
+    pub default: String,
+    /// Every network served, including the default one.
+    pub networks: Vec<String>,
+    /// Symbols of the token faucets of each network, selected by the `token` of a request.
+    pub tokens: BTreeMap<String, Vec<String>>,
+}
+
+/// Query GitHub redirects back to the callback with.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GithubCallback {
+    pub code: String,
+    /// Echoed from the sign-in redirect.
+    pub state: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SessionResponse {
+    /// Sent with mints as `Authorization: Bearer <token>`.
+    pub token: String,
+    pub login: String,
+    /// Unix timestamp, in seconds, after which the token is rejected.
+    pub expires_at: u64,
+}
+
+impl From<Session> for SessionResponse {
+    fn from(session: Session) -> Self {
+        Self {
+            token: session.token,
+            login: session.login,
+            expires_at: session.expires_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+/// Queues a mint and returns once the mint transaction is submitted.
+#[utoipa::path(
+    post,
+    path = "/api/mint",
+    request_body = MintRequest,
+    responses(
+        (status = 200, body = MintResponse),
+        (status = 400, description = "Invalid recipient, amount, campaign or referral code, or unknown network", body = ErrorResponse),
+        (status = 401, description = "Unknown API key or GitHub sign-in required", body = ErrorResponse),
+        (status = 403, description = "Campaign not minting, its budget or per-user limit reached, or refused by an abuse policy", body = ErrorResponse),
+        (status = 429, description = "Drip limit of the GitHub account, request rate limit or daily budget reached", body = ErrorResponse),
+        (status = 503, description = "Node unreachable or faucet paused", body = ErrorResponse),
+    )
+)]
+async fn mint(
+    State(state): State<ApiState>,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    Json(request): Json<MintRequest>,
+) -> Result<Json<MintResponse>, ApiError> {
+    let network = state.networks.get(request.network.as_deref())?;
+    let entry = batch_entry(network, &request)?;
+    let (caller, mut handle) =
+        authorize(&state, network, request.token.as_deref(), &headers, peer)?;
+    state.access.admit(&caller, handle.client_ip(), 1)?;
+    if let Some(code) = &request.referral_code {
+        handle = handle.with_referral(parse_referral_code(code)?);
+    }
+    let drip = match (&caller, state.access.github()) {
+        (Caller::Github(user), Some(github)) => Some((github, github.reserve_drip(user)?)),
+        _ => None,
+    };
+    let result = handle
+        .mint_with_email(entry.recipient, entry.amount, entry.options, entry.email)
+        .await;
+    if let Some((github, drip_id)) = drip {
+        github.finish_drip(drip_id, result.as_ref().ok().map(|ticket| ticket.mint_id))?;
+    }
+
+    let ticket = result?;
+    Ok(Json(MintResponse {
+        mint_id: ticket.mint_id,
+        transaction_id: ticket.transaction_id.to_hex(),
+        note_id: ticket.note_id.to_hex(),
+        amount: ticket.amount,
+        explorer_url: network
+            .explorer
+            .as_ref()
+            .map(|explorer| explorer.transaction(ticket.transaction_id)),
+        note_file: private_note_file(&ticket),
+    }))
+}
+
+/// Queues several mints submitted together in one transaction, and returns once it is submitted.
+///
+/// The batch succeeds or fails as a whole. Each mint counts against the drip limit of GitHub
+/// accounts.
+#[utoipa::path(
+    post,
+    path = "/api/mint/batch",
+    request_body = BatchMintRequest,
+    responses(
+        (status = 200, body = BatchMintResponse),
+        (status = 400, description = "Invalid recipient or amount, too many mints or unknown network", body = ErrorResponse),
+        (status = 401, description = "Unknown API key or GitHub sign-in required", body = ErrorResponse),
+        (status = 403, description = "Campaign not minting, its budget or per-user limit reached, or refused by an abuse policy", body = ErrorResponse),
+        (status = 429, description = "Drip limit of the GitHub account, request rate limit or daily budget reached", body = ErrorResponse),
+        (status = 503, description = "Node unreachable or faucet paused", body = ErrorResponse),
+    )
+)]
+async fn mint_batch(
+    State(state): State<ApiState>,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    Json(request): Json<BatchMintRequest>,
+) -> Result<Json<BatchMintResponse>, ApiError> {
+    if let Some(code) = request
+        .mints
+        .iter()
+        .find_map(|mint| mint.referral_code.clone())
+    {
+        return Err(FaucetError::InvalidReferral(
+            code,
+            "referral codes are not accepted in batches".into(),
+        )
+        .into());
+    }
+    if request
+        .mints
+        .iter()
+        .any(|mint| mint.network.is_some() || mint.token.is_some())
+    {
+        return Err(ApiError(
+            StatusCode::BAD_REQUEST,
+            "set the network and token of a batch on the batch, not on its mints".into(),
+        ));
+    }
+    let network = state.networks.get(request.network.as_deref())?;
+    let entries = request
+        .mints
+        .iter()
+        .map(|mint| batch_entry(network, mint))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (caller, handle) = authorize(&state, network, request.token.as_deref(), &headers, peer)?;
+    state
+        .access
+        .admit(&caller, handle.client_ip(), entries.len())?;
+    let mut drips = Vec::new();
+    if let (Caller::Github(user), Some(github)) = (&caller, state.access.github()) {
+        for _ in &entries {
+            match github.reserve_drip(user) {
+                Ok(drip_id) => drips.push(drip_id),
+                Err(err) => {
+                    for drip_id in drips {
+                        github.finish_drip(drip_id, None)?;
+                    }
+                    return Err(err.into());
+                }
+            }
+        }
+    }
+    let result = handle.mint_batch(entries).await;
+    if let Some(github) = state.access.github() {
+        for (index, drip_id) in drips.into_iter().enumerate() {
+            let mint_id = result.as_ref().ok().map(|batch| batch.mints[index].mint_id);
+            github.finish_drip(drip_id, mint_id)?;
+        }
+    }
+
+    let batch = result?;
+    Ok(Json(BatchMintResponse {
+        transaction_id: batch.transaction_id.to_hex(),
+        explorer_url: network
+            .explorer
+            .as_ref()
+            .map(|explorer| explorer.transaction(batch.transaction_id)),
+        mints: request
+            .mints
+            .iter()
+            .zip(batch.mints)
+            .map(|(request, ticket)| BatchMintResult {
+                mint_id: ticket.mint_id,
+                recipient: request.recipient.clone(),
+                note_id: ticket.note_id.to_hex(),
+                note_commitment: ticket.note_commitment.to_hex(),
+                amount: ticket.amount,
+                note_file: private_note_file(&ticket),
+            })
+            .collect(),
+    }))
+}
+
+/// Executes the transaction of a mint without submitting it and returns what it would cost.
+///
+/// Nothing is minted or recorded, and GitHub drips are not counted. The `email` field is ignored.
+#[utoipa::path(
+    post,
+    path = "/api/mint/preview",
+    request_body = MintRequest,
+    responses(
+        (status = 200, body = MintPreviewResponse),
+        (status = 400, description = "Invalid recipient or amount, or unknown network", body = ErrorResponse),
+        (status = 401, description = "Unknown API key or GitHub sign-in required", body = ErrorResponse),
+        (status = 503, description = "Node unreachable", body = ErrorResponse),
+    )
+)]
+async fn mint_preview(
+    State(state): State<ApiState>,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    Json(request): Json<MintRequest>,
+) -> Result<Json<MintPreviewResponse>, ApiError> {
+    let network = state.networks.get(request.network.as_deref())?;
+    let entry = batch_entry(network, &request)?;
+    let (_, handle) = authorize(&state, network, request.token.as_deref(), &headers, peer)?;
+    let preview = handle
+        .preview(entry.recipient, entry.amount, entry.options)
+        .await?;
+    Ok(Json(MintPreviewResponse {
+        recipient: entry.recipient.to_hex(),
+        amount: preview.amount,
+        fee: preview.cost.fee,
+        cycles: preview.cost.cycles,
+    }))
+}
+
+/// Parses the fields of a mint request to `network`.
+fn batch_entry(network: &Network, request: &MintRequest) -> Result<BatchEntry, FaucetError> {
+    let serial_num = request
+        .serial_num
+        .as_deref()
+        .map(parse_serial_num)
+        .transpose()?;
+    Ok(BatchEntry {
+        recipient: network.parse_account_id(&request.recipient)?,
+        amount: request.amount,
+        options: MintOptions {
+            serial_num,
+            note_kind: MintNoteKind::from_reclaim_height(request.reclaim_height.map(Into::into))
+                .with_unlock_height(request.unlock_height.map(Into::into)),
+            note_type: request
+                .note_type
+                .as_deref()
+                .map(parse_note_type)
+                .transpose()?,
+            aux: AuxData::new(request.campaign_id.unwrap_or_default(), RequestSource::Rest),
+            note_tag: request.note_tag.as_deref().map(str::parse).transpose()?,
+        },
+        email: request.email.as_deref().map(parse_email).transpose()?,
+    })
+}
+
+/// Hex-encoded note file of the note of a mint, if the recipient cannot find it through sync.
+fn private_note_file(ticket: &MintTicket) -> Option<String> {
+    (ticket.note.metadata().note_type() != NoteType::Public).then(|| {
+        hex::encode(pending_note_file(ticket.note.clone(), BlockNumber::GENESIS).to_bytes())
+    })
+}
+
+/// Authenticates the caller of a mint and returns a handle minting `token` on `network` with the
+/// amounts of its tier, for the abuse policies of [`crate::abuse`] to judge by the address of
+/// `peer`.
+fn authorize(
+    state: &ApiState,
+    network: &Network,
+    token: Option<&str>,
+    headers: &HeaderMap,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+) -> Result<(Caller, FaucetHandle), FaucetError> {
+    let authorization = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    let caller = state.access.authenticate(authorization)?;
+    let mut handle = network.mint_handle(token, state.access.amounts(caller.tier()))?;
+    if let Some(identity) = caller.identity() {
+        handle = handle.with_actor(identity.clone()).with_identity(identity);
+    }
+    if let Some(Extension(ConnectInfo(peer))) = peer {
+        handle = handle.with_client_ip(peer.ip());
+    }
+    Ok((caller, handle))
+}
+
+/// Returns the ledger record of a mint and its lifecycle: its stages from the queue to the claim of
+/// its note, and what it waits for.
+#[utoipa::path(
+    get,
+    path = "/api/mints/{mint_id}",
+    params(("mint_id" = i64, Path, description = "ID returned by the mint endpoint"), NetworkQuery),
+    responses(
+        (status = 200, body = MintStatusResponse),
+        (status = 400, description = "Unknown network", body = ErrorResponse),
+        (status = 404, description = "Unknown mint", body = ErrorResponse),
+    )
+)]
+async fn mint_status(
+    State(networks): State<Networks>,
+    Path(mint_id): Path<i64>,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Json<MintStatusResponse>, ApiError> {
+    let network = networks.get(query.network.as_deref())?;
+    let record = network
+        .faucet(query.token.as_deref())?
+        .status(mint_id)
+        .await?
+        .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("mint {mint_id} not found")))?;
+    let explorer_url = network.explorer.as_ref().and_then(|explorer| {
+        let transaction_id = parse_transaction_id(&record.transaction_id).ok()?;
+        Some(explorer.transaction(transaction_id))
+    });
+    Ok(Json(MintStatusResponse {
+        explorer_url,
+        ..record.into()
+    }))
+}
+
+/// Streams the progress of a mint as server-sent events.
+///
+/// The current state is sent first; the stream ends after the `committed` or `discarded` event.
+#[utoipa::path(
+    get,
+    path = "/api/mint/{mint_id}/events",
+    params(("mint_id" = i64, Path, description = "ID returned by the mint endpoint"), NetworkQuery),
+    responses(
+        (status = 200, content_type = "text/event-stream", body = MintEventResponse),
+        (status = 400, description = "Unknown network", body = ErrorResponse),
+        (status = 404, description = "Unknown mint", body = ErrorResponse),
+    )
+)]
+async fn mint_events(
+    State(networks): State<Networks>,
+    Path(mint_id): Path<i64>,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let handle = networks
+        .get(query.network.as_deref())?
+        .faucet(query.token.as_deref())?
+        .clone();
+    // Subscribe before reading the ledger so no update between the two is lost.
+    let events = handle.subscribe();
+    let record = handle
+        .status(mint_id)
+        .await?
+        .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("mint {mint_id} not found")))?;
+
+    let initial = MintUpdate::from(&record);
+    let updates = stream::unfold(Some((Some(initial), events)), move |state| {
+        let handle = handle.clone();
+        async move {
+            let (next, mut events) = state?;
+            let update = match next {
+                Some(update) => update,
+                None => loop {
+                    match events.recv().await {
+                        Ok(event) if event.mint_id == mint_id => break event.update,
+                        Ok(_) => {}
+                        // Updates were dropped, fall back to the state recorded in the ledger.
+                        Err(RecvError::Lagged(_)) => match handle.status(mint_id).await {
+                            Ok(Some(record)) => break MintUpdate::from(&record),
+                            _ => return None,
+                        },
+                        Err(RecvError::Closed) => return None,
+                    }
+                },
+            };
+
+            let state = (!update.is_final()).then_some((None, events));
+            Some((Ok(sse_event(update)), state))
+        }
+    });
+
+    Ok(Sse::new(updates).keep_alive(KeepAlive::default()))
+}
+
+/// Returns a receipt of a committed mint, signed by the faucet owner.
+#[utoipa::path(
+    get,
+    path = "/api/mints/{mint_id}/receipt",
+    params(("mint_id" = i64, Path, description = "ID returned by the mint endpoint"), NetworkQuery),
+    responses(
+        (status = 200, body = MintReceipt),
+        (status = 400, description = "Unknown network", body = ErrorResponse),
+        (status = 404, description = "Unknown mint", body = ErrorResponse),
+        (status = 409, description = "Mint not committed yet", body = ErrorResponse),
+    )
+)]
+async fn mint_receipt(
+    State(networks): State<Networks>,
+    Path(mint_id): Path<i64>,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Json<MintReceipt>, ApiError> {
+    let receipt = networks
+        .get(query.network.as_deref())?
+        .faucet(query.token.as_deref())?
+        .receipt(mint_id)
+        .await?
+        .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("mint {mint_id} not found")))?;
+    Ok(Json(receipt))
+}
+
+/// Redirects to GitHub to sign in; GitHub then redirects to the callback.
+#[utoipa::path(
+    get,
+    path = "/api/auth/github",
+    responses(
+        (status = 303, description = "Redirect to the GitHub authorization page"),
+        (status = 404, description = "GitHub sign-in not configured", body = ErrorResponse),
+    )
+)]
+async fn github_sign_in(State(access): State<Arc<Access>>) -> Result<Redirect, ApiError> {
+    let github = access.github().ok_or_else(github_disabled)?;
+    Ok(Redirect::to(&github.authorize_url()))
+}
+
+/// Completes a GitHub sign-in and opens a session if the account is old enough.
+#[utoipa::path(
+    get,
+    path = "/api/auth/github/callback",
+    params(GithubCallback),
+    responses(
+        (status = 200, body = SessionResponse),
+        (status = 401, description = "Sign-in expired or refused", body = ErrorResponse),
+        (status = 403, description = "GitHub account too new", body = ErrorResponse),
+        (status = 404, description = "GitHub sign-in not configured", body = ErrorResponse),
+        (status = 502, description = "GitHub unreachable", body = ErrorResponse),
+    )
+)]
+async fn github_callback(
+    State(access): State<Arc<Access>>,
+    Query(callback): Query<GithubCallback>,
+) -> Result<Json<SessionResponse>, ApiError> {
+    let github = access.github().ok_or_else(github_disabled)?;
+    let session = github.sign_in(&callback.code, &callback.state).await?;
+    Ok(Json(session.into()))
+}
+
+fn github_disabled() -> ApiError {
+    ApiError(
+        StatusCode::NOT_FOUND,
+        "GitHub sign-in is not configured".into(),
+    )
+}
+
+fn sse_event(update: MintUpdate) -> Event {
+    let payload = MintEventResponse::from(update);
+    let name = match payload {
+        MintEventResponse::Submitted => "submitted",
+        MintEventResponse::Pending => "pending",
+        MintEventResponse::Committed { .. } => "committed",
+        MintEventResponse::Discarded { .. } => "discarded",
+        MintEventResponse::Invalidated { .. } => "invalidated",
+    };
+    Event::default()
+        .event(name)
+        .json_data(&payload)
+        .expect("mint events serialize to JSON")
+}
+
+/// Returns aggregated mint and claim statistics of a network.
+#[utoipa::path(
+    get,
+    path = "/api/stats",
+    params(NetworkQuery),
+    responses(
+        (status = 200, body = StatsResponse),
+        (status = 400, description = "Unknown network", body = ErrorResponse),
+    )
+)]
+async fn stats(
+    State(networks): State<Networks>,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Json<StatsResponse>, ApiError> {
+    let handle = networks
+        .get(query.network.as_deref())?
+        .faucet(query.token.as_deref())?;
+    Ok(Json(handle.stats().await?.into()))
+}
+
+/// Returns the sync progress of a faucet against the chain tip and its queue and prover
+/// backlogs, so monitoring can tell a stalled faucet.
+#[utoipa::path(
+    get,
+    path = "/api/status",
+    params(NetworkQuery),
+    responses(
+        (status = 200, body = StatusResponse),
+        (status = 400, description = "Unknown network", body = ErrorResponse),
+        (status = 503, description = "Node unreachable", body = ErrorResponse),
+    )
+)]
+async fn status(
+    State(networks): State<Networks>,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Json<StatusResponse>, ApiError> {
+    let handle = networks
+        .get(query.network.as_deref())?
+        .faucet(query.token.as_deref())?;
+    Ok(Json(handle.sync_status().await?.into()))
+}
+
+/// Returns the networks requests can pick with their `network` field or query parameter.
+#[utoipa::path(get, path = "/api/networks", responses((status = 200, body = NetworksResponse)))]
+async fn list_networks(State(networks): State<Networks>) -> Json<NetworksResponse> {
+    Json(NetworksResponse {
+        default: networks.default_name().to_string(),
+        networks: networks.names().map(str::to_string).collect(),
+        tokens: networks
+            .iter()
+            .map(|(name, network)| (name.to_string(), network.tokens.keys().cloned().collect()))
+            .collect(),
+    })
+}
+
+impl From<MintRecord> for MintStatusResponse {
+    fn from(record: MintRecord) -> Self {
+        let mut stages = Vec::new();
+        if let Some(at) = record.queued_at {
+            stages.push(MintStage::Queued { at });
+        }
+        stages.push(MintStage::Submitted {
+            at: record.created_at,
+        });
+        let waiting_for = match record.status {
+            MintStatus::Submitted => Some("commitment of the mint transaction".to_string()),
+            MintStatus::Failed => {
+                stages.push(MintStage::Failed {
+                    error: record.error.clone(),
+                });
+                None
+            }
+            MintStatus::Invalidated => {
+                stages.push(MintStage::Invalidated {
+                    replaced_by: record.replaced_by,
+                });
+                record
+                    .replaced_by
+                    .map(|mint_id| format!("commitment of mint {mint_id}"))
+            }
+            MintStatus::Committed => {
+                if let Some(block_num) = record.commit_block {
+                    stages.push(MintStage::Committed { block_num });
+                }
+                if let Some(block_num) = record.claim_block {
+                    stages.push(MintStage::Claimed { block_num });
+                    None
+                } else if let Some(block_num) = record.reclaimed_block {
+                    stages.push(MintStage::Reclaimed {
+                        block_num,
+                        transaction_id: record.reclaim_transaction_id.clone(),
+                    });
+                    None
+                } else {
+                    Some(match (record.unlock_block, record.reclaim_block) {
+                        (Some(unlock), _) => {
+                            format!("claim by the recipient, possible from block {unlock}")
+                        }
+                        (None, Some(reclaim)) => format!(
+                            "claim by the recipient before block {reclaim}, from which the \
+                             faucet can reclaim the note"
+                        ),
+                        (None, None) => "claim by the recipient".to_string(),
+                    })
+                }
+            }
+        };
+        let status = match record.status {
+            MintStatus::Submitted => MintState::Submitted,
+            MintStatus::Committed => MintState::Committed,
+            MintStatus::Failed => MintState::Failed,
+            MintStatus::Invalidated => MintState::Invalidated,
+        };
+
+        Self {
+            mint_id: record.id,
+            faucet_id: record.faucet_id,
+            recipient: record.recipient,
+            amount: record.amount,
+            transaction_id: record.transaction_id,
+            note_id: record.note_id,
+            status,
+            commit_block: record.commit_block,
+            claim_block: record.claim_block,
+            reclaim_block: record.reclaim_block,
+            unlock_block: record.unlock_block,
+            replaced_by: record.replaced_by,
+            error: record.error,
+            stages,
+            waiting_for,
+            explorer_url: None,
+        }
+    }
+}
+
+impl From<MintUpdate> for MintEventResponse {
+    fn from(update: MintUpdate) -> Self {
+        match update {
+            MintUpdate::Submitted => Self::Submitted,
+            MintUpdate::Pending => Self::Pending,
+            MintUpdate::Committed { block_num } => Self::Committed { block_num },
+            MintUpdate::Discarded { reason } => Self::Discarded { reason },
+            MintUpdate::Invalidated {
+                reason,
+                replaced_by,
+            } => Self::Invalidated {
+                reason,
+                replaced_by,
+            },
+        }
+    }
+}
+
+impl From<MintStats> for StatsResponse {
+    fn from(stats: MintStats) -> Self {
+        Self {
+            submitted: stats.submitted,
+            committed: stats.committed,
+            failed: stats.failed,
+            delivered: stats.delivered,
+            unclaimed: stats.unclaimed,
+            minted_amount: stats.minted_amount,
+            delivered_amount: stats.delivered_amount,
+            reclaimed: stats.reclaimed,
+            reclaimed_amount: stats.reclaimed_amount,
+            burned: stats.burned,
+            burned_amount: stats.burned_amount,
+            returned: stats.returned,
+            returned_amount: stats.returned_amount,
+            fees_paid: stats.fees_paid,
+            cycles_spent: stats.cycles_spent,
+        }
+    }
+}
+
+impl From<SyncStatus> for StatusResponse {
+    fn from(status: SyncStatus) -> Self {
+        let seconds_since_sync = status.since_last_sync.map(|age| age.as_secs());
+        Self {
+            store_block: status.store_block.map(|block| block.as_u32()),
+            chain_tip: status.chain_tip.as_u32(),
+            lag_blocks: status.lag(),
+            last_sync_at: seconds_since_sync.map(|age| unix_now().saturating_sub(age)),
+            seconds_since_sync,
+            queue_len: status.queue_len,
+            queue_capacity: status.queue_capacity,
+            prover_backlog: status.prover_backlog,
+        }
+    }
+}
+
+pub(crate) struct ApiError(pub(crate) StatusCode, pub(crate) String);
+
+impl From<FaucetError> for ApiError {
+    fn from(err: FaucetError) -> Self {
+        let status = match &err {
+            FaucetError::AmountOutOfRange { .. }
+            | FaucetError::BatchSize { .. }
+            | FaucetError::InvalidAccessList(..)
+            | FaucetError::InvalidAccountId(..)
+            | FaucetError::AccountNetworkMismatch { .. }
+            | FaucetError::InvalidCampaign(..)
+            | FaucetError::InvalidEmail(..)
+            | FaucetError::InvalidNoteType(_)
+            | FaucetError::InvalidNoteTag(..)
+            | FaucetError::InvalidRecipient(..)
+            | FaucetError::RecipientNotFound(_)
+            | FaucetError::InvalidReferral(..)
+            | FaucetError::InvalidSerialNumber(..)
+            | FaucetError::InvalidTier(_)
+            | FaucetError::UnknownNetwork(..)
+            | FaucetError::UnknownToken(..)
+            | FaucetError::EmailDisabled => StatusCode::BAD_REQUEST,
+            FaucetError::ServiceStopped
+            | FaucetError::ExecutorStopped(_)
+            | FaucetError::FaucetPaused(_)
+            | FaucetError::FinalityUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            FaucetError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            FaucetError::AccessDenied(_)
+            | FaucetError::AbuseRefused(..)
+            | FaucetError::AccountTooNew { .. }
+            | FaucetError::CampaignBudgetExhausted { .. }
+            | FaucetError::CampaignInactive(..)
+            | FaucetError::CampaignUserLimit { .. } => StatusCode::FORBIDDEN,
+            FaucetError::RateLimited { .. } | FaucetError::DailyBudgetExhausted { .. } => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            FaucetError::MintNotCommitted(_) | FaucetError::FinalityDisputed { .. } => {
+                StatusCode::CONFLICT
+            }
+            FaucetError::Github(_) => StatusCode::BAD_GATEWAY,
+            err if err.is_transient() => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self(status, err.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(ErrorResponse { error: self.1 })).into_response()
+    }
+}
@@ -0,0 +1,180 @@
+//! Returned supply.
+//!
+//! Holders return tokens by paying them to the faucet owner in a P2ID note. [`collect_returns`]
+//! consumes these deposits into the owner wallet and records them in the [`Ledger`] as returned
+//! supply. Optionally, the collected tokens are then sent back to the faucet in a BURN note, so
+//! the issued supply shrinks as well. [`run_returns_watcher`] collects new deposits on every block.
+//!
+//! Only notes carrying nothing but tokens of the faucet are treated as deposits; the owner's other
+//! notes are left alone.
+
+use miden_client::{account::AccountId, asset::Asset, transaction::TransactionId};
+use miden_objects::MAX_INPUT_NOTES_PER_TX;
+
+use crate::{
+    ledger::{Ledger, ReturnRecord},
+    mint::{burn, faucet_owner},
+    node::{FaucetNode, StoredNote},
+    tx::TxBuilder,
+    watcher::{wait_for_transaction, BlockWatcher, SharedNode},
+    FaucetError,
+};
+
+/// Result of [`collect_returns`].
+#[derive(Debug, Default)]
+pub struct ReturnsReport {
+    /// Deposits consumed into the owner wallet.
+    pub collected: Vec<ReturnRecord>,
+    pub collected_amount: u64,
+    /// Ledger ID of the burn of the collected tokens, if they were burned.
+    pub burn_id: Option<i64>,
+    /// Collect transactions that did not commit; their deposits are retried on the next call.
+    pub failed: Vec<(TransactionId, FaucetError)>,
+}
+
+/// Tokens of `faucet_id` in `note`, or `None` if the note carries any other asset.
+fn deposit_amount(note: &StoredNote, faucet_id: AccountId) -> Option<u64> {
+    note.note
+        .assets()
+        .iter()
+        .map(|asset| match asset {
+            Asset::Fungible(asset) if asset.faucet_id() == faucet_id => Some(asset.amount()),
+            _ => None,
+        })
+        .sum()
+}
+
+/// Consumes the deposits of `faucet_id` tokens waiting for the faucet owner and waits for them to
+/// commit, then burns the collected tokens if `burn_collected` is set.
+///
+/// The owner must be a wallet managed by `node`. Deposits already collected are skipped, so
+/// calling this again only picks up new ones.
+pub async fn collect_returns<N: FaucetNode>(
+    node: &SharedNode<N>,
+    watcher: &BlockWatcher,
+    ledger: &Ledger,
+    faucet_id: AccountId,
+    burn_collected: bool,
+) -> Result<ReturnsReport, FaucetError> {
+    let mut report = ReturnsReport::default();
+    let owner_id = faucet_owner(&mut *node.lock().await, faucet_id).await?;
+
+    let notes = node.lock().await.consumable_notes(owner_id).await?;
+    let mut deposits = Vec::new();
+    for note in notes {
+        let Some(amount) = deposit_amount(&note, faucet_id) else {
+            continue;
+        };
+        if amount > 0 && !ledger.is_collected_return(&note.note.id().to_hex())? {
+            deposits.push((note, amount));
+        }
+    }
+
+    let mut submitted = Vec::new();
+    while !deposits.is_empty() {
+        let batch: Vec<_> = deposits
+            .drain(..deposits.len().min(MAX_INPUT_NOTES_PER_TX))
+            .collect();
+        let request = TxBuilder::new()
+            .consume(batch.iter().map(|(note, _)| note.clone()).collect())
+            .build()?;
+        let transaction_id = node
+            .lock()
+            .await
+            .submit_transaction(owner_id, request)
+            .await?;
+        for (note, amount) in &batch {
+            ledger.record_return(faucet_id, owner_id, &note.note, *amount, transaction_id)?;
+        }
+        submitted.push(transaction_id);
+    }
+
+    for transaction_id in submitted {
+        match wait_for_transaction(node, watcher, transaction_id).await {
+            Ok(block_num) => {
+                ledger.mark_returns_committed(transaction_id, block_num)?;
+                report.collected.extend(ledger.returns_of(transaction_id)?);
+            }
+            Err(err) => {
+                ledger.mark_returns_failed(transaction_id, &err.to_string())?;
+                report.failed.push((transaction_id, err));
+            }
+        }
+    }
+    report.collected_amount = report.collected.iter().map(|record| record.amount).sum();
+
+    if burn_collected && report.collected_amount > 0 {
+        let outcome = burn(
+            &mut *node.lock().await,
+            owner_id,
+            faucet_id,
+            report.collected_amount,
+        )
+        .await?;
+        let burn_id = ledger.record_burn(
+            faucet_id,
+            owner_id,
+            report.collected_amount,
+            outcome.transaction_id,
+            &outcome.burn_note,
+        )?;
+        report.burn_id = Some(burn_id);
+        match wait_for_transaction(node, watcher, outcome.transaction_id).await {
+            Ok(block_num) => ledger.mark_burn_committed(outcome.transaction_id, block_num)?,
+            Err(err) => {
+                ledger.mark_burn_failed(outcome.transaction_id, &err.to_string())?;
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Runs [`collect_returns`] every time the watcher reports a new block, printing each collection.
+///
+/// Transient failures are logged and retried on the next block; other errors stop the watcher.
+pub async fn run_returns_watcher<N: FaucetNode>(
+    node: &SharedNode<N>,
+    watcher: &BlockWatcher,
+    ledger: &Ledger,
+    faucet_id: AccountId,
+    burn_collected: bool,
+) -> Result<(), FaucetError> {
+    let mut tip = watcher.subscribe();
+
+    loop {
+        match collect_returns(node, watcher, ledger, faucet_id, burn_collected).await {
+            Ok(report) => {
+                for record in &report.collected {
+                    println!(
+                        "Collected {} tokens returned by {} at block {}",
+                        record.amount,
+                        record.sender,
+                        record.commit_block.unwrap_or_default()
+                    );
+                }
+                if let Some(burn_id) = report.burn_id {
+                    println!(
+                        "Burned {} returned tokens in burn {burn_id}",
+                        report.collected_amount
+                    );
+                }
+                for (transaction_id, err) in &report.failed {
+                    eprintln!(
+                        "Collecting returns in {} failed: {err}",
+                        transaction_id.to_hex()
+                    );
+                }
+            }
+            Err(err) if err.is_transient() => {
+                eprintln!("Collecting returns failed, retrying: {err}")
+            }
+            Err(err) => return Err(err),
+        }
+
+        tip.changed()
+            .await
+            .map_err(|_| FaucetError::WatcherStopped)?;
+    }
+}
@@ -0,0 +1,486 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    future::Future,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    time::Duration,
+};
+
+use miden_client::{
+    rpc::{Endpoint, GrpcClient},
+    ClientError,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+use tokio_rustls::{
+    rustls::{
+        crypto::ring,
+        pki_types::{pem::PemObject, CertificateDer, ServerName},
+        ClientConfig, RootCertStore,
+    },
+    TlsConnector,
+};
+
+use crate::FaucetError;
+
+/// Time allowed for establishing the TCP connection to the node.
+pub const DEFAULT_CONNECT_TIMEOUT_MS: u64 = 5_000;
+
+/// Time allowed for a single RPC request once connected.
+pub const DEFAULT_REQUEST_TIMEOUT_MS: u64 = 10_000;
+
+/// Node endpoint, either as a single string or as its individual parts.
+///
+/// The string form accepts the well-known network names understood by [`Endpoint`]
+/// (`testnet`, `devnet`, `localhost`) as well as full URLs such as `https://rpc.example.com:443`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum EndpointConfig {
+    Url(String),
+    Parts {
+        scheme: String,
+        host: String,
+        port: Option<u16>,
+    },
+}
+
+impl Default for EndpointConfig {
+    fn default() -> Self {
+        Self::Url("testnet".into())
+    }
+}
+
+impl EndpointConfig {
+    pub fn to_endpoint(&self) -> Result<Endpoint, FaucetError> {
+        match self {
+            Self::Url(url) => Endpoint::try_from(url.as_str())
+                .map_err(|err| FaucetError::Config(format!("invalid rpc endpoint `{url}`: {err}"))),
+            Self::Parts { scheme, host, port } => {
+                if scheme != "http" && scheme != "https" {
+                    return Err(FaucetError::Config(format!(
+                        "unsupported rpc scheme `{scheme}`, expected `http` or `https`"
+                    )));
+                }
+                Ok(Endpoint::new(scheme.clone(), host.clone(), *port))
+            }
+        }
+    }
+
+    /// Name of the network the endpoint connects to: `testnet`, `devnet` or `localhost` for their
+    /// well-known endpoints, otherwise the host and port of the node.
+    pub fn network_name(&self) -> String {
+        let host = match self {
+            Self::Url(url) => {
+                let host = url.split("://").last().unwrap_or(url);
+                host.split('/').next().unwrap_or(host).to_string()
+            }
+            Self::Parts { host, port, .. } => match port {
+                Some(port) => format!("{host}:{port}"),
+                None => host.clone(),
+            },
+        };
+        let host = host.to_ascii_lowercase();
+        if host == "testnet" || host.starts_with("rpc.testnet.miden.io") {
+            "testnet".into()
+        } else if host == "devnet" || host.starts_with("rpc.devnet.miden.io") {
+            "devnet".into()
+        } else if ["localhost", "127.0.0.1"]
+            .iter()
+            .any(|local| host == *local || host.starts_with(&format!("{local}:")))
+        {
+            "localhost".into()
+        } else {
+            host
+        }
+    }
+}
+
+/// Client operations that go through [`with_retries`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcCall {
+    SyncState,
+    GetAccount,
+    GetTransactions,
+    SubmitTransaction,
+    SyncNullifiers,
+    GetNotes,
+    GetBlockHeader,
+}
+
+impl fmt::Display for RpcCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::SyncState => "sync_state",
+            Self::GetAccount => "get_account",
+            Self::GetTransactions => "get_transactions",
+            Self::SubmitTransaction => "submit_transaction",
+            Self::SyncNullifiers => "sync_nullifiers",
+            Self::GetNotes => "get_notes",
+            Self::GetBlockHeader => "get_block_header",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How often failed RPC calls are retried.
+///
+/// `attempts` counts the first try, so `1` disables retries. Entries in `per_call` override it for
+/// individual operations, e.g. to retry `sync_state` aggressively but never resubmit a transaction.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    pub attempts: u32,
+    pub backoff_ms: u64,
+    pub per_call: BTreeMap<RpcCall, u32>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            backoff_ms: 1_000,
+            per_call: BTreeMap::from([(RpcCall::SubmitTransaction, 1)]),
+        }
+    }
+}
+
+impl RetryConfig {
+    pub fn attempts_for(&self, call: RpcCall) -> u32 {
+        self.per_call
+            .get(&call)
+            .copied()
+            .unwrap_or(self.attempts)
+            .max(1)
+    }
+}
+
+/// Connection settings for the node RPC client.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RpcConfig {
+    pub endpoint: EndpointConfig,
+    /// HTTP proxy that supports `CONNECT`, e.g. `http://proxy.internal:3128`.
+    pub proxy: Option<String>,
+    /// PEM bundle used instead of the system trust store when verifying the node certificate.
+    pub ca_cert: Option<PathBuf>,
+    pub connect_timeout_ms: u64,
+    pub request_timeout_ms: u64,
+    pub retries: RetryConfig,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: EndpointConfig::default(),
+            proxy: None,
+            ca_cert: None,
+            connect_timeout_ms: DEFAULT_CONNECT_TIMEOUT_MS,
+            request_timeout_ms: DEFAULT_REQUEST_TIMEOUT_MS,
+            retries: RetryConfig::default(),
+        }
+    }
+}
+
+impl RpcConfig {
+    /// Checks the settings without contacting the node.
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        self.endpoint.to_endpoint()?;
+        if let Some(proxy) = &self.proxy {
+            parse_proxy(proxy)?;
+        }
+        if let Some(ca_cert) = &self.ca_cert {
+            if !ca_cert.is_file() {
+                return Err(FaucetError::Config(format!(
+                    "ca certificate `{}` does not exist",
+                    ca_cert.display()
+                )));
+            }
+        }
+        for (name, value) in [
+            ("connect_timeout_ms", self.connect_timeout_ms),
+            ("request_timeout_ms", self.request_timeout_ms),
+        ] {
+            if value == 0 {
+                return Err(FaucetError::Config(format!("rpc.{name} must be positive")));
+            }
+        }
+        if self.retries.attempts == 0 {
+            return Err(FaucetError::Config(
+                "rpc.retries.attempts counts the first try and must be at least 1".into(),
+            ));
+        }
+        if let Some((call, _)) = self
+            .retries
+            .per_call
+            .iter()
+            .find(|(_, attempts)| **attempts == 0)
+        {
+            return Err(FaucetError::Config(format!(
+                "rpc.retries.per_call.{call} counts the first try and must be at least 1"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Builds the gRPC client described by `config`.
+///
+/// The node (or the proxy, if one is configured) is probed once with `connect_timeout_ms` so an
+/// unreachable host fails here with [`FaucetError::ConnectTimeout`] rather than surfacing later
+/// as a generic request failure.
+///
+/// The underlying [`GrpcClient`] does not expose its transport, so the extra options are applied
+/// around it:
+/// - `proxy` starts a local `CONNECT` tunnel and points the client at it.
+/// - For `https` endpoints reached through a proxy or verified with `ca_cert`, a local tunnel runs
+///   the TLS handshake with the node, verified against the node host name and the roots of
+///   `ca_cert`, or else the system roots, and the client talks plain HTTP/2 to the tunnel. The
+///   bundle of `ca_cert` replaces the system roots, so it has to contain every root the node chain
+///   needs.
+pub async fn build_rpc_client(config: &RpcConfig) -> Result<Arc<GrpcClient>, FaucetError> {
+    let mut endpoint = config.endpoint.to_endpoint()?;
+
+    if let Some(ca_cert) = &config.ca_cert {
+        if !ca_cert.is_file() {
+            return Err(FaucetError::Config(format!(
+                "ca certificate `{}` does not exist",
+                ca_cert.display()
+            )));
+        }
+    }
+
+    let default_port = if endpoint.protocol() == "https" {
+        443
+    } else {
+        80
+    };
+    let target = format!(
+        "{}:{}",
+        endpoint.host(),
+        endpoint.port().unwrap_or(default_port)
+    );
+
+    let https = endpoint.protocol() == "https";
+    let proxy = config.proxy.as_deref().map(parse_proxy).transpose()?;
+    probe_connect(proxy.as_ref().unwrap_or(&target), config.connect_timeout_ms).await?;
+    if proxy.is_some() || (https && config.ca_cert.is_some()) {
+        let tls = if https {
+            Some(tunnel_tls(endpoint.host(), config.ca_cert.as_deref())?)
+        } else {
+            None
+        };
+        let local_addr = spawn_tunnel(proxy, target, tls).await?;
+        endpoint = Endpoint::new(
+            "http".into(),
+            local_addr.ip().to_string(),
+            Some(local_addr.port()),
+        );
+    }
+
+    Ok(Arc::new(GrpcClient::new(
+        &endpoint,
+        config.request_timeout_ms,
+    )))
+}
+
+/// Runs a client operation, retrying RPC failures according to `config.retries`.
+///
+/// Each attempt is bounded by `request_timeout_ms`. Attempts that run out of time are reported as
+/// [`FaucetError::RequestTimeout`] so they can be told apart from the node rejecting the call.
+pub async fn with_retries<C, T, F>(
+    client: &mut C,
+    config: &RpcConfig,
+    call: RpcCall,
+    mut op: F,
+) -> Result<T, FaucetError>
+where
+    F: for<'a> FnMut(&'a mut C) -> Pin<Box<dyn Future<Output = Result<T, ClientError>> + 'a>>,
+{
+    let attempts = config.retries.attempts_for(call);
+    let timeout = Duration::from_millis(config.request_timeout_ms);
+
+    let mut attempt = 1;
+    loop {
+        let err = match tokio::time::timeout(timeout, op(client)).await {
+            Ok(Ok(value)) => return Ok(value),
+            Ok(Err(err @ ClientError::RpcError(_))) => err.into(),
+            Ok(Err(err)) => return Err(err.into()),
+            Err(_) => FaucetError::RequestTimeout {
+                call,
+                timeout_ms: config.request_timeout_ms,
+            },
+        };
+
+        if attempt >= attempts {
+            return Err(err);
+        }
+        eprintln!("{call} failed (attempt {attempt}/{attempts}), retrying: {err}");
+        tokio::time::sleep(Duration::from_millis(config.retries.backoff_ms)).await;
+        attempt += 1;
+    }
+}
+
+/// Opens and immediately drops a TCP connection to `addr`.
+async fn probe_connect(addr: &str, timeout_ms: u64) -> Result<(), FaucetError> {
+    match tokio::time::timeout(Duration::from_millis(timeout_ms), TcpStream::connect(addr)).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(source)) => Err(FaucetError::Connect {
+            addr: addr.to_string(),
+            source,
+        }),
+        Err(_) => Err(FaucetError::ConnectTimeout {
+            addr: addr.to_string(),
+            timeout_ms,
+        }),
+    }
+}
+
+/// Extracts `host:port` from an `http://` proxy URL.
+fn parse_proxy(proxy: &str) -> Result<String, FaucetError> {
+    let authority = proxy
+        .strip_prefix("http://")
+        .ok_or_else(|| FaucetError::Config(format!("proxy `{proxy}` must be an http:// URL")))?
+        .trim_end_matches('/');
+
+    if authority.is_empty() || authority.contains('/') {
+        return Err(FaucetError::Config(format!("invalid proxy `{proxy}`")));
+    }
+
+    if authority.rsplit_once(':').is_some() {
+        Ok(authority.to_string())
+    } else {
+        Ok(format!("{authority}:80"))
+    }
+}
+
+/// TLS client of a tunnel to `host`, trusting the certificates of `ca_cert` or else the system
+/// roots.
+fn tunnel_tls(
+    host: &str,
+    ca_cert: Option<&Path>,
+) -> Result<(TlsConnector, ServerName<'static>), FaucetError> {
+    let server_name = ServerName::try_from(host.to_string())
+        .map_err(|err| FaucetError::Config(format!("invalid rpc host `{host}`: {err}")))?;
+
+    let mut roots = RootCertStore::empty();
+    match ca_cert {
+        Some(ca_cert) => {
+            let invalid = |reason: String| {
+                FaucetError::Tls(format!(
+                    "invalid ca certificate `{}`: {reason}",
+                    ca_cert.display()
+                ))
+            };
+            let certs = CertificateDer::pem_file_iter(ca_cert)
+                .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+                .map_err(|err| invalid(err.to_string()))?;
+            let (added, _) = roots.add_parsable_certificates(certs);
+            if added == 0 {
+                return Err(invalid("no certificates".into()));
+            }
+        }
+        None => {
+            let native = rustls_native_certs::load_native_certs();
+            let (added, _) = roots.add_parsable_certificates(native.certs);
+            if added == 0 {
+                return Err(FaucetError::Tls(match native.errors.first() {
+                    Some(err) => format!("no trusted root certificates: {err}"),
+                    None => "no trusted root certificates".into(),
+                }));
+            }
+        }
+    }
+
+    let mut config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|err| FaucetError::Tls(err.to_string()))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    config.alpn_protocols = vec![b"h2".to_vec()];
+    Ok((TlsConnector::from(Arc::new(config)), server_name))
+}
+
+/// Listens on an ephemeral local port and forwards every accepted connection to `target`, through
+/// an HTTP `CONNECT` request sent to `proxy` if given, wrapping the forwarded stream in `tls` if
+/// given.
+async fn spawn_tunnel(
+    proxy: Option<String>,
+    target: String,
+    tls: Option<(TlsConnector, ServerName<'static>)>,
+) -> Result<SocketAddr, FaucetError> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let local_addr = listener.local_addr()?;
+
+    tokio::spawn(async move {
+        while let Ok((inbound, _)) = listener.accept().await {
+            let proxy = proxy.clone();
+            let target = target.clone();
+            let tls = tls.clone();
+            tokio::spawn(async move {
+                if let Err(err) = tunnel_connection(inbound, proxy.as_deref(), &target, tls).await {
+                    eprintln!("Tunnel to {target} failed: {err}");
+                }
+            });
+        }
+    });
+
+    Ok(local_addr)
+}
+
+async fn tunnel_connection(
+    mut inbound: TcpStream,
+    proxy: Option<&str>,
+    target: &str,
+    tls: Option<(TlsConnector, ServerName<'static>)>,
+) -> std::io::Result<()> {
+    let mut outbound = match proxy {
+        Some(proxy) => connect_through(proxy, target).await?,
+        None => BufReader::new(TcpStream::connect(target).await?),
+    };
+
+    // Go through the reader so bytes it already buffered past the proxy response are not lost.
+    match tls {
+        Some((connector, server_name)) => {
+            let mut outbound = connector.connect(server_name, outbound).await?;
+            tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await?;
+        }
+        None => {
+            tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Opens a connection to `target` through an HTTP `CONNECT` request sent to `proxy`.
+async fn connect_through(proxy: &str, target: &str) -> std::io::Result<BufReader<TcpStream>> {
+    let mut outbound = BufReader::new(TcpStream::connect(proxy).await?);
+    outbound
+        .get_mut()
+        .write_all(format!("CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n\r\n").as_bytes())
+        .await?;
+
+    let mut status = String::new();
+    outbound.read_line(&mut status).await?;
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(std::io::Error::other(format!(
+            "proxy refused CONNECT: {}",
+            status.trim()
+        )));
+    }
+
+    // Skip the remaining response headers.
+    loop {
+        let mut line = String::new();
+        if outbound.read_line(&mut line).await? == 0 || line == "\r\n" {
+            break;
+        }
+    }
+    Ok(outbound)
+}
@@ -0,0 +1,322 @@
+//! Load test scenarios.
+//!
+//! A [`Scenario`] describes the workload of the `loadtest` binary in a TOML file: the rate and
+//! duration of the mints, the distribution of their amounts, the pool of recipients and the mix
+//! of note types. [`Scenario::plan`] draws every mint from an RNG seeded by the scenario, so two
+//! runs of the same file submit the same sequence of mints, and their [`ScenarioReport`]s differ
+//! only by how the faucet, node and client handled it, e.g. across `miden-client` upgrades.
+
+use std::{collections::BTreeMap, fs, path::Path, time::Duration};
+
+use miden_objects::block::BlockNumber;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{mint::MintNoteKind, FaucetError};
+
+/// Version of `miden-client` the binaries were built against, as locked in `Cargo.lock`.
+pub const MIDEN_CLIENT_VERSION: &str = env!("MIDEN_CLIENT_VERSION");
+
+/// Workload of a load test, read from a scenario file.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// Copied to the report, to tell runs of different scenarios apart.
+    pub name: String,
+    /// Mint submissions per second.
+    pub rate: f64,
+    /// Length of the submission phase; `rate * duration_secs` mints are submitted.
+    pub duration_secs: f64,
+    /// Seed of the draws of [`Scenario::plan`].
+    #[serde(default)]
+    pub seed: u64,
+    /// Time allowed for the submitted mints to commit once the submission phase ends.
+    #[serde(default = "default_commit_timeout_secs")]
+    pub commit_timeout_secs: u64,
+    pub amount: AmountDistribution,
+    #[serde(default)]
+    pub recipients: RecipientPool,
+    #[serde(default)]
+    pub notes: NoteMix,
+}
+
+fn default_commit_timeout_secs() -> u64 {
+    300
+}
+
+/// Distribution the amount of each mint is drawn from.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum AmountDistribution {
+    Fixed {
+        amount: u64,
+    },
+    /// Any amount of `min..=max`, equally likely.
+    Uniform {
+        min: u64,
+        max: u64,
+    },
+    /// One of `choices`, each as likely as its weight.
+    Weighted {
+        choices: Vec<WeightedAmount>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeightedAmount {
+    pub amount: u64,
+    pub weight: u32,
+}
+
+/// Throwaway wallets receiving the mints, never registered with the store.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecipientPool {
+    pub size: usize,
+    /// Draw each recipient at random instead of taking the pool in turn.
+    pub random: bool,
+}
+
+impl Default for RecipientPool {
+    fn default() -> Self {
+        Self {
+            size: 10,
+            random: false,
+        }
+    }
+}
+
+/// Relative weights of the note types minted, plain P2ID notes only by default.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NoteMix {
+    pub p2id: u32,
+    pub p2ide: u32,
+    pub timelocked: u32,
+    /// Blocks after the mint from which P2IDE notes can be reclaimed.
+    pub reclaim_after_blocks: u32,
+    /// Blocks after the mint from which timelocked notes can be consumed.
+    pub unlock_after_blocks: u32,
+}
+
+impl Default for NoteMix {
+    fn default() -> Self {
+        Self {
+            p2id: 1,
+            p2ide: 0,
+            timelocked: 0,
+            reclaim_after_blocks: 100,
+            unlock_after_blocks: 10,
+        }
+    }
+}
+
+/// Note type of a planned mint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlannedNote {
+    P2id,
+    P2ide,
+    Timelocked,
+}
+
+impl PlannedNote {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::P2id => "p2id",
+            Self::P2ide => "p2ide",
+            Self::Timelocked => "timelocked",
+        }
+    }
+
+    /// Note kind of a mint submitted at block `tip`.
+    pub fn note_kind(self, mix: &NoteMix, tip: BlockNumber) -> MintNoteKind {
+        let after = |blocks: u32| BlockNumber::from(tip.as_u32().saturating_add(blocks));
+        match self {
+            Self::P2id => MintNoteKind::P2id,
+            Self::P2ide => MintNoteKind::P2ide {
+                reclaim_height: after(mix.reclaim_after_blocks),
+            },
+            Self::Timelocked => MintNoteKind::Timelocked {
+                unlock_height: after(mix.unlock_after_blocks),
+                reclaim_height: None,
+            },
+        }
+    }
+}
+
+/// A mint of [`Scenario::plan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedMint {
+    /// Time from the start of the run at which the mint is submitted.
+    pub offset: Duration,
+    /// Index of the recipient in the pool.
+    pub recipient: usize,
+    pub amount: u64,
+    pub note: PlannedNote,
+}
+
+impl Scenario {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, FaucetError> {
+        let contents = fs::read_to_string(path.as_ref())?;
+        let scenario: Self = toml::from_str(&contents)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        let invalid = |reason: &str| FaucetError::Config(format!("scenario: {reason}"));
+        if !(self.rate > 0.0 && self.rate.is_finite()) {
+            return Err(invalid("rate must be positive"));
+        }
+        if !(self.duration_secs > 0.0 && self.duration_secs.is_finite()) {
+            return Err(invalid("duration_secs must be positive"));
+        }
+        if self.recipients.size == 0 {
+            return Err(invalid("recipients.size must be positive"));
+        }
+        match &self.amount {
+            AmountDistribution::Fixed { amount: 0 } => {
+                return Err(invalid("amount must be positive"))
+            }
+            AmountDistribution::Uniform { min, max } if *min == 0 || min > max => {
+                return Err(invalid("amount needs 0 < min <= max"))
+            }
+            AmountDistribution::Weighted { choices }
+                if choices.iter().all(|choice| choice.weight == 0)
+                    || choices.iter().any(|choice| choice.amount == 0) =>
+            {
+                return Err(invalid(
+                    "amount choices must be positive and one weight positive",
+                ))
+            }
+            _ => {}
+        }
+        if self.notes.p2id == 0 && self.notes.p2ide == 0 && self.notes.timelocked == 0 {
+            return Err(invalid("one note weight must be positive"));
+        }
+        Ok(())
+    }
+
+    /// Mints submitted by a run, `rate * duration_secs` rounded.
+    pub fn mint_count(&self) -> usize {
+        (self.rate * self.duration_secs).round() as usize
+    }
+
+    /// Draws the mints of a run in submission order; the same scenario always gives the same
+    /// plan.
+    pub fn plan(&self) -> Vec<PlannedMint> {
+        let mut rng = ChaCha20Rng::seed_from_u64(self.seed);
+        let notes = [
+            (PlannedNote::P2id, self.notes.p2id),
+            (PlannedNote::P2ide, self.notes.p2ide),
+            (PlannedNote::Timelocked, self.notes.timelocked),
+        ];
+        (0..self.mint_count())
+            .map(|index| {
+                let recipient = if self.recipients.random {
+                    rng.random_range(0..self.recipients.size)
+                } else {
+                    index % self.recipients.size
+                };
+                let amount = match &self.amount {
+                    AmountDistribution::Fixed { amount } => *amount,
+                    AmountDistribution::Uniform { min, max } => rng.random_range(*min..=*max),
+                    AmountDistribution::Weighted { choices } => *weighted(
+                        &mut rng,
+                        choices.iter().map(|choice| (&choice.amount, choice.weight)),
+                    ),
+                };
+                PlannedMint {
+                    offset: Duration::from_secs_f64(index as f64 / self.rate),
+                    recipient,
+                    amount,
+                    note: *weighted(&mut rng, notes.iter().map(|(note, w)| (note, *w))),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Draws one of `items`, each as likely as its weight; at least one weight must be positive.
+fn weighted<'a, T>(
+    rng: &mut ChaCha20Rng,
+    items: impl Iterator<Item = (&'a T, u32)> + Clone,
+) -> &'a T {
+    let total: u64 = items.clone().map(|(_, weight)| u64::from(weight)).sum();
+    let mut draw = rng.random_range(0..total);
+    for (item, weight) in items {
+        if draw < u64::from(weight) {
+            return item;
+        }
+        draw -= u64::from(weight);
+    }
+    unreachable!("the draw is below the total weight")
+}
+
+/// Percentiles of latency samples, in milliseconds; all zero without samples.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub samples: usize,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencySummary {
+    pub fn new(samples: &mut [Duration]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort();
+        let percentile = |p: usize| samples[(samples.len() - 1) * p / 100].as_secs_f64() * 1000.0;
+        Self {
+            samples: samples.len(),
+            p50_ms: percentile(50),
+            p90_ms: percentile(90),
+            p99_ms: percentile(99),
+            max_ms: percentile(100),
+        }
+    }
+}
+
+/// Outcome of a scenario run, written as JSON for comparison with other runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioReport {
+    pub scenario: String,
+    pub seed: u64,
+    pub faucet_version: String,
+    pub miden_client_version: String,
+    /// Unix timestamp, in seconds, of the start of the run.
+    pub started_at: u64,
+    pub planned: usize,
+    pub submitted: usize,
+    pub committed: usize,
+    pub minted_amount: u64,
+    /// Submissions per second actually achieved over the submission phase.
+    pub achieved_rate: f64,
+    /// Mints submitted per note type.
+    pub notes: BTreeMap<String, usize>,
+    pub submit_latency: LatencySummary,
+    /// Time from submission to commitment.
+    pub commit_time: LatencySummary,
+    /// Failed mints per kind of error.
+    pub failures: BTreeMap<String, usize>,
+}
+
+impl ScenarioReport {
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), FaucetError> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|err| FaucetError::Config(format!("cannot encode report: {err}")))?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, FaucetError> {
+        let contents = fs::read_to_string(path)?;
+        serde_json::from_str(&contents)
+            .map_err(|err| FaucetError::Config(format!("bad scenario report: {err}")))
+    }
+}
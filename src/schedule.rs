@@ -0,0 +1,252 @@
+//! Recurring mints.
+//!
+//! A schedule mints a fixed amount to a recipient at a fixed interval, e.g. `100` tokens every
+//! `6h`. Schedules are persisted in the [`Ledger`] and run by `serve`, which submits their mints
+//! through the same [`FaucetHandle`] as the network APIs, so they share the worker queue, the
+//! ledger records and the pause flag of the faucet.
+//!
+//! Each schedule stores the time of its next run. When `serve` was down past one or more runs,
+//! the [`CatchUp`] policy of the schedule decides whether every missed run is minted or only one.
+
+use std::{fmt, rc::Rc, str::FromStr, time::Duration};
+
+use miden_client::account::AccountId;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
+
+use crate::{
+    ledger::{unix_now, Ledger, ScheduleRecord},
+    mint::{AuxData, MintOptions, RequestSource},
+    service::FaucetHandle,
+    FaucetError,
+};
+
+/// Default time between two checks for due schedules, see [`crate::watcher::PollConfig`].
+pub const SCHEDULE_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// What a schedule does about runs missed while the scheduler was not running.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CatchUp {
+    /// Mint once for every missed run.
+    #[default]
+    All,
+    /// Mint once, however many runs were missed.
+    Once,
+}
+
+impl CatchUp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Once => "once",
+        }
+    }
+}
+
+impl FromSql for CatchUp {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|err| FromSqlError::Other(Box::new(err)))
+    }
+}
+
+impl fmt::Display for CatchUp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CatchUp {
+    type Err = FaucetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Self::All),
+            "once" => Ok(Self::Once),
+            other => Err(FaucetError::InvalidSchedule(
+                other.to_string(),
+                "catch-up policy must be `all` or `once`".into(),
+            )),
+        }
+    }
+}
+
+/// Parses an interval such as `6h`, `30 minutes` or `every 2 days`.
+///
+/// Supported units are seconds, minutes, hours, days and weeks, as `s`, `m`, `h`, `d`, `w` or
+/// spelled out.
+pub fn parse_interval(input: &str) -> Result<Duration, FaucetError> {
+    let invalid = |reason: &str| FaucetError::InvalidSchedule(input.to_string(), reason.into());
+
+    let spec = input.trim();
+    let spec = spec.strip_prefix("every ").unwrap_or(spec).trim();
+    let split = spec
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(spec.len());
+    let (count, unit) = spec.split_at(split);
+    let count: u64 = match count {
+        "" => 1,
+        count => count.parse().map_err(|_| invalid("interval is too long"))?,
+    };
+    let unit_secs = match unit.trim() {
+        "s" | "sec" | "secs" | "second" | "seconds" => 1,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60,
+        "h" | "hour" | "hours" => 60 * 60,
+        "d" | "day" | "days" => 24 * 60 * 60,
+        "w" | "week" | "weeks" => 7 * 24 * 60 * 60,
+        "" => return Err(invalid("missing unit, e.g. `6h`")),
+        _ => return Err(invalid("unknown unit")),
+    };
+
+    match count.checked_mul(unit_secs) {
+        Some(0) => Err(invalid("interval must not be zero")),
+        Some(secs) => Ok(Duration::from_secs(secs)),
+        None => Err(invalid("interval is too long")),
+    }
+}
+
+/// Formats an interval in the largest unit dividing it, e.g. `6h`.
+pub fn format_interval(interval: Duration) -> String {
+    let secs = interval.as_secs();
+    [
+        (7 * 24 * 60 * 60, "w"),
+        (24 * 60 * 60, "d"),
+        (60 * 60, "h"),
+        (60, "m"),
+    ]
+    .into_iter()
+    .find(|(unit, _)| secs.is_multiple_of(*unit))
+    .map_or_else(
+        || format!("{secs}s"),
+        |(unit, suffix)| format!("{}{suffix}", secs / unit),
+    )
+}
+
+impl ScheduleRecord {
+    /// Runs of the schedule due at `now`, counting every run missed since `next_run`.
+    pub fn due_runs(&self, now: u64) -> u64 {
+        if now < self.next_run {
+            return 0;
+        }
+        let missed = (now - self.next_run) / self.interval.as_secs().max(1) + 1;
+        match self.catch_up {
+            CatchUp::All => missed,
+            CatchUp::Once => 1,
+        }
+    }
+
+    /// First run after `now`, keeping the schedule aligned to its original start time.
+    pub fn next_run_after(&self, now: u64) -> u64 {
+        let interval = self.interval.as_secs().max(1);
+        if now < self.next_run {
+            return self.next_run;
+        }
+        self.next_run + ((now - self.next_run) / interval + 1) * interval
+    }
+}
+
+/// Mints of one schedule submitted by [`run_due_schedules`].
+#[derive(Debug)]
+pub struct ScheduleRun {
+    pub schedule_id: i64,
+    /// Ledger IDs of the submitted mints.
+    pub mint_ids: Vec<i64>,
+    /// Error that stopped the schedule short of its due runs; they are retried on the next poll.
+    pub error: Option<FaucetError>,
+}
+
+/// Submits the due runs of every schedule of `faucet_id` at `now` through `handle`.
+///
+/// Every submitted run moves the schedule on right away. When a mint fails, e.g. because the faucet
+/// is paused, the schedule stops there and its remaining due runs are retried on the next call.
+pub async fn run_due_schedules(
+    handle: &FaucetHandle,
+    ledger: &Ledger,
+    faucet_id: AccountId,
+    now: u64,
+) -> Result<Vec<ScheduleRun>, FaucetError> {
+    let mut runs = Vec::new();
+    for schedule in ledger.due_schedules(faucet_id, now)? {
+        let mut run = ScheduleRun {
+            schedule_id: schedule.id,
+            mint_ids: Vec::new(),
+            error: None,
+        };
+        let mut next_run = schedule.next_run;
+        for _ in 0..schedule.due_runs(now) {
+            match handle
+                .mint(
+                    schedule.recipient,
+                    schedule.amount,
+                    MintOptions {
+                        aux: AuxData::new(0, RequestSource::Schedule),
+                        ..MintOptions::default()
+                    },
+                )
+                .await
+            {
+                Ok(ticket) => {
+                    // Recorded run by run, so a restart does not mint the same run twice.
+                    next_run = match schedule.catch_up {
+                        CatchUp::All => next_run + schedule.interval.as_secs(),
+                        CatchUp::Once => schedule.next_run_after(now),
+                    };
+                    ledger.record_schedule_run(
+                        schedule.id,
+                        next_run,
+                        Some(ticket.mint_id),
+                        None,
+                    )?;
+                    run.mint_ids.push(ticket.mint_id);
+                }
+                Err(err) => {
+                    ledger.record_schedule_run(
+                        schedule.id,
+                        next_run,
+                        None,
+                        Some(&err.to_string()),
+                    )?;
+                    run.error = Some(err);
+                    break;
+                }
+            }
+        }
+        runs.push(run);
+    }
+    Ok(runs)
+}
+
+/// Runs the schedules of `faucet_id` every `interval` until the service stops.
+///
+/// Failed runs are reported on stderr and retried on the next poll. Must run inside a
+/// [`tokio::task::LocalSet`], next to the worker behind `handle`.
+pub async fn run_scheduler(
+    handle: FaucetHandle,
+    ledger: Rc<Ledger>,
+    faucet_id: AccountId,
+    interval: Duration,
+) {
+    let mut poll = tokio::time::interval(interval);
+    loop {
+        poll.tick().await;
+        let runs = match run_due_schedules(&handle, &ledger, faucet_id, unix_now()).await {
+            Ok(runs) => runs,
+            Err(err) => {
+                eprintln!("Failed to run schedules: {err}");
+                continue;
+            }
+        };
+        for run in runs {
+            match run.error {
+                Some(FaucetError::ServiceStopped) => return,
+                Some(err) => eprintln!(
+                    "Schedule {} failed after {} mint(s): {err}",
+                    run.schedule_id,
+                    run.mint_ids.len()
+                ),
+                None => {}
+            }
+        }
+    }
+}
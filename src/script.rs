@@ -0,0 +1,291 @@
+//! Transaction script templates.
+//!
+//! The default scripts are embedded in the binary, so deployments work from any working directory;
+//! [`load_script`] reads an override from disk instead.
+//!
+//! Scripts may contain `{{name}}` placeholders that are substituted with [`ScriptValue`]s before
+//! compilation, e.g. `push.{{owner}}` or `push.{{max_supply}}`. Values can also be handed to the
+//! script at run time as the script argument or through the advice map. Every problem with the
+//! template or its parameters is reported before anything is submitted.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use miden_client::{account::AccountId, transaction::TransactionRequest, Felt, Word};
+
+use crate::{
+    account::parse_account_id,
+    node::{FaucetNode, TransactionCost},
+    tx::TxBuilder,
+    FaucetError,
+};
+
+/// Outcome of [`check_script`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptCheck {
+    /// The script compiled; it was not executed.
+    Compiled,
+    /// The script compiled and executed against the account without error, at this cost.
+    Executed(AccountId, TransactionCost),
+}
+
+/// Default deployment script, run against a new faucet by [`crate::deploy::deploy_faucet`].
+pub const DEPLOY_SCRIPT: &str = include_str!("../masm/deploy.masm");
+
+/// Directory of the repository holding the scripts, also searched for relative overrides.
+pub const SCRIPT_DIR: &str = "masm";
+
+/// Returns the script at `path`, or `default` when no override is given.
+///
+/// A relative `path` is looked up in the working directory, in its [`SCRIPT_DIR`] and in the
+/// directory of the running executable, in this order.
+pub fn load_script(path: Option<&Path>, default: &str) -> Result<String, FaucetError> {
+    let Some(path) = path else {
+        return Ok(default.to_string());
+    };
+
+    let searched = script_search_paths(path);
+    match searched.iter().find(|candidate| candidate.is_file()) {
+        Some(found) => Ok(fs::read_to_string(found)?),
+        None => Err(FaucetError::ScriptNotFound {
+            path: path.display().to_string(),
+            searched: searched
+                .iter()
+                .map(|candidate| candidate.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        }),
+    }
+}
+
+fn script_search_paths(path: &Path) -> Vec<PathBuf> {
+    if path.is_absolute() {
+        return vec![path.to_path_buf()];
+    }
+
+    let mut paths = vec![path.to_path_buf(), Path::new(SCRIPT_DIR).join(path)];
+    if let Some(exe_dir) = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        paths.push(exe_dir.join(path));
+    }
+    paths
+}
+
+/// A value substituted for a placeholder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptValue {
+    /// Rendered as `suffix.prefix`, so `push.{{id}}` leaves the prefix on top of the stack.
+    Account(AccountId),
+    /// A single field element, rendered in decimal.
+    Felt(u64),
+    /// Rendered as a hex word literal.
+    Word(Word),
+}
+
+impl fmt::Display for ScriptValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Account(id) => write!(f, "{}.{}", id.suffix(), id.prefix().as_felt()),
+            Self::Felt(value) => write!(f, "{value}"),
+            Self::Word(word) => f.write_str(&word.to_hex()),
+        }
+    }
+}
+
+impl FromStr for ScriptValue {
+    type Err = FaucetError;
+
+    /// Parses a decimal number, an account ID or a hex word.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        if let Ok(value) = input.parse::<u64>() {
+            return Ok(Self::Felt(value));
+        }
+        if let Ok(id) = parse_account_id(input, None) {
+            return Ok(Self::Account(id));
+        }
+        Word::try_from(input).map(Self::Word).map_err(|_| {
+            FaucetError::ScriptTemplate(format!(
+                "`{input}` is neither a number, an account ID nor a word"
+            ))
+        })
+    }
+}
+
+/// Parameters of a [`ScriptTemplate`].
+#[derive(Debug, Clone, Default)]
+pub struct ScriptParams {
+    values: BTreeMap<String, ScriptValue>,
+    script_arg: Option<Word>,
+    advice: Vec<(Word, Vec<Felt>)>,
+}
+
+impl ScriptParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Substitutes `value` for the `{{name}}` placeholders.
+    pub fn with(mut self, name: impl Into<String>, value: ScriptValue) -> Self {
+        self.values.insert(name.into(), value);
+        self
+    }
+
+    /// Parses a `name=value` pair as given on the command line.
+    pub fn with_assignment(self, assignment: &str) -> Result<Self, FaucetError> {
+        let (name, value) = assignment.split_once('=').ok_or_else(|| {
+            FaucetError::ScriptTemplate(format!("expected `name=value`, got `{assignment}`"))
+        })?;
+        Ok(self.with(name.trim(), value.parse()?))
+    }
+
+    /// Word placed on the stack when the script starts.
+    pub fn with_script_arg(mut self, arg: Word) -> Self {
+        self.script_arg = Some(arg);
+        self
+    }
+
+    /// Makes `values` available to the script under `key` in the advice map.
+    pub fn with_advice(mut self, key: Word, values: Vec<Felt>) -> Self {
+        self.advice.push((key, values));
+        self
+    }
+
+    fn get(&self, name: &str) -> Option<&ScriptValue> {
+        self.values.get(name)
+    }
+}
+
+/// Transaction script source with `{{name}}` placeholders.
+#[derive(Debug, Clone)]
+pub struct ScriptTemplate {
+    source: String,
+}
+
+impl ScriptTemplate {
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+        }
+    }
+
+    /// Names of the placeholders used by the template.
+    pub fn placeholders(&self) -> Result<BTreeSet<String>, FaucetError> {
+        let mut names = BTreeSet::new();
+        self.substitute(|name| {
+            names.insert(name.to_string());
+            Ok(String::new())
+        })?;
+        Ok(names)
+    }
+
+    /// Substitutes every placeholder with its value in `params`.
+    ///
+    /// Fails on placeholders without a value and on numbers outside the field. Values named in
+    /// `params` but missing from the template are rejected as well, unless they are listed in
+    /// `builtins`.
+    pub fn render(&self, params: &ScriptParams, builtins: &[&str]) -> Result<String, FaucetError> {
+        if let Some((name, _)) = params
+            .values
+            .iter()
+            .find(|(_, value)| matches!(value, ScriptValue::Felt(v) if Felt::try_from(*v).is_err()))
+        {
+            return Err(FaucetError::ScriptTemplate(format!(
+                "value of `{name}` is not a field element"
+            )));
+        }
+        let used = self.placeholders()?;
+        if let Some(unused) = params
+            .values
+            .keys()
+            .find(|name| !used.contains(*name) && !builtins.contains(&name.as_str()))
+        {
+            return Err(FaucetError::ScriptTemplate(format!(
+                "parameter `{unused}` is not used by the script"
+            )));
+        }
+
+        self.substitute(|name| {
+            params.get(name).map(ToString::to_string).ok_or_else(|| {
+                FaucetError::ScriptTemplate(format!("no value for placeholder `{{{{{name}}}}}`"))
+            })
+        })
+    }
+
+    fn substitute(
+        &self,
+        mut value: impl FnMut(&str) -> Result<String, FaucetError>,
+    ) -> Result<String, FaucetError> {
+        let source = self.source.as_str();
+        let mut output = String::with_capacity(source.len());
+        let mut pos = 0;
+        while let Some(start) = source[pos..].find("{{").map(|offset| pos + offset) {
+            let line = source[..start].matches('\n').count() + 1;
+            let end = source[start..]
+                .find("}}")
+                .map(|offset| start + offset + 2)
+                .ok_or_else(|| {
+                    FaucetError::ScriptTemplate(format!("unterminated placeholder on line {line}"))
+                })?;
+            let name = source[start + 2..end - 2].trim();
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(FaucetError::ScriptTemplate(format!(
+                    "invalid placeholder `{}` on line {line}",
+                    &source[start..end]
+                )));
+            }
+            output.push_str(&source[pos..start]);
+            output.push_str(&value(name)?);
+            pos = end;
+        }
+        output.push_str(&source[pos..]);
+        Ok(output)
+    }
+}
+
+/// Renders `template` with `params` and compiles it into a transaction request.
+///
+/// `builtins` names parameters the caller always supplies, which the template may ignore.
+pub fn script_request<N: FaucetNode>(
+    node: &N,
+    template: &ScriptTemplate,
+    params: &ScriptParams,
+    builtins: &[&str],
+) -> Result<TransactionRequest, FaucetError> {
+    let code = template.render(params, builtins)?;
+    let script = node.compile_tx_script(&code)?;
+
+    let mut builder = TxBuilder::new()
+        .script(script)
+        .advice(params.advice.iter().cloned());
+    if let Some(arg) = params.script_arg {
+        builder = builder.script_arg(arg);
+    }
+    builder.build()
+}
+
+/// Renders and compiles `template` without submitting anything.
+///
+/// With `dry_run`, the script is also executed against that account, which must be tracked by
+/// `node`, so errors raised by the script itself show up as well.
+pub async fn check_script<N: FaucetNode>(
+    node: &mut N,
+    template: &ScriptTemplate,
+    params: &ScriptParams,
+    dry_run: Option<AccountId>,
+) -> Result<ScriptCheck, FaucetError> {
+    let request = script_request(node, template, params, &[])?;
+    match dry_run {
+        Some(account_id) => {
+            let cost = node.execute_transaction(account_id, request).await?;
+            Ok(ScriptCheck::Executed(account_id, cost))
+        }
+        None => Ok(ScriptCheck::Compiled),
+    }
+}
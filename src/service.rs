@@ -0,0 +1,1699 @@
+//! Mint service shared by the network APIs.
+//!
+//! The client is not `Send`, so it cannot be used from the request handlers of the network
+//! servers directly. [`FaucetWorker`] owns the node and processes requests one at a time on the
+//! local task set; handlers talk to it through the cloneable, `Send` [`FaucetHandle`].
+//!
+//! Mint transactions of the faucet owner go through its [`TxExecutor`](crate::executor::TxExecutor),
+//! so they never overlap with other transactions of the owner.
+//!
+//! Mints are queued per requester identity, see [`FaucetHandle::with_identity`], and served in
+//! turns by a [`FairQueue`], so a bulk requester cannot starve the others. Mints naming a
+//! campaign are checked against it when served, see [`crate::campaign`], and mints requested
+//! with a referral code credit its referrer, see [`crate::referral`]. Mints are refused to and by
+//! the accounts and requesters of the deny list, see [`check_access_lists`], and to faucets, which
+//! could never claim them, see [`check_recipient`]. Mints requested through the APIs are judged by
+//! the abuse policies of the worker, see [`crate::abuse`], among them the daily budget of the
+//! faucet.
+//!
+//! The worker also serves the operations of the admin API, see [`crate::admin`]: pausing the
+//! faucet, inspecting the queue, managing the access lists and reclaiming expired mints, and
+//! reports its sync progress for monitoring, see [`FaucetHandle::sync_status`].
+
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    rc::Rc,
+    time::Duration,
+};
+
+use lettre::Address;
+use miden_client::{
+    account::AccountId,
+    asset::TokenSymbol,
+    note::{Note, NoteId},
+    transaction::TransactionId,
+    Word,
+};
+use miden_objects::{block::BlockNumber, MAX_OUTPUT_NOTES_PER_TX};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc, mpsc::error::TryRecvError, oneshot};
+
+use crate::{
+    abuse::{AbusePolicies, AbuseRequest},
+    access::{check_access_lists, AccessList},
+    account::parse_account_id,
+    audit::AuditEntry,
+    cache::FaucetCache,
+    campaign::check_campaign_mints,
+    email::NoteMailer,
+    execution::track_network_execution,
+    executor::Executors,
+    fair::FairQueue,
+    finality::FinalityChecker,
+    ledger::{unix_now, Ledger, ListEntry, MintRecord, MintStats, MintStatus, ReferralCodeRecord},
+    mint::{
+        check_recipient, check_recipient_on_chain, estimate_mint_batch, mint_batch_from,
+        remint_options, AmountConfig, AuxData, BatchMint, MintNoteKind, MintOptions,
+        NoteTagStrategy, RequestSource,
+    },
+    network::validate_network_name,
+    node::{FaucetNode, TransactionCost, TxState},
+    note_file::mint_note_file,
+    pause::set_paused,
+    receipt::{mint_receipt, MintReceipt},
+    reclaim::{reclaim_expired, ReclaimReport},
+    referral::{check_referral, ReferralConfig},
+    watcher::{track_transaction, BlockWatcher, SharedNode},
+    FaucetError,
+};
+
+/// Settings of the `serve` command, read from the `[service]` section of the configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServiceConfig {
+    /// Network faucet the service mints from.
+    pub faucet_id: Option<String>,
+    /// Name requests select the network of the top-level settings by, see [`crate::network`].
+    pub network: String,
+    /// Address of the gRPC API; the API is disabled when unset.
+    pub grpc_addr: Option<SocketAddr>,
+    /// Address of the REST API; the API is disabled when unset.
+    pub rest_addr: Option<SocketAddr>,
+    /// Requests buffered before callers have to wait for the worker.
+    pub queue_capacity: usize,
+    /// Mints served per turn of each requester identity when the queue backs up, 1 for
+    /// identities not listed, e.g. `scheduler = 4` or `"github:octocat" = 2`.
+    pub queue_weights: BTreeMap<String, u32>,
+    /// Mints of a batch request, all submitted in one transaction.
+    pub max_batch_size: usize,
+    /// Mint reclaimable P2IDE notes the faucet can recover this many blocks after the mint, or
+    /// after the unlock height of timelocked notes, unless the request sets its own reclaim
+    /// height. Mints plain P2ID notes when unset.
+    pub reclaim_after_blocks: Option<u32>,
+    /// How the notes of mints that name no tag are tagged: `account_id`, the tag of the
+    /// recipient, `network` for the network to consume them into network account recipients, or
+    /// `use_case:<id>[:<payload>]` for network accounts picking them up by use case.
+    pub note_tag: NoteTagStrategy,
+    /// Time allowed for the network to consume the committed notes of mints with network-execution
+    /// tags, see [`crate::execution`], before the service stops waiting for it.
+    pub network_execution_timeout_ms: u64,
+    /// Blocks produced on top of a mint transaction before the mint is reported committed.
+    pub confirmations: u32,
+    /// Re-check the mints committed within this many blocks of the chain tip on every block, so
+    /// mints a reorg dropped from the chain are invalidated and minted again. Disabled when 0.
+    pub reorg_check_blocks: u32,
+    /// Refuse mints to public accounts without a state on chain. New public accounts only reach
+    /// the chain with their first transaction, so this also refuses fresh wallets.
+    pub require_onchain_recipients: bool,
+    /// Tokens the main faucet distributes at most within any 24 hours, a `distributed`
+    /// `daily_budget` abuse policy of the faucet, see [`crate::abuse`]. Unlimited when unset.
+    pub daily_budget: Option<u64>,
+    /// Further faucets of the default network minting other tokens, by token symbol, selected by
+    /// the `token` of a request. Requests without one mint from `faucet_id`. Other networks set
+    /// theirs in `[networks.<name>.tokens]`.
+    pub tokens: BTreeMap<String, TokenFaucetConfig>,
+}
+
+/// Faucet minting one token next to the main faucet of `serve`, read from a
+/// `[service.tokens.<SYMBOL>]` section of the configuration.
+///
+/// Each token faucet is served by its own worker with its own amounts and, optionally, its own
+/// ledger, on the node of its network.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TokenFaucetConfig {
+    /// Faucet minting the token; the latest faucet deployed with the token's symbol, as recorded
+    /// by `deploy` in the deployments registry of the network ledger and not retired since, when
+    /// unset.
+    pub faucet_id: Option<String>,
+    /// Ledger of the mints of the token; the ledger of the network when unset.
+    pub ledger_path: Option<PathBuf>,
+    /// Amounts a mint of the token may request, in place of the `[mint]` section. Access tiers
+    /// only adjust the amounts of the main faucet.
+    pub mint: AmountConfig,
+    /// Tokens the faucet distributes at most within 24 hours, like `service.daily_budget`.
+    pub daily_budget: Option<u64>,
+}
+
+impl TokenFaucetConfig {
+    /// Faucet minting `symbol`, looked up in the deployments registry of `ledger` unless
+    /// configured.
+    pub fn resolve_faucet_id(
+        &self,
+        symbol: &str,
+        ledger: &Ledger,
+    ) -> Result<AccountId, FaucetError> {
+        if let Some(faucet_id) = &self.faucet_id {
+            return parse_account_id(faucet_id, None);
+        }
+        let deployment = ledger
+            .deployments()?
+            .into_iter()
+            .rev()
+            .find(|deployment| deployment.symbol == symbol && deployment.retired_at.is_none())
+            .ok_or_else(|| {
+                FaucetError::Config(format!(
+                    "no faucet deployed for token {symbol}, set service.tokens.{symbol}.faucet_id"
+                ))
+            })?;
+        parse_account_id(&deployment.faucet_id, None)
+    }
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            faucet_id: None,
+            network: "default".into(),
+            grpc_addr: None,
+            rest_addr: None,
+            queue_capacity: 64,
+            queue_weights: BTreeMap::new(),
+            max_batch_size: 50,
+            reclaim_after_blocks: None,
+            note_tag: NoteTagStrategy::AccountId,
+            network_execution_timeout_ms: 300_000,
+            confirmations: 0,
+            reorg_check_blocks: 0,
+            require_onchain_recipients: false,
+            daily_budget: None,
+            tokens: BTreeMap::new(),
+        }
+    }
+}
+
+impl ServiceConfig {
+    /// Checks the settings without contacting the node.
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        if let Some(faucet_id) = &self.faucet_id {
+            parse_account_id(faucet_id, None)?;
+        }
+        validate_network_name(&self.network)?;
+        if let (Some(grpc_addr), Some(rest_addr)) = (self.grpc_addr, self.rest_addr) {
+            if grpc_addr == rest_addr {
+                return Err(FaucetError::Config(format!(
+                    "service.grpc_addr and service.rest_addr both use {grpc_addr}"
+                )));
+            }
+        }
+        if self.queue_capacity == 0 {
+            return Err(FaucetError::Config(
+                "service.queue_capacity must be positive".into(),
+            ));
+        }
+        if let Some((identity, _)) = self.queue_weights.iter().find(|(_, weight)| **weight == 0) {
+            return Err(FaucetError::Config(format!(
+                "service.queue_weights of `{identity}` must be positive"
+            )));
+        }
+        if !(1..=MAX_OUTPUT_NOTES_PER_TX).contains(&self.max_batch_size) {
+            return Err(FaucetError::Config(format!(
+                "service.max_batch_size must be between 1 and {MAX_OUTPUT_NOTES_PER_TX}"
+            )));
+        }
+        if self.reclaim_after_blocks == Some(0) {
+            return Err(FaucetError::Config(
+                "service.reclaim_after_blocks must be positive, leave it unset for plain P2ID notes"
+                    .into(),
+            ));
+        }
+        if self.network_execution_timeout_ms == 0 {
+            return Err(FaucetError::Config(
+                "service.network_execution_timeout_ms must be positive".into(),
+            ));
+        }
+        if self.daily_budget == Some(0) {
+            return Err(FaucetError::Config(
+                "service.daily_budget must be positive, leave it unset for no budget".into(),
+            ));
+        }
+        for (symbol, token) in &self.tokens {
+            if token.daily_budget == Some(0) {
+                return Err(FaucetError::Config(format!(
+                    "service.tokens.{symbol}.daily_budget must be positive"
+                )));
+            }
+            TokenSymbol::new(symbol).map_err(|err| {
+                FaucetError::Config(format!("invalid token symbol `{symbol}`: {err}"))
+            })?;
+            if let Some(faucet_id) = &token.faucet_id {
+                parse_account_id(faucet_id, None)?;
+            }
+            token.mint.validate().map_err(|err| match err {
+                FaucetError::Config(message) => {
+                    FaucetError::Config(format!("service.tokens.{symbol}.{message}"))
+                }
+                err => err,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Mint events buffered for each subscriber of [`FaucetHandle::subscribe`].
+pub const EVENT_CAPACITY: usize = 256;
+
+/// Returned by [`FaucetHandle::mint`] once the mint transaction is submitted.
+#[derive(Debug, Clone)]
+pub struct MintTicket {
+    pub mint_id: i64,
+    pub transaction_id: TransactionId,
+    /// ID of the P2ID note the recipient will receive.
+    pub note_id: NoteId,
+    /// The note itself, which the recipient of a private note needs to claim it.
+    pub note: Note,
+    /// Commitment to the note and its metadata, as found in the note tree of the block.
+    pub note_commitment: Word,
+    /// Amount minted, which differs from the requested one if the service clamped it.
+    pub amount: u64,
+}
+
+/// One mint of [`FaucetHandle::mint_batch`].
+#[derive(Debug, Clone)]
+pub struct BatchEntry {
+    pub recipient: AccountId,
+    /// The service's default amount when `None`.
+    pub amount: Option<u64>,
+    pub options: MintOptions,
+    pub email: Option<Address>,
+}
+
+/// Returned by [`FaucetHandle::mint_batch`] once the batch transaction is submitted.
+#[derive(Debug, Clone)]
+pub struct BatchTicket {
+    pub transaction_id: TransactionId,
+    /// Tickets of the mints, in the order of the entries.
+    pub mints: Vec<MintTicket>,
+}
+
+/// Progress of a mint, published to [`FaucetHandle::subscribe`] subscribers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MintEvent {
+    pub mint_id: i64,
+    pub update: MintUpdate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MintUpdate {
+    /// The mint transaction was submitted and recorded in the ledger.
+    Submitted,
+    /// The node tracks the transaction and it awaits inclusion in a block.
+    Pending,
+    Committed {
+        block_num: u32,
+    },
+    /// The transaction was discarded or could not be tracked to commitment.
+    Discarded {
+        reason: String,
+    },
+    /// A reorg reverted the reported commitment.
+    ///
+    /// Without `replaced_by` the transaction went back to pending and further updates follow.
+    /// Otherwise it was dropped, and the note is minted again by mint `replaced_by`.
+    Invalidated {
+        reason: String,
+        replaced_by: Option<i64>,
+    },
+}
+
+impl MintUpdate {
+    /// Whether no further updates follow this one.
+    pub fn is_final(&self) -> bool {
+        match self {
+            Self::Committed { .. } | Self::Discarded { .. } => true,
+            Self::Invalidated { replaced_by, .. } => replaced_by.is_some(),
+            Self::Submitted | Self::Pending => false,
+        }
+    }
+}
+
+impl From<&MintRecord> for MintUpdate {
+    fn from(record: &MintRecord) -> Self {
+        match record.status {
+            MintStatus::Submitted => Self::Submitted,
+            MintStatus::Committed => Self::Committed {
+                block_num: record.commit_block.unwrap_or_default(),
+            },
+            MintStatus::Failed => Self::Discarded {
+                reason: record.error.clone().unwrap_or_default(),
+            },
+            MintStatus::Invalidated => Self::Invalidated {
+                reason: record.error.clone().unwrap_or_default(),
+                replaced_by: record.replaced_by,
+            },
+        }
+    }
+}
+
+/// Who asked for a queued mint, as judged by the abuse policies.
+struct Requester {
+    identity: Option<String>,
+    /// Address of the API client, `None` for mints of the service itself.
+    client_ip: Option<IpAddr>,
+}
+
+impl Requester {
+    /// Request the abuse policies judge for a mint of `amount` from `faucet_id` to `recipient`,
+    /// `None` for mints not requested through an API.
+    fn abuse_request(
+        &self,
+        faucet_id: AccountId,
+        recipient: AccountId,
+        amount: u64,
+    ) -> Option<AbuseRequest> {
+        Some(AbuseRequest {
+            identity: self.identity.clone(),
+            ip: self.client_ip?,
+            faucet_id,
+            recipient,
+            amount,
+            batched: 0,
+            now: unix_now(),
+        })
+    }
+}
+
+struct QueuedMint {
+    actor: String,
+    requester: Requester,
+    recipient: AccountId,
+    amount: Option<u64>,
+    /// Bounds of the requester's tier, the worker's when unset.
+    amounts: Option<AmountConfig>,
+    options: MintOptions,
+    email: Option<Address>,
+    referral: Option<String>,
+    /// Unix timestamp at which the mint was queued, recorded with it, see
+    /// [`MintRecord::queued_at`].
+    queued_at: u64,
+    reply: oneshot::Sender<Result<MintTicket, FaucetError>>,
+}
+
+struct QueuedBatch {
+    actor: String,
+    requester: Requester,
+    entries: Vec<BatchEntry>,
+    amounts: Option<AmountConfig>,
+    queued_at: u64,
+    reply: oneshot::Sender<Result<BatchTicket, FaucetError>>,
+}
+
+/// Item of the mint queue; a batch takes a single turn of its requester.
+enum Queued {
+    Mint(QueuedMint),
+    Batch(QueuedBatch),
+}
+
+enum Request {
+    Mint {
+        identity: String,
+        mint: Queued,
+    },
+    Status {
+        mint_id: i64,
+        reply: oneshot::Sender<Result<Option<MintRecord>, FaucetError>>,
+    },
+    Stats {
+        reply: oneshot::Sender<Result<MintStats, FaucetError>>,
+    },
+    Receipt {
+        mint_id: i64,
+        reply: oneshot::Sender<Result<Option<MintReceipt>, FaucetError>>,
+    },
+    Preview {
+        recipient: AccountId,
+        amount: Option<u64>,
+        amounts: Option<AmountConfig>,
+        options: MintOptions,
+        reply: oneshot::Sender<Result<MintPreview, FaucetError>>,
+    },
+    SetPaused {
+        actor: String,
+        paused: bool,
+        reply: oneshot::Sender<Result<TransactionId, FaucetError>>,
+    },
+    Queue {
+        reply: oneshot::Sender<Result<QueueInfo, FaucetError>>,
+    },
+    SyncStatus {
+        reply: oneshot::Sender<Result<SyncStatus, FaucetError>>,
+    },
+    Reclaim {
+        actor: String,
+        reply: oneshot::Sender<Result<ReclaimReport, FaucetError>>,
+    },
+    ListEntries {
+        reply: oneshot::Sender<Result<Vec<ListEntry>, FaucetError>>,
+    },
+    AddListEntry {
+        actor: String,
+        list: AccessList,
+        subject: String,
+        note: Option<String>,
+        reply: oneshot::Sender<Result<ListEntry, FaucetError>>,
+    },
+    RemoveListEntry {
+        actor: String,
+        list: AccessList,
+        subject: String,
+        reply: oneshot::Sender<Result<bool, FaucetError>>,
+    },
+    Audit {
+        entry: AuditEntry,
+        reply: oneshot::Sender<Result<(), FaucetError>>,
+    },
+}
+
+/// Mints waiting in the queue of a [`FaucetWorker`], see [`FaucetHandle::queue`].
+#[derive(Debug, Clone)]
+pub struct QueueInfo {
+    /// Queued mints, a batch counting as one.
+    pub len: usize,
+    /// Mints the worker queues at most before holding back requests.
+    pub capacity: usize,
+    /// Requester identities with queued mints and how many, the one being served first.
+    pub identities: Vec<(String, usize)>,
+}
+
+/// Sync progress and backlogs of a [`FaucetWorker`], see [`FaucetHandle::sync_status`].
+#[derive(Debug, Clone)]
+pub struct SyncStatus {
+    /// Block the local store is synced to, `None` before the first sync.
+    pub store_block: Option<BlockNumber>,
+    /// Latest block of the chain according to the node.
+    pub chain_tip: BlockNumber,
+    /// Time since the last successful sync, `None` before the first one.
+    pub since_last_sync: Option<Duration>,
+    /// Queued mints, a batch counting as one.
+    pub queue_len: usize,
+    /// Mints the worker queues at most before holding back requests.
+    pub queue_capacity: usize,
+    /// Transactions waiting to be built, proven and submitted, see [`Executors::backlog`].
+    pub prover_backlog: usize,
+}
+
+impl SyncStatus {
+    /// Blocks the local store is behind the chain, the whole chain before the first sync.
+    pub fn lag(&self) -> u32 {
+        let store_block = self.store_block.map_or(0, |block| block.as_u32());
+        self.chain_tip.as_u32().saturating_sub(store_block)
+    }
+}
+
+/// Mint a [`FaucetHandle::preview`] would submit and what submitting it would cost.
+#[derive(Debug, Clone)]
+pub struct MintPreview {
+    pub amount: u64,
+    pub cost: TransactionCost,
+}
+
+/// Cloneable handle submitting requests to a [`FaucetWorker`].
+#[derive(Clone)]
+pub struct FaucetHandle {
+    sender: mpsc::Sender<Request>,
+    events: broadcast::Sender<MintEvent>,
+    actor: String,
+    identity: Option<String>,
+    client_ip: Option<IpAddr>,
+    amounts: Option<AmountConfig>,
+    referral: Option<String>,
+}
+
+impl FaucetHandle {
+    /// Handle whose mints are recorded in the audit log as requested by `actor`, e.g. `rest`.
+    ///
+    /// Handles returned by [`faucet_service`] record their mints as requested by `service`.
+    pub fn with_actor(&self, actor: impl Into<String>) -> Self {
+        Self {
+            actor: actor.into(),
+            ..self.clone()
+        }
+    }
+
+    /// Handle whose mints share the turns of `identity` in the mint queue, e.g. a signed-in
+    /// user.
+    ///
+    /// Mints of handles without identity are queued under their client address, `ip:<addr>`, see
+    /// [`with_client_ip`](Self::with_client_ip), and only without one under their recipient,
+    /// `recipient:<id>`, so an anonymous client sending to many recipients still takes one turn.
+    pub fn with_identity(&self, identity: impl Into<String>) -> Self {
+        Self {
+            identity: Some(identity.into()),
+            ..self.clone()
+        }
+    }
+
+    /// Handle whose mints were requested from `ip`, judged by the abuse policies of the worker,
+    /// see [`crate::abuse`].
+    pub fn with_client_ip(&self, ip: IpAddr) -> Self {
+        Self {
+            client_ip: Some(ip),
+            ..self.clone()
+        }
+    }
+
+    /// Address set with [`with_client_ip`](Self::with_client_ip).
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        self.client_ip
+    }
+
+    /// Handle whose mints are bounded by `amounts` instead of the worker's, e.g. those of the
+    /// requester's [`Tier`](crate::access::Tier).
+    pub fn with_amounts(&self, amounts: AmountConfig) -> Self {
+        Self {
+            amounts: Some(amounts),
+            ..self.clone()
+        }
+    }
+
+    /// Handle whose mints convert referral code `code`, see [`crate::referral`].
+    ///
+    /// Mints fail with [`FaucetError::InvalidReferral`] if the worker has no referral settings or
+    /// [`check_referral`] rejects the code. Batches ignore the code.
+    pub fn with_referral(&self, code: impl Into<String>) -> Self {
+        Self {
+            referral: Some(code.into()),
+            ..self.clone()
+        }
+    }
+
+    /// Mints `amount` tokens to `recipient`.
+    ///
+    /// A plain P2ID note in `options` is minted as P2IDE, and a timelocked note without reclaim
+    /// height made reclaimable, when the service has a default reclaim period. Fails with
+    /// [`FaucetError::FaucetPaused`] while the faucet is paused, and with
+    /// [`FaucetError::AmountOutOfRange`] for amounts outside the service's bounds unless it clamps
+    /// them. Mints naming a campaign in their aux value fail as described in
+    /// [`check_campaign_mints`].
+    pub async fn mint(
+        &self,
+        recipient: AccountId,
+        amount: u64,
+        options: MintOptions,
+    ) -> Result<MintTicket, FaucetError> {
+        self.mint_with_email(recipient, Some(amount), options, None)
+            .await
+    }
+
+    /// Mints like [`mint`](Self::mint) the service's default amount if `amount` is `None`, and
+    /// emails the note file to `email` once the mint is committed. Fails with
+    /// [`FaucetError::EmailDisabled`] if the worker has no mailer.
+    pub async fn mint_with_email(
+        &self,
+        recipient: AccountId,
+        amount: Option<u64>,
+        options: MintOptions,
+        email: Option<Address>,
+    ) -> Result<MintTicket, FaucetError> {
+        let identity = self.queue_identity(recipient);
+        self.call(|reply| Request::Mint {
+            identity,
+            mint: Queued::Mint(QueuedMint {
+                actor: self.actor.clone(),
+                requester: self.requester(),
+                recipient,
+                amount,
+                amounts: self.amounts.clone(),
+                options,
+                email,
+                referral: self.referral.clone(),
+                queued_at: unix_now(),
+                reply,
+            }),
+        })
+        .await
+    }
+
+    /// Mints `entries` like [`mint_with_email`](Self::mint_with_email) in a single transaction.
+    ///
+    /// The batch is queued as one mint, under the identity of the handle, else its client address
+    /// or else its first recipient. Fails as a whole, with [`FaucetError::BatchSize`] if it is
+    /// empty or larger than the service's [`ServiceConfig::max_batch_size`].
+    pub async fn mint_batch(&self, entries: Vec<BatchEntry>) -> Result<BatchTicket, FaucetError> {
+        let identity = entries
+            .first()
+            .map(|entry| self.queue_identity(entry.recipient))
+            .unwrap_or_default();
+        self.call(|reply| Request::Mint {
+            identity,
+            mint: Queued::Batch(QueuedBatch {
+                actor: self.actor.clone(),
+                requester: self.requester(),
+                entries,
+                amounts: self.amounts.clone(),
+                queued_at: unix_now(),
+                reply,
+            }),
+        })
+        .await
+    }
+
+    /// Executes the transaction [`mint_with_email`](Self::mint_with_email) would submit for
+    /// `recipient` without submitting it, and returns the resolved amount and the cost.
+    ///
+    /// Served right away rather than queued, nothing is recorded.
+    pub async fn preview(
+        &self,
+        recipient: AccountId,
+        amount: Option<u64>,
+        options: MintOptions,
+    ) -> Result<MintPreview, FaucetError> {
+        self.call(|reply| Request::Preview {
+            recipient,
+            amount,
+            amounts: self.amounts.clone(),
+            options,
+            reply,
+        })
+        .await
+    }
+
+    /// Returns the ledger record of a mint.
+    pub async fn status(&self, mint_id: i64) -> Result<Option<MintRecord>, FaucetError> {
+        self.call(|reply| Request::Status { mint_id, reply }).await
+    }
+
+    /// Subscribes to the progress of every mint submitted from now on.
+    ///
+    /// Subscribers that fall behind by more than [`EVENT_CAPACITY`] events miss the oldest ones;
+    /// the ledger holds the latest state of each mint.
+    pub fn subscribe(&self) -> broadcast::Receiver<MintEvent> {
+        self.events.subscribe()
+    }
+
+    pub async fn stats(&self) -> Result<MintStats, FaucetError> {
+        self.call(|reply| Request::Stats { reply }).await
+    }
+
+    /// Issues a receipt of a committed mint signed by the faucet owner, or `None` for an unknown
+    /// mint.
+    pub async fn receipt(&self, mint_id: i64) -> Result<Option<MintReceipt>, FaucetError> {
+        self.call(|reply| Request::Receipt { mint_id, reply }).await
+    }
+
+    /// Submits a transaction of the owner setting the pause flag of the faucet to `paused`, see
+    /// [`crate::pause`], and returns its ID.
+    pub async fn set_paused(&self, paused: bool) -> Result<TransactionId, FaucetError> {
+        self.call(|reply| Request::SetPaused {
+            actor: self.actor.clone(),
+            paused,
+            reply,
+        })
+        .await
+    }
+
+    pub async fn queue(&self) -> Result<QueueInfo, FaucetError> {
+        self.call(|reply| Request::Queue { reply }).await
+    }
+
+    /// Sync progress of the local store against the chain tip and the backlogs of the worker,
+    /// for detecting a stalled faucet.
+    pub async fn sync_status(&self) -> Result<SyncStatus, FaucetError> {
+        self.call(|reply| Request::SyncStatus { reply }).await
+    }
+
+    /// Reclaims the expired unclaimed mints of the faucet like `faucet reclaim`, returning once
+    /// the reclaims are committed. The worker keeps serving requests meanwhile.
+    pub async fn reclaim(&self) -> Result<ReclaimReport, FaucetError> {
+        self.call(|reply| Request::Reclaim {
+            actor: self.actor.clone(),
+            reply,
+        })
+        .await
+    }
+
+    /// Entries of the access lists checked by [`check_access_lists`].
+    pub async fn list_entries(&self) -> Result<Vec<ListEntry>, FaucetError> {
+        self.call(|reply| Request::ListEntries { reply }).await
+    }
+
+    /// Puts `subject`, a normalized account ID or identity, on `list`.
+    pub async fn add_list_entry(
+        &self,
+        list: AccessList,
+        subject: String,
+        note: Option<String>,
+    ) -> Result<ListEntry, FaucetError> {
+        self.call(|reply| Request::AddListEntry {
+            actor: self.actor.clone(),
+            list,
+            subject,
+            note,
+            reply,
+        })
+        .await
+    }
+
+    /// Takes `subject` off `list` and returns whether it was on it.
+    pub async fn remove_list_entry(
+        &self,
+        list: AccessList,
+        subject: String,
+    ) -> Result<bool, FaucetError> {
+        self.call(|reply| Request::RemoveListEntry {
+            actor: self.actor.clone(),
+            list,
+            subject,
+            reply,
+        })
+        .await
+    }
+
+    /// Appends `entry` to the audit log of the worker's ledger.
+    pub async fn audit(&self, entry: AuditEntry) -> Result<(), FaucetError> {
+        self.call(|reply| Request::Audit { entry, reply }).await
+    }
+
+    /// Identity the mints of the handle to `recipient` are queued under: the handle's identity,
+    /// else the client address, `ip:<addr>`, else the recipient, `recipient:<id>`.
+    fn queue_identity(&self, recipient: AccountId) -> String {
+        match (&self.identity, self.client_ip) {
+            (Some(identity), _) => identity.clone(),
+            (None, Some(ip)) => format!("ip:{ip}"),
+            (None, None) => format!("recipient:{}", recipient.to_hex()),
+        }
+    }
+
+    fn requester(&self) -> Requester {
+        Requester {
+            identity: self.identity.clone(),
+            client_ip: self.client_ip,
+        }
+    }
+
+    async fn call<T>(
+        &self,
+        request: impl FnOnce(oneshot::Sender<Result<T, FaucetError>>) -> Request,
+    ) -> Result<T, FaucetError> {
+        let (reply, response) = oneshot::channel();
+        self.sender
+            .send(request(reply))
+            .await
+            .map_err(|_| FaucetError::ServiceStopped)?;
+        response.await.map_err(|_| FaucetError::ServiceStopped)?
+    }
+}
+
+/// Owns the node and ledger and serves the requests of every [`FaucetHandle`].
+pub struct FaucetWorker<N> {
+    node: SharedNode<N>,
+    executors: Rc<Executors<N>>,
+    watcher: Rc<BlockWatcher>,
+    ledger: Rc<Ledger>,
+    faucet_id: AccountId,
+    /// Owner and pause flag of the faucet, read again on every block.
+    faucet: FaucetCache,
+    reclaim_after_blocks: Option<u32>,
+    note_tag: NoteTagStrategy,
+    network_execution_timeout: Duration,
+    reorg_check_blocks: u32,
+    require_onchain_recipients: bool,
+    mailer: Option<Rc<NoteMailer>>,
+    finality: Option<FinalityChecker>,
+    amounts: AmountConfig,
+    referrals: Option<ReferralConfig>,
+    abuse: AbusePolicies,
+    receiver: mpsc::Receiver<Request>,
+    /// Mints received and not served yet, at most `queue_capacity`.
+    mints: FairQueue<Queued>,
+    queue_capacity: usize,
+    max_batch_size: usize,
+    events: broadcast::Sender<MintEvent>,
+}
+
+/// Creates a worker minting from `faucet_id` and a handle to reach it.
+pub fn faucet_service<N: FaucetNode + 'static>(
+    node: SharedNode<N>,
+    watcher: Rc<BlockWatcher>,
+    ledger: Rc<Ledger>,
+    faucet_id: AccountId,
+    config: &ServiceConfig,
+) -> (FaucetHandle, FaucetWorker<N>) {
+    let (sender, receiver) = mpsc::channel(config.queue_capacity.max(1));
+    let (events, _) = broadcast::channel(EVENT_CAPACITY);
+    let handle = FaucetHandle {
+        sender,
+        events: events.clone(),
+        actor: "service".into(),
+        identity: None,
+        client_ip: None,
+        amounts: None,
+        referral: None,
+    };
+    let worker = FaucetWorker {
+        executors: Rc::new(Executors::new(node.clone())),
+        node,
+        watcher,
+        ledger,
+        faucet_id,
+        faucet: FaucetCache::new(faucet_id),
+        reclaim_after_blocks: config.reclaim_after_blocks,
+        note_tag: config.note_tag,
+        network_execution_timeout: Duration::from_millis(config.network_execution_timeout_ms),
+        reorg_check_blocks: config.reorg_check_blocks,
+        require_onchain_recipients: config.require_onchain_recipients,
+        mailer: None,
+        finality: None,
+        amounts: AmountConfig::default(),
+        referrals: None,
+        abuse: AbusePolicies::default().with_daily_budget(config.daily_budget),
+        receiver,
+        mints: FairQueue::new(config.queue_weights.clone()),
+        queue_capacity: config.queue_capacity.max(1),
+        max_batch_size: config.max_batch_size,
+        events,
+    };
+    (handle, worker)
+}
+
+impl<N: FaucetNode + 'static> FaucetWorker<N> {
+    /// Emails the notes of the mints requested with an email address through `mailer`.
+    pub fn with_mailer(mut self, mailer: NoteMailer) -> Self {
+        self.mailer = Some(Rc::new(mailer));
+        self
+    }
+
+    /// Submits the mints through `executors`, e.g. ones shared with other flows submitting from
+    /// the faucet owner or holding account locks, instead of executors of its own.
+    pub fn with_executors(mut self, executors: Rc<Executors<N>>) -> Self {
+        self.executors = executors;
+        self
+    }
+
+    /// Confirms the commitment of mints with `checker` before issuing their receipts.
+    pub fn with_finality(mut self, checker: FinalityChecker) -> Self {
+        self.finality = Some(checker);
+        self
+    }
+
+    /// Bounds the amounts of requested mints, [`AmountConfig::default`] otherwise. Handles set up
+    /// with [`FaucetHandle::with_amounts`] bring their own bounds.
+    pub fn with_amounts(mut self, amounts: AmountConfig) -> Self {
+        self.amounts = amounts;
+        self
+    }
+
+    /// Accepts mints requested with referral codes, crediting their referrers as set in `config`.
+    pub fn with_referrals(mut self, config: ReferralConfig) -> Self {
+        self.referrals = Some(config);
+        self
+    }
+
+    /// Judges the mints requested through the APIs with `policies`, see [`crate::abuse`], in
+    /// place of the daily budget of the [`ServiceConfig`].
+    pub fn with_abuse_policies(mut self, policies: AbusePolicies) -> Self {
+        self.abuse = policies;
+        self
+    }
+
+    /// Serves requests until every handle has been dropped, checking recent mints for reorgs on
+    /// every block if enabled.
+    ///
+    /// Must run inside a [`tokio::task::LocalSet`].
+    pub async fn run(mut self) {
+        let mut tip = self.watcher.subscribe();
+        let mut check_reorgs = self.reorg_check_blocks > 0;
+        let mut open = true;
+        loop {
+            // Take in every waiting request first, so the next mint is picked among all
+            // requesters rather than the earliest.
+            while open && self.mints.len() < self.queue_capacity {
+                match self.receiver.try_recv() {
+                    Ok(request) => self.serve(request).await,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => open = false,
+                }
+            }
+
+            if let Some((_, mint)) = self.mints.pop() {
+                if check_reorgs && tip.has_changed().unwrap_or(false) {
+                    tip.mark_unchanged();
+                    self.check_reorgs_logged().await;
+                }
+                match mint {
+                    Queued::Mint(mint) => self.serve_mint(mint).await,
+                    Queued::Batch(batch) => self.serve_batch(batch).await,
+                }
+                continue;
+            }
+            if !open {
+                break;
+            }
+
+            tokio::select! {
+                request = self.receiver.recv() => match request {
+                    Some(request) => self.serve(request).await,
+                    None => open = false,
+                },
+                changed = tip.changed(), if check_reorgs => match changed {
+                    Ok(()) => self.check_reorgs_logged().await,
+                    Err(_) => check_reorgs = false,
+                },
+            }
+        }
+    }
+
+    async fn serve_mint(&self, mint: QueuedMint) {
+        let amounts = mint.amounts.as_ref().unwrap_or(&self.amounts);
+        let result = match amounts.resolve(mint.amount) {
+            Ok(amount) => {
+                self.mint(
+                    &mint.actor,
+                    &mint.requester,
+                    mint.recipient,
+                    amount,
+                    mint.options,
+                    mint.email,
+                    mint.referral.as_deref(),
+                )
+                .await
+            }
+            Err(err) => Err(err),
+        };
+        if let Ok(ticket) = &result {
+            self.record_queued_at(ticket.mint_id, mint.queued_at);
+        }
+        let _ = mint.reply.send(result);
+    }
+
+    async fn serve_batch(&self, batch: QueuedBatch) {
+        let result = self.mint_entries(&batch).await;
+        if let Ok(ticket) = &result {
+            for mint in &ticket.mints {
+                self.record_queued_at(mint.mint_id, batch.queued_at);
+            }
+        }
+        let _ = batch.reply.send(result);
+    }
+
+    /// Records when a served mint was queued, for its lifecycle. The mint is submitted already,
+    /// so a failure is only logged.
+    fn record_queued_at(&self, mint_id: i64, queued_at: u64) {
+        if let Err(err) = self.ledger.set_queued_at(mint_id, queued_at) {
+            eprintln!("Failed to record when mint {mint_id} was queued: {err}");
+        }
+    }
+
+    async fn mint_entries(&self, batch: &QueuedBatch) -> Result<BatchTicket, FaucetError> {
+        if batch.entries.is_empty() || batch.entries.len() > self.max_batch_size {
+            return Err(FaucetError::BatchSize {
+                size: batch.entries.len(),
+                max: self.max_batch_size,
+            });
+        }
+        let amounts = batch.amounts.as_ref().unwrap_or(&self.amounts);
+        let mut mints = Vec::with_capacity(batch.entries.len());
+        let mut emails = Vec::with_capacity(batch.entries.len());
+        for entry in &batch.entries {
+            if entry.email.is_some() && self.mailer.is_none() {
+                return Err(FaucetError::EmailDisabled);
+            }
+            check_access_lists(&self.ledger, &batch.actor, entry.recipient)?;
+            self.check_recipient(entry.recipient).await?;
+            mints.push(BatchMint {
+                recipient: entry.recipient,
+                amount: amounts.resolve(entry.amount)?,
+                options: self.apply_defaults(entry.options.clone()).await?,
+            });
+            emails.push(entry.email.clone());
+        }
+        check_campaign_mints(&self.ledger, self.faucet_id, &mints, unix_now())?;
+        // Each mint is judged against the requests before it, including those of the batch.
+        let abuse_requests = mints
+            .iter()
+            .filter_map(|mint| {
+                batch
+                    .requester
+                    .abuse_request(self.faucet_id, mint.recipient, mint.amount)
+            })
+            .collect::<Vec<_>>();
+        self.abuse.check_all(&abuse_requests, &self.ledger)?;
+        let ticket = self.submit_batch(&batch.actor, mints, emails).await?;
+        for request in &abuse_requests {
+            self.record_abuse_request(request);
+        }
+        Ok(ticket)
+    }
+
+    /// Records an admitted request in the history of the abuse policies. The mint is submitted
+    /// already, so a failure is only logged.
+    fn record_abuse_request(&self, request: &AbuseRequest) {
+        if let Err(err) = self.abuse.record(request, &self.ledger) {
+            eprintln!("Failed to record the request of {}: {err}", request.ip);
+        }
+    }
+
+    /// Refuses mints to accounts that cannot claim them, see [`check_recipient`].
+    async fn check_recipient(&self, recipient: AccountId) -> Result<(), FaucetError> {
+        check_recipient(self.faucet_id, recipient)?;
+        if self.require_onchain_recipients {
+            check_recipient_on_chain(&mut *self.node.lock().await, recipient).await?;
+        }
+        Ok(())
+    }
+
+    async fn check_reorgs_logged(&self) {
+        if let Err(err) = self.check_reorgs().await {
+            eprintln!("Failed to check recent mints for reorgs: {err}");
+        }
+    }
+
+    /// Serves a request right away, except mints, which are queued.
+    async fn serve(&mut self, request: Request) {
+        match request {
+            Request::Mint { identity, mint } => self.mints.push(&identity, mint),
+            Request::Status { mint_id, reply } => {
+                let _ = reply.send(self.ledger.get_mint(mint_id));
+            }
+            Request::Stats { reply } => {
+                let _ = reply.send(self.ledger.stats(Some(self.faucet_id)));
+            }
+            Request::Receipt { mint_id, reply } => {
+                let _ = reply.send(self.receipt(mint_id).await);
+            }
+            Request::Preview {
+                recipient,
+                amount,
+                amounts,
+                options,
+                reply,
+            } => {
+                let amounts = amounts.as_ref().unwrap_or(&self.amounts);
+                let result = match amounts.resolve(amount) {
+                    Ok(amount) => self.preview(recipient, amount, options).await,
+                    Err(err) => Err(err),
+                };
+                let _ = reply.send(result);
+            }
+            Request::SetPaused {
+                actor,
+                paused,
+                reply,
+            } => {
+                let _ = reply.send(self.toggle_pause(&actor, paused).await);
+            }
+            Request::Queue { reply } => {
+                let _ = reply.send(Ok(QueueInfo {
+                    len: self.mints.len(),
+                    capacity: self.queue_capacity,
+                    identities: self
+                        .mints
+                        .identities()
+                        .into_iter()
+                        .map(|(identity, len)| (identity.to_string(), len))
+                        .collect(),
+                }));
+            }
+            Request::SyncStatus { reply } => self.sync_status(reply),
+            Request::Reclaim { actor, reply } => self.reclaim(actor, reply),
+            Request::ListEntries { reply } => {
+                let _ = reply.send(self.ledger.list_entries());
+            }
+            Request::AddListEntry {
+                actor,
+                list,
+                subject,
+                note,
+                reply,
+            } => {
+                let result = self
+                    .ledger
+                    .add_list_entry(list, &subject, note.as_deref())
+                    .and_then(|entry| {
+                        self.ledger.append_audit(
+                            &AuditEntry::new(actor, "access_list.add")
+                                .param("list", list.as_str())
+                                .param("subject", &subject)
+                                .param("note", &note),
+                        )?;
+                        Ok(entry)
+                    });
+                let _ = reply.send(result);
+            }
+            Request::RemoveListEntry {
+                actor,
+                list,
+                subject,
+                reply,
+            } => {
+                let result = self
+                    .ledger
+                    .remove_list_entry(list, &subject)
+                    .and_then(|removed| {
+                        if removed {
+                            self.ledger.append_audit(
+                                &AuditEntry::new(actor, "access_list.remove")
+                                    .param("list", list.as_str())
+                                    .param("subject", &subject),
+                            )?;
+                        }
+                        Ok(removed)
+                    });
+                let _ = reply.send(result);
+            }
+            Request::Audit { entry, reply } => {
+                let _ = reply.send(self.ledger.append_audit(&entry).map(|_| ()));
+            }
+        }
+    }
+
+    async fn toggle_pause(&self, actor: &str, paused: bool) -> Result<TransactionId, FaucetError> {
+        let faucet_id = self.faucet_id;
+        let tip = self.watcher.tip().map(|tip| tip.block_num);
+        let owner_id = self
+            .faucet
+            .get(&mut *self.node.lock().await, tip)
+            .await?
+            .owner_id;
+        // The PAUSE note is sent by the owner, and the cached flag follows once the network
+        // consumed it and the store synced.
+        let transaction_id = self
+            .executors
+            .for_account(owner_id)
+            .run(move |node| Box::pin(async move { set_paused(node, faucet_id, paused).await }))
+            .await?;
+
+        let action = if paused {
+            "faucet.pause"
+        } else {
+            "faucet.unpause"
+        };
+        let entry = AuditEntry::new(actor, action)
+            .account(faucet_id)
+            .transaction(transaction_id);
+        if let Err(err) = self.ledger.append_audit(&entry) {
+            eprintln!("Failed to audit {action} of {faucet_id}: {err}");
+        }
+        Ok(transaction_id)
+    }
+
+    /// Reports the [`SyncStatus`], asking the node for the chain tip in the background, so a slow
+    /// node does not hold up the queue.
+    fn sync_status(&self, reply: oneshot::Sender<Result<SyncStatus, FaucetError>>) {
+        let store_block = self.watcher.tip().map(|tip| tip.block_num);
+        let since_last_sync = self.watcher.last_sync().map(|at| at.elapsed());
+        let (queue_len, queue_capacity) = (self.mints.len(), self.queue_capacity);
+        let prover_backlog = self.executors.backlog();
+        let node = self.node.clone();
+        tokio::task::spawn_local(async move {
+            let status = node
+                .lock()
+                .await
+                .chain_tip()
+                .await
+                .map(|chain_tip| SyncStatus {
+                    store_block,
+                    chain_tip,
+                    since_last_sync,
+                    queue_len,
+                    queue_capacity,
+                    prover_backlog,
+                });
+            let _ = reply.send(status);
+        });
+    }
+
+    /// Reclaims expired mints in the background, since waiting for the reclaims to commit would
+    /// hold up the other requests.
+    fn reclaim(&self, actor: String, reply: oneshot::Sender<Result<ReclaimReport, FaucetError>>) {
+        let (node, watcher, ledger) =
+            (self.node.clone(), self.watcher.clone(), self.ledger.clone());
+        let faucet_id = self.faucet_id;
+        tokio::task::spawn_local(async move {
+            let report = reclaim_expired(&node, &watcher, &ledger, faucet_id).await;
+            for mint in report.iter().flat_map(|report| &report.reclaimed) {
+                let mut entry = AuditEntry::new(&actor, "faucet.reclaim")
+                    .account(faucet_id)
+                    .param("mint_id", mint.id)
+                    .param("amount", mint.amount);
+                entry.transaction_id = mint.reclaim_transaction_id.clone();
+                if let Err(err) = ledger.append_audit(&entry) {
+                    eprintln!("Failed to audit the reclaim of mint {}: {err}", mint.id);
+                }
+            }
+            let _ = reply.send(report);
+        });
+    }
+
+    async fn preview(
+        &self,
+        recipient: AccountId,
+        amount: u64,
+        options: MintOptions,
+    ) -> Result<MintPreview, FaucetError> {
+        let mint = BatchMint {
+            recipient,
+            amount,
+            options: self.apply_defaults(options).await?,
+        };
+        let cost =
+            estimate_mint_batch(&mut *self.node.lock().await, self.faucet_id, vec![mint]).await?;
+        Ok(MintPreview { amount, cost })
+    }
+
+    async fn receipt(&self, mint_id: i64) -> Result<Option<MintReceipt>, FaucetError> {
+        let Some(record) = self.ledger.get_mint(mint_id)? else {
+            return Ok(None);
+        };
+        if let Some(finality) = &self.finality {
+            finality
+                .verify_mint(&self.ledger, "service", &record)
+                .await?;
+        }
+        Ok(Some(
+            mint_receipt(&mut *self.node.lock().await, &record).await?,
+        ))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn mint(
+        &self,
+        actor: &str,
+        requester: &Requester,
+        recipient: AccountId,
+        amount: u64,
+        options: MintOptions,
+        email: Option<Address>,
+        referral: Option<&str>,
+    ) -> Result<MintTicket, FaucetError> {
+        if email.is_some() && self.mailer.is_none() {
+            return Err(FaucetError::EmailDisabled);
+        }
+        check_access_lists(&self.ledger, actor, recipient)?;
+        self.check_recipient(recipient).await?;
+        let referral = match (referral, &self.referrals) {
+            (None, _) => None,
+            (Some(code), None) => {
+                return Err(FaucetError::InvalidReferral(
+                    code.to_string(),
+                    "referrals are not enabled".into(),
+                ))
+            }
+            (Some(code), Some(config)) => {
+                Some((check_referral(&self.ledger, code, recipient)?, config))
+            }
+        };
+        let options = self.apply_defaults(options).await?;
+        let mint = BatchMint {
+            recipient,
+            amount,
+            options: options.clone(),
+        };
+        check_campaign_mints(&self.ledger, self.faucet_id, &[mint], unix_now())?;
+        let abuse_request = requester.abuse_request(self.faucet_id, recipient, amount);
+        if let Some(request) = &abuse_request {
+            self.abuse.check(request, &self.ledger)?;
+        }
+        let ticket = self
+            .submit(actor, recipient, amount, options, email)
+            .await?;
+        if let Some(request) = &abuse_request {
+            self.record_abuse_request(request);
+        }
+
+        if let Some((code, config)) = referral {
+            if let Err(err) = self
+                .credit_referrer(actor, requester, &code, config, recipient, &ticket)
+                .await
+            {
+                eprintln!("Failed to credit referral code {}: {err}", code.code);
+            }
+        }
+        Ok(ticket)
+    }
+
+    /// Records the conversion of `code` by the mint `ticket` to `referee`, and mints the bonus of
+    /// the referrer unless it received `config.max_bonuses` already.
+    ///
+    /// The bonus is checked like the mint of `requester` it follows, against the access lists,
+    /// [`check_recipient`] and the abuse policies, and is not minted if any refuses it.
+    async fn credit_referrer(
+        &self,
+        actor: &str,
+        requester: &Requester,
+        code: &ReferralCodeRecord,
+        config: &ReferralConfig,
+        referee: AccountId,
+        ticket: &MintTicket,
+    ) -> Result<(), FaucetError> {
+        let referral_id = self
+            .ledger
+            .record_referral(&code.code, referee, ticket.mint_id)?;
+        if self.ledger.referral_bonuses(code.referrer)? >= config.max_bonuses {
+            return Ok(());
+        }
+        check_access_lists(&self.ledger, actor, code.referrer)?;
+        self.check_recipient(code.referrer).await?;
+        let abuse_request =
+            requester.abuse_request(self.faucet_id, code.referrer, config.bonus_amount);
+        if let Some(request) = &abuse_request {
+            self.abuse.check(request, &self.ledger)?;
+        }
+        let options = MintOptions {
+            aux: AuxData::new(0, RequestSource::Referral),
+            ..MintOptions::default()
+        };
+        let options = self.apply_defaults(options).await?;
+        let bonus = self
+            .submit(
+                "referral",
+                code.referrer,
+                config.bonus_amount,
+                options,
+                None,
+            )
+            .await?;
+        if let Some(request) = &abuse_request {
+            self.record_abuse_request(request);
+        }
+        self.ledger.set_referral_bonus(referral_id, bonus.mint_id)
+    }
+
+    /// Applies the service's defaults to `options`: its note tag strategy, and its reclaim period,
+    /// so a plain P2ID note is minted as P2IDE and a timelocked note without reclaim height made
+    /// reclaimable.
+    async fn apply_defaults(&self, mut options: MintOptions) -> Result<MintOptions, FaucetError> {
+        options.note_tag.get_or_insert(self.note_tag);
+        if let Some(blocks) = self.reclaim_after_blocks {
+            let tip = match self.watcher.tip() {
+                Some(tip) => tip.block_num,
+                None => self.node.lock().await.sync_state().await?,
+            };
+            let reclaim_after = |from: BlockNumber| {
+                BlockNumber::from(from.as_u32().max(tip.as_u32()).saturating_add(blocks))
+            };
+            options.note_kind = match options.note_kind {
+                MintNoteKind::P2id => MintNoteKind::P2ide {
+                    reclaim_height: reclaim_after(tip),
+                },
+                // The reclaim period starts once the recipient can consume the note.
+                MintNoteKind::Timelocked {
+                    unlock_height,
+                    reclaim_height: None,
+                } => MintNoteKind::Timelocked {
+                    unlock_height,
+                    reclaim_height: Some(reclaim_after(unlock_height)),
+                },
+                kind => kind,
+            };
+        }
+        Ok(options)
+    }
+
+    /// Submits a mint, records it in the ledger and the audit log and tracks its commitment in the
+    /// background, emailing its note to `email` once committed.
+    async fn submit(
+        &self,
+        actor: &str,
+        recipient: AccountId,
+        amount: u64,
+        options: MintOptions,
+        email: Option<Address>,
+    ) -> Result<MintTicket, FaucetError> {
+        let mint = BatchMint {
+            recipient,
+            amount,
+            options,
+        };
+        let mut batch = self.submit_batch(actor, vec![mint], vec![email]).await?;
+        Ok(batch.mints.remove(0))
+    }
+
+    /// Like [`submit`](Self::submit) for several mints sharing one transaction, each mint emailed
+    /// to the address of the same index in `emails`.
+    async fn submit_batch(
+        &self,
+        actor: &str,
+        mints: Vec<BatchMint>,
+        emails: Vec<Option<Address>>,
+    ) -> Result<BatchTicket, FaucetError> {
+        let tip = self.watcher.tip().map(|tip| tip.block_num);
+        let faucet = self.faucet.get(&mut *self.node.lock().await, tip).await?;
+        if faucet.paused {
+            return Err(FaucetError::FaucetPaused(self.faucet_id));
+        }
+        let payments: Vec<_> = mints
+            .iter()
+            .map(|mint| (mint.recipient, mint.amount))
+            .collect();
+        let batch = self
+            .executors
+            .for_account(faucet.owner_id)
+            .run(move |node| Box::pin(async move { mint_batch_from(node, &faucet, mints).await }))
+            .await?;
+
+        if let Some(cost) = batch.cost {
+            let recorded =
+                self.ledger
+                    .record_cost(self.faucet_id, "mint", batch.transaction_id, cost);
+            if let Err(err) = recorded {
+                eprintln!(
+                    "Failed to record the cost of {}: {err}",
+                    batch.transaction_id
+                );
+            }
+        }
+
+        let mut tickets = Vec::with_capacity(payments.len());
+        for ((recipient, amount), p2id_note) in payments.into_iter().zip(&batch.p2id_notes) {
+            let mint_id = self.ledger.record_mint(
+                self.faucet_id,
+                recipient,
+                amount,
+                batch.transaction_id,
+                p2id_note,
+            )?;
+            // The mint went out already, so a failure to audit it does not fail the request.
+            let entry = AuditEntry::new(actor, "mint")
+                .account(batch.owner_id)
+                .param("faucet_id", self.faucet_id.to_hex())
+                .param("recipient", recipient.to_hex())
+                .param("amount", amount)
+                .param("mint_id", mint_id)
+                .param("note_id", p2id_note.id().to_hex())
+                .transaction(batch.transaction_id);
+            if let Err(err) = self.ledger.append_audit(&entry) {
+                eprintln!("Failed to audit mint {mint_id}: {err}");
+            }
+            tickets.push(MintTicket {
+                mint_id,
+                transaction_id: batch.transaction_id,
+                note_id: p2id_note.id(),
+                note: p2id_note.clone(),
+                note_commitment: p2id_note.commitment(),
+                amount,
+            });
+        }
+
+        for ticket in &tickets {
+            self.publish(ticket.mint_id, MintUpdate::Submitted);
+        }
+        let mint_ids = tickets.iter().map(|ticket| ticket.mint_id);
+        self.track(batch.transaction_id, mint_ids.zip(emails).collect());
+
+        Ok(BatchTicket {
+            transaction_id: batch.transaction_id,
+            mints: tickets,
+        })
+    }
+
+    fn publish(&self, mint_id: i64, update: MintUpdate) {
+        // Sending only fails when nobody is subscribed.
+        let _ = self.events.send(MintEvent { mint_id, update });
+    }
+
+    /// Tracks the commitment of the mints of `transaction_id` in the background so the next
+    /// request is not held up, then emails the note of each mint given an address and confirms
+    /// the network execution of the notes with network-execution tags.
+    fn track(&self, transaction_id: TransactionId, mints: Vec<(i64, Option<Address>)>) {
+        let (node, watcher, ledger, events) = (
+            self.node.clone(),
+            self.watcher.clone(),
+            self.ledger.clone(),
+            self.events.clone(),
+        );
+        let mailer = self.mailer.clone();
+        let execution_timeout = self.network_execution_timeout;
+        let mint_ids: Vec<_> = mints.iter().map(|(mint_id, _)| *mint_id).collect();
+        let publish = move |update: MintUpdate| {
+            for &mint_id in &mint_ids {
+                let update = update.clone();
+                let _ = events.send(MintEvent { mint_id, update });
+            }
+        };
+        tokio::task::spawn_local(async move {
+            let mut pending = false;
+            let tracked = track_transaction(&node, &watcher, transaction_id, |state| {
+                if matches!(state, TxState::Pending) && !pending {
+                    pending = true;
+                    publish(MintUpdate::Pending);
+                }
+            })
+            .await;
+
+            // The ledger is updated first so subscribers re-reading it see the final state.
+            let (result, update) = match tracked {
+                Ok(block_num) => (
+                    ledger.mark_committed(transaction_id, block_num),
+                    MintUpdate::Committed {
+                        block_num: block_num.as_u32(),
+                    },
+                ),
+                Err(err) => (
+                    ledger.mark_failed(transaction_id, &err.to_string()),
+                    MintUpdate::Discarded {
+                        reason: err.to_string(),
+                    },
+                ),
+            };
+            if let Err(err) = result {
+                eprintln!("Failed to update ledger for transaction {transaction_id}: {err}");
+            }
+            let committed = matches!(update, MintUpdate::Committed { .. });
+            publish(update);
+            if !committed {
+                return;
+            }
+
+            if let Some(mailer) = &mailer {
+                for (mint_id, to) in &mints {
+                    let Some(to) = to else { continue };
+                    if let Err(err) = email_note(&node, &ledger, mailer, *mint_id, to).await {
+                        eprintln!("Failed to email the note of mint {mint_id}: {err}");
+                    }
+                }
+            }
+            for (mint_id, _) in mints {
+                let record = match ledger.get_mint(mint_id) {
+                    Ok(Some(record)) if record.network_executed() => record,
+                    Ok(_) => continue,
+                    Err(err) => {
+                        eprintln!("Failed to read mint {mint_id}: {err}");
+                        continue;
+                    }
+                };
+                let executed = track_network_execution(
+                    &node,
+                    &watcher,
+                    &ledger,
+                    "service",
+                    &record,
+                    execution_timeout,
+                )
+                .await;
+                if let Err(err) = executed {
+                    eprintln!("Failed to confirm the network execution of mint {mint_id}: {err}");
+                }
+            }
+        });
+    }
+
+    /// Re-reads the state of the mints committed within `reorg_check_blocks` of the chain tip.
+    ///
+    /// A mint whose transaction went back to pending is tracked again. One whose transaction was
+    /// dropped is invalidated and its note minted again with the same serial number, so the
+    /// recipient can consume it once whichever transaction lands. Re-submissions that fail, e.g.
+    /// while the faucet is paused, are retried on the next block.
+    async fn check_reorgs(&self) -> Result<(), FaucetError> {
+        let Some(tip) = self.watcher.tip() else {
+            return Ok(());
+        };
+        let since = tip
+            .block_num
+            .as_u32()
+            .saturating_sub(self.reorg_check_blocks);
+
+        for record in self
+            .ledger
+            .committed_mints_since(self.faucet_id, BlockNumber::from(since))?
+        {
+            let transaction_id = Word::try_from(record.transaction_id.as_str())
+                .map(TransactionId::from)
+                .map_err(|_| {
+                    FaucetError::Ledger(format!("mint {} has a bad transaction ID", record.id))
+                })?;
+            let state = self
+                .node
+                .lock()
+                .await
+                .transaction_state(transaction_id)
+                .await?;
+            let commit_block = record.commit_block.unwrap_or_default();
+            let reason = match state {
+                Some(TxState::Committed(block_num)) if block_num.as_u32() == commit_block => {
+                    continue
+                }
+                Some(TxState::Committed(block_num)) => {
+                    self.ledger.mark_committed(transaction_id, block_num)?;
+                    self.publish(
+                        record.id,
+                        MintUpdate::Committed {
+                            block_num: block_num.as_u32(),
+                        },
+                    );
+                    continue;
+                }
+                Some(TxState::Pending) => {
+                    let reason = format!("transaction reverted from block {commit_block}");
+                    self.ledger.mark_reverted(transaction_id, &reason)?;
+                    self.publish(
+                        record.id,
+                        MintUpdate::Invalidated {
+                            reason,
+                            replaced_by: None,
+                        },
+                    );
+                    self.track(transaction_id, vec![(record.id, None)]);
+                    continue;
+                }
+                Some(TxState::Discarded(cause)) => {
+                    format!("transaction dropped from block {commit_block}: {cause}")
+                }
+                None => format!("transaction dropped from block {commit_block}"),
+            };
+            eprintln!("Mint {} invalidated: {reason}", record.id);
+            self.ledger.mark_invalidated(transaction_id, &reason)?;
+        }
+
+        for record in self.ledger.unreplaced_mints(self.faucet_id)? {
+            let recipient = AccountId::from_hex(&record.recipient).map_err(|_| {
+                FaucetError::Ledger(format!("mint {} has a bad recipient", record.id))
+            })?;
+            let options = remint_options(&record)?;
+            match self
+                .submit("reorg", recipient, record.amount, options, None)
+                .await
+            {
+                Ok(ticket) => {
+                    self.ledger.set_replacement(record.id, ticket.mint_id)?;
+                    self.publish(
+                        record.id,
+                        MintUpdate::Invalidated {
+                            reason: record.error.unwrap_or_default(),
+                            replaced_by: Some(ticket.mint_id),
+                        },
+                    );
+                }
+                Err(err) => eprintln!("Failed to mint invalidated mint {} again: {err}", record.id),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Emails the note file of committed mint `mint_id` to `to`.
+async fn email_note<N: FaucetNode>(
+    node: &SharedNode<N>,
+    ledger: &Ledger,
+    mailer: &NoteMailer,
+    mint_id: i64,
+    to: &Address,
+) -> Result<(), FaucetError> {
+    let record = ledger
+        .get_mint(mint_id)?
+        .ok_or_else(|| FaucetError::Ledger(format!("mint {mint_id} not found")))?;
+    let note_file = mint_note_file(&mut *node.lock().await, &record).await?;
+    mailer.send(&record, &note_file, to).await
+}
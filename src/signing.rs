@@ -0,0 +1,80 @@
+//! Startup self-test of the signing path.
+//!
+//! Every mint is a transaction of the faucet owner, signed by the authenticator of the client with
+//! the RPO Falcon 512 key the keystore holds for the owner. A missing or damaged key only shows
+//! once the first mint fails to be proven, so `serve` runs [`check_signing`] for every faucet it
+//! serves before accepting requests: the key is looked up by the commitment stored in the owner
+//! account, signs a dummy transaction summary and the signature is verified locally.
+//!
+//! The filesystem keystore is the only signer backend of the client, and the one tested here.
+
+use miden_client::{account::AccountId, auth::AuthSecretKey, Felt, Word};
+use miden_crypto::hash::rpo::Rpo256;
+
+use crate::{mint::faucet_owner, node::FaucetNode, receipt::AUTH_KEY_SLOT, FaucetError};
+
+/// Domain of the dummy summaries signed by [`check_account_signing`], so their signatures are
+/// never valid for anything else.
+const SELF_TEST_DOMAIN: u64 = u64::from_be_bytes(*b"selftest");
+
+/// Outcome of a passed self-test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SigningCheck {
+    /// Account whose key was tested.
+    pub account_id: AccountId,
+    /// Commitment to the public key, as stored in the account.
+    pub key_commitment: Word,
+}
+
+/// Tests the key of the owner of `faucet_id`, which signs the mints of the faucet.
+pub async fn check_signing<N: FaucetNode>(
+    node: &mut N,
+    faucet_id: AccountId,
+) -> Result<SigningCheck, FaucetError> {
+    let owner_id = faucet_owner(node, faucet_id).await?;
+    check_account_signing(node, owner_id).await
+}
+
+/// Signs a dummy transaction summary with the key of `account_id` and verifies the signature
+/// against the auth key of the account.
+///
+/// Fails with [`FaucetError::KeyNotFound`] if the keystore holds no key for the account and with
+/// [`FaucetError::SigningSelfTest`] if the key does not produce valid signatures.
+pub async fn check_account_signing<N: FaucetNode>(
+    node: &mut N,
+    account_id: AccountId,
+) -> Result<SigningCheck, FaucetError> {
+    let account = node
+        .get_account(account_id)
+        .await?
+        .ok_or(FaucetError::AccountNotFound(account_id))?;
+    let key_commitment = account.storage().get_item(AUTH_KEY_SLOT)?;
+    let Some(AuthSecretKey::RpoFalcon512(key)) = node.secret_key(key_commitment).await? else {
+        return Err(FaucetError::KeyNotFound(account_id));
+    };
+
+    let public_key = key.public_key();
+    if public_key.to_commitment() != key_commitment {
+        return Err(FaucetError::SigningSelfTest(
+            account_id,
+            "the key does not match the auth key of the account".into(),
+        ));
+    }
+    let summary = Rpo256::hash_elements(&[
+        Felt::new(SELF_TEST_DOMAIN),
+        account_id.prefix().as_felt(),
+        account_id.suffix(),
+        key_commitment[0],
+    ]);
+    if !public_key.verify(summary, &key.sign(summary)) {
+        return Err(FaucetError::SigningSelfTest(
+            account_id,
+            "the signature of a dummy transaction summary does not verify".into(),
+        ));
+    }
+
+    Ok(SigningCheck {
+        account_id,
+        key_commitment,
+    })
+}
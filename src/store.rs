@@ -0,0 +1,152 @@
+//! Pruning of the client store.
+//!
+//! The SQLite store of the client keeps every note, transaction and block header it ever saw, so
+//! the store of a busy faucet grows without bound. [`prune_store`] deletes the records the faucet
+//! no longer needs and vacuums the file:
+//!
+//! - input and output notes that were consumed,
+//! - committed and discarded transactions older than the retention window,
+//! - block headers older than the retention window that carry no note of the client.
+//!
+//! Pending transactions, unconsumed notes, the genesis header and the partial blockchain are kept.
+//! The faucet's own records live in the [`crate::ledger::Ledger`] and are not touched.
+//!
+//! The store schema belongs to the client, so the tables are checked before anything is deleted.
+//! The store must not be in use by another command while it is pruned.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection, OpenFlags};
+
+use crate::FaucetError;
+
+/// Input note states of the client store that mark the note as consumed.
+const CONSUMED_INPUT_NOTE_STATES: &str = "6, 7, 8";
+
+/// Output note state of the client store that marks the note as consumed.
+const CONSUMED_OUTPUT_NOTE_STATE: u8 = 4;
+
+/// Columns read or filtered on by [`prune_store`], per table.
+const PRUNED_COLUMNS: &[(&str, &[&str])] = &[
+    ("input_notes", &["state_discriminant"]),
+    ("output_notes", &["state_discriminant"]),
+    ("transactions", &["block_num", "status_variant"]),
+    ("block_headers", &["block_num", "has_client_notes"]),
+];
+
+/// Records removed by [`prune_store`], or that would be removed by a dry run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    pub input_notes: u64,
+    pub output_notes: u64,
+    pub transactions: u64,
+    pub block_headers: u64,
+    /// Size of the store file before pruning, in bytes.
+    pub size_before: u64,
+    /// Size of the store file after vacuuming, equal to `size_before` for a dry run.
+    pub size_after: u64,
+}
+
+/// Prunes the client store at `path`, keeping the transactions and block headers of the last
+/// `keep_blocks` blocks, and vacuums it.
+///
+/// With `dry_run` the records are only counted.
+pub fn prune_store(
+    path: impl AsRef<Path>,
+    keep_blocks: u32,
+    dry_run: bool,
+) -> Result<PruneReport, FaucetError> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Err(FaucetError::Store(format!(
+            "no store at `{}`",
+            path.display()
+        )));
+    }
+    let conn = Connection::open(path)?;
+    check_schema(&conn)?;
+
+    let tip: u32 = conn.query_row(
+        "SELECT COALESCE(MAX(block_num), 0) FROM block_headers",
+        [],
+        |row| row.get(0),
+    )?;
+    let cutoff = tip.saturating_sub(keep_blocks);
+
+    let size_before = std::fs::metadata(path)?.len();
+    let tx = conn.unchecked_transaction()?;
+    let prune = |table: &str, filter: &str, params: &[&dyn rusqlite::ToSql]| {
+        let count: u64 = tx.query_row(
+            &format!("SELECT COUNT(*) FROM {table} WHERE {filter}"),
+            params,
+            |row| row.get(0),
+        )?;
+        if !dry_run {
+            tx.execute(&format!("DELETE FROM {table} WHERE {filter}"), params)?;
+        }
+        Ok::<_, FaucetError>(count)
+    };
+
+    let mut report = PruneReport {
+        input_notes: prune(
+            "input_notes",
+            &format!("state_discriminant IN ({CONSUMED_INPUT_NOTE_STATES})"),
+            params![],
+        )?,
+        output_notes: prune(
+            "output_notes",
+            "state_discriminant = ?1",
+            params![CONSUMED_OUTPUT_NOTE_STATE],
+        )?,
+        // Pending transactions are never pruned, whatever their age.
+        transactions: prune(
+            "transactions",
+            "status_variant != 0 AND block_num < ?1",
+            params![cutoff],
+        )?,
+        block_headers: prune(
+            "block_headers",
+            "has_client_notes = 0 AND block_num != 0 AND block_num < ?1",
+            params![cutoff],
+        )?,
+        size_before,
+        size_after: size_before,
+    };
+    tx.commit()?;
+
+    if !dry_run {
+        conn.execute_batch("VACUUM")?;
+        drop(conn);
+        report.size_after = std::fs::metadata(path)?.len();
+    }
+    Ok(report)
+}
+
+/// Fails unless the client store at `path` has every table and column [`prune_store`] relies on.
+pub fn check_store_schema(path: impl AsRef<Path>) -> Result<(), FaucetError> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    check_schema(&conn)
+}
+
+fn check_schema(conn: &Connection) -> Result<(), FaucetError> {
+    for (table, columns) in PRUNED_COLUMNS {
+        let mut stmt = conn.prepare(&format!("SELECT name FROM pragma_table_info('{table}')"))?;
+        let existing = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        if existing.is_empty() {
+            return Err(FaucetError::Store(format!(
+                "unsupported store schema: no table `{table}`"
+            )));
+        }
+        if let Some(column) = columns
+            .iter()
+            .find(|column| !existing.iter().any(|name| name == *column))
+        {
+            return Err(FaucetError::Store(format!(
+                "unsupported store schema: no column `{table}.{column}`"
+            )));
+        }
+    }
+    Ok(())
+}
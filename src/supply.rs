@@ -0,0 +1,138 @@
+//! Supply reconciliation.
+//!
+//! Fungible faucets count the tokens they issued in a reserved storage slot, which the faucet
+//! updates on every mint and burn it executes. The [`Ledger`] knows the mints and burns submitted
+//! through this service, so both totals agree as long as nothing else touches the faucet. A
+//! difference points at transactions the ledger missed, a reorg the ledger did not follow, or
+//! tokens minted out of band with the faucet key.
+//!
+//! [`reconcile_supply`] compares the two once. `serve` runs [`run_supply_reconciler`] every
+//! `poll.supply_interval_ms` and alerts on stderr and in the audit log (`supply.diverged`) when a
+//! difference persists across two checks: mints committed by the ledger may still wait for the
+//! network to execute their MINT note, which opens a gap for a block or two.
+
+use std::time::Duration;
+
+use miden_client::account::{Account, AccountId};
+use miden_objects::block::BlockNumber;
+
+use crate::{
+    audit::AuditEntry,
+    ledger::Ledger,
+    node::FaucetNode,
+    watcher::{BlockWatcher, SharedNode},
+    FaucetError,
+};
+
+/// Storage slot reserved by fungible faucets, whose last element is the total issuance.
+pub const ISSUANCE_SLOT: u8 = 0;
+
+/// Actor of the audit entries recorded by the reconciler.
+const RECONCILER_ACTOR: &str = "supply";
+
+/// Outcome of [`reconcile_supply`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupplyReport {
+    pub faucet_id: AccountId,
+    /// Block of the faucet state read from the store, if the chain tip is known.
+    pub block_num: Option<BlockNumber>,
+    /// Committed mints minus committed burns recorded in the ledger.
+    pub ledger_issued: u64,
+    /// Total issuance stored by the faucet account.
+    pub on_chain_issued: u64,
+}
+
+impl SupplyReport {
+    /// Tokens issued on chain beyond what the ledger accounts for, negative when the ledger
+    /// records more than the faucet issued.
+    pub fn divergence(&self) -> i64 {
+        self.on_chain_issued as i64 - self.ledger_issued as i64
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.divergence() == 0
+    }
+}
+
+/// Reads the total issuance of `faucet`.
+pub fn issued_supply(faucet: &Account) -> Result<u64, FaucetError> {
+    let word = faucet.storage().get_item(ISSUANCE_SLOT)?;
+    Ok(word[3].as_int())
+}
+
+/// Compares the supply recorded in `ledger` for `faucet_id` with the issuance of the faucet
+/// account known to `node`.
+pub async fn reconcile_supply<N: FaucetNode>(
+    node: &mut N,
+    ledger: &Ledger,
+    faucet_id: AccountId,
+    block_num: Option<BlockNumber>,
+) -> Result<SupplyReport, FaucetError> {
+    let faucet = node
+        .get_account(faucet_id)
+        .await?
+        .ok_or(FaucetError::AccountNotFound(faucet_id))?;
+    let stats = ledger.stats(Some(faucet_id))?;
+    Ok(SupplyReport {
+        faucet_id,
+        block_num,
+        ledger_issued: stats.minted_amount.saturating_sub(stats.burned_amount),
+        on_chain_issued: issued_supply(&faucet)?,
+    })
+}
+
+/// Reconciles the supply of `faucet_id` every `interval`, alerting once per divergence that is
+/// seen by two consecutive checks.
+pub async fn run_supply_reconciler<N: FaucetNode>(
+    node: &SharedNode<N>,
+    watcher: &BlockWatcher,
+    ledger: &Ledger,
+    faucet_id: AccountId,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    let mut previous = 0;
+    let mut alerted = 0;
+    loop {
+        ticker.tick().await;
+        let block_num = watcher.tip().map(|tip| tip.block_num);
+        let report =
+            match reconcile_supply(&mut *node.lock().await, ledger, faucet_id, block_num).await {
+                Ok(report) => report,
+                Err(err) => {
+                    eprintln!("Failed to reconcile the supply of {faucet_id}: {err}");
+                    continue;
+                }
+            };
+        let divergence = report.divergence();
+        if divergence != 0 && divergence == previous && divergence != alerted {
+            alerted = divergence;
+            if let Err(err) = alert_divergence(ledger, &report) {
+                eprintln!("Failed to record the supply divergence of {faucet_id}: {err}");
+            }
+        } else if divergence == 0 {
+            alerted = 0;
+        }
+        previous = divergence;
+    }
+}
+
+fn alert_divergence(ledger: &Ledger, report: &SupplyReport) -> Result<(), FaucetError> {
+    eprintln!(
+        "Supply of faucet {} diverged: ledger {}, on chain {} ({:+})",
+        report.faucet_id,
+        report.ledger_issued,
+        report.on_chain_issued,
+        report.divergence()
+    );
+    let mut entry = AuditEntry::new(RECONCILER_ACTOR, "supply.diverged")
+        .account(report.faucet_id)
+        .param("ledger_issued", report.ledger_issued)
+        .param("on_chain_issued", report.on_chain_issued)
+        .param("divergence", report.divergence());
+    if let Some(block_num) = report.block_num {
+        entry = entry.param("block_num", block_num.as_u32());
+    }
+    ledger.append_audit(&entry)?;
+    Ok(())
+}
@@ -0,0 +1,173 @@
+//! Scoped state sync.
+//!
+//! Every `sync_state` asks the node for the notes matching each note tag tracked by the store, so
+//! tags picked up over time, e.g. added by other tools sharing the store, make every sync of the
+//! faucet slower. With [`SyncConfig::scoped`] set, [`NodeClient`](crate::node::NodeClient) drops
+//! the user tags of the store that are not listed in [`SyncConfig::note_tags`] when it connects.
+//! The tags of the tracked accounts and of the notes the store expects are always kept, so the
+//! faucet still sees its own accounts and notes.
+//!
+//! Sync durations are measured by the node client, see [`SyncStats`], and syncs slower than
+//! [`SyncConfig::slow_sync_ms`] are reported on stderr.
+//!
+//! Catching up on consumed nullifiers far behind the chain tip, e.g. when the claim indexer starts
+//! on an old ledger, is split into ranges of [`SyncConfig::chunk_blocks`] blocks fetched
+//! [`SyncConfig::parallelism`] at a time, see [`block_chunks`]. The sync of the client store
+//! itself is run by the client one step at a time and cannot be split.
+
+use std::time::Duration;
+
+use miden_client::{
+    note::NoteTag,
+    sync::{NoteTagRecord, NoteTagSource},
+};
+use miden_objects::block::BlockNumber;
+use serde::{Deserialize, Serialize};
+
+use crate::{client::FaucetClient, FaucetError};
+
+/// Settings of the state sync, read from the `[sync]` section of the configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SyncConfig {
+    /// Only track the tags of the tracked accounts, of the expected notes and of `note_tags`.
+    pub scoped: bool,
+    /// Note tags tracked in addition to those of the faucet's accounts and notes.
+    pub note_tags: Vec<u32>,
+    /// Report syncs taking longer than this on stderr.
+    pub slow_sync_ms: Option<u64>,
+    /// Block ranges of a catch-up fetched concurrently.
+    pub parallelism: usize,
+    /// Blocks per range of a catch-up; shorter catch-ups are fetched in one request.
+    pub chunk_blocks: u32,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            scoped: false,
+            note_tags: Vec::new(),
+            slow_sync_ms: None,
+            parallelism: 4,
+            chunk_blocks: 10_000,
+        }
+    }
+}
+
+impl SyncConfig {
+    /// Checks the settings without contacting the node.
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        if self.parallelism == 0 {
+            return Err(FaucetError::Config(
+                "sync.parallelism must be positive".into(),
+            ));
+        }
+        if self.chunk_blocks == 0 {
+            return Err(FaucetError::Config(
+                "sync.chunk_blocks must be positive".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Splits the blocks from `from` to `to` into ranges of `chunk_blocks` blocks.
+///
+/// Consecutive ranges share their boundary block, so no block is missed whether the node treats
+/// range ends as inclusive or not; callers drop the duplicates.
+pub fn block_chunks(
+    from: BlockNumber,
+    to: BlockNumber,
+    chunk_blocks: u32,
+) -> Vec<(BlockNumber, BlockNumber)> {
+    let chunk_blocks = chunk_blocks.max(1);
+    let (mut start, end) = (from.as_u32(), to.as_u32());
+    let mut chunks = Vec::new();
+    while start < end {
+        let chunk_end = start.saturating_add(chunk_blocks).min(end);
+        chunks.push((BlockNumber::from(start), BlockNumber::from(chunk_end)));
+        start = chunk_end;
+    }
+    chunks
+}
+
+/// Tags removed and added by [`scope_note_tags`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagScope {
+    pub removed: Vec<NoteTag>,
+    pub added: Vec<NoteTag>,
+}
+
+/// Restricts the user tags of the store to the `note_tags` of `config`.
+pub async fn scope_note_tags(
+    client: &mut FaucetClient,
+    config: &SyncConfig,
+) -> Result<TagScope, FaucetError> {
+    let wanted: Vec<NoteTag> = config
+        .note_tags
+        .iter()
+        .copied()
+        .map(NoteTag::from)
+        .collect();
+    let tracked = client.get_note_tags().await?;
+    let (removed, added) = tag_changes(&tracked, &wanted);
+
+    for tag in &removed {
+        client.remove_note_tag(*tag).await?;
+    }
+    for tag in &added {
+        client.add_note_tag(*tag).await?;
+    }
+    Ok(TagScope { removed, added })
+}
+
+/// User tags of `tracked` to remove and tags of `wanted` to add so the store tracks exactly the
+/// account and note tags plus `wanted`.
+pub fn tag_changes(tracked: &[NoteTagRecord], wanted: &[NoteTag]) -> (Vec<NoteTag>, Vec<NoteTag>) {
+    let mut user_tags: Vec<NoteTag> = Vec::new();
+    for record in tracked {
+        if matches!(record.source, NoteTagSource::User) && !user_tags.contains(&record.tag) {
+            user_tags.push(record.tag);
+        }
+    }
+
+    let removed = user_tags
+        .iter()
+        .filter(|tag| !wanted.contains(tag))
+        .copied()
+        .collect();
+    let mut added: Vec<NoteTag> = Vec::new();
+    for tag in wanted {
+        if !user_tags.contains(tag) && !added.contains(tag) {
+            added.push(*tag);
+        }
+    }
+    (removed, added)
+}
+
+/// Durations of the syncs run by a node client.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncStats {
+    pub syncs: u64,
+    pub total: Duration,
+    pub last: Duration,
+    pub max: Duration,
+}
+
+impl SyncStats {
+    pub fn record(&mut self, elapsed: Duration) {
+        self.syncs += 1;
+        self.total += elapsed;
+        self.last = elapsed;
+        self.max = self.max.max(elapsed);
+    }
+
+    /// Mean sync duration, zero before the first sync.
+    pub fn mean(&self) -> Duration {
+        match u32::try_from(self.syncs) {
+            Ok(0) => Duration::ZERO,
+            Ok(syncs) => self.total / syncs,
+            Err(_) => self.total / u32::MAX,
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! HTTPS for the REST API.
+//!
+//! With a `[tls]` section, `serve` terminates TLS itself, so a small deployment can expose the
+//! REST API directly instead of fronting it with a reverse proxy. The certificate chain and key
+//! are read from PEM files or, in builds with the `acme` feature, obtained and renewed from
+//! Let's Encrypt with the TLS-ALPN-01 challenge, which needs the REST API reachable on port 443
+//! of every domain.
+//!
+//! The gRPC API is still served in plain text; keep it private or behind a proxy.
+
+use std::{io, net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    task::JoinSet,
+};
+use tokio_rustls::{
+    rustls::{
+        crypto::ring,
+        pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+        ConfigBuilder, ServerConfig, WantsVerifier,
+    },
+    server::TlsStream,
+    TlsAcceptor,
+};
+
+use crate::FaucetError;
+
+/// TLS settings of the REST API, read from the `[tls]` section of the configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM file with the certificate chain, leaf first.
+    #[serde(default)]
+    pub cert_path: Option<PathBuf>,
+    /// PEM file with the private key of the certificate.
+    #[serde(default)]
+    pub key_path: Option<PathBuf>,
+    /// Connections not done with the handshake within this time are dropped.
+    #[serde(default = "default_handshake_timeout_ms")]
+    pub handshake_timeout_ms: u64,
+    /// Certificates obtained from Let's Encrypt instead of `cert_path` and `key_path`.
+    #[cfg(feature = "acme")]
+    #[serde(default)]
+    pub acme: Option<AcmeConfig>,
+}
+
+fn default_handshake_timeout_ms() -> u64 {
+    10_000
+}
+
+impl TlsConfig {
+    /// Checks the settings without reading the certificate.
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        #[cfg(feature = "acme")]
+        if let Some(acme) = &self.acme {
+            if self.cert_path.is_some() || self.key_path.is_some() {
+                return Err(FaucetError::Config(
+                    "tls.acme cannot be combined with tls.cert_path and tls.key_path".into(),
+                ));
+            }
+            acme.validate()?;
+            return self.validate_timeout();
+        }
+        let (Some(cert_path), Some(key_path)) = (&self.cert_path, &self.key_path) else {
+            return Err(FaucetError::Config(
+                "tls needs cert_path and key_path".into(),
+            ));
+        };
+        for (name, path) in [("tls.cert_path", cert_path), ("tls.key_path", key_path)] {
+            if !path.is_file() {
+                return Err(FaucetError::Config(format!(
+                    "{name} `{}` does not exist",
+                    path.display()
+                )));
+            }
+        }
+        self.validate_timeout()
+    }
+
+    fn validate_timeout(&self) -> Result<(), FaucetError> {
+        if self.handshake_timeout_ms == 0 {
+            return Err(FaucetError::Config(
+                "tls.handshake_timeout_ms must be positive".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn handshake_timeout(&self) -> Duration {
+        Duration::from_millis(self.handshake_timeout_ms)
+    }
+
+    /// Server configuration presenting the configured certificate.
+    ///
+    /// With ACME, also starts the task ordering and renewing the certificate, so this must be
+    /// called within the runtime.
+    pub fn server_config(&self) -> Result<ServerConfig, FaucetError> {
+        #[cfg(feature = "acme")]
+        if let Some(acme) = &self.acme {
+            return acme.server_config();
+        }
+        let (Some(cert_path), Some(key_path)) = (&self.cert_path, &self.key_path) else {
+            return Err(FaucetError::Config(
+                "tls needs cert_path and key_path".into(),
+            ));
+        };
+        let certs = CertificateDer::pem_file_iter(cert_path)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .map_err(|err| FaucetError::Tls(format!("{}: {err}", cert_path.display())))?;
+        if certs.is_empty() {
+            return Err(FaucetError::Tls(format!(
+                "{} holds no certificate",
+                cert_path.display()
+            )));
+        }
+        let key = PrivateKeyDer::from_pem_file(key_path)
+            .map_err(|err| FaucetError::Tls(format!("{}: {err}", key_path.display())))?;
+        let mut config = builder()?
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(|err| FaucetError::Tls(err.to_string()))?;
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        Ok(config)
+    }
+}
+
+fn builder() -> Result<ConfigBuilder<ServerConfig, WantsVerifier>, FaucetError> {
+    ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|err| FaucetError::Tls(err.to_string()))
+}
+
+/// Let's Encrypt settings, read from the `[tls.acme]` section of the configuration.
+#[cfg(feature = "acme")]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AcmeConfig {
+    /// Domains of the certificate, all pointing at the REST API.
+    pub domains: Vec<String>,
+    /// Email addresses Let's Encrypt sends expiry notices to.
+    #[serde(default)]
+    pub contacts: Vec<String>,
+    /// Directory keeping the account key and certificates across restarts, so they are not
+    /// ordered again on every start.
+    pub cache_dir: PathBuf,
+    /// Order certificates from the production directory, otherwise from the staging directory,
+    /// whose certificates browsers do not trust.
+    #[serde(default)]
+    pub production: bool,
+}
+
+#[cfg(feature = "acme")]
+impl AcmeConfig {
+    fn validate(&self) -> Result<(), FaucetError> {
+        if self.domains.is_empty() {
+            return Err(FaucetError::Config(
+                "tls.acme.domains must name at least one domain".into(),
+            ));
+        }
+        if let Some(contact) = self.contacts.iter().find(|contact| !contact.contains('@')) {
+            return Err(FaucetError::Config(format!(
+                "tls.acme.contacts entry `{contact}` is not an email address"
+            )));
+        }
+        Ok(())
+    }
+
+    fn server_config(&self) -> Result<ServerConfig, FaucetError> {
+        use futures::StreamExt;
+        use rustls_acme::{acme::ACME_TLS_ALPN_NAME, caches::DirCache};
+        use tokio_rustls::rustls::server::ResolvesServerCert;
+
+        let mut state = rustls_acme::AcmeConfig::new(&self.domains)
+            .contact(self.contacts.iter().map(|email| format!("mailto:{email}")))
+            .cache(DirCache::new(self.cache_dir.clone()))
+            .directory_lets_encrypt(self.production)
+            .state();
+        let resolver: Arc<dyn ResolvesServerCert> = state.resolver();
+        let mut config = builder()?
+            .with_no_client_auth()
+            .with_cert_resolver(resolver);
+        config.alpn_protocols = vec![b"http/1.1".to_vec(), ACME_TLS_ALPN_NAME.to_vec()];
+        tokio::spawn(async move {
+            while let Some(event) = state.next().await {
+                match event {
+                    Ok(event) => println!("ACME: {event:?}"),
+                    Err(err) => eprintln!("ACME error: {err:?}"),
+                }
+            }
+        });
+        Ok(config)
+    }
+}
+
+/// Listener handing out connections once their TLS handshake is done.
+///
+/// Handshakes run concurrently, so a slow or stalled client does not hold up the others.
+pub struct TlsListener {
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    handshake_timeout: Duration,
+    handshakes: JoinSet<Option<(TlsStream<TcpStream>, SocketAddr)>>,
+}
+
+impl TlsListener {
+    pub async fn bind(addr: SocketAddr, config: &TlsConfig) -> Result<Self, FaucetError> {
+        let acceptor = TlsAcceptor::from(Arc::new(config.server_config()?));
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            acceptor,
+            handshake_timeout: config.handshake_timeout(),
+            handshakes: JoinSet::new(),
+        })
+    }
+}
+
+impl axum::serve::Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        loop {
+            let accepted = tokio::select! {
+                accepted = self.listener.accept() => accepted,
+                Some(Ok(Some(connection))) = self.handshakes.join_next() => return connection,
+            };
+            match accepted {
+                Ok((stream, addr)) => {
+                    let handshake = self.acceptor.accept(stream);
+                    let timeout = self.handshake_timeout;
+                    self.handshakes.spawn(async move {
+                        match tokio::time::timeout(timeout, handshake).await {
+                            Ok(Ok(stream)) => Some((stream, addr)),
+                            Ok(Err(_)) | Err(_) => None,
+                        }
+                    });
+                }
+                // Out of file descriptors and the like: back off as `axum::serve` does.
+                Err(err) => {
+                    eprintln!("failed to accept a connection: {err}");
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        self.listener.local_addr()
+    }
+}
@@ -0,0 +1,225 @@
+//! Transaction requests built with the crate's policy.
+//!
+//! Every transaction the faucet flows submit is built by [`TxBuilder`], which wraps
+//! `TransactionRequestBuilder` and applies a [`TxPolicy`]: transactions expire if the network
+//! has not included them within [`DEFAULT_EXPIRATION_DELTA`] blocks, so a stuck submission is
+//! discarded instead of pending forever, and the notes the faucet creates share a default aux
+//! value and note type. Transaction scripts compiled over and over, like the deployment and flow
+//! templates, go through the [`tx_scripts`] cache.
+
+use std::{collections::BTreeMap, sync::Mutex};
+
+use miden_client::{
+    note::{Note, NoteType},
+    transaction::{OutputNote, TransactionRequest, TransactionRequestBuilder, TransactionScript},
+    Felt, Word,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{mint::MINT_NOTE_AUX, node::StoredNote, FaucetError};
+
+/// Blocks within which a submitted transaction must be included before the network drops it.
+pub const DEFAULT_EXPIRATION_DELTA: u16 = 256;
+
+/// How a note known to the faucet, e.g. the output note of a mint, is consumed.
+///
+/// Authenticated notes are consumed with their inclusion proof, which the node checks against the
+/// chain. Unauthenticated notes are only proven by the block producer, so they can be consumed
+/// before they are committed, in the same batch as the transaction creating them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsumeMode {
+    /// Authenticated once the note is committed, unauthenticated while racing the chain.
+    #[default]
+    Auto,
+    /// Only once the note is committed; fails before.
+    Authenticated,
+    /// Always without the inclusion proof.
+    Unauthenticated,
+}
+
+/// Parses a [`ConsumeMode`] supplied by a user, `auto`, `authenticated` or `unauthenticated`.
+pub fn parse_consume_mode(input: &str) -> Result<ConsumeMode, FaucetError> {
+    match input.trim().to_ascii_lowercase().as_str() {
+        "auto" => Ok(ConsumeMode::Auto),
+        "authenticated" => Ok(ConsumeMode::Authenticated),
+        "unauthenticated" => Ok(ConsumeMode::Unauthenticated),
+        _ => Err(FaucetError::InvalidConsumeMode(input.to_string())),
+    }
+}
+
+/// Defaults applied to the transactions and notes built by the faucet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxPolicy {
+    /// Blocks after submission from which a transaction not yet included expires, `None` to let
+    /// it pend until the protocol's limit. Transactions running a custom script cannot carry it
+    /// and never expire early.
+    pub expiration_delta: Option<u16>,
+    /// Type of the notes the faucet's wallets send unless the caller picks one.
+    pub note_type: NoteType,
+    /// Aux value of the BURN notes. MINT notes and the notes the network faucet emits carry the
+    /// [`AuxData`](crate::mint::AuxData) of their mint instead, [`MINT_NOTE_AUX`] by default.
+    pub aux: Felt,
+    /// How [`crate::mint::consume_note`] consumes the notes it is given.
+    pub consume: ConsumeMode,
+}
+
+impl Default for TxPolicy {
+    fn default() -> Self {
+        Self {
+            expiration_delta: Some(DEFAULT_EXPIRATION_DELTA),
+            note_type: NoteType::Private,
+            aux: Felt::new(MINT_NOTE_AUX),
+            consume: ConsumeMode::default(),
+        }
+    }
+}
+
+/// `TransactionRequestBuilder` applying a [`TxPolicy`] when built.
+pub struct TxBuilder {
+    policy: TxPolicy,
+    inner: TransactionRequestBuilder,
+    custom_script: bool,
+}
+
+impl TxBuilder {
+    /// Builder with the default policy.
+    pub fn new() -> Self {
+        Self::with_policy(TxPolicy::default())
+    }
+
+    pub fn with_policy(policy: TxPolicy) -> Self {
+        Self {
+            policy,
+            inner: TransactionRequestBuilder::new(),
+            custom_script: false,
+        }
+    }
+
+    /// Creates `notes`, fully known to the sender.
+    pub fn output_notes(mut self, notes: impl IntoIterator<Item = Note>) -> Self {
+        self.inner = self
+            .inner
+            .own_output_notes(notes.into_iter().map(OutputNote::Full));
+        self
+    }
+
+    /// Consumes `notes` of the store: those with an inclusion proof as authenticated notes, the
+    /// others without waiting for their proof.
+    pub fn consume(mut self, notes: Vec<StoredNote>) -> Self {
+        let (authenticated, unauthenticated): (Vec<_>, Vec<_>) =
+            notes.into_iter().partition(|stored| stored.authenticated);
+        self.inner = self
+            .inner
+            .authenticated_input_notes(
+                authenticated
+                    .into_iter()
+                    .map(|stored| (stored.note.id(), None)),
+            )
+            .unauthenticated_input_notes(
+                unauthenticated
+                    .into_iter()
+                    .map(|stored| (stored.note, None)),
+            );
+        self
+    }
+
+    /// Consumes `notes` without waiting for their inclusion proof.
+    pub fn consume_unauthenticated(mut self, notes: impl IntoIterator<Item = Note>) -> Self {
+        self.inner = self
+            .inner
+            .unauthenticated_input_notes(notes.into_iter().map(|note| (note, None)));
+        self
+    }
+
+    /// Runs `script` instead of the default transaction script.
+    pub fn script(mut self, script: TransactionScript) -> Self {
+        self.inner = self.inner.custom_script(script);
+        self.custom_script = true;
+        self
+    }
+
+    /// Word placed on the stack when the script starts.
+    pub fn script_arg(mut self, arg: Word) -> Self {
+        self.inner = self.inner.script_arg(arg);
+        self
+    }
+
+    /// Makes the entries available to the script through the advice map.
+    pub fn advice(mut self, entries: impl IntoIterator<Item = (Word, Vec<Felt>)>) -> Self {
+        self.inner = self.inner.extend_advice_map(entries);
+        self
+    }
+
+    pub fn build(self) -> Result<TransactionRequest, FaucetError> {
+        let mut inner = self.inner;
+        if let Some(delta) = self.policy.expiration_delta.filter(|_| !self.custom_script) {
+            inner = inner.expiration_delta(delta);
+        }
+        Ok(inner.build()?)
+    }
+}
+
+impl Default for TxBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Transaction scripts compiled by the nodes and deployment plans of the process.
+static TX_SCRIPTS: ScriptCache = ScriptCache::new();
+
+/// Cache of the transaction scripts compiled by the crate, shared by the whole process.
+pub fn tx_scripts() -> &'static ScriptCache {
+    &TX_SCRIPTS
+}
+
+/// Compiled transaction scripts by source code, so each script is compiled once per process.
+pub struct ScriptCache {
+    scripts: Mutex<BTreeMap<String, TransactionScript>>,
+}
+
+impl ScriptCache {
+    pub const fn new() -> Self {
+        Self {
+            scripts: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// The script compiled from `code`, compiled with `compile` unless cached. Failures are not
+    /// cached.
+    pub fn get_or_compile(
+        &self,
+        code: &str,
+        compile: impl FnOnce(&str) -> Result<TransactionScript, FaucetError>,
+    ) -> Result<TransactionScript, FaucetError> {
+        if let Some(script) = self.lock().get(code) {
+            return Ok(script.clone());
+        }
+        let script = compile(code)?;
+        self.lock().insert(code.to_string(), script.clone());
+        Ok(script)
+    }
+
+    /// Scripts compiled so far.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, TransactionScript>> {
+        // The map is consistent even if a holder panicked.
+        self.scripts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for ScriptCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
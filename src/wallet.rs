@@ -0,0 +1,196 @@
+use miden_client::{
+    account::{
+        component::BasicWallet, Account, AccountBuilder, AccountId, AccountStorageMode, AccountType,
+    },
+    asset::FungibleAsset,
+    auth::{AuthRpoFalcon512, AuthSecretKey},
+    crypto::{rpo_falcon512::SecretKey, FeltRng},
+    note::{Note, NoteType},
+    transaction::TransactionId,
+    Felt, Word,
+};
+use miden_objects::MAX_INPUT_NOTES_PER_TX;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use crate::{
+    ledger::Ledger, mint::create_p2id_note_exact, node::FaucetNode, tx::TxBuilder, FaucetError,
+};
+
+/// Builds a public basic wallet and its key without registering either with the client.
+pub fn build_wallet<N: FaucetNode>(node: &mut N) -> Result<(Account, SecretKey), FaucetError> {
+    let mut seed = [0_u8; 32];
+    node.rng().fill_bytes(&mut seed);
+    wallet_from_seed(seed)
+}
+
+/// Derives a public basic wallet and its key from `seed`; the same seed always gives the same
+/// account ID and key, see [`crate::backup`].
+pub fn wallet_from_seed(seed: [u8; 32]) -> Result<(Account, SecretKey), FaucetError> {
+    let mut rng = ChaCha20Rng::from_seed(seed);
+    let mut init_seed = [0_u8; 32];
+    rng.fill_bytes(&mut init_seed);
+    let key_pair = SecretKey::with_rng(&mut rng);
+    let account = wallet_account(init_seed, &key_pair)?;
+
+    Ok((account, key_pair))
+}
+
+/// Public basic wallet authenticated by `key`.
+fn wallet_account(init_seed: [u8; 32], key: &SecretKey) -> Result<Account, FaucetError> {
+    Ok(AccountBuilder::new(init_seed)
+        .account_type(AccountType::RegularAccountUpdatableCode)
+        .storage_mode(AccountStorageMode::Public)
+        .with_auth_component(AuthRpoFalcon512::new(
+            key.public_key().to_commitment().into(),
+        ))
+        .with_component(BasicWallet)
+        .build()?)
+}
+
+/// Parses the ID of a keystore key supplied by a user: the hex commitment of its public key.
+pub fn parse_key_id(input: &str) -> Result<Word, FaucetError> {
+    Word::try_from(input.trim())
+        .map_err(|err| FaucetError::InvalidKeyId(input.to_string(), err.to_string()))
+}
+
+/// Falcon key of the keystore whose public key has commitment `key_id`.
+///
+/// Keys generated on another host, or held for a KMS, are added to the keystore beforehand, so
+/// hosts deploying together can bind their accounts to the same key.
+pub async fn keystore_key<N: FaucetNode>(
+    node: &mut N,
+    key_id: Word,
+) -> Result<SecretKey, FaucetError> {
+    match node.secret_key(key_id).await? {
+        Some(AuthSecretKey::RpoFalcon512(key)) => Ok(key),
+        _ => Err(FaucetError::KeyIdNotFound(key_id.to_hex())),
+    }
+}
+
+/// Creates a public basic wallet, registers it with the client and stores its key.
+pub async fn create_wallet<N: FaucetNode>(node: &mut N) -> Result<Account, FaucetError> {
+    let mut seed = [0_u8; 32];
+    node.rng().fill_bytes(&mut seed);
+    create_wallet_from_seed(node, seed).await
+}
+
+/// Like [`create_wallet`], with the wallet authenticated by the keystore key `key_id`, see
+/// [`keystore_key`].
+pub async fn create_wallet_with_key<N: FaucetNode>(
+    node: &mut N,
+    key_id: Word,
+) -> Result<Account, FaucetError> {
+    let key = keystore_key(node, key_id).await?;
+    let mut init_seed = [0_u8; 32];
+    node.rng().fill_bytes(&mut init_seed);
+    let account = wallet_account(init_seed, &key)?;
+
+    node.add_account(&account).await?;
+
+    Ok(account)
+}
+
+/// Like [`create_wallet`], with the wallet derived from `seed` by [`wallet_from_seed`].
+pub async fn create_wallet_from_seed<N: FaucetNode>(
+    node: &mut N,
+    seed: [u8; 32],
+) -> Result<Account, FaucetError> {
+    let (account, key_pair) = wallet_from_seed(seed)?;
+
+    node.add_account(&account).await?;
+    node.add_key(AuthSecretKey::RpoFalcon512(key_pair)).await?;
+
+    Ok(account)
+}
+
+/// Wallets of the store, excluding faucets, watch-only accounts and the wallets removed from the
+/// list in `ledger`.
+pub async fn list_wallets<N: FaucetNode>(
+    node: &mut N,
+    ledger: &Ledger,
+) -> Result<Vec<AccountId>, FaucetError> {
+    let mut wallets = Vec::new();
+    for account_id in node.tracked_accounts().await? {
+        if !account_id.is_faucet()
+            && !ledger.is_removed_wallet(account_id)?
+            && !ledger.is_watched_account(account_id)?
+        {
+            wallets.push(account_id);
+        }
+    }
+    Ok(wallets)
+}
+
+/// A transaction submitted by [`sweep_notes`].
+#[derive(Debug, Clone)]
+pub struct SweepBatch {
+    pub transaction_id: TransactionId,
+    pub notes: usize,
+}
+
+/// Consumes every note of the store that `account_id` can consume now.
+///
+/// Notes are batched into as few transactions as the protocol's input note limit allows.
+pub async fn sweep_notes<N: FaucetNode>(
+    node: &mut N,
+    account_id: AccountId,
+) -> Result<Vec<SweepBatch>, FaucetError> {
+    let mut notes = node.consumable_notes(account_id).await?;
+    let mut batches = Vec::new();
+
+    while !notes.is_empty() {
+        let batch: Vec<_> = notes
+            .drain(..notes.len().min(MAX_INPUT_NOTES_PER_TX))
+            .collect();
+        let count = batch.len();
+        let transaction_id = node
+            .submit_transaction(account_id, TxBuilder::new().consume(batch).build()?)
+            .await?;
+        batches.push(SweepBatch {
+            transaction_id,
+            notes: count,
+        });
+    }
+
+    Ok(batches)
+}
+
+/// Result of [`pay`].
+#[derive(Debug, Clone)]
+pub struct Payment {
+    pub transaction_id: TransactionId,
+    /// The P2ID note paying the target.
+    pub note: Note,
+}
+
+/// Sends `amount` tokens of `faucet_id` from the wallet `sender` to `target` in a P2ID note.
+///
+/// `sender` must be a wallet managed by `node` that holds the tokens.
+pub async fn pay<N: FaucetNode>(
+    node: &mut N,
+    sender: AccountId,
+    target: AccountId,
+    faucet_id: AccountId,
+    amount: u64,
+    note_type: NoteType,
+) -> Result<Payment, FaucetError> {
+    let asset = FungibleAsset::new(faucet_id, amount)?;
+    let serial_num = node.rng().draw_word();
+    let note = create_p2id_note_exact(
+        sender,
+        target,
+        vec![asset.into()],
+        note_type,
+        Felt::new(0),
+        serial_num,
+    )?;
+
+    let request = TxBuilder::new().output_notes([note.clone()]).build()?;
+    let transaction_id = node.submit_transaction(sender, request).await?;
+
+    Ok(Payment {
+        transaction_id,
+        note,
+    })
+}
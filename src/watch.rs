@@ -0,0 +1,108 @@
+//! Watch-only accounts.
+//!
+//! Recipients can be tracked for reporting without the faucet ever holding their keys:
+//! [`watch_account`] imports the public state of an account from the node into the client store,
+//! which keeps it current on every sync, and records the account as watched in the [`Ledger`].
+//! No key is added to the keystore, so no transaction can be signed for a watched account;
+//! [`ensure_spendable`] refuses it up front in the commands spending from wallets, and the wallet
+//! list leaves it out.
+//!
+//! `serve` snapshots watched accounts on every block like the other tracked accounts, see
+//! [`crate::history`]. Private accounts keep their state off chain, so only public accounts can
+//! be watched.
+
+use miden_client::{
+    account::{AccountId, AccountStorageMode},
+    asset::Asset,
+};
+
+use crate::{
+    ledger::{Ledger, RecipientStats},
+    node::FaucetNode,
+    FaucetError,
+};
+
+/// Starts tracking the public account `account_id` without its keys. Returns whether it was not
+/// watched yet.
+///
+/// Fails for private accounts and for accounts this client manages, e.g. its own wallets.
+pub async fn watch_account<N: FaucetNode>(
+    node: &mut N,
+    ledger: &Ledger,
+    account_id: AccountId,
+) -> Result<bool, FaucetError> {
+    if account_id.storage_mode() == AccountStorageMode::Private {
+        return Err(FaucetError::WatchPrivate(account_id));
+    }
+    if ledger.is_watched_account(account_id)? {
+        return Ok(false);
+    }
+    if node.tracked_accounts().await?.contains(&account_id) {
+        return Err(FaucetError::AlreadyTracked(account_id));
+    }
+    node.import_account(account_id).await?;
+    ledger.add_watched_account(account_id)
+}
+
+/// Stops reporting on a watched account. Returns whether it was watched.
+///
+/// The client store has no way to stop tracking an account, so it stays in the store, hidden from
+/// the wallet list like a removed wallet.
+pub fn unwatch_account(ledger: &Ledger, account_id: AccountId) -> Result<bool, FaucetError> {
+    if !ledger.remove_watched_account(account_id)? {
+        return Ok(false);
+    }
+    ledger.remove_wallet(account_id)?;
+    Ok(true)
+}
+
+/// Fails with [`FaucetError::WatchOnly`] if `account_id` is watched, before a transaction is
+/// built for it.
+pub fn ensure_spendable(ledger: &Ledger, account_id: AccountId) -> Result<(), FaucetError> {
+    if ledger.is_watched_account(account_id)? {
+        return Err(FaucetError::WatchOnly(account_id));
+    }
+    Ok(())
+}
+
+/// State of a watched account as of the last sync, with the mints it received from the faucet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchReport {
+    pub account_id: AccountId,
+    /// When the account was watched, in seconds since the Unix epoch.
+    pub watched_at: u64,
+    pub nonce: u64,
+    /// Fungible balances by faucet.
+    pub balances: Vec<(AccountId, u64)>,
+    pub mints: RecipientStats,
+}
+
+/// Reports on every watched account from the store and the ledger, oldest watched first.
+pub async fn watch_reports<N: FaucetNode>(
+    node: &mut N,
+    ledger: &Ledger,
+) -> Result<Vec<WatchReport>, FaucetError> {
+    let mut reports = Vec::new();
+    for (account_id, watched_at) in ledger.watched_accounts()? {
+        let account = node
+            .get_account(account_id)
+            .await?
+            .ok_or(FaucetError::AccountNotFound(account_id))?;
+        let balances = account
+            .vault()
+            .assets()
+            .filter_map(|asset| match asset {
+                Asset::Fungible(asset) => Some((asset.faucet_id(), asset.amount())),
+                Asset::NonFungible(_) => None,
+            })
+            .collect();
+        reports.push(WatchReport {
+            account_id,
+            watched_at,
+            nonce: account.nonce().as_int(),
+            balances,
+            mints: ledger.recipient_stats(account_id)?,
+        });
+    }
+    Ok(reports)
+}
@@ -0,0 +1,444 @@
+//! Chain tip tracking.
+//!
+//! A [`BlockWatcher`] owns the sync loop: it periodically syncs the shared node and publishes the
+//! chain tip whenever it moves. Code waiting on the chain subscribes to the watcher instead of
+//! running its own `sync_state` loop.
+//!
+//! Transactions waited for with [`track_transaction`] are polled by the watcher as well: after
+//! every sync it reads the state of all of them with a single store query and hands each waiter
+//! its own, so many mints in flight cost one `get_transactions` call per sync rather than one per
+//! mint.
+//!
+//! A watcher can require a number of confirmations: transactions are then only reported committed
+//! once that many blocks were produced on top of their block, see
+//! [`BlockWatcher::with_confirmations`].
+//!
+//! Client futures are not `Send`, so the watcher runs on the current thread through
+//! [`tokio::task::spawn_local`] and must be started inside a [`tokio::task::LocalSet`].
+//!
+//! Every wait of the faucet, here and in the scheduler, webhook and retry loops, goes through the
+//! [`tokio::time`] clock and its intervals are configured by [`PollConfig`] and the sections of the
+//! respective features. Tests run on a paused clock (`#[tokio::test(start_paused = true)]`), which
+//! jumps to the next timer as soon as every task is idle, so they wait no wall-clock time.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    rc::Rc,
+    time::Duration,
+};
+
+use miden_client::{note::Nullifier, transaction::TransactionId};
+use miden_objects::block::BlockNumber;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::{watch, Mutex},
+    task::JoinHandle,
+    time::Instant,
+};
+
+use crate::{
+    node::{FaucetNode, TxState},
+    schedule::SCHEDULE_POLL_INTERVAL,
+    FaucetError,
+};
+
+/// Default interval between two syncs of the watcher.
+pub const SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default interval between two supply reconciliations.
+pub const SUPPLY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Polling intervals, read from the `[poll]` section of the configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PollConfig {
+    /// Interval between two syncs of the block watcher, which also paces transaction tracking.
+    pub sync_interval_ms: u64,
+    /// Interval between two checks for due schedules by `serve`.
+    pub schedule_interval_ms: u64,
+    /// Interval between two supply reconciliations by `serve`, see [`crate::supply`].
+    pub supply_interval_ms: u64,
+}
+
+impl Default for PollConfig {
+    fn default() -> Self {
+        Self {
+            sync_interval_ms: SYNC_INTERVAL.as_millis() as u64,
+            schedule_interval_ms: SCHEDULE_POLL_INTERVAL.as_millis() as u64,
+            supply_interval_ms: SUPPLY_INTERVAL.as_millis() as u64,
+        }
+    }
+}
+
+impl PollConfig {
+    pub fn sync_interval(&self) -> Duration {
+        Duration::from_millis(self.sync_interval_ms)
+    }
+
+    pub fn schedule_interval(&self) -> Duration {
+        Duration::from_millis(self.schedule_interval_ms)
+    }
+
+    pub fn supply_interval(&self) -> Duration {
+        Duration::from_millis(self.supply_interval_ms)
+    }
+
+    /// Checks the settings without contacting the node.
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        for (name, value) in [
+            ("sync_interval_ms", self.sync_interval_ms),
+            ("schedule_interval_ms", self.schedule_interval_ms),
+            ("supply_interval_ms", self.supply_interval_ms),
+        ] {
+            if value == 0 {
+                return Err(FaucetError::Config(format!("poll.{name} must be positive")));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Node shared between the watcher and the flows submitting transactions.
+pub type SharedNode<N> = Rc<Mutex<N>>;
+
+/// Latest block known to the local store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainTip {
+    pub block_num: BlockNumber,
+    pub synced_at: Instant,
+}
+
+/// Transactions tracked through a [`BlockWatcher`], by ID.
+type TrackedTransactions = Rc<RefCell<BTreeMap<TransactionId, TrackedTransaction>>>;
+
+#[derive(Debug, Default)]
+struct TrackedTransaction {
+    waiters: usize,
+    /// State read by the last poll and the block the store was synced to, `None` if the store
+    /// did not know the transaction.
+    polled: Option<(BlockNumber, Option<TxState>)>,
+}
+
+/// Background sync loop publishing the chain tip to subscribers.
+///
+/// The loop stops when the watcher is dropped.
+pub struct BlockWatcher {
+    tip: watch::Receiver<Option<ChainTip>>,
+    task: JoinHandle<()>,
+    confirmations: u32,
+    tracked: TrackedTransactions,
+    /// When the last sync succeeded, whether or not the tip moved.
+    last_sync: Rc<Cell<Option<Instant>>>,
+}
+
+impl BlockWatcher {
+    /// Starts the sync loop, printing failed syncs to stderr.
+    pub fn spawn<N: FaucetNode + 'static>(node: SharedNode<N>, interval: Duration) -> Self {
+        Self::spawn_with_reporter(node, interval, |err| {
+            eprintln!("Block watcher failed to sync: {err}")
+        })
+    }
+
+    /// Like [`BlockWatcher::spawn`], handing failed syncs and transaction polls to `report`
+    /// instead.
+    pub fn spawn_with_reporter<N: FaucetNode + 'static>(
+        node: SharedNode<N>,
+        interval: Duration,
+        report: impl Fn(&FaucetError) + 'static,
+    ) -> Self {
+        let (sender, tip) = watch::channel(None);
+        let tracked = TrackedTransactions::default();
+        let last_sync = Rc::new(Cell::new(None));
+
+        let polled = tracked.clone();
+        let synced = last_sync.clone();
+        let task = tokio::task::spawn_local(async move {
+            loop {
+                let mut guard = node.lock().await;
+                let result = guard.sync_state().await;
+                if let Ok(block_num) = result {
+                    // Waiters woken by the new tip read the states polled here.
+                    if let Err(err) = poll_tracked(&mut *guard, &polled, block_num).await {
+                        report(&err);
+                    }
+                }
+                drop(guard);
+                match result {
+                    Ok(block_num) => {
+                        synced.set(Some(Instant::now()));
+                        sender.send_if_modified(|tip| {
+                            let advanced =
+                                tip.is_none_or(|tip: ChainTip| tip.block_num < block_num);
+                            if advanced {
+                                *tip = Some(ChainTip {
+                                    block_num,
+                                    synced_at: Instant::now(),
+                                });
+                            }
+                            advanced
+                        });
+                    }
+                    Err(err) => report(&err),
+                }
+
+                tokio::time::sleep(interval).await;
+            }
+        });
+
+        Self {
+            tip,
+            task,
+            confirmations: 0,
+            tracked,
+            last_sync,
+        }
+    }
+
+    /// Makes [`track_transaction`] wait until `confirmations` blocks were produced on top of the
+    /// block of a transaction before reporting it committed.
+    ///
+    /// Without confirmations a transaction counts as final as soon as its block is known, which a
+    /// reorg can still revert.
+    pub fn with_confirmations(mut self, confirmations: u32) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    pub fn confirmations(&self) -> u32 {
+        self.confirmations
+    }
+
+    /// Returns a receiver notified every time the chain tip advances.
+    pub fn subscribe(&self) -> watch::Receiver<Option<ChainTip>> {
+        self.tip.clone()
+    }
+
+    /// The most recent chain tip, or `None` before the first successful sync.
+    pub fn tip(&self) -> Option<ChainTip> {
+        *self.tip.borrow()
+    }
+
+    /// When the last sync succeeded, or `None` before the first one. Unlike the `synced_at` of
+    /// the [`tip`](Self::tip), it advances on syncs that found no new block, so a watcher whose
+    /// syncs keep failing shows a growing age here.
+    pub fn last_sync(&self) -> Option<Instant> {
+        self.last_sync.get()
+    }
+
+    /// Adds `transaction_id` to the transactions polled after every sync.
+    fn track(&self, transaction_id: TransactionId) -> Tracking {
+        self.tracked
+            .borrow_mut()
+            .entry(transaction_id)
+            .or_default()
+            .waiters += 1;
+        Tracking {
+            tracked: self.tracked.clone(),
+            transaction_id,
+        }
+    }
+
+    /// Resolves once the chain tip is at or past `block_num`.
+    pub async fn wait_for_block(&self, block_num: BlockNumber) -> Result<ChainTip, FaucetError> {
+        let mut tip = self.subscribe();
+        let reached = tip
+            .wait_for(|tip| tip.is_some_and(|tip| tip.block_num >= block_num))
+            .await
+            .map_err(|_| FaucetError::WatcherStopped)?;
+        Ok(reached.expect("the predicate only accepts a known tip"))
+    }
+}
+
+/// Reads the state of every tracked transaction in one query and records it for the waiters.
+async fn poll_tracked<N: FaucetNode>(
+    node: &mut N,
+    tracked: &TrackedTransactions,
+    block_num: BlockNumber,
+) -> Result<(), FaucetError> {
+    let transaction_ids = tracked.borrow().keys().copied().collect::<Vec<_>>();
+    if transaction_ids.is_empty() {
+        return Ok(());
+    }
+    let mut states = node.transaction_states(&transaction_ids).await?;
+    // Waiters may have come and gone during the query.
+    for (transaction_id, tracked) in tracked.borrow_mut().iter_mut() {
+        if transaction_ids.contains(transaction_id) {
+            tracked.polled = Some((block_num, states.remove(transaction_id)));
+        }
+    }
+    Ok(())
+}
+
+/// Registration of a waiter with the watcher, withdrawn on drop.
+struct Tracking {
+    tracked: TrackedTransactions,
+    transaction_id: TransactionId,
+}
+
+impl Tracking {
+    /// State of the transaction polled by the watcher at the current tip, if any.
+    fn polled_state(&self, tip: Option<ChainTip>) -> Option<Option<TxState>> {
+        let tip = tip?.block_num;
+        let tracked = self.tracked.borrow();
+        match &tracked.get(&self.transaction_id)?.polled {
+            Some((block_num, state)) if *block_num == tip => Some(state.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl Drop for Tracking {
+    fn drop(&mut self) {
+        let mut tracked = self.tracked.borrow_mut();
+        if let Some(entry) = tracked.get_mut(&self.transaction_id) {
+            entry.waiters -= 1;
+            if entry.waiters == 0 {
+                tracked.remove(&self.transaction_id);
+            }
+        }
+    }
+}
+
+impl Drop for BlockWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Waits for a transaction to be committed by the network.
+///
+/// The transaction state is re-checked every time the watcher reports a new block, until the
+/// transaction has the confirmations required by the watcher. A transaction reverted to pending
+/// before that is waited for again. Transient failures while reading the state do not abort the
+/// wait; a discarded transaction or any other error does.
+///
+/// The watcher polls the states of all waited transactions together; a transaction its last poll
+/// missed is queried on its own.
+pub async fn wait_for_transaction<N: FaucetNode>(
+    node: &SharedNode<N>,
+    watcher: &BlockWatcher,
+    transaction_id: TransactionId,
+) -> Result<BlockNumber, FaucetError> {
+    track_transaction(node, watcher, transaction_id, |_| {}).await
+}
+
+/// Like [`wait_for_transaction`], but reports every state read from the node to `on_state`.
+pub async fn track_transaction<N: FaucetNode>(
+    node: &SharedNode<N>,
+    watcher: &BlockWatcher,
+    transaction_id: TransactionId,
+    mut on_state: impl FnMut(&TxState),
+) -> Result<BlockNumber, FaucetError> {
+    let mut tip = watcher.subscribe();
+    let tracking = watcher.track(transaction_id);
+    let mut committed_at = None;
+
+    loop {
+        let state = match tracking.polled_state(watcher.tip()) {
+            Some(state) => Ok(state),
+            None => node.lock().await.transaction_state(transaction_id).await,
+        };
+        if let Ok(Some(state)) = &state {
+            on_state(state);
+        }
+        match state {
+            Ok(Some(TxState::Committed(block_number))) => {
+                let confirmed_at = block_number
+                    .as_u32()
+                    .saturating_add(watcher.confirmations());
+                let confirmed = watcher.confirmations() == 0
+                    || watcher
+                        .tip()
+                        .is_some_and(|tip| tip.block_num.as_u32() >= confirmed_at);
+                if confirmed {
+                    println!("Transaction committed at block {block_number}.");
+                    return Ok(block_number);
+                }
+                if committed_at != Some(block_number) {
+                    println!(
+                        "Transaction included in block {block_number}, awaiting {} confirmations.",
+                        watcher.confirmations()
+                    );
+                    committed_at = Some(block_number);
+                }
+            }
+            Ok(Some(TxState::Pending)) => {
+                if let Some(block_number) = committed_at.take() {
+                    eprintln!(
+                        "Transaction {transaction_id} was reverted from block {block_number}, \
+                         waiting for it to be committed again."
+                    );
+                }
+            }
+            Ok(Some(TxState::Discarded(cause))) => {
+                return Err(FaucetError::TransactionDiscarded(transaction_id, cause));
+            }
+            Ok(None) => return Err(FaucetError::TransactionNotFound(transaction_id)),
+            Err(err) if err.is_transient() => {
+                eprintln!("Failed to poll transaction {transaction_id}, retrying: {err}");
+            }
+            Err(err) => return Err(err),
+        }
+
+        tip.changed()
+            .await
+            .map_err(|_| FaucetError::WatcherStopped)?;
+    }
+}
+
+/// Waits for the note with `nullifier` to be consumed on chain, e.g. to confirm that the
+/// recipient of a mint claimed it, and returns the block that consumed it.
+///
+/// The nullifiers consumed from `from_block` on, typically the block the note was created in,
+/// are checked every time the watcher reports a new block. Transient failures do not abort the
+/// wait. Fails with [`FaucetError::ConsumeTimeout`] if the note is still unconsumed after
+/// `timeout`.
+pub async fn wait_for_note_consumption<N: FaucetNode>(
+    node: &SharedNode<N>,
+    watcher: &BlockWatcher,
+    nullifier: Nullifier,
+    from_block: BlockNumber,
+    timeout: Duration,
+) -> Result<BlockNumber, FaucetError> {
+    let wait = async {
+        let mut tip = watcher.subscribe();
+        let mut from_block = from_block;
+        loop {
+            let checked_to = watcher.tip().map(|tip| tip.block_num);
+            let consumed = node
+                .lock()
+                .await
+                .consumed_nullifiers(&[nullifier.prefix()], from_block)
+                .await;
+            match consumed {
+                Ok(consumed) => {
+                    if let Some((_, block_num)) =
+                        consumed.into_iter().find(|(found, _)| *found == nullifier)
+                    {
+                        return Ok(block_num);
+                    }
+                    from_block = checked_to.map_or(from_block, |tip| tip.max(from_block));
+                }
+                Err(err) if err.is_transient() => {
+                    eprintln!(
+                        "Failed to check nullifier {}, retrying: {err}",
+                        nullifier.to_hex()
+                    );
+                }
+                Err(err) => return Err(err),
+            }
+
+            tip.changed()
+                .await
+                .map_err(|_| FaucetError::WatcherStopped)?;
+        }
+    };
+
+    tokio::time::timeout(timeout, wait)
+        .await
+        .map_err(|_| FaucetError::ConsumeTimeout {
+            nullifier: nullifier.to_hex(),
+            timeout_ms: timeout.as_millis() as u64,
+        })?
+}
@@ -0,0 +1,154 @@
+//! Claim notifications.
+//!
+//! When the indexer sees a faucet-issued note consumed, [`ClaimWebhook`] POSTs a JSON
+//! [`ClaimEvent`] to the configured URL.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ledger::MintRecord, FaucetError};
+
+/// Webhook settings, read from the `[webhook]` section of the configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Sent as `Authorization: Bearer <token>` when set.
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Deliveries attempted per event, including the first.
+    #[serde(default = "default_attempts")]
+    pub attempts: u32,
+    /// Wait before the second delivery, doubled before every further one.
+    #[serde(default = "default_backoff_ms")]
+    pub backoff_ms: u64,
+}
+
+impl WebhookConfig {
+    /// Checks the settings without sending anything.
+    pub fn validate(&self) -> Result<(), FaucetError> {
+        if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+            return Err(FaucetError::Config(format!(
+                "webhook.url `{}` must be an http:// or https:// URL",
+                self.url
+            )));
+        }
+        if self.timeout_ms == 0 {
+            return Err(FaucetError::Config(
+                "webhook.timeout_ms must be positive".into(),
+            ));
+        }
+        if self.attempts == 0 {
+            return Err(FaucetError::Config(
+                "webhook.attempts counts the first delivery and must be at least 1".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn default_timeout_ms() -> u64 {
+    5_000
+}
+
+fn default_attempts() -> u32 {
+    3
+}
+
+fn default_backoff_ms() -> u64 {
+    2_000
+}
+
+/// Payload of a claim notification.
+///
+/// The node only reports which nullifiers were spent and in which block, so the consuming
+/// transaction is not known and is not part of the event.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClaimEvent<'a> {
+    pub event: &'static str,
+    pub mint_id: i64,
+    pub faucet_id: &'a str,
+    pub recipient: &'a str,
+    pub amount: u64,
+    pub note_id: &'a str,
+    pub nullifier: &'a str,
+    pub mint_transaction_id: &'a str,
+    pub block_num: u32,
+    /// Campaign decoded from the aux value of the note, 0 for none.
+    pub campaign_id: u32,
+    /// Where the mint was requested from, e.g. `rest`.
+    pub source: &'static str,
+}
+
+impl<'a> ClaimEvent<'a> {
+    pub fn from_record(record: &'a MintRecord) -> Option<Self> {
+        let aux = record.aux_data().unwrap_or_default();
+        Some(Self {
+            event: "note_claimed",
+            mint_id: record.id,
+            faucet_id: &record.faucet_id,
+            recipient: &record.recipient,
+            amount: record.amount,
+            note_id: &record.note_id,
+            nullifier: &record.nullifier,
+            mint_transaction_id: &record.transaction_id,
+            block_num: record.claim_block?,
+            campaign_id: aux.campaign_id,
+            source: aux.source.as_str(),
+        })
+    }
+}
+
+/// Delivers [`ClaimEvent`]s to the configured endpoint.
+pub struct ClaimWebhook {
+    http: reqwest::Client,
+    config: WebhookConfig,
+}
+
+impl ClaimWebhook {
+    pub fn new(config: WebhookConfig) -> Result<Self, FaucetError> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .map_err(|err| FaucetError::Webhook(err.to_string()))?;
+        Ok(Self { http, config })
+    }
+
+    /// Sends the claim event of `record`, retrying failed deliveries.
+    pub async fn notify(&self, record: &MintRecord) -> Result<(), FaucetError> {
+        let event = ClaimEvent::from_record(record).ok_or_else(|| {
+            FaucetError::Webhook(format!("mint {} has not been claimed", record.id))
+        })?;
+
+        let attempts = self.config.attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            let mut request = self.http.post(&self.config.url).json(&event);
+            if let Some(token) = &self.config.token {
+                request = request.bearer_auth(token);
+            }
+
+            let result = match request.send().await {
+                Ok(response) => response.error_for_status().map(|_| ()),
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(()) => return Ok(()),
+                Err(err) if attempt >= attempts => {
+                    return Err(FaucetError::Webhook(err.to_string()))
+                }
+                Err(err) => {
+                    eprintln!("Claim webhook attempt {attempt}/{attempts} failed: {err}");
+                    tokio::time::sleep(Duration::from_millis(
+                        self.config.backoff_ms << (attempt - 1),
+                    ))
+                    .await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,379 @@
+mod common;
+
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    rc::Rc,
+    time::Duration,
+};
+
+use common::{
+    fixtures::{faucet_id, wallet_id},
+    transaction_id, MockNode,
+};
+use faucet_notes::mint_output_note;
+use miden_client::{Felt, Word};
+use network_faucet::{
+    abuse::{
+        AbusePolicies, AbusePolicy, AbusePolicyConfig, AbuseRequest, BudgetBasis, RequestKey,
+        DAY_SECS,
+    },
+    config::Config,
+    deploy::deploy_faucet,
+    ledger::Ledger,
+    mint::{MintNoteKind, MintOptions},
+    service::{faucet_service, BatchEntry, ServiceConfig},
+    wallet::create_wallet,
+    watcher::BlockWatcher,
+    FaucetError,
+};
+use tokio::{sync::Mutex, task::LocalSet};
+
+const DEPLOY_SCRIPT: &str = include_str!("../masm/deploy.masm");
+const SYNC_INTERVAL: Duration = Duration::from_millis(10);
+const CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7));
+
+fn request(ip: IpAddr, amount: u64, now: u64) -> AbuseRequest {
+    AbuseRequest {
+        identity: None,
+        ip,
+        faucet_id: faucet_id([1; 15]),
+        recipient: wallet_id([2; 15]),
+        amount,
+        batched: 0,
+        now,
+    }
+}
+
+#[test]
+fn policies_are_combined_from_the_configuration() {
+    let config: Config = toml::from_str(
+        r#"
+        [[abuse_policies]]
+        kind = "velocity"
+        key = "ip"
+        max_requests = 2
+        window_secs = 60
+
+        [[abuse_policies]]
+        kind = "daily_budget"
+        max_amount = 250
+
+        [[abuse_policies]]
+        kind = "daily_budget"
+        max_amount = 1000
+        basis = "distributed"
+        "#,
+    )
+    .unwrap();
+    config.validate().unwrap();
+    assert_eq!(
+        config.abuse_policies[0],
+        AbusePolicyConfig::Velocity {
+            key: RequestKey::Ip,
+            max_requests: 2,
+            window_secs: 60,
+        }
+    );
+    assert_eq!(
+        config.abuse_policies[2],
+        AbusePolicyConfig::DailyBudget {
+            max_amount: 1000,
+            basis: BudgetBasis::Distributed,
+        }
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let policies = AbusePolicies::from_config(&config.abuse_policies);
+    for now in [1_000, 1_010] {
+        let admitted = request(CLIENT, 100, now);
+        policies.check(&admitted, &ledger).unwrap();
+        policies.record(&admitted, &ledger).unwrap();
+    }
+
+    // The third request of the address waits for the first to leave the window.
+    assert!(matches!(
+        policies.check(&request(CLIENT, 10, 1_020), &ledger),
+        Err(FaucetError::RateLimited {
+            retry_after_secs: 40
+        })
+    ));
+    // Other addresses share the daily budget.
+    let other = IpAddr::V4(Ipv4Addr::new(198, 51, 100, 1));
+    policies.check(&request(other, 50, 1_020), &ledger).unwrap();
+    assert!(matches!(
+        policies.check(&request(other, 51, 1_020), &ledger),
+        Err(FaucetError::AbuseRefused(policy, _)) if policy == "daily_budget"
+    ));
+    policies
+        .check(&request(other, 51, 1_000 + DAY_SECS), &ledger)
+        .unwrap();
+}
+
+#[test]
+fn distributed_budget_frees_up_as_mints_leave_the_window() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ledger.sqlite3");
+    let ledger = Ledger::open(&path).unwrap();
+    let faucet = faucet_id([1; 15]);
+    let recipient = wallet_id([2; 15]);
+
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    for (n, (amount, created_at)) in [(40, 1_000), (30, 2_000), (20, 3_000)]
+        .into_iter()
+        .enumerate()
+    {
+        let note = mint_output_note(
+            faucet,
+            recipient,
+            amount,
+            Word::from([Felt::new(n as u64); 4]),
+            MintNoteKind::P2id,
+        )
+        .unwrap();
+        let tx_id = transaction_id(n as u64 + 1);
+        let id = ledger
+            .record_mint(faucet, recipient, amount, tx_id, &note)
+            .unwrap();
+        conn.execute(
+            "UPDATE mints SET created_at = ?1 WHERE id = ?2",
+            (created_at, id),
+        )
+        .unwrap();
+        if n == 2 {
+            // Failed mints distributed nothing.
+            ledger.mark_failed(tx_id, "discarded").unwrap();
+        }
+    }
+
+    let budget = AbusePolicyConfig::DailyBudget {
+        max_amount: 100,
+        basis: BudgetBasis::Distributed,
+    };
+    let now = 5_000;
+    budget.check(&request(CLIENT, 30, now), &ledger).unwrap();
+    // 70 of 100 are spent; 50 more fit once the first mint of 40 leaves the window.
+    let err = budget
+        .check(&request(CLIENT, 50, now), &ledger)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        FaucetError::DailyBudgetExhausted {
+            budget: 100,
+            remaining: 30,
+            retry_at,
+        } if retry_at == 1_000 + DAY_SECS
+    ));
+    assert!(err
+        .to_string()
+        .contains("come back at 1970-01-02T00:16:40Z"));
+    let err = budget
+        .check(&request(CLIENT, 90, now), &ledger)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        FaucetError::DailyBudgetExhausted { retry_at, .. } if retry_at == 2_000 + DAY_SECS
+    ));
+    budget
+        .check(&request(CLIENT, 90, 2_000 + DAY_SECS), &ledger)
+        .unwrap();
+
+    assert!(matches!(
+        budget.check(&request(CLIENT, 101, now), &ledger),
+        Err(FaucetError::AmountOutOfRange { max: 100, .. })
+    ));
+    // The requests judged before it in the same submission count as distributed.
+    let batched = AbuseRequest {
+        batched: 20,
+        ..request(CLIENT, 20, now)
+    };
+    assert!(matches!(
+        budget.check(&batched, &ledger),
+        Err(FaucetError::DailyBudgetExhausted { .. })
+    ));
+}
+
+#[test]
+fn new_accounts_receive_limited_amounts() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let policies = AbusePolicies::default().with_policy(AbusePolicyConfig::NewAccount {
+        max_amount: Some(100),
+        max_unclaimed: None,
+    });
+    policies.check(&request(CLIENT, 100, 1), &ledger).unwrap();
+    assert!(matches!(
+        policies.check(&request(CLIENT, 101, 1), &ledger),
+        Err(FaucetError::AbuseRefused(policy, _)) if policy == "new_account"
+    ));
+
+    let invalid: Config = toml::from_str(
+        r#"
+        [[abuse_policies]]
+        kind = "new_account"
+        "#,
+    )
+    .unwrap();
+    assert!(matches!(invalid.validate(), Err(FaucetError::Config(_))));
+}
+
+#[tokio::test]
+async fn worker_judges_mints_of_api_clients() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+            let recipient = create_wallet(&mut node).await.unwrap();
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), SYNC_INTERVAL));
+            let (handle, worker) = faucet_service(
+                node,
+                watcher,
+                ledger,
+                deployment.faucet.id(),
+                &ServiceConfig::default(),
+            );
+            let policies = AbusePolicies::default().with_policy(AbusePolicyConfig::Velocity {
+                key: RequestKey::Ip,
+                max_requests: 1,
+                window_secs: 3600,
+            });
+            tokio::task::spawn_local(worker.with_abuse_policies(policies).run());
+
+            let client = handle.with_client_ip(CLIENT);
+            client
+                .mint(recipient.id(), 10, MintOptions::default())
+                .await
+                .unwrap();
+            assert!(matches!(
+                client
+                    .mint(recipient.id(), 10, MintOptions::default())
+                    .await,
+                Err(FaucetError::RateLimited { .. })
+            ));
+
+            // Mints of the service itself are not judged.
+            handle
+                .mint(recipient.id(), 10, MintOptions::default())
+                .await
+                .unwrap();
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn batch_mints_count_the_mints_before_them() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+            let mut recipients = Vec::new();
+            for _ in 0..3 {
+                recipients.push(create_wallet(&mut node).await.unwrap().id());
+            }
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), SYNC_INTERVAL));
+            let (handle, worker) = faucet_service(
+                node,
+                watcher,
+                ledger,
+                deployment.faucet.id(),
+                &ServiceConfig::default(),
+            );
+            let policies = AbusePolicies::default().with_policy(AbusePolicyConfig::Velocity {
+                key: RequestKey::Ip,
+                max_requests: 2,
+                window_secs: 3600,
+            });
+            tokio::task::spawn_local(worker.with_abuse_policies(policies).run());
+
+            let entries: Vec<_> = recipients
+                .iter()
+                .map(|&recipient| BatchEntry {
+                    recipient,
+                    amount: Some(10),
+                    options: MintOptions::default(),
+                    email: None,
+                })
+                .collect();
+            let client = handle.with_client_ip(CLIENT);
+            assert!(matches!(
+                client.mint_batch(entries.clone()).await,
+                Err(FaucetError::RateLimited { .. })
+            ));
+
+            // The refused batch left no requests behind.
+            client.mint_batch(entries[..2].to_vec()).await.unwrap();
+            assert!(matches!(
+                client.mint(recipients[2], 10, MintOptions::default()).await,
+                Err(FaucetError::RateLimited { .. })
+            ));
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn worker_holds_api_mints_to_the_daily_budget() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+            let recipient = create_wallet(&mut node).await.unwrap();
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), SYNC_INTERVAL));
+            let config = ServiceConfig {
+                daily_budget: Some(100),
+                ..ServiceConfig::default()
+            };
+            let (handle, worker) =
+                faucet_service(node, watcher, ledger, deployment.faucet.id(), &config);
+            tokio::task::spawn_local(worker.run());
+
+            // Mints of the service itself are not judged, but count against the budget.
+            handle
+                .mint(recipient.id(), 50, MintOptions::default())
+                .await
+                .unwrap();
+            let client = handle.with_client_ip(CLIENT);
+            let entry = BatchEntry {
+                recipient: recipient.id(),
+                amount: Some(30),
+                options: MintOptions::default(),
+                email: None,
+            };
+            assert!(matches!(
+                client.mint_batch(vec![entry.clone(), entry]).await,
+                Err(FaucetError::DailyBudgetExhausted { remaining: 50, .. })
+            ));
+            client
+                .mint(recipient.id(), 50, MintOptions::default())
+                .await
+                .unwrap();
+            assert!(matches!(
+                client.mint(recipient.id(), 1, MintOptions::default()).await,
+                Err(FaucetError::DailyBudgetExhausted { remaining: 0, .. })
+            ));
+        })
+        .await;
+}
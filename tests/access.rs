@@ -0,0 +1,364 @@
+mod common;
+
+use std::net::IpAddr;
+
+use common::fixtures::wallet_id;
+use network_faucet::{
+    access::{
+        check_access_lists, parse_list_subject, Access, AccessList, Caller, Tier, TierConfig,
+    },
+    authz::Role,
+    config::Config,
+    github::{DripLimit, GithubAuth, GithubUser},
+    ledger::Ledger,
+    FaucetError,
+};
+
+const CONFIG: &str = r#"
+[mint]
+max_amount = 100
+
+[tiers.anonymous]
+default_amount = 5
+max_amount = 10
+
+[tiers.admin]
+max_amount = 1000000
+clamp = true
+
+[[api_keys]]
+name = "ci"
+key = "ci-key"
+
+[[api_keys]]
+name = "ops"
+key = "ops-key"
+admin = true
+"#;
+
+fn access(config: &Config, github: Option<GithubAuth>) -> Access {
+    Access::new(
+        config.mint.clone(),
+        config.tiers.clone(),
+        config.api_keys.clone(),
+        github,
+    )
+}
+
+#[test]
+fn credentials_select_the_tier() {
+    let config: Config = toml::from_str(CONFIG).unwrap();
+    let access = access(&config, None);
+
+    let anonymous = access.authenticate(None).unwrap();
+    assert_eq!(anonymous, Caller::Anonymous);
+    assert_eq!(anonymous.identity(), None);
+
+    let ci = access.authenticate(Some("Bearer ci-key")).unwrap();
+    assert_eq!(ci.tier(), Tier::ApiKey);
+    assert_eq!(ci.identity().as_deref(), Some("api_key:ci"));
+    let ops = access.authenticate(Some("Bearer ops-key")).unwrap();
+    assert_eq!(ops.tier(), Tier::Admin);
+
+    assert!(matches!(
+        access.authenticate(Some("Bearer guess")),
+        Err(FaucetError::Unauthorized(_))
+    ));
+}
+
+#[test]
+fn tiers_override_the_mint_amounts() {
+    let config: Config = toml::from_str(CONFIG).unwrap();
+    config.validate().unwrap();
+    let access = access(&config, None);
+
+    assert!(matches!(
+        access.amounts(Tier::Anonymous).resolve(Some(50)),
+        Err(FaucetError::AmountOutOfRange { max: 10, .. })
+    ));
+    assert_eq!(access.amounts(Tier::ApiKey).resolve(Some(50)).unwrap(), 50);
+    assert_eq!(
+        access
+            .amounts(Tier::Admin)
+            .resolve(Some(5_000_000))
+            .unwrap(),
+        1_000_000
+    );
+    // The default amount of `[mint]` is outside the anonymous bounds.
+    let config: Config = toml::from_str("[tiers.anonymous]\nmax_amount = 10").unwrap();
+    assert!(matches!(
+        config.validate(),
+        Err(FaucetError::Config(message)) if message.starts_with("tiers.anonymous")
+    ));
+}
+
+#[test]
+fn tiers_limit_the_requests_of_each_caller() {
+    let config: Config = toml::from_str(
+        r#"
+[tiers.anonymous]
+max_requests = 1
+window_secs = 60
+
+[tiers.api_key]
+max_requests = 3
+window_secs = 60
+daily_requests = 4
+
+[[api_keys]]
+name = "ci"
+key = "ci-key"
+"#,
+    )
+    .unwrap();
+    config.validate().unwrap();
+    let access = access(&config, None);
+    let anonymous = access.authenticate(None).unwrap();
+    let ci = access.authenticate(Some("Bearer ci-key")).unwrap();
+    let ip = Some(IpAddr::from([10, 0, 0, 1]));
+
+    access.admit(&anonymous, ip, 1).unwrap();
+    assert!(matches!(
+        access.admit(&anonymous, ip, 1),
+        Err(FaucetError::RateLimited { retry_after_secs }) if retry_after_secs <= 60
+    ));
+    // Anonymous callers are told apart by address.
+    access
+        .admit(&anonymous, Some(IpAddr::from([10, 0, 0, 2])), 1)
+        .unwrap();
+
+    // A refused batch counts for nothing.
+    assert!(access.admit(&ci, ip, 4).is_err());
+    access.admit(&ci, ip, 1).unwrap();
+    access.admit(&ci, ip, 2).unwrap();
+    assert!(matches!(
+        access.admit(&ci, ip, 1),
+        Err(FaucetError::RateLimited { .. })
+    ));
+    // Tiers without limits admit every request.
+    let ops = Caller::ApiKey {
+        name: "ops".into(),
+        role: Some(Role::Admin),
+    };
+    for _ in 0..10 {
+        access.admit(&ops, None, 1).unwrap();
+    }
+
+    let config: Config = toml::from_str("[tiers.github]\nmax_requests = 1").unwrap();
+    assert!(matches!(
+        config.validate(),
+        Err(FaucetError::Config(message)) if message.starts_with("tiers.github")
+    ));
+}
+
+#[test]
+fn github_sign_in_refuses_anonymous_callers() {
+    let config: Config = toml::from_str(CONFIG).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let github = github_auth(&dir);
+    let user = GithubUser {
+        id: 42,
+        login: "octocat".into(),
+    };
+    let session = github.start_session(&user).unwrap();
+    let access = access(&config, Some(github));
+
+    assert!(matches!(
+        access.authenticate(None),
+        Err(FaucetError::Unauthorized(_))
+    ));
+    let header = format!("Bearer {}", session.token);
+    assert_eq!(
+        access.authenticate(Some(&header)).unwrap(),
+        Caller::Github(user)
+    );
+    // API keys still work without signing in.
+    assert_eq!(
+        access.authenticate(Some("Bearer ci-key")).unwrap().tier(),
+        Tier::ApiKey
+    );
+}
+
+#[test]
+fn tiers_and_drip_limits_change_at_runtime() {
+    let config: Config = toml::from_str(CONFIG).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let access = access(&config, Some(github_auth(&dir)));
+
+    let amounts = access
+        .set_tier(
+            Tier::Anonymous,
+            TierConfig {
+                max_amount: Some(60),
+                ..TierConfig::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(amounts.max_amount, 60);
+    assert_eq!(
+        access.amounts(Tier::Anonymous).resolve(Some(50)).unwrap(),
+        50
+    );
+    assert_eq!(access.tiers().anonymous.max_amount, Some(60));
+    // The default amount of `[mint]` would be out of range.
+    assert!(access
+        .set_tier(
+            Tier::Github,
+            TierConfig {
+                max_amount: Some(1),
+                ..TierConfig::default()
+            },
+        )
+        .is_err());
+    assert_eq!("api_key".parse::<Tier>().unwrap(), Tier::ApiKey);
+    assert!(matches!(
+        "root".parse::<Tier>(),
+        Err(FaucetError::InvalidTier(_))
+    ));
+
+    let github = access.github().unwrap();
+    assert_eq!(github.drip_limit().max_drips, 1);
+    let limit = DripLimit {
+        max_drips: 3,
+        window_secs: 3_600,
+    };
+    github.set_drip_limit(limit).unwrap();
+    assert_eq!(github.drip_limit(), limit);
+    assert!(github
+        .set_drip_limit(DripLimit {
+            max_drips: 0,
+            window_secs: 3_600,
+        })
+        .is_err());
+    let user = GithubUser {
+        id: 7,
+        login: "hubot".into(),
+    };
+    for _ in 0..3 {
+        github.reserve_drip(&user).unwrap();
+    }
+    assert!(matches!(
+        github.reserve_drip(&user),
+        Err(FaucetError::RateLimited { .. })
+    ));
+}
+
+#[test]
+fn access_lists_refuse_and_restrict_mints() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let (alice, bob) = (wallet_id([2; 15]), wallet_id([3; 15]));
+    check_access_lists(&ledger, "api_key:ci", alice).unwrap();
+
+    let subject = parse_list_subject(&alice.to_hex()).unwrap();
+    ledger
+        .add_list_entry(AccessList::Deny, &subject, Some("abuse"))
+        .unwrap();
+    assert!(matches!(
+        check_access_lists(&ledger, "api_key:ci", alice),
+        Err(FaucetError::AccessDenied(_))
+    ));
+    ledger
+        .add_list_entry(AccessList::Deny, "github:mallory", None)
+        .unwrap();
+    assert!(check_access_lists(&ledger, "github:mallory", bob).is_err());
+    check_access_lists(&ledger, "github:octocat", bob).unwrap();
+
+    // Once the allow list has entries, only they are served.
+    ledger
+        .add_list_entry(AccessList::Allow, &bob.to_hex(), None)
+        .unwrap();
+    check_access_lists(&ledger, "github:octocat", bob).unwrap();
+    assert!(check_access_lists(&ledger, "github:octocat", wallet_id([4; 15])).is_err());
+    ledger
+        .add_list_entry(AccessList::Allow, "api_key:ci", None)
+        .unwrap();
+    check_access_lists(&ledger, "api_key:ci", wallet_id([4; 15])).unwrap();
+
+    assert_eq!(ledger.list_entries().unwrap().len(), 4);
+    assert!(ledger
+        .remove_list_entry(AccessList::Deny, &subject)
+        .unwrap());
+    assert!(!ledger
+        .remove_list_entry(AccessList::Deny, &subject)
+        .unwrap());
+    check_access_lists(&ledger, "api_key:ci", alice).unwrap();
+
+    assert!(parse_list_subject("github:").is_err());
+    assert!(parse_list_subject("root:admin").is_err());
+    assert!(parse_list_subject("not-an-account").is_err());
+    assert!("block".parse::<AccessList>().is_err());
+}
+
+#[test]
+fn adjusted_limits_survive_restarts_until_reset() {
+    let config: Config = toml::from_str(CONFIG).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = || Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let restart = || {
+        access(&config, Some(github_auth(&dir)))
+            .with_ledger(ledger())
+            .unwrap()
+    };
+    let limit = DripLimit {
+        max_drips: 5,
+        window_secs: 600,
+    };
+
+    let access = restart();
+    access
+        .set_tier(
+            Tier::Anonymous,
+            TierConfig {
+                default_amount: Some(5),
+                max_amount: Some(5),
+                ..TierConfig::default()
+            },
+        )
+        .unwrap();
+    access.github().unwrap().set_drip_limit(limit).unwrap();
+    drop(access);
+
+    let access = restart();
+    assert_eq!(access.amounts(Tier::Anonymous).max_amount, 5);
+    assert_eq!(access.github().unwrap().drip_limit(), limit);
+    assert!(ledger()
+        .limit_override("github.drip_limit")
+        .unwrap()
+        .is_some());
+
+    assert_eq!(access.reset_tier(Tier::Anonymous).unwrap().max_amount, 10);
+    assert_eq!(
+        access
+            .github()
+            .unwrap()
+            .reset_drip_limit()
+            .unwrap()
+            .max_drips,
+        1
+    );
+    drop(access);
+
+    let access = restart();
+    assert_eq!(access.amounts(Tier::Anonymous).max_amount, 10);
+    assert_eq!(access.github().unwrap().drip_limit().max_drips, 1);
+    assert!(ledger()
+        .limit_override("tiers.anonymous")
+        .unwrap()
+        .is_none());
+}
+
+fn github_auth(dir: &tempfile::TempDir) -> GithubAuth {
+    GithubAuth::new(
+        toml::from_str(
+            r#"
+            client_id = "client"
+            client_secret = "secret"
+            redirect_url = "https://faucet.example.com/api/auth/github/callback"
+            "#,
+        )
+        .unwrap(),
+        Ledger::open(dir.path().join("ledger.sqlite3")).unwrap(),
+    )
+    .unwrap()
+}
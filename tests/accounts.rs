@@ -0,0 +1,238 @@
+mod common;
+
+use common::{
+    fixtures::{faucet_id, wallet_id},
+    MockNode,
+};
+use miden_client::{
+    account::{Account, AccountStorage, StorageSlot},
+    asset::{AssetVault, FungibleAsset},
+    auth::AuthSecretKey,
+    Felt, Word, ONE,
+};
+use miden_objects::{address::NetworkId, block::BlockNumber};
+use network_faucet::{
+    account::{parse_account_id, resolve_account_id, validate_label},
+    history::{account_diff, snapshot_account, Change},
+    ledger::Ledger,
+    wallet::{create_wallet, create_wallet_with_key, list_wallets, parse_key_id},
+    FaucetError,
+};
+
+#[test]
+fn labels_resolve_to_accounts() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let (alice, faucet) = (wallet_id([2; 15]), faucet_id([1; 15]));
+
+    ledger.set_label("alice", alice).unwrap();
+    ledger.set_label("mde-faucet", faucet).unwrap();
+    assert_eq!(resolve_account_id(&ledger, "alice", None).unwrap(), alice);
+    assert_eq!(
+        resolve_account_id(&ledger, &faucet.to_hex(), None).unwrap(),
+        faucet
+    );
+    assert!(resolve_account_id(&ledger, "bob", None).is_err());
+
+    // Labels move when reassigned.
+    ledger.set_label("alice", faucet).unwrap();
+    assert_eq!(resolve_account_id(&ledger, "alice", None).unwrap(), faucet);
+    assert_eq!(ledger.labels().unwrap().len(), 2);
+
+    assert!(ledger.remove_label("alice").unwrap());
+    assert!(!ledger.remove_label("alice").unwrap());
+
+    assert!(validate_label("mde-faucet").is_ok());
+    for invalid in ["", "0xabc", "with space"] {
+        assert!(validate_label(invalid).is_err());
+    }
+}
+
+#[test]
+fn account_ids_parse_from_hex_and_bech32() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let alice = wallet_id([2; 15]);
+
+    assert_eq!(parse_account_id(&alice.to_hex(), None).unwrap(), alice);
+    for network in [NetworkId::Testnet, NetworkId::Devnet, NetworkId::Mainnet] {
+        let bech32 = alice.to_bech32(network);
+        assert_eq!(
+            parse_account_id(&format!(" {bech32} "), None).unwrap(),
+            alice
+        );
+        assert_eq!(resolve_account_id(&ledger, &bech32, None).unwrap(), alice);
+    }
+
+    let mut corrupted = alice.to_bech32(NetworkId::Testnet);
+    corrupted.pop();
+    for invalid in ["0x12", "alice", corrupted.as_str()] {
+        assert!(matches!(
+            parse_account_id(invalid, None),
+            Err(FaucetError::InvalidAccountId(..))
+        ));
+    }
+}
+
+#[test]
+fn bech32_account_ids_must_carry_the_prefix_of_their_network() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let alice = wallet_id([2; 15]);
+    ledger.set_label("alice", alice).unwrap();
+    let testnet = Some(&NetworkId::Testnet);
+
+    let devnet_id = alice.to_bech32(NetworkId::Devnet);
+    for parsed in [
+        parse_account_id(&devnet_id, testnet),
+        resolve_account_id(&ledger, &devnet_id, testnet),
+    ] {
+        assert!(matches!(
+            parsed,
+            Err(FaucetError::AccountNetworkMismatch {
+                expected: NetworkId::Testnet,
+                found: NetworkId::Devnet,
+                ..
+            })
+        ));
+    }
+    let testnet_id = alice.to_bech32(NetworkId::Testnet);
+    assert_eq!(parse_account_id(&testnet_id, testnet).unwrap(), alice);
+
+    // Hex IDs and labels carry no network.
+    for network in [NetworkId::Testnet, NetworkId::Devnet, NetworkId::Mainnet] {
+        assert_eq!(
+            parse_account_id(&alice.to_hex(), Some(&network)).unwrap(),
+            alice
+        );
+        assert_eq!(
+            resolve_account_id(&ledger, "alice", Some(&network)).unwrap(),
+            alice
+        );
+    }
+}
+
+#[tokio::test]
+async fn removed_wallets_leave_the_wallet_list() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let mut node = MockNode::new();
+    let alice = create_wallet(&mut node).await.unwrap().id();
+    let bob = create_wallet(&mut node).await.unwrap().id();
+    node.accounts.insert(
+        faucet_id([1; 15]),
+        node.accounts.get(&alice).unwrap().clone(),
+    );
+
+    let mut wallets = list_wallets(&mut node, &ledger).await.unwrap();
+    wallets.sort();
+    let mut expected = vec![alice, bob];
+    expected.sort();
+    assert_eq!(wallets, expected);
+
+    ledger.set_label("alice", alice).unwrap();
+    ledger.remove_wallet(alice).unwrap();
+    assert_eq!(list_wallets(&mut node, &ledger).await.unwrap(), [bob]);
+    assert!(ledger.labels_of(alice).unwrap().is_empty());
+    assert!(node.accounts.contains_key(&alice));
+}
+
+#[tokio::test]
+async fn wallets_can_share_a_keystore_key() {
+    let mut node = MockNode::new();
+    let alice = create_wallet(&mut node).await.unwrap();
+    let AuthSecretKey::RpoFalcon512(key) = node.keys[0].clone() else {
+        panic!("expected a Falcon key");
+    };
+    let key_id = parse_key_id(&key.public_key().to_commitment().to_hex()).unwrap();
+
+    let bob = create_wallet_with_key(&mut node, key_id).await.unwrap();
+    assert_ne!(bob.id(), alice.id());
+    assert_eq!(node.keys.len(), 1);
+    assert!(node.accounts.contains_key(&bob.id()));
+
+    let unknown = Word::from([Felt::new(3); 4]);
+    assert!(matches!(
+        create_wallet_with_key(&mut node, unknown).await,
+        Err(FaucetError::KeyIdNotFound(_))
+    ));
+    assert!(parse_key_id("0x12").is_err());
+}
+
+#[test]
+fn default_account_is_persisted() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ledger.sqlite3");
+    let alice = wallet_id([2; 15]);
+
+    let ledger = Ledger::open(&path).unwrap();
+    assert_eq!(ledger.default_account().unwrap(), None);
+    ledger.set_default_account(Some(alice)).unwrap();
+    drop(ledger);
+
+    let ledger = Ledger::open(&path).unwrap();
+    assert_eq!(ledger.default_account().unwrap(), Some(alice));
+    ledger.remove_wallet(alice).unwrap();
+    assert_eq!(ledger.default_account().unwrap(), None);
+
+    ledger.set_default_account(Some(alice)).unwrap();
+    ledger.set_default_account(None).unwrap();
+    assert_eq!(ledger.default_account().unwrap(), None);
+}
+
+#[tokio::test]
+async fn account_diff_compares_recorded_states() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let mut node = MockNode::new();
+    let alice = create_wallet(&mut node).await.unwrap().id();
+    let faucet = faucet_id([1; 15]);
+
+    assert!(
+        snapshot_account(&mut node, &ledger, alice, BlockNumber::from(3))
+            .await
+            .unwrap()
+    );
+    // Unchanged states are not recorded again.
+    assert!(
+        !snapshot_account(&mut node, &ledger, alice, BlockNumber::from(5))
+            .await
+            .unwrap()
+    );
+
+    let (id, _, _, code, nonce, _) = node.accounts.remove(&alice).unwrap().into_parts();
+    let vault = AssetVault::new(&[FungibleAsset::new(faucet, 10).unwrap().into()]).unwrap();
+    let new_key = Word::from([Felt::new(7); 4]);
+    let storage = AccountStorage::new(vec![StorageSlot::Value(new_key)]).unwrap();
+    node.accounts.insert(
+        alice,
+        Account::new_unchecked(id, vault, storage, code, nonce + ONE, None),
+    );
+    assert!(
+        snapshot_account(&mut node, &ledger, alice, BlockNumber::from(8))
+            .await
+            .unwrap()
+    );
+
+    let diff = account_diff(&ledger, alice, BlockNumber::from(6), BlockNumber::from(9)).unwrap();
+    assert_eq!((diff.from_block, diff.to_block), (3, 8));
+    assert_eq!(diff.nonce.1, diff.nonce.0 + 1);
+    assert_eq!(
+        diff.vault,
+        [Change {
+            key: faucet.to_hex(),
+            before: None,
+            after: Some("10".into()),
+        }]
+    );
+    assert_eq!(diff.storage.len(), 1);
+    assert_eq!(diff.storage[0].key, "slot 0");
+    assert_eq!(diff.storage[0].after, Some(new_key.to_hex()));
+
+    assert!(
+        account_diff(&ledger, alice, BlockNumber::from(4), BlockNumber::from(7))
+            .unwrap()
+            .is_empty()
+    );
+    assert!(account_diff(&ledger, alice, BlockNumber::from(2), BlockNumber::from(9)).is_err());
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{
+    fixtures::{faucet_id, wallet_id},
+    transaction_id,
+};
+use faucet_notes::mint_output_note;
+use miden_client::{Felt, Word};
+use network_faucet::{
+    archive::{create_snapshot, restore_snapshot, Snapshot, SNAPSHOT_MAGIC, STORE_ENTRY},
+    config::Config,
+    deploy::TokenConfig,
+    ledger::Ledger,
+    mint::MintNoteKind,
+    network::network_file,
+    rpc::EndpointConfig,
+    FaucetError,
+};
+
+/// Configuration keeping the files of the host in `dir`.
+fn host(dir: &Path) -> Config {
+    Config {
+        store_path: dir.join("store.sqlite3"),
+        ledger_path: dir.join("ledger.sqlite3"),
+        keystore_path: dir.join("keystore"),
+        ..Config::default()
+    }
+}
+
+/// Fills the files of `config` with a store, a deployment, a pending mint and a key file.
+fn populate(config: &Config) {
+    rusqlite::Connection::open(&config.store_path)
+        .unwrap()
+        .execute_batch(
+            "CREATE TABLE accounts (id TEXT PRIMARY KEY);
+             INSERT INTO accounts VALUES ('0xfaucet');",
+        )
+        .unwrap();
+
+    let ledger = Ledger::open(&config.ledger_path).unwrap();
+    let (faucet, owner) = (faucet_id([1; 15]), wallet_id([2; 15]));
+    let token = TokenConfig {
+        symbol: "TST".into(),
+        ..TokenConfig::default()
+    };
+    ledger
+        .record_deployment(faucet, owner, &token, transaction_id(1))
+        .unwrap();
+    let recipient = wallet_id([3; 15]);
+    let note = mint_output_note(
+        faucet,
+        recipient,
+        10,
+        Word::from([Felt::new(1); 4]),
+        MintNoteKind::P2id,
+    )
+    .unwrap();
+    ledger
+        .record_mint(faucet, recipient, 10, transaction_id(2), &note)
+        .unwrap();
+
+    fs::create_dir_all(&config.keystore_path).unwrap();
+    fs::write(config.keystore_path.join("0xkey"), b"secret key").unwrap();
+}
+
+#[test]
+fn snapshots_restore_the_store_ledger_and_pending_mints() {
+    let (source, target) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+    let config = host(source.path());
+    populate(&config);
+    let archive = source.path().join("host.snapshot");
+
+    let manifest = create_snapshot(&config, &archive, false).unwrap();
+    assert_eq!(manifest.network, "testnet");
+    assert_eq!(manifest.pending_mints, vec![1]);
+    assert_eq!(manifest.keys.len(), 1);
+    assert!(!manifest.includes_keys(), "keys are only referenced");
+    assert!(
+        create_snapshot(&config, &archive, false).is_err(),
+        "archives are not overwritten"
+    );
+
+    let restored = host(target.path());
+    let report = restore_snapshot(&restored, &archive, false).unwrap();
+    assert_eq!(report.manifest, manifest);
+    assert!(report.restored_keys.is_empty());
+    assert_eq!(report.missing_keys, vec!["0xkey".to_string()]);
+
+    let ledger = Ledger::open(&restored.ledger_path).unwrap();
+    let before = Ledger::open(&config.ledger_path).unwrap();
+    assert_eq!(ledger.deployments().unwrap(), before.deployments().unwrap());
+    let pending = ledger.submitted_mints().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].transaction_id, transaction_id(2).to_hex());
+    let account: String = rusqlite::Connection::open(&restored.store_path)
+        .unwrap()
+        .query_row("SELECT id FROM accounts", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(account, "0xfaucet");
+    assert_eq!(
+        fs::read_to_string(network_file(&restored.store_path)).unwrap(),
+        "testnet\n"
+    );
+
+    // Existing files are only replaced on request.
+    assert!(matches!(
+        restore_snapshot(&restored, &archive, false),
+        Err(FaucetError::Snapshot(message)) if message.contains("--force")
+    ));
+    restore_snapshot(&restored, &archive, true).unwrap();
+}
+
+#[test]
+fn snapshots_can_include_keys() {
+    let (source, target) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+    let config = host(source.path());
+    populate(&config);
+    let archive = source.path().join("host.snapshot");
+
+    assert!(create_snapshot(&config, &archive, true)
+        .unwrap()
+        .includes_keys());
+    let restored = host(target.path());
+    let report = restore_snapshot(&restored, &archive, false).unwrap();
+    assert_eq!(report.restored_keys, vec!["0xkey".to_string()]);
+    assert!(report.missing_keys.is_empty());
+    assert_eq!(
+        fs::read(restored.keystore_path.join("0xkey")).unwrap(),
+        b"secret key"
+    );
+}
+
+#[test]
+fn damaged_or_foreign_snapshots_are_refused() {
+    let (source, target) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+    let config = host(source.path());
+    populate(&config);
+    let archive = source.path().join("host.snapshot");
+    create_snapshot(&config, &archive, false).unwrap();
+    let snapshot = Snapshot::read(&archive).unwrap();
+    assert!(snapshot.entry(STORE_ENTRY).is_some());
+
+    let mut devnet = host(target.path());
+    devnet.rpc.endpoint = EndpointConfig::Url("devnet".into());
+    assert!(matches!(
+        restore_snapshot(&devnet, &archive, false),
+        Err(FaucetError::NetworkMismatch { .. })
+    ));
+
+    let mut bytes = fs::read(&archive).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    let damaged = source.path().join("damaged.snapshot");
+    fs::write(&damaged, &bytes).unwrap();
+    assert!(matches!(
+        Snapshot::read(&damaged),
+        Err(FaucetError::Snapshot(message)) if message.contains("digest")
+    ));
+    fs::write(&damaged, &bytes[..SNAPSHOT_MAGIC.len() + 4]).unwrap();
+    assert!(matches!(
+        Snapshot::read(&damaged),
+        Err(FaucetError::Snapshot(message)) if message.contains("truncated")
+    ));
+    assert!(!host(target.path()).store_path.exists());
+}
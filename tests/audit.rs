@@ -0,0 +1,113 @@
+mod common;
+
+use std::{rc::Rc, time::Duration};
+
+use common::{fixtures::faucet_id, transaction_id, MockNode};
+use network_faucet::{
+    audit::{genesis_hash, verify_audit_log, AuditEntry},
+    deploy::deploy_faucet,
+    ledger::Ledger,
+    mint::MintOptions,
+    service::{faucet_service, ServiceConfig},
+    wallet::create_wallet,
+    watcher::BlockWatcher,
+    FaucetError,
+};
+use tokio::{sync::Mutex, task::LocalSet};
+
+const DEPLOY_SCRIPT: &str = include_str!("../masm/deploy.masm");
+
+#[test]
+fn audit_log_is_a_tamper_evident_chain() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ledger.sqlite3");
+    let ledger = Ledger::open(&path).unwrap();
+    let faucet = faucet_id([1; 15]);
+
+    assert_eq!(verify_audit_log(&ledger).unwrap().hash, genesis_hash());
+    let first = ledger
+        .append_audit(
+            &AuditEntry::new("cli:alice", "faucet.pause")
+                .account(faucet)
+                .transaction(transaction_id(1)),
+        )
+        .unwrap();
+    let second = ledger
+        .append_audit(&AuditEntry::new("cli:bob", "schedule.remove").param("schedule_id", 3))
+        .unwrap();
+    assert_eq!(first.prev_hash, genesis_hash());
+    assert_eq!(second.prev_hash, first.hash);
+    assert_eq!(second.params, r#"{"schedule_id":3}"#);
+
+    let head = verify_audit_log(&ledger).unwrap();
+    assert_eq!(head.entries, 2);
+    assert_eq!(head.hash, second.hash);
+
+    // The ledger refuses to rewrite the log.
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    assert!(conn
+        .execute(
+            "UPDATE audit_log SET actor = 'cli:mallory' WHERE id = 1",
+            []
+        )
+        .is_err());
+    assert!(conn
+        .execute("DELETE FROM audit_log WHERE id = 2", [])
+        .is_err());
+
+    // Edits made around the triggers break the chain.
+    conn.execute_batch(
+        "DROP TRIGGER audit_log_no_update;
+         UPDATE audit_log SET actor = 'cli:mallory' WHERE id = 1;",
+    )
+    .unwrap();
+    assert!(matches!(
+        verify_audit_log(&ledger),
+        Err(FaucetError::AuditChainBroken(1, _))
+    ));
+}
+
+#[tokio::test]
+async fn service_mints_are_audited_with_their_actor() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+            let recipient = create_wallet(&mut node).await.unwrap();
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), Duration::from_millis(10)));
+            let (handle, worker) = faucet_service(
+                node,
+                watcher,
+                ledger.clone(),
+                deployment.faucet.id(),
+                &ServiceConfig::default(),
+            );
+            tokio::task::spawn_local(worker.run());
+
+            let ticket = handle
+                .with_actor("rest")
+                .mint(recipient.id(), 50, MintOptions::default())
+                .await
+                .unwrap();
+
+            let entries = ledger.audit_log().unwrap();
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].actor, "rest");
+            assert_eq!(entries[0].action, "mint");
+            assert_eq!(entries[0].account, Some(owner.id().to_hex()));
+            assert_eq!(
+                entries[0].transaction_id,
+                Some(ticket.transaction_id.to_hex())
+            );
+            assert!(verify_audit_log(&ledger).is_ok());
+        })
+        .await;
+}
@@ -0,0 +1,111 @@
+use network_faucet::{
+    access::{Access, Tier},
+    authz::{authorize, authorize_cli, Action, Role},
+    config::Config,
+    FaucetError,
+};
+
+const CONFIG: &str = r#"
+[[api_keys]]
+name = "dashboard"
+key = "viewer-key"
+role = "viewer"
+
+[[api_keys]]
+name = "oncall"
+key = "operator-key"
+role = "operator"
+
+[[api_keys]]
+name = "ops"
+key = "admin-key"
+admin = true
+"#;
+
+#[test]
+fn roles_allow_the_actions_below_them() {
+    authorize("api_key:dashboard", Some(Role::Viewer), Action::View).unwrap();
+    authorize("api_key:oncall", Some(Role::Operator), Action::Pause).unwrap();
+    authorize("api_key:ops", Some(Role::Admin), Action::SetLimits).unwrap();
+    authorize("api_key:ops", Some(Role::Admin), Action::Burn).unwrap();
+
+    assert!(matches!(
+        authorize("api_key:dashboard", Some(Role::Viewer), Action::Reclaim),
+        Err(FaucetError::AccessDenied(_))
+    ));
+    assert!(matches!(
+        authorize("api_key:oncall", Some(Role::Operator), Action::RestoreKey),
+        Err(FaucetError::AccessDenied(_))
+    ));
+    assert!(matches!(
+        authorize("github:octocat", None, Action::View),
+        Err(FaucetError::AccessDenied(_))
+    ));
+}
+
+#[test]
+fn admin_flag_grants_the_admin_role_and_tier() {
+    let config: Config = toml::from_str(CONFIG).unwrap();
+    config.validate().unwrap();
+    let access = Access::new(
+        config.mint.clone(),
+        config.tiers.clone(),
+        config.api_keys.clone(),
+        None,
+    );
+
+    let ops = access.authenticate(Some("Bearer admin-key")).unwrap();
+    assert_eq!(ops.role(), Some(Role::Admin));
+    assert_eq!(ops.tier(), Tier::Admin);
+    let oncall = access.authenticate(Some("Bearer operator-key")).unwrap();
+    assert_eq!(oncall.role(), Some(Role::Operator));
+    assert_eq!(oncall.tier(), Tier::ApiKey);
+
+    let conflicting: Config = toml::from_str(
+        r#"
+[[api_keys]]
+name = "ops"
+key = "admin-key"
+admin = true
+role = "viewer"
+"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        conflicting.validate(),
+        Err(FaucetError::Config(_))
+    ));
+}
+
+#[test]
+fn cli_needs_a_key_once_roles_are_configured() {
+    authorize_cli(&Config::default(), Action::PruneStore).unwrap();
+
+    let mut config: Config = toml::from_str(CONFIG).unwrap();
+    assert!(matches!(
+        authorize_cli(&config, Action::Pause),
+        Err(FaucetError::Unauthorized(_))
+    ));
+
+    config.api_key = Some("guess".into());
+    assert!(matches!(
+        authorize_cli(&config, Action::Pause),
+        Err(FaucetError::Unauthorized(_))
+    ));
+
+    config.api_key = Some("viewer-key".into());
+    assert!(matches!(
+        authorize_cli(&config, Action::Pause),
+        Err(FaucetError::AccessDenied(_))
+    ));
+
+    config.api_key = Some("operator-key".into());
+    authorize_cli(&config, Action::Pause).unwrap();
+    assert!(matches!(
+        authorize_cli(&config, Action::RestoreKey),
+        Err(FaucetError::AccessDenied(_))
+    ));
+
+    config.api_key = Some("admin-key".into());
+    authorize_cli(&config, Action::RestoreKey).unwrap();
+}
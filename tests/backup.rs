@@ -0,0 +1,34 @@
+use network_faucet::{backup::RecoveryBundle, wallet::wallet_from_seed, FaucetError};
+
+#[test]
+fn bundle_restores_the_wallet_key() {
+    let seed = [7_u8; 32];
+    let (account, key) = wallet_from_seed(seed).unwrap();
+    let bundle = RecoveryBundle::new(account.id(), seed).unwrap();
+    assert_eq!(bundle.phrase.split_whitespace().count(), 24);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("owner.json");
+    bundle.write(&path).unwrap();
+    assert!(bundle.write(&path).is_err());
+
+    let (account_id, recovered) = RecoveryBundle::read(&path).unwrap().recover().unwrap();
+    assert_eq!(account_id, account.id());
+    assert_eq!(
+        recovered.public_key().to_commitment(),
+        key.public_key().to_commitment()
+    );
+}
+
+#[test]
+fn bundle_of_another_account_is_rejected() {
+    let (other, _) = wallet_from_seed([1_u8; 32]).unwrap();
+    let bundle = RecoveryBundle::new(other.id(), [2_u8; 32]).unwrap();
+    assert!(matches!(bundle.recover(), Err(FaucetError::Backup(_))));
+
+    let mangled = RecoveryBundle {
+        phrase: "abandon abandon".into(),
+        ..bundle
+    };
+    assert!(matches!(mangled.seed(), Err(FaucetError::Backup(_))));
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::MockNode;
+use miden_objects::block::BlockNumber;
+use network_faucet::{
+    cache::{FaucetCache, FaucetSnapshot},
+    deploy::deploy_faucet,
+    wallet::create_wallet,
+};
+
+const DEPLOY_SCRIPT: &str = include_str!("../masm/deploy.masm");
+
+#[tokio::test]
+async fn faucet_cache_reads_the_account_once_per_block() {
+    let mut node = MockNode::new();
+    let owner = create_wallet(&mut node).await.unwrap();
+    let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+        .await
+        .unwrap();
+    let faucet_id = deployment.faucet.id();
+
+    let snapshot = FaucetSnapshot::read(&mut node, faucet_id).await.unwrap();
+    assert_eq!(snapshot.owner_id, owner.id());
+    assert!(!snapshot.paused);
+
+    let cache = FaucetCache::new(faucet_id);
+    let reads = node.account_reads;
+    let tip = Some(BlockNumber::from(7));
+    assert_eq!(cache.get(&mut node, tip).await.unwrap(), snapshot);
+    assert_eq!(cache.get(&mut node, tip).await.unwrap(), snapshot);
+    assert_eq!(node.account_reads, reads + 1);
+
+    // A sync moves the tip, after which the account is read again.
+    cache
+        .get(&mut node, Some(BlockNumber::from(8)))
+        .await
+        .unwrap();
+    assert_eq!(node.account_reads, reads + 2);
+
+    cache.invalidate();
+    cache
+        .get(&mut node, Some(BlockNumber::from(8)))
+        .await
+        .unwrap();
+    assert_eq!(node.account_reads, reads + 3);
+
+    // Nothing is cached before the tip is known.
+    cache.get(&mut node, None).await.unwrap();
+    cache.get(&mut node, None).await.unwrap();
+    assert_eq!(node.account_reads, reads + 5);
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use common::{
+    fixtures::{faucet_id, wallet_id},
+    transaction_id,
+};
+use faucet_notes::mint_output_note_with;
+use miden_client::{account::AccountId, note::NoteType, Felt, Word};
+use network_faucet::{
+    campaign::{check_campaign_mints, resolve_campaign, validate_campaign_name, CampaignStatus},
+    ledger::Ledger,
+    mint::{AuxData, BatchMint, MintNoteKind, MintOptions, RequestSource},
+    FaucetError,
+};
+
+fn campaign_mint(recipient: AccountId, amount: u64, campaign_id: u32) -> BatchMint {
+    BatchMint {
+        recipient,
+        amount,
+        options: MintOptions {
+            aux: AuxData::new(campaign_id, RequestSource::Rest),
+            ..MintOptions::default()
+        },
+    }
+}
+
+fn record(ledger: &Ledger, faucet: AccountId, mint: &BatchMint, n: u64) {
+    let note = mint_output_note_with(
+        faucet,
+        mint.recipient,
+        mint.amount,
+        Word::from([Felt::new(n); 4]),
+        MintNoteKind::P2id,
+        NoteType::Private,
+        mint.options.aux,
+    )
+    .unwrap();
+    ledger
+        .record_mint(
+            faucet,
+            mint.recipient,
+            mint.amount,
+            transaction_id(n),
+            &note,
+        )
+        .unwrap();
+}
+
+#[test]
+fn campaign_mints_stay_within_budget_and_per_user_limit() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let faucet = faucet_id([1; 15]);
+    let (alice, bob) = (wallet_id([2; 15]), wallet_id([3; 15]));
+    let id = ledger
+        .add_campaign("hackathon", faucet, 100, Some(40), None, None)
+        .unwrap();
+    let now = 1_750_000_000;
+
+    let first = campaign_mint(alice, 30, id);
+    check_campaign_mints(&ledger, faucet, std::slice::from_ref(&first), now).unwrap();
+    record(&ledger, faucet, &first, 1);
+
+    assert!(matches!(
+        check_campaign_mints(&ledger, faucet, &[campaign_mint(alice, 20, id)], now),
+        Err(FaucetError::CampaignUserLimit { remaining: 10, .. })
+    ));
+    // Mints of a batch count together.
+    let batch = [campaign_mint(bob, 30, id), campaign_mint(bob, 20, id)];
+    assert!(matches!(
+        check_campaign_mints(&ledger, faucet, &batch, now),
+        Err(FaucetError::CampaignUserLimit { remaining: 40, .. })
+    ));
+    for (n, mint) in [campaign_mint(bob, 40, id), campaign_mint(alice, 10, id)]
+        .iter()
+        .enumerate()
+    {
+        check_campaign_mints(&ledger, faucet, std::slice::from_ref(mint), now).unwrap();
+        record(&ledger, faucet, mint, n as u64 + 2);
+    }
+    assert!(matches!(
+        check_campaign_mints(
+            &ledger,
+            faucet,
+            &[campaign_mint(wallet_id([4; 15]), 30, id)],
+            now
+        ),
+        Err(FaucetError::CampaignBudgetExhausted { remaining: 20, .. })
+    ));
+
+    // Failed mints give their tokens back.
+    ledger.mark_failed(transaction_id(1), "discarded").unwrap();
+    assert_eq!(ledger.campaign_spent(id, None).unwrap(), 50);
+    check_campaign_mints(&ledger, faucet, &[campaign_mint(alice, 30, id)], now).unwrap();
+
+    let stats = ledger.campaign_stats(Some(faucet)).unwrap();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].name, "hackathon");
+    assert_eq!(stats[0].mints, 3);
+    assert_eq!(stats[0].unique_recipients, 2);
+    assert_eq!(stats[0].spent_amount, 50);
+    // Mints without campaign are not checked.
+    check_campaign_mints(&ledger, faucet, &[campaign_mint(alice, 500, 0)], now).unwrap();
+}
+
+#[test]
+fn campaigns_only_mint_within_their_window_and_for_their_faucet() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let faucet = faucet_id([1; 15]);
+    let alice = wallet_id([2; 15]);
+    let id = ledger
+        .add_campaign("onboarding", faucet, 100, None, Some(1_000), Some(2_000))
+        .unwrap();
+    let mint = [campaign_mint(alice, 10, id)];
+
+    let campaign = resolve_campaign(&ledger, "onboarding").unwrap();
+    assert_eq!(campaign.id, id);
+    assert_eq!(campaign.status(999), CampaignStatus::Scheduled);
+    assert_eq!(campaign.status(2_000), CampaignStatus::Ended);
+    assert!(matches!(
+        check_campaign_mints(&ledger, faucet, &mint, 999),
+        Err(FaucetError::CampaignInactive(..))
+    ));
+    check_campaign_mints(&ledger, faucet, &mint, 1_500).unwrap();
+    assert!(matches!(
+        check_campaign_mints(&ledger, faucet_id([9; 15]), &mint, 1_500),
+        Err(FaucetError::InvalidCampaign(..))
+    ));
+
+    assert!(ledger.close_campaign(id, 1_600).unwrap());
+    assert!(!ledger.close_campaign(id, 1_700).unwrap());
+    assert_eq!(
+        resolve_campaign(&ledger, &id.to_string())
+            .unwrap()
+            .status(1_700),
+        CampaignStatus::Closed
+    );
+    assert!(matches!(
+        check_campaign_mints(&ledger, faucet, &mint, 1_700),
+        Err(FaucetError::CampaignInactive(..))
+    ));
+
+    assert!(matches!(
+        check_campaign_mints(&ledger, faucet, &[campaign_mint(alice, 10, id + 1)], 1_500),
+        Err(FaucetError::InvalidCampaign(..))
+    ));
+    assert!(ledger
+        .add_campaign("onboarding", faucet, 5, None, None, None)
+        .is_err());
+    assert!(validate_campaign_name("partners-q3").is_ok());
+    assert!(validate_campaign_name("42").is_err());
+    assert!(validate_campaign_name("two words").is_err());
+}
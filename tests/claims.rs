@@ -0,0 +1,233 @@
+mod common;
+
+use common::{
+    fixtures::{faucet_id, fungible_asset, wallet_id},
+    transaction_id, MockNode,
+};
+use std::{rc::Rc, time::Duration};
+
+use faucet_notes::mint_output_note;
+use miden_client::{note::NoteType, Felt, Word};
+use miden_objects::block::BlockNumber;
+use network_faucet::{
+    indexer::index_claims,
+    ledger::Ledger,
+    mint::{create_p2id_note_exact, MintNoteKind},
+    reclaim::reclaim_expired,
+    rest::{MintStage, MintStatusResponse},
+    watcher::{wait_for_note_consumption, BlockWatcher},
+    FaucetError,
+};
+use tokio::{sync::Mutex, task::LocalSet};
+
+#[tokio::test]
+async fn indexer_marks_consumed_mints_as_delivered() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let mut node = MockNode::new();
+
+    let faucet = faucet_id([1; 15]);
+    let mut notes = Vec::new();
+    for (i, recipient) in [wallet_id([2; 15]), wallet_id([3; 15])]
+        .into_iter()
+        .enumerate()
+    {
+        let note = create_p2id_note_exact(
+            faucet,
+            recipient,
+            vec![fungible_asset(faucet, 50)],
+            NoteType::Private,
+            Felt::new(27),
+            Word::from([Felt::new(i as u64); 4]),
+        )
+        .unwrap();
+        let tx_id = transaction_id(i as u64);
+
+        ledger
+            .record_mint(faucet, recipient, 50, tx_id, &note)
+            .unwrap();
+        ledger.mark_committed(tx_id, BlockNumber::from(2)).unwrap();
+        notes.push(note);
+    }
+
+    node.consumed.push((notes[0].nullifier(), 5));
+    let claimed = index_claims(&mut node, &ledger, None).await.unwrap();
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(claimed[0].note_id, notes[0].id().to_hex());
+    assert_eq!(claimed[0].claim_block, Some(5));
+    // Already claimed notes are not reported twice.
+    assert!(index_claims(&mut node, &ledger, None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let stats = ledger.stats(Some(faucet)).unwrap();
+    assert_eq!(stats.submitted, 2);
+    assert_eq!(stats.committed, 2);
+    assert_eq!(stats.delivered, 1);
+    assert_eq!(stats.unclaimed, 1);
+    assert_eq!(stats.minted_amount, 100);
+    assert_eq!(stats.delivered_amount, 50);
+
+    let unclaimed = ledger.unclaimed_mints().unwrap();
+    assert_eq!(unclaimed.len(), 1);
+    assert_eq!(unclaimed[0].note_id, notes[1].id().to_hex());
+}
+
+#[tokio::test]
+async fn expired_mints_are_reclaimed_by_the_faucet() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+            let faucet = faucet_id([1; 15]);
+
+            let mut ids = Vec::new();
+            for (i, reclaim_height) in [3, 1_000].into_iter().enumerate() {
+                let note = mint_output_note(
+                    faucet,
+                    wallet_id([2; 15]),
+                    50,
+                    Word::from([Felt::new(i as u64); 4]),
+                    MintNoteKind::P2ide {
+                        reclaim_height: BlockNumber::from(reclaim_height),
+                    },
+                )
+                .unwrap();
+                let tx_id = transaction_id(100 + i as u64);
+                ids.push(
+                    ledger
+                        .record_mint(faucet, wallet_id([2; 15]), 50, tx_id, &note)
+                        .unwrap(),
+                );
+                ledger.mark_committed(tx_id, BlockNumber::from(2)).unwrap();
+            }
+
+            let mut node = MockNode::new();
+            node.block = 5;
+            let node = Rc::new(Mutex::new(node));
+            let watcher = BlockWatcher::spawn(node.clone(), Duration::from_millis(10));
+
+            let report = reclaim_expired(&node, &watcher, &ledger, faucet)
+                .await
+                .unwrap();
+            assert!(report.failed.is_empty());
+            assert_eq!(report.reclaimed.len(), 1);
+            assert_eq!(report.reclaimed[0].id, ids[0]);
+            assert_eq!(report.reclaimed_amount, 50);
+            assert_eq!(node.lock().await.submitted_by(faucet).len(), 1);
+
+            let stats = ledger.stats(Some(faucet)).unwrap();
+            assert_eq!(stats.reclaimed, 1);
+            assert_eq!(stats.reclaimed_amount, 50);
+            assert_eq!(stats.unclaimed, 1);
+        })
+        .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn waits_for_the_note_to_be_consumed() {
+    LocalSet::new()
+        .run_until(async {
+            let note = mint_output_note(
+                faucet_id([1; 15]),
+                wallet_id([2; 15]),
+                50,
+                Word::default(),
+                MintNoteKind::P2id,
+            )
+            .unwrap();
+            let nullifier = note.nullifier();
+
+            let mut node = MockNode::new();
+            node.consumed.push((nullifier, 7));
+            let node = Rc::new(Mutex::new(node));
+            let watcher = BlockWatcher::spawn(node.clone(), Duration::from_secs(1));
+            let timeout = Duration::from_secs(30);
+
+            let consumed_at = wait_for_note_consumption(
+                &node,
+                &watcher,
+                nullifier,
+                BlockNumber::from(2),
+                timeout,
+            )
+            .await
+            .unwrap();
+            assert_eq!(consumed_at, BlockNumber::from(7));
+
+            node.lock().await.consumed.clear();
+            let result = wait_for_note_consumption(
+                &node,
+                &watcher,
+                nullifier,
+                BlockNumber::from(2),
+                timeout,
+            )
+            .await;
+            assert!(matches!(
+                result,
+                Err(FaucetError::ConsumeTimeout {
+                    timeout_ms: 30_000,
+                    ..
+                })
+            ));
+        })
+        .await;
+}
+
+#[test]
+fn mint_lifecycle_follows_the_ledger_and_indexer() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let faucet = faucet_id([1; 15]);
+    let recipient = wallet_id([2; 15]);
+    let note = create_p2id_note_exact(
+        faucet,
+        recipient,
+        vec![fungible_asset(faucet, 50)],
+        NoteType::Public,
+        Felt::new(27),
+        Word::from([Felt::new(1); 4]),
+    )
+    .unwrap();
+    let mint_id = ledger
+        .record_mint(faucet, recipient, 50, transaction_id(1), &note)
+        .unwrap();
+    let lifecycle = || MintStatusResponse::from(ledger.get_mint(mint_id).unwrap().unwrap());
+
+    let submitted = lifecycle();
+    assert!(matches!(
+        submitted.stages[..],
+        [MintStage::Submitted { .. }]
+    ));
+    // Mints served from the queue of the service start with the time they were queued.
+    ledger.set_queued_at(mint_id, 1).unwrap();
+    let submitted = lifecycle();
+    assert!(matches!(
+        submitted.stages[..],
+        [MintStage::Queued { at: 1 }, MintStage::Submitted { .. }]
+    ));
+    assert_eq!(
+        submitted.waiting_for.as_deref(),
+        Some("commitment of the mint transaction")
+    );
+
+    ledger
+        .mark_committed(transaction_id(1), BlockNumber::from(4))
+        .unwrap();
+    let committed = lifecycle();
+    assert_eq!(committed.stages[2], MintStage::Committed { block_num: 4 });
+    assert_eq!(
+        committed.waiting_for.as_deref(),
+        Some("claim by the recipient")
+    );
+
+    ledger
+        .mark_claimed(&note.nullifier().to_hex(), BlockNumber::from(9))
+        .unwrap();
+    let claimed = lifecycle();
+    assert_eq!(claimed.stages.len(), 4);
+    assert_eq!(claimed.stages[3], MintStage::Claimed { block_num: 9 });
+    assert_eq!(claimed.waiting_for, None);
+}
@@ -0,0 +1,60 @@
+//! Account, asset and serial-number fixtures shared by the test suites.
+
+use miden_client::{
+    account::{AccountId, AccountStorageMode, AccountType},
+    asset::{Asset, FungibleAsset},
+    note::NoteType,
+    Felt, Word,
+};
+use miden_objects::account::AccountIdVersion;
+use proptest::prelude::*;
+
+/// A public regular account ID derived from `seed`.
+pub fn wallet_id(seed: [u8; 15]) -> AccountId {
+    AccountId::dummy(
+        seed,
+        AccountIdVersion::Version0,
+        AccountType::RegularAccountUpdatableCode,
+        AccountStorageMode::Public,
+    )
+}
+
+/// A network regular account ID derived from `seed`, whose notes the network consumes.
+pub fn network_wallet_id(seed: [u8; 15]) -> AccountId {
+    AccountId::dummy(
+        seed,
+        AccountIdVersion::Version0,
+        AccountType::RegularAccountUpdatableCode,
+        AccountStorageMode::Network,
+    )
+}
+
+/// A network fungible faucet ID derived from `seed`.
+pub fn faucet_id(seed: [u8; 15]) -> AccountId {
+    AccountId::dummy(
+        seed,
+        AccountIdVersion::Version0,
+        AccountType::FungibleFaucet,
+        AccountStorageMode::Network,
+    )
+}
+
+pub fn fungible_asset(faucet: AccountId, amount: u64) -> Asset {
+    FungibleAsset::new(faucet, amount).unwrap().into()
+}
+
+pub fn arb_wallet_id() -> impl Strategy<Value = AccountId> {
+    any::<[u8; 15]>().prop_map(wallet_id)
+}
+
+pub fn arb_faucet_id() -> impl Strategy<Value = AccountId> {
+    any::<[u8; 15]>().prop_map(faucet_id)
+}
+
+pub fn arb_serial_num() -> impl Strategy<Value = Word> {
+    any::<[u32; 4]>().prop_map(|limbs| Word::from(limbs.map(|limb| Felt::new(limb.into()))))
+}
+
+pub fn arb_note_type() -> impl Strategy<Value = NoteType> {
+    prop_oneof![Just(NoteType::Public), Just(NoteType::Private)]
+}
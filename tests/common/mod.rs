@@ -0,0 +1,374 @@
+//! In-memory [`FaucetNode`] used by the integration tests.
+
+#![allow(dead_code)]
+
+pub mod fixtures;
+
+use std::collections::BTreeMap;
+
+use miden_client::{
+    account::{Account, AccountId},
+    auth::AuthSecretKey,
+    crypto::RpoRandomCoin,
+    note::{Note, NoteFile, NoteId, NoteInclusionProof, Nullifier},
+    transaction::{TransactionId, TransactionRequest, TransactionScript},
+    ClientRng, Felt, Word,
+};
+use miden_lib::utils::ScriptBuilder;
+use miden_objects::block::BlockNumber;
+use network_faucet::{
+    client::seeded_rng,
+    node::{FaucetNode, StoredNote, TransactionCost, TxState},
+    rpc::RpcCall,
+    FaucetError,
+};
+
+/// Deterministic transaction ID for tests.
+pub fn transaction_id(n: u64) -> TransactionId {
+    TransactionId::new(
+        Word::from([Felt::new(n); 4]),
+        Word::default(),
+        Word::default(),
+        Word::default(),
+    )
+}
+
+/// A transaction accepted by [`MockNode::submit_transaction`].
+pub struct Submitted {
+    pub account_id: AccountId,
+    pub request: TransactionRequest,
+    pub transaction_id: TransactionId,
+    pub commit_block: u32,
+}
+
+/// Fake node that commits every submitted transaction `commit_delay` blocks after submission.
+///
+/// Each call to [`FaucetNode::sync_state`] advances the chain by one block. Failures can be queued
+/// for submissions and syncs to exercise the error paths of the flows.
+pub struct MockNode {
+    rng: ClientRng,
+    pub block: u32,
+    /// Blocks the chain is ahead of the last sync, reported by [`FaucetNode::chain_tip`].
+    pub chain_lead: u32,
+    pub commit_delay: u32,
+    pub accounts: BTreeMap<AccountId, Account>,
+    /// Public accounts on chain, tracked once imported with [`FaucetNode::import_account`] and
+    /// returned by [`FaucetNode::fetch_account`].
+    pub chain_accounts: BTreeMap<AccountId, Account>,
+    /// Calls of [`FaucetNode::get_account`].
+    pub account_reads: u32,
+    pub keys: Vec<AuthSecretKey>,
+    pub submitted: Vec<Submitted>,
+    /// Accounts of the transactions passed to [`FaucetNode::execute_transaction`].
+    pub executed: Vec<AccountId>,
+    /// Cost reported for every executed or submitted transaction.
+    pub cost: TransactionCost,
+    pub discarded: Vec<TransactionId>,
+    /// Nullifiers reported as consumed, with the block that consumed them.
+    pub consumed: Vec<(Nullifier, u32)>,
+    /// Inclusion proofs of the notes committed on chain.
+    pub inclusion_proofs: Vec<(NoteId, NoteInclusionProof)>,
+    /// Note tree roots of the blocks known to the node.
+    pub note_roots: BTreeMap<u32, Word>,
+    /// Input notes of the store.
+    pub stored_notes: Vec<StoredNote>,
+    pub failing_submits: u32,
+    pub failing_syncs: u32,
+    pub failing_state_queries: u32,
+    /// Calls of [`FaucetNode::transaction_state`] and [`FaucetNode::transaction_states`].
+    pub state_queries: u32,
+    pub batched_state_queries: u32,
+}
+
+impl MockNode {
+    /// Node whose RNG is seeded like a client run with `--seed`.
+    pub fn seeded(seed: Word) -> Self {
+        Self {
+            rng: seeded_rng(seed),
+            ..Self::new()
+        }
+    }
+
+    pub fn new() -> Self {
+        Self {
+            rng: ClientRng::new(Box::new(RpoRandomCoin::new(Word::default()))),
+            block: 0,
+            chain_lead: 0,
+            commit_delay: 2,
+            accounts: BTreeMap::new(),
+            chain_accounts: BTreeMap::new(),
+            account_reads: 0,
+            keys: Vec::new(),
+            submitted: Vec::new(),
+            executed: Vec::new(),
+            cost: TransactionCost::default(),
+            discarded: Vec::new(),
+            consumed: Vec::new(),
+            inclusion_proofs: Vec::new(),
+            note_roots: BTreeMap::new(),
+            stored_notes: Vec::new(),
+            failing_submits: 0,
+            failing_syncs: 0,
+            failing_state_queries: 0,
+            state_queries: 0,
+            batched_state_queries: 0,
+        }
+    }
+
+    fn fail_state_query(&mut self) -> Result<(), FaucetError> {
+        if self.failing_state_queries > 0 {
+            self.failing_state_queries -= 1;
+            return Err(FaucetError::RequestTimeout {
+                call: RpcCall::GetTransactions,
+                timeout_ms: 0,
+            });
+        }
+        Ok(())
+    }
+
+    fn state_of(&self, transaction_id: TransactionId) -> Option<TxState> {
+        if self.discarded.contains(&transaction_id) {
+            return Some(TxState::Discarded("discarded by mock".into()));
+        }
+
+        self.submitted
+            .iter()
+            .find(|tx| tx.transaction_id == transaction_id)
+            .map(|tx| {
+                if tx.commit_block <= self.block {
+                    TxState::Committed(BlockNumber::from(tx.commit_block))
+                } else {
+                    TxState::Pending
+                }
+            })
+    }
+
+    pub fn submitted_by(&self, account_id: AccountId) -> Vec<&Submitted> {
+        self.submitted
+            .iter()
+            .filter(|tx| tx.account_id == account_id)
+            .collect()
+    }
+}
+
+impl FaucetNode for MockNode {
+    fn rng(&mut self) -> &mut ClientRng {
+        &mut self.rng
+    }
+
+    async fn sync_state(&mut self) -> Result<BlockNumber, FaucetError> {
+        if self.failing_syncs > 0 {
+            self.failing_syncs -= 1;
+            return Err(FaucetError::RequestTimeout {
+                call: RpcCall::SyncState,
+                timeout_ms: 0,
+            });
+        }
+
+        self.block += 1;
+        Ok(BlockNumber::from(self.block))
+    }
+
+    async fn chain_tip(&mut self) -> Result<BlockNumber, FaucetError> {
+        Ok(BlockNumber::from(self.block + self.chain_lead))
+    }
+
+    async fn add_account(&mut self, account: &Account) -> Result<(), FaucetError> {
+        self.accounts.insert(account.id(), account.clone());
+        Ok(())
+    }
+
+    async fn add_key(&mut self, key: AuthSecretKey) -> Result<(), FaucetError> {
+        self.keys.push(key);
+        Ok(())
+    }
+
+    async fn secret_key(&mut self, pub_key: Word) -> Result<Option<AuthSecretKey>, FaucetError> {
+        Ok(self
+            .keys
+            .iter()
+            .find(|key| {
+                matches!(key, AuthSecretKey::RpoFalcon512(key)
+                    if key.public_key().to_commitment() == pub_key)
+            })
+            .cloned())
+    }
+
+    async fn get_account(&mut self, account_id: AccountId) -> Result<Option<Account>, FaucetError> {
+        self.account_reads += 1;
+        Ok(self.accounts.get(&account_id).cloned())
+    }
+
+    async fn tracked_accounts(&mut self) -> Result<Vec<AccountId>, FaucetError> {
+        Ok(self.accounts.keys().copied().collect())
+    }
+
+    async fn import_account(&mut self, account_id: AccountId) -> Result<(), FaucetError> {
+        let account = self
+            .chain_accounts
+            .get(&account_id)
+            .cloned()
+            .ok_or(FaucetError::AccountNotFound(account_id))?;
+        self.accounts.insert(account_id, account);
+        Ok(())
+    }
+
+    async fn fetch_account(
+        &mut self,
+        account_id: AccountId,
+    ) -> Result<Option<Account>, FaucetError> {
+        Ok(self.chain_accounts.get(&account_id).cloned())
+    }
+
+    async fn import_note(&mut self, note_file: NoteFile) -> Result<NoteId, FaucetError> {
+        let (note, authenticated) = match note_file {
+            NoteFile::NoteWithProof(note, _) => (note, true),
+            _ => {
+                return Err(FaucetError::InputNote(
+                    "the mock store only imports notes with proofs".into(),
+                ))
+            }
+        };
+        let note_id = note.id();
+        self.stored_notes.push(StoredNote {
+            note,
+            authenticated,
+        });
+        Ok(note_id)
+    }
+
+    async fn stored_note(&mut self, note_id: NoteId) -> Result<Option<StoredNote>, FaucetError> {
+        Ok(self
+            .stored_notes
+            .iter()
+            .find(|stored| stored.note.id() == note_id)
+            .cloned())
+    }
+
+    /// Every stored note is consumable by the account its P2ID inputs target.
+    async fn consumable_notes(
+        &mut self,
+        account_id: AccountId,
+    ) -> Result<Vec<StoredNote>, FaucetError> {
+        Ok(self
+            .stored_notes
+            .iter()
+            .filter(|stored| {
+                stored.note.recipient().inputs().values()[..2]
+                    == [account_id.suffix(), account_id.prefix().as_felt()]
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn compile_tx_script(&self, code: &str) -> Result<TransactionScript, FaucetError> {
+        ScriptBuilder::new(true)
+            .compile_tx_script(code)
+            .map_err(|err| FaucetError::Script(err.to_string()))
+    }
+
+    async fn execute_transaction(
+        &mut self,
+        account_id: AccountId,
+        _request: TransactionRequest,
+    ) -> Result<TransactionCost, FaucetError> {
+        self.executed.push(account_id);
+        Ok(self.cost)
+    }
+
+    async fn submit_transaction(
+        &mut self,
+        account_id: AccountId,
+        request: TransactionRequest,
+    ) -> Result<TransactionId, FaucetError> {
+        if self.failing_submits > 0 {
+            self.failing_submits -= 1;
+            return Err(FaucetError::RequestTimeout {
+                call: RpcCall::SubmitTransaction,
+                timeout_ms: 0,
+            });
+        }
+
+        let transaction_id = transaction_id(self.submitted.len() as u64 + 1);
+        self.submitted.push(Submitted {
+            account_id,
+            request,
+            transaction_id,
+            commit_block: self.block + self.commit_delay,
+        });
+
+        Ok(transaction_id)
+    }
+
+    fn take_transaction_cost(&mut self, transaction_id: TransactionId) -> Option<TransactionCost> {
+        self.submitted
+            .iter()
+            .any(|tx| tx.transaction_id == transaction_id)
+            .then_some(self.cost)
+    }
+
+    async fn transaction_state(
+        &mut self,
+        transaction_id: TransactionId,
+    ) -> Result<Option<TxState>, FaucetError> {
+        self.state_queries += 1;
+        self.fail_state_query()?;
+        Ok(self.state_of(transaction_id))
+    }
+
+    async fn transaction_states(
+        &mut self,
+        transaction_ids: &[TransactionId],
+    ) -> Result<BTreeMap<TransactionId, TxState>, FaucetError> {
+        self.batched_state_queries += 1;
+        self.fail_state_query()?;
+        Ok(transaction_ids
+            .iter()
+            .filter_map(|id| self.state_of(*id).map(|state| (*id, state)))
+            .collect())
+    }
+
+    async fn transaction_output_notes(
+        &mut self,
+        transaction_id: TransactionId,
+    ) -> Result<Option<Vec<Note>>, FaucetError> {
+        Ok(self
+            .submitted
+            .iter()
+            .find(|tx| tx.transaction_id == transaction_id)
+            .map(|tx| tx.request.expected_output_own_notes()))
+    }
+
+    async fn consumed_nullifiers(
+        &mut self,
+        prefixes: &[u16],
+        from_block: BlockNumber,
+    ) -> Result<Vec<(Nullifier, BlockNumber)>, FaucetError> {
+        Ok(self
+            .consumed
+            .iter()
+            .filter(|(nullifier, block)| {
+                prefixes.contains(&nullifier.prefix()) && *block >= from_block.as_u32()
+            })
+            .map(|(nullifier, block)| (*nullifier, BlockNumber::from(*block)))
+            .collect())
+    }
+
+    async fn note_inclusion_proof(
+        &mut self,
+        note_id: NoteId,
+    ) -> Result<Option<NoteInclusionProof>, FaucetError> {
+        Ok(self
+            .inclusion_proofs
+            .iter()
+            .find(|(id, _)| *id == note_id)
+            .map(|(_, proof)| proof.clone()))
+    }
+
+    async fn block_note_root(
+        &mut self,
+        block_num: BlockNumber,
+    ) -> Result<Option<Word>, FaucetError> {
+        Ok(self.note_roots.get(&block_num.as_u32()).copied())
+    }
+}
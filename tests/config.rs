@@ -0,0 +1,108 @@
+use network_faucet::{config::Config, deploy::check_token_parameters, FaucetError};
+
+fn invalid(toml: &str) -> String {
+    let config: Config = toml::from_str(toml).unwrap();
+    match config.validate() {
+        Err(FaucetError::Config(message)) => message,
+        other => panic!("expected a configuration error, got {other:?}"),
+    }
+}
+
+#[test]
+fn defaults_are_valid() {
+    Config::default().validate().unwrap();
+}
+
+#[test]
+fn out_of_range_values_are_rejected() {
+    assert!(invalid("[sync]\nparallelism = 0").contains("sync.parallelism"));
+    assert!(invalid("[poll]\nsync_interval_ms = 0").contains("poll.sync_interval_ms"));
+    assert!(invalid("[service]\nqueue_capacity = 0").contains("queue_capacity"));
+    assert!(invalid("[rpc.retries]\nper_call = { sync_state = 0 }").contains("sync_state"));
+    assert!(invalid("[webhook]\nurl = \"ftp://example.com\"").contains("webhook.url"));
+    assert!(invalid("[mint]\ndefault_amount = 5000").contains("mint.default_amount"));
+    assert!(invalid("[mint]\nmin_amount = 10\nmax_amount = 5").contains("mint.min_amount"));
+    assert!(invalid("store_path = \"./missing/store.sqlite3\"").contains("store_path"));
+    assert!(
+        invalid("[service]\ngrpc_addr = \"127.0.0.1:8080\"\nrest_addr = \"127.0.0.1:8080\"")
+            .contains("both use")
+    );
+
+    let config: Config = toml::from_str("[service]\nfaucet_id = \"0x12\"").unwrap();
+    assert!(matches!(
+        config.validate(),
+        Err(FaucetError::InvalidAccountId(..))
+    ));
+}
+
+#[test]
+fn mint_amounts_are_bounded() {
+    let config: Config = toml::from_str("[mint]\nmin_amount = 10\nmax_amount = 100").unwrap();
+    assert_eq!(config.mint.resolve(None).unwrap(), 50);
+    assert_eq!(config.mint.resolve(Some(100)).unwrap(), 100);
+    assert!(matches!(
+        config.mint.resolve(Some(101)),
+        Err(FaucetError::AmountOutOfRange {
+            amount: 101,
+            min: 10,
+            max: 100
+        })
+    ));
+    assert!(matches!(
+        config.mint.resolve(Some(0)),
+        Err(FaucetError::AmountOutOfRange { .. })
+    ));
+
+    let config: Config =
+        toml::from_str("[mint]\nmin_amount = 10\nmax_amount = 100\nclamp = true").unwrap();
+    assert_eq!(config.mint.resolve(Some(5)).unwrap(), 10);
+    assert_eq!(config.mint.resolve(Some(500)).unwrap(), 100);
+}
+
+#[test]
+fn token_parameters_stay_within_asset_limits() {
+    check_token_parameters(8, 1_000_000).unwrap();
+    assert!(check_token_parameters(13, 1_000_000).is_err());
+    assert!(check_token_parameters(8, 0).is_err());
+    assert!(check_token_parameters(8, u64::MAX).is_err());
+}
+
+#[test]
+fn token_section_is_validated() {
+    let config: Config =
+        toml::from_str("[token]\nsymbol = \"TST\"\ndecimals = 6\nmax_supply = 5000").unwrap();
+    config.token.validate().unwrap();
+    assert_eq!(config.token.symbol, "TST");
+    assert_eq!(config.token.max_supply, 5000);
+    assert_eq!(Config::default().token.symbol, "MDE");
+
+    for token in [
+        "symbol = \"lower\"",
+        "symbol = \"TOOLONGSYM\"",
+        "decimals = 13",
+        "max_supply = 0",
+    ] {
+        let config: Config = toml::from_str(&format!("[token]\n{token}")).unwrap();
+        assert!(config.token.validate().is_err(), "{token}");
+    }
+}
+
+#[test]
+fn effective_configuration_round_trips() {
+    let mut config: Config = toml::from_str(
+        "store_path = \"data/store.sqlite3\"\n\
+         [rpc]\nendpoint = { scheme = \"http\", host = \"10.0.0.12\", port = 57291 }\n\
+         [rpc.retries]\nper_call = { submit_transaction = 1 }\n\
+         [sync]\nnote_tags = [3]",
+    )
+    .unwrap();
+    config.resolve_paths().unwrap();
+    assert!(config.store_path.is_absolute());
+    assert!(config.store_path.ends_with("data/store.sqlite3"));
+
+    let document = toml::to_string_pretty(&config).unwrap();
+    let parsed: Config = toml::from_str(&document).unwrap();
+    assert_eq!(parsed.store_path, config.store_path);
+    assert_eq!(parsed.sync.note_tags, [3]);
+    assert_eq!(parsed.rpc.retries.per_call, config.rpc.retries.per_call);
+}
@@ -0,0 +1,57 @@
+use network_faucet::doctor::{
+    check_clock, check_keystore, check_store, disk_space_check, CheckStatus, CRITICAL_DISK_SPACE,
+    LOW_DISK_SPACE,
+};
+use rusqlite::Connection;
+
+#[test]
+fn clock_skew_beyond_a_minute_warns() {
+    let now = 1_700_000_000;
+    assert_eq!(check_clock(now - 5, now).status, CheckStatus::Ok);
+    assert_eq!(check_clock(now + 120, now).status, CheckStatus::Warn);
+    let stale = check_clock(now - 600, now);
+    assert_eq!(stale.status, CheckStatus::Warn);
+    assert!(stale.detail.contains("600s"));
+}
+
+#[test]
+fn missing_and_foreign_stores_are_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("store.sqlite3");
+    assert_eq!(check_store(&path).status, CheckStatus::Warn);
+
+    Connection::open(&path)
+        .unwrap()
+        .execute_batch("CREATE TABLE accounts (id TEXT PRIMARY KEY);")
+        .unwrap();
+    let check = check_store(&path);
+    assert_eq!(check.status, CheckStatus::Fail);
+    assert!(check.fix.is_some());
+}
+
+#[test]
+fn keystore_needs_readable_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let keystore = dir.path().join("keystore");
+    assert_eq!(check_keystore(&keystore).status, CheckStatus::Warn);
+
+    std::fs::create_dir(&keystore).unwrap();
+    assert_eq!(check_keystore(&keystore).status, CheckStatus::Warn);
+
+    std::fs::write(keystore.join("key"), b"secret").unwrap();
+    assert_eq!(check_keystore(&keystore).status, CheckStatus::Ok);
+}
+
+#[test]
+fn disk_space_thresholds() {
+    let dir = std::path::Path::new(".");
+    assert_eq!(disk_space_check(dir, 10 << 30).status, CheckStatus::Ok);
+    assert_eq!(
+        disk_space_check(dir, LOW_DISK_SPACE - 1).status,
+        CheckStatus::Warn
+    );
+    assert_eq!(
+        disk_space_check(dir, CRITICAL_DISK_SPACE - 1).status,
+        CheckStatus::Fail
+    );
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::{
+    fixtures::{faucet_id, wallet_id},
+    transaction_id, MockNode,
+};
+use faucet_notes::mint_output_note;
+use miden_client::{Felt, Word};
+use miden_objects::block::BlockNumber;
+use network_faucet::{
+    email::{claim_link, parse_email, NoteMailer, SmtpConfig},
+    ledger::{Ledger, MintRecord},
+    mint::MintNoteKind,
+    note_file::mint_note_file,
+    FaucetError,
+};
+
+fn smtp_config() -> SmtpConfig {
+    toml::from_str(
+        r#"
+        host = "localhost"
+        from = "Faucet <faucet@example.com>"
+        starttls = false
+        claim_url = "https://wallet.example.com/claim/{mint_id}/{note_id}"
+        "#,
+    )
+    .unwrap()
+}
+
+fn committed_mint(ledger: &Ledger) -> MintRecord {
+    let (faucet, recipient) = (faucet_id([1; 15]), wallet_id([2; 15]));
+    let note = mint_output_note(
+        faucet,
+        recipient,
+        50,
+        Word::from([Felt::new(9); 4]),
+        MintNoteKind::P2id,
+    )
+    .unwrap();
+    let tx_id = transaction_id(1);
+    let mint_id = ledger
+        .record_mint(faucet, recipient, 50, tx_id, &note)
+        .unwrap();
+    ledger.mark_committed(tx_id, BlockNumber::from(4)).unwrap();
+    ledger.get_mint(mint_id).unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn note_email_attaches_the_note_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let record = committed_mint(&ledger);
+    let note_file = mint_note_file(&mut MockNode::new(), &record).await.unwrap();
+
+    let mailer = NoteMailer::new(smtp_config()).unwrap();
+    let to = parse_email("alice@example.com").unwrap();
+    let email = String::from_utf8(
+        mailer
+            .note_email(&record, &note_file, &to)
+            .unwrap()
+            .formatted(),
+    )
+    .unwrap();
+
+    assert!(email.contains("To: alice@example.com"));
+    assert!(email.contains("Subject: Your 50 tokens from the faucet"));
+    assert!(email.contains(&format!("mint-{}.mno", record.id)));
+    assert_eq!(
+        claim_link(
+            "https://wallet.example.com/claim/{mint_id}/{note_id}",
+            &record
+        ),
+        format!(
+            "https://wallet.example.com/claim/{}/{}",
+            record.id, record.note_id
+        )
+    );
+}
+
+#[test]
+fn bad_addresses_are_rejected() {
+    assert!(parse_email(" bob@example.com ").is_ok());
+    assert!(matches!(
+        parse_email("bob"),
+        Err(FaucetError::InvalidEmail(..))
+    ));
+
+    let mut config = smtp_config();
+    config.from = "not an address".into();
+    assert!(matches!(
+        config.validate(),
+        Err(FaucetError::InvalidEmail(..))
+    ));
+
+    let mut config = smtp_config();
+    config.username = Some("faucet".into());
+    assert!(matches!(config.validate(), Err(FaucetError::Config(_))));
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use common::MockNode;
+use miden_client::account::AccountId;
+use network_faucet::executor::{AccountLock, Executors, TxExecutor};
+use tokio::{sync::Mutex, task::LocalSet};
+
+fn account() -> AccountId {
+    AccountId::from_hex("0xd8e3fa793ea82360734ec91a98e798").unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn jobs_of_an_account_run_one_after_the_other() {
+    LocalSet::new()
+        .run_until(async {
+            let node = Rc::new(Mutex::new(MockNode::new()));
+            let executors = Executors::new(node);
+            let executor = executors.for_account(account());
+            let log = Rc::new(RefCell::new(Vec::new()));
+
+            let jobs: Vec<_> = (0..3)
+                .map(|job| {
+                    let (executor, log) = (executor.clone(), log.clone());
+                    tokio::task::spawn_local(async move {
+                        executor
+                            .run(move |_node| {
+                                Box::pin(async move {
+                                    log.borrow_mut().push(format!("start {job}"));
+                                    // Later jobs would overtake a slow one if they ran at once.
+                                    tokio::time::sleep(Duration::from_millis(30 - job * 10)).await;
+                                    log.borrow_mut().push(format!("end {job}"));
+                                    Ok(job)
+                                })
+                            })
+                            .await
+                    })
+                })
+                .collect();
+            for (expected, job) in jobs.into_iter().enumerate() {
+                assert_eq!(job.await.unwrap().unwrap(), expected as u64);
+            }
+
+            assert_eq!(
+                *log.borrow(),
+                ["start 0", "end 0", "start 1", "end 1", "start 2", "end 2"]
+            );
+        })
+        .await;
+}
+
+#[test]
+fn account_locks_exclude_other_holders() {
+    let dir = tempfile::tempdir().unwrap();
+    let lock = AccountLock::try_acquire(dir.path(), account())
+        .unwrap()
+        .unwrap();
+    assert!(AccountLock::path(dir.path(), account()).exists());
+    assert!(AccountLock::try_acquire(dir.path(), account())
+        .unwrap()
+        .is_none());
+
+    drop(lock);
+    assert!(AccountLock::try_acquire(dir.path(), account())
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test(start_paused = true)]
+async fn executors_wait_for_the_account_lock() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let node = Rc::new(Mutex::new(MockNode::new()));
+            let executor = TxExecutor::spawn(node, account(), Some(dir.path().to_path_buf()));
+
+            // Held by another process.
+            let lock = AccountLock::try_acquire(dir.path(), account())
+                .unwrap()
+                .unwrap();
+            let job = tokio::task::spawn_local(async move {
+                executor.run(|_node| Box::pin(async { Ok(()) })).await
+            });
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            assert!(!job.is_finished());
+
+            drop(lock);
+            job.await.unwrap().unwrap();
+        })
+        .await;
+}
@@ -0,0 +1,54 @@
+use network_faucet::{
+    config::Config,
+    explorer::{Explorer, ExplorerConfig},
+    mint::parse_transaction_id,
+    rpc::EndpointConfig,
+};
+
+const TRANSACTION_ID: &str = "0x8bd5ad2d3f4e5a6b7c8d9e0f1a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d";
+
+#[test]
+fn explorer_follows_the_network() {
+    let config = ExplorerConfig::default();
+    let testnet = Explorer::for_network(&config, &EndpointConfig::default()).unwrap();
+    assert_eq!(testnet, Explorer::new("https://testnet.midenscan.com"));
+    let devnet = EndpointConfig::Url("https://rpc.devnet.miden.io:443".into());
+    assert_eq!(
+        Explorer::for_network(&config, &devnet),
+        Some(Explorer::new("https://devnet.midenscan.com"))
+    );
+    let local = EndpointConfig::Url("localhost".into());
+    assert_eq!(Explorer::for_network(&config, &local), None);
+
+    let custom = ExplorerConfig {
+        url: Some("https://explorer.example.com/".into()),
+        disabled: false,
+    };
+    assert_eq!(
+        Explorer::for_network(&custom, &local),
+        Some(Explorer::new("https://explorer.example.com"))
+    );
+    let disabled = ExplorerConfig {
+        disabled: true,
+        ..ExplorerConfig::default()
+    };
+    assert_eq!(
+        Explorer::for_network(&disabled, &EndpointConfig::default()),
+        None
+    );
+}
+
+#[test]
+fn links_use_hex_ids() {
+    let config: Config =
+        toml::from_str("[explorer]\nurl = \"https://explorer.example.com\"").unwrap();
+    config.validate().unwrap();
+    let transaction_id = parse_transaction_id(TRANSACTION_ID).unwrap();
+    assert_eq!(
+        config.explorer().unwrap().transaction(transaction_id),
+        format!("https://explorer.example.com/tx/{TRANSACTION_ID}")
+    );
+
+    let config: Config = toml::from_str("[explorer]\nurl = \"explorer.example.com\"").unwrap();
+    assert!(config.validate().is_err());
+}
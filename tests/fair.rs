@@ -0,0 +1,57 @@
+use std::collections::BTreeMap;
+
+use network_faucet::fair::FairQueue;
+
+fn drain(queue: &mut FairQueue<u32>) -> Vec<(String, u32)> {
+    std::iter::from_fn(|| queue.pop()).collect()
+}
+
+#[test]
+fn bulk_requester_takes_turns() {
+    let mut queue = FairQueue::new(BTreeMap::new());
+    for item in 0..3 {
+        queue.push("bulk", item);
+    }
+    queue.push("alice", 10);
+    queue.push("bob", 20);
+    assert_eq!(queue.len(), 5);
+    assert_eq!(queue.identities(), [("bulk", 3), ("alice", 1), ("bob", 1)]);
+
+    let order: Vec<_> = drain(&mut queue)
+        .into_iter()
+        .map(|(identity, item)| format!("{identity}:{item}"))
+        .collect();
+    assert_eq!(order, ["bulk:0", "alice:10", "bob:20", "bulk:1", "bulk:2"]);
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn weights_set_mints_per_turn() {
+    let mut queue = FairQueue::new(BTreeMap::from([("scheduler".to_string(), 2)]));
+    for item in 0..4 {
+        queue.push("scheduler", item);
+        queue.push("alice", 10 + item);
+    }
+
+    let order: Vec<_> = drain(&mut queue)
+        .into_iter()
+        .map(|(_, item)| item)
+        .collect();
+    assert_eq!(order, [0, 1, 10, 2, 3, 11, 12, 13]);
+}
+
+#[test]
+fn late_identities_join_the_back_of_the_round() {
+    let mut queue = FairQueue::new(BTreeMap::new());
+    queue.push("bulk", 0);
+    queue.push("bulk", 1);
+    assert_eq!(queue.pop(), Some(("bulk".to_string(), 0)));
+
+    queue.push("alice", 10);
+    queue.push("bulk", 2);
+    let order: Vec<_> = drain(&mut queue)
+        .into_iter()
+        .map(|(_, item)| item)
+        .collect();
+    assert_eq!(order, [1, 10, 2]);
+}
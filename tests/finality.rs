@@ -0,0 +1,47 @@
+use network_faucet::finality::{remote_block, FinalityConfig};
+use serde_json::json;
+
+fn config(transaction_url: &str) -> FinalityConfig {
+    toml::from_str(&format!("transaction_url = \"{transaction_url}\"")).unwrap()
+}
+
+#[test]
+fn finality_config_needs_a_transaction_url_template() {
+    let valid = config("https://api.example.com/tx/{transaction_id}");
+    assert_eq!(valid.block_pointer, "/block_num");
+    assert!(!valid.required);
+    valid.validate().unwrap();
+
+    assert!(config("https://api.example.com/tx").validate().is_err());
+    assert!(config("api.example.com/tx/{transaction_id}")
+        .validate()
+        .is_err());
+    let bad_pointer = FinalityConfig {
+        block_pointer: "block_num".into(),
+        ..valid
+    };
+    assert!(bad_pointer.validate().is_err());
+}
+
+#[test]
+fn remote_block_reads_the_pointer() {
+    let committed = json!({ "data": { "block_num": 1042 } });
+    assert_eq!(remote_block(&committed, "/data/block_num"), Ok(Some(1042)));
+    assert_eq!(
+        remote_block(&json!({ "block_num": "17" }), "/block_num"),
+        Ok(Some(17))
+    );
+
+    // Pending transactions have no block yet.
+    assert_eq!(
+        remote_block(&json!({ "block_num": null }), "/block_num"),
+        Ok(None)
+    );
+    assert_eq!(
+        remote_block(&json!({ "status": "pending" }), "/block_num"),
+        Ok(None)
+    );
+
+    assert!(remote_block(&json!({ "block_num": -1 }), "/block_num").is_err());
+    assert!(remote_block(&json!({ "block_num": "soon" }), "/block_num").is_err());
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::MockNode;
+use network_faucet::{
+    deploy::deploy_faucet,
+    fixtures::{create_fixture_wallets, mint_starting_balances, FixtureManifest, FixtureWallet},
+    wallet::create_wallet,
+};
+
+const DEPLOY_SCRIPT: &str = include_str!("../masm/deploy.masm");
+
+#[tokio::test]
+async fn fixture_wallets_are_funded_in_one_transaction() {
+    let mut node = MockNode::new();
+    let owner = create_wallet(&mut node).await.unwrap();
+    let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+        .await
+        .unwrap();
+    let faucet_id = deployment.faucet.id();
+
+    let wallets = create_fixture_wallets(&mut node, 3).await.unwrap();
+    assert_eq!(wallets.len(), 3);
+    // Each wallet is tracked by the client and its key stored.
+    assert!(wallets
+        .iter()
+        .all(|wallet| node.accounts.contains_key(wallet)));
+    assert_eq!(node.keys.len(), 4);
+
+    let batches = mint_starting_balances(&mut node, faucet_id, &wallets, 100)
+        .await
+        .unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].p2id_notes.len(), 3);
+    let submitted = node.submitted.last().unwrap();
+    assert_eq!(submitted.transaction_id, batches[0].transaction_id);
+    assert_eq!(submitted.account_id, owner.id());
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fixtures.json");
+    let manifest = FixtureManifest {
+        faucet_id: faucet_id.to_hex(),
+        keystore_path: "./keystore".into(),
+        created_at: 1,
+        wallets: wallets
+            .iter()
+            .zip(&batches[0].p2id_notes)
+            .map(|(wallet, note)| FixtureWallet {
+                account_id: wallet.to_hex(),
+                label: None,
+                balance: 100,
+                note_id: note.id().to_hex(),
+            })
+            .collect(),
+    };
+    manifest.write(&path).unwrap();
+    assert_eq!(FixtureManifest::read(&path).unwrap(), manifest);
+}
@@ -0,0 +1,1313 @@
+mod common;
+
+use std::{rc::Rc, time::Duration};
+
+use common::{fixtures::network_wallet_id, MockNode};
+use faucet_notes::{mint_output_note, mint_output_note_with};
+use miden_client::{
+    account::{component::BasicWallet, Account, AccountBuilder, AccountStorageMode},
+    auth::AuthSecretKey,
+    note::{NoteTag, NoteType},
+    testing::Auth,
+    transaction::TransactionScriptTemplate,
+    Felt, Word,
+};
+use miden_objects::{block::BlockNumber, note::NoteExecutionMode};
+use network_faucet::{
+    client::{parse_seed, seeded_rng},
+    decommission::decommission,
+    deploy::{
+        deploy_faucet, deploy_faucet_with_params, parse_storage_mode, plan_deployment, Deployment,
+        FaucetAccount, FaucetAuth, TokenConfig,
+    },
+    flow::{start_flow, DeployFlow, Flow, FlowRunner, MintFlow, MintStep},
+    ledger::Ledger,
+    mint::{
+        burn, consume_mint_note, consume_note, consume_note_with_policy, consume_stored_notes,
+        mint_p2id, mint_with_options, parse_note_type, rebuild_mint_note, reclaim_height,
+        remint_options, unlock_height, verify_mint_note, AuxData, MintNoteKind, MintOptions,
+        NoteTagStrategy, RequestSource, OWNER_SLOT,
+    },
+    node::{FaucetNode, StoredNote},
+    pause::{is_paused, set_paused},
+    receipt::{mint_receipt, MintReceipt, AUTH_KEY_SLOT},
+    returns::collect_returns,
+    script::ScriptParams,
+    signing::check_signing,
+    tx::{ConsumeMode, TxPolicy},
+    wallet::{create_wallet, create_wallet_from_seed, pay, sweep_notes, wallet_from_seed},
+    watcher::{wait_for_transaction, BlockWatcher, SharedNode, SYNC_INTERVAL},
+    FaucetError,
+};
+use rand::RngCore;
+use tokio::{sync::Mutex, task::LocalSet};
+
+const DEPLOY_SCRIPT: &str = include_str!("../masm/deploy.masm");
+
+async fn deployed_faucet(node: &mut MockNode) -> (Account, Deployment) {
+    let owner = create_wallet(node).await.unwrap();
+    let deployment = deploy_faucet(node, owner.id(), DEPLOY_SCRIPT)
+        .await
+        .unwrap();
+    (owner, deployment)
+}
+
+fn watch(node: MockNode) -> (SharedNode<MockNode>, BlockWatcher) {
+    let node = Rc::new(Mutex::new(node));
+    let watcher = BlockWatcher::spawn(node.clone(), SYNC_INTERVAL);
+    (node, watcher)
+}
+
+#[tokio::test(start_paused = true)]
+async fn deploy_registers_accounts_and_submits_from_faucet() {
+    let mut node = MockNode::new();
+    let (owner, deployment) = deployed_faucet(&mut node).await;
+
+    assert_eq!(node.accounts.len(), 2);
+    assert_eq!(node.keys.len(), 1);
+
+    let submitted = node.submitted_by(deployment.faucet.id());
+    assert_eq!(submitted.len(), 1);
+    assert_eq!(submitted[0].transaction_id, deployment.transaction_id);
+
+    let owner_word = deployment.faucet.storage().get_item(OWNER_SLOT).unwrap();
+    assert_eq!(owner_word[3], owner.id().prefix().as_felt());
+    assert_eq!(owner_word[2], owner.id().suffix());
+}
+
+#[tokio::test(start_paused = true)]
+async fn mint_and_consume_flow() {
+    LocalSet::new()
+        .run_until(async {
+            let mut node = MockNode::new();
+            let (owner, deployment) = deployed_faucet(&mut node).await;
+            let recipient = create_wallet(&mut node).await.unwrap();
+            let (node, watcher) = watch(node);
+
+            let mint = mint_p2id(
+                &mut *node.lock().await,
+                deployment.faucet.id(),
+                recipient.id(),
+                50,
+            )
+            .await
+            .unwrap();
+            assert_eq!(node.lock().await.submitted_by(owner.id()).len(), 1);
+            assert_eq!(mint.p2id_note.metadata().sender(), deployment.faucet.id());
+
+            wait_for_transaction(&node, &watcher, mint.transaction_id)
+                .await
+                .unwrap();
+
+            let note_id = mint.p2id_note.id();
+            let consume_id = consume_note(&mut *node.lock().await, recipient.id(), mint.p2id_note)
+                .await
+                .unwrap();
+            {
+                let node = node.lock().await;
+                let consume = node.submitted_by(recipient.id());
+                assert_eq!(consume.len(), 1);
+                assert_eq!(consume[0].transaction_id, consume_id);
+                assert_eq!(
+                    consume[0].request.unauthenticated_input_notes()[0].id(),
+                    note_id
+                );
+            }
+
+            wait_for_transaction(&node, &watcher, consume_id)
+                .await
+                .unwrap();
+        })
+        .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn mint_with_serial_matches_precomputed_note() {
+    let mut node = MockNode::new();
+    let (_, deployment) = deployed_faucet(&mut node).await;
+    let recipient = create_wallet(&mut node).await.unwrap();
+    let serial_num = Word::from([Felt::new(7); 4]);
+    let note_kind = MintNoteKind::P2ide {
+        reclaim_height: BlockNumber::from(100),
+    };
+
+    let expected = mint_output_note(
+        deployment.faucet.id(),
+        recipient.id(),
+        50,
+        serial_num,
+        note_kind,
+    )
+    .unwrap();
+    let mint = mint_with_options(
+        &mut node,
+        deployment.faucet.id(),
+        recipient.id(),
+        50,
+        MintOptions {
+            serial_num: Some(serial_num),
+            note_kind,
+            ..MintOptions::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(mint.p2id_note.commitment(), expected.commitment());
+    assert_eq!(
+        reclaim_height(&mint.p2id_note),
+        Some(BlockNumber::from(100))
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn consumes_refuse_notes_that_drifted_from_their_mint() {
+    let mut node = MockNode::new();
+    let (_, deployment) = deployed_faucet(&mut node).await;
+    let recipient = create_wallet(&mut node).await.unwrap();
+    let mint = mint_p2id(&mut node, deployment.faucet.id(), recipient.id(), 50)
+        .await
+        .unwrap();
+    verify_mint_note(&mut node, mint.transaction_id, &mint.p2id_note)
+        .await
+        .unwrap();
+
+    // Same mint with another serial number, as if rebuilt from a stale record.
+    let drifted = mint_output_note(
+        deployment.faucet.id(),
+        recipient.id(),
+        50,
+        Word::from([Felt::new(9); 4]),
+        MintNoteKind::P2id,
+    )
+    .unwrap();
+    let err = consume_mint_note(
+        &mut node,
+        recipient.id(),
+        mint.transaction_id,
+        drifted,
+        ConsumeMode::Auto,
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, FaucetError::NoteLinkage(..)), "{err}");
+    assert!(node.submitted_by(recipient.id()).is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn plain_mints_are_not_reclaimable() {
+    let mut node = MockNode::new();
+    let (_, deployment) = deployed_faucet(&mut node).await;
+    let recipient = create_wallet(&mut node).await.unwrap();
+
+    let mint = mint_p2id(&mut node, deployment.faucet.id(), recipient.id(), 50)
+        .await
+        .unwrap();
+    assert_eq!(reclaim_height(&mint.p2id_note), None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn timelocked_mints_can_be_rebuilt_from_the_ledger() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let mut node = MockNode::new();
+    let (_, deployment) = deployed_faucet(&mut node).await;
+    let recipient = create_wallet(&mut node).await.unwrap();
+    let unlock = Some(BlockNumber::from(40));
+
+    let mint = mint_with_options(
+        &mut node,
+        deployment.faucet.id(),
+        recipient.id(),
+        50,
+        MintOptions {
+            note_kind: MintNoteKind::P2id.with_unlock_height(unlock),
+            ..MintOptions::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(unlock_height(&mint.p2id_note), unlock);
+    assert_eq!(reclaim_height(&mint.p2id_note), None);
+
+    let mint_id = ledger
+        .record_mint(
+            deployment.faucet.id(),
+            recipient.id(),
+            50,
+            mint.transaction_id,
+            &mint.p2id_note,
+        )
+        .unwrap();
+    let record = ledger.get_mint(mint_id).unwrap().unwrap();
+    assert_eq!(record.unlock_block, Some(40));
+    assert_eq!(record.reclaim_block, None);
+    assert_eq!(
+        rebuild_mint_note(&record).unwrap().id(),
+        mint.p2id_note.id()
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn mints_keep_their_note_type_and_attribution() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let mut node = MockNode::new();
+    let (_, deployment) = deployed_faucet(&mut node).await;
+    let recipient = create_wallet(&mut node).await.unwrap();
+
+    for (requested, expected) in [
+        (None, NoteType::Private),
+        (Some(parse_note_type("public").unwrap()), NoteType::Public),
+        (
+            Some(parse_note_type(" Private").unwrap()),
+            NoteType::Private,
+        ),
+    ] {
+        let mint = mint_with_options(
+            &mut node,
+            deployment.faucet.id(),
+            recipient.id(),
+            50,
+            MintOptions {
+                note_type: requested,
+                ..MintOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(mint.p2id_note.metadata().note_type(), expected);
+
+        let mint_id = ledger
+            .record_mint(
+                deployment.faucet.id(),
+                recipient.id(),
+                50,
+                mint.transaction_id,
+                &mint.p2id_note,
+            )
+            .unwrap();
+        let record = ledger.get_mint(mint_id).unwrap().unwrap();
+        assert_eq!(record.note_type(), expected);
+        assert_eq!(
+            rebuild_mint_note(&record).unwrap().id(),
+            mint.p2id_note.id()
+        );
+    }
+
+    let campaign = AuxData::new(7, RequestSource::Rest);
+    let mint = mint_with_options(
+        &mut node,
+        deployment.faucet.id(),
+        recipient.id(),
+        50,
+        MintOptions {
+            aux: campaign,
+            ..MintOptions::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(mint.p2id_note.metadata().aux(), campaign.to_felt());
+    let mint_id = ledger
+        .record_mint(
+            deployment.faucet.id(),
+            recipient.id(),
+            50,
+            mint.transaction_id,
+            &mint.p2id_note,
+        )
+        .unwrap();
+    let record = ledger.get_mint(mint_id).unwrap().unwrap();
+    assert_eq!(record.aux_data(), Some(campaign));
+    assert_eq!(
+        rebuild_mint_note(&record).unwrap().commitment(),
+        mint.p2id_note.commitment()
+    );
+    assert_eq!(remint_options(&record).unwrap().aux, campaign);
+
+    assert!(matches!(
+        parse_note_type("encrypted"),
+        Err(FaucetError::InvalidNoteType(_))
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn mints_tag_their_notes_by_the_configured_strategy() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let mut node = MockNode::new();
+    let (_, deployment) = deployed_faucet(&mut node).await;
+    let recipient = create_wallet(&mut node).await.unwrap();
+
+    let strategy: NoteTagStrategy = "use_case:42:7".parse().unwrap();
+    assert_eq!(strategy.to_string(), "use_case:42:7");
+    assert_eq!(
+        "account_id".parse::<NoteTagStrategy>().unwrap(),
+        NoteTagStrategy::AccountId
+    );
+    for invalid in [
+        "recipient",
+        "use_case:x",
+        "use_case:1:70000",
+        "use_case:40000",
+    ] {
+        assert!(matches!(
+            invalid.parse::<NoteTagStrategy>(),
+            Err(FaucetError::InvalidNoteTag(..))
+        ));
+    }
+
+    // Use-case tags make the note public unless the mint asks for a private one.
+    let mint = mint_with_options(
+        &mut node,
+        deployment.faucet.id(),
+        recipient.id(),
+        50,
+        MintOptions {
+            note_tag: Some(strategy),
+            ..MintOptions::default()
+        },
+    )
+    .await
+    .unwrap();
+    let metadata = mint.p2id_note.metadata();
+    assert_eq!(metadata.tag(), strategy.tag(recipient.id()).unwrap());
+    assert_ne!(metadata.tag(), NoteTag::from_account_id(recipient.id()));
+    assert_eq!(metadata.note_type(), NoteType::Public);
+
+    let mint_id = ledger
+        .record_mint(
+            deployment.faucet.id(),
+            recipient.id(),
+            50,
+            mint.transaction_id,
+            &mint.p2id_note,
+        )
+        .unwrap();
+    let record = ledger.get_mint(mint_id).unwrap().unwrap();
+    assert_eq!(
+        rebuild_mint_note(&record).unwrap().commitment(),
+        mint.p2id_note.commitment()
+    );
+    assert_eq!(remint_options(&record).unwrap().note_tag, Some(strategy));
+
+    let private = mint_with_options(
+        &mut node,
+        deployment.faucet.id(),
+        recipient.id(),
+        50,
+        MintOptions {
+            note_type: Some(NoteType::Private),
+            note_tag: Some(strategy),
+            ..MintOptions::default()
+        },
+    )
+    .await;
+    assert!(matches!(private, Err(FaucetError::InvalidNoteTag(..))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn committed_mints_get_receipts_signed_by_the_owner() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let mut node = MockNode::new();
+    let (owner, deployment) = deployed_faucet(&mut node).await;
+    let recipient = create_wallet(&mut node).await.unwrap();
+
+    let mint = mint_p2id(&mut node, deployment.faucet.id(), recipient.id(), 50)
+        .await
+        .unwrap();
+    let mint_id = ledger
+        .record_mint(
+            deployment.faucet.id(),
+            recipient.id(),
+            50,
+            mint.transaction_id,
+            &mint.p2id_note,
+        )
+        .unwrap();
+    let record = ledger.get_mint(mint_id).unwrap().unwrap();
+    assert!(matches!(
+        mint_receipt(&mut node, &record).await,
+        Err(FaucetError::MintNotCommitted(id)) if id == mint_id
+    ));
+
+    ledger
+        .mark_committed(mint.transaction_id, BlockNumber::from(3))
+        .unwrap();
+    let record = ledger.get_mint(mint_id).unwrap().unwrap();
+    let receipt = mint_receipt(&mut node, &record).await.unwrap();
+    assert_eq!(receipt.signer, owner.id().to_hex());
+    assert_eq!(
+        receipt.note_commitment,
+        mint.p2id_note.commitment().to_hex()
+    );
+    assert_eq!(receipt.block_num, 3);
+    assert_eq!(
+        receipt.verify().unwrap(),
+        owner.storage().get_item(AUTH_KEY_SLOT).unwrap()
+    );
+
+    let forged = MintReceipt {
+        amount: 5_000,
+        ..receipt
+    };
+    assert!(matches!(
+        forged.verify(),
+        Err(FaucetError::InvalidReceipt(_))
+    ));
+}
+
+#[tokio::test]
+async fn signing_self_test_needs_the_owner_key() {
+    let mut node = MockNode::new();
+    let (owner, deployment) = deployed_faucet(&mut node).await;
+    let faucet = deployment.faucet.id();
+
+    let check = check_signing(&mut node, faucet).await.unwrap();
+    assert_eq!(check.account_id, owner.id());
+    assert_eq!(
+        check.key_commitment,
+        owner.storage().get_item(AUTH_KEY_SLOT).unwrap()
+    );
+
+    node.keys.clear();
+    assert!(matches!(
+        check_signing(&mut node, faucet).await,
+        Err(FaucetError::KeyNotFound(id)) if id == owner.id()
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn mint_surfaces_submission_failure() {
+    let mut node = MockNode::new();
+    let (owner, deployment) = deployed_faucet(&mut node).await;
+    let recipient = create_wallet(&mut node).await.unwrap();
+
+    node.failing_submits = 1;
+    let err = mint_p2id(&mut node, deployment.faucet.id(), recipient.id(), 50)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, FaucetError::RequestTimeout { .. }));
+    assert!(node.submitted_by(owner.id()).is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_recovers_from_transient_failures() {
+    LocalSet::new()
+        .run_until(async {
+            let mut node = MockNode::new();
+            let (_, deployment) = deployed_faucet(&mut node).await;
+            node.failing_syncs = 2;
+            node.failing_state_queries = 2;
+            let commit_delay = node.commit_delay;
+            let (node, watcher) = watch(node);
+
+            let block = wait_for_transaction(&node, &watcher, deployment.transaction_id)
+                .await
+                .unwrap();
+
+            let node = node.lock().await;
+            assert_eq!(node.failing_syncs, 0);
+            assert_eq!(node.failing_state_queries, 0);
+            assert_eq!(block, BlockNumber::from(commit_delay));
+        })
+        .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_reports_discarded_transactions() {
+    LocalSet::new()
+        .run_until(async {
+            let mut node = MockNode::new();
+            let (_, deployment) = deployed_faucet(&mut node).await;
+            node.discarded.push(deployment.transaction_id);
+            let (node, watcher) = watch(node);
+
+            let err = wait_for_transaction(&node, &watcher, deployment.transaction_id)
+                .await
+                .unwrap_err();
+
+            assert!(matches!(
+                err,
+                FaucetError::TransactionDiscarded(id, _) if id == deployment.transaction_id
+            ));
+        })
+        .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn waits_share_one_state_query_per_sync() {
+    LocalSet::new()
+        .run_until(async {
+            let mut node = MockNode::new();
+            let (_, deployment) = deployed_faucet(&mut node).await;
+            let recipient = create_wallet(&mut node).await.unwrap();
+            let mut transaction_ids = Vec::new();
+            for _ in 0..5 {
+                let mint = mint_p2id(&mut node, deployment.faucet.id(), recipient.id(), 10)
+                    .await
+                    .unwrap();
+                transaction_ids.push(mint.transaction_id);
+            }
+            let commit_block = node.block + node.commit_delay;
+            let (node, watcher) = watch(node);
+
+            let blocks = futures::future::try_join_all(
+                transaction_ids
+                    .iter()
+                    .map(|transaction_id| wait_for_transaction(&node, &watcher, *transaction_id)),
+            )
+            .await
+            .unwrap();
+
+            assert!(blocks
+                .iter()
+                .all(|block| *block == BlockNumber::from(commit_block)));
+            let node = node.lock().await;
+            // Each wait reads its state once before the watcher tracks it, every later read
+            // comes from the watcher's poll.
+            assert_eq!(node.state_queries, 5);
+            assert!(node.batched_state_queries <= node.commit_delay);
+        })
+        .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn deploy_flows_resume_at_the_failed_step() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+            let mut node = MockNode::new();
+            node.failing_submits = 1;
+            let (node, watcher) = watch(node);
+            let runner = FlowRunner::new(node.clone(), &watcher, &ledger, "cli:test");
+
+            let flow = DeployFlow::new(
+                None,
+                TokenConfig::default(),
+                FaucetAccount::default(),
+                DEPLOY_SCRIPT,
+            );
+            let id = start_flow(&ledger, &Flow::Deploy(flow)).unwrap();
+            let err = runner.resume(id).await.unwrap_err();
+            assert!(matches!(err, FaucetError::RequestTimeout { .. }));
+
+            // Owner and faucet exist, the submission is not retried on its own.
+            let record = ledger.flow(id).unwrap().unwrap();
+            assert_eq!(record.step, "run_script");
+            assert_eq!(record.attempts, 1);
+            assert!(record.last_error.is_some());
+            assert_eq!(node.lock().await.accounts.len(), 2);
+            assert!(node.lock().await.submitted.is_empty());
+
+            let Flow::Deploy(deployed) = runner.resume(id).await.unwrap() else {
+                panic!("expected a deploy flow");
+            };
+            let faucet_id = deployed.faucet_id.unwrap();
+            assert_eq!(node.lock().await.accounts.len(), 2);
+            assert_eq!(ledger.deployments().unwrap()[0].faucet_id, faucet_id);
+            assert!(ledger.flows(false).unwrap().is_empty());
+            assert_eq!(ledger.flows(true).unwrap()[0].step, "done");
+        })
+        .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn mint_flows_resume_with_the_note_they_built() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+            let mut node = MockNode::new();
+            let (owner, deployment) = deployed_faucet(&mut node).await;
+            let faucet_id = deployment.faucet.id();
+            let recipient = create_wallet(&mut node).await.unwrap();
+            node.failing_submits = 1;
+            let (node, watcher) = watch(node);
+            let runner = FlowRunner::new(node.clone(), &watcher, &ledger, "cli:test");
+
+            let flow = MintFlow::new(faucet_id, Some(recipient.id()), 50)
+                .with_note_type(Some(NoteType::Public))
+                .with_consume(ConsumeMode::Unauthenticated, Duration::from_secs(60));
+            let id = start_flow(&ledger, &Flow::Mint(flow)).unwrap();
+            assert!(runner.resume(id).await.is_err());
+
+            let record = ledger.flow(id).unwrap().unwrap();
+            assert_eq!(record.step, "submit");
+            let Flow::Mint(stopped) = Flow::from_record(&record).unwrap() else {
+                panic!("expected a mint flow");
+            };
+            assert_eq!(stopped.step, MintStep::Submit);
+            assert!(node.lock().await.submitted_by(owner.id()).is_empty());
+
+            // The resumed flow mints the note built before the failure.
+            let serial_num = Word::try_from(stopped.serial_num.unwrap().as_str()).unwrap();
+            let note = mint_output_note(
+                faucet_id,
+                recipient.id(),
+                50,
+                serial_num,
+                MintNoteKind::P2id,
+            )
+            .unwrap();
+            node.lock().await.consumed.push((note.nullifier(), 1_000));
+
+            let Flow::Mint(minted) = runner.resume(id).await.unwrap() else {
+                panic!("expected a mint flow");
+            };
+            assert_eq!(minted.step, MintStep::Done);
+            assert_eq!(minted.claimed_at, Some(1_000));
+            assert_eq!(node.lock().await.submitted_by(owner.id()).len(), 1);
+            let mint = ledger.get_mint(minted.mint_id.unwrap()).unwrap().unwrap();
+            assert_eq!(mint.note_id, note.id().to_hex());
+            assert!(ledger.flows(false).unwrap().is_empty());
+        })
+        .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn network_executed_mints_wait_for_the_network_to_consume_them() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+            let mut node = MockNode::new();
+            let (owner, deployment) = deployed_faucet(&mut node).await;
+            let faucet_id = deployment.faucet.id();
+            let wallet = create_wallet(&mut node).await.unwrap().id();
+            let recipient = network_wallet_id([7; 15]);
+            assert!(matches!(
+                NoteTagStrategy::Network.tag(wallet),
+                Err(FaucetError::InvalidNoteTag(..))
+            ));
+            node.failing_submits = 1;
+            let (node, watcher) = watch(node);
+            let runner = FlowRunner::new(node.clone(), &watcher, &ledger, "cli:test");
+
+            let flow = MintFlow::new(faucet_id, Some(recipient), 50)
+                .with_note_tag(Some(NoteTagStrategy::Network))
+                .with_consume(ConsumeMode::Auto, Duration::from_secs(60));
+            let id = start_flow(&ledger, &Flow::Mint(flow)).unwrap();
+            assert!(runner.resume(id).await.is_err());
+            let Flow::Mint(stopped) =
+                Flow::from_record(&ledger.flow(id).unwrap().unwrap()).unwrap()
+            else {
+                panic!("expected a mint flow");
+            };
+            let serial_num = Word::try_from(stopped.serial_num.unwrap().as_str()).unwrap();
+            // Notes for network accounts are public, their private counterpart does not exist.
+            let note = mint_output_note_with(
+                faucet_id,
+                recipient,
+                50,
+                serial_num,
+                MintNoteKind::P2id,
+                NoteType::Public,
+                AuxData::default(),
+            )
+            .unwrap();
+            node.lock().await.consumed.push((note.nullifier(), 1_000));
+
+            let Flow::Mint(minted) = runner.resume(id).await.unwrap() else {
+                panic!("expected a mint flow");
+            };
+            assert_eq!(minted.step, MintStep::Done);
+            assert_eq!(minted.claimed_at, Some(1_000));
+            assert!(minted.consume_transaction_id.is_none());
+
+            let mint = ledger.get_mint(minted.mint_id.unwrap()).unwrap().unwrap();
+            assert!(mint.network_executed());
+            assert_eq!(mint.note_type(), NoteType::Public);
+            assert_eq!(mint.claim_block, Some(1_000));
+            let minted_note = rebuild_mint_note(&mint).unwrap();
+            assert_eq!(
+                minted_note.metadata().tag().execution_mode(),
+                NoteExecutionMode::Network
+            );
+            // Only the MINT transaction was submitted, no consume transaction.
+            assert_eq!(node.lock().await.submitted_by(owner.id()).len(), 1);
+            assert!(node.lock().await.submitted_by(recipient).is_empty());
+            let audit = ledger.audit_log().unwrap();
+            assert!(audit
+                .iter()
+                .any(|entry| entry.action == "mint.network_executed"));
+        })
+        .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn watcher_publishes_advancing_tip() {
+    LocalSet::new()
+        .run_until(async {
+            let (_, watcher) = watch(MockNode::new());
+
+            let tip = watcher.wait_for_block(BlockNumber::from(3)).await.unwrap();
+
+            assert!(tip.block_num >= BlockNumber::from(3));
+            assert!(watcher.tip().unwrap().block_num >= tip.block_num);
+        })
+        .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn consume_stored_notes_splits_authenticated_notes() {
+    let mut node = MockNode::new();
+    let (_, deployment) = deployed_faucet(&mut node).await;
+    let recipient = create_wallet(&mut node).await.unwrap();
+
+    let mut note_ids = Vec::new();
+    for authenticated in [true, false] {
+        let mint = mint_p2id(&mut node, deployment.faucet.id(), recipient.id(), 50)
+            .await
+            .unwrap();
+        note_ids.push(mint.p2id_note.id());
+        node.stored_notes.push(StoredNote {
+            note: mint.p2id_note,
+            authenticated,
+        });
+    }
+
+    let transaction_id = consume_stored_notes(&mut node, recipient.id(), &note_ids)
+        .await
+        .unwrap();
+    let consume = node.submitted_by(recipient.id());
+    assert_eq!(consume.len(), 1);
+    assert_eq!(consume[0].transaction_id, transaction_id);
+    let unauthenticated = consume[0].request.unauthenticated_input_notes();
+    assert_eq!(unauthenticated.len(), 1);
+    assert_eq!(unauthenticated[0].id(), note_ids[1]);
+
+    let unknown = mint_p2id(&mut node, deployment.faucet.id(), recipient.id(), 50)
+        .await
+        .unwrap();
+    let result = consume_stored_notes(&mut node, recipient.id(), &[unknown.p2id_note.id()]).await;
+    assert!(matches!(result, Err(FaucetError::InputNote(_))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn consumed_notes_are_authenticated_once_committed() {
+    let mut node = MockNode::new();
+    let (_, deployment) = deployed_faucet(&mut node).await;
+    let recipient = create_wallet(&mut node).await.unwrap();
+    let policy = |consume| TxPolicy {
+        consume,
+        ..TxPolicy::default()
+    };
+
+    let committed = mint_p2id(&mut node, deployment.faucet.id(), recipient.id(), 50)
+        .await
+        .unwrap()
+        .p2id_note;
+    node.stored_notes.push(StoredNote {
+        note: committed.clone(),
+        authenticated: true,
+    });
+    let racing = mint_p2id(&mut node, deployment.faucet.id(), recipient.id(), 50)
+        .await
+        .unwrap()
+        .p2id_note;
+
+    consume_note(&mut node, recipient.id(), committed.clone())
+        .await
+        .unwrap();
+    consume_note(&mut node, recipient.id(), racing.clone())
+        .await
+        .unwrap();
+    let result = consume_note_with_policy(
+        &mut node,
+        recipient.id(),
+        racing.clone(),
+        policy(ConsumeMode::Authenticated),
+    )
+    .await;
+    assert!(matches!(result, Err(FaucetError::InputNote(_))));
+    consume_note_with_policy(
+        &mut node,
+        recipient.id(),
+        committed.clone(),
+        policy(ConsumeMode::Unauthenticated),
+    )
+    .await
+    .unwrap();
+
+    let unauthenticated: Vec<_> = node
+        .submitted_by(recipient.id())
+        .iter()
+        .map(|consume| consume.request.unauthenticated_input_notes().len())
+        .collect();
+    assert_eq!(unauthenticated, [0, 1, 1]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn sweep_consumes_all_notes_of_the_wallet() {
+    let mut node = MockNode::new();
+    let (owner, deployment) = deployed_faucet(&mut node).await;
+    let recipient = create_wallet(&mut node).await.unwrap();
+
+    for target in [recipient.id(), recipient.id(), owner.id()] {
+        let mint = mint_p2id(&mut node, deployment.faucet.id(), target, 50)
+            .await
+            .unwrap();
+        node.stored_notes.push(StoredNote {
+            note: mint.p2id_note,
+            authenticated: false,
+        });
+    }
+
+    let batches = sweep_notes(&mut node, recipient.id()).await.unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].notes, 2);
+
+    let consume = node.submitted_by(recipient.id());
+    assert_eq!(consume.len(), 1);
+    assert_eq!(consume[0].request.unauthenticated_input_notes().len(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn pay_sends_p2id_note_from_wallet() {
+    let mut node = MockNode::new();
+    let (_, deployment) = deployed_faucet(&mut node).await;
+    let alice = create_wallet(&mut node).await.unwrap();
+    let bob = create_wallet(&mut node).await.unwrap();
+
+    let payment = pay(
+        &mut node,
+        alice.id(),
+        bob.id(),
+        deployment.faucet.id(),
+        30,
+        NoteType::Private,
+    )
+    .await
+    .unwrap();
+    assert_eq!(payment.note.metadata().sender(), alice.id());
+    assert_eq!(
+        payment.note.recipient().inputs().values()[0],
+        bob.id().suffix()
+    );
+
+    let submitted = node.submitted_by(alice.id());
+    assert_eq!(submitted.len(), 1);
+    assert_eq!(submitted[0].transaction_id, payment.transaction_id);
+    match submitted[0].request.expected_output_own_notes().as_slice() {
+        [note] => assert_eq!(note.id(), payment.note.id()),
+        notes => panic!("expected one output note, got {}", notes.len()),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn burn_sends_tokens_back_to_the_faucet() {
+    let mut node = MockNode::new();
+    let (_, deployment) = deployed_faucet(&mut node).await;
+    let wallet = create_wallet(&mut node).await.unwrap();
+    let faucet = deployment.faucet.id();
+
+    let outcome = burn(&mut node, wallet.id(), faucet, 20).await.unwrap();
+    assert_eq!(outcome.burn_note.metadata().sender(), wallet.id());
+    assert_eq!(node.submitted_by(wallet.id()).len(), 1);
+
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let id = ledger
+        .record_burn(
+            faucet,
+            wallet.id(),
+            20,
+            outcome.transaction_id,
+            &outcome.burn_note,
+        )
+        .unwrap();
+    assert_eq!(ledger.stats(Some(faucet)).unwrap().burned, 0);
+
+    ledger
+        .mark_burn_committed(outcome.transaction_id, BlockNumber::from(4))
+        .unwrap();
+    let record = ledger.get_burn(id).unwrap().unwrap();
+    assert_eq!(record.note_id, outcome.burn_note.id().to_hex());
+    assert_eq!(record.commit_block, Some(4));
+
+    let stats = ledger.stats(Some(faucet)).unwrap();
+    assert_eq!(stats.burned, 1);
+    assert_eq!(stats.burned_amount, 20);
+    assert_eq!(stats.submitted, 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn deposits_to_the_owner_are_collected_as_returned_supply() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+            let mut node = MockNode::new();
+            let (owner, deployment) = deployed_faucet(&mut node).await;
+            let (_, other) = deployed_faucet(&mut node).await;
+            let holder = create_wallet(&mut node).await.unwrap();
+            let faucet = deployment.faucet.id();
+
+            // Only the deposit of the faucet's own token counts as returned supply.
+            for (faucet_id, amount) in [(faucet, 30), (other.faucet.id(), 5)] {
+                let payment = pay(
+                    &mut node,
+                    holder.id(),
+                    owner.id(),
+                    faucet_id,
+                    amount,
+                    NoteType::Private,
+                )
+                .await
+                .unwrap();
+                node.stored_notes.push(StoredNote {
+                    note: payment.note,
+                    authenticated: false,
+                });
+            }
+
+            let (node, watcher) = watch(node);
+            let report = collect_returns(&node, &watcher, &ledger, faucet, true)
+                .await
+                .unwrap();
+            assert_eq!(report.collected.len(), 1);
+            assert_eq!(report.collected_amount, 30);
+            assert_eq!(report.collected[0].sender, holder.id().to_hex());
+            assert!(report.burn_id.is_some());
+
+            let stats = ledger.stats(Some(faucet)).unwrap();
+            assert_eq!((stats.returned, stats.returned_amount), (1, 30));
+            assert_eq!((stats.burned, stats.burned_amount), (1, 30));
+
+            // The store still lists the deposit, but it is not collected twice.
+            let report = collect_returns(&node, &watcher, &ledger, faucet, true)
+                .await
+                .unwrap();
+            assert!(report.collected.is_empty());
+            assert_eq!(report.burn_id, None);
+        })
+        .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn deployments_record_their_token() {
+    let mut node = MockNode::new();
+    let owner = create_wallet(&mut node).await.unwrap();
+    let token = TokenConfig {
+        symbol: "TST".into(),
+        decimals: 6,
+        max_supply: 5_000,
+    };
+    let deployment = deploy_faucet_with_params(
+        &mut node,
+        owner.id(),
+        DEPLOY_SCRIPT,
+        &ScriptParams::default(),
+        &token,
+        FaucetAccount::default(),
+    )
+    .await
+    .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    ledger
+        .record_deployment(
+            deployment.faucet.id(),
+            owner.id(),
+            &token,
+            deployment.transaction_id,
+        )
+        .unwrap();
+    let deployments = ledger.deployments().unwrap();
+    assert_eq!(deployments.len(), 1);
+    assert_eq!(deployments[0].faucet_id, deployment.faucet.id().to_hex());
+    assert_eq!(deployments[0].symbol, "TST");
+    assert_eq!(deployments[0].decimals, 6);
+    assert_eq!(deployments[0].max_supply, 5_000);
+
+    // Invalid tokens are refused before the faucet reaches the store.
+    let accounts = node.accounts.len();
+    let invalid = TokenConfig {
+        symbol: "tst".into(),
+        ..token
+    };
+    let result = deploy_faucet_with_params(
+        &mut node,
+        owner.id(),
+        DEPLOY_SCRIPT,
+        &ScriptParams::default(),
+        &invalid,
+        FaucetAccount::default(),
+    )
+    .await;
+    assert!(matches!(result, Err(FaucetError::Config(_))));
+    assert_eq!(node.accounts.len(), accounts);
+}
+
+#[tokio::test(start_paused = true)]
+async fn keyed_faucets_are_deployed_with_their_key() {
+    let mut node = MockNode::new();
+    let owner = create_wallet(&mut node).await.unwrap();
+    let keys = node.keys.len();
+    let account = FaucetAccount {
+        auth: FaucetAuth::RpoFalcon512,
+        storage_mode: AccountStorageMode::Public,
+        key_id: None,
+    };
+    account.check(true).unwrap();
+
+    let deployment = deploy_faucet_with_params(
+        &mut node,
+        owner.id(),
+        DEPLOY_SCRIPT,
+        &ScriptParams::default(),
+        &TokenConfig::default(),
+        account,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        deployment.faucet.id().storage_mode(),
+        AccountStorageMode::Public
+    );
+    assert_eq!(node.keys.len(), keys + 1);
+
+    // A second faucet bound to the same key, as deployed by another host.
+    let AuthSecretKey::RpoFalcon512(key) = node.keys.last().unwrap().clone() else {
+        panic!("expected a Falcon key");
+    };
+    let key_id = key.public_key().to_commitment();
+    let shared = FaucetAccount {
+        key_id: Some(key_id),
+        ..account
+    };
+    let second = deploy_faucet_with_params(
+        &mut node,
+        owner.id(),
+        DEPLOY_SCRIPT,
+        &ScriptParams::default(),
+        &TokenConfig::default(),
+        shared,
+    )
+    .await
+    .unwrap();
+    assert_ne!(second.faucet.id(), deployment.faucet.id());
+    assert_eq!(node.keys.len(), keys + 1);
+
+    let unknown = FaucetAccount {
+        key_id: Some(Word::from([Felt::new(1); 4])),
+        ..account
+    };
+    let result = deploy_faucet_with_params(
+        &mut node,
+        owner.id(),
+        DEPLOY_SCRIPT,
+        &ScriptParams::default(),
+        &TokenConfig::default(),
+        unknown,
+    )
+    .await;
+    assert!(matches!(result, Err(FaucetError::KeyIdNotFound(_))));
+}
+
+#[tokio::test]
+async fn deployments_create_the_planned_accounts() {
+    let seed = parse_seed("0xc0ffee").unwrap();
+    let params = ScriptParams::default();
+    let token = TokenConfig::default();
+    let plan = plan_deployment(
+        &mut seeded_rng(seed),
+        None,
+        DEPLOY_SCRIPT,
+        &params,
+        &token,
+        FaucetAccount::default(),
+    )
+    .unwrap();
+    assert!(plan.new_owner);
+    assert_eq!(
+        plan.components,
+        ["incr-nonce", "network-fungible-faucet", "pausable"]
+    );
+    assert_eq!(plan.key_commitment, None);
+
+    // The deployment of `deploy` with the same seed: a new owner, then the faucet.
+    let mut node = MockNode::seeded(seed);
+    let mut owner_seed = [0_u8; 32];
+    node.rng().fill_bytes(&mut owner_seed);
+    let owner = create_wallet_from_seed(&mut node, owner_seed)
+        .await
+        .unwrap();
+    let deployment = deploy_faucet_with_params(
+        &mut node,
+        owner.id(),
+        DEPLOY_SCRIPT,
+        &params,
+        &token,
+        FaucetAccount::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(plan.owner_id, owner.id().to_hex());
+    assert_eq!(plan.faucet_id, deployment.faucet.id().to_hex());
+    let submitted = node.submitted.last().unwrap();
+    assert_eq!(
+        Some(plan.script_root),
+        match submitted.request.script_template() {
+            Some(TransactionScriptTemplate::CustomScript(script)) => Some(script.root().to_hex()),
+            _ => None,
+        }
+    );
+}
+
+#[test]
+fn unsafe_faucet_accounts_are_refused() {
+    let account = |auth, storage_mode| FaucetAccount {
+        auth,
+        storage_mode,
+        key_id: None,
+    };
+    let keyed_network = account(FaucetAuth::RpoFalcon512, AccountStorageMode::Network);
+    for mainnet in [false, true] {
+        assert!(matches!(
+            keyed_network.check(mainnet),
+            Err(FaucetError::UnsafeFaucetAccount(..))
+        ));
+        FaucetAccount::default().check(mainnet).unwrap();
+    }
+    for storage_mode in [AccountStorageMode::Public, AccountStorageMode::Private] {
+        let nonce_only = account(FaucetAuth::IncrNonce, storage_mode);
+        nonce_only.check(false).unwrap();
+        assert!(nonce_only.check(true).is_err());
+    }
+    assert!(
+        account(FaucetAuth::RpoFalcon512, AccountStorageMode::Private)
+            .check(true)
+            .is_err()
+    );
+    let nonce_only_with_key = FaucetAccount {
+        key_id: Some(Word::default()),
+        ..FaucetAccount::default()
+    };
+    assert!(nonce_only_with_key.check(false).is_err());
+
+    // The owner's key guards the minting and pausing of nonce-only faucets on mainnet.
+    let (keyed_owner, _) = wallet_from_seed([1; 32]).unwrap();
+    let nonce_only_owner = AccountBuilder::new([2; 32])
+        .with_auth_component(Auth::IncrNonce)
+        .with_component(BasicWallet)
+        .build_existing()
+        .unwrap();
+    for mainnet in [false, true] {
+        FaucetAccount::default()
+            .check_owner(&keyed_owner, mainnet)
+            .unwrap();
+    }
+    FaucetAccount::default()
+        .check_owner(&nonce_only_owner, false)
+        .unwrap();
+    assert!(matches!(
+        FaucetAccount::default().check_owner(&nonce_only_owner, true),
+        Err(FaucetError::UnsafeFaucetAccount(..))
+    ));
+    account(FaucetAuth::RpoFalcon512, AccountStorageMode::Public)
+        .check_owner(&nonce_only_owner, true)
+        .unwrap();
+
+    assert_eq!(
+        "rpo-falcon512".parse::<FaucetAuth>().unwrap(),
+        FaucetAuth::RpoFalcon512
+    );
+    assert!("falcon".parse::<FaucetAuth>().is_err());
+    assert_eq!(
+        parse_storage_mode("Network").unwrap(),
+        AccountStorageMode::Network
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn deployed_faucets_start_unpaused_and_can_be_paused() {
+    let mut node = MockNode::new();
+    let (owner, deployment) = deployed_faucet(&mut node).await;
+    let faucet = deployment.faucet.id();
+    assert!(!is_paused(&mut node, faucet).await.unwrap());
+
+    // The owner sends the faucet a PAUSE note, the faucet runs no transaction of its own.
+    let transaction_id = set_paused(&mut node, faucet, true).await.unwrap();
+    assert_eq!(node.submitted_by(faucet).len(), 1);
+    let submitted = node.submitted_by(owner.id());
+    assert_eq!(submitted.len(), 1);
+    assert_eq!(submitted[0].transaction_id, transaction_id);
+    let notes = submitted[0].request.expected_output_own_notes();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].metadata().sender(), owner.id());
+    assert_eq!(notes[0].metadata().tag(), NoteTag::from_account_id(faucet));
+    assert_eq!(notes[0].recipient().inputs().values(), [Felt::new(1)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn decommission_pauses_reclaims_and_retires_the_faucet() {
+    LocalSet::new()
+        .run_until(async {
+            let mut node = MockNode::new();
+            let (owner, deployment) = deployed_faucet(&mut node).await;
+            let faucet = deployment.faucet.id();
+            let recipient = create_wallet(&mut node).await.unwrap().id();
+            node.block = 5;
+
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+            ledger
+                .record_deployment(
+                    faucet,
+                    owner.id(),
+                    &TokenConfig::default(),
+                    deployment.transaction_id,
+                )
+                .unwrap();
+            let mut mint_ids = Vec::new();
+            for (i, reclaim_height) in [3, 1_000].into_iter().enumerate() {
+                let note = mint_output_note(
+                    faucet,
+                    recipient,
+                    40,
+                    Word::from([Felt::new(i as u64); 4]),
+                    MintNoteKind::P2ide {
+                        reclaim_height: BlockNumber::from(reclaim_height),
+                    },
+                )
+                .unwrap();
+                let tx_id = common::transaction_id(100 + i as u64);
+                mint_ids.push(
+                    ledger
+                        .record_mint(faucet, recipient, 40, tx_id, &note)
+                        .unwrap(),
+                );
+                ledger.mark_committed(tx_id, BlockNumber::from(2)).unwrap();
+            }
+
+            let (node, watcher) = watch(node);
+            let report = decommission(&node, &watcher, &ledger, faucet)
+                .await
+                .unwrap();
+            let pause_transaction_id = node.lock().await.submitted_by(owner.id())[0].transaction_id;
+            assert_eq!(
+                report.pause_transaction_id,
+                Some(pause_transaction_id.to_hex())
+            );
+            assert_eq!((report.reclaimed, report.reclaimed_amount), (1, 40));
+            assert!(report.failed_reclaims.is_empty());
+            assert_eq!(report.outstanding.len(), 1);
+            assert_eq!(report.outstanding[0].mint_id, mint_ids[1]);
+            assert_eq!(report.outstanding[0].reclaim_block, Some(1_000));
+            assert_eq!(report.stats.reclaimed_amount, 40);
+            assert!(report.retired_at.is_some());
+            assert_eq!(report.deployment.unwrap().retired_at, report.retired_at);
+            assert_eq!(
+                ledger.deployments().unwrap()[0].retired_at,
+                report.retired_at
+            );
+        })
+        .await;
+}
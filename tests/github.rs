@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use network_faucet::{
+    github::{check_account_age, GithubAuth, GithubConfig, GithubUser},
+    ledger::Ledger,
+    FaucetError,
+};
+
+const DAY: u64 = 86_400;
+
+fn github_config() -> GithubConfig {
+    toml::from_str(
+        r#"
+        client_id = "client"
+        client_secret = "secret"
+        redirect_url = "https://faucet.example.com/api/auth/github/callback"
+        max_drips = 2
+        "#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn young_accounts_are_refused() {
+    // 2024-01-01T00:00:00Z
+    let created = 1_704_067_200;
+    check_account_age("old", "2024-01-01T00:00:00Z", created + 30 * DAY, 30).unwrap();
+    assert!(matches!(
+        check_account_age("new", "2024-01-01T00:00:00Z", created + 29 * DAY, 30),
+        Err(FaucetError::AccountTooNew { age_days: 29, .. })
+    ));
+    assert!(matches!(
+        check_account_age("bad", "yesterday", created, 30),
+        Err(FaucetError::Github(_))
+    ));
+}
+
+#[test]
+fn drips_are_limited_per_window() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let window = Duration::from_secs(DAY);
+    let now = 1_000_000;
+
+    ledger.reserve_drip(7, now, window, 2).unwrap();
+    let second = ledger.reserve_drip(7, now + 100, window, 2).unwrap();
+    assert!(matches!(
+        ledger.reserve_drip(7, now + 200, window, 2),
+        Err(FaucetError::RateLimited { retry_after_secs }) if retry_after_secs == DAY - 200
+    ));
+    // Other accounts have drips of their own.
+    ledger.reserve_drip(8, now + 200, window, 2).unwrap();
+
+    // A drip whose mint failed is given back.
+    ledger.release_drip(second).unwrap();
+    ledger.reserve_drip(7, now + 300, window, 2).unwrap();
+
+    // The first drip leaves the window.
+    ledger.reserve_drip(7, now + DAY, window, 2).unwrap();
+}
+
+#[test]
+fn sessions_authenticate_their_user() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let auth = GithubAuth::new(github_config(), ledger).unwrap();
+    let user = GithubUser {
+        id: 42,
+        login: "octocat".into(),
+    };
+
+    let session = auth.start_session(&user).unwrap();
+    let header = format!("Bearer {}", session.token);
+    assert_eq!(auth.authenticate(Some(&header)).unwrap(), user);
+    assert!(matches!(
+        auth.authenticate(Some("Bearer forged")),
+        Err(FaucetError::Unauthorized(_))
+    ));
+    assert!(matches!(
+        auth.authenticate(None),
+        Err(FaucetError::Unauthorized(_))
+    ));
+
+    let drip = auth.reserve_drip(&user).unwrap();
+    auth.finish_drip(drip, Some(1)).unwrap();
+    auth.reserve_drip(&user).unwrap();
+    assert!(matches!(
+        auth.reserve_drip(&user),
+        Err(FaucetError::RateLimited { .. })
+    ));
+
+    // The sign-in state must come from the authorize redirect.
+    let url = auth.authorize_url();
+    assert!(url.starts_with("https://github.com/login/oauth/authorize?client_id=client"));
+}
@@ -0,0 +1,131 @@
+use std::net::SocketAddr;
+
+use axum::{routing::post, Router};
+use network_faucet::{config::Config, http::HttpConfig, FaucetError};
+
+const WALLET: &str = "https://wallet.example.com";
+
+async fn serve(config: &HttpConfig, https: bool) -> SocketAddr {
+    let router = Router::new().route("/api/mint", post(|| async { "minted" }));
+    let router = config.apply(router, https).unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    addr
+}
+
+fn invalid(toml: &str) -> String {
+    let config: Config = toml::from_str(toml).unwrap();
+    match config.validate() {
+        Err(FaucetError::Config(message)) => message,
+        other => panic!("expected a configuration error, got {other:?}"),
+    }
+}
+
+#[test]
+fn cors_settings_are_checked() {
+    assert!(invalid("[http.cors]\nallowed_origins = []").contains("allowed_origins"));
+    assert!(
+        invalid("[http.cors]\nallowed_origins = [\"wallet.example.com\"]")
+            .contains("wallet.example.com")
+    );
+    assert!(
+        invalid("[http.cors]\nallowed_origins = [\"https://wallet.example.com/app\"]")
+            .contains("not an origin")
+    );
+    assert!(
+        invalid("[http.cors]\nallowed_origins = [\"*\", \"https://wallet.example.com\"]")
+            .contains("`*`")
+    );
+    assert!(
+        invalid("[http.cors]\nallowed_origins = [\"*\"]\nallowed_methods = [\"GE T\"]")
+            .contains("allowed_methods")
+    );
+}
+
+#[tokio::test]
+async fn allowed_origins_pass_preflight() {
+    let config: HttpConfig = toml::from_str(&format!(
+        "[cors]\nallowed_origins = [\"{WALLET}\"]\nallowed_headers = [\"x-request-id\"]"
+    ))
+    .unwrap();
+    let addr = serve(&config, false).await;
+    let client = reqwest::Client::new();
+    let url = format!("http://{addr}/api/mint");
+
+    let preflight = client
+        .request(reqwest::Method::OPTIONS, &url)
+        .header("origin", WALLET)
+        .header("access-control-request-method", "POST")
+        .header(
+            "access-control-request-headers",
+            "authorization,x-request-id",
+        )
+        .send()
+        .await
+        .unwrap();
+    assert!(preflight.status().is_success());
+    let headers = preflight.headers();
+    assert_eq!(headers["access-control-allow-origin"], WALLET);
+    assert!(headers["access-control-allow-methods"]
+        .to_str()
+        .unwrap()
+        .contains("POST"));
+    assert!(headers["access-control-allow-headers"]
+        .to_str()
+        .unwrap()
+        .contains("x-request-id"));
+    assert_eq!(headers["access-control-max-age"], "3600");
+
+    let minted = client
+        .post(&url)
+        .header("origin", WALLET)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(minted.headers()["access-control-allow-origin"], WALLET);
+
+    let foreign = client
+        .post(&url)
+        .header("origin", "https://evil.example.com")
+        .send()
+        .await
+        .unwrap();
+    assert!(!foreign
+        .headers()
+        .contains_key("access-control-allow-origin"));
+}
+
+#[tokio::test]
+async fn security_headers_are_added_unless_disabled() {
+    let addr = serve(&HttpConfig::default(), true).await;
+    let response = reqwest::Client::new()
+        .post(format!("http://{addr}/api/mint"))
+        .send()
+        .await
+        .unwrap();
+    let headers = response.headers();
+    assert_eq!(headers["x-content-type-options"], "nosniff");
+    assert_eq!(headers["x-frame-options"], "DENY");
+    assert_eq!(headers["referrer-policy"], "no-referrer");
+    assert!(headers.contains_key("content-security-policy"));
+    assert_eq!(headers["strict-transport-security"], "max-age=31536000");
+    assert!(!headers.contains_key("access-control-allow-origin"));
+
+    let plain = serve(&HttpConfig::default(), false).await;
+    let response = reqwest::Client::new()
+        .post(format!("http://{plain}/api/mint"))
+        .send()
+        .await
+        .unwrap();
+    assert!(!response.headers().contains_key("strict-transport-security"));
+
+    let disabled: HttpConfig = toml::from_str("security_headers = false").unwrap();
+    let addr = serve(&disabled, false).await;
+    let response = reqwest::Client::new()
+        .post(format!("http://{addr}/api/mint"))
+        .send()
+        .await
+        .unwrap();
+    assert!(!response.headers().contains_key("x-frame-options"));
+}
@@ -0,0 +1,66 @@
+use std::{rc::Rc, time::Duration};
+
+use network_faucet::{
+    deploy::deploy_faucet,
+    localnet::{LocalNode, LocalnetConfig, NodeLauncher},
+    mint::mint_p2id,
+    node::{connect, FaucetNode},
+    wallet::create_wallet,
+    watcher::{wait_for_transaction, BlockWatcher, SYNC_INTERVAL},
+};
+use tokio::{sync::Mutex, task::LocalSet};
+
+const DEPLOY_SCRIPT: &str = include_str!("../masm/deploy.masm");
+
+// Needs docker or `MIDEN_NODE_BIN`, see `NodeLauncher::from_env`.
+#[tokio::test]
+#[ignore = "starts a miden node, run with `cargo test --test localnet -- --ignored`"]
+async fn deploys_and_mints_on_a_local_node() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let local_node = LocalNode::start(LocalnetConfig {
+                launcher: NodeLauncher::from_env(),
+                data_dir: dir.path().to_path_buf(),
+                startup_timeout: Duration::from_secs(120),
+                ..LocalnetConfig::default()
+            })
+            .await
+            .unwrap();
+
+            let mut node = connect(&local_node.client_config()).await.unwrap();
+            node.sync_state().await.unwrap();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let recipient = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = BlockWatcher::spawn(node.clone(), SYNC_INTERVAL);
+            wait_for_transaction(&node, &watcher, deployment.transaction_id)
+                .await
+                .unwrap();
+            let faucet = node
+                .lock()
+                .await
+                .get_account(deployment.faucet.id())
+                .await
+                .unwrap();
+            assert!(faucet.is_some_and(|faucet| faucet.nonce().as_int() > 0));
+
+            let mint = mint_p2id(
+                &mut *node.lock().await,
+                deployment.faucet.id(),
+                recipient.id(),
+                50,
+            )
+            .await
+            .unwrap();
+            assert_eq!(mint.p2id_note.metadata().sender(), deployment.faucet.id());
+            wait_for_transaction(&node, &watcher, mint.transaction_id)
+                .await
+                .unwrap();
+        })
+        .await;
+}
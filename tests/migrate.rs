@@ -0,0 +1,85 @@
+use network_faucet::{
+    ledger::{Ledger, SCHEMA_VERSION},
+    FaucetError,
+};
+
+/// Schema of the first release, before batch mints.
+const FIRST_RELEASE: &str = "
+CREATE TABLE mints (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    faucet_id TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    transaction_id TEXT NOT NULL UNIQUE,
+    note_id TEXT NOT NULL,
+    nullifier TEXT NOT NULL,
+    nullifier_prefix INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    error TEXT,
+    created_at INTEGER NOT NULL,
+    commit_block INTEGER,
+    claim_block INTEGER
+);
+INSERT INTO mints (faucet_id, recipient, amount, transaction_id, note_id, nullifier,
+    nullifier_prefix, status, created_at)
+VALUES ('0xfaucet', '0xalice', 5, '0xold', '0xnote', '0xnullifier', 7, 'submitted', 1);";
+
+#[test]
+fn old_ledgers_are_backed_up_and_upgraded_in_place() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ledger.sqlite3");
+    rusqlite::Connection::open(&path)
+        .unwrap()
+        .execute_batch(FIRST_RELEASE)
+        .unwrap();
+
+    let ledger = Ledger::open_unmigrated(&path).unwrap();
+    assert_eq!(ledger.schema_version().unwrap(), 0);
+    let pending = ledger.pending_migrations().unwrap();
+    assert_eq!(pending.last().unwrap().version, SCHEMA_VERSION);
+
+    let backup = dir.path().join("ledger.sqlite3.v0.bak");
+    ledger.backup(&backup).unwrap();
+    assert!(
+        ledger.backup(&backup).is_err(),
+        "backups are not overwritten"
+    );
+    let applied = ledger.migrate().unwrap();
+    assert_eq!(applied.len(), pending.len());
+    assert_eq!(ledger.schema_version().unwrap(), SCHEMA_VERSION);
+    assert!(ledger.pending_migrations().unwrap().is_empty());
+    assert_eq!(ledger.get_mint(1).unwrap().unwrap().transaction_id, "0xold");
+    drop(ledger);
+
+    // The backup keeps the old schema.
+    let backup = Ledger::open_unmigrated(&backup).unwrap();
+    assert_eq!(backup.schema_version().unwrap(), 0);
+
+    // Reopening runs nothing.
+    let ledger = Ledger::open(&path).unwrap();
+    assert!(ledger.migrate().unwrap().is_empty());
+}
+
+#[test]
+fn new_ledgers_start_at_the_current_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    assert_eq!(ledger.schema_version().unwrap(), SCHEMA_VERSION);
+}
+
+#[test]
+fn ledgers_of_newer_builds_are_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ledger.sqlite3");
+    drop(Ledger::open(&path).unwrap());
+    rusqlite::Connection::open(&path)
+        .unwrap()
+        .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+        .unwrap();
+
+    assert!(matches!(
+        Ledger::open(&path),
+        Err(FaucetError::LedgerVersion { found, supported })
+            if found == SCHEMA_VERSION + 1 && supported == SCHEMA_VERSION
+    ));
+}
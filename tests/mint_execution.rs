@@ -0,0 +1,380 @@
+//! MINT and PAUSE notes executed by the transaction executor against a network faucet, on a mock
+//! chain.
+
+use faucet_notes::mint_output_note_tagged;
+use miden_client::{
+    account::{
+        component::{BasicWallet, NetworkFungibleFaucet},
+        Account, AccountBuilder, AccountStorageMode, AccountType,
+    },
+    asset::TokenSymbol,
+    crypto::RpoRandomCoin,
+    note::{Note, NoteInputs, NoteRecipient, NoteType},
+    testing::{AccountState, Auth, MockChain, MockChainBuilder},
+    transaction::{ExecutedTransaction, OutputNote},
+    Felt, Word,
+};
+use miden_lib::note::WellKnownNote;
+use network_faucet::{
+    mint::{mint_note, AuxData, MintNoteKind, NoteTagStrategy, RequestSource},
+    pause::{pausable_component, pause_note, PAUSED_SLOT},
+};
+
+const AMOUNT: u64 = 75;
+
+fn rng() -> RpoRandomCoin {
+    RpoRandomCoin::new(Word::from([Felt::new(42); 4]))
+}
+
+/// Pausable network faucet of `owner`, like the faucets this crate deploys.
+fn network_faucet(builder: &mut MockChainBuilder, owner: &Account) -> Account {
+    let faucet = NetworkFungibleFaucet::new(
+        TokenSymbol::new("NET").unwrap(),
+        8,
+        Felt::new(1_000_000),
+        owner.id(),
+    )
+    .unwrap();
+    let account = AccountBuilder::new([3; 32])
+        .account_type(AccountType::FungibleFaucet)
+        .storage_mode(AccountStorageMode::Network)
+        .with_component(faucet)
+        .with_component(pausable_component(owner.id()).unwrap());
+    builder
+        .add_account_from_builder(Auth::IncrNonce, account, AccountState::Exists)
+        .unwrap()
+}
+
+/// Wallet the network executes notes for.
+fn network_wallet(builder: &mut MockChainBuilder) -> Account {
+    let account = AccountBuilder::new([7; 32])
+        .storage_mode(AccountStorageMode::Network)
+        .with_component(BasicWallet);
+    builder
+        .add_account_from_builder(Auth::IncrNonce, account, AccountState::Exists)
+        .unwrap()
+}
+
+/// The note `recipient` is paid with by a mint of `strategy` and `note_type`.
+fn output_note(
+    faucet: &Account,
+    recipient: &Account,
+    strategy: NoteTagStrategy,
+    note_type: NoteType,
+) -> Note {
+    mint_output_note_tagged(
+        faucet.id(),
+        recipient.id(),
+        AMOUNT,
+        Word::from([Felt::new(7); 4]),
+        MintNoteKind::P2id,
+        note_type,
+        AuxData::new(3, RequestSource::Rest),
+        strategy.tag(recipient.id()).unwrap(),
+    )
+    .unwrap()
+}
+
+/// Executes `mint` against `faucet`, with the script of P2ID notes known like on the network.
+async fn execute_mint(chain: &MockChain, faucet: &Account, mint: &Note) -> ExecutedTransaction {
+    chain
+        .build_tx_context(faucet.id(), &[mint.id()], &[])
+        .unwrap()
+        .add_note_script(WellKnownNote::P2ID.script())
+        .build()
+        .unwrap()
+        .execute()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn public_mint_notes_make_the_faucet_emit_the_full_note() {
+    let mut builder = MockChain::builder();
+    let owner = builder.add_existing_wallet(Auth::IncrNonce).unwrap();
+    let faucet = network_faucet(&mut builder, &owner);
+    let wallet = builder.add_existing_wallet(Auth::IncrNonce).unwrap();
+    let network = network_wallet(&mut builder);
+
+    let outputs = [
+        output_note(
+            &faucet,
+            &wallet,
+            NoteTagStrategy::AccountId,
+            NoteType::Public,
+        ),
+        output_note(
+            &faucet,
+            &wallet,
+            NoteTagStrategy::UseCase {
+                use_case_id: 12,
+                payload: 34,
+            },
+            NoteType::Public,
+        ),
+        output_note(
+            &faucet,
+            &network,
+            NoteTagStrategy::Network,
+            NoteType::Public,
+        ),
+    ];
+    let mints: Vec<_> = outputs
+        .iter()
+        .map(|output| {
+            let mint = mint_note(
+                faucet.id(),
+                owner.id(),
+                output,
+                AMOUNT,
+                AuxData::new(3, RequestSource::Rest),
+                &mut rng(),
+            )
+            .unwrap();
+            builder.add_output_note(OutputNote::Full(mint.clone()));
+            mint
+        })
+        .collect();
+    let chain = builder.build().unwrap();
+
+    for (output, mint) in outputs.iter().zip(&mints) {
+        let executed = execute_mint(&chain, &faucet, mint).await;
+        assert_eq!(executed.output_notes().num_notes(), 1);
+        let OutputNote::Full(emitted) = executed.output_notes().get_note(0) else {
+            panic!("expected the faucet to emit the full public note");
+        };
+        assert_eq!(emitted, output);
+    }
+}
+
+#[tokio::test]
+async fn private_mint_notes_make_the_faucet_emit_the_note() {
+    let mut builder = MockChain::builder();
+    let owner = builder.add_existing_wallet(Auth::IncrNonce).unwrap();
+    let faucet = network_faucet(&mut builder, &owner);
+    let wallet = builder.add_existing_wallet(Auth::IncrNonce).unwrap();
+
+    let output = output_note(
+        &faucet,
+        &wallet,
+        NoteTagStrategy::AccountId,
+        NoteType::Private,
+    );
+    let mint = mint_note(
+        faucet.id(),
+        owner.id(),
+        &output,
+        AMOUNT,
+        AuxData::default(),
+        &mut rng(),
+    )
+    .unwrap();
+    builder.add_output_note(OutputNote::Full(mint.clone()));
+    let chain = builder.build().unwrap();
+
+    let executed = execute_mint(&chain, &faucet, &mint).await;
+    let emitted = executed.output_notes().get_note(0);
+    assert_eq!(emitted.id(), output.id());
+    assert_eq!(emitted.metadata(), output.metadata());
+}
+
+#[tokio::test]
+async fn network_accounts_consume_the_public_note_of_a_mint() {
+    let mut builder = MockChain::builder();
+    let owner = builder.add_existing_wallet(Auth::IncrNonce).unwrap();
+    let faucet = network_faucet(&mut builder, &owner);
+    let mut network = network_wallet(&mut builder);
+
+    let output = output_note(
+        &faucet,
+        &network,
+        NoteTagStrategy::Network,
+        NoteType::Public,
+    );
+    let mint = mint_note(
+        faucet.id(),
+        owner.id(),
+        &output,
+        AMOUNT,
+        AuxData::default(),
+        &mut rng(),
+    )
+    .unwrap();
+    builder.add_output_note(OutputNote::Full(mint.clone()));
+    let mut chain = builder.build().unwrap();
+
+    let executed = execute_mint(&chain, &faucet, &mint).await;
+    chain.add_pending_executed_transaction(&executed).unwrap();
+    chain.prove_next_block().unwrap();
+
+    // The network consumes the committed note into the recipient without its key.
+    let consumed = chain
+        .build_tx_context(network.id(), &[output.id()], &[])
+        .unwrap()
+        .build()
+        .unwrap()
+        .execute()
+        .await
+        .unwrap();
+    network.apply_delta(consumed.account_delta()).unwrap();
+    assert_eq!(network.vault().get_balance(faucet.id()).unwrap(), AMOUNT);
+}
+
+#[tokio::test]
+async fn public_mint_notes_with_tampered_details_fail() {
+    let mut builder = MockChain::builder();
+    let owner = builder.add_existing_wallet(Auth::IncrNonce).unwrap();
+    let faucet = network_faucet(&mut builder, &owner);
+    let wallet = builder.add_existing_wallet(Auth::IncrNonce).unwrap();
+
+    let output = output_note(
+        &faucet,
+        &wallet,
+        NoteTagStrategy::AccountId,
+        NoteType::Public,
+    );
+    let other = output_note(
+        &faucet,
+        &owner,
+        NoteTagStrategy::AccountId,
+        NoteType::Public,
+    );
+    let mint = mint_note(
+        faucet.id(),
+        owner.id(),
+        &output,
+        AMOUNT,
+        AuxData::default(),
+        &mut rng(),
+    )
+    .unwrap();
+    // Pay `owner` through the recipient details while claiming the recipient of `wallet`.
+    let mut inputs = mint.recipient().inputs().values().to_vec();
+    let details = inputs.len() - other.recipient().inputs().num_values() as usize;
+    inputs.splice(
+        details..,
+        other.recipient().inputs().values().iter().copied(),
+    );
+    let tampered = Note::new(
+        mint.assets().clone(),
+        *mint.metadata(),
+        NoteRecipient::new(
+            mint.recipient().serial_num(),
+            mint.recipient().script().clone(),
+            NoteInputs::new(inputs).unwrap(),
+        ),
+    );
+    builder.add_output_note(OutputNote::Full(tampered.clone()));
+    let chain = builder.build().unwrap();
+
+    let result = chain
+        .build_tx_context(faucet.id(), &[tampered.id()], &[])
+        .unwrap()
+        .add_note_script(WellKnownNote::P2ID.script())
+        .build()
+        .unwrap()
+        .execute()
+        .await;
+    assert!(format!("{:?}", result.unwrap_err()).contains("do not match its RECIPIENT"));
+}
+
+/// Executes `note` against `faucet` and returns the error it fails with, if any.
+async fn execute_against(chain: &MockChain, faucet: &Account, note: &Note) -> Result<(), String> {
+    chain
+        .build_tx_context(faucet.id(), &[note.id()], &[])
+        .unwrap()
+        .add_note_script(WellKnownNote::P2ID.script())
+        .build()
+        .unwrap()
+        .execute()
+        .await
+        .map(|_| ())
+        .map_err(|err| format!("{err:?}"))
+}
+
+#[tokio::test]
+async fn only_the_owner_can_pause_the_faucet() {
+    let mut builder = MockChain::builder();
+    let owner = builder.add_existing_wallet(Auth::IncrNonce).unwrap();
+    let faucet = network_faucet(&mut builder, &owner);
+    let other = builder.add_existing_wallet(Auth::IncrNonce).unwrap();
+
+    let mut rng = rng();
+    let foreign = pause_note(faucet.id(), other.id(), true, &mut rng).unwrap();
+    let own = pause_note(faucet.id(), owner.id(), true, &mut rng).unwrap();
+    builder.add_output_note(OutputNote::Full(foreign.clone()));
+    builder.add_output_note(OutputNote::Full(own.clone()));
+    let chain = builder.build().unwrap();
+
+    let err = execute_against(&chain, &faucet, &foreign)
+        .await
+        .unwrap_err();
+    assert!(
+        err.contains("not the owner of the faucet who can pause it"),
+        "{err}"
+    );
+
+    let executed = chain
+        .build_tx_context(faucet.id(), &[own.id()], &[])
+        .unwrap()
+        .build()
+        .unwrap()
+        .execute()
+        .await
+        .unwrap();
+    let mut paused = faucet.clone();
+    paused.apply_delta(executed.account_delta()).unwrap();
+    assert_eq!(
+        faucet.storage().get_item(PAUSED_SLOT).unwrap(),
+        Word::default()
+    );
+    assert_eq!(
+        paused.storage().get_item(PAUSED_SLOT).unwrap(),
+        Word::from([Felt::new(0), Felt::new(0), Felt::new(0), Felt::new(1)])
+    );
+}
+
+#[tokio::test]
+async fn paused_faucets_reject_mint_notes() {
+    let mut builder = MockChain::builder();
+    let owner = builder.add_existing_wallet(Auth::IncrNonce).unwrap();
+    let faucet = network_faucet(&mut builder, &owner);
+    let wallet = builder.add_existing_wallet(Auth::IncrNonce).unwrap();
+
+    let pause = pause_note(faucet.id(), owner.id(), true, &mut rng()).unwrap();
+    builder.add_output_note(OutputNote::Full(pause.clone()));
+    let mints: Vec<_> = [NoteType::Private, NoteType::Public]
+        .into_iter()
+        .map(|note_type| {
+            let output = output_note(&faucet, &wallet, NoteTagStrategy::AccountId, note_type);
+            let mint = mint_note(
+                faucet.id(),
+                owner.id(),
+                &output,
+                AMOUNT,
+                AuxData::default(),
+                &mut rng(),
+            )
+            .unwrap();
+            builder.add_output_note(OutputNote::Full(mint.clone()));
+            mint
+        })
+        .collect();
+    let mut chain = builder.build().unwrap();
+
+    let paused = chain
+        .build_tx_context(faucet.id(), &[pause.id()], &[])
+        .unwrap()
+        .build()
+        .unwrap()
+        .execute()
+        .await
+        .unwrap();
+    chain.add_pending_executed_transaction(&paused).unwrap();
+    chain.prove_next_block().unwrap();
+
+    for mint in &mints {
+        let err = execute_against(&chain, &faucet, mint).await.unwrap_err();
+        assert!(err.contains("the faucet is paused"));
+    }
+}
@@ -0,0 +1,213 @@
+mod common;
+
+use std::path::Path;
+
+use common::{
+    fixtures::{faucet_id, wallet_id},
+    transaction_id,
+};
+use miden_objects::address::NetworkId;
+use network_faucet::{
+    config::Config,
+    deploy::TokenConfig,
+    explorer::Explorer,
+    ledger::Ledger,
+    network::{claim_for_network, network_file},
+    rpc::EndpointConfig,
+    service::TokenFaucetConfig,
+    FaucetError,
+};
+
+const DEVNET: &str = r#"
+[service]
+network = "testnet"
+faucet_id = "0xd8e3fa793ea82360734ec91a98e798"
+
+[networks.devnet]
+store_path = "./devnet-store.sqlite3"
+keystore_path = "./devnet-keystore"
+ledger_path = "./devnet-ledger.sqlite3"
+faucet_id = "0xd8e3fa793ea82360734ec91a98e799"
+
+[networks.devnet.rpc]
+endpoint = "devnet"
+"#;
+
+fn invalid(toml: &str) -> String {
+    let config: Config = toml::from_str(toml).unwrap();
+    match config.validate() {
+        Err(FaucetError::Config(message)) => message,
+        other => panic!("expected a configuration error, got {other:?}"),
+    }
+}
+
+#[test]
+fn network_profiles_replace_node_files_and_faucet() {
+    let config: Config = toml::from_str(DEVNET).unwrap();
+    config.validate().unwrap();
+    assert_eq!(config.network_names(), ["testnet", "devnet"]);
+
+    let testnet = config.network("testnet").unwrap();
+    assert_eq!(testnet.store_path, config.store_path);
+    assert!(testnet.networks.is_empty());
+
+    let devnet = config.network("devnet").unwrap();
+    assert_eq!(devnet.service.network, "devnet");
+    assert!(matches!(&devnet.rpc.endpoint, EndpointConfig::Url(url) if url == "devnet"));
+    assert_eq!(devnet.ledger_path, config.networks["devnet"].ledger_path);
+    assert_eq!(testnet.account_network(), Some(NetworkId::Testnet));
+    assert_eq!(devnet.account_network(), Some(NetworkId::Devnet));
+    assert_eq!(
+        devnet.service.faucet_id.as_deref(),
+        Some("0xd8e3fa793ea82360734ec91a98e799")
+    );
+    // Everything else is shared with the default network.
+    assert_eq!(devnet.service.queue_capacity, config.service.queue_capacity);
+    assert_eq!(
+        devnet.explorer(),
+        Some(Explorer::new("https://devnet.midenscan.com"))
+    );
+
+    assert!(matches!(
+        config.network("mainnet"),
+        Err(FaucetError::UnknownNetwork(name, _)) if name == "mainnet"
+    ));
+}
+
+#[test]
+fn network_profiles_are_checked() {
+    assert!(
+        invalid(&DEVNET.replace("[networks.devnet", "[networks.Devnet")).contains("network name")
+    );
+    assert!(
+        invalid(&DEVNET.replace("network = \"testnet\"", "network = \"devnet\""))
+            .contains("clashes")
+    );
+    assert!(
+        invalid(&DEVNET.replace("./devnet-ledger.sqlite3", "./ledger.sqlite3"))
+            .contains("share their ledger_path")
+    );
+    assert!(
+        invalid(&DEVNET.replace("./devnet-store.sqlite3", "./missing/store.sqlite3"))
+            .starts_with("networks.devnet:")
+    );
+}
+
+#[test]
+fn endpoints_name_their_network() {
+    for (endpoint, network) in [
+        ("testnet", "testnet"),
+        ("https://rpc.testnet.miden.io:443", "testnet"),
+        ("devnet", "devnet"),
+        ("http://localhost:57291", "localhost"),
+        ("http://127.0.0.1:57291", "localhost"),
+        ("https://rpc.example.com:443", "rpc.example.com:443"),
+    ] {
+        assert_eq!(EndpointConfig::Url(endpoint.into()).network_name(), network);
+    }
+
+    let mut config = Config::default();
+    assert_eq!(config.network_dir(), Path::new("./data/testnet"));
+    config.rpc.endpoint = EndpointConfig::Url("https://rpc.example.com:443".into());
+    assert_eq!(
+        config.network_dir(),
+        Path::new("./data/rpc.example.com_443")
+    );
+    // Nodes of unknown networks accept account IDs of any.
+    assert_eq!(config.account_network(), None);
+}
+
+#[test]
+fn stores_stay_with_their_network() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = dir.path().join("store.sqlite3");
+
+    claim_for_network(&store, "testnet").unwrap();
+    assert_eq!(
+        std::fs::read_to_string(network_file(&store))
+            .unwrap()
+            .trim(),
+        "testnet"
+    );
+    claim_for_network(&store, "testnet").unwrap();
+
+    let Err(FaucetError::NetworkMismatch {
+        recorded,
+        configured,
+        ..
+    }) = claim_for_network(&store, "devnet")
+    else {
+        panic!("expected a network mismatch");
+    };
+    assert_eq!(
+        (recorded.as_str(), configured.as_str()),
+        ("testnet", "devnet")
+    );
+}
+
+#[test]
+fn token_faucets_are_configured_per_network() {
+    let config: Config = toml::from_str(&format!(
+        "{DEVNET}
+[service.tokens.TST]
+faucet_id = \"0xd8e3fa793ea82360734ec91a98e79a\"
+
+[service.tokens.TST.mint]
+max_amount = 100
+
+[networks.devnet.tokens.DEV]
+"
+    ))
+    .unwrap();
+    config.validate().unwrap();
+    assert_eq!(config.service.tokens["TST"].mint.max_amount, 100);
+    let devnet = config.network("devnet").unwrap();
+    assert_eq!(devnet.service.tokens.keys().collect::<Vec<_>>(), ["DEV"]);
+
+    let message = invalid(&format!("{DEVNET}\n[service.tokens.\"not a symbol\"]\n"));
+    assert!(message.contains("invalid token symbol"));
+    let message = invalid(&format!(
+        "{DEVNET}\n[service.tokens.TST.mint]\nmin_amount = 0\n"
+    ));
+    assert!(message.starts_with("service.tokens.TST.mint.min_amount"));
+}
+
+#[test]
+fn token_faucets_default_to_the_latest_deployment_of_their_symbol() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let owner = wallet_id([2; 15]);
+    let token = TokenConfig {
+        symbol: "TST".into(),
+        ..TokenConfig::default()
+    };
+    let mut config = TokenFaucetConfig::default();
+    assert!(matches!(
+        config.resolve_faucet_id("TST", &ledger),
+        Err(FaucetError::Config(message)) if message.contains("service.tokens.TST.faucet_id")
+    ));
+
+    for (n, faucet) in [(1, faucet_id([1; 15])), (2, faucet_id([3; 15]))] {
+        ledger
+            .record_deployment(faucet, owner, &token, transaction_id(n))
+            .unwrap();
+    }
+    ledger
+        .record_deployment(
+            faucet_id([4; 15]),
+            owner,
+            &TokenConfig::default(),
+            transaction_id(3),
+        )
+        .unwrap();
+    assert_eq!(
+        config.resolve_faucet_id("TST", &ledger).unwrap(),
+        faucet_id([3; 15])
+    );
+
+    config.faucet_id = Some(faucet_id([5; 15]).to_hex());
+    assert_eq!(
+        config.resolve_faucet_id("TST", &ledger).unwrap(),
+        faucet_id([5; 15])
+    );
+}
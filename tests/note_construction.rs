@@ -0,0 +1,154 @@
+mod common;
+
+use common::fixtures::{
+    arb_faucet_id, arb_note_type, arb_serial_num, arb_wallet_id, fungible_asset,
+};
+use miden_client::{
+    note::{NoteTag, NoteType},
+    Felt,
+};
+use network_faucet::mint::{create_p2id_note_exact, AuxData, RequestSource, MINT_NOTE_AUX};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn tag_and_metadata_follow_target(
+        faucet in arb_faucet_id(),
+        target in arb_wallet_id(),
+        note_type in arb_note_type(),
+        serial_num in arb_serial_num(),
+        aux in any::<u32>(),
+        amount in 1..1_000_000u64,
+    ) {
+        let aux = Felt::new(aux.into());
+        let note = create_p2id_note_exact(
+            faucet,
+            target,
+            vec![fungible_asset(faucet, amount)],
+            note_type,
+            aux,
+            serial_num,
+        )
+        .unwrap();
+
+        prop_assert_eq!(note.metadata().tag(), NoteTag::from_account_id(target));
+        prop_assert_eq!(note.metadata().sender(), faucet);
+        prop_assert_eq!(note.metadata().note_type(), note_type);
+        prop_assert_eq!(note.metadata().aux(), aux);
+        prop_assert_eq!(note.recipient().serial_num(), serial_num);
+    }
+
+    #[test]
+    fn inputs_encode_target_suffix_then_prefix(
+        faucet in arb_faucet_id(),
+        target in arb_wallet_id(),
+        serial_num in arb_serial_num(),
+    ) {
+        let note = create_p2id_note_exact(
+            faucet,
+            target,
+            vec![fungible_asset(faucet, 1)],
+            NoteType::Private,
+            Felt::new(27),
+            serial_num,
+        )
+        .unwrap();
+
+        prop_assert_eq!(
+            note.recipient().inputs().values(),
+            &[target.suffix(), target.prefix().as_felt()][..]
+        );
+    }
+
+    #[test]
+    fn commitment_is_stable_and_serial_dependent(
+        faucet in arb_faucet_id(),
+        target in arb_wallet_id(),
+        note_type in arb_note_type(),
+        serial_a in arb_serial_num(),
+        serial_b in arb_serial_num(),
+    ) {
+        let build = |serial_num, note_type| {
+            create_p2id_note_exact(
+                faucet,
+                target,
+                vec![fungible_asset(faucet, 50)],
+                note_type,
+                Felt::new(27),
+                serial_num,
+            )
+            .unwrap()
+        };
+
+        let note = build(serial_a, note_type);
+        prop_assert_eq!(note.commitment(), build(serial_a, note_type).commitment());
+
+        if serial_a != serial_b {
+            let other = build(serial_b, note_type);
+            prop_assert_ne!(note.recipient().digest(), other.recipient().digest());
+            prop_assert_ne!(note.commitment(), other.commitment());
+        }
+    }
+
+    #[test]
+    fn note_type_changes_commitment_but_not_recipient(
+        faucet in arb_faucet_id(),
+        target in arb_wallet_id(),
+        serial_num in arb_serial_num(),
+    ) {
+        let build = |note_type| {
+            create_p2id_note_exact(
+                faucet,
+                target,
+                vec![fungible_asset(faucet, 50)],
+                note_type,
+                Felt::new(27),
+                serial_num,
+            )
+            .unwrap()
+        };
+
+        let public = build(NoteType::Public);
+        let private = build(NoteType::Private);
+        prop_assert_eq!(public.recipient().digest(), private.recipient().digest());
+        prop_assert_eq!(public.id(), private.id());
+        prop_assert_ne!(public.commitment(), private.commitment());
+    }
+
+    #[test]
+    fn aux_data_round_trips_through_the_aux_felt(
+        campaign_id in any::<u32>(),
+        source in prop::sample::select(vec![
+            RequestSource::Unknown,
+            RequestSource::Cli,
+            RequestSource::Rest,
+            RequestSource::Grpc,
+            RequestSource::Schedule,
+            RequestSource::Referral,
+        ]),
+    ) {
+        let aux = AuxData::new(campaign_id, source);
+        let felt = aux.to_felt();
+        prop_assert_eq!(felt.as_int() & 0xff, MINT_NOTE_AUX);
+        prop_assert_eq!(AuxData::from_felt(felt), Some(aux));
+    }
+}
+
+#[test]
+fn default_aux_data_is_the_plain_mint_aux() {
+    assert_eq!(AuxData::default().to_felt(), Felt::new(MINT_NOTE_AUX));
+    assert_eq!(
+        AuxData::from_felt(Felt::new(MINT_NOTE_AUX)),
+        Some(AuxData::default())
+    );
+    // Aux values of other senders, and unknown sources, are not attributed.
+    assert_eq!(AuxData::from_felt(Felt::new(0)), None);
+    assert_eq!(
+        AuxData::from_felt(Felt::new(MINT_NOTE_AUX | (0xff << 8))),
+        None
+    );
+    assert_eq!(
+        AuxData::from_felt(Felt::new(MINT_NOTE_AUX | (1 << 48))),
+        None
+    );
+}
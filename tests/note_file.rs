@@ -0,0 +1,112 @@
+mod common;
+
+use common::{
+    fixtures::{faucet_id, wallet_id},
+    transaction_id, MockNode,
+};
+use faucet_notes::mint_output_note;
+use miden_client::{
+    note::{NoteDetails, NoteFile, NoteInclusionProof},
+    Felt, Word,
+};
+use miden_objects::{
+    block::BlockNumber,
+    block::{BlockNoteIndex, BlockNoteTree},
+};
+use network_faucet::{
+    ledger::Ledger,
+    mint::MintNoteKind,
+    note_file::{mint_note_file, read_note_file, write_note_file},
+    FaucetError,
+};
+
+#[tokio::test]
+async fn exported_mint_note_round_trips() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let faucet = faucet_id([1; 15]);
+    let recipient = wallet_id([2; 15]);
+
+    let note = mint_output_note(
+        faucet,
+        recipient,
+        50,
+        Word::from([Felt::new(9); 4]),
+        MintNoteKind::P2id,
+    )
+    .unwrap();
+    let tx_id = transaction_id(1);
+    let mint_id = ledger
+        .record_mint(faucet, recipient, 50, tx_id, &note)
+        .unwrap();
+    ledger.mark_committed(tx_id, BlockNumber::from(4)).unwrap();
+    let record = ledger.get_mint(mint_id).unwrap().unwrap();
+
+    // The mock node has no inclusion proof, so only the details are exported.
+    let note_file = mint_note_file(&mut MockNode::new(), &record).await.unwrap();
+    let path = dir.path().join("mint.mno");
+    write_note_file(&path, &note_file).unwrap();
+
+    match read_note_file(&path).unwrap() {
+        NoteFile::NoteDetails {
+            details,
+            after_block_num,
+            tag,
+        } => {
+            assert_eq!(details, NoteDetails::from(note.clone()));
+            assert_eq!(after_block_num, BlockNumber::from(4));
+            assert_eq!(tag, Some(note.metadata().tag()));
+        }
+        _ => panic!("expected note details"),
+    }
+}
+
+#[tokio::test]
+async fn exported_mint_note_carries_verified_proof() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let faucet = faucet_id([1; 15]);
+    let recipient = wallet_id([2; 15]);
+
+    let note = mint_output_note(
+        faucet,
+        recipient,
+        50,
+        Word::from([Felt::new(9); 4]),
+        MintNoteKind::P2id,
+    )
+    .unwrap();
+    let tx_id = transaction_id(1);
+    let mint_id = ledger
+        .record_mint(faucet, recipient, 50, tx_id, &note)
+        .unwrap();
+    ledger.mark_committed(tx_id, BlockNumber::from(4)).unwrap();
+    let record = ledger.get_mint(mint_id).unwrap().unwrap();
+
+    let index = BlockNoteIndex::new(0, 3).unwrap();
+    let tree = BlockNoteTree::with_entries([(index, note.id(), *note.metadata())]).unwrap();
+    let proof = NoteInclusionProof::new(
+        BlockNumber::from(4),
+        index.leaf_index_value(),
+        tree.open(index),
+    )
+    .unwrap();
+
+    let mut node = MockNode::new();
+    node.inclusion_proofs.push((note.id(), proof.clone()));
+    node.note_roots.insert(4, tree.root());
+    match mint_note_file(&mut node, &record).await.unwrap() {
+        NoteFile::NoteWithProof(exported, exported_proof) => {
+            assert_eq!(exported, note);
+            assert_eq!(exported_proof, proof);
+        }
+        _ => panic!("expected a note with proof"),
+    }
+
+    // A proof that does not lead to the root of the block header is rejected.
+    node.note_roots.insert(4, Word::default());
+    assert!(matches!(
+        mint_note_file(&mut node, &record).await,
+        Err(FaucetError::InvalidInclusionProof(..))
+    ));
+}
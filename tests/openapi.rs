@@ -0,0 +1,58 @@
+use network_faucet::rest::openapi;
+
+#[test]
+fn openapi_document_covers_rest_routes() {
+    let document = openapi();
+    for path in [
+        "/api/mint",
+        "/api/mint/batch",
+        "/api/mint/preview",
+        "/api/mints/{mint_id}",
+        "/api/mint/{mint_id}/events",
+        "/api/stats",
+        "/api/status",
+        "/api/networks",
+        "/api/auth/github",
+        "/api/auth/github/callback",
+        "/admin/pause",
+        "/admin/unpause",
+        "/admin/limits",
+        "/admin/tiers/{tier}",
+        "/admin/drip-limit",
+        "/admin/lists",
+        "/admin/lists/{list}/{subject}",
+        "/admin/queue",
+        "/admin/reclaim",
+    ] {
+        assert!(
+            document.paths.paths.contains_key(path),
+            "missing {path} in OpenAPI document"
+        );
+    }
+
+    for path in ["/admin/tiers/{tier}", "/admin/drip-limit"] {
+        let item = &document.paths.paths[path];
+        assert!(
+            item.put.is_some() && item.delete.is_some(),
+            "{path} is set and reset"
+        );
+    }
+
+    let schemas = document.components.expect("schemas are generated").schemas;
+    for schema in [
+        "MintRequest",
+        "MintResponse",
+        "BatchMintRequest",
+        "BatchMintResponse",
+        "MintPreviewResponse",
+        "MintStatusResponse",
+        "StatsResponse",
+        "NetworksResponse",
+        "SessionResponse",
+        "LimitsResponse",
+        "QueueResponse",
+        "ReclaimResponse",
+    ] {
+        assert!(schemas.contains_key(schema), "missing {schema} schema");
+    }
+}
@@ -0,0 +1,166 @@
+mod common;
+
+use std::{rc::Rc, time::Duration};
+
+use common::{
+    fixtures::{faucet_id, wallet_id},
+    transaction_id, MockNode,
+};
+use faucet_notes::mint_output_note_with;
+use miden_client::{account::AccountId, note::NoteType, Felt, Word};
+use network_faucet::{
+    access::AccessList,
+    deploy::deploy_faucet,
+    ledger::Ledger,
+    mint::{AuxData, MintNoteKind, MintOptions},
+    referral::{
+        check_referral, generate_referral_code, parse_referral_code, ReferralConfig, CODE_LENGTH,
+    },
+    service::{faucet_service, ServiceConfig},
+    wallet::create_wallet,
+    watcher::BlockWatcher,
+    FaucetError,
+};
+use tokio::{sync::Mutex, task::LocalSet};
+
+const DEPLOY_SCRIPT: &str = include_str!("../masm/deploy.masm");
+const SYNC_INTERVAL: Duration = Duration::from_millis(10);
+
+fn record(ledger: &Ledger, faucet: AccountId, recipient: AccountId, amount: u64, n: u64) -> i64 {
+    let note = mint_output_note_with(
+        faucet,
+        recipient,
+        amount,
+        Word::from([Felt::new(n); 4]),
+        MintNoteKind::P2id,
+        NoteType::Private,
+        AuxData::default(),
+    )
+    .unwrap();
+    ledger
+        .record_mint(faucet, recipient, amount, transaction_id(n), &note)
+        .unwrap()
+}
+
+#[test]
+fn referral_codes_convert_once_per_referee() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let faucet = faucet_id([1; 15]);
+    let (referrer, alice, bob) = (wallet_id([2; 15]), wallet_id([3; 15]), wallet_id([4; 15]));
+    let code = generate_referral_code();
+    assert_eq!(code.len(), CODE_LENGTH);
+    ledger.add_referral_code(&code, referrer).unwrap();
+    assert!(ledger.add_referral_code(&code, alice).is_err());
+
+    assert_eq!(
+        parse_referral_code(&format!(" {} ", code.to_lowercase())).unwrap(),
+        code
+    );
+    assert!(parse_referral_code("no-code").is_err());
+    assert!(matches!(
+        check_referral(&ledger, "UNKNOWN", alice),
+        Err(FaucetError::InvalidReferral(..))
+    ));
+    assert!(check_referral(&ledger, &code, referrer).is_err());
+
+    let referral = check_referral(&ledger, &code, alice).unwrap();
+    assert_eq!(referral.referrer, referrer);
+    let mint_id = record_mint_and_bonus(&ledger, faucet, &code, referrer, alice, 1);
+    assert!(ledger.get_mint(mint_id).unwrap().is_some());
+    assert!(matches!(
+        check_referral(&ledger, &code, alice),
+        Err(FaucetError::InvalidReferral(..))
+    ));
+
+    // Conversions past the bonuses are still counted.
+    check_referral(&ledger, &code, bob).unwrap();
+    let mint_id = record(&ledger, faucet, bob, 100, 3);
+    ledger.record_referral(&code, bob, mint_id).unwrap();
+    assert_eq!(ledger.referral_bonuses(referrer).unwrap(), 1);
+
+    let stats = ledger.referral_stats().unwrap();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].code, code);
+    assert_eq!(stats[0].conversions, 2);
+    assert_eq!(stats[0].bonuses, 1);
+    assert_eq!(stats[0].bonus_amount, 10);
+
+    assert!(ledger.disable_referral_code(&code).unwrap());
+    assert!(!ledger.disable_referral_code(&code).unwrap());
+    assert!(check_referral(&ledger, &code, wallet_id([5; 15])).is_err());
+    assert!(ledger.referral_stats().unwrap()[0].disabled);
+}
+
+/// Records a mint of 100 to `referee` converting `code`, and a bonus of 10 to `referrer`.
+fn record_mint_and_bonus(
+    ledger: &Ledger,
+    faucet: AccountId,
+    code: &str,
+    referrer: AccountId,
+    referee: AccountId,
+    n: u64,
+) -> i64 {
+    let mint_id = record(ledger, faucet, referee, 100, n);
+    let referral_id = ledger.record_referral(code, referee, mint_id).unwrap();
+    let bonus_id = record(ledger, faucet, referrer, 10, n + 1);
+    ledger.set_referral_bonus(referral_id, bonus_id).unwrap();
+    mint_id
+}
+
+#[tokio::test]
+async fn bonuses_of_denied_referrers_are_not_minted() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+            let mut wallets = Vec::new();
+            for _ in 0..4 {
+                wallets.push(create_wallet(&mut node).await.unwrap().id());
+            }
+            let [referrer, denied, alice, bob] = wallets[..] else {
+                unreachable!()
+            };
+            ledger.add_referral_code("GOODCODE", referrer).unwrap();
+            ledger.add_referral_code("BADCODE1", denied).unwrap();
+            ledger
+                .add_list_entry(AccessList::Deny, &denied.to_hex(), None)
+                .unwrap();
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), SYNC_INTERVAL));
+            let (handle, worker) = faucet_service(
+                node,
+                watcher,
+                ledger.clone(),
+                deployment.faucet.id(),
+                &ServiceConfig::default(),
+            );
+            tokio::task::spawn_local(worker.with_referrals(ReferralConfig::default()).run());
+
+            handle
+                .with_referral("GOODCODE")
+                .mint(alice, 50, MintOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(ledger.referral_bonuses(referrer).unwrap(), 1);
+
+            // The referee is served, the conversion counted, but the referrer gets no bonus.
+            handle
+                .with_referral("BADCODE1")
+                .mint(bob, 50, MintOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(ledger.referral_bonuses(denied).unwrap(), 0);
+            let stats = ledger.referral_stats().unwrap();
+            let bad = stats.iter().find(|stats| stats.code == "BADCODE1").unwrap();
+            assert_eq!((bad.conversions, bad.bonuses), (1, 0));
+        })
+        .await;
+}
@@ -0,0 +1,46 @@
+use axum::http::Uri;
+use network_faucet::{
+    access::Access,
+    config::Config,
+    request_log::{redact_caller, redact_uri, REDACTED},
+};
+
+#[test]
+fn credentials_in_queries_are_redacted() {
+    let uri: Uri = "/api/auth/github/callback?code=secret-code&state=abc&Token=t"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        redact_uri(&uri),
+        format!("/api/auth/github/callback?code={REDACTED}&state={REDACTED}&Token={REDACTED}")
+    );
+
+    let uri: Uri = "/api/stats?faucet=0x12&granularity=day".parse().unwrap();
+    assert_eq!(redact_uri(&uri), "/api/stats?faucet=0x12&granularity=day");
+    let uri: Uri = "/api/mints/7".parse().unwrap();
+    assert_eq!(redact_uri(&uri), "/api/mints/7");
+}
+
+#[test]
+fn callers_are_named_without_their_keys() {
+    let config: Config = toml::from_str(
+        r#"
+[[api_keys]]
+name = "ci"
+key = "ci-key"
+"#,
+    )
+    .unwrap();
+    let access = Access::new(
+        config.mint.clone(),
+        config.tiers.clone(),
+        config.api_keys.clone(),
+        None,
+    );
+
+    assert_eq!(redact_caller(&access, None), "anonymous");
+    assert_eq!(redact_caller(&access, Some("Bearer ci-key")), "api_key:ci");
+    let unknown = redact_caller(&access, Some("Bearer leaked-session-token"));
+    assert!(!unknown.contains("leaked"));
+    assert_eq!(unknown, format!("bearer:{REDACTED}"));
+}
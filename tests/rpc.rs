@@ -0,0 +1,162 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use miden_client::rpc::NodeRpcClient;
+use network_faucet::rpc::{build_rpc_client, EndpointConfig, RpcConfig};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+use tokio_rustls::{
+    rustls::{
+        crypto::ring,
+        pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+        ServerConfig,
+    },
+    TlsAcceptor,
+};
+
+/// Connection preface every HTTP/2 client sends first.
+const H2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+fn data(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/data/tls")
+        .join(name)
+}
+
+/// Accepts one TLS connection with the test certificate of `localhost` and reports the first bytes
+/// the client sent after the handshake.
+async fn tls_node() -> (u16, oneshot::Receiver<Vec<u8>>) {
+    let certs = CertificateDer::pem_file_iter(data("cert.pem"))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let key = PrivateKeyDer::from_pem_file(data("key.pem")).unwrap();
+    let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .unwrap();
+    config.alpn_protocols = vec![b"h2".to_vec()];
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (sender, receiver) = oneshot::channel();
+    tokio::spawn(async move {
+        // Connections without handshake, like the reachability probe of the client, are skipped.
+        let mut stream = loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            if let Ok(stream) = acceptor.accept(stream).await {
+                break stream;
+            }
+        };
+        let mut preface = vec![0; H2_PREFACE.len()];
+        stream.read_exact(&mut preface).await.unwrap();
+        let _ = sender.send(preface);
+        // Keep the connection open until the client gives up.
+        let _ = stream.read(&mut [0; 1]).await;
+    });
+    (port, receiver)
+}
+
+/// Answers the first `CONNECT` request and reports its target, ignoring connections that close
+/// without one like the reachability probe of the client.
+async fn connect_proxy() -> (u16, oneshot::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (sender, receiver) = oneshot::channel();
+    tokio::spawn(async move {
+        let (mut inbound, request) = loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut inbound = BufReader::new(stream);
+            let mut request = String::new();
+            if inbound.read_line(&mut request).await.unwrap() > 0 {
+                break (inbound, request);
+            }
+        };
+        loop {
+            let mut line = String::new();
+            if inbound.read_line(&mut line).await.unwrap() == 0 || line == "\r\n" {
+                break;
+            }
+        }
+        let target = request.split_whitespace().nth(1).unwrap().to_string();
+        let mut outbound = TcpStream::connect(("127.0.0.1", target_port(&target)))
+            .await
+            .unwrap();
+        let _ = sender.send(target);
+        inbound
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+            .await
+            .unwrap();
+        let _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+    });
+    (port, receiver)
+}
+
+fn target_port(target: &str) -> u16 {
+    target.rsplit_once(':').unwrap().1.parse().unwrap()
+}
+
+#[tokio::test]
+async fn https_endpoints_are_tunnelled_through_the_proxy() {
+    let (node_port, preface) = tls_node().await;
+    let (proxy_port, target) = connect_proxy().await;
+    let config = RpcConfig {
+        endpoint: EndpointConfig::Parts {
+            scheme: "https".into(),
+            host: "localhost".into(),
+            port: Some(node_port),
+        },
+        proxy: Some(format!("http://127.0.0.1:{proxy_port}")),
+        ca_cert: Some(data("cert.pem")),
+        ..RpcConfig::default()
+    };
+
+    let client = build_rpc_client(&config).await.unwrap();
+    // The fake node never answers, the request only has to reach it.
+    tokio::spawn(async move { client.get_block_header_by_number(None, false).await });
+
+    let timeout = Duration::from_secs(10);
+    let target = tokio::time::timeout(timeout, target)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(target, format!("localhost:{node_port}"));
+    // The node certificate was verified for `localhost` and gRPC runs inside the TLS session.
+    let preface = tokio::time::timeout(timeout, preface)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(preface, H2_PREFACE);
+}
+
+#[tokio::test]
+async fn https_endpoints_are_verified_with_the_configured_ca() {
+    let env_before = std::env::var_os("SSL_CERT_FILE");
+    let (node_port, preface) = tls_node().await;
+    let config = RpcConfig {
+        endpoint: EndpointConfig::Parts {
+            scheme: "https".into(),
+            host: "localhost".into(),
+            port: Some(node_port),
+        },
+        ca_cert: Some(data("cert.pem")),
+        ..RpcConfig::default()
+    };
+
+    let client = build_rpc_client(&config).await.unwrap();
+    tokio::spawn(async move { client.get_block_header_by_number(None, false).await });
+
+    let preface = tokio::time::timeout(Duration::from_secs(10), preface)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(preface, H2_PREFACE);
+    // The bundle is trusted by the client alone, not by the whole process.
+    assert_eq!(std::env::var_os("SSL_CERT_FILE"), env_before);
+}
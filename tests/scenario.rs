@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use network_faucet::{
+    scenario::{AmountDistribution, LatencySummary, PlannedNote, Scenario},
+    FaucetError,
+};
+
+const EXAMPLE: &str = include_str!("../scenario.example.toml");
+
+#[test]
+fn plans_are_repeatable() {
+    let scenario: Scenario = toml::from_str(EXAMPLE).unwrap();
+    scenario.validate().unwrap();
+
+    let plan = scenario.plan();
+    assert_eq!(plan.len(), 120);
+    assert_eq!(plan, scenario.plan());
+    assert_eq!(plan[2].offset, Duration::from_secs(1));
+    assert!(plan
+        .iter()
+        .all(|mint| [10, 100, 1000].contains(&mint.amount) && mint.recipient < 20));
+    assert!(plan.iter().any(|mint| mint.note == PlannedNote::P2id));
+
+    let reseeded = Scenario {
+        seed: 2,
+        ..scenario
+    };
+    assert_ne!(plan, reseeded.plan());
+}
+
+#[test]
+fn recipients_are_taken_in_turn_by_default() {
+    let scenario: Scenario = toml::from_str(
+        r#"
+        name = "fixed"
+        rate = 1.0
+        duration_secs = 5
+        amount = { kind = "fixed", amount = 50 }
+        recipients = { size = 2 }
+        "#,
+    )
+    .unwrap();
+    let plan = scenario.plan();
+    assert_eq!(
+        plan.iter().map(|mint| mint.recipient).collect::<Vec<_>>(),
+        [0, 1, 0, 1, 0]
+    );
+    assert!(plan
+        .iter()
+        .all(|mint| mint.amount == 50 && mint.note == PlannedNote::P2id));
+}
+
+#[test]
+fn invalid_scenarios_are_rejected() {
+    let mut scenario: Scenario = toml::from_str(EXAMPLE).unwrap();
+    scenario.amount = AmountDistribution::Uniform { min: 10, max: 5 };
+    assert!(matches!(scenario.validate(), Err(FaucetError::Config(_))));
+
+    let mut scenario: Scenario = toml::from_str(EXAMPLE).unwrap();
+    scenario.rate = 0.0;
+    assert!(matches!(scenario.validate(), Err(FaucetError::Config(_))));
+}
+
+#[test]
+fn latency_summaries_report_percentiles() {
+    let mut samples: Vec<_> = (1..=100).rev().map(Duration::from_millis).collect();
+    let summary = LatencySummary::new(&mut samples);
+    assert_eq!(summary.samples, 100);
+    assert_eq!(summary.p50_ms, 50.0);
+    assert_eq!(summary.max_ms, 100.0);
+    assert_eq!(LatencySummary::new(&mut []), LatencySummary::default());
+}
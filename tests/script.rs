@@ -0,0 +1,144 @@
+mod common;
+
+use std::path::Path;
+
+use common::MockNode;
+use miden_client::{account::AccountId, Word};
+use network_faucet::{
+    node::TransactionCost,
+    script::{
+        check_script, load_script, ScriptCheck, ScriptParams, ScriptTemplate, ScriptValue,
+        DEPLOY_SCRIPT,
+    },
+    FaucetError,
+};
+
+const TEMPLATE: &str = "begin
+    push.{{ amount }}
+    push.{{owner}}
+    drop drop drop
+end";
+
+fn owner() -> AccountId {
+    AccountId::from_hex("0xd8e3fa793ea82360734ec91a98e798").unwrap()
+}
+
+#[test]
+fn placeholders_are_substituted() {
+    let template = ScriptTemplate::new(TEMPLATE);
+    assert_eq!(
+        template
+            .placeholders()
+            .unwrap()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        ["amount", "owner"]
+    );
+
+    let params = ScriptParams::new()
+        .with_assignment("amount=100")
+        .unwrap()
+        .with("owner", ScriptValue::Account(owner()));
+    let code = template.render(&params, &[]).unwrap();
+    assert!(code.contains("push.100\n"));
+    assert!(code.contains(&format!(
+        "push.{}.{}\n",
+        owner().suffix(),
+        owner().prefix().as_felt()
+    )));
+    assert!(!code.contains("{{"));
+}
+
+#[test]
+fn template_errors_are_reported_before_compilation() {
+    let template = ScriptTemplate::new(TEMPLATE);
+    let amount = ScriptParams::new().with("amount", ScriptValue::Felt(1));
+
+    let missing = template.render(&amount, &[]).unwrap_err();
+    assert!(matches!(&missing, FaucetError::ScriptTemplate(msg) if msg.contains("{{owner}}")));
+
+    let unused = amount
+        .clone()
+        .with("owner", ScriptValue::Account(owner()))
+        .with("limit", ScriptValue::Felt(5));
+    assert!(template.render(&unused, &[]).is_err());
+    assert!(template.render(&unused, &["limit"]).is_ok());
+
+    let overflow = ScriptParams::new()
+        .with("amount", ScriptValue::Felt(u64::MAX))
+        .with("owner", ScriptValue::Account(owner()));
+    assert!(template.render(&overflow, &[]).is_err());
+
+    let unterminated = ScriptTemplate::new("begin\n    push.{{amount\nend");
+    assert!(matches!(
+        unterminated.placeholders(),
+        Err(FaucetError::ScriptTemplate(msg)) if msg.contains("line 2")
+    ));
+}
+
+#[test]
+fn values_parse_from_the_command_line() {
+    assert_eq!("7".parse::<ScriptValue>().unwrap(), ScriptValue::Felt(7));
+    assert_eq!(
+        "0xd8e3fa793ea82360734ec91a98e798"
+            .parse::<ScriptValue>()
+            .unwrap(),
+        ScriptValue::Account(owner())
+    );
+    let word = Word::default();
+    assert_eq!(
+        word.to_hex().parse::<ScriptValue>().unwrap(),
+        ScriptValue::Word(word)
+    );
+    assert!("alice".parse::<ScriptValue>().is_err());
+    assert!(ScriptParams::new().with_assignment("amount").is_err());
+}
+
+#[test]
+fn script_overrides_are_read_from_disk() {
+    assert_eq!(load_script(None, DEPLOY_SCRIPT).unwrap(), DEPLOY_SCRIPT);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("custom.masm");
+    std::fs::write(&path, "begin push.1 drop end").unwrap();
+    assert_eq!(
+        load_script(Some(&path), DEPLOY_SCRIPT).unwrap(),
+        "begin push.1 drop end"
+    );
+
+    let err = load_script(Some(Path::new("missing.masm")), DEPLOY_SCRIPT).unwrap_err();
+    match err {
+        FaucetError::ScriptNotFound { searched, .. } => {
+            assert!(searched.contains("missing.masm"));
+            assert!(
+                searched.contains(&Path::new("masm").join("missing.masm").display().to_string())
+            );
+        }
+        err => panic!("unexpected error: {err}"),
+    }
+}
+
+#[tokio::test]
+async fn check_compiles_and_optionally_executes_scripts() {
+    let mut node = MockNode::new();
+    let template = ScriptTemplate::new("begin\n    push.{{amount}} drop\nend");
+    let params = ScriptParams::new().with("amount", ScriptValue::Felt(3));
+
+    let check = check_script(&mut node, &template, &params, None).await;
+    assert_eq!(check.unwrap(), ScriptCheck::Compiled);
+    assert!(node.executed.is_empty());
+
+    let check = check_script(&mut node, &template, &params, Some(owner())).await;
+    assert_eq!(
+        check.unwrap(),
+        ScriptCheck::Executed(owner(), TransactionCost::default())
+    );
+    assert_eq!(node.executed, [owner()]);
+
+    let broken = ScriptTemplate::new("begin\n    push.1 frobnicate\nend");
+    let err = check_script(&mut node, &broken, &ScriptParams::new(), None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, FaucetError::Script(_)));
+    assert!(node.submitted.is_empty());
+}
@@ -0,0 +1,24 @@
+mod common;
+
+use common::MockNode;
+use miden_client::crypto::FeltRng;
+use network_faucet::{client::parse_seed, node::FaucetNode, wallet::create_wallet, FaucetError};
+
+async fn wallet_and_serial(seed: &str) -> (String, String) {
+    let mut node = MockNode::seeded(parse_seed(seed).unwrap());
+    let wallet = create_wallet(&mut node).await.unwrap();
+    (wallet.id().to_hex(), node.rng().draw_word().to_hex())
+}
+
+#[tokio::test]
+async fn same_seed_reproduces_accounts_and_serial_numbers() {
+    let first = wallet_and_serial("0xc0ffee").await;
+    assert_eq!(wallet_and_serial("c0ffee").await, first);
+    assert_ne!(wallet_and_serial("0xdecaf0").await, first);
+}
+
+#[test]
+fn seed_must_be_hex() {
+    assert!(matches!(parse_seed("xyz"), Err(FaucetError::Config(_))));
+    assert!(matches!(parse_seed("0x"), Err(FaucetError::Config(_))));
+}
@@ -0,0 +1,620 @@
+mod common;
+
+use std::{net::IpAddr, rc::Rc, time::Duration};
+
+use common::MockNode;
+use network_faucet::{
+    deploy::deploy_faucet,
+    email::parse_email,
+    ledger::{Ledger, MintStatus},
+    mint::{AmountConfig, MintOptions},
+    network::Networks,
+    node::TransactionCost,
+    schedule::{run_due_schedules, CatchUp},
+    service::{faucet_service, BatchEntry, MintEvent, MintUpdate, ServiceConfig},
+    wallet::create_wallet,
+    watcher::BlockWatcher,
+    FaucetError,
+};
+use tokio::{
+    sync::{broadcast, Mutex},
+    task::LocalSet,
+};
+
+const DEPLOY_SCRIPT: &str = include_str!("../masm/deploy.masm");
+const SYNC_INTERVAL: Duration = Duration::from_millis(10);
+
+#[tokio::test]
+async fn service_publishes_mint_progress() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+            let recipient = create_wallet(&mut node).await.unwrap();
+            // Leave the tracker enough syncs to observe the pending state.
+            node.commit_delay = 5;
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), SYNC_INTERVAL));
+            let (handle, worker) = faucet_service(
+                node,
+                watcher,
+                ledger,
+                deployment.faucet.id(),
+                &ServiceConfig::default(),
+            );
+            tokio::task::spawn_local(worker.run());
+
+            let mut events = handle.subscribe();
+            let ticket = handle
+                .mint(recipient.id(), 50, MintOptions::default())
+                .await
+                .unwrap();
+
+            let mut updates = Vec::new();
+            while updates
+                .last()
+                .is_none_or(|update: &MintUpdate| !update.is_final())
+            {
+                let event = events.recv().await.unwrap();
+                assert_eq!(event.mint_id, ticket.mint_id);
+                updates.push(event.update);
+            }
+            assert_eq!(updates[0], MintUpdate::Submitted);
+            assert_eq!(updates[1], MintUpdate::Pending);
+            assert!(matches!(
+                updates.last(),
+                Some(MintUpdate::Committed { block_num }) if *block_num >= 5
+            ));
+
+            let record = handle.status(ticket.mint_id).await.unwrap().unwrap();
+            assert_eq!(record.status, MintStatus::Committed);
+            assert!(record.queued_at.is_some_and(|at| at <= record.created_at));
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn schedules_catch_up_on_missed_runs() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+            let faucet_id = deployment.faucet.id();
+            let recipient = create_wallet(&mut node).await.unwrap();
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), SYNC_INTERVAL));
+            let (handle, worker) = faucet_service(
+                node,
+                watcher,
+                ledger.clone(),
+                faucet_id,
+                &ServiceConfig::default(),
+            );
+            tokio::task::spawn_local(worker.run());
+
+            // Both schedules missed their first three runs.
+            let now = 10_000;
+            let every = Duration::from_secs(60);
+            let first_run = now - 150;
+            let all = ledger
+                .add_schedule(
+                    faucet_id,
+                    recipient.id(),
+                    10,
+                    every,
+                    CatchUp::All,
+                    first_run,
+                )
+                .unwrap();
+            let once = ledger
+                .add_schedule(
+                    faucet_id,
+                    recipient.id(),
+                    20,
+                    every,
+                    CatchUp::Once,
+                    first_run,
+                )
+                .unwrap();
+
+            let runs = run_due_schedules(&handle, &ledger, faucet_id, now)
+                .await
+                .unwrap();
+            let minted = |id| {
+                runs.iter()
+                    .find(|run| run.schedule_id == id)
+                    .map(|run| run.mint_ids.len())
+            };
+            assert_eq!(minted(all), Some(3));
+            assert_eq!(minted(once), Some(1));
+
+            let schedules = ledger.schedules(Some(faucet_id)).unwrap();
+            assert!(schedules
+                .iter()
+                .all(|schedule| schedule.next_run == first_run + 180
+                    && schedule.last_error.is_none()));
+            assert_eq!(ledger.stats(Some(faucet_id)).unwrap().submitted, 4);
+
+            // Nothing is due until the next run.
+            let runs = run_due_schedules(&handle, &ledger, faucet_id, now + 1)
+                .await
+                .unwrap();
+            assert!(runs.is_empty());
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn mints_dropped_by_a_reorg_are_minted_again() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+            let recipient = create_wallet(&mut node).await.unwrap();
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), SYNC_INTERVAL));
+            let config = ServiceConfig {
+                reorg_check_blocks: 100,
+                ..ServiceConfig::default()
+            };
+            let (handle, worker) = faucet_service(
+                node.clone(),
+                watcher,
+                ledger.clone(),
+                deployment.faucet.id(),
+                &config,
+            );
+            tokio::task::spawn_local(worker.run());
+
+            let mut events = handle.subscribe();
+            let ticket = handle
+                .mint(recipient.id(), 50, MintOptions::default())
+                .await
+                .unwrap();
+            assert!(matches!(
+                final_update(&mut events, ticket.mint_id).await,
+                MintUpdate::Committed { .. }
+            ));
+
+            // The committed transaction disappears from the chain.
+            node.lock().await.discarded.push(ticket.transaction_id);
+            let replaced_by = match final_update(&mut events, ticket.mint_id).await {
+                MintUpdate::Invalidated {
+                    replaced_by: Some(replaced_by),
+                    ..
+                } => replaced_by,
+                update => panic!("unexpected update {update:?}"),
+            };
+            assert!(matches!(
+                final_update(&mut events, replaced_by).await,
+                MintUpdate::Committed { .. }
+            ));
+
+            let invalidated = ledger.get_mint(ticket.mint_id).unwrap().unwrap();
+            assert_eq!(invalidated.status, MintStatus::Invalidated);
+            assert_eq!(invalidated.replaced_by, Some(replaced_by));
+            // The replacement mints the same note, so it can only be consumed once.
+            let replacement = ledger.get_mint(replaced_by).unwrap().unwrap();
+            assert_eq!(replacement.note_id, invalidated.note_id);
+            assert_eq!(replacement.nullifier, invalidated.nullifier);
+        })
+        .await;
+}
+
+/// Waits for the update of `mint_id` after which no further updates follow.
+async fn final_update(events: &mut broadcast::Receiver<MintEvent>, mint_id: i64) -> MintUpdate {
+    loop {
+        let event = events.recv().await.unwrap();
+        if event.mint_id == mint_id && event.update.is_final() {
+            return event.update;
+        }
+    }
+}
+
+#[tokio::test]
+async fn email_delivery_requires_a_mailer() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+            let recipient = create_wallet(&mut node).await.unwrap();
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), SYNC_INTERVAL));
+            let (handle, worker) = faucet_service(
+                node,
+                watcher,
+                ledger.clone(),
+                deployment.faucet.id(),
+                &ServiceConfig::default(),
+            );
+            tokio::task::spawn_local(worker.run());
+
+            let email = parse_email("alice@example.com").unwrap();
+            let result = handle
+                .mint_with_email(
+                    recipient.id(),
+                    Some(50),
+                    MintOptions::default(),
+                    Some(email),
+                )
+                .await;
+            assert!(matches!(result, Err(FaucetError::EmailDisabled)));
+            // Nothing was minted.
+            assert!(handle.status(1).await.unwrap().is_none());
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn mints_to_unclaimable_recipients_are_refused() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+            let faucet = deployment.faucet.id();
+            let fresh = create_wallet(&mut node).await.unwrap();
+            let deployed = create_wallet(&mut node).await.unwrap();
+            node.chain_accounts.insert(deployed.id(), deployed.clone());
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), SYNC_INTERVAL));
+            let config = ServiceConfig {
+                require_onchain_recipients: true,
+                ..ServiceConfig::default()
+            };
+            let (handle, worker) = faucet_service(node, watcher, ledger, faucet, &config);
+            tokio::task::spawn_local(worker.run());
+
+            let result = handle.mint(faucet, 50, MintOptions::default()).await;
+            assert!(matches!(
+                result,
+                Err(FaucetError::InvalidRecipient(id, _)) if id == faucet
+            ));
+            let result = handle.mint(fresh.id(), 50, MintOptions::default()).await;
+            assert!(matches!(
+                result,
+                Err(FaucetError::RecipientNotFound(id)) if id == fresh.id()
+            ));
+            assert!(handle.status(1).await.unwrap().is_none());
+
+            handle
+                .mint(deployed.id(), 50, MintOptions::default())
+                .await
+                .unwrap();
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn requests_pick_a_token_faucet() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+            let token_ledger = Rc::new(Ledger::open(dir.path().join("tst.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let main = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap()
+                .faucet
+                .id();
+            let token = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap()
+                .faucet
+                .id();
+            let recipient = create_wallet(&mut node).await.unwrap().id();
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), SYNC_INTERVAL));
+            let config = ServiceConfig::default();
+            let (main_handle, main_worker) =
+                faucet_service(node.clone(), watcher.clone(), ledger, main, &config);
+            let (token_handle, token_worker) =
+                faucet_service(node, watcher, token_ledger.clone(), token, &config);
+            let token_amounts = AmountConfig {
+                default_amount: 5,
+                max_amount: 10,
+                ..AmountConfig::default()
+            };
+            tokio::task::spawn_local(main_worker.run());
+            tokio::task::spawn_local(token_worker.with_amounts(token_amounts).run());
+            let networks = Networks::new("testnet", main_handle, None).with_token(
+                "testnet",
+                "TST",
+                token_handle,
+            );
+            let network = networks.get(None).unwrap();
+
+            assert!(matches!(
+                network.faucet(Some("XYZ")),
+                Err(FaucetError::UnknownToken(token, served)) if token == "XYZ" && served == "TST"
+            ));
+            // Token faucets keep their own amounts whatever the caller is granted.
+            let handle = network
+                .mint_handle(Some("TST"), AmountConfig::default())
+                .unwrap();
+            assert!(matches!(
+                handle.mint(recipient, 50, MintOptions::default()).await,
+                Err(FaucetError::AmountOutOfRange { max: 10, .. })
+            ));
+            let ticket = handle
+                .mint_with_email(recipient, None, MintOptions::default(), None)
+                .await
+                .unwrap();
+            assert_eq!(ticket.amount, 5);
+            let record = token_ledger.get_mint(ticket.mint_id).unwrap().unwrap();
+            assert_eq!(record.faucet_id, token.to_hex());
+
+            let handle = network.mint_handle(None, AmountConfig::default()).unwrap();
+            let ticket = handle
+                .mint(recipient, 50, MintOptions::default())
+                .await
+                .unwrap();
+            let record = network.faucet(None).unwrap().status(ticket.mint_id).await;
+            assert_eq!(record.unwrap().unwrap().faucet_id, main.to_hex());
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn batches_share_one_transaction() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+            let mut recipients = Vec::new();
+            for _ in 0..3 {
+                recipients.push(create_wallet(&mut node).await.unwrap().id());
+            }
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), SYNC_INTERVAL));
+            let config = ServiceConfig {
+                max_batch_size: 3,
+                ..ServiceConfig::default()
+            };
+            let (handle, worker) = faucet_service(
+                node,
+                watcher,
+                ledger.clone(),
+                deployment.faucet.id(),
+                &config,
+            );
+            tokio::task::spawn_local(worker.run());
+
+            let entries: Vec<_> = recipients
+                .iter()
+                .zip([Some(10), None, Some(30)])
+                .map(|(&recipient, amount)| BatchEntry {
+                    recipient,
+                    amount,
+                    options: MintOptions::default(),
+                    email: None,
+                })
+                .collect();
+            let mut events = handle.subscribe();
+            let batch = handle.mint_batch(entries.clone()).await.unwrap();
+            assert_eq!(batch.mints.len(), 3);
+            assert_eq!(
+                batch
+                    .mints
+                    .iter()
+                    .map(|mint| mint.amount)
+                    .collect::<Vec<_>>(),
+                [10, 50, 30]
+            );
+            for ticket in &batch.mints {
+                assert_eq!(ticket.transaction_id, batch.transaction_id);
+                assert!(matches!(
+                    final_update(&mut events, ticket.mint_id).await,
+                    MintUpdate::Committed { .. }
+                ));
+                let record = ledger.get_mint(ticket.mint_id).unwrap().unwrap();
+                assert_eq!(record.note_id, ticket.note_id.to_hex());
+            }
+
+            let too_many = [entries.clone(), entries].concat();
+            assert!(matches!(
+                handle.mint_batch(too_many).await,
+                Err(FaucetError::BatchSize { size: 6, max: 3 })
+            ));
+            assert!(matches!(
+                handle.mint_batch(Vec::new()).await,
+                Err(FaucetError::BatchSize { size: 0, .. })
+            ));
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn previews_cost_without_minting_and_records_actual_costs() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            node.cost = TransactionCost {
+                fee: 7,
+                cycles: 12_000,
+            };
+            let owner = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+            let recipient = create_wallet(&mut node).await.unwrap().id();
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), SYNC_INTERVAL));
+            let (handle, worker) = faucet_service(
+                node.clone(),
+                watcher,
+                ledger.clone(),
+                deployment.faucet.id(),
+                &ServiceConfig::default(),
+            );
+            tokio::task::spawn_local(worker.run());
+
+            let submitted = node.lock().await.submitted.len();
+            let preview = handle
+                .preview(recipient, None, MintOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(preview.amount, 50);
+            assert_eq!(preview.cost, node.lock().await.cost);
+            assert_eq!(node.lock().await.submitted.len(), submitted);
+            assert_eq!(ledger.stats(None).unwrap().submitted, 0);
+
+            handle
+                .mint(recipient, 20, MintOptions::default())
+                .await
+                .unwrap();
+            let stats = ledger.stats(Some(deployment.faucet.id())).unwrap();
+            assert_eq!((stats.fees_paid, stats.cycles_spent), (7, 12_000));
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn sync_status_reports_the_lag_behind_the_chain_tip() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+            node.chain_lead = 7;
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), SYNC_INTERVAL));
+            while watcher.last_sync().is_none() {
+                tokio::time::sleep(SYNC_INTERVAL).await;
+            }
+            let config = ServiceConfig::default();
+            let (handle, worker) =
+                faucet_service(node, watcher, ledger, deployment.faucet.id(), &config);
+            tokio::task::spawn_local(worker.run());
+
+            let status = handle.sync_status().await.unwrap();
+            let store_block = status.store_block.unwrap();
+            assert!(status.chain_tip > store_block);
+            assert!(status.lag() >= 7);
+            assert!(status.since_last_sync.unwrap() < Duration::from_secs(5));
+            assert_eq!(status.queue_len, 0);
+            assert_eq!(status.queue_capacity, config.queue_capacity);
+            assert_eq!(status.prover_backlog, 0);
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn anonymous_mints_are_queued_per_client_address() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+
+            let mut node = MockNode::new();
+            let owner = create_wallet(&mut node).await.unwrap();
+            let deployment = deploy_faucet(&mut node, owner.id(), DEPLOY_SCRIPT)
+                .await
+                .unwrap();
+            let mut recipients = Vec::new();
+            for _ in 0..3 {
+                recipients.push(create_wallet(&mut node).await.unwrap().id());
+            }
+
+            let node = Rc::new(Mutex::new(node));
+            let watcher = Rc::new(BlockWatcher::spawn(node.clone(), SYNC_INTERVAL));
+            let (handle, worker) = faucet_service(
+                node,
+                watcher,
+                ledger,
+                deployment.faucet.id(),
+                &ServiceConfig::default(),
+            );
+
+            // Queue the mints before the worker runs, so it reports them all queued.
+            let client = handle.with_client_ip(IpAddr::from([10, 0, 0, 1]));
+            let mut mints = Vec::new();
+            for &recipient in &recipients {
+                let client = client.clone();
+                mints.push(tokio::task::spawn_local(async move {
+                    client.mint(recipient, 10, MintOptions::default()).await
+                }));
+            }
+            let (anonymous, recipient) = (handle.clone(), recipients[0]);
+            mints.push(tokio::task::spawn_local(async move {
+                anonymous.mint(recipient, 10, MintOptions::default()).await
+            }));
+            tokio::task::yield_now().await;
+            let queue = tokio::task::spawn_local({
+                let handle = handle.clone();
+                async move { handle.queue().await }
+            });
+            tokio::task::yield_now().await;
+            tokio::task::spawn_local(worker.run());
+
+            let queue = queue.await.unwrap().unwrap();
+            let mut identities = queue.identities;
+            identities.sort();
+            assert_eq!(
+                identities,
+                [
+                    ("ip:10.0.0.1".to_string(), 3),
+                    (format!("recipient:{}", recipients[0].to_hex()), 1),
+                ]
+            );
+            for mint in mints {
+                mint.await.unwrap().unwrap();
+            }
+        })
+        .await;
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::{
+    fixtures::{faucet_id, wallet_id},
+    transaction_id,
+};
+use faucet_notes::mint_output_note;
+use miden_client::{Felt, Word};
+use miden_objects::block::BlockNumber;
+use network_faucet::{
+    ledger::{Ledger, StatsGranularity},
+    mint::MintNoteKind,
+};
+
+#[test]
+fn mints_are_reported_per_time_bucket() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ledger.sqlite3");
+    let ledger = Ledger::open(&path).unwrap();
+    let faucet = faucet_id([1; 15]);
+    let (alice, bob) = (wallet_id([2; 15]), wallet_id([3; 15]));
+
+    // 2025-06-01 10:15, 10:45 and 13:05 UTC.
+    let mints = [
+        (alice, 10, 1_748_772_900),
+        (bob, 20, 1_748_774_700),
+        (alice, 30, 1_748_783_100),
+    ];
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    for (n, (recipient, amount, created_at)) in mints.into_iter().enumerate() {
+        let note = mint_output_note(
+            faucet,
+            recipient,
+            amount,
+            Word::from([Felt::new(n as u64); 4]),
+            MintNoteKind::P2id,
+        )
+        .unwrap();
+        let tx_id = transaction_id(n as u64 + 1);
+        let id = ledger
+            .record_mint(faucet, recipient, amount, tx_id, &note)
+            .unwrap();
+        conn.execute(
+            "UPDATE mints SET created_at = ?1 WHERE id = ?2",
+            (created_at, id),
+        )
+        .unwrap();
+        if n == 1 {
+            ledger.mark_failed(tx_id, "discarded").unwrap();
+        } else {
+            ledger.mark_committed(tx_id, BlockNumber::from(4)).unwrap();
+        }
+    }
+
+    let hours = ledger
+        .bucketed_stats(Some(faucet), StatsGranularity::Hour)
+        .unwrap();
+    assert_eq!(hours.len(), 2);
+    assert_eq!(hours[0].label, "2025-06-01 10:00");
+    assert_eq!(hours[0].start, 1_748_772_000);
+    assert_eq!(
+        (hours[0].mints, hours[0].unique_recipients, hours[0].amount),
+        (2, 2, 30)
+    );
+    assert_eq!(hours[0].failure_rate, 0.5);
+    assert_eq!(hours[1].label, "2025-06-01 13:00");
+
+    let days = ledger.bucketed_stats(None, StatsGranularity::Day).unwrap();
+    assert_eq!(days.len(), 1);
+    assert_eq!(days[0].label, "2025-06-01");
+    assert_eq!(
+        (days[0].mints, days[0].unique_recipients, days[0].amount),
+        (3, 2, 60)
+    );
+    assert_eq!((days[0].committed, days[0].failed), (2, 1));
+}
+
+#[test]
+fn ledgers_with_unique_transactions_accept_batches() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ledger.sqlite3");
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    // Schema of the first release, before batch mints.
+    conn.execute_batch(
+        "CREATE TABLE mints (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            faucet_id TEXT NOT NULL,
+            recipient TEXT NOT NULL,
+            amount INTEGER NOT NULL,
+            transaction_id TEXT NOT NULL UNIQUE,
+            note_id TEXT NOT NULL,
+            nullifier TEXT NOT NULL,
+            nullifier_prefix INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            error TEXT,
+            created_at INTEGER NOT NULL,
+            commit_block INTEGER,
+            claim_block INTEGER
+        );
+        INSERT INTO mints (faucet_id, recipient, amount, transaction_id, note_id, nullifier,
+            nullifier_prefix, status, created_at)
+        VALUES ('0xfaucet', '0xalice', 5, '0xold', '0xnote', '0xnullifier', 7, 'submitted', 1);",
+    )
+    .unwrap();
+
+    let ledger = Ledger::open(&path).unwrap();
+    assert_eq!(ledger.get_mint(1).unwrap().unwrap().transaction_id, "0xold");
+
+    let faucet = faucet_id([1; 15]);
+    let tx_id = transaction_id(9);
+    for (n, recipient) in [wallet_id([2; 15]), wallet_id([3; 15])]
+        .into_iter()
+        .enumerate()
+    {
+        let note = mint_output_note(
+            faucet,
+            recipient,
+            10,
+            Word::from([Felt::new(n as u64); 4]),
+            MintNoteKind::P2id,
+        )
+        .unwrap();
+        ledger
+            .record_mint(faucet, recipient, 10, tx_id, &note)
+            .unwrap();
+    }
+    ledger.mark_committed(tx_id, BlockNumber::from(4)).unwrap();
+    assert_eq!(ledger.stats(Some(faucet)).unwrap().committed, 2);
+}
@@ -0,0 +1,73 @@
+use network_faucet::{store::prune_store, FaucetError};
+use rusqlite::Connection;
+
+/// The tables of the client store read by `prune_store`, reduced to the columns it uses.
+const STORE_SCHEMA: &str = "
+CREATE TABLE input_notes (note_id TEXT PRIMARY KEY, state_discriminant INTEGER NOT NULL);
+CREATE TABLE output_notes (note_id TEXT PRIMARY KEY, state_discriminant INTEGER NOT NULL);
+CREATE TABLE transactions (
+    id TEXT PRIMARY KEY, block_num INTEGER NOT NULL, status_variant INTEGER NOT NULL
+);
+CREATE TABLE block_headers (block_num INTEGER PRIMARY KEY, has_client_notes BOOL NOT NULL);
+";
+
+#[test]
+fn prune_keeps_pending_and_recent_records() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("store.sqlite3");
+    let conn = Connection::open(&path).unwrap();
+    conn.execute_batch(STORE_SCHEMA).unwrap();
+    conn.execute_batch(
+        "INSERT INTO input_notes VALUES ('a', 2), ('b', 6), ('c', 8);
+         INSERT INTO output_notes VALUES ('d', 1), ('e', 4);
+         INSERT INTO transactions VALUES ('t1', 10, 1), ('t2', 20, 2), ('t3', 10, 0),
+             ('t4', 95, 1);
+         INSERT INTO block_headers VALUES (0, 0), (10, 1), (20, 0), (95, 0), (100, 0);",
+    )
+    .unwrap();
+    drop(conn);
+
+    let dry_run = prune_store(&path, 10, true).unwrap();
+    assert_eq!(
+        (
+            dry_run.input_notes,
+            dry_run.output_notes,
+            dry_run.transactions,
+            dry_run.block_headers
+        ),
+        (2, 1, 2, 1)
+    );
+
+    assert_eq!(prune_store(&path, 10, false).unwrap().transactions, 2);
+    let conn = Connection::open(&path).unwrap();
+    let ids = |sql: &str| -> Vec<String> {
+        let mut stmt = conn.prepare(sql).unwrap();
+        stmt.query_map([], |row| row.get::<_, String>(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    };
+    assert_eq!(ids("SELECT note_id FROM input_notes"), ["a"]);
+    assert_eq!(ids("SELECT note_id FROM output_notes"), ["d"]);
+    assert_eq!(ids("SELECT id FROM transactions ORDER BY id"), ["t3", "t4"]);
+    assert_eq!(
+        ids("SELECT CAST(block_num AS TEXT) FROM block_headers ORDER BY block_num"),
+        ["0", "10", "95", "100"]
+    );
+    assert_eq!(prune_store(&path, 10, false).unwrap().input_notes, 0);
+}
+
+#[test]
+fn prune_refuses_unknown_store_schemas() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("store.sqlite3");
+    Connection::open(&path)
+        .unwrap()
+        .execute_batch("CREATE TABLE input_notes (note_id TEXT PRIMARY KEY);")
+        .unwrap();
+
+    assert!(matches!(
+        prune_store(&path, 10, false),
+        Err(FaucetError::Store(_))
+    ));
+}
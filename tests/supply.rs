@@ -0,0 +1,148 @@
+mod common;
+
+use std::{rc::Rc, time::Duration};
+
+use common::{fixtures::fungible_asset, transaction_id, MockNode};
+use miden_client::{
+    account::{Account, AccountId},
+    note::{Note, NoteType},
+    Felt, Word,
+};
+use miden_objects::block::BlockNumber;
+use network_faucet::{
+    deploy::deploy_faucet,
+    ledger::Ledger,
+    mint::create_p2id_note_exact,
+    supply::{reconcile_supply, run_supply_reconciler, ISSUANCE_SLOT},
+    wallet::create_wallet,
+    watcher::{BlockWatcher, SYNC_INTERVAL},
+};
+use tokio::{sync::Mutex, task::LocalSet};
+
+const DEPLOY_SCRIPT: &str = include_str!("../masm/deploy.masm");
+
+fn note(faucet: AccountId, recipient: AccountId, amount: u64, serial: u64) -> Note {
+    create_p2id_note_exact(
+        faucet,
+        recipient,
+        vec![fungible_asset(faucet, amount)],
+        NoteType::Public,
+        Felt::new(27),
+        Word::from([Felt::new(serial); 4]),
+    )
+    .unwrap()
+}
+
+/// Overwrites the total issuance stored by `faucet`.
+fn set_issuance(node: &mut MockNode, faucet: AccountId, issued: u64) {
+    let (id, vault, mut storage, code, nonce, seed) =
+        node.accounts.remove(&faucet).unwrap().into_parts();
+    let issuance = Word::from([Felt::new(0), Felt::new(0), Felt::new(0), Felt::new(issued)]);
+    storage.set_item(ISSUANCE_SLOT, issuance).unwrap();
+    node.accounts.insert(
+        faucet,
+        Account::new_unchecked(id, vault, storage, code, nonce, seed),
+    );
+}
+
+async fn faucet_with_supply(ledger: &Ledger) -> (MockNode, AccountId) {
+    let mut node = MockNode::new();
+    let owner = create_wallet(&mut node).await.unwrap().id();
+    let faucet = deploy_faucet(&mut node, owner, DEPLOY_SCRIPT)
+        .await
+        .unwrap()
+        .faucet
+        .id();
+    ledger
+        .record_mint(
+            faucet,
+            owner,
+            50,
+            transaction_id(1),
+            &note(faucet, owner, 50, 1),
+        )
+        .unwrap();
+    ledger
+        .mark_committed(transaction_id(1), BlockNumber::from(3))
+        .unwrap();
+    ledger
+        .record_burn(
+            faucet,
+            owner,
+            20,
+            transaction_id(2),
+            &note(faucet, faucet, 20, 2),
+        )
+        .unwrap();
+    ledger
+        .mark_burn_committed(transaction_id(2), BlockNumber::from(4))
+        .unwrap();
+    // Submitted mints are not part of the supply yet.
+    ledger
+        .record_mint(
+            faucet,
+            owner,
+            70,
+            transaction_id(3),
+            &note(faucet, owner, 70, 3),
+        )
+        .unwrap();
+    (node, faucet)
+}
+
+#[tokio::test]
+async fn supply_is_reconciled_with_the_faucet_issuance() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let (mut node, faucet) = faucet_with_supply(&ledger).await;
+
+    set_issuance(&mut node, faucet, 30);
+    let report = reconcile_supply(&mut node, &ledger, faucet, None)
+        .await
+        .unwrap();
+    assert_eq!((report.ledger_issued, report.on_chain_issued), (30, 30));
+    assert!(report.is_consistent());
+
+    set_issuance(&mut node, faucet, 40);
+    let report = reconcile_supply(&mut node, &ledger, faucet, None)
+        .await
+        .unwrap();
+    assert_eq!(report.divergence(), 10);
+    assert!(!report.is_consistent());
+}
+
+#[tokio::test(start_paused = true)]
+async fn persistent_divergences_are_audited_once() {
+    LocalSet::new()
+        .run_until(async {
+            let dir = tempfile::tempdir().unwrap();
+            let ledger = Rc::new(Ledger::open(dir.path().join("ledger.sqlite3")).unwrap());
+            let (mut node, faucet) = faucet_with_supply(&ledger).await;
+            set_issuance(&mut node, faucet, 45);
+            let node = Rc::new(Mutex::new(node));
+            let watcher = BlockWatcher::spawn(node.clone(), SYNC_INTERVAL);
+            let interval = Duration::from_secs(60);
+            let reconciler = {
+                let (node, ledger) = (node.clone(), ledger.clone());
+                tokio::task::spawn_local(async move {
+                    run_supply_reconciler(&node, &watcher, &ledger, faucet, interval).await
+                })
+            };
+
+            // The first check only notes the divergence.
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            assert!(ledger.audit_log().unwrap().is_empty());
+
+            tokio::time::sleep(interval * 3).await;
+            let log = ledger.audit_log().unwrap();
+            assert_eq!(log.len(), 1);
+            assert_eq!(log[0].action, "supply.diverged");
+            assert_eq!(log[0].account, Some(faucet.to_hex()));
+            let params: serde_json::Value = serde_json::from_str(&log[0].params).unwrap();
+            assert_eq!(params["ledger_issued"], 30);
+            assert_eq!(params["on_chain_issued"], 45);
+            assert_eq!(params["divergence"], 15);
+            reconciler.abort();
+        })
+        .await;
+}
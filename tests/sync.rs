@@ -0,0 +1,67 @@
+mod common;
+
+use std::time::Duration;
+
+use common::fixtures::wallet_id;
+use miden_client::{
+    note::NoteTag,
+    sync::{NoteTagRecord, NoteTagSource},
+};
+use miden_objects::block::BlockNumber;
+use network_faucet::{
+    config::Config,
+    sync::{block_chunks, tag_changes, SyncStats},
+};
+
+#[test]
+fn scoped_sync_only_drops_unlisted_user_tags() {
+    let user = |tag: u32| NoteTagRecord {
+        tag: NoteTag::from(tag),
+        source: NoteTagSource::User,
+    };
+    let tracked = [
+        NoteTagRecord {
+            tag: NoteTag::from(1),
+            source: NoteTagSource::Account(wallet_id([2; 15])),
+        },
+        user(2),
+        user(3),
+        user(3),
+    ];
+
+    let (removed, added) = tag_changes(&tracked, &[NoteTag::from(3), NoteTag::from(4)]);
+    assert_eq!(removed, [NoteTag::from(2)]);
+    assert_eq!(added, [NoteTag::from(4)]);
+
+    let config: Config = toml::from_str("[sync]\nscoped = true\nnote_tags = [3, 4]").unwrap();
+    assert!(config.sync.scoped);
+    assert_eq!(config.sync.note_tags, [3, 4]);
+}
+
+#[test]
+fn sync_stats_track_mean_and_max() {
+    let mut stats = SyncStats::default();
+    assert_eq!(stats.mean(), Duration::ZERO);
+
+    stats.record(Duration::from_millis(300));
+    stats.record(Duration::from_millis(100));
+    assert_eq!(stats.syncs, 2);
+    assert_eq!(stats.mean(), Duration::from_millis(200));
+    assert_eq!(stats.max, Duration::from_millis(300));
+    assert_eq!(stats.last, Duration::from_millis(100));
+}
+
+#[test]
+fn catch_ups_are_split_into_overlapping_ranges() {
+    let block = BlockNumber::from;
+    assert_eq!(
+        block_chunks(block(5), block(30), 10),
+        [
+            (block(5), block(15)),
+            (block(15), block(25)),
+            (block(25), block(30))
+        ]
+    );
+    assert_eq!(block_chunks(block(5), block(8), 10), [(block(5), block(8))]);
+    assert!(block_chunks(block(8), block(5), 10).is_empty());
+}
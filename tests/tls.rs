@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use axum::{routing::get, Router};
+use network_faucet::{
+    config::Config,
+    tls::{TlsConfig, TlsListener},
+    FaucetError,
+};
+
+/// Self-signed certificate of `localhost` and `127.0.0.1`, valid until 2125.
+const CERT: &str = include_str!("data/tls/cert.pem");
+
+fn data(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/data/tls")
+        .join(name)
+}
+
+fn tls_config(cert_path: PathBuf, key_path: PathBuf) -> TlsConfig {
+    toml::from_str(&format!(
+        "cert_path = {:?}\nkey_path = {:?}",
+        cert_path.display().to_string(),
+        key_path.display().to_string()
+    ))
+    .unwrap()
+}
+
+#[test]
+fn tls_needs_existing_certificate_files() {
+    let config: Config = toml::from_str("[tls]\ncert_path = \"cert.pem\"").unwrap();
+    let Err(FaucetError::Config(message)) = config.validate() else {
+        panic!("expected a configuration error");
+    };
+    assert!(message.contains("key_path"));
+
+    let missing = tls_config(data("cert.pem"), data("missing.pem"));
+    let Err(FaucetError::Config(message)) = missing.validate() else {
+        panic!("expected a configuration error");
+    };
+    assert!(message.contains("tls.key_path"));
+
+    let swapped = tls_config(data("key.pem"), data("cert.pem"));
+    swapped.validate().unwrap();
+    assert!(matches!(swapped.server_config(), Err(FaucetError::Tls(_))));
+
+    tls_config(data("cert.pem"), data("key.pem"))
+        .server_config()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn serves_https_with_the_configured_certificate() {
+    let config = tls_config(data("cert.pem"), data("key.pem"));
+    let listener = TlsListener::bind("127.0.0.1:0".parse().unwrap(), &config)
+        .await
+        .unwrap();
+    let addr = axum::serve::Listener::local_addr(&listener).unwrap();
+    let router = Router::new().route("/health", get(|| async { "ok" }));
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let client = reqwest::Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(CERT.as_bytes()).unwrap())
+        .build()
+        .unwrap();
+    let url = format!("https://localhost:{}/health", addr.port());
+    let response = client.get(&url).send().await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "ok");
+
+    // Plain HTTP fails the handshake without stopping the listener.
+    assert!(
+        reqwest::get(format!("http://127.0.0.1:{}/health", addr.port()))
+            .await
+            .is_err()
+    );
+    let response = client.get(&url).send().await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "ok");
+}
@@ -0,0 +1,77 @@
+use std::cell::Cell;
+
+use faucet_notes::MINT_NOTE_AUX;
+use miden_client::{note::NoteType, Felt};
+use miden_lib::utils::ScriptBuilder;
+use network_faucet::{
+    client::{parse_seed, seeded_rng},
+    deploy::{plan_deployment, FaucetAccount, TokenConfig},
+    script::ScriptParams,
+    tx::{tx_scripts, ScriptCache, TxPolicy, DEFAULT_EXPIRATION_DELTA},
+    FaucetError,
+};
+
+const DEPLOY_SCRIPT: &str = include_str!("../masm/deploy.masm");
+
+#[test]
+fn default_policy_expires_transactions_and_keeps_mint_aux() {
+    let policy = TxPolicy::default();
+    assert_eq!(policy.expiration_delta, Some(DEFAULT_EXPIRATION_DELTA));
+    assert_eq!(policy.note_type, NoteType::Private);
+    assert_eq!(policy.aux, Felt::new(MINT_NOTE_AUX));
+}
+
+#[test]
+fn script_cache_compiles_each_script_once() {
+    let cache = ScriptCache::new();
+    let compiled = Cell::new(0);
+    let compile = |code: &str| {
+        compiled.set(compiled.get() + 1);
+        ScriptBuilder::new(true)
+            .compile_tx_script(code)
+            .map_err(|err| FaucetError::Script(err.to_string()))
+    };
+
+    let first = cache
+        .get_or_compile("begin push.1 drop end", compile)
+        .unwrap();
+    let second = cache
+        .get_or_compile("begin push.1 drop end", compile)
+        .unwrap();
+    assert_eq!(first.root(), second.root());
+    assert_eq!(compiled.get(), 1);
+
+    cache
+        .get_or_compile("begin push.2 drop end", compile)
+        .unwrap();
+    assert_eq!(compiled.get(), 2);
+    assert_eq!(cache.len(), 2);
+
+    // Failures are not cached.
+    assert!(cache.get_or_compile("begin nope end", compile).is_err());
+    assert!(cache.get_or_compile("begin nope end", compile).is_err());
+    assert_eq!(compiled.get(), 4);
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn repeated_deployment_plans_reuse_the_compiled_script() {
+    let seed = parse_seed("0xc0ffee").unwrap();
+    let plan = || {
+        plan_deployment(
+            &mut seeded_rng(seed),
+            None,
+            DEPLOY_SCRIPT,
+            &ScriptParams::default(),
+            &TokenConfig::default(),
+            FaucetAccount::default(),
+        )
+        .unwrap()
+    };
+
+    let first = plan();
+    let compiled = tx_scripts().len();
+    assert!(compiled > 0);
+    assert_eq!(plan(), first);
+    assert_eq!(tx_scripts().len(), compiled);
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::{
+    fixtures::{faucet_id, fungible_asset},
+    transaction_id, MockNode,
+};
+use miden_client::{
+    account::{AccountId, AccountStorageMode, AccountType},
+    note::NoteType,
+    Felt, Word,
+};
+use miden_objects::{account::AccountIdVersion, block::BlockNumber};
+use network_faucet::{
+    ledger::Ledger,
+    mint::create_p2id_note_exact,
+    wallet::{build_wallet, create_wallet, list_wallets},
+    watch::{ensure_spendable, unwatch_account, watch_account, watch_reports},
+    FaucetError,
+};
+
+#[tokio::test]
+async fn watched_accounts_are_reported_but_never_spendable() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let mut node = MockNode::new();
+    let own = create_wallet(&mut node).await.unwrap().id();
+    let (recipient, _) = build_wallet(&mut node).unwrap();
+    let recipient_id = recipient.id();
+    node.chain_accounts.insert(recipient_id, recipient);
+    let keys = node.keys.len();
+
+    assert!(watch_account(&mut node, &ledger, recipient_id)
+        .await
+        .unwrap());
+    assert!(!watch_account(&mut node, &ledger, recipient_id)
+        .await
+        .unwrap());
+    // The account is tracked without a key.
+    assert!(node.accounts.contains_key(&recipient_id));
+    assert_eq!(node.keys.len(), keys);
+    assert_eq!(list_wallets(&mut node, &ledger).await.unwrap(), [own]);
+    assert!(matches!(
+        ensure_spendable(&ledger, recipient_id),
+        Err(FaucetError::WatchOnly(id)) if id == recipient_id
+    ));
+    ensure_spendable(&ledger, own).unwrap();
+
+    let faucet = faucet_id([1; 15]);
+    for (i, block) in [(1_u64, Some(7)), (2, None)] {
+        let note = create_p2id_note_exact(
+            faucet,
+            recipient_id,
+            vec![fungible_asset(faucet, 50)],
+            NoteType::Public,
+            Felt::new(27),
+            Word::from([Felt::new(i); 4]),
+        )
+        .unwrap();
+        let tx_id = transaction_id(i);
+        ledger
+            .record_mint(faucet, recipient_id, 50, tx_id, &note)
+            .unwrap();
+        ledger.mark_committed(tx_id, BlockNumber::from(3)).unwrap();
+        if let Some(block) = block {
+            ledger
+                .mark_claimed(&note.nullifier().to_hex(), BlockNumber::from(block))
+                .unwrap();
+        }
+    }
+
+    let reports = watch_reports(&mut node, &ledger).await.unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].account_id, recipient_id);
+    assert!(reports[0].balances.is_empty());
+    assert_eq!(reports[0].mints.mints, 2);
+    assert_eq!(reports[0].mints.minted_amount, 100);
+    assert_eq!(reports[0].mints.claimed, 1);
+    assert_eq!(reports[0].mints.claimed_amount, 50);
+    assert_eq!(reports[0].mints.last_claim_block, Some(7));
+
+    assert!(unwatch_account(&ledger, recipient_id).unwrap());
+    assert!(!unwatch_account(&ledger, recipient_id).unwrap());
+    assert!(watch_reports(&mut node, &ledger).await.unwrap().is_empty());
+    assert_eq!(list_wallets(&mut node, &ledger).await.unwrap(), [own]);
+}
+
+#[tokio::test]
+async fn only_public_foreign_accounts_can_be_watched() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = Ledger::open(dir.path().join("ledger.sqlite3")).unwrap();
+    let mut node = MockNode::new();
+    let own = create_wallet(&mut node).await.unwrap().id();
+    let private = AccountId::dummy(
+        [4; 15],
+        AccountIdVersion::Version0,
+        AccountType::RegularAccountUpdatableCode,
+        AccountStorageMode::Private,
+    );
+
+    assert!(matches!(
+        watch_account(&mut node, &ledger, private).await,
+        Err(FaucetError::WatchPrivate(_))
+    ));
+    assert!(matches!(
+        watch_account(&mut node, &ledger, own).await,
+        Err(FaucetError::AlreadyTracked(_))
+    ));
+    let (unknown, _) = build_wallet(&mut node).unwrap();
+    assert!(matches!(
+        watch_account(&mut node, &ledger, unknown.id()).await,
+        Err(FaucetError::AccountNotFound(_))
+    ));
+    assert!(ledger.watched_accounts().unwrap().is_empty());
+}